@@ -46,6 +46,43 @@ impl BitPacker {
         Ok(())
     }
 
+    /// Writes the first `num_bits` bits of `packed`, a buffer of already bitpacked values.
+    ///
+    /// The bits are copied one 64-bit word at a time, shifted to the current position of the
+    /// bitpacker. If that position is aligned on a word, the words are copied as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `packed` holds less than `num_bits` bits.
+    pub fn write_packed<TWrite: io::Write + ?Sized>(
+        &mut self,
+        packed: &[u8],
+        num_bits: u64,
+        output: &mut TWrite,
+    ) -> io::Result<()> {
+        assert!(
+            num_bits <= packed.len() as u64 * 8,
+            "The packed buffer is too short."
+        );
+        let (words, remaining_bytes) = packed.split_at((num_bits / 64) as usize * 8);
+        if self.mini_buffer_written == 0 {
+            output.write_all(words)?;
+        } else {
+            for word in words.chunks_exact(8) {
+                self.write(u64::from_le_bytes(word.try_into().unwrap()), 64, output)?;
+            }
+        }
+        let num_remaining_bits = (num_bits % 64) as u8;
+        if num_remaining_bits > 0 {
+            let num_remaining_bytes = (num_remaining_bits as usize).div_ceil(8);
+            let mut bytes = [0u8; 8];
+            bytes[..num_remaining_bytes].copy_from_slice(&remaining_bytes[..num_remaining_bytes]);
+            let mask = (1u64 << num_remaining_bits) - 1;
+            self.write(u64::from_le_bytes(bytes) & mask, num_remaining_bits, output)?;
+        }
+        Ok(())
+    }
+
     pub fn flush<TWrite: io::Write + ?Sized>(&mut self, output: &mut TWrite) -> io::Result<()> {
         if self.mini_buffer_written > 0 {
             let num_bytes = (self.mini_buffer_written + 7) / 8;
@@ -324,6 +361,39 @@ mod test {
         }
     }
 
+    proptest::proptest! {
+        #[test]
+        fn test_write_packed_proptest(
+            (num_bits, vals) in vals_strategy(),
+            num_bits_before in 0u8..64u8,
+        ) {
+            let mut packed: Vec<u8> = Vec::new();
+            let mut bitpacker = BitPacker::new();
+            for &val in &vals {
+                bitpacker.write(val, num_bits, &mut packed).unwrap();
+            }
+            bitpacker.close(&mut packed).unwrap();
+
+            // The copy starts at an arbitrary bit position, and is followed by other values.
+            let mut expected: Vec<u8> = Vec::new();
+            let mut buffer: Vec<u8> = Vec::new();
+            let mut expected_bitpacker = BitPacker::new();
+            let mut bitpacker = BitPacker::new();
+            expected_bitpacker.write(0, num_bits_before, &mut expected).unwrap();
+            bitpacker.write(0, num_bits_before, &mut buffer).unwrap();
+            for &val in &vals {
+                expected_bitpacker.write(val, num_bits, &mut expected).unwrap();
+            }
+            let num_packed_bits = vals.len() as u64 * num_bits as u64;
+            bitpacker.write_packed(&packed, num_packed_bits, &mut buffer).unwrap();
+            expected_bitpacker.write(1, 1, &mut expected).unwrap();
+            bitpacker.write(1, 1, &mut buffer).unwrap();
+            expected_bitpacker.close(&mut expected).unwrap();
+            bitpacker.close(&mut buffer).unwrap();
+            assert_eq!(buffer, expected);
+        }
+    }
+
     #[test]
     #[should_panic]
     fn test_get_batch_panics_over_32_bits() {
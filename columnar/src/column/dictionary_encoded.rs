@@ -45,6 +45,21 @@ impl BytesColumn {
         self.dictionary.ord_to_term(ord, output)
     }
 
+    /// Calls `cb` with the term associated to each ordinal of `sorted_ords`.
+    ///
+    /// The ordinals must be strictly increasing. This is much faster than calling
+    /// [`BytesColumn::ord_to_bytes`] for each of them, as the dictionary blocks are
+    /// only decoded once.
+    ///
+    /// Returns `false` if one of the terms does not exist.
+    pub fn sorted_ords_to_bytes<F: FnMut(&[u8]) -> io::Result<()>>(
+        &self,
+        sorted_ords: impl Iterator<Item = u64>,
+        cb: F,
+    ) -> io::Result<bool> {
+        self.dictionary.sorted_ords_to_term_cb(sorted_ords, cb)
+    }
+
     /// Returns the number of rows in the column.
     pub fn num_rows(&self) -> RowId {
         self.term_ord_column.num_docs()
@@ -54,6 +69,15 @@ impl BytesColumn {
         self.term_ord_column.values_for_doc(row_id)
     }
 
+    /// Fills `output` with the first term ordinal of each row of `row_ids`,
+    /// or `None` if the row has no value.
+    ///
+    /// `output` must have the same length as `row_ids`.
+    pub fn first_term_ords(&self, row_ids: &[RowId], output: &mut [Option<u64>]) {
+        output.fill(None);
+        self.term_ord_column.first_vals(row_ids, output)
+    }
+
     /// Returns the column of ordinals
     pub fn ords(&self) -> &Column<u64> {
         &self.term_ord_column
@@ -110,6 +134,23 @@ impl StrColumn {
         }
         Ok(true)
     }
+
+    /// Calls `cb` with the string associated to each ordinal of `sorted_ords`.
+    ///
+    /// The ordinals must be strictly increasing, see [`BytesColumn::sorted_ords_to_bytes`].
+    ///
+    /// Returns `false` if one of the terms does not exist.
+    pub fn sorted_ords_to_str<F: FnMut(&str) -> io::Result<()>>(
+        &self,
+        sorted_ords: impl Iterator<Item = u64>,
+        mut cb: F,
+    ) -> io::Result<bool> {
+        self.0.sorted_ords_to_bytes(sorted_ords, |term_bytes| {
+            let term_str = std::str::from_utf8(term_bytes)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Not valid utf-8"))?;
+            cb(term_str)
+        })
+    }
 }
 
 impl Deref for StrColumn {
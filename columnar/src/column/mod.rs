@@ -12,6 +12,10 @@ pub use serialize::{
     open_column_bytes, open_column_str, open_column_u128, open_column_u128_as_compact_u64,
     open_column_u64, serialize_column_mappable_to_u128, serialize_column_mappable_to_u64,
 };
+pub(crate) use serialize::{
+    open_column_u64_bitpacked_values, open_column_u64_custom_codec_name,
+    serialize_column_mappable_to_u64_with_custom_codec, serialize_column_stacked_bitpacked,
+};
 
 use crate::column_index::{ColumnIndex, Set};
 use crate::column_values::monotonic_mapping::StrictlyMonotonicMappingToInternal;
@@ -83,7 +87,12 @@ impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
         self.values.max_value()
     }
 
+    /// Returns the first value of the row, or `None` if the row has no value.
+    ///
+    /// Missing values are distinguished from default values such as `0`:
+    /// they are tracked by the column index.
     #[inline]
+    #[doc(alias = "get_opt")]
     pub fn first(&self, row_id: RowId) -> Option<T> {
         self.values_for_doc(row_id).next()
     }
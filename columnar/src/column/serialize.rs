@@ -8,8 +8,10 @@ use sstable::Dictionary;
 use crate::column::{BytesColumn, Column};
 use crate::column_index::{serialize_column_index, SerializableColumnIndex};
 use crate::column_values::{
-    load_u64_based_column_values, serialize_column_values_u128, serialize_u64_based_column_values,
-    CodecType, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
+    custom_codec_name, load_u64_based_column_values, open_bitpacked_column_values,
+    serialize_column_values_u128, serialize_stacked_bitpacked_column_values,
+    serialize_u64_based_column_values_with_custom_codec, BitpackedReader, CodecType,
+    CustomColumnCodec, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
 };
 use crate::iterable::Iterable;
 use crate::{StrColumn, Version};
@@ -29,10 +31,22 @@ pub fn serialize_column_mappable_to_u64<T: MonotonicallyMappableToU64>(
     column_index: SerializableColumnIndex<'_>,
     column_values: &impl Iterable<T>,
     output: &mut impl Write,
+) -> io::Result<()> {
+    serialize_column_mappable_to_u64_with_custom_codec(column_index, column_values, None, output)
+}
+
+/// Same as [`serialize_column_mappable_to_u64`], but serializes the values with the custom
+/// codec, if any.
+pub(crate) fn serialize_column_mappable_to_u64_with_custom_codec<T: MonotonicallyMappableToU64>(
+    column_index: SerializableColumnIndex<'_>,
+    column_values: &impl Iterable<T>,
+    custom_codec_opt: Option<&dyn CustomColumnCodec>,
+    output: &mut impl Write,
 ) -> io::Result<()> {
     let column_index_num_bytes = serialize_column_index(column_index, output)?;
-    serialize_u64_based_column_values(
+    serialize_u64_based_column_values_with_custom_codec(
         column_values,
+        custom_codec_opt,
         &[CodecType::Bitpacked, CodecType::BlockwiseLinear],
         output,
     )?;
@@ -40,6 +54,49 @@ pub fn serialize_column_mappable_to_u64<T: MonotonicallyMappableToU64>(
     Ok(())
 }
 
+/// Serializes a column whose values are the values of several bitpacked columns,
+/// one after the other.
+///
+/// Contrary to [`serialize_column_mappable_to_u64`], the values are neither decoded,
+/// nor used to pick the best codec.
+pub(crate) fn serialize_column_stacked_bitpacked(
+    column_index: SerializableColumnIndex<'_>,
+    column_values: &[BitpackedReader],
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let column_index_num_bytes = serialize_column_index(column_index, output)?;
+    serialize_stacked_bitpacked_column_values(column_values, output)?;
+    output.write_all(&column_index_num_bytes.to_le_bytes())?;
+    Ok(())
+}
+
+/// Returns the bytes of the values of a u64-based column.
+fn column_u64_values_bytes(bytes: OwnedBytes) -> OwnedBytes {
+    let (body, column_index_num_bytes_payload) = bytes.rsplit(4);
+    let column_index_num_bytes = u32::from_le_bytes(
+        column_index_num_bytes_payload
+            .as_slice()
+            .try_into()
+            .unwrap(),
+    );
+    let (_column_index_data, column_values_data) = body.split(column_index_num_bytes as usize);
+    column_values_data
+}
+
+/// Opens the values of a u64-based column, provided they were serialized with the
+/// bitpacked codec.
+pub(crate) fn open_column_u64_bitpacked_values(
+    bytes: OwnedBytes,
+) -> io::Result<Option<BitpackedReader>> {
+    open_bitpacked_column_values(column_u64_values_bytes(bytes))
+}
+
+/// Returns the name of the custom codec used to serialize the values of a u64-based column,
+/// if any.
+pub(crate) fn open_column_u64_custom_codec_name(bytes: OwnedBytes) -> io::Result<Option<String>> {
+    custom_codec_name(column_u64_values_bytes(bytes))
+}
+
 pub fn open_column_u64<T: MonotonicallyMappableToU64>(
     bytes: OwnedBytes,
     format_version: Version,
@@ -55,7 +55,7 @@ pub fn open_multivalued_index(
                 start_index_column,
             }))
         }
-        Version::V2 => {
+        Version::V2 | Version::V3 => {
             let (body_bytes, optional_index_len) = bytes.rsplit(4);
             let optional_index_len =
                 u32::from_le_bytes(optional_index_len.as_slice().try_into().unwrap());
@@ -215,6 +215,16 @@ impl MultiValueIndex {
         }
     }
 
+    /// Returns the number of documents with at least one value.
+    pub fn num_docs_with_values(&self) -> u32 {
+        match self {
+            MultiValueIndex::MultiValueIndexV1(idx) => (0..idx.num_docs())
+                .filter(|&doc_id| !idx.range(doc_id).is_empty())
+                .count() as u32,
+            MultiValueIndex::MultiValueIndexV2(idx) => idx.optional_index.num_non_nulls(),
+        }
+    }
+
     /// Converts a list of ranks (row ids of values) in a 1:n index to the corresponding list of
     /// docids. Positions are converted inplace to docids.
     ///
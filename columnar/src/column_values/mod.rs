@@ -30,9 +30,14 @@ pub use u128_based::{
     open_u128_as_compact_u64, open_u128_mapped, serialize_column_values_u128,
     CompactSpaceU64Accessor,
 };
+pub(crate) use u64_based::{
+    custom_codec_name, open_bitpacked_column_values, serialize_stacked_bitpacked_column_values,
+    serialize_u64_based_column_values_with_custom_codec, BitpackedReader,
+};
 pub use u64_based::{
-    load_u64_based_column_values, serialize_and_load_u64_based_column_values,
-    serialize_u64_based_column_values, CodecType, ALL_U64_CODEC_TYPES,
+    get_column_codec, load_u64_based_column_values, register_column_codec,
+    serialize_and_load_u64_based_column_values, serialize_u64_based_column_values, CodecType,
+    ColumnCodecEstimator, CustomColumnCodec, ALL_U64_CODEC_TYPES,
 };
 pub use vec_column::VecColumn;
 
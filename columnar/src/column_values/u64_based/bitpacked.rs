@@ -6,6 +6,7 @@ use common::{BinarySerializable, OwnedBytes};
 use fastdivide::DividerU64;
 use tantivy_bitpacker::{compute_num_bits, BitPacker, BitUnpacker};
 
+use super::stats_collector::compute_gcd;
 use crate::column_values::u64_based::{ColumnCodec, ColumnCodecEstimator, ColumnStats};
 use crate::{ColumnValues, RowId};
 
@@ -130,6 +131,61 @@ impl ColumnCodecEstimator for BitpackedCodecEstimator {
     }
 }
 
+/// Serializes the values of several bitpacked columns, one after the other.
+///
+/// The values are not decoded: their bitpacked representation only goes through the
+/// affine transformation mapping the `min_value` and `gcd` of their column to the ones
+/// of the stacked column. When that transformation is the identity and the columns have the
+/// same number of bits, the packed data of the column is copied word by word.
+pub(crate) fn serialize_stacked(
+    columns: &[BitpackedReader],
+    wrt: &mut dyn Write,
+) -> io::Result<()> {
+    let min_value = columns
+        .iter()
+        .map(|column| column.stats.min_value)
+        .min()
+        .unwrap_or(0u64);
+    let max_value = columns
+        .iter()
+        .map(|column| column.stats.max_value)
+        .max()
+        .unwrap_or(0u64);
+    let num_rows = columns.iter().map(|column| column.stats.num_rows).sum();
+    let mut gcd_opt: Option<NonZeroU64> = None;
+    for column in columns {
+        let mut column_gcd = column.stats.gcd;
+        if let Some(min_value_delta) = NonZeroU64::new(column.stats.min_value - min_value) {
+            column_gcd = compute_gcd(min_value_delta, column_gcd);
+        }
+        gcd_opt = Some(gcd_opt.map_or(column_gcd, |gcd| compute_gcd(gcd, column_gcd)));
+    }
+    let stats = ColumnStats {
+        gcd: gcd_opt.unwrap_or(NonZeroU64::MIN),
+        min_value,
+        max_value,
+        num_rows,
+    };
+    stats.serialize(wrt)?;
+    let num_bits = num_bits(&stats);
+    let mut bit_packer = BitPacker::new();
+    for column in columns {
+        let offset = (column.stats.min_value - min_value) / stats.gcd;
+        let multiplier = column.stats.gcd.get() / stats.gcd;
+        if offset == 0 && multiplier == 1 && column.bit_unpacker.bit_width() == num_bits {
+            let num_packed_bits = column.stats.num_rows as u64 * num_bits as u64;
+            bit_packer.write_packed(&column.data, num_packed_bits, wrt)?;
+            continue;
+        }
+        for row_id in 0..column.stats.num_rows {
+            let bitpacked_val = column.bit_unpacker.get(row_id, &column.data);
+            bit_packer.write(offset + bitpacked_val * multiplier, num_bits, wrt)?;
+        }
+    }
+    bit_packer.close(wrt)?;
+    Ok(())
+}
+
 pub struct BitpackedCodec;
 
 impl ColumnCodec for BitpackedCodec {
@@ -153,6 +209,47 @@ impl ColumnCodec for BitpackedCodec {
 mod tests {
     use super::*;
     use crate::column_values::u64_based::tests::create_and_validate;
+    use crate::column_values::u64_based::StatsCollector;
+
+    fn bitpacked_reader(vals: &[u64]) -> BitpackedReader {
+        let mut stats_collector = StatsCollector::default();
+        for &val in vals {
+            stats_collector.collect(val);
+        }
+        let mut buffer = Vec::new();
+        BitpackedCodecEstimator
+            .serialize(
+                &stats_collector.stats(),
+                &mut vals.iter().copied(),
+                &mut buffer,
+            )
+            .unwrap();
+        BitpackedCodec::load(OwnedBytes::new(buffer)).unwrap()
+    }
+
+    #[test]
+    fn test_serialize_stacked() {
+        // The first two columns have the stats of the stacked column and are copied, the
+        // second one starting in the middle of a word. The last one is transformed.
+        let columns_vals: Vec<Vec<u64>> = vec![
+            (0..40).map(|i| 3 + (i * 7) % 16).collect(),
+            (0..37).map(|i| 3 + (i * 5) % 16).collect(),
+            vec![5, 9, 13],
+        ];
+        let columns: Vec<BitpackedReader> = columns_vals
+            .iter()
+            .map(|vals| bitpacked_reader(vals))
+            .collect();
+        let mut buffer = Vec::new();
+        serialize_stacked(&columns, &mut buffer).unwrap();
+        let stacked = BitpackedCodec::load(OwnedBytes::new(buffer)).unwrap();
+        let expected_vals: Vec<u64> = columns_vals.concat();
+        assert_eq!(stacked.num_vals(), expected_vals.len() as u32);
+        assert_eq!(stacked.bit_unpacker.bit_width(), 4);
+        for (row_id, expected_val) in expected_vals.iter().enumerate() {
+            assert_eq!(stacked.get_val(row_id as u32), *expected_val);
+        }
+    }
 
     #[test]
     fn test_with_codec_data_sets_simple() {
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use common::OwnedBytes;
+
+use crate::column_values::u64_based::ColumnCodecEstimator;
+use crate::ColumnValues;
+
+/// A codec for u64-based column values, provided by the user of the library.
+///
+/// Contrary to the built-in codecs, a custom codec is never picked automatically:
+/// it is used for the columns it has been configured for, see
+/// [`ColumnarWriter::set_column_codec`](crate::ColumnarWriter::set_column_codec).
+///
+/// The name of the codec is persisted with the column values. Custom codecs
+/// therefore need to be registered with [`register_column_codec`] before
+/// the columns using them are written or read.
+pub trait CustomColumnCodec: Send + Sync + 'static {
+    /// Name identifying the codec.
+    fn name(&self) -> &str;
+
+    /// Returns an estimator, in charge of serializing a column.
+    ///
+    /// If the estimator returns `None` in its `estimate`, the column
+    /// falls back to the built-in codecs.
+    fn estimator(&self) -> Box<dyn ColumnCodecEstimator>;
+
+    /// Loads column values serialized by the estimator of this codec.
+    fn load(&self, bytes: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>>;
+}
+
+fn custom_codecs() -> &'static RwLock<HashMap<String, Arc<dyn CustomColumnCodec>>> {
+    static CUSTOM_CODECS: OnceLock<RwLock<HashMap<String, Arc<dyn CustomColumnCodec>>>> =
+        OnceLock::new();
+    CUSTOM_CODECS.get_or_init(Default::default)
+}
+
+/// Registers a custom codec, replacing any codec previously registered with the same name.
+pub fn register_column_codec(codec: Arc<dyn CustomColumnCodec>) {
+    let codec_name = codec.name().to_string();
+    custom_codecs().write().unwrap().insert(codec_name, codec);
+}
+
+/// Returns the custom codec registered with the given name.
+pub fn get_column_codec(codec_name: &str) -> Option<Arc<dyn CustomColumnCodec>> {
+    custom_codecs().read().unwrap().get(codec_name).cloned()
+}
@@ -1,5 +1,6 @@
 mod bitpacked;
 mod blockwise_linear;
+mod custom_codec;
 mod line;
 mod linear;
 mod stats_collector;
@@ -14,7 +15,11 @@ use crate::column_values::monotonic_mapping::{
     StrictlyMonotonicMappingInverter, StrictlyMonotonicMappingToInternal,
 };
 pub use crate::column_values::u64_based::bitpacked::BitpackedCodec;
+pub(crate) use crate::column_values::u64_based::bitpacked::BitpackedReader;
 pub use crate::column_values::u64_based::blockwise_linear::BlockwiseLinearCodec;
+pub use crate::column_values::u64_based::custom_codec::{
+    get_column_codec, register_column_codec, CustomColumnCodec,
+};
 pub use crate::column_values::u64_based::linear::LinearCodec;
 pub use crate::column_values::u64_based::stats_collector::StatsCollector;
 use crate::column_values::{monotonic_map_column, ColumnStats};
@@ -88,6 +93,11 @@ pub enum CodecType {
     BlockwiseLinear = 2u8,
 }
 
+/// Code identifying the columns serialized with a [`CustomColumnCodec`].
+///
+/// It is followed by the name of the codec.
+const CUSTOM_CODEC_CODE: u8 = u8::MAX;
+
 /// List of all available u64-base codecs.
 pub const ALL_U64_CODEC_TYPES: [CodecType; 3] = [
     CodecType::Bitpacked,
@@ -148,8 +158,23 @@ pub fn serialize_u64_based_column_values<T: MonotonicallyMappableToU64>(
     vals: &dyn Iterable<T>,
     codec_types: &[CodecType],
     wrt: &mut dyn Write,
+) -> io::Result<()> {
+    serialize_u64_based_column_values_with_custom_codec(vals, None, codec_types, wrt)
+}
+
+/// Serializes a given column of u64-mapped values.
+///
+/// If a custom codec is given, it is used unless its estimator reports that it
+/// is not applicable. In that case, the best of the `codec_types` is used.
+pub(crate) fn serialize_u64_based_column_values_with_custom_codec<T: MonotonicallyMappableToU64>(
+    vals: &dyn Iterable<T>,
+    custom_codec_opt: Option<&dyn CustomColumnCodec>,
+    codec_types: &[CodecType],
+    wrt: &mut dyn Write,
 ) -> io::Result<()> {
     let mut stats_collector = StatsCollector::default();
+    let mut custom_estimator_opt: Option<Box<dyn ColumnCodecEstimator>> =
+        custom_codec_opt.map(|custom_codec| custom_codec.estimator());
     let mut estimators: Vec<(CodecType, Box<dyn ColumnCodecEstimator>)> =
         Vec::with_capacity(codec_types.len());
     for &codec_type in codec_types {
@@ -158,14 +183,32 @@ pub fn serialize_u64_based_column_values<T: MonotonicallyMappableToU64>(
     for val in vals.boxed_iter() {
         let val_u64 = val.to_u64();
         stats_collector.collect(val_u64);
+        if let Some(custom_estimator) = &mut custom_estimator_opt {
+            custom_estimator.collect(val_u64);
+        }
         for (_, estimator) in &mut estimators {
             estimator.collect(val_u64);
         }
     }
+    if let Some(custom_estimator) = &mut custom_estimator_opt {
+        custom_estimator.finalize();
+    }
     for (_, estimator) in &mut estimators {
         estimator.finalize();
     }
     let stats = stats_collector.stats();
+    if let (Some(custom_codec), Some(custom_estimator)) = (custom_codec_opt, custom_estimator_opt) {
+        if custom_estimator.estimate(&stats).is_some() {
+            CUSTOM_CODEC_CODE.serialize(wrt)?;
+            custom_codec.name().to_string().serialize(wrt)?;
+            custom_estimator.serialize(
+                &stats,
+                &mut vals.boxed_iter().map(MonotonicallyMappableToU64::to_u64),
+                wrt,
+            )?;
+            return Ok(());
+        }
+    }
     let (_, best_codec, best_codec_estimator) = estimators
         .into_iter()
         .flat_map(|(codec_type, estimator)| {
@@ -185,12 +228,65 @@ pub fn serialize_u64_based_column_values<T: MonotonicallyMappableToU64>(
     Ok(())
 }
 
+/// Opens u64-based column values, provided they were serialized with the bitpacked codec.
+pub(crate) fn open_bitpacked_column_values(
+    mut bytes: OwnedBytes,
+) -> io::Result<Option<BitpackedReader>> {
+    if bytes.first().copied() != Some(CodecType::Bitpacked.to_code()) {
+        return Ok(None);
+    }
+    bytes.advance(1);
+    BitpackedCodec::load(bytes).map(Some)
+}
+
+/// Serializes the values of several bitpacked columns, one after the other,
+/// without decoding them.
+pub(crate) fn serialize_stacked_bitpacked_column_values(
+    columns: &[BitpackedReader],
+    wrt: &mut dyn Write,
+) -> io::Result<()> {
+    CodecType::Bitpacked.to_code().serialize(wrt)?;
+    bitpacked::serialize_stacked(columns, wrt)
+}
+
+/// Returns the name of the custom codec used to serialize u64-based column values,
+/// or `None` if they were serialized with a built-in codec.
+pub(crate) fn custom_codec_name(mut bytes: OwnedBytes) -> io::Result<Option<String>> {
+    if bytes.first().copied() != Some(CUSTOM_CODEC_CODE) {
+        return Ok(None);
+    }
+    bytes.advance(1);
+    String::deserialize(&mut bytes).map(Some)
+}
+
+fn load_custom_codec<T: MonotonicallyMappableToU64>(
+    mut bytes: OwnedBytes,
+) -> io::Result<Arc<dyn ColumnValues<T>>> {
+    let codec_name = String::deserialize(&mut bytes)?;
+    let custom_codec = get_column_codec(&codec_name).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Column codec {codec_name:?} is not registered"),
+        )
+    })?;
+    let reader = custom_codec.load(bytes)?;
+    let reader_typed = monotonic_map_column(
+        reader,
+        StrictlyMonotonicMappingInverter::from(StrictlyMonotonicMappingToInternal::<T>::new()),
+    );
+    Ok(Arc::new(reader_typed))
+}
+
 /// Load u64-based column values.
 ///
 /// This method first identifies the codec off the first byte.
 pub fn load_u64_based_column_values<T: MonotonicallyMappableToU64>(
     mut bytes: OwnedBytes,
 ) -> io::Result<Arc<dyn ColumnValues<T>>> {
+    if bytes.first().copied() == Some(CUSTOM_CODEC_CODE) {
+        bytes.advance(1);
+        return load_custom_codec(bytes);
+    }
     let codec_type: CodecType = bytes
         .first()
         .copied()
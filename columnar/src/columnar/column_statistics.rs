@@ -0,0 +1,234 @@
+use std::io;
+use std::sync::Arc;
+
+use common::file_slice::FileSlice;
+use common::OwnedBytes;
+
+use crate::column_index::ColumnIndex;
+use crate::columnar::ColumnType;
+use crate::{Cardinality, DynamicColumnHandle, MonotonicallyMappableToU64, RowId, CURRENT_VERSION};
+
+/// Number of bytes of a serialized [`ColumnStatistics`].
+pub(crate) const COLUMN_STATISTICS_NUM_BYTES: usize = 1 + 1 + 4 + 4 + 8 + 8;
+
+/// Statistics about a column, computed when the column is serialized and stored in the
+/// footer of the columnar.
+///
+/// They make it possible to reason about the content of a column without opening it, for instance
+/// to skip a column that cannot contain any value within a given range.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ColumnStatistics {
+    cardinality: Cardinality,
+    null_count: RowId,
+    num_values: RowId,
+    // Exact min and max values, in the u64 representation of the column values.
+    //
+    // `None` if the column has no value, or if it is not a numerical, bool or date column.
+    min_max: Option<(u64, u64)>,
+}
+
+impl ColumnStatistics {
+    /// Returns the cardinality of the column.
+    pub fn cardinality(&self) -> Cardinality {
+        self.cardinality
+    }
+
+    /// Returns the number of docs without any value in the column.
+    pub fn null_count(&self) -> RowId {
+        self.null_count
+    }
+
+    /// Returns the overall number of values in the column.
+    pub fn num_values(&self) -> RowId {
+        self.num_values
+    }
+
+    /// Returns the smallest value of the column.
+    ///
+    /// Returns `None` if the column is empty, or if the column is of type
+    /// `Str`, `Bytes` or `IpAddr`.
+    pub fn min_value<T: MonotonicallyMappableToU64>(&self) -> Option<T> {
+        self.min_max.map(|(min, _)| T::from_u64(min))
+    }
+
+    /// Returns the largest value of the column.
+    ///
+    /// Returns `None` if the column is empty, or if the column is of type
+    /// `Str`, `Bytes` or `IpAddr`.
+    pub fn max_value<T: MonotonicallyMappableToU64>(&self) -> Option<T> {
+        self.min_max.map(|(_, max)| T::from_u64(max))
+    }
+
+    /// Returns the min and max values of the column, in their `u64` representation.
+    pub fn min_max_u64(&self) -> Option<(u64, u64)> {
+        self.min_max
+    }
+
+    /// Computes the statistics of a serialized column.
+    pub(crate) fn compute(
+        column_bytes: OwnedBytes,
+        column_type: ColumnType,
+    ) -> io::Result<ColumnStatistics> {
+        let column_handle = DynamicColumnHandle {
+            file_slice: FileSlice::new(Arc::new(column_bytes)),
+            column_type,
+            format_version: CURRENT_VERSION,
+            statistics: None,
+        };
+        let Some(column) = column_handle.open_u64_lenient()? else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "failed to open serialized column",
+            ));
+        };
+        let num_docs = column.num_docs();
+        let num_docs_with_values = match &column.index {
+            ColumnIndex::Empty { .. } => 0,
+            ColumnIndex::Full => num_docs,
+            ColumnIndex::Optional(optional_index) => optional_index.num_non_nulls(),
+            ColumnIndex::Multivalued(multivalued_index) => multivalued_index.num_docs_with_values(),
+        };
+        let min_max = match column_type {
+            ColumnType::Str | ColumnType::Bytes | ColumnType::IpAddr => None,
+            ColumnType::I64
+            | ColumnType::U64
+            | ColumnType::F64
+            | ColumnType::Bool
+            | ColumnType::DateTime => column.values.iter().fold(None, |min_max, val| {
+                Some(match min_max {
+                    Some((min, max)) => (u64::min(min, val), u64::max(max, val)),
+                    None => (val, val),
+                })
+            }),
+        };
+        Ok(ColumnStatistics {
+            cardinality: column.get_cardinality(),
+            null_count: num_docs - num_docs_with_values,
+            num_values: column.values.num_vals(),
+            min_max,
+        })
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; COLUMN_STATISTICS_NUM_BYTES] {
+        let mut bytes = [0u8; COLUMN_STATISTICS_NUM_BYTES];
+        bytes[0] = self.cardinality.to_code();
+        bytes[1] = self.min_max.is_some() as u8;
+        bytes[2..6].copy_from_slice(&self.null_count.to_le_bytes());
+        bytes[6..10].copy_from_slice(&self.num_values.to_le_bytes());
+        let (min, max) = self.min_max.unwrap_or((0u64, 0u64));
+        bytes[10..18].copy_from_slice(&min.to_le_bytes());
+        bytes[18..26].copy_from_slice(&max.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; COLUMN_STATISTICS_NUM_BYTES]) -> Option<ColumnStatistics> {
+        let cardinality = Cardinality::try_from_code(bytes[0]).ok()?;
+        let null_count = RowId::from_le_bytes(bytes[2..6].try_into().unwrap());
+        let num_values = RowId::from_le_bytes(bytes[6..10].try_into().unwrap());
+        let min_max = match bytes[1] {
+            0 => None,
+            1 => Some((
+                u64::from_le_bytes(bytes[10..18].try_into().unwrap()),
+                u64::from_le_bytes(bytes[18..26].try_into().unwrap()),
+            )),
+            _ => return None,
+        };
+        Some(ColumnStatistics {
+            cardinality,
+            null_count,
+            num_values,
+            min_max,
+        })
+    }
+
+    /// Reads the statistics of the column with the given ordinal in the column dictionary.
+    ///
+    /// Returns `None` if the statistics are not available, e.g. for columnars written before
+    /// statistics were introduced.
+    pub(crate) fn read(statistics_bytes: &[u8], column_ord: u64) -> Option<ColumnStatistics> {
+        let start = (column_ord as usize).checked_mul(COLUMN_STATISTICS_NUM_BYTES)?;
+        let entry_bytes = statistics_bytes.get(start..start + COLUMN_STATISTICS_NUM_BYTES)?;
+        ColumnStatistics::from_bytes(entry_bytes.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColumnarReader, ColumnarWriter, DateTime};
+
+    fn statistics(columnar: &ColumnarReader, column_name: &str) -> ColumnStatistics {
+        let column_handles = columnar.read_columns(column_name).unwrap();
+        assert_eq!(column_handles.len(), 1);
+        column_handles[0].statistics().unwrap()
+    }
+
+    #[test]
+    fn test_column_statistics_serialization() {
+        let stats = ColumnStatistics {
+            cardinality: Cardinality::Multivalued,
+            null_count: 3,
+            num_values: 17,
+            min_max: Some((2, u64::MAX)),
+        };
+        assert_eq!(ColumnStatistics::from_bytes(&stats.to_bytes()), Some(stats));
+        let stats = ColumnStatistics {
+            min_max: None,
+            ..stats
+        };
+        assert_eq!(ColumnStatistics::from_bytes(&stats.to_bytes()), Some(stats));
+    }
+
+    #[test]
+    fn test_column_statistics() {
+        let mut columnar_writer = ColumnarWriter::default();
+        for doc in 0..10u32 {
+            columnar_writer.record_numerical(doc, "full", -(doc as i64));
+            if doc % 3 == 0 {
+                columnar_writer.record_numerical(doc, "optional", doc as f64 + 0.5);
+            }
+            if doc % 2 == 0 {
+                columnar_writer.record_numerical(doc, "multi", doc as u64 + 100);
+                columnar_writer.record_numerical(doc, "multi", doc as u64 + 1000);
+            }
+            columnar_writer.record_datetime(doc, "date", DateTime::from_timestamp_secs(7));
+        }
+        columnar_writer.record_str(4, "str", "hello");
+        let mut buffer = Vec::new();
+        columnar_writer.serialize(10, &mut buffer).unwrap();
+        let columnar = ColumnarReader::open(buffer).unwrap();
+
+        let full_stats = statistics(&columnar, "full");
+        assert_eq!(full_stats.cardinality(), Cardinality::Full);
+        assert_eq!(full_stats.null_count(), 0);
+        assert_eq!(full_stats.num_values(), 10);
+        assert_eq!(full_stats.min_value::<i64>(), Some(-9));
+        assert_eq!(full_stats.max_value::<i64>(), Some(0));
+
+        let optional_stats = statistics(&columnar, "optional");
+        assert_eq!(optional_stats.cardinality(), Cardinality::Optional);
+        assert_eq!(optional_stats.null_count(), 6);
+        assert_eq!(optional_stats.num_values(), 4);
+        assert_eq!(optional_stats.min_value::<f64>(), Some(0.5));
+        assert_eq!(optional_stats.max_value::<f64>(), Some(9.5));
+
+        let multi_stats = statistics(&columnar, "multi");
+        assert_eq!(multi_stats.cardinality(), Cardinality::Multivalued);
+        assert_eq!(multi_stats.null_count(), 5);
+        assert_eq!(multi_stats.num_values(), 10);
+        assert_eq!(multi_stats.min_value::<i64>(), Some(100));
+        assert_eq!(multi_stats.max_value::<i64>(), Some(1008));
+
+        let date_stats = statistics(&columnar, "date");
+        assert_eq!(
+            date_stats.min_value::<DateTime>(),
+            Some(DateTime::from_timestamp_secs(7))
+        );
+
+        let str_stats = statistics(&columnar, "str");
+        assert_eq!(str_stats.cardinality(), Cardinality::Optional);
+        assert_eq!(str_stats.null_count(), 9);
+        assert_eq!(str_stats.num_values(), 1);
+        assert_eq!(str_stats.min_max_u64(), None);
+    }
+}
@@ -8,10 +8,17 @@ use std::net::Ipv6Addr;
 use std::sync::Arc;
 
 pub use merge_mapping::{MergeRowOrder, ShuffleMergeOrder, StackMergeOrder};
+pub use term_merger::{TermMerger, TermsWithSegmentOrd};
 
 use super::writer::ColumnarSerializer;
-use crate::column::{serialize_column_mappable_to_u128, serialize_column_mappable_to_u64};
-use crate::column_values::MergedColumnValues;
+use crate::column::{
+    open_column_u64_bitpacked_values, open_column_u64_custom_codec_name,
+    serialize_column_mappable_to_u128, serialize_column_mappable_to_u64_with_custom_codec,
+    serialize_column_stacked_bitpacked,
+};
+use crate::column_values::{
+    get_column_codec, BitpackedReader, CustomColumnCodec, MergedColumnValues,
+};
 use crate::columnar::merge::merge_dict_column::merge_bytes_or_str_column;
 use crate::columnar::writer::CompatibleNumericalTypes;
 use crate::columnar::ColumnarReader;
@@ -87,8 +94,9 @@ pub fn merge_columnar(
 
     let columns_to_merge = group_columns_for_merge(columnar_readers, required_columns)?;
     for res in columns_to_merge {
-        let ((column_name, _column_type_category), grouped_columns) = res;
-        let grouped_columns = grouped_columns.open(&merge_row_order)?;
+        let ((column_name, _column_type_category), grouped_columns_handle) = res;
+        let column_handles = grouped_columns_handle.columns.clone();
+        let grouped_columns = grouped_columns_handle.open(&merge_row_order)?;
         if grouped_columns.is_empty() {
             continue;
         }
@@ -98,6 +106,14 @@ pub fn merge_columnar(
         // Make sure the number of columns is the same as the number of columnar readers.
         // Or num_docs_per_columnar would be incorrect.
         assert_eq!(columns.len(), columnar_readers.len());
+        let stacked_bitpacked_values = stackable_bitpacked_values(
+            column_type_after_merge,
+            &column_handles,
+            &columns,
+            &merge_row_order,
+        )?;
+        let custom_codec_opt =
+            shared_custom_codec(column_type_after_merge, &column_handles, &columns)?;
         coerce_columns(column_type_after_merge, &mut columns)?;
 
         let mut column_serializer =
@@ -106,6 +122,8 @@ pub fn merge_columnar(
             column_type_after_merge,
             &num_docs_per_columnar,
             columns,
+            stacked_bitpacked_values,
+            custom_codec_opt.as_deref(),
             &merge_row_order,
             &mut column_serializer,
         )?;
@@ -127,10 +145,91 @@ fn dynamic_column_to_u64_monotonic(dynamic_column: DynamicColumn) -> Option<Colu
     }
 }
 
+/// Returns the bitpacked values of the columns, if they can be stacked without being decoded.
+///
+/// This is the case if the rows are simply stacked, and the values of all of the columns
+/// are bitpacked and do not need to be coerced to another type.
+fn stackable_bitpacked_values(
+    column_type: ColumnType,
+    column_handles: &[Option<DynamicColumnHandle>],
+    columns: &[Option<DynamicColumn>],
+    merge_row_order: &MergeRowOrder,
+) -> io::Result<Option<Vec<BitpackedReader>>> {
+    if !matches!(merge_row_order, MergeRowOrder::Stack(_)) {
+        return Ok(None);
+    }
+    if !matches!(
+        column_type,
+        ColumnType::I64
+            | ColumnType::U64
+            | ColumnType::F64
+            | ColumnType::DateTime
+            | ColumnType::Bool
+    ) {
+        return Ok(None);
+    }
+    let mut bitpacked_values = Vec::with_capacity(columns.len());
+    for (column_handle_opt, column_opt) in column_handles.iter().zip(columns) {
+        // Columns without any value are skipped.
+        let (Some(column_handle), Some(_)) = (column_handle_opt, column_opt) else {
+            continue;
+        };
+        if column_handle.column_type() != column_type {
+            return Ok(None);
+        }
+        let column_bytes = column_handle.file_slice().read_bytes()?;
+        let Some(column_values) = open_column_u64_bitpacked_values(column_bytes)? else {
+            return Ok(None);
+        };
+        bitpacked_values.push(column_values);
+    }
+    if bitpacked_values.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(bitpacked_values))
+}
+
+/// Returns the custom codec of the columns, if all of them use the same one.
+///
+/// The merged column is then serialized with this codec too.
+fn shared_custom_codec(
+    column_type: ColumnType,
+    column_handles: &[Option<DynamicColumnHandle>],
+    columns: &[Option<DynamicColumn>],
+) -> io::Result<Option<Arc<dyn CustomColumnCodec>>> {
+    if !matches!(
+        column_type,
+        ColumnType::I64 | ColumnType::U64 | ColumnType::F64 | ColumnType::DateTime
+    ) {
+        return Ok(None);
+    }
+    let mut shared_codec_name: Option<String> = None;
+    for (column_handle_opt, column_opt) in column_handles.iter().zip(columns) {
+        // Columns without any value are skipped.
+        let (Some(column_handle), Some(_)) = (column_handle_opt, column_opt) else {
+            continue;
+        };
+        let column_bytes = column_handle.file_slice().read_bytes()?;
+        let Some(codec_name) = open_column_u64_custom_codec_name(column_bytes)? else {
+            return Ok(None);
+        };
+        if let Some(shared_codec_name) = &shared_codec_name {
+            if shared_codec_name != &codec_name {
+                return Ok(None);
+            }
+        } else {
+            shared_codec_name = Some(codec_name);
+        }
+    }
+    Ok(shared_codec_name.and_then(|codec_name| get_column_codec(&codec_name)))
+}
+
 fn merge_column(
     column_type: ColumnType,
     num_docs_per_column: &[u32],
     columns_to_merge: Vec<Option<DynamicColumn>>,
+    stacked_bitpacked_values: Option<Vec<BitpackedReader>>,
+    custom_codec_opt: Option<&dyn CustomColumnCodec>,
     merge_row_order: &MergeRowOrder,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
@@ -158,12 +257,25 @@ fn merge_column(
             }
             let merged_column_index =
                 crate::column_index::merge_column_index(&column_indexes[..], merge_row_order);
+            if let Some(stacked_bitpacked_values) = stacked_bitpacked_values {
+                serialize_column_stacked_bitpacked(
+                    merged_column_index,
+                    &stacked_bitpacked_values,
+                    wrt,
+                )?;
+                return Ok(());
+            }
             let merge_column_values = MergedColumnValues {
                 column_indexes: &column_indexes[..],
                 column_values: &column_values[..],
                 merge_row_order,
             };
-            serialize_column_mappable_to_u64(merged_column_index, &merge_column_values, wrt)?;
+            serialize_column_mappable_to_u64_with_custom_codec(
+                merged_column_index,
+                &merge_column_values,
+                custom_codec_opt,
+                wrt,
+            )?;
         }
         ColumnType::IpAddr => {
             let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
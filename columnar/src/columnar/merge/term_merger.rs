@@ -51,7 +51,9 @@ impl<'a> TermMerger<'a> {
         }
     }
 
-    pub(crate) fn matching_segments<'b: 'a>(
+    /// Returns the segment ordinals and term ordinals of the segments containing the current
+    /// term.
+    pub fn matching_segments<'b: 'a>(
         &'b self,
     ) -> impl 'b + Iterator<Item = (usize, TermOrdinal)> {
         self.term_streams_with_segment
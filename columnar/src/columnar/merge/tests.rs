@@ -221,6 +221,56 @@ fn test_merge_columnar_numbers() {
     assert_eq!(vals.first(2u32), Some(-3f64));
 }
 
+#[test]
+fn test_merge_columnar_stack_bitpacked() {
+    let columnar1 = make_numerical_columnar_multiple_columns(&[(
+        "numbers",
+        &[
+            &[NumericalValue::from(10u64)],
+            &[30u64.into(), 50u64.into()],
+        ],
+    )]);
+    let columnar2 = make_numerical_columnar_multiple_columns(&[(
+        "numbers",
+        &[&[], &[NumericalValue::from(1_000u64)], &[4u64.into()]],
+    )]);
+    let columnars = &[&columnar1, &columnar2];
+    let merge_row_order = MergeRowOrder::Stack(StackMergeOrder::stack(columnars));
+    let grouped_columns_handle = group_columns_for_merge(columnars, &[])
+        .unwrap()
+        .remove(&("numbers".to_string(), ColumnTypeCategory::Numerical))
+        .unwrap();
+    let column_handles = grouped_columns_handle.columns.clone();
+    let grouped_columns = grouped_columns_handle.open(&merge_row_order).unwrap();
+    let stacked_bitpacked_values = stackable_bitpacked_values(
+        ColumnType::I64,
+        &column_handles,
+        &grouped_columns.columns,
+        &merge_row_order,
+    )
+    .unwrap();
+    assert_eq!(stacked_bitpacked_values.map(|values| values.len()), Some(2));
+
+    let mut buffer = Vec::new();
+    crate::columnar::merge_columnar(columnars, &[], merge_row_order, &mut buffer).unwrap();
+    let columnar_reader = ColumnarReader::open(buffer).unwrap();
+    assert_eq!(columnar_reader.num_docs(), 5);
+    let cols = columnar_reader.read_columns("numbers").unwrap();
+    let DynamicColumn::I64(vals) = cols[0].open().unwrap() else {
+        panic!()
+    };
+    assert_eq!(vals.get_cardinality(), Cardinality::Multivalued);
+    let vals_per_doc: Vec<Vec<i64>> = (0..5)
+        .map(|doc| vals.values_for_doc(doc).collect())
+        .collect();
+    assert_eq!(
+        vals_per_doc,
+        vec![vec![10], vec![30, 50], vec![], vec![1_000], vec![4]]
+    );
+    assert_eq!(vals.min_value(), 4);
+    assert_eq!(vals.max_value(), 1_000);
+}
+
 #[test]
 fn test_merge_columnar_texts() {
     let columnar1 = make_text_columnar_multiple_columns(&[("texts", &[&["a"]])]);
@@ -1,13 +1,18 @@
+mod column_statistics;
 mod column_type;
 mod format_version;
 mod merge;
 mod reader;
 mod writer;
 
+pub use column_statistics::ColumnStatistics;
 pub use column_type::{ColumnType, HasAssociatedColumnType};
 pub use format_version::{Version, CURRENT_VERSION};
 #[cfg(test)]
 pub(crate) use merge::ColumnTypeCategory;
-pub use merge::{merge_columnar, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder};
+pub use merge::{
+    merge_columnar, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, TermMerger,
+    TermsWithSegmentOrd,
+};
 pub use reader::ColumnarReader;
 pub use writer::ColumnarWriter;
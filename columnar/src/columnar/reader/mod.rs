@@ -2,10 +2,10 @@ use std::{fmt, io, mem};
 
 use common::file_slice::FileSlice;
 use common::json_path_writer::JSON_PATH_SEGMENT_SEP;
-use common::BinarySerializable;
+use common::{BinarySerializable, OwnedBytes};
 use sstable::{Dictionary, RangeSSTable};
 
-use crate::columnar::{format_version, ColumnType};
+use crate::columnar::{format_version, ColumnStatistics, ColumnType};
 use crate::dynamic_column::DynamicColumnHandle;
 use crate::{RowId, Version};
 
@@ -19,6 +19,10 @@ fn io_invalid_data(msg: String) -> io::Error {
 pub struct ColumnarReader {
     column_dictionary: Dictionary<RangeSSTable>,
     column_data: FileSlice,
+    // Statistics of the columns, in the order of the column dictionary.
+    //
+    // Empty for columnars written before format version 3.
+    column_statistics: OwnedBytes,
     num_docs: RowId,
     format_version: Version,
 }
@@ -55,6 +59,7 @@ impl fmt::Debug for ColumnarReader {
 fn read_all_columns_in_stream(
     mut stream: sstable::Streamer<'_, RangeSSTable>,
     column_data: &FileSlice,
+    column_statistics: &[u8],
     format_version: Version,
 ) -> io::Result<Vec<DynamicColumnHandle>> {
     let mut results = Vec::new();
@@ -71,6 +76,7 @@ fn read_all_columns_in_stream(
             file_slice,
             column_type,
             format_version,
+            statistics: ColumnStatistics::read(column_statistics, stream.term_ord()),
         };
         results.push(dynamic_column_handle);
     }
@@ -106,12 +112,24 @@ impl ColumnarReader {
         let version_footer_bytes: [u8; format_version::VERSION_FOOTER_NUM_BYTES] =
             footer_bytes[12..].try_into().unwrap();
         let format_version = format_version::parse_footer(version_footer_bytes)?;
-        let (column_data, sstable) =
+        let (file_slice_without_sstable, sstable) =
             file_slice_without_sstable_len.split_from_end(sstable_len as usize);
         let column_dictionary = Dictionary::open(sstable)?;
+        let (column_data, column_statistics) = match format_version {
+            Version::V1 | Version::V2 => (file_slice_without_sstable, OwnedBytes::empty()),
+            Version::V3 => {
+                let (file_slice_without_statistics_len, statistics_len_slice) =
+                    file_slice_without_sstable.split_from_end(mem::size_of::<u64>());
+                let statistics_len = u64::deserialize(&mut statistics_len_slice.read_bytes()?)?;
+                let (column_data, column_statistics) =
+                    file_slice_without_statistics_len.split_from_end(statistics_len as usize);
+                (column_data, column_statistics.read_bytes()?)
+            }
+        };
         Ok(ColumnarReader {
             column_dictionary,
             column_data,
+            column_statistics,
             num_docs: num_rows,
             format_version,
         })
@@ -145,6 +163,7 @@ impl ColumnarReader {
                     file_slice,
                     column_type,
                     format_version: self.format_version,
+                    statistics: ColumnStatistics::read(&self.column_statistics, stream.term_ord()),
                 };
                 Some((column_name, column_handle))
             } else {
@@ -168,7 +187,12 @@ impl ColumnarReader {
             .prefix_range(prefix)
             .into_stream_async()
             .await?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            &self.column_statistics,
+            self.format_version,
+        )
     }
 
     /// Get all columns for the given column name.
@@ -178,7 +202,12 @@ impl ColumnarReader {
     pub fn read_columns(&self, column_name: &str) -> io::Result<Vec<DynamicColumnHandle>> {
         let prefix = column_dictionary_prefix_for_column_name(column_name);
         let stream = self.column_dictionary.prefix_range(prefix).into_stream()?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            &self.column_statistics,
+            self.format_version,
+        )
     }
 
     pub async fn read_subpath_columns_async(
@@ -191,7 +220,12 @@ impl ColumnarReader {
             .prefix_range(prefix)
             .into_stream_async()
             .await?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            &self.column_statistics,
+            self.format_version,
+        )
     }
 
     /// Get all inner columns for a given JSON prefix, i.e columns for which the name starts
@@ -205,7 +239,12 @@ impl ColumnarReader {
             .column_dictionary
             .prefix_range(prefix.as_bytes())
             .into_stream()?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            &self.column_statistics,
+            self.format_version,
+        )
     }
 
     /// Return the number of columns in the columnar.
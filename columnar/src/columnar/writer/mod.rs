@@ -3,8 +3,10 @@ mod column_writers;
 mod serializer;
 mod value_index;
 
+use std::collections::HashMap;
 use std::io;
 use std::net::Ipv6Addr;
+use std::sync::Arc;
 
 use column_operation::ColumnOperation;
 pub(crate) use column_writers::CompatibleNumericalTypes;
@@ -14,7 +16,9 @@ pub(crate) use serializer::ColumnarSerializer;
 use stacker::{Addr, ArenaHashMap, MemoryArena};
 
 use crate::column_index::{SerializableColumnIndex, SerializableOptionalIndex};
-use crate::column_values::{MonotonicallyMappableToU128, MonotonicallyMappableToU64};
+use crate::column_values::{
+    CustomColumnCodec, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
+};
 use crate::columnar::column_type::ColumnType;
 use crate::columnar::writer::column_writers::{
     ColumnWriter, NumericalColumnWriter, StrOrBytesColumnWriter,
@@ -57,6 +61,8 @@ pub struct ColumnarWriter {
     arena: MemoryArena,
     // Dictionaries used to store dictionary-encoded values.
     dictionaries: Vec<DictionaryBuilder>,
+    // Custom codecs used to serialize the values of some columns.
+    column_codecs: HashMap<Vec<u8>, Arc<dyn CustomColumnCodec>>,
     buffers: SpareBuffers,
 }
 
@@ -148,6 +154,15 @@ impl ColumnarWriter {
         }
     }
 
+    /// Sets the custom codec used to serialize the values of the columns named `column_name`.
+    ///
+    /// It only applies to numerical and date time columns: other columns, or columns
+    /// for which the estimator of the codec returns `None`, use the built-in codecs.
+    pub fn set_column_codec(&mut self, column_name: &str, codec: Arc<dyn CustomColumnCodec>) {
+        self.column_codecs
+            .insert(column_name.as_bytes().to_vec(), codec);
+    }
+
     pub fn record_numerical<T: Into<NumericalValue> + Copy>(
         &mut self,
         doc: RowId,
@@ -362,6 +377,7 @@ impl ColumnarWriter {
                         num_docs,
                         numerical_type,
                         numerical_column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        self.column_codecs.get(column_name).map(Arc::as_ref),
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -377,6 +393,7 @@ impl ColumnarWriter {
                         num_docs,
                         NumericalType::I64,
                         column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        self.column_codecs.get(column_name).map(Arc::as_ref),
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -427,6 +444,7 @@ fn serialize_bytes_or_str_column(
         cardinality,
         num_docs,
         sort_values_within_row,
+        None,
         value_index_builders,
         u64_values,
         &mut wrt,
@@ -440,6 +458,7 @@ fn serialize_numerical_column(
     num_docs: RowId,
     numerical_type: NumericalType,
     op_iterator: impl Iterator<Item = ColumnOperation<NumericalValue>>,
+    custom_codec_opt: Option<&dyn CustomColumnCodec>,
     buffers: &mut SpareBuffers,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
@@ -455,6 +474,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                custom_codec_opt,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -466,6 +486,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                custom_codec_opt,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -477,6 +498,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                custom_codec_opt,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -506,6 +528,7 @@ fn serialize_bool_column(
         cardinality,
         num_docs,
         false,
+        None,
         value_index_builders,
         u64_values,
         wrt,
@@ -581,11 +604,13 @@ fn send_to_serialize_column_mappable_to_u128<
     Ok(())
 }
 
+#[expect(clippy::too_many_arguments)]
 fn send_to_serialize_column_mappable_to_u64(
     op_iterator: impl Iterator<Item = ColumnOperation<u64>>,
     cardinality: Cardinality,
     num_rows: RowId,
     sort_values_within_row: bool,
+    custom_codec_opt: Option<&dyn CustomColumnCodec>,
     value_index_builders: &mut PreallocatedIndexBuilders,
     values: &mut Vec<u64>,
     mut wrt: impl io::Write,
@@ -622,9 +647,10 @@ fn send_to_serialize_column_mappable_to_u64(
             SerializableColumnIndex::Multivalued(serializable_multivalued_index)
         }
     };
-    crate::column::serialize_column_mappable_to_u64(
+    crate::column::serialize_column_mappable_to_u64_with_custom_codec(
         serializable_column_index,
         &&values[..],
+        custom_codec_opt,
         &mut wrt,
     )?;
     Ok(())
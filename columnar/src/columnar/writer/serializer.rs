@@ -2,17 +2,19 @@ use std::io;
 use std::io::Write;
 
 use common::json_path_writer::JSON_END_OF_PATH;
-use common::{BinarySerializable, CountingWriter};
+use common::{BinarySerializable, CountingWriter, OwnedBytes};
 use sstable::value::RangeValueWriter;
 use sstable::RangeSSTable;
 
-use crate::columnar::ColumnType;
+use crate::columnar::{ColumnStatistics, ColumnType};
 use crate::RowId;
 
 pub struct ColumnarSerializer<W: io::Write> {
     wrt: CountingWriter<W>,
     sstable_range: sstable::Writer<Vec<u8>, RangeValueWriter>,
     prepare_key_buffer: Vec<u8>,
+    // Statistics of the serialized columns, in the order of the column dictionary.
+    column_statistics: Vec<u8>,
 }
 
 /// Returns a key consisting of the concatenation of the key and the column_type_and_cardinality
@@ -32,6 +34,7 @@ impl<W: io::Write> ColumnarSerializer<W> {
             wrt: CountingWriter::wrap(wrt),
             sstable_range,
             prepare_key_buffer: Vec::new(),
+            column_statistics: Vec::new(),
         }
     }
 
@@ -46,10 +49,16 @@ impl<W: io::Write> ColumnarSerializer<W> {
         ColumnSerializer {
             columnar_serializer: self,
             start_offset,
+            column_type,
+            column_bytes: Vec::new(),
         }
     }
 
     pub(crate) fn finalize(mut self, num_rows: RowId) -> io::Result<()> {
+        let column_statistics_num_bytes: u64 = self.column_statistics.len() as u64;
+        self.wrt.write_all(&self.column_statistics)?;
+        self.wrt
+            .write_all(&column_statistics_num_bytes.to_le_bytes()[..])?;
         let sstable_bytes: Vec<u8> = self.sstable_range.finish()?;
         let sstable_num_bytes: u64 = sstable_bytes.len() as u64;
         self.wrt.write_all(&sstable_bytes)?;
@@ -65,6 +74,9 @@ impl<W: io::Write> ColumnarSerializer<W> {
 pub struct ColumnSerializer<'a, W: io::Write> {
     columnar_serializer: &'a mut ColumnarSerializer<W>,
     start_offset: u64,
+    column_type: ColumnType,
+    // Copy of the column bytes, used to compute the column statistics.
+    column_bytes: Vec<u8>,
 }
 
 impl<W: io::Write> ColumnSerializer<'_, W> {
@@ -76,13 +88,20 @@ impl<W: io::Write> ColumnSerializer<'_, W> {
             &byte_range,
         )?;
         self.columnar_serializer.prepare_key_buffer.clear();
+        let column_statistics =
+            ColumnStatistics::compute(OwnedBytes::new(self.column_bytes), self.column_type)?;
+        self.columnar_serializer
+            .column_statistics
+            .extend_from_slice(&column_statistics.to_bytes());
         Ok(())
     }
 }
 
 impl<W: io::Write> io::Write for ColumnSerializer<'_, W> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.columnar_serializer.wrt.write(buf)
+        let num_bytes = self.columnar_serializer.wrt.write(buf)?;
+        self.column_bytes.extend_from_slice(&buf[..num_bytes]);
+        Ok(num_bytes)
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -90,6 +109,8 @@ impl<W: io::Write> io::Write for ColumnSerializer<'_, W> {
     }
 
     fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
-        self.columnar_serializer.wrt.write_all(buf)
+        self.columnar_serializer.wrt.write_all(buf)?;
+        self.column_bytes.extend_from_slice(buf);
+        Ok(())
     }
 }
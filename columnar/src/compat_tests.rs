@@ -60,6 +60,18 @@ fn test_format_v2() {
     test_format(&path);
 }
 
+#[test]
+fn test_format_v3() {
+    let path = path_for_version("v3");
+    test_format(&path);
+    let reader = ColumnarReader::open(std::fs::read(path).unwrap()).unwrap();
+    let statistics = reader.read_columns("full").unwrap()[0]
+        .statistics()
+        .unwrap();
+    assert_eq!(statistics.min_value::<i64>(), Some(0));
+    assert_eq!(statistics.max_value::<i64>(), Some(NUM_DOCS as i64 - 1));
+}
+
 fn test_format(path: &str) {
     let file_content = std::fs::read(path).unwrap();
     let reader = ColumnarReader::open(file_content).unwrap();
@@ -74,6 +86,13 @@ fn test_format(path: &str) {
     merge_columnar(&columnar_readers, &[], merge_row_order.into(), &mut out).unwrap();
     let reader = ColumnarReader::open(out).unwrap();
     check_columns(&reader);
+    let statistics = reader.read_columns("dense").unwrap()[0]
+        .statistics()
+        .unwrap();
+    assert_eq!(
+        statistics.null_count(),
+        2 * NUM_DOCS - 2 * NUM_DOCS.div_ceil(5)
+    );
 }
 
 fn check_columns(reader: &ColumnarReader) {
@@ -7,8 +7,8 @@ use common::{ByteCount, DateTime, HasLen, OwnedBytes};
 
 use crate::column::{BytesColumn, Column, StrColumn};
 use crate::column_values::{monotonic_map_column, StrictlyMonotonicFn};
-use crate::columnar::ColumnType;
-use crate::{Cardinality, ColumnIndex, ColumnValues, NumericalType, Version};
+use crate::columnar::{ColumnStatistics, ColumnType};
+use crate::{Cardinality, ColumnIndex, ColumnValues, NumericalType, RowId, Version};
 
 #[derive(Clone)]
 pub enum DynamicColumn {
@@ -70,6 +70,19 @@ impl DynamicColumn {
         }
     }
 
+    pub fn num_docs(&self) -> RowId {
+        match self {
+            DynamicColumn::Bool(c) => c.num_docs(),
+            DynamicColumn::I64(c) => c.num_docs(),
+            DynamicColumn::U64(c) => c.num_docs(),
+            DynamicColumn::F64(c) => c.num_docs(),
+            DynamicColumn::IpAddr(c) => c.num_docs(),
+            DynamicColumn::DateTime(c) => c.num_docs(),
+            DynamicColumn::Bytes(c) => c.ords().num_docs(),
+            DynamicColumn::Str(c) => c.ords().num_docs(),
+        }
+    }
+
     pub fn column_type(&self) -> ColumnType {
         match self {
             DynamicColumn::Bool(_) => ColumnType::Bool,
@@ -233,6 +246,7 @@ pub struct DynamicColumnHandle {
     pub(crate) file_slice: FileSlice,
     pub(crate) column_type: ColumnType,
     pub(crate) format_version: Version,
+    pub(crate) statistics: Option<ColumnStatistics>,
 }
 
 impl DynamicColumnHandle {
@@ -323,4 +337,12 @@ impl DynamicColumnHandle {
     pub fn column_type(&self) -> ColumnType {
         self.column_type
     }
+
+    /// Returns the statistics of the column, computed when it was serialized.
+    ///
+    /// Returns `None` for columns written by a version of the columnar format that did not
+    /// store statistics.
+    pub fn statistics(&self) -> Option<ColumnStatistics> {
+        self.statistics
+    }
 }
@@ -44,11 +44,13 @@ pub use block_accessor::ColumnBlockAccessor;
 pub use column::{BytesColumn, Column, StrColumn};
 pub use column_index::ColumnIndex;
 pub use column_values::{
-    ColumnValues, EmptyColumnValues, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
+    get_column_codec, register_column_codec, ColumnCodecEstimator, ColumnValues, CustomColumnCodec,
+    EmptyColumnValues, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
 };
 pub use columnar::{
-    merge_columnar, ColumnType, ColumnarReader, ColumnarWriter, HasAssociatedColumnType,
-    MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, Version, CURRENT_VERSION,
+    merge_columnar, ColumnStatistics, ColumnType, ColumnarReader, ColumnarWriter,
+    HasAssociatedColumnType, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, TermMerger,
+    TermsWithSegmentOrd, Version, CURRENT_VERSION,
 };
 use sstable::VoidSSTable;
 pub use value::{NumericalType, NumericalValue};
@@ -302,6 +302,12 @@ impl BitSet {
     pub fn tinyset(&self, bucket: u32) -> TinySet {
         self.tinysets[bucket as usize]
     }
+
+    /// Returns the number of tiny bitsets of the `BitSet`.
+    #[inline]
+    pub fn num_buckets(&self) -> u32 {
+        self.tinysets.len() as u32
+    }
 }
 
 /// Serialized BitSet.
@@ -353,6 +359,15 @@ impl ReadOnlyBitSet {
         })
     }
 
+    /// Returns the tiny bitset representing the
+    /// the set restricted to the number range from
+    /// `bucket * 64` to `(bucket + 1) * 64`.
+    #[inline]
+    pub fn tinyset(&self, bucket: u32) -> TinySet {
+        let start = bucket as usize * 8;
+        TinySet::deserialize(self.data[start..start + 8].try_into().unwrap())
+    }
+
     /// Iterate over the positions of the elements.
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
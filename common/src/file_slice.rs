@@ -25,6 +25,19 @@ pub trait FileHandle: 'static + Send + Sync + HasLen + fmt::Debug {
     /// This method may panic if the range requested is invalid.
     fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes>;
 
+    /// Reads several slices of bytes.
+    ///
+    /// Implementations may submit all the reads at once. By default, the slices are read
+    /// one after the other.
+    ///
+    /// This method may panic if one of the ranges requested is invalid.
+    fn read_bytes_batch(&self, ranges: &[Range<usize>]) -> io::Result<Vec<OwnedBytes>> {
+        ranges
+            .iter()
+            .map(|range| self.read_bytes(range.clone()))
+            .collect()
+    }
+
     #[doc(hidden)]
     async fn read_bytes_async(&self, _byte_range: Range<usize>) -> io::Result<OwnedBytes> {
         Err(io::Error::new(
@@ -249,6 +262,26 @@ impl FileSlice {
             .read_bytes(self.range.start + range.start..self.range.start + range.end)
     }
 
+    /// Reads several slices of data at once.
+    ///
+    /// This is equivalent to calling [`FileSlice::read_bytes_slice`] for each range, but lets
+    /// the underlying file handle batch the reads.
+    pub fn read_bytes_slices(&self, ranges: &[Range<usize>]) -> io::Result<Vec<OwnedBytes>> {
+        let ranges: Vec<Range<usize>> = ranges
+            .iter()
+            .map(|range| {
+                assert!(
+                    range.end <= self.len(),
+                    "end of requested range exceeds the fileslice length ({} > {})",
+                    range.end,
+                    self.len()
+                );
+                self.range.start + range.start..self.range.start + range.end
+            })
+            .collect();
+        self.data.read_bytes_batch(&ranges)
+    }
+
     #[doc(hidden)]
     pub async fn read_bytes_slice_async(&self, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
         assert!(
@@ -317,6 +350,10 @@ impl FileHandle for FileSlice {
         self.read_bytes_slice(range)
     }
 
+    fn read_bytes_batch(&self, ranges: &[Range<usize>]) -> io::Result<Vec<OwnedBytes>> {
+        self.read_bytes_slices(ranges)
+    }
+
     async fn read_bytes_async(&self, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
         self.read_bytes_slice_async(byte_range).await
     }
@@ -386,6 +423,15 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_file_slice_read_bytes_slices() -> io::Result<()> {
+        let file_slice = FileSlice::new(Arc::new(b"abcdef".as_ref())).slice_from(1);
+        let slices = file_slice.read_bytes_slices(&[0..2, 3..5, 1..1])?;
+        let slices: Vec<&[u8]> = slices.iter().map(|bytes| bytes.as_slice()).collect();
+        assert_eq!(slices, [&b"bc"[..], &b"ef"[..], &b""[..]]);
+        Ok(())
+    }
+
     #[test]
     fn test_file_slice_trait_slice_len() {
         let blop: &'static [u8] = b"abc";
@@ -14,6 +14,12 @@ pub enum Occur {
     /// Document that contain the query are excluded from the
     /// search.
     MustNot,
+    /// Document without the queries are excluded from the search, like `Must`, but the
+    /// queries do not contribute to the score.
+    ///
+    /// The query grammar has no syntax for `Filter`: its `#` prefix is display-only, and is
+    /// parsed back as part of the term, e.g. `#tag` is the term `#tag`.
+    Filter,
 }
 
 impl Occur {
@@ -21,11 +27,13 @@ impl Occur {
     /// - `Should` => '?',
     /// - `Must` => '+'
     /// - `Not` => '-'
+    /// - `Filter` => '#' (display-only, not parsed by the query grammar)
     fn to_char(self) -> char {
         match self {
             Occur::Should => '?',
             Occur::Must => '+',
             Occur::MustNot => '-',
+            Occur::Filter => '#',
         }
     }
 
@@ -34,9 +42,13 @@ impl Occur {
         match (left, right) {
             (Occur::Should, _) => right,
             (Occur::Must, Occur::MustNot) => Occur::MustNot,
+            (Occur::Must, Occur::Filter) => Occur::Filter,
             (Occur::Must, _) => Occur::Must,
             (Occur::MustNot, Occur::MustNot) => Occur::Must,
+            (Occur::MustNot, Occur::Filter) => Occur::MustNot,
             (Occur::MustNot, _) => Occur::MustNot,
+            (Occur::Filter, Occur::MustNot) => Occur::MustNot,
+            (Occur::Filter, _) => Occur::Filter,
         }
     }
 }
@@ -68,5 +80,15 @@ mod test {
         );
         assert_eq!(Occur::compose(Occur::MustNot, Occur::Must), Occur::MustNot);
         assert_eq!(Occur::compose(Occur::MustNot, Occur::MustNot), Occur::Must);
+        assert_eq!(Occur::compose(Occur::Must, Occur::Filter), Occur::Filter);
+        assert_eq!(Occur::compose(Occur::Filter, Occur::Must), Occur::Filter);
+        assert_eq!(
+            Occur::compose(Occur::Filter, Occur::MustNot),
+            Occur::MustNot
+        );
+        assert_eq!(
+            Occur::compose(Occur::MustNot, Occur::Filter),
+            Occur::MustNot
+        );
     }
 }
@@ -999,7 +999,19 @@ pub fn parse_to_ast_lenient(query_str: &str) -> (UserInputAst, Vec<LenientError>
     if query_str.trim().is_empty() {
         return (UserInputAst::Clause(Vec::new()), Vec::new());
     }
-    let (left, (res, mut errors)) = ast_infallible(query_str).unwrap();
+    let (mut left, (res, mut errors)) = ast_infallible(query_str).unwrap();
+    let mut asts = vec![res];
+    // An unmatched closing parenthesis stops the parser. Skip it and parse the rest of the query.
+    while let Some(rest) = left.trim_start().strip_prefix(')') {
+        errors.push(LenientErrorInternal {
+            pos: left.trim_start().len(),
+            message: "unmatched ')'".to_string(),
+        });
+        let (rest_left, (res, mut rest_errors)) = ast_infallible(rest).unwrap();
+        asts.push(res);
+        errors.append(&mut rest_errors);
+        left = rest_left;
+    }
     if !left.trim().is_empty() {
         errors.push(LenientErrorInternal {
             pos: left.len(),
@@ -1013,6 +1025,13 @@ pub fn parse_to_ast_lenient(query_str: &str) -> (UserInputAst, Vec<LenientError>
         .map(|internal_error| LenientError::from_internal(internal_error, query_str.len()))
         .collect();
 
+    asts.retain(|ast| !matches!(ast, UserInputAst::Clause(clause) if clause.is_empty()));
+    let res = if asts.len() == 1 {
+        asts.pop().unwrap()
+    } else {
+        UserInputAst::Clause(asts.into_iter().map(|ast| (None, ast)).collect())
+    };
+
     (rewrite_ast(res), errors)
 }
 
@@ -1147,6 +1166,22 @@ mod test {
         // test_is_parse_err("'www-form-encoded", "'www-form-encoded'");
     }
 
+    #[test]
+    fn test_parse_query_lenient_unmatched_parenthesis() {
+        test_is_parse_err("a) b", "(*a *b)");
+        test_is_parse_err("a) (b", "(*a *b)");
+        test_is_parse_err(") a", "a");
+        test_is_parse_err("a)", "a");
+        let (_, errs) = parse_to_ast_lenient("a) b");
+        assert_eq!(
+            errs,
+            vec![LenientError {
+                pos: 1,
+                message: "unmatched ')'".to_string()
+            }]
+        );
+    }
+
     #[test]
     fn test_parse_query_to_ast_not_op() {
         test_is_parse_err("NOT", "NOT");
@@ -1235,6 +1270,10 @@ mod test {
         let (_, (occur, ast)) = super::occur_leaf("+abc").unwrap();
         assert_eq!(occur, Some(Occur::Must));
         assert_eq!(format!("{ast:?}"), "abc");
+        // `#` is the display-only prefix of `Occur::Filter`, and is part of the term.
+        let (_, (occur, ast)) = super::occur_leaf("#abc").unwrap();
+        assert_eq!(occur, None);
+        assert_eq!(format!("{ast:?}"), "#abc");
     }
 
     #[test]
@@ -6,6 +6,7 @@ use common::ByteCount;
 
 use super::collector::DEFAULT_MEMORY_LIMIT;
 use super::{AggregationError, DEFAULT_BUCKET_LIMIT};
+use crate::core::{MemoryBudget, MemoryReservation};
 
 /// An estimate for memory consumption. Non recursive
 pub trait MemoryConsumption {
@@ -32,8 +33,20 @@ pub struct AggregationLimitsGuard {
     /// The maximum number of buckets _returned_
     /// This is not counting intermediate buckets.
     bucket_limit: u32,
+    /// The maximum nesting depth of the aggregation request.
+    max_depth: Option<u32>,
+    /// The counter of buckets created during collection, shared between the aggregations for
+    /// one request.
+    collected_buckets: Arc<AtomicU64>,
+    /// The maximum number of buckets created during collection, across the aggregation tree.
+    collected_bucket_limit: Option<u32>,
     /// Allocated memory with this guard.
     allocated_with_the_guard: u64,
+    /// Buckets created with this guard.
+    collected_buckets_with_the_guard: u64,
+    /// Allocated memory with this guard, reserved against the memory budget shared with other
+    /// requests.
+    memory_reservation: Option<MemoryReservation>,
 }
 impl Clone for AggregationLimitsGuard {
     fn clone(&self) -> Self {
@@ -41,7 +54,15 @@ impl Clone for AggregationLimitsGuard {
             memory_consumption: Arc::clone(&self.memory_consumption),
             memory_limit: self.memory_limit,
             bucket_limit: self.bucket_limit,
+            max_depth: self.max_depth,
+            collected_buckets: Arc::clone(&self.collected_buckets),
+            collected_bucket_limit: self.collected_bucket_limit,
             allocated_with_the_guard: 0,
+            collected_buckets_with_the_guard: 0,
+            memory_reservation: self
+                .memory_reservation
+                .as_ref()
+                .map(|memory_reservation| memory_reservation.budget().empty_reservation()),
         }
     }
 }
@@ -52,6 +73,8 @@ impl Drop for AggregationLimitsGuard {
     fn drop(&mut self) {
         self.memory_consumption
             .fetch_sub(self.allocated_with_the_guard, Ordering::Relaxed);
+        self.collected_buckets
+            .fetch_sub(self.collected_buckets_with_the_guard, Ordering::Relaxed);
     }
 }
 
@@ -61,7 +84,12 @@ impl Default for AggregationLimitsGuard {
             memory_consumption: Default::default(),
             memory_limit: DEFAULT_MEMORY_LIMIT.into(),
             bucket_limit: DEFAULT_BUCKET_LIMIT,
+            max_depth: None,
+            collected_buckets: Default::default(),
+            collected_bucket_limit: None,
             allocated_with_the_guard: 0,
+            collected_buckets_with_the_guard: 0,
+            memory_reservation: None,
         }
     }
 }
@@ -83,16 +111,91 @@ impl AggregationLimitsGuard {
             memory_consumption: Default::default(),
             memory_limit: memory_limit.unwrap_or(DEFAULT_MEMORY_LIMIT).into(),
             bucket_limit: bucket_limit.unwrap_or(DEFAULT_BUCKET_LIMIT),
+            max_depth: None,
+            collected_buckets: Default::default(),
+            collected_bucket_limit: None,
             allocated_with_the_guard: 0,
+            collected_buckets_with_the_guard: 0,
+            memory_reservation: None,
         }
     }
 
+    /// Reserves the memory consumed by the aggregation against `memory_budget` too.
+    ///
+    /// The budget may be shared with other aggregation requests and with
+    /// [`IndexReader`](crate::IndexReader)s. The aggregation fails with
+    /// [`AggregationError::MemoryExceeded`] when its memory does not fit in the budget, even
+    /// if it is below `memory_limit`.
+    #[must_use]
+    pub fn with_memory_budget(mut self, memory_budget: &MemoryBudget) -> Self {
+        self.memory_reservation = Some(memory_budget.empty_reservation());
+        self
+    }
+
+    /// Limits the nesting depth of the aggregation request, e.g. a `terms` aggregation with a
+    /// `histogram` sub-aggregation has a depth of 2.
+    ///
+    /// The request fails with [`AggregationError::DepthLimitExceeded`] when it is nested deeper.
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Limits the number of buckets created during collection, summed over all bucket
+    /// aggregations in the tree, including nested ones.
+    ///
+    /// Unlike `bucket_limit`, this is checked while collecting, before the buckets are merged
+    /// and cut off. The aggregation fails with [`AggregationError::BucketLimitExceeded`] when
+    /// more buckets are created.
+    #[must_use]
+    pub fn with_collected_bucket_limit(mut self, collected_bucket_limit: u32) -> Self {
+        self.collected_bucket_limit = Some(collected_bucket_limit);
+        self
+    }
+
+    pub(crate) fn validate_depth(&self, depth: u32) -> Result<(), AggregationError> {
+        match self.max_depth {
+            Some(limit) if depth > limit => Err(AggregationError::DepthLimitExceeded {
+                limit,
+                current: depth,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn add_buckets_collected(&mut self, add_num_buckets: u64) -> crate::Result<()> {
+        let prev_value = self
+            .collected_buckets
+            .fetch_add(add_num_buckets, Ordering::Relaxed);
+        self.collected_buckets_with_the_guard += add_num_buckets;
+        if let Some(limit) = self.collected_bucket_limit {
+            let current = prev_value + add_num_buckets;
+            if current > limit as u64 {
+                return Err(AggregationError::BucketLimitExceeded {
+                    limit,
+                    current: current.min(u32::MAX as u64) as u32,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn add_memory_consumed(&mut self, add_num_bytes: u64) -> crate::Result<()> {
         let prev_value = self
             .memory_consumption
             .fetch_add(add_num_bytes, Ordering::Relaxed);
         self.allocated_with_the_guard += add_num_bytes;
         validate_memory_consumption(prev_value + add_num_bytes, self.memory_limit)?;
+        if let Some(memory_reservation) = self.memory_reservation.as_ref() {
+            memory_reservation.try_grow(add_num_bytes).map_err(|err| {
+                AggregationError::MemoryExceeded {
+                    limit: err.limit,
+                    current: err.used + err.requested,
+                }
+            })?;
+        }
         Ok(())
     }
 
@@ -253,4 +356,101 @@ mod tests {
             assert_eq!(res, expected_res);
         }
     }
+
+    #[test]
+    fn test_agg_limits_with_memory_budget() -> crate::Result<()> {
+        use crate::aggregation::agg_req::Aggregations;
+        use crate::aggregation::tests::{
+            exec_request_with_query_and_memory_limit, get_test_index_from_terms,
+        };
+        use crate::aggregation::AggregationLimitsGuard;
+        use crate::MemoryBudget;
+
+        let terms: Vec<String> = (0..1_000).map(|el| el.to_string()).collect();
+        let terms_per_segment = vec![terms.iter().map(|el| el.as_str()).collect()];
+        let index = get_test_index_from_terms(true, &terms_per_segment)?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "my_texts": { "terms": { "field": "string_id" } }
+        }))
+        .unwrap();
+
+        let memory_budget = MemoryBudget::new(10_000);
+        let err = exec_request_with_query_and_memory_limit(
+            agg_req.clone(),
+            &index,
+            None,
+            AggregationLimitsGuard::default().with_memory_budget(&memory_budget),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Aborting aggregation because memory limit was exceeded. Limit: 10.00 KB"));
+        // The memory of the failed aggregation is given back to the budget.
+        assert_eq!(memory_budget.used().get_bytes(), 0);
+
+        let memory_budget = MemoryBudget::new(10_000_000);
+        let res = exec_request_with_query_and_memory_limit(
+            agg_req,
+            &index,
+            None,
+            AggregationLimitsGuard::default().with_memory_budget(&memory_budget),
+        )?;
+        assert_eq!(res["my_texts"]["buckets"].as_array().unwrap().len(), 10);
+        assert_eq!(memory_budget.used().get_bytes(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_agg_limits_with_depth_and_collected_buckets() -> crate::Result<()> {
+        use crate::aggregation::agg_req::Aggregations;
+        use crate::aggregation::tests::{
+            exec_request_with_query_and_memory_limit, get_test_index_from_terms,
+        };
+        use crate::aggregation::AggregationLimitsGuard;
+
+        let terms: Vec<String> = (0..1_000).map(|el| el.to_string()).collect();
+        let terms_per_segment = vec![terms.iter().map(|el| el.as_str()).collect()];
+        let index = get_test_index_from_terms(true, &terms_per_segment)?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "my_texts": {
+                "terms": { "field": "string_id" },
+                "aggs": { "my_scores": { "histogram": { "field": "score", "interval": 1.0 } } }
+            }
+        }))
+        .unwrap();
+
+        let err = exec_request_with_query_and_memory_limit(
+            agg_req.clone(),
+            &index,
+            None,
+            AggregationLimitsGuard::default().with_max_depth(1),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Aborting aggregation because depth limit was exceeded. Limit: 1, Current: 2"
+        );
+
+        let err = exec_request_with_query_and_memory_limit(
+            agg_req.clone(),
+            &index,
+            None,
+            AggregationLimitsGuard::default().with_collected_bucket_limit(500),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Aborting aggregation because bucket limit was exceeded. Limit: 500"));
+
+        let res = exec_request_with_query_and_memory_limit(
+            agg_req,
+            &index,
+            None,
+            AggregationLimitsGuard::default()
+                .with_max_depth(2)
+                .with_collected_bucket_limit(10_000),
+        )?;
+        assert_eq!(res["my_texts"]["buckets"].as_array().unwrap().len(), 10);
+        Ok(())
+    }
 }
@@ -31,12 +31,14 @@ use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 
 use super::bucket::{
-    DateHistogramAggregationReq, HistogramAggregation, RangeAggregation, TermsAggregation,
+    CompositeAggregation, DateHistogramAggregationReq, FilterAggregation, FiltersAggregation,
+    HistogramAggregation, RangeAggregation, TermsAggregation,
 };
 use super::metric::{
     AverageAggregation, CardinalityAggregationReq, CountAggregation, ExtendedStatsAggregation,
-    MaxAggregation, MinAggregation, PercentilesAggregationReq, StatsAggregation, SumAggregation,
-    TopHitsAggregationReq,
+    GeoBoundsAggregation, GeoCentroidAggregation, MaxAggregation, MinAggregation,
+    PercentilesAggregationReq, RateAggregation, StatsAggregation, SumAggregation,
+    TopHitsAggregationReq, TopMetricsAggregationReq,
 };
 
 /// The top-level aggregation request structure, which contains [`Aggregation`] and their user
@@ -113,6 +115,14 @@ pub fn get_fast_field_names(aggs: &Aggregations) -> HashSet<String> {
     fast_field_names
 }
 
+/// Returns the nesting depth of the tree. A request without sub-aggregations has a depth of 1.
+pub(crate) fn get_aggregation_depth(aggs: &Aggregations) -> u32 {
+    aggs.values()
+        .map(|agg| 1 + get_aggregation_depth(agg.sub_aggregation()))
+        .max()
+        .unwrap_or(0)
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 /// All aggregation types.
 pub enum AggregationVariants {
@@ -129,6 +139,15 @@ pub enum AggregationVariants {
     /// Put data into buckets of terms.
     #[serde(rename = "terms")]
     Terms(TermsAggregation),
+    /// Put the documents matching a query into a bucket.
+    #[serde(rename = "filter")]
+    Filter(FilterAggregation),
+    /// Put the documents matching each of several named queries into a bucket.
+    #[serde(rename = "filters")]
+    Filters(FiltersAggregation),
+    /// Page through the buckets of the combinations of the values of several sources.
+    #[serde(rename = "composite")]
+    Composite(CompositeAggregation),
 
     // Metric aggregation types
     /// Computes the average of the extracted values.
@@ -161,9 +180,21 @@ pub enum AggregationVariants {
     /// Finds the top k values matching some order
     #[serde(rename = "top_hits")]
     TopHits(TopHitsAggregationReq),
+    /// Returns the values of some fields of the best document by a sort field.
+    #[serde(rename = "top_metrics")]
+    TopMetrics(TopMetricsAggregationReq),
     /// Computes an estimate of the number of unique values
     #[serde(rename = "cardinality")]
     Cardinality(CardinalityAggregationReq),
+    /// Computes the centroid of the geo points.
+    #[serde(rename = "geo_centroid")]
+    GeoCentroid(GeoCentroidAggregation),
+    /// Computes the bounding box of the geo points.
+    #[serde(rename = "geo_bounds")]
+    GeoBounds(GeoBoundsAggregation),
+    /// Computes the rate of the documents or values per unit of time, in a date histogram.
+    #[serde(rename = "rate")]
+    Rate(RateAggregation),
 }
 
 impl AggregationVariants {
@@ -174,6 +205,8 @@ impl AggregationVariants {
             AggregationVariants::Range(range) => vec![range.field.as_str()],
             AggregationVariants::Histogram(histogram) => vec![histogram.field.as_str()],
             AggregationVariants::DateHistogram(histogram) => vec![histogram.field.as_str()],
+            AggregationVariants::Filter(_) | AggregationVariants::Filters(_) => Vec::new(),
+            AggregationVariants::Composite(composite) => composite.field_names(),
             AggregationVariants::Average(avg) => vec![avg.field_name()],
             AggregationVariants::Count(count) => vec![count.field_name()],
             AggregationVariants::Max(max) => vec![max.field_name()],
@@ -183,7 +216,11 @@ impl AggregationVariants {
             AggregationVariants::Sum(sum) => vec![sum.field_name()],
             AggregationVariants::Percentiles(per) => vec![per.field_name()],
             AggregationVariants::TopHits(top_hits) => top_hits.field_names(),
+            AggregationVariants::TopMetrics(top_metrics) => top_metrics.field_names(),
             AggregationVariants::Cardinality(per) => vec![per.field_name()],
+            AggregationVariants::GeoCentroid(geo_centroid) => vec![geo_centroid.field_name()],
+            AggregationVariants::GeoBounds(geo_bounds) => vec![geo_bounds.field_name()],
+            AggregationVariants::Rate(rate) => rate.field_name().into_iter().collect(),
         }
     }
 
@@ -215,6 +252,20 @@ impl AggregationVariants {
         }
     }
 
+    pub(crate) fn as_top_metrics(&self) -> Option<&TopMetricsAggregationReq> {
+        match &self {
+            AggregationVariants::TopMetrics(top_metrics) => Some(top_metrics),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_rate(&self) -> Option<&RateAggregation> {
+        match &self {
+            AggregationVariants::Rate(rate_req) => Some(rate_req),
+            _ => None,
+        }
+    }
+
     pub(crate) fn as_percentile(&self) -> Option<&PercentilesAggregationReq> {
         match &self {
             AggregationVariants::Percentiles(percentile_req) => Some(percentile_req),
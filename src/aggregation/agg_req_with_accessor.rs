@@ -2,8 +2,10 @@
 
 use std::collections::HashMap;
 use std::io;
+use std::sync::Arc;
 
 use columnar::{Column, ColumnBlockAccessor, ColumnType, DynamicColumn, StrColumn};
+use common::BitSet;
 
 use super::agg_req::{Aggregation, AggregationVariants, Aggregations};
 use super::bucket::{
@@ -11,13 +13,15 @@ use super::bucket::{
 };
 use super::metric::{
     AverageAggregation, CardinalityAggregationReq, CountAggregation, ExtendedStatsAggregation,
-    MaxAggregation, MinAggregation, StatsAggregation, SumAggregation,
+    GeoBoundsAggregation, GeoCentroidAggregation, MaxAggregation, MinAggregation, RateAggregation,
+    StatsAggregation, SumAggregation,
 };
 use super::segment_agg_result::AggregationLimitsGuard;
 use super::VecWithNames;
 use crate::aggregation::{f64_to_fastfield_u64, Key};
+use crate::fastfield::GlobalOrdinals;
 use crate::index::SegmentReader;
-use crate::SegmentOrdinal;
+use crate::{Searcher, SegmentOrdinal};
 
 #[derive(Default)]
 pub(crate) struct AggregationsWithAccessor {
@@ -43,12 +47,18 @@ pub struct AggregationWithAccessor {
     /// Load insert u64 for missing use case
     pub(crate) missing_value_for_accessor: Option<u64>,
     pub(crate) str_dict_column: Option<StrColumn>,
+    /// The global ordinals of the `str` column of a `terms` aggregation, if the aggregation is
+    /// collected with [`AggregationCollector::with_global_ordinals`](
+    /// crate::aggregation::AggregationCollector::with_global_ordinals).
+    pub(crate) global_ordinals: Option<Arc<GlobalOrdinals>>,
     pub(crate) field_type: ColumnType,
     pub(crate) sub_aggregation: AggregationsWithAccessor,
     pub(crate) limits: AggregationLimitsGuard,
     pub(crate) column_block_accessor: ColumnBlockAccessor<u64>,
     /// Used for missing term aggregation, which checks all columns for existence.
     /// And also for `top_hits` aggregation, which may sort on multiple fields.
+    /// And also for the geo aggregations, which read the latitude and longitude columns, and
+    /// for `top_metrics`, which reads a sort field and metric fields.
     /// By convention the missing aggregation is chosen, when this property is set
     /// (instead bein set in `agg`).
     /// If this needs to used by other aggregations, we need to refactor this.
@@ -58,6 +68,12 @@ pub struct AggregationWithAccessor {
     /// Map field names to all associated column accessors.
     /// This field is used for `docvalue_fields`, which is currently only supported for `top_hits`.
     pub(crate) value_accessors: HashMap<String, Vec<DynamicColumn>>,
+    /// The documents of the segment matching the queries of the `filter` and `filters`
+    /// aggregations, in the order of their buckets.
+    pub(crate) filters_matching_docs: Vec<BitSet>,
+    /// The dictionaries of the `str` columns of `accessors`, used by the `composite`
+    /// aggregation to resolve and seek term ordinals.
+    pub(crate) str_dict_columns: Vec<Option<StrColumn>>,
     pub(crate) agg: Aggregation,
 }
 
@@ -93,7 +109,10 @@ impl AggregationWithAccessor {
                 limits: limits.clone(),
                 missing_value_for_accessor: None,
                 str_dict_column: None,
+                global_ordinals: None,
                 column_block_accessor: Default::default(),
+                filters_matching_docs: Vec::new(),
+                str_dict_columns: Vec::new(),
             };
             aggs.push(res);
             Ok(())
@@ -123,7 +142,46 @@ impl AggregationWithAccessor {
                 limits,
                 missing_value_for_accessor: None,
                 str_dict_column: None,
+                global_ordinals: None,
                 column_block_accessor: Default::default(),
+                filters_matching_docs: Vec::new(),
+                str_dict_columns: Vec::new(),
+            };
+            aggs.push(res);
+            Ok(())
+        };
+
+        let add_agg_with_filters = |agg: &Aggregation,
+                                    filters_matching_docs: Vec<BitSet>,
+                                    aggs: &mut Vec<AggregationWithAccessor>|
+         -> crate::Result<()> {
+            let mut limits = limits.clone();
+            // A bitset uses 8 bytes per bucket of 64 documents.
+            let num_bytes: u64 = filters_matching_docs
+                .iter()
+                .map(|matching_docs| matching_docs.num_buckets() as u64 * 8)
+                .sum();
+            limits.add_memory_consumed(num_bytes)?;
+            let res = AggregationWithAccessor {
+                segment_ordinal,
+                accessor: Column::build_empty_column(reader.num_docs()),
+                value_accessors: Default::default(),
+                field_type: ColumnType::U64,
+                accessors: Default::default(),
+                sub_aggregation: get_aggs_with_segment_accessor_and_validate(
+                    sub_aggregation,
+                    reader,
+                    segment_ordinal,
+                    &limits,
+                )?,
+                agg: agg.clone(),
+                limits,
+                missing_value_for_accessor: None,
+                str_dict_column: None,
+                global_ordinals: None,
+                column_block_accessor: Default::default(),
+                filters_matching_docs,
+                str_dict_columns: Vec::new(),
             };
             aggs.push(res);
             Ok(())
@@ -158,6 +216,65 @@ impl AggregationWithAccessor {
                     get_ff_reader(reader, field_name, Some(&[ColumnType::DateTime]))?;
                 add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
             }
+            Filter(ref filter) => {
+                let filters_matching_docs = vec![filter.matching_docs(reader)?];
+                add_agg_with_filters(&agg, filters_matching_docs, &mut res)?;
+            }
+            Filters(ref filters) => {
+                let filters_matching_docs = filters
+                    .filters
+                    .values()
+                    .map(|filter| filter.matching_docs(reader))
+                    .collect::<crate::Result<Vec<_>>>()?;
+                add_agg_with_filters(&agg, filters_matching_docs, &mut res)?;
+            }
+            Composite(ref composite) => {
+                // The accessors and dictionaries are in the order of the sources.
+                let accessors = composite
+                    .sources
+                    .iter()
+                    .map(|source| {
+                        get_ff_reader(
+                            reader,
+                            source.source.field_name(),
+                            Some(source.source.allowed_column_types()),
+                        )
+                    })
+                    .collect::<crate::Result<Vec<_>>>()?;
+                let str_dict_columns = composite
+                    .sources
+                    .iter()
+                    .map(|source| reader.fast_fields().str(source.source.field_name()))
+                    .collect::<crate::Result<Vec<_>>>()?;
+                let (accessor, field_type) = accessors.first().cloned().unwrap_or_else(|| {
+                    (
+                        Column::build_empty_column(reader.num_docs()),
+                        ColumnType::U64,
+                    )
+                });
+                let limits = limits.clone();
+                res.push(AggregationWithAccessor {
+                    segment_ordinal,
+                    accessor,
+                    value_accessors: Default::default(),
+                    field_type,
+                    accessors,
+                    sub_aggregation: get_aggs_with_segment_accessor_and_validate(
+                        sub_aggregation,
+                        reader,
+                        segment_ordinal,
+                        &limits,
+                    )?,
+                    agg: agg.clone(),
+                    limits,
+                    missing_value_for_accessor: None,
+                    str_dict_column: None,
+                    global_ordinals: None,
+                    column_block_accessor: Default::default(),
+                    filters_matching_docs: Vec::new(),
+                    str_dict_columns,
+                });
+            }
             Terms(TermsAggregation {
                 field: ref field_name,
                 ref missing,
@@ -261,8 +378,11 @@ impl AggregationWithAccessor {
                         )?,
                         agg: agg.clone(),
                         str_dict_column: str_dict_column.clone(),
+                        global_ordinals: None,
                         limits,
                         column_block_accessor: Default::default(),
+                        filters_matching_docs: Vec::new(),
+                        str_dict_columns: Vec::new(),
                     };
                     res.push(agg);
                 }
@@ -321,6 +441,49 @@ impl AggregationWithAccessor {
                 )?;
                 add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
             }
+            GeoCentroid(GeoCentroidAggregation {
+                field: ref field_name,
+            })
+            | GeoBounds(GeoBoundsAggregation {
+                field: ref field_name,
+            }) => {
+                let allowed_column_types = [ColumnType::I64, ColumnType::U64, ColumnType::F64];
+                let accessors = ["lat", "lon"]
+                    .iter()
+                    .map(|coordinate| {
+                        get_ff_reader(
+                            reader,
+                            &format!("{field_name}.{coordinate}"),
+                            Some(&allowed_column_types),
+                        )
+                    })
+                    .collect::<crate::Result<Vec<_>>>()?;
+                add_agg_with_accessors(&agg, accessors, &mut res, Default::default())?;
+            }
+            Rate(RateAggregation { ref field, .. }) => {
+                let (accessor, column_type) = match field {
+                    Some(field_name) => {
+                        get_ff_reader(reader, field_name, Some(get_numeric_or_date_column_types()))?
+                    }
+                    // The documents are counted.
+                    None => (
+                        Column::build_empty_column(reader.num_docs()),
+                        ColumnType::U64,
+                    ),
+                };
+                add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
+            }
+            TopMetrics(ref top_metrics) => {
+                // The sort field comes first, followed by the metric fields.
+                let accessors = top_metrics
+                    .field_names()
+                    .into_iter()
+                    .map(|field_name| {
+                        get_ff_reader(reader, field_name, Some(get_numeric_or_date_column_types()))
+                    })
+                    .collect::<crate::Result<Vec<_>>>()?;
+                add_agg_with_accessors(&agg, accessors, &mut res, Default::default())?;
+            }
             TopHits(ref mut top_hits) => {
                 top_hits.validate_and_resolve_field_names(reader.fast_fields().columnar())?;
                 let accessors: Vec<(Column<u64>, ColumnType)> = top_hits
@@ -421,6 +584,38 @@ pub(crate) fn get_aggs_with_segment_accessor_and_validate(
     ))
 }
 
+/// Attaches the global ordinals of the searcher to the `terms` aggregations on `str` columns.
+///
+/// The global ordinals are only used if they were built for the segment of `reader`, i.e. if
+/// the collector runs on the segments of `searcher`.
+pub(crate) fn attach_global_ordinals(
+    aggs: &mut AggregationsWithAccessor,
+    searcher: &Searcher,
+    reader: &SegmentReader,
+) -> crate::Result<()> {
+    for agg_with_accessor in aggs.aggs.values_mut() {
+        if let AggregationVariants::Terms(TermsAggregation { field, .. }) =
+            &agg_with_accessor.agg.agg
+        {
+            // The aggregations with several `accessors` handle the `missing` value on mixed
+            // columns, and do not bucket on term ordinals.
+            if agg_with_accessor.field_type == ColumnType::Str
+                && agg_with_accessor.accessors.is_empty()
+            {
+                let global_ordinals = searcher.global_ordinals(field)?;
+                let segment_ord = agg_with_accessor.segment_ordinal;
+                if (segment_ord as usize) < global_ordinals.num_segments()
+                    && global_ordinals.segment_id(segment_ord) == reader.segment_id()
+                {
+                    agg_with_accessor.global_ordinals = Some(global_ordinals);
+                }
+            }
+        }
+        attach_global_ordinals(&mut agg_with_accessor.sub_aggregation, searcher, reader)?;
+    }
+    Ok(())
+}
+
 /// Get fast field reader or empty as default.
 fn get_ff_reader(
     reader: &SegmentReader,
@@ -8,9 +8,10 @@
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
-use super::bucket::GetDocCount;
+use super::bucket::{get_agg_name_and_property, GetDocCount};
 use super::metric::{
-    ExtendedStats, PercentilesMetricResult, SingleMetricResult, Stats, TopHitsMetricResult,
+    ExtendedStats, GeoBounds, GeoCentroid, PercentilesMetricResult, SingleMetricResult, Stats,
+    TopHitsMetricResult, TopMetricsResult,
 };
 use super::{AggregationError, Key};
 use crate::TantivyError;
@@ -67,6 +68,15 @@ impl AggregationResult {
         agg_property: &str,
     ) -> crate::Result<Option<f64>> {
         match self {
+            AggregationResult::BucketResult(BucketResult::Filter(bucket)) => {
+                if agg_property == "_count" {
+                    return Ok(Some(bucket.doc_count as f64));
+                }
+                let (agg_name, agg_property) = get_agg_name_and_property(agg_property);
+                bucket
+                    .sub_aggregation
+                    .get_value_from_aggregation(agg_name, agg_property)
+            }
             AggregationResult::BucketResult(_bucket) => Err(TantivyError::InternalError(
                 "Tried to retrieve value from bucket aggregation. This is not supported and \
                  should not happen during collection phase, but should be caught during validation"
@@ -99,8 +109,16 @@ pub enum MetricResult {
     Percentiles(PercentilesMetricResult),
     /// Top hits metric result
     TopHits(TopHitsMetricResult),
+    /// Top metrics metric result
+    TopMetrics(TopMetricsResult),
     /// Cardinality metric result
     Cardinality(SingleMetricResult),
+    /// Geo centroid metric result
+    GeoCentroid(GeoCentroid),
+    /// Geo bounds metric result
+    GeoBounds(GeoBounds),
+    /// Rate metric result
+    Rate(SingleMetricResult),
 }
 
 impl MetricResult {
@@ -119,7 +137,14 @@ impl MetricResult {
             MetricResult::TopHits(_) => Err(TantivyError::AggregationError(
                 AggregationError::InvalidRequest("top_hits can't be used to order".to_string()),
             )),
+            MetricResult::TopMetrics(top_metrics) => top_metrics.get_value(agg_property),
             MetricResult::Cardinality(card) => Ok(card.value),
+            MetricResult::Rate(rate) => Ok(rate.value),
+            MetricResult::GeoCentroid(_) | MetricResult::GeoBounds(_) => Err(
+                TantivyError::AggregationError(AggregationError::InvalidRequest(
+                    "geo aggregations can't be used to order".to_string(),
+                )),
+            ),
         }
     }
 }
@@ -156,6 +181,30 @@ pub enum BucketResult {
         /// The upper bound error for the doc count of each term.
         doc_count_error_upper_bound: Option<u64>,
     },
+    /// This is the filter result, a single bucket with a count, and optionally
+    /// sub-aggregations.
+    ///
+    /// See [`FilterAggregation`](super::bucket::FilterAggregation)
+    Filter(FilterBucketEntry),
+    /// This is the filters result
+    Filters {
+        /// The buckets, by the name of their filter.
+        ///
+        /// See [`FiltersAggregation`](super::bucket::FiltersAggregation)
+        buckets: FxHashMap<String, FilterBucketEntry>,
+    },
+    /// This is the composite result, a page of buckets
+    Composite {
+        /// The buckets, sorted by key.
+        ///
+        /// See [`CompositeAggregation`](super::bucket::CompositeAggregation)
+        buckets: Vec<CompositeBucketEntry>,
+        /// The key of the last bucket, to pass as `after` to get the next page.
+        ///
+        /// It is only set if the page is full, so there may be more buckets.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        after_key: Option<FxHashMap<String, Key>>,
+    },
 }
 
 impl BucketResult {
@@ -172,6 +221,15 @@ impl BucketResult {
                 sum_other_doc_count: _,
                 doc_count_error_upper_bound: _,
             } => buckets.iter().map(|bucket| bucket.get_bucket_count()).sum(),
+            BucketResult::Filter(bucket) => bucket.get_bucket_count(),
+            BucketResult::Filters { buckets } => buckets
+                .values()
+                .map(|bucket| bucket.get_bucket_count())
+                .sum(),
+            BucketResult::Composite {
+                buckets,
+                after_key: _,
+            } => buckets.iter().map(|bucket| bucket.get_bucket_count()).sum(),
         }
     }
 }
@@ -226,6 +284,9 @@ impl<T> BucketEntries<T> {
 pub struct BucketEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// The string representation of the bucket.
+    ///
+    /// Set for date keys (RFC 3339), bool keys (`true`/`false`) and IP address keys (dotted
+    /// notation for IPv4, colon notation for IPv6).
     pub key_as_string: Option<String>,
     /// The identifier of the bucket.
     pub key: Key,
@@ -308,3 +369,66 @@ impl RangeBucketEntry {
         1 + self.sub_aggregation.get_bucket_count()
     }
 }
+
+/// This is the entry of a bucket of the `filter` and `filters` aggregations, which contains a
+/// count, and optionally sub-aggregations.
+///
+/// # JSON Format
+/// ```json
+/// {
+///   ...
+///     "failed_requests": {
+///       "doc_count": 12,
+///       "avg_response_time": {
+///         "value": 154.5
+///       }
+///     }
+///    ...
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FilterBucketEntry {
+    /// Number of documents in the bucket.
+    pub doc_count: u64,
+    #[serde(flatten)]
+    /// Sub-aggregations in this bucket.
+    pub sub_aggregation: AggregationResults,
+}
+impl FilterBucketEntry {
+    pub(crate) fn get_bucket_count(&self) -> u64 {
+        1 + self.sub_aggregation.get_bucket_count()
+    }
+}
+
+/// This is the entry of a bucket of the `composite` aggregation, which contains a composite key,
+/// a count, and optionally sub-aggregations.
+///
+/// # JSON Format
+/// ```json
+/// {
+///   ...
+///     "buckets": [
+///       {
+///         "key": { "brand": "acme", "price": 20.0 },
+///         "doc_count": 12
+///       }
+///     ],
+///     "after_key": { "brand": "acme", "price": 20.0 }
+///    ...
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompositeBucketEntry {
+    /// The values of the key, by the name of their source.
+    pub key: FxHashMap<String, Key>,
+    /// Number of documents in the bucket.
+    pub doc_count: u64,
+    #[serde(flatten)]
+    /// Sub-aggregations in this bucket.
+    pub sub_aggregation: AggregationResults,
+}
+impl CompositeBucketEntry {
+    pub(crate) fn get_bucket_count(&self) -> u64 {
+        1 + self.sub_aggregation.get_bucket_count()
+    }
+}
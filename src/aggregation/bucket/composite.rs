@@ -0,0 +1,957 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use columnar::{Column, ColumnType, MonotonicallyMappableToU64, StrColumn};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use super::{parse_into_milliseconds, Order};
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
+    IntermediateCompositeBucketEntry, IntermediateKey,
+};
+use crate::aggregation::segment_agg_result::{
+    build_segment_agg_collector, SegmentAggregationCollector,
+};
+use crate::aggregation::{f64_from_fastfield_u64, AggregationError, Key};
+use crate::TantivyError;
+
+/// Pages through all the buckets of the combinations of the values of one or more sources.
+///
+/// Each bucket has a composite key, with one value per source. The buckets are sorted by their
+/// keys, and at most `size` buckets are returned per request. The result contains an
+/// `after_key`, which can be passed as `after` to the next request to get the next page of
+/// buckets. That way all the buckets can be iterated, without ever holding more than a page in
+/// memory.
+///
+/// The sources are:
+/// - `terms`: the values of a `str`, `u64`, `i64`, `f64` or `bool` fast field.
+/// - `histogram`: the buckets of width `interval` of a numeric fast field, keyed by the lower
+///   bound of the bucket.
+/// - `date_histogram`: the buckets of a `fixed_interval` of a date fast field, keyed by the
+///   start of the bucket in milliseconds since the epoch.
+///
+/// Each source is sorted in ascending order by default, which can be changed with
+/// `"order": "desc"`. Documents without a value for one of the sources are ignored. A document
+/// with multiple values is counted in the buckets of all the combinations of its values.
+///
+/// Result type is [`BucketResult`](crate::aggregation::agg_result::BucketResult) with
+/// [`CompositeBucketEntry`](crate::aggregation::agg_result::CompositeBucketEntry) on the
+/// `AggregationCollector`.
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "products": {
+///         "composite": {
+///             "size": 100,
+///             "sources": [
+///                 { "brand": { "terms": { "field": "brand" } } },
+///                 { "price": { "histogram": { "field": "price", "interval": 10.0 } } }
+///             ],
+///             "after": { "brand": "acme", "price": 20.0 }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompositeAggregation {
+    /// The sources of the values of the composite keys, in the order in which the keys are
+    /// sorted.
+    pub sources: Vec<CompositeAggregationSource>,
+    /// The maximum number of buckets returned. Defaults to 10.
+    #[serde(default = "default_size")]
+    pub size: u32,
+    /// Only the buckets whose key comes after this key are returned. This is the `after_key`
+    /// of the previous page.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub after: Option<FxHashMap<String, Key>>,
+}
+
+fn default_size() -> u32 {
+    10
+}
+
+impl CompositeAggregation {
+    /// Returns the fields of the sources.
+    pub(crate) fn field_names(&self) -> Vec<&str> {
+        self.sources
+            .iter()
+            .map(|source| source.source.field_name())
+            .collect()
+    }
+}
+
+/// A named source of the values of the composite keys.
+///
+/// De/Serializes to a map with a single entry, from the name to the source.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(
+    try_from = "HashMap<String, CompositeSource>",
+    into = "HashMap<String, CompositeSource>"
+)]
+pub struct CompositeAggregationSource {
+    /// The name of the source, which is the key of its value in the composite keys.
+    pub name: String,
+    /// The source of the values.
+    pub source: CompositeSource,
+}
+
+impl TryFrom<HashMap<String, CompositeSource>> for CompositeAggregationSource {
+    type Error = String;
+
+    fn try_from(map: HashMap<String, CompositeSource>) -> Result<Self, Self::Error> {
+        if map.len() != 1 {
+            return Err(format!(
+                "expected exactly one named source, but got {}",
+                map.len()
+            ));
+        }
+        let (name, source) = map.into_iter().next().unwrap();
+        Ok(CompositeAggregationSource { name, source })
+    }
+}
+
+impl From<CompositeAggregationSource> for HashMap<String, CompositeSource> {
+    fn from(source: CompositeAggregationSource) -> Self {
+        std::iter::once((source.name, source.source)).collect()
+    }
+}
+
+/// The source of the values of a composite key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CompositeSource {
+    /// The values of a field.
+    #[serde(rename = "terms")]
+    Terms {
+        /// The field to read the values from.
+        field: String,
+        /// The order of the values.
+        #[serde(default = "default_order")]
+        order: Order,
+    },
+    /// The buckets of a fixed width of a numeric field.
+    #[serde(rename = "histogram")]
+    Histogram {
+        /// The field to read the values from.
+        field: String,
+        /// The width of the buckets.
+        interval: f64,
+        /// The order of the buckets.
+        #[serde(default = "default_order")]
+        order: Order,
+    },
+    /// The buckets of a fixed interval of a date field.
+    #[serde(rename = "date_histogram")]
+    DateHistogram {
+        /// The field to read the values from.
+        field: String,
+        /// The interval of the buckets, e.g. `30d`. See
+        /// [`DateHistogramAggregationReq`](super::DateHistogramAggregationReq) for the units.
+        fixed_interval: String,
+        /// The order of the buckets.
+        #[serde(default = "default_order")]
+        order: Order,
+    },
+}
+
+fn default_order() -> Order {
+    Order::Asc
+}
+
+impl CompositeSource {
+    /// Returns the field of the source.
+    pub fn field_name(&self) -> &str {
+        match self {
+            CompositeSource::Terms { field, .. }
+            | CompositeSource::Histogram { field, .. }
+            | CompositeSource::DateHistogram { field, .. } => field,
+        }
+    }
+
+    /// Returns the order of the values of the source.
+    pub fn order(&self) -> Order {
+        match self {
+            CompositeSource::Terms { order, .. }
+            | CompositeSource::Histogram { order, .. }
+            | CompositeSource::DateHistogram { order, .. } => *order,
+        }
+    }
+
+    /// Returns the column types the source can read.
+    pub(crate) fn allowed_column_types(&self) -> &'static [ColumnType] {
+        match self {
+            CompositeSource::Terms { .. } => &[
+                ColumnType::Str,
+                ColumnType::U64,
+                ColumnType::I64,
+                ColumnType::F64,
+                ColumnType::Bool,
+            ],
+            CompositeSource::Histogram { .. } => {
+                &[ColumnType::U64, ColumnType::I64, ColumnType::F64]
+            }
+            CompositeSource::DateHistogram { .. } => &[ColumnType::DateTime],
+        }
+    }
+}
+
+/// Compares two values of a composite key.
+///
+/// Numbers are compared by value, and sort before strings.
+fn cmp_key_values(left: &IntermediateKey, right: &IntermediateKey) -> Ordering {
+    fn as_f64(key: &IntermediateKey) -> Option<f64> {
+        match key {
+            IntermediateKey::Bool(val) => Some(*val as u64 as f64),
+            IntermediateKey::F64(val) => Some(*val),
+            IntermediateKey::I64(val) => Some(*val as f64),
+            IntermediateKey::U64(val) => Some(*val as f64),
+            IntermediateKey::Str(_) | IntermediateKey::IpAddr(_) => None,
+        }
+    }
+    match (left, right) {
+        (IntermediateKey::Str(left), IntermediateKey::Str(right)) => left.cmp(right),
+        (IntermediateKey::I64(left), IntermediateKey::I64(right)) => left.cmp(right),
+        (IntermediateKey::U64(left), IntermediateKey::U64(right)) => left.cmp(right),
+        _ => match (as_f64(left), as_f64(right)) {
+            (Some(left), Some(right)) => left.total_cmp(&right),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => left.partial_cmp(right).unwrap_or(Ordering::Equal),
+        },
+    }
+}
+
+/// Compares two composite keys, with the given order for each of their values.
+pub(crate) fn cmp_composite_keys(
+    left: &[IntermediateKey],
+    right: &[IntermediateKey],
+    orders: impl Iterator<Item = Order>,
+) -> Ordering {
+    for ((left, right), order) in left.iter().zip(right).zip(orders) {
+        let ordering = cmp_key_values(left, right);
+        let ordering = if order == Order::Desc {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Compares two composite keys in ascending order, which is the order of the buckets of the
+/// intermediate results.
+pub(crate) fn cmp_composite_keys_asc(
+    left: &[IntermediateKey],
+    right: &[IntermediateKey],
+) -> Ordering {
+    cmp_composite_keys(left, right, std::iter::repeat(Order::Asc))
+}
+
+// The bounds of the `after` key are compared to the values of the documents in a doubled space:
+// a value `v` is `2v + 1`, and a bound between `v - 1` and `v` is `2v`.
+const BEFORE_ALL: u128 = 0;
+const AFTER_ALL: u128 = 2 * u64::MAX as u128 + 2;
+
+#[derive(Clone, Debug)]
+enum SegmentCompositeSourceKind {
+    Terms,
+    Histogram { interval: f64 },
+    DateHistogram { interval_ms: i64, interval_ns: i64 },
+}
+
+#[derive(Clone, Debug)]
+struct SegmentCompositeSource {
+    kind: SegmentCompositeSourceKind,
+    column_type: ColumnType,
+    order: Order,
+}
+
+impl SegmentCompositeSource {
+    /// Maps a value of the column to the value of the key, in the sort order of the source.
+    #[inline]
+    fn to_sort_value(&self, val: u64) -> u64 {
+        let val = match self.kind {
+            // The values of the column are monotonically mapped, and the term ordinals of a
+            // `str` column match the order of the strings.
+            SegmentCompositeSourceKind::Terms => val,
+            SegmentCompositeSourceKind::Histogram { interval } => {
+                let val = f64_from_fastfield_u64(val, &self.column_type);
+                ((val / interval).floor() * interval).to_u64()
+            }
+            SegmentCompositeSourceKind::DateHistogram {
+                interval_ms,
+                interval_ns,
+            } => (i64::from_u64(val).div_euclid(interval_ns) * interval_ms).to_u64(),
+        };
+        self.flip(val)
+    }
+
+    #[inline]
+    fn flip(&self, val: u64) -> u64 {
+        if self.order == Order::Desc {
+            u64::MAX - val
+        } else {
+            val
+        }
+    }
+
+    fn to_intermediate_key(
+        &self,
+        sort_value: u64,
+        str_dict_column: Option<&StrColumn>,
+    ) -> crate::Result<IntermediateKey> {
+        let val = self.flip(sort_value);
+        let key = match self.kind {
+            SegmentCompositeSourceKind::Terms => match self.column_type {
+                ColumnType::Str => {
+                    let str_dict_column = str_dict_column.expect("str column has a dictionary");
+                    let mut term = String::new();
+                    str_dict_column.ord_to_str(val, &mut term)?;
+                    IntermediateKey::Str(term)
+                }
+                ColumnType::Bool => IntermediateKey::Bool(bool::from_u64(val)),
+                ColumnType::I64 => IntermediateKey::I64(i64::from_u64(val)),
+                ColumnType::F64 => IntermediateKey::F64(f64::from_u64(val)),
+                _ => IntermediateKey::U64(val),
+            },
+            SegmentCompositeSourceKind::Histogram { .. } => {
+                IntermediateKey::F64(f64::from_u64(val))
+            }
+            SegmentCompositeSourceKind::DateHistogram { .. } => {
+                IntermediateKey::I64(i64::from_u64(val))
+            }
+        };
+        Ok(key)
+    }
+
+    /// Returns the bound of the `after` value in the doubled space, in the sort order of the
+    /// source.
+    fn after_bound(&self, after: &Key, str_dict_column: Option<&StrColumn>) -> crate::Result<u128> {
+        let bound = match (&self.kind, after) {
+            (SegmentCompositeSourceKind::Terms, Key::Str(after)) => match str_dict_column {
+                Some(str_dict_column) if self.column_type == ColumnType::Str => {
+                    let mut stream = str_dict_column
+                        .dictionary()
+                        .range()
+                        .ge(after)
+                        .into_stream()?;
+                    if stream.advance() {
+                        let term_ord = stream.term_ord() as u128;
+                        if stream.key() == after.as_bytes() {
+                            2 * term_ord + 1
+                        } else {
+                            2 * term_ord
+                        }
+                    } else {
+                        2 * str_dict_column.num_terms() as u128
+                    }
+                }
+                // Strings sort after numbers.
+                _ => AFTER_ALL,
+            },
+            (SegmentCompositeSourceKind::Terms, after) => {
+                if self.column_type == ColumnType::Str {
+                    BEFORE_ALL
+                } else {
+                    numeric_after_bound(self.column_type, after)
+                }
+            }
+            (_, Key::Str(_)) => AFTER_ALL,
+            (SegmentCompositeSourceKind::Histogram { .. }, after) => {
+                2 * key_to_f64(after).to_u64() as u128 + 1
+            }
+            (SegmentCompositeSourceKind::DateHistogram { .. }, after) => {
+                let after_ms = match *after {
+                    Key::I64(val) => val,
+                    Key::U64(val) => i64::try_from(val).unwrap_or(i64::MAX),
+                    // The keys of the buckets are whole milliseconds.
+                    Key::F64(val) => val.floor() as i64,
+                    Key::Str(_) => unreachable!(),
+                };
+                2 * after_ms.to_u64() as u128 + 1
+            }
+        };
+        if self.order == Order::Desc {
+            Ok(AFTER_ALL - bound)
+        } else {
+            Ok(bound)
+        }
+    }
+}
+
+fn key_to_f64(key: &Key) -> f64 {
+    match *key {
+        Key::I64(val) => val as f64,
+        Key::U64(val) => val as f64,
+        Key::F64(val) => val,
+        Key::Str(_) => f64::NAN,
+    }
+}
+
+/// Returns the bound of a numeric `after` value for a numeric column in the doubled space.
+fn numeric_after_bound(column_type: ColumnType, after: &Key) -> u128 {
+    let exact_or_next = |val: u64, is_exact: bool| 2 * val as u128 + is_exact as u128;
+    match (column_type, after) {
+        (ColumnType::F64, after) => exact_or_next(key_to_f64(after).to_u64(), true),
+        (ColumnType::I64, Key::I64(val)) => exact_or_next(val.to_u64(), true),
+        (ColumnType::I64, Key::U64(val)) => match i64::try_from(*val) {
+            Ok(val) => exact_or_next(val.to_u64(), true),
+            Err(_) => AFTER_ALL,
+        },
+        (ColumnType::I64, after) => {
+            let val = key_to_f64(after);
+            if val < i64::MIN as f64 {
+                BEFORE_ALL
+            } else if val >= i64::MAX as f64 {
+                AFTER_ALL
+            } else {
+                let next = val.ceil();
+                exact_or_next((next as i64).to_u64(), next == val)
+            }
+        }
+        (_, Key::U64(val)) => exact_or_next(*val, true),
+        (_, Key::I64(val)) => match u64::try_from(*val) {
+            Ok(val) => exact_or_next(val, true),
+            Err(_) => BEFORE_ALL,
+        },
+        (_, after) => {
+            let val = key_to_f64(after);
+            if val < 0.0 {
+                BEFORE_ALL
+            } else if val >= u64::MAX as f64 {
+                AFTER_ALL
+            } else {
+                let next = val.ceil();
+                exact_or_next(next as u64, next == val)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SegmentCompositeBucketEntry {
+    doc_count: u64,
+    sub_aggregation: Option<Box<dyn SegmentAggregationCollector>>,
+}
+
+/// The collector of the `composite` aggregation.
+///
+/// The keys of the buckets are the values of the sources, mapped to `u64` in the sort order of
+/// each source. Only the `size` first buckets after the `after` key are kept, the other buckets
+/// are evicted as soon as the segment has collected `size` buckets with smaller keys.
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentCompositeCollector {
+    sources: Vec<SegmentCompositeSource>,
+    size: usize,
+    after_bounds: Option<Vec<u128>>,
+    buckets: BTreeMap<Vec<u64>, SegmentCompositeBucketEntry>,
+    blueprint: Option<Box<dyn SegmentAggregationCollector>>,
+    accessor_idx: usize,
+    // The sort values of the current document, per source.
+    values: Vec<Vec<u64>>,
+    key: Vec<u64>,
+}
+
+fn invalid_request(message: String) -> TantivyError {
+    TantivyError::AggregationError(AggregationError::InvalidRequest(message))
+}
+
+impl SegmentCompositeCollector {
+    pub(crate) fn from_req_and_validate(
+        req: &CompositeAggregation,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        accessors: &[(Column<u64>, ColumnType)],
+        str_dict_columns: &[Option<StrColumn>],
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        if req.sources.is_empty() {
+            return Err(invalid_request(
+                "composite aggregation requires at least one source".to_string(),
+            ));
+        }
+        if req.size == 0 {
+            return Err(invalid_request(
+                "size of the composite aggregation must be greater than 0".to_string(),
+            ));
+        }
+        let sources = req
+            .sources
+            .iter()
+            .zip(accessors)
+            .map(|(source, (_, column_type))| {
+                let kind = match &source.source {
+                    CompositeSource::Terms { .. } => SegmentCompositeSourceKind::Terms,
+                    CompositeSource::Histogram { interval, .. } => {
+                        if *interval <= 0.0 {
+                            return Err(invalid_request(format!(
+                                "interval of source {} must be greater than 0",
+                                source.name
+                            )));
+                        }
+                        SegmentCompositeSourceKind::Histogram {
+                            interval: *interval,
+                        }
+                    }
+                    CompositeSource::DateHistogram { fixed_interval, .. } => {
+                        let interval_ms = parse_into_milliseconds(fixed_interval)?;
+                        if interval_ms <= 0 {
+                            return Err(invalid_request(format!(
+                                "fixed_interval of source {} must be greater than 0",
+                                source.name
+                            )));
+                        }
+                        SegmentCompositeSourceKind::DateHistogram {
+                            interval_ms,
+                            // `parse_into_milliseconds` rejects the intervals overflowing
+                            // nanoseconds.
+                            interval_ns: interval_ms * 1_000_000,
+                        }
+                    }
+                };
+                Ok(SegmentCompositeSource {
+                    kind,
+                    column_type: *column_type,
+                    order: source.source.order(),
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let after_bounds = req
+            .after
+            .as_ref()
+            .map(|after| {
+                req.sources
+                    .iter()
+                    .zip(&sources)
+                    .zip(str_dict_columns)
+                    .map(|((source_req, source), str_dict_column)| {
+                        let after_val = after.get(&source_req.name).ok_or_else(|| {
+                            invalid_request(format!(
+                                "after key of the composite aggregation is missing source {}",
+                                source_req.name
+                            ))
+                        })?;
+                        source.after_bound(after_val, str_dict_column.as_ref())
+                    })
+                    .collect::<crate::Result<Vec<_>>>()
+            })
+            .transpose()?;
+
+        let blueprint = if sub_aggregation.is_empty() {
+            None
+        } else {
+            Some(build_segment_agg_collector(sub_aggregation)?)
+        };
+
+        Ok(SegmentCompositeCollector {
+            values: vec![Vec::new(); sources.len()],
+            key: Vec::with_capacity(sources.len()),
+            sources,
+            size: req.size as usize,
+            after_bounds,
+            buckets: BTreeMap::new(),
+            blueprint,
+            accessor_idx,
+        })
+    }
+
+    /// Returns true if the key comes after the `after` key.
+    fn is_after(&self, key: &[u64]) -> bool {
+        let Some(after_bounds) = self.after_bounds.as_ref() else {
+            return true;
+        };
+        for (&val, &bound) in key.iter().zip(after_bounds) {
+            match (2 * val as u128 + 1).cmp(&bound) {
+                Ordering::Greater => return true,
+                Ordering::Less => return false,
+                Ordering::Equal => {}
+            }
+        }
+        false
+    }
+
+    fn collect_key(
+        &mut self,
+        doc: crate::DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        if !self.is_after(&self.key) {
+            return Ok(());
+        }
+        let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+        if let Some(bucket) = self.buckets.get_mut(&self.key) {
+            bucket.doc_count += 1;
+            if let Some(sub_aggregation) = &mut bucket.sub_aggregation {
+                sub_aggregation.collect(doc, &mut bucket_agg_accessor.sub_aggregation)?;
+            }
+            return Ok(());
+        }
+        if self.buckets.len() >= self.size {
+            let is_smaller_than_last = self
+                .buckets
+                .last_key_value()
+                .map(|(last_key, _)| self.key < *last_key)
+                .unwrap_or(true);
+            if !is_smaller_than_last {
+                return Ok(());
+            }
+            self.buckets.pop_last();
+        } else {
+            bucket_agg_accessor.limits.add_memory_consumed(
+                (std::mem::size_of::<SegmentCompositeBucketEntry>()
+                    + self.key.len() * std::mem::size_of::<u64>()) as u64,
+            )?;
+            bucket_agg_accessor.limits.add_buckets_collected(1)?;
+        }
+        let mut sub_aggregation = self.blueprint.clone();
+        if let Some(sub_aggregation) = &mut sub_aggregation {
+            sub_aggregation.collect(doc, &mut bucket_agg_accessor.sub_aggregation)?;
+        }
+        self.buckets.insert(
+            self.key.clone(),
+            SegmentCompositeBucketEntry {
+                doc_count: 1,
+                sub_aggregation,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl SegmentAggregationCollector for SegmentCompositeCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let bucket_agg_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+        let mut buckets = self
+            .buckets
+            .into_iter()
+            .map(|(key, bucket)| {
+                let key = key
+                    .into_iter()
+                    .zip(&self.sources)
+                    .zip(&bucket_agg_accessor.str_dict_columns)
+                    .map(|((val, source), str_dict_column)| {
+                        source.to_intermediate_key(val, str_dict_column.as_ref())
+                    })
+                    .collect::<crate::Result<Vec<_>>>()?;
+                let mut sub_aggregation_res = IntermediateAggregationResults::default();
+                if let Some(sub_aggregation) = bucket.sub_aggregation {
+                    sub_aggregation.add_intermediate_aggregation_result(
+                        &bucket_agg_accessor.sub_aggregation,
+                        &mut sub_aggregation_res,
+                    )?;
+                }
+                Ok(IntermediateCompositeBucketEntry {
+                    key,
+                    doc_count: bucket.doc_count,
+                    sub_aggregation: sub_aggregation_res,
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        buckets.sort_by(|left, right| cmp_composite_keys_asc(&left.key, &right.key));
+
+        let bucket = IntermediateBucketResult::Composite { buckets };
+        results.push(name, IntermediateAggregationResult::Bucket(bucket))?;
+        Ok(())
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: crate::DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let bucket_agg_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+        for ((source, (accessor, _)), values) in self
+            .sources
+            .iter()
+            .zip(&bucket_agg_accessor.accessors)
+            .zip(self.values.iter_mut())
+        {
+            values.clear();
+            values.extend(
+                accessor
+                    .values_for_doc(doc)
+                    .map(|val| source.to_sort_value(val)),
+            );
+            if values.is_empty() {
+                return Ok(());
+            }
+            // A document is counted once per bucket.
+            values.sort_unstable();
+            values.dedup();
+        }
+
+        // Visit all the combinations of the values of the sources.
+        let mut value_idxs = vec![0; self.values.len()];
+        loop {
+            self.key.clear();
+            self.key.extend(
+                value_idxs
+                    .iter()
+                    .zip(&self.values)
+                    .map(|(&value_idx, values)| values[value_idx]),
+            );
+            self.collect_key(doc, agg_with_accessor)?;
+
+            let mut source_idx = self.values.len();
+            loop {
+                if source_idx == 0 {
+                    return Ok(());
+                }
+                source_idx -= 1;
+                value_idxs[source_idx] += 1;
+                if value_idxs[source_idx] < self.values[source_idx].len() {
+                    break;
+                }
+                value_idxs[source_idx] = 0;
+            }
+        }
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[crate::DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        for &doc in docs {
+            self.collect(doc, agg_with_accessor)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, agg_with_accessor: &mut AggregationsWithAccessor) -> crate::Result<()> {
+        let sub_aggregation_accessor =
+            &mut agg_with_accessor.aggs.values[self.accessor_idx].sub_aggregation;
+        for bucket in self.buckets.values_mut() {
+            if let Some(sub_aggregation) = bucket.sub_aggregation.as_mut() {
+                sub_aggregation.flush(sub_aggregation_accessor)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::{exec_request, get_test_index_2_segments};
+    use crate::aggregation::AggregationError;
+    use crate::TantivyError;
+
+    #[test]
+    fn composite_aggregation_paging_test() -> crate::Result<()> {
+        composite_aggregation_paging_test_with_merge_segments(false)?;
+        composite_aggregation_paging_test_with_merge_segments(true)
+    }
+
+    fn composite_aggregation_paging_test_with_merge_segments(
+        merge_segments: bool,
+    ) -> crate::Result<()> {
+        let index = get_test_index_2_segments(merge_segments)?;
+        let mut after = Value::Null;
+        let mut pages = Vec::new();
+        loop {
+            let mut composite = json!({
+                "size": 2,
+                "sources": [
+                    { "text": { "terms": { "field": "text" } } },
+                    { "score": { "histogram": { "field": "score", "interval": 10.0 } } }
+                ]
+            });
+            if !after.is_null() {
+                composite["after"] = after;
+            }
+            let agg_req: Aggregations =
+                serde_json::from_value(json!({ "pages": { "composite": composite } })).unwrap();
+            let res = exec_request(agg_req, &index)?;
+            let page: Vec<(Value, Value)> = res["pages"]["buckets"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|bucket| (bucket["key"].clone(), bucket["doc_count"].clone()))
+                .collect();
+            pages.push(page);
+            after = res["pages"]["after_key"].clone();
+            if after.is_null() {
+                break;
+            }
+        }
+        assert_eq!(
+            pages,
+            vec![
+                vec![
+                    (json!({ "text": "cool", "score": 0.0 }), json!(4)),
+                    (json!({ "text": "cool", "score": 10.0 }), json!(2)),
+                ],
+                vec![
+                    (json!({ "text": "cool", "score": 40.0 }), json!(1)),
+                    (json!({ "text": "nohit", "score": 0.0 }), json!(1)),
+                ],
+                vec![(json!({ "text": "nohit", "score": 40.0 }), json!(1))],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn composite_aggregation_date_histogram_desc_test() -> crate::Result<()> {
+        let index = get_test_index_2_segments(false)?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "by_day": {
+                "composite": {
+                    "sources": [
+                        { "day": { "date_histogram": {
+                            "field": "date", "fixed_interval": "1d", "order": "desc"
+                        } } }
+                    ]
+                },
+                "aggs": {
+                    "score_avg": { "avg": { "field": "score" } }
+                }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req, &index)?;
+        let buckets = &res["by_day"]["buckets"];
+        assert_eq!(buckets[0]["key"]["day"], 1_546_473_600_000i64);
+        assert_eq!(buckets[0]["doc_count"], 3);
+        assert_eq!(buckets[1]["key"]["day"], 1_546_387_200_000i64);
+        assert_eq!(buckets[1]["doc_count"], 5);
+        assert_eq!(buckets[2]["key"]["day"], 1_546_300_800_000i64);
+        assert_eq!(buckets[2]["doc_count"], 1);
+        assert_eq!(buckets[2]["score_avg"]["value"], 1.0);
+        assert_eq!(buckets.as_array().unwrap().len(), 3);
+        // The page is not full, so there are no more buckets.
+        assert_eq!(res["by_day"]["after_key"], Value::Null);
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "by_day": {
+                "composite": {
+                    "sources": [
+                        { "day": { "date_histogram": {
+                            "field": "date", "fixed_interval": "1d", "order": "desc"
+                        } } }
+                    ],
+                    "after": { "day": 1_546_387_200_000i64 }
+                }
+            }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        assert_eq!(
+            res["by_day"]["buckets"],
+            json!([{ "key": { "day": 1_546_300_800_000i64 }, "doc_count": 1 }])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn composite_aggregation_multi_value_test() -> crate::Result<()> {
+        let index = get_test_index_2_segments(true)?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "scores": {
+                "composite": {
+                    "sources": [
+                        { "scores": { "terms": { "field": "scores_i64" } } }
+                    ]
+                }
+            },
+            "scores_after": {
+                "composite": {
+                    "sources": [
+                        { "scores": { "terms": { "field": "scores_i64" } } }
+                    ],
+                    "after": { "scores": 1.5 }
+                }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req, &index)?;
+        // A document is counted once per bucket, even if it has the same value twice.
+        assert_eq!(
+            res["scores"]["buckets"],
+            json!([
+                { "key": { "scores": 1 }, "doc_count": 1 },
+                { "key": { "scores": 2 }, "doc_count": 1 },
+                { "key": { "scores": 5 }, "doc_count": 1 },
+            ])
+        );
+        assert_eq!(
+            res["scores_after"]["buckets"],
+            json!([
+                { "key": { "scores": 2 }, "doc_count": 1 },
+                { "key": { "scores": 5 }, "doc_count": 1 },
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn composite_aggregation_invalid_request_test() -> crate::Result<()> {
+        let index = get_test_index_2_segments(false)?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "pages": {
+                "composite": {
+                    "sources": [
+                        { "text": { "terms": { "field": "text" } } },
+                        { "score": { "terms": { "field": "score" } } }
+                    ],
+                    "after": { "text": "cool" }
+                }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert!(err.to_string().contains("missing source score"), "{err}");
+        assert!(
+            matches!(
+                err,
+                TantivyError::AggregationError(AggregationError::InvalidRequest(_))
+            ),
+            "{err}"
+        );
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "by_day": {
+                "composite": {
+                    "sources": [
+                        { "day": { "date_histogram": {
+                            "field": "date", "fixed_interval": "100000000000000d"
+                        } } }
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert!(err.to_string().contains("OutOfBounds"), "{err}");
+
+        let agg_req: serde_json::Result<Aggregations> = serde_json::from_value(json!({
+            "pages": {
+                "composite": {
+                    "sources": [
+                        { "text": { "terms": { "field": "text" } },
+                          "score": { "terms": { "field": "score" } } }
+                    ]
+                }
+            }
+        }));
+        assert!(agg_req.is_err());
+        Ok(())
+    }
+}
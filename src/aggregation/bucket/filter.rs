@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+
+use common::BitSet;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::agg_req::AggregationVariants;
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
+    IntermediateFilterBucketEntry,
+};
+use crate::aggregation::segment_agg_result::{
+    build_segment_agg_collector, AggregationLimitsGuard, SegmentAggregationCollector,
+};
+use crate::query::{EnableScoring, QueryParser};
+use crate::tokenizer::TokenizerManager;
+use crate::{DocId, SegmentReader};
+
+/// Creates a single bucket with the documents matching a query.
+///
+/// This is typically used to compute metrics on a subset of the documents, e.g. the average
+/// response time of the requests that failed.
+///
+/// The query uses the syntax of the [`QueryParser`]. There are no default fields, so the field
+/// of each term has to be given, e.g. `status:500`. The text is tokenized with the default
+/// tokenizers.
+///
+/// Result type is [`BucketResult`](crate::aggregation::agg_result::BucketResult) with
+/// [`FilterBucketEntry`](crate::aggregation::agg_result::FilterBucketEntry) on the
+/// `AggregationCollector`.
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "failed_requests": {
+///         "filter": { "query": "status:[500 TO 599]" },
+///         "aggs": {
+///             "avg_response_time": { "avg": { "field": "response_time" } }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FilterAggregation {
+    /// The query matching the documents of the bucket.
+    pub query: String,
+}
+
+impl FilterAggregation {
+    /// Returns the documents of the segment matching the query.
+    pub(crate) fn matching_docs(&self, reader: &SegmentReader) -> crate::Result<BitSet> {
+        let schema = reader.schema();
+        let query_parser =
+            QueryParser::new(schema.clone(), Vec::new(), TokenizerManager::default());
+        let query = query_parser.parse_query(&self.query)?;
+        let weight = query.weight(EnableScoring::disabled_from_schema(schema))?;
+        let mut matching_docs = BitSet::with_max_value(reader.max_doc());
+        weight.for_each_no_score(reader, &mut |docs| {
+            for &doc in docs {
+                matching_docs.insert(doc);
+            }
+        })?;
+        Ok(matching_docs)
+    }
+}
+
+/// Creates a bucket for each of several named queries, with the documents matching the query.
+///
+/// The buckets may overlap, a document is counted in each of the buckets whose query it
+/// matches. See [`FilterAggregation`] for the syntax of the queries.
+///
+/// Result type is [`BucketResult`](crate::aggregation::agg_result::BucketResult) with
+/// [`FilterBucketEntry`](crate::aggregation::agg_result::FilterBucketEntry) on the
+/// `AggregationCollector`.
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "requests_by_status": {
+///         "filters": {
+///             "filters": {
+///                 "client_errors": { "query": "status:[400 TO 499]" },
+///                 "server_errors": { "query": "status:[500 TO 599]" }
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FiltersAggregation {
+    /// The filters of the buckets, by bucket name.
+    pub filters: BTreeMap<String, FilterAggregation>,
+}
+
+#[derive(Clone, Debug)]
+struct SegmentFilterBucketEntry {
+    doc_count: u64,
+    sub_aggregation: Option<Box<dyn SegmentAggregationCollector>>,
+}
+
+impl SegmentFilterBucketEntry {
+    fn into_intermediate_bucket_entry(
+        self,
+        agg_with_accessor: &AggregationsWithAccessor,
+    ) -> crate::Result<IntermediateFilterBucketEntry> {
+        let mut sub_aggregation_res = IntermediateAggregationResults::default();
+        if let Some(sub_aggregation) = self.sub_aggregation {
+            sub_aggregation
+                .add_intermediate_aggregation_result(agg_with_accessor, &mut sub_aggregation_res)?;
+        }
+        Ok(IntermediateFilterBucketEntry {
+            doc_count: self.doc_count,
+            sub_aggregation: sub_aggregation_res,
+        })
+    }
+}
+
+/// The collector of the `filter` and `filters` aggregations.
+///
+/// The documents matching the queries are computed for the whole segment beforehand, see
+/// `AggregationWithAccessor::filters_matching_docs`. The buckets are in the same order.
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentFilterCollector {
+    buckets: Vec<SegmentFilterBucketEntry>,
+    docs_buffer: Vec<DocId>,
+    accessor_idx: usize,
+}
+
+impl SegmentFilterCollector {
+    pub(crate) fn from_req_and_validate(
+        num_filters: usize,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        limits: &mut AggregationLimitsGuard,
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        let buckets = (0..num_filters)
+            .map(|_| {
+                let sub_aggregation = if sub_aggregation.is_empty() {
+                    None
+                } else {
+                    Some(build_segment_agg_collector(sub_aggregation)?)
+                };
+                Ok(SegmentFilterBucketEntry {
+                    doc_count: 0,
+                    sub_aggregation,
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        limits.add_memory_consumed(
+            buckets.len() as u64 * std::mem::size_of::<SegmentFilterBucketEntry>() as u64,
+        )?;
+        limits.add_buckets_collected(buckets.len() as u64)?;
+        Ok(SegmentFilterCollector {
+            buckets,
+            docs_buffer: Vec::new(),
+            accessor_idx,
+        })
+    }
+}
+
+impl SegmentAggregationCollector for SegmentFilterCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let bucket_agg_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+        let mut buckets = self
+            .buckets
+            .into_iter()
+            .map(|bucket| {
+                bucket.into_intermediate_bucket_entry(&bucket_agg_accessor.sub_aggregation)
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let bucket = match &bucket_agg_accessor.agg.agg {
+            AggregationVariants::Filters(filters_req) => IntermediateBucketResult::Filters {
+                buckets: filters_req.filters.keys().cloned().zip(buckets).collect(),
+            },
+            _ => IntermediateBucketResult::Filter {
+                bucket: buckets
+                    .pop()
+                    .expect("filter aggregation must have a single bucket"),
+            },
+        };
+        results.push(name, IntermediateAggregationResult::Bucket(bucket))?;
+        Ok(())
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: crate::DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[crate::DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+        for (bucket, matching_docs) in self
+            .buckets
+            .iter_mut()
+            .zip(&bucket_agg_accessor.filters_matching_docs)
+        {
+            self.docs_buffer.clear();
+            self.docs_buffer.extend(
+                docs.iter()
+                    .copied()
+                    .filter(|&doc| matching_docs.contains(doc)),
+            );
+            bucket.doc_count += self.docs_buffer.len() as u64;
+            if let Some(sub_aggregation) = &mut bucket.sub_aggregation {
+                sub_aggregation
+                    .collect_block(&self.docs_buffer, &mut bucket_agg_accessor.sub_aggregation)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, agg_with_accessor: &mut AggregationsWithAccessor) -> crate::Result<()> {
+        let sub_aggregation_accessor =
+            &mut agg_with_accessor.aggs.values[self.accessor_idx].sub_aggregation;
+        for bucket in self.buckets.iter_mut() {
+            if let Some(sub_aggregation) = bucket.sub_aggregation.as_mut() {
+                sub_aggregation.flush(sub_aggregation_accessor)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::{
+        exec_request, exec_request_with_query, get_test_index_2_segments,
+    };
+
+    #[test]
+    fn filter_aggregation_test() -> crate::Result<()> {
+        filter_aggregation_test_with_merge_segments(false)?;
+        filter_aggregation_test_with_merge_segments(true)
+    }
+
+    fn filter_aggregation_test_with_merge_segments(merge_segments: bool) -> crate::Result<()> {
+        let index = get_test_index_2_segments(merge_segments)?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "cool": {
+                "filter": { "query": "text:cool" },
+                "aggs": {
+                    "score_avg": { "avg": { "field": "score" } }
+                }
+            },
+            "by_score": {
+                "filters": {
+                    "filters": {
+                        "low": { "query": "score:[0 TO 5]" },
+                        "high": { "query": "score:[10 TO 50]" },
+                        "cool_and_high": { "query": "text:cool AND score:[10 TO 50]" }
+                    }
+                },
+                "aggs": {
+                    "score_max": { "max": { "field": "score" } }
+                }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req.clone(), &index)?;
+        assert_eq!(res["cool"]["doc_count"], 7);
+        assert_eq!(res["cool"]["score_avg"]["value"], 85.0 / 7.0);
+        assert_eq!(res["by_score"]["buckets"]["low"]["doc_count"], 3);
+        assert_eq!(res["by_score"]["buckets"]["low"]["score_max"]["value"], 5.0);
+        assert_eq!(res["by_score"]["buckets"]["high"]["doc_count"], 4);
+        assert_eq!(res["by_score"]["buckets"]["cool_and_high"]["doc_count"], 3);
+
+        // The filters only apply to the documents matching the query.
+        let res = exec_request_with_query(agg_req, &index, Some(("text", "nohit")))?;
+        assert_eq!(res["cool"]["doc_count"], 0);
+        assert_eq!(res["cool"]["score_avg"]["value"], serde_json::Value::Null);
+        assert_eq!(res["by_score"]["buckets"]["low"]["doc_count"], 0);
+        assert_eq!(res["by_score"]["buckets"]["high"]["doc_count"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn filter_aggregation_invalid_query_test() -> crate::Result<()> {
+        let index = get_test_index_2_segments(false)?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "unknown": {
+                "filter": { "query": "unknown_field:cool" }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert!(err.to_string().contains("unknown_field"), "{err}");
+        Ok(())
+    }
+}
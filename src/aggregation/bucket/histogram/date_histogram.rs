@@ -1,20 +1,23 @@
 use serde::{Deserialize, Serialize};
+use time::{Date, Month};
 
-use super::{HistogramAggregation, HistogramBounds};
+use super::{HistogramAggregation, HistogramBounds, TimeZone};
 use crate::aggregation::*;
 
 /// DateHistogramAggregation is similar to `HistogramAggregation`, but it can only be used with date
 /// type.
 ///
-/// Currently only **fixed time** intervals are supported. Calendar-aware time intervals are not
-/// supported.
+/// The interval is either a **fixed** interval, set with `fixed_interval`, or a **calendar-aware**
+/// interval, set with `calendar_interval`.
 ///
 /// Like the histogram, values are rounded down into the closest bucket.
 ///
 /// For this calculation all fastfield values are converted to f64.
 ///
 /// # Limitations/Compatibility
-/// Only fixed time intervals are supported.
+/// Named time zones, like `Europe/Paris`, come from the version of the IANA time zone database
+/// bundled with tantivy, see [`TimeZone`]. Recent changes to the rules of a time zone may be
+/// missing.
 ///
 /// # JSON Format
 /// ```json
@@ -28,6 +31,18 @@ use crate::aggregation::*;
 /// }
 /// ```
 ///
+/// ```json
+/// {
+///     "sales_per_month": {
+///         "date_histogram": {
+///             "field": "date",
+///             "calendar_interval": "month",
+///             "time_zone": "+01:00"
+///         }
+///     }
+/// }
+/// ```
+///
 /// Response
 /// See [`BucketEntry`](crate::aggregation::agg_result::BucketEntry)
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -35,8 +50,19 @@ pub struct DateHistogramAggregationReq {
     #[doc(hidden)]
     /// Only for validation
     pub interval: Option<String>,
-    #[doc(hidden)]
-    /// Only for validation
+    /// The calendar-aware interval to chunk your data range. Calendar-aware intervals know that
+    /// months have different amounts of days.
+    ///
+    /// The accepted values are:
+    /// * `minute`, `1m`
+    /// * `hour`, `1h`
+    /// * `day`, `1d`
+    /// * `week`, `1w`: weeks start on Monday.
+    /// * `month`, `1M`
+    /// * `quarter`, `1q`
+    /// * `year`, `1y`
+    ///
+    /// Only one of `fixed_interval` and `calendar_interval` can be set.
     pub calendar_interval: Option<String>,
     /// The field to aggregate on.
     pub field: String,
@@ -63,7 +89,7 @@ pub struct DateHistogramAggregationReq {
     /// Fractional time values are not supported, but you can address this by shifting to another
     /// time unit (e.g., `1.5h` could instead be specified as `90m`).
     ///
-    /// `Option` for validation, the parameter is not optional
+    /// Only one of `fixed_interval` and `calendar_interval` can be set.
     pub fixed_interval: Option<String>,
     /// Intervals implicitly defines an absolute grid of buckets `[interval * k, interval * (k +
     /// 1))`.
@@ -75,6 +101,16 @@ pub struct DateHistogramAggregationReq {
     /// The `offset` parameter is has the same syntax as the `fixed_interval` parameter, but
     /// also allows for negative values.
     pub offset: Option<String>,
+    /// The time zone used to round the dates into buckets, and to format `key_as_string`.
+    /// Defaults to UTC.
+    ///
+    /// Either a fixed UTC offset, like `"+01:00"`, `"-05:30"` or `"UTC"`, or the name of a time
+    /// zone of the IANA time zone database, like `"Europe/Paris"`.
+    ///
+    /// E.g. with a `calendar_interval` of `day` and a `time_zone` of `"+01:00"`, the buckets start
+    /// at midnight in UTC+1, i.e. at 23:00 in UTC. With a `time_zone` of `"Europe/Paris"`, they
+    /// start at 23:00 in UTC in winter and at 22:00 in UTC in summer.
+    pub time_zone: Option<String>,
     /// The minimum number of documents in a bucket to be returned. Defaults to 0.
     pub min_doc_count: Option<u64>,
     /// Limits the data range to `[min, max]` closed interval.
@@ -119,20 +155,87 @@ pub struct DateHistogramAggregationReq {
 impl DateHistogramAggregationReq {
     pub(crate) fn to_histogram_req(&self) -> crate::Result<HistogramAggregation> {
         self.validate()?;
+        let offset = self
+            .offset
+            .as_ref()
+            .map(|offset| parse_offset_into_milliseconds(offset))
+            .transpose()?;
+        let time_zone = self
+            .time_zone
+            .as_ref()
+            .map(|time_zone| TimeZone::parse(time_zone))
+            .transpose()?;
+
+        let (interval, grid_offset, rounding_unit) =
+            if let Some(fixed_interval) = self.fixed_interval.as_ref() {
+                let interval = parse_into_milliseconds(fixed_interval)?;
+                (interval, 0, RoundingUnit::Fixed(interval))
+            } else {
+                let calendar_interval =
+                    CalendarInterval::parse(self.calendar_interval.as_ref().unwrap())?;
+                let (interval, grid_offset) = match calendar_interval {
+                    CalendarInterval::Minute => (MINUTE_MS, 0),
+                    CalendarInterval::Hour => (HOUR_MS, 0),
+                    CalendarInterval::Day => (DAY_MS, 0),
+                    // The epoch is a Thursday, the first Monday is 4 days later.
+                    CalendarInterval::Week => (7 * DAY_MS, 4 * DAY_MS),
+                    // Only used for validation, the buckets are rounded by `DateRounding`.
+                    CalendarInterval::Month
+                    | CalendarInterval::Quarter
+                    | CalendarInterval::Year => (DAY_MS, 0),
+                };
+                (
+                    interval,
+                    grid_offset,
+                    RoundingUnit::Calendar(calendar_interval),
+                )
+            };
+        let time_zone_offset = match &time_zone {
+            Some(time_zone) => time_zone.fixed_offset_ms(),
+            None => Some(0),
+        };
+
+        // Buckets of a fixed duration in a fixed time zone are on a regular grid. Months,
+        // quarters and years, and the days of time zones with daylight saving time, are not.
+        let (offset, date_rounding) = match (rounding_unit, time_zone_offset) {
+            (RoundingUnit::Fixed(_), Some(time_zone_offset))
+            | (
+                RoundingUnit::Calendar(
+                    CalendarInterval::Minute
+                    | CalendarInterval::Hour
+                    | CalendarInterval::Day
+                    | CalendarInterval::Week,
+                ),
+                Some(time_zone_offset),
+            ) => {
+                let offset = if offset.is_some() || time_zone_offset != 0 || grid_offset != 0 {
+                    Some((grid_offset + offset.unwrap_or(0) - time_zone_offset) as f64)
+                } else {
+                    None
+                };
+                (offset, None)
+            }
+            _ => {
+                let date_rounding = DateRounding {
+                    unit: rounding_unit,
+                    offset_ms: offset.unwrap_or(0),
+                    time_zone: time_zone.unwrap_or(TimeZone::UTC),
+                };
+                (None, Some(date_rounding))
+            }
+        };
+
         Ok(HistogramAggregation {
             field: self.field.to_string(),
-            interval: parse_into_milliseconds(self.fixed_interval.as_ref().unwrap())? as f64,
-            offset: self
-                .offset
-                .as_ref()
-                .map(|offset| parse_offset_into_milliseconds(offset))
-                .transpose()?
-                .map(|el| el as f64),
+            interval: interval as f64,
+            offset,
             min_doc_count: self.min_doc_count,
             hard_bounds: self.hard_bounds,
             extended_bounds: self.extended_bounds,
             keyed: self.keyed,
             is_normalized_to_ns: false,
+            date_rounding,
+            time_zone,
         })
     }
 
@@ -140,13 +243,7 @@ impl DateHistogramAggregationReq {
         if let Some(interval) = self.interval.as_ref() {
             return Err(crate::TantivyError::InvalidArgument(format!(
                 "`interval` parameter {interval:?} in date histogram is unsupported, only \
-                 `fixed_interval` is supported"
-            )));
-        }
-        if let Some(interval) = self.calendar_interval.as_ref() {
-            return Err(crate::TantivyError::InvalidArgument(format!(
-                "`calendar_interval` parameter {interval:?} in date histogram is unsupported, \
-                 only `fixed_interval` is supported"
+                 `fixed_interval` and `calendar_interval` are supported"
             )));
         }
         if self.format.is_some() {
@@ -155,18 +252,237 @@ impl DateHistogramAggregationReq {
             ));
         }
 
-        if self.fixed_interval.is_none() {
-            return Err(crate::TantivyError::InvalidArgument(
-                "fixed_interval in date histogram is missing".to_string(),
-            ));
+        match (&self.fixed_interval, &self.calendar_interval) {
+            (Some(fixed_interval), None) => {
+                parse_into_milliseconds(fixed_interval)?;
+            }
+            (None, Some(calendar_interval)) => {
+                CalendarInterval::parse(calendar_interval)?;
+            }
+            (Some(_), Some(_)) => {
+                return Err(crate::TantivyError::InvalidArgument(
+                    "fixed_interval and calendar_interval cannot be set at the same time in date \
+                     histogram"
+                        .to_string(),
+                ));
+            }
+            (None, None) => {
+                return Err(crate::TantivyError::InvalidArgument(
+                    "fixed_interval or calendar_interval in date histogram is missing".to_string(),
+                ));
+            }
         }
 
-        parse_into_milliseconds(self.fixed_interval.as_ref().unwrap())?;
-
         Ok(())
     }
 }
 
+pub(super) const MINUTE_MS: i64 = 60 * 1000;
+pub(super) const HOUR_MS: i64 = 60 * MINUTE_MS;
+pub(super) const DAY_MS: i64 = 24 * HOUR_MS;
+const UNIX_EPOCH_JULIAN_DAY: i64 = 2_440_588;
+
+/// Returns the date `day` days after the epoch.
+pub(super) fn date_from_day(day: i64) -> Option<Date> {
+    i32::try_from(UNIX_EPOCH_JULIAN_DAY + day)
+        .ok()
+        .and_then(|julian_day| Date::from_julian_day(julian_day).ok())
+}
+
+/// Returns the number of days between the epoch and `date`.
+pub(super) fn day_from_date(date: Date) -> i64 {
+    date.to_julian_day() as i64 - UNIX_EPOCH_JULIAN_DAY
+}
+
+/// A calendar-aware interval of a [`DateHistogramAggregationReq`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalendarInterval {
+    /// One minute.
+    Minute,
+    /// One hour.
+    Hour,
+    /// One day.
+    Day,
+    /// One week, starting on Monday.
+    Week,
+    /// One month.
+    Month,
+    /// Three months, starting in January, April, July and October.
+    Quarter,
+    /// One year.
+    Year,
+}
+
+impl CalendarInterval {
+    fn parse(input: &str) -> Result<CalendarInterval, AggregationError> {
+        let calendar_interval = match input {
+            "minute" | "1m" => CalendarInterval::Minute,
+            "hour" | "1h" => CalendarInterval::Hour,
+            "day" | "1d" => CalendarInterval::Day,
+            "week" | "1w" => CalendarInterval::Week,
+            "month" | "1M" => CalendarInterval::Month,
+            "quarter" | "1q" => CalendarInterval::Quarter,
+            "year" | "1y" => CalendarInterval::Year,
+            _ => {
+                return Err(
+                    DateHistogramParseError::InvalidCalendarInterval(input.to_string()).into(),
+                )
+            }
+        };
+        Ok(calendar_interval)
+    }
+}
+
+/// The unit of a [`DateRounding`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RoundingUnit {
+    Calendar(CalendarInterval),
+    /// A fixed interval, in milliseconds.
+    Fixed(i64),
+}
+
+impl RoundingUnit {
+    /// Rounds a local time down to the start of its bucket.
+    fn floor(self, local_ms: i64) -> Option<i64> {
+        let floor_to = |unit_ms: i64| local_ms.div_euclid(unit_ms) * unit_ms;
+        let num_months = match self {
+            RoundingUnit::Fixed(interval_ms) => return Some(floor_to(interval_ms)),
+            RoundingUnit::Calendar(CalendarInterval::Minute) => return Some(floor_to(MINUTE_MS)),
+            RoundingUnit::Calendar(CalendarInterval::Hour) => return Some(floor_to(HOUR_MS)),
+            RoundingUnit::Calendar(CalendarInterval::Day) => return Some(floor_to(DAY_MS)),
+            RoundingUnit::Calendar(CalendarInterval::Week) => {
+                // The epoch is a Thursday, the first Monday is 4 days later.
+                let day = local_ms.div_euclid(DAY_MS);
+                return (day - 4)
+                    .div_euclid(7)
+                    .checked_mul(7 * DAY_MS)?
+                    .checked_add(4 * DAY_MS);
+            }
+            RoundingUnit::Calendar(CalendarInterval::Month) => 1,
+            RoundingUnit::Calendar(CalendarInterval::Quarter) => 3,
+            RoundingUnit::Calendar(CalendarInterval::Year) => 12,
+        };
+        let date = date_from_day(local_ms.div_euclid(DAY_MS))?;
+        let month_index = date.year() as i64 * 12 + u8::from(date.month()) as i64 - 1;
+        first_day_of_month(month_index - month_index.rem_euclid(num_months))?.checked_mul(DAY_MS)
+    }
+
+    /// Returns the start of the bucket following the bucket starting at the local time
+    /// `start_local_ms`.
+    fn next(self, start_local_ms: i64) -> Option<i64> {
+        let num_months = match self {
+            RoundingUnit::Fixed(interval_ms) => return start_local_ms.checked_add(interval_ms),
+            RoundingUnit::Calendar(CalendarInterval::Minute) => {
+                return start_local_ms.checked_add(MINUTE_MS)
+            }
+            RoundingUnit::Calendar(CalendarInterval::Hour) => {
+                return start_local_ms.checked_add(HOUR_MS)
+            }
+            RoundingUnit::Calendar(CalendarInterval::Day) => {
+                return start_local_ms.checked_add(DAY_MS)
+            }
+            RoundingUnit::Calendar(CalendarInterval::Week) => {
+                return start_local_ms.checked_add(7 * DAY_MS)
+            }
+            RoundingUnit::Calendar(CalendarInterval::Month) => 1,
+            RoundingUnit::Calendar(CalendarInterval::Quarter) => 3,
+            RoundingUnit::Calendar(CalendarInterval::Year) => 12,
+        };
+        let date = date_from_day(start_local_ms.div_euclid(DAY_MS))?;
+        let month_index = date.year() as i64 * 12 + u8::from(date.month()) as i64 - 1;
+        first_day_of_month(month_index + num_months)?.checked_mul(DAY_MS)
+    }
+
+    /// Returns a lower bound of the duration of the buckets, in milliseconds.
+    ///
+    /// Days may be one hour shorter because of daylight saving time.
+    fn min_duration_ms(self) -> i64 {
+        match self {
+            RoundingUnit::Fixed(interval_ms) => interval_ms,
+            RoundingUnit::Calendar(CalendarInterval::Minute) => MINUTE_MS,
+            RoundingUnit::Calendar(CalendarInterval::Hour) => HOUR_MS,
+            RoundingUnit::Calendar(CalendarInterval::Day) => DAY_MS - HOUR_MS,
+            RoundingUnit::Calendar(CalendarInterval::Week) => 7 * DAY_MS - HOUR_MS,
+            RoundingUnit::Calendar(CalendarInterval::Month) => 28 * DAY_MS - HOUR_MS,
+            RoundingUnit::Calendar(CalendarInterval::Quarter) => 89 * DAY_MS - HOUR_MS,
+            RoundingUnit::Calendar(CalendarInterval::Year) => 365 * DAY_MS - HOUR_MS,
+        }
+    }
+}
+
+/// Returns the number of days between the epoch and the first day of the month
+/// `month_index`, counted in months since January of year 0.
+fn first_day_of_month(month_index: i64) -> Option<i64> {
+    let year = i32::try_from(month_index.div_euclid(12)).ok()?;
+    let month = Month::try_from(month_index.rem_euclid(12) as u8 + 1).ok()?;
+    let date = Date::from_calendar_date(year, month, 1).ok()?;
+    Some(day_from_date(date))
+}
+
+/// Rounds dates down to the start of their bucket in a [`DateHistogramAggregationReq`].
+///
+/// Used for the buckets which are not on a regular grid: months, quarters and years, whose
+/// durations vary, and the buckets of time zones with daylight saving time, whose offset from
+/// UTC varies. The bucket of each value is computed when it is collected, in the local time of
+/// the time zone.
+///
+/// All times are in milliseconds since the epoch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DateRounding {
+    unit: RoundingUnit,
+    /// The `offset` of the request, applied in local time.
+    offset_ms: i64,
+    time_zone: TimeZone,
+}
+
+impl DateRounding {
+    /// Returns the start of the bucket containing `timestamp_ms`.
+    pub(crate) fn round(&self, timestamp_ms: i64) -> crate::Result<i64> {
+        let local_ms = timestamp_ms + self.time_zone.utc_offset_ms(timestamp_ms) - self.offset_ms;
+        let start_local_ms = self
+            .unit
+            .floor(local_ms)
+            .ok_or_else(|| out_of_range_error(timestamp_ms))?;
+        Ok(self
+            .time_zone
+            .local_to_utc_ms(start_local_ms + self.offset_ms))
+    }
+
+    /// Returns the start of the bucket following the bucket starting at `bucket_key_ms`.
+    pub(crate) fn next(&self, bucket_key_ms: i64) -> crate::Result<i64> {
+        let mut start_local_ms = self
+            .unit
+            .floor(bucket_key_ms + self.time_zone.utc_offset_ms(bucket_key_ms) - self.offset_ms)
+            .ok_or_else(|| out_of_range_error(bucket_key_ms))?;
+        loop {
+            start_local_ms = self
+                .unit
+                .next(start_local_ms)
+                .ok_or_else(|| out_of_range_error(bucket_key_ms))?;
+            // The start of the next bucket may be in a local time repeated at the end of
+            // daylight saving time.
+            let next_bucket_key_ms = self
+                .time_zone
+                .local_to_utc_ms(start_local_ms + self.offset_ms);
+            if next_bucket_key_ms > bucket_key_ms {
+                return Ok(next_bucket_key_ms);
+            }
+        }
+    }
+
+    /// Returns an upper bound of the number of buckets between `min_ms` and `max_ms`.
+    pub(crate) fn max_num_buckets(&self, min_ms: i64, max_ms: i64) -> u64 {
+        let num_buckets = (max_ms as i128 - min_ms as i128) / self.unit.min_duration_ms() as i128;
+        (num_buckets.max(0) as u64).saturating_add(2)
+    }
+}
+
+fn out_of_range_error(timestamp_ms: i64) -> crate::TantivyError {
+    crate::TantivyError::InvalidArgument(format!(
+        "date histogram value {timestamp_ms}ms is out of the range of dates"
+    ))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 /// Errors when parsing the fixed interval for `DateHistogramAggregationReq`.
 pub enum DateHistogramParseError {
@@ -185,6 +501,12 @@ pub enum DateHistogramParseError {
     /// Value out of bounds
     #[error("passed value is out of bounds: {0:?}")]
     OutOfBounds(String),
+    /// Calendar interval not recognized
+    #[error("calendar interval not recognized {0:?}")]
+    InvalidCalendarInterval(String),
+    /// Time zone invalid or unknown
+    #[error("passed time zone is invalid or unknown {0:?}")]
+    InvalidTimeZone(String),
 }
 
 fn parse_offset_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
@@ -207,7 +529,7 @@ fn parse_offset_into_milliseconds(input: &str) -> Result<i64, AggregationError>
     }
 }
 
-fn parse_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
+pub(crate) fn parse_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
     let split_boundary = input
         .as_bytes()
         .iter()
@@ -235,7 +557,9 @@ fn parse_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
         _ => return Err(DateHistogramParseError::UnitNotRecognized(unit.to_string()).into()),
     };
 
-    let val = number * unit_in_ms;
+    let val = number
+        .checked_mul(unit_in_ms)
+        .ok_or_else(|| DateHistogramParseError::OutOfBounds(input.to_string()))?;
     // The field type is in nanoseconds precision, so validate the value to fit the range
     val.checked_mul(1_000_000)
         .ok_or_else(|| DateHistogramParseError::OutOfBounds(input.to_string()))?;
@@ -246,6 +570,7 @@ fn parse_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
 #[cfg(test)]
 pub(crate) mod tests {
     use pretty_assertions::assert_eq;
+    use serde_json::Value;
 
     use super::*;
     use crate::aggregation::agg_req::Aggregations;
@@ -646,6 +971,307 @@ pub(crate) mod tests {
             assert_eq!(res, expected_res);
         }
     }
+    #[test]
+    fn histogram_test_date_calendar_interval_force_merge_segments() {
+        histogram_test_date_calendar_interval_merge_segments(true)
+    }
+
+    #[test]
+    fn histogram_test_date_calendar_interval() {
+        histogram_test_date_calendar_interval_merge_segments(false)
+    }
+
+    fn histogram_test_date_calendar_interval_merge_segments(merge_segments: bool) {
+        let docs = vec![
+            vec![r#"{ "date": "2015-01-31T23:30:00Z", "text": "aaa" }"#],
+            vec![r#"{ "date": "2015-02-15T00:00:00Z", "text": "aaa" }"#],
+            vec![r#"{ "date": "2015-04-01T00:00:00Z", "text": "bbb" }"#],
+        ];
+        let index = get_test_index_from_docs(merge_segments, &docs).unwrap();
+
+        {
+            // month + time zone + sub_agg
+            let elasticsearch_compatible_json = json!(
+                {
+                    "sales_over_time": {
+                        "date_histogram": {
+                            "field": "date",
+                            "calendar_interval": "month",
+                            "time_zone": "+01:00"
+                        },
+                        "aggs": {
+                            "texts": {
+                                "terms": {"field": "text"}
+                            }
+                        }
+                    }
+                }
+            );
+
+            let agg_req: Aggregations = serde_json::from_str(
+                &serde_json::to_string(&elasticsearch_compatible_json).unwrap(),
+            )
+            .unwrap();
+            let res = exec_request(agg_req, &index).unwrap();
+            let expected_res = json!({
+                "sales_over_time" : {
+                    "buckets" : [
+                        {
+                            "key_as_string" : "2015-02-01T00:00:00+01:00",
+                            "key" : 1422745200000.0,
+                            "doc_count" : 2,
+                            "texts": {
+                                "buckets": [
+                                    { "doc_count": 2, "key": "aaa" }
+                                ],
+                                "doc_count_error_upper_bound": 0,
+                                "sum_other_doc_count": 0
+                            }
+                        },
+                        {
+                            "key_as_string" : "2015-03-01T00:00:00+01:00",
+                            "key" : 1425164400000.0,
+                            "doc_count" : 0,
+                            "texts": {
+                                "buckets": [],
+                                "doc_count_error_upper_bound": 0,
+                                "sum_other_doc_count": 0
+                            }
+                        },
+                        {
+                            "key_as_string" : "2015-04-01T00:00:00+01:00",
+                            "key" : 1427842800000.0,
+                            "doc_count" : 1,
+                            "texts": {
+                                "buckets": [
+                                    { "doc_count": 1, "key": "bbb" }
+                                ],
+                                "doc_count_error_upper_bound": 0,
+                                "sum_other_doc_count": 0
+                            }
+                        }
+                    ]
+                }
+            });
+            assert_eq!(res, expected_res);
+        }
+
+        {
+            // quarter + extended_bounds
+            let elasticsearch_compatible_json = json!(
+                {
+                    "sales_over_time": {
+                        "date_histogram": {
+                            "field": "date",
+                            "calendar_interval": "quarter",
+                            "extended_bounds": {
+                                "min": "2015-01-01T00:00:00Z",
+                                "max": "2015-12-31T00:00:00Z"
+                            }
+                        }
+                    }
+                }
+            );
+
+            let agg_req: Aggregations = serde_json::from_str(
+                &serde_json::to_string(&elasticsearch_compatible_json).unwrap(),
+            )
+            .unwrap();
+            let res = exec_request(agg_req, &index).unwrap();
+            let buckets = res["sales_over_time"]["buckets"].as_array().unwrap();
+            let keys_and_counts: Vec<(&str, u64)> = buckets
+                .iter()
+                .map(|bucket| {
+                    (
+                        bucket["key_as_string"].as_str().unwrap(),
+                        bucket["doc_count"].as_u64().unwrap(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                keys_and_counts,
+                vec![
+                    ("2015-01-01T00:00:00Z", 2),
+                    ("2015-04-01T00:00:00Z", 1),
+                    ("2015-07-01T00:00:00Z", 0),
+                    ("2015-10-01T00:00:00Z", 0),
+                ]
+            );
+        }
+
+        {
+            // weeks start on Monday
+            let elasticsearch_compatible_json = json!(
+                {
+                    "sales_over_time": {
+                        "date_histogram": {
+                            "field": "date",
+                            "calendar_interval": "week",
+                            "min_doc_count": 1
+                        }
+                    }
+                }
+            );
+
+            let agg_req: Aggregations = serde_json::from_str(
+                &serde_json::to_string(&elasticsearch_compatible_json).unwrap(),
+            )
+            .unwrap();
+            let res = exec_request(agg_req, &index).unwrap();
+            let buckets = res["sales_over_time"]["buckets"].as_array().unwrap();
+            let keys: Vec<&str> = buckets
+                .iter()
+                .map(|bucket| bucket["key_as_string"].as_str().unwrap())
+                .collect();
+            assert_eq!(
+                keys,
+                vec![
+                    "2015-01-26T00:00:00Z",
+                    "2015-02-09T00:00:00Z",
+                    "2015-03-30T00:00:00Z"
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn histogram_test_date_named_time_zone_force_merge_segments() {
+        histogram_test_date_named_time_zone_merge_segments(true)
+    }
+
+    #[test]
+    fn histogram_test_date_named_time_zone() {
+        histogram_test_date_named_time_zone_merge_segments(false)
+    }
+
+    fn histogram_test_date_named_time_zone_merge_segments(merge_segments: bool) {
+        let docs = vec![
+            vec![r#"{ "date": "2024-03-30T22:30:00Z" }"#],
+            vec![r#"{ "date": "2024-03-30T23:30:00Z" }"#],
+            vec![r#"{ "date": "2024-03-31T21:30:00Z" }"#],
+            vec![r#"{ "date": "2024-03-31T22:30:00Z" }"#],
+            vec![r#"{ "date": "2024-03-01T04:30:00Z" }"#],
+            vec![r#"{ "date": "2024-03-01T05:30:00Z" }"#],
+            vec![r#"{ "date": "2024-11-01T03:30:00Z" }"#],
+        ];
+        let index = get_test_index_from_docs(merge_segments, &docs).unwrap();
+        let keys_and_counts = |res: &Value| -> Vec<(String, u64)> {
+            res["sales_over_time"]["buckets"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|bucket| {
+                    (
+                        bucket["key_as_string"].as_str().unwrap().to_string(),
+                        bucket["doc_count"].as_u64().unwrap(),
+                    )
+                })
+                .collect()
+        };
+
+        {
+            // days across the start of daylight saving time, on March 31st 2024
+            let elasticsearch_compatible_json = json!(
+                {
+                    "sales_over_time": {
+                        "date_histogram": {
+                            "field": "date",
+                            "calendar_interval": "day",
+                            "time_zone": "Europe/Paris",
+                            "min_doc_count": 1,
+                            "hard_bounds": {
+                                "min": "2024-03-29T00:00:00Z",
+                                "max": "2024-04-02T00:00:00Z"
+                            }
+                        }
+                    }
+                }
+            );
+
+            let agg_req: Aggregations = serde_json::from_str(
+                &serde_json::to_string(&elasticsearch_compatible_json).unwrap(),
+            )
+            .unwrap();
+            let res = exec_request(agg_req, &index).unwrap();
+            assert_eq!(
+                keys_and_counts(&res),
+                vec![
+                    ("2024-03-30T00:00:00+01:00".to_string(), 1),
+                    ("2024-03-31T00:00:00+01:00".to_string(), 2),
+                    ("2024-04-01T00:00:00+02:00".to_string(), 1),
+                ]
+            );
+            // The day of the change has 23 hours.
+            assert_eq!(
+                res["sales_over_time"]["buckets"][1]["key"],
+                json!(1711839600000.0)
+            );
+            assert_eq!(
+                res["sales_over_time"]["buckets"][2]["key"],
+                json!(1711922400000.0)
+            );
+        }
+
+        {
+            // months and quarters across daylight saving time
+            let elasticsearch_compatible_json = json!(
+                {
+                    "sales_over_time": {
+                        "date_histogram": {
+                            "field": "date",
+                            "calendar_interval": "month",
+                            "time_zone": "America/New_York",
+                            "hard_bounds": {
+                                "min": "2024-02-01T00:00:00Z",
+                                "max": "2024-03-02T00:00:00Z"
+                            }
+                        }
+                    }
+                }
+            );
+
+            let agg_req: Aggregations = serde_json::from_str(
+                &serde_json::to_string(&elasticsearch_compatible_json).unwrap(),
+            )
+            .unwrap();
+            let res = exec_request(agg_req, &index).unwrap();
+            assert_eq!(
+                keys_and_counts(&res),
+                vec![
+                    ("2024-02-01T00:00:00-05:00".to_string(), 1),
+                    ("2024-03-01T00:00:00-05:00".to_string(), 1),
+                ]
+            );
+
+            let elasticsearch_compatible_json = json!(
+                {
+                    "sales_over_time": {
+                        "date_histogram": {
+                            "field": "date",
+                            "calendar_interval": "quarter",
+                            "time_zone": "America/New_York"
+                        }
+                    }
+                }
+            );
+
+            let agg_req: Aggregations = serde_json::from_str(
+                &serde_json::to_string(&elasticsearch_compatible_json).unwrap(),
+            )
+            .unwrap();
+            let res = exec_request(agg_req, &index).unwrap();
+            assert_eq!(
+                keys_and_counts(&res),
+                vec![
+                    ("2024-01-01T00:00:00-05:00".to_string(), 6),
+                    ("2024-04-01T00:00:00-04:00".to_string(), 0),
+                    ("2024-07-01T00:00:00-04:00".to_string(), 0),
+                    ("2024-10-01T00:00:00-04:00".to_string(), 1),
+                ]
+            );
+        }
+    }
+
     #[test]
     fn histogram_test_invalid_req() {
         let docs = vec![];
@@ -669,7 +1295,7 @@ pub(crate) mod tests {
         let err = exec_request(agg_req, &index).unwrap_err();
         assert_eq!(
             err.to_string(),
-            r#"An invalid argument was passed: '`interval` parameter "30d" in date histogram is unsupported, only `fixed_interval` is supported'"#
+            r#"An invalid argument was passed: '`interval` parameter "30d" in date histogram is unsupported, only `fixed_interval` and `calendar_interval` are supported'"#
         );
     }
 }
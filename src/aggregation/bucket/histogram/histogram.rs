@@ -4,6 +4,8 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use tantivy_bitpacker::minmax;
 
+use super::date_histogram::parse_into_milliseconds;
+use super::{DateRounding, TimeZone};
 use crate::aggregation::agg_limits::MemoryConsumption;
 use crate::aggregation::agg_req::Aggregations;
 use crate::aggregation::agg_req_with_accessor::{
@@ -62,6 +64,18 @@ use crate::TantivyError;
 /// }
 /// ```
 ///
+/// On a date field, the interval can be a fixed interval string:
+/// ```json
+/// {
+///     "per_hour": {
+///         "histogram": {
+///             "field": "timestamp",
+///             "interval": "1h"
+///         }
+///     }
+/// }
+/// ```
+///
 /// Response
 /// See [`BucketEntry`](crate::aggregation::agg_result::BucketEntry)
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -70,7 +84,10 @@ pub struct HistogramAggregation {
     pub field: String,
     /// The interval to chunk your data range. Each bucket spans a value range of [0..interval).
     /// Must be a positive value.
-    #[serde(deserialize_with = "deserialize_f64")]
+    ///
+    /// On date fields, the interval is in milliseconds. It can also be given as a fixed interval
+    /// string, like `"30s"`, `"5m"`, `"1h"` or `"1d"`, which is converted to milliseconds.
+    #[serde(deserialize_with = "deserialize_interval")]
     pub interval: f64,
     /// Intervals implicitly defines an absolute grid of buckets `[interval * k, interval * (k +
     /// 1))`.
@@ -124,6 +141,15 @@ pub struct HistogramAggregation {
     /// Whether the values are normalized to ns for date time values. Defaults to false.
     #[serde(default)]
     pub is_normalized_to_ns: bool,
+    /// Set by date histograms whose buckets are not on a regular grid, like months. The values
+    /// are then rounded into their bucket by `date_rounding` instead of `interval` and `offset`.
+    #[doc(hidden)]
+    #[serde(skip)]
+    pub date_rounding: Option<DateRounding>,
+    /// The time zone of a date histogram. Used to format `key_as_string`.
+    #[doc(hidden)]
+    #[serde(skip)]
+    pub time_zone: Option<TimeZone>,
 }
 
 impl HistogramAggregation {
@@ -187,8 +213,62 @@ pub struct HistogramBounds {
     pub max: f64,
 }
 
+/// Deserializes an interval from a number, or from a fixed interval string in milliseconds.
+fn deserialize_interval<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct IntervalVisitor;
+
+    impl serde::de::Visitor<'_> for IntervalVisitor {
+        type Value = f64;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a float or a fixed interval string")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            if value.ends_with(|ch: char| ch.is_ascii_alphabetic()) {
+                parse_into_milliseconds(value)
+                    .map(|milliseconds| milliseconds as f64)
+                    .map_err(E::custom)
+            } else {
+                deserialize_f64(serde::de::value::StrDeserializer::new(value))
+            }
+        }
+
+        fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(value)
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(value as f64)
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(value as f64)
+        }
+    }
+
+    deserializer.deserialize_any(IntervalVisitor)
+}
+
 fn deserialize_date_or_num<'de, D>(deserializer: D) -> Result<f64, D::Error>
-where D: serde::Deserializer<'de> {
+where
+    D: serde::Deserializer<'de>,
+{
     let value: serde_json::Value = Deserialize::deserialize(deserializer)?;
 
     // Check if the value is a string representing an Rfc3339 formatted date
@@ -260,6 +340,7 @@ pub struct SegmentHistogramCollector {
     column_type: ColumnType,
     interval: f64,
     offset: f64,
+    date_rounding: Option<DateRounding>,
     bounds: HistogramBounds,
     accessor_idx: usize,
 }
@@ -297,10 +378,12 @@ impl SegmentAggregationCollector for SegmentHistogramCollector {
         let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
 
         let mem_pre = self.get_memory_consumption();
+        let num_buckets_pre = self.buckets.len();
 
         let bounds = self.bounds;
         let interval = self.interval;
         let offset = self.offset;
+        let date_rounding = self.date_rounding.as_ref();
         let get_bucket_pos = |val| (get_bucket_pos_f64(val, interval, offset) as i64);
 
         bucket_agg_accessor
@@ -313,11 +396,17 @@ impl SegmentAggregationCollector for SegmentHistogramCollector {
         {
             let val = self.f64_from_fastfield_u64(val);
 
-            let bucket_pos = get_bucket_pos(val);
-
             if bounds.contains(val) {
+                // With a date rounding, the key of the bucket in milliseconds is its position.
+                let bucket_pos = match date_rounding {
+                    Some(date_rounding) => date_rounding.round(ns_to_ms(val))?,
+                    None => get_bucket_pos(val),
+                };
                 let bucket = self.buckets.entry(bucket_pos).or_insert_with(|| {
-                    let key = get_bucket_key_from_pos(bucket_pos as f64, interval, offset);
+                    let key = match date_rounding {
+                        Some(_) => ms_to_ns(bucket_pos),
+                        None => get_bucket_key_from_pos(bucket_pos as f64, interval, offset),
+                    };
                     SegmentHistogramBucketEntry { key, doc_count: 0 }
                 });
                 bucket.doc_count += 1;
@@ -336,6 +425,12 @@ impl SegmentAggregationCollector for SegmentHistogramCollector {
                 .limits
                 .add_memory_consumed(mem_delta as u64)?;
         }
+        let num_buckets_delta = self.buckets.len() - num_buckets_pre;
+        if num_buckets_delta > 0 {
+            bucket_agg_accessor
+                .limits
+                .add_buckets_collected(num_buckets_delta as u64)?;
+        }
 
         Ok(())
     }
@@ -410,6 +505,7 @@ impl SegmentHistogramCollector {
             column_type: field_type,
             interval: req.interval,
             offset: req.offset.unwrap_or(0.0),
+            date_rounding: req.date_rounding,
             bounds,
             sub_aggregations: Default::default(),
             sub_aggregation_blueprint,
@@ -433,9 +529,56 @@ fn get_bucket_key_from_pos(bucket_pos: f64, interval: f64, offset: f64) -> f64 {
     bucket_pos * interval + offset
 }
 
+#[inline]
+fn ns_to_ms(val: f64) -> i64 {
+    (val / 1_000_000.0).floor() as i64
+}
+
+#[inline]
+fn ms_to_ns(val: i64) -> f64 {
+    val as f64 * 1_000_000.0
+}
+
+/// Sets the duration of a date histogram bucket on the `rate` aggregations below it.
+///
+/// The first and last buckets may be cut by the hard bounds.
+fn set_rate_bucket_duration(
+    bucket: &mut IntermediateHistogramBucketEntry,
+    histogram_req: &HistogramAggregation,
+) -> crate::Result<()> {
+    let mut start = bucket.key;
+    let mut end = if let Some(date_rounding) = &histogram_req.date_rounding {
+        ms_to_ns(date_rounding.next(ns_to_ms(bucket.key))?)
+    } else {
+        bucket.key + histogram_req.interval
+    };
+    if let Some(hard_bounds) = histogram_req.hard_bounds {
+        start = start.max(hard_bounds.min);
+        end = end.min(hard_bounds.max);
+    }
+    bucket
+        .sub_aggregation
+        .set_rate_bucket_duration_ns(end - start);
+    Ok(())
+}
+
+fn into_final_histogram_bucket_entry(
+    mut bucket: IntermediateHistogramBucketEntry,
+    is_date_agg: bool,
+    histogram_req: &HistogramAggregation,
+    sub_aggregation: &Aggregations,
+    limits: &mut AggregationLimitsGuard,
+) -> crate::Result<BucketEntry> {
+    if is_date_agg {
+        set_rate_bucket_duration(&mut bucket, histogram_req)?;
+    }
+    bucket.into_final_bucket_entry(sub_aggregation, limits)
+}
+
 // Convert to BucketEntry and fill gaps
 fn intermediate_buckets_to_final_buckets_fill_gaps(
     buckets: Vec<IntermediateHistogramBucketEntry>,
+    is_date_agg: bool,
     histogram_req: &HistogramAggregation,
     sub_aggregation: &Aggregations,
     limits: &mut AggregationLimitsGuard,
@@ -447,8 +590,22 @@ fn intermediate_buckets_to_final_buckets_fill_gaps(
     let min_max = minmax(buckets.iter().map(|bucket| bucket.key));
 
     // memory check upfront
-    let (_, first_bucket_num, last_bucket_num) =
-        generate_bucket_pos_with_opt_minmax(histogram_req, min_max);
+    let (first_bucket_num, last_bucket_num) = match &histogram_req.date_rounding {
+        Some(date_rounding) => {
+            let (min, max) = get_req_min_max(histogram_req, min_max);
+            if min > max {
+                (0, 0)
+            } else {
+                let num_buckets = date_rounding.max_num_buckets(ns_to_ms(min), ns_to_ms(max));
+                (0, num_buckets.min(i64::MAX as u64) as i64)
+            }
+        }
+        None => {
+            let (_, first_bucket_num, last_bucket_num) =
+                generate_bucket_pos_with_opt_minmax(histogram_req, min_max);
+            (first_bucket_num, last_bucket_num)
+        }
+    };
 
     // It's based on user input, so we need to account for overflows
     let added_buckets = ((last_bucket_num.saturating_sub(first_bucket_num)).max(0) as u64)
@@ -457,7 +614,11 @@ fn intermediate_buckets_to_final_buckets_fill_gaps(
         added_buckets * std::mem::size_of::<IntermediateHistogramBucketEntry>() as u64,
     )?;
     // create buckets
-    let fill_gaps_buckets = generate_buckets_with_opt_minmax(histogram_req, min_max);
+    let fill_gaps_buckets = if let Some(date_rounding) = &histogram_req.date_rounding {
+        generate_rounded_buckets_with_opt_minmax(histogram_req, date_rounding, min_max)?
+    } else {
+        generate_buckets_with_opt_minmax(histogram_req, min_max)
+    };
 
     let empty_sub_aggregation = IntermediateAggregationResults::empty_from_req(sub_aggregation);
 
@@ -483,7 +644,13 @@ fn intermediate_buckets_to_final_buckets_fill_gaps(
             },
         })
         .map(|intermediate_bucket| {
-            intermediate_bucket.into_final_bucket_entry(sub_aggregation, limits)
+            into_final_histogram_bucket_entry(
+                intermediate_bucket,
+                is_date_agg,
+                histogram_req,
+                sub_aggregation,
+                limits,
+            )
         })
         .collect::<crate::Result<Vec<_>>>()?;
 
@@ -511,6 +678,7 @@ pub(crate) fn intermediate_histogram_buckets_to_final_buckets(
         // reduce serialization size).
         intermediate_buckets_to_final_buckets_fill_gaps(
             buckets,
+            is_date_agg,
             &histogram_req,
             sub_aggregation,
             limits,
@@ -520,7 +688,13 @@ pub(crate) fn intermediate_histogram_buckets_to_final_buckets(
             .into_iter()
             .filter(|histogram_bucket| histogram_bucket.doc_count >= histogram_req.min_doc_count())
             .map(|histogram_bucket| {
-                histogram_bucket.into_final_bucket_entry(sub_aggregation, limits)
+                into_final_histogram_bucket_entry(
+                    histogram_bucket,
+                    is_date_agg,
+                    &histogram_req,
+                    sub_aggregation,
+                    limits,
+                )
             })
             .collect::<crate::Result<Vec<_>>>()?
     };
@@ -530,7 +704,11 @@ pub(crate) fn intermediate_histogram_buckets_to_final_buckets(
     if is_date_agg {
         for bucket in buckets.iter_mut() {
             if let crate::aggregation::Key::F64(ref mut val) = bucket.key {
-                let key_as_string = format_date(*val as i64)?;
+                let time_zone_offset = histogram_req
+                    .time_zone
+                    .as_ref()
+                    .map(|time_zone| time_zone.utc_offset_ms(ns_to_ms(*val)));
+                let key_as_string = format_date_with_offset(*val as i64, time_zone_offset)?;
                 *val /= 1_000_000.0;
                 bucket.key_as_string = Some(key_as_string);
             }
@@ -592,6 +770,28 @@ pub(crate) fn generate_buckets_with_opt_minmax(
     buckets
 }
 
+/// Generates the keys of the buckets of a date rounding
+/// Range is computed for provided min_max and request extended_bounds/hard_bounds
+/// returns empty vec when there is no range to span
+fn generate_rounded_buckets_with_opt_minmax(
+    req: &HistogramAggregation,
+    date_rounding: &DateRounding,
+    min_max: Option<(f64, f64)>,
+) -> crate::Result<Vec<f64>> {
+    let (min, max) = get_req_min_max(req, min_max);
+    let mut buckets = Vec::new();
+    if min > max {
+        return Ok(buckets);
+    }
+    let max_ms = ns_to_ms(max);
+    let mut bucket_key_ms = date_rounding.round(ns_to_ms(min))?;
+    while bucket_key_ms <= max_ms {
+        buckets.push(ms_to_ns(bucket_key_ms));
+        bucket_key_ms = date_rounding.next(bucket_key_ms)?;
+    }
+    Ok(buckets)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1301,6 +1501,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn histogram_date_fixed_interval_test() -> crate::Result<()> {
+        let index = get_test_index_2_segments(false)?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "histogram": {
+                "histogram": {
+                    "field": "date",
+                    "interval": "1d",
+                },
+            }
+        }))
+        .unwrap();
+        let agg_res = exec_request(agg_req, &index)?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "histogram": {
+                "histogram": {
+                    "field": "date",
+                    "interval": 86400000.0,
+                },
+            }
+        }))
+        .unwrap();
+        assert_eq!(agg_res, exec_request(agg_req, &index)?);
+        assert_eq!(
+            agg_res["histogram"]["buckets"][1]["key_as_string"],
+            "2019-01-02T00:00:00Z"
+        );
+
+        let histogram: HistogramAggregation =
+            serde_json::from_value(json!({ "field": "date", "interval": "30s" })).unwrap();
+        assert_eq!(histogram.interval, 30_000.0);
+        let histogram: HistogramAggregation =
+            serde_json::from_value(json!({ "field": "date", "interval": "10" })).unwrap();
+        assert_eq!(histogram.interval, 10.0);
+        assert!(serde_json::from_value::<HistogramAggregation>(
+            json!({ "field": "date", "interval": "1y" })
+        )
+        .is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn histogram_invalid_request() -> crate::Result<()> {
         let index = get_test_index_2_segments(true)?;
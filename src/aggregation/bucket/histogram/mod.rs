@@ -1,4 +1,6 @@
 mod date_histogram;
 mod histogram;
+mod time_zone;
 pub use date_histogram::*;
 pub use histogram::*;
+pub use time_zone::TimeZone;
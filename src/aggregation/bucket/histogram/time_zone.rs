@@ -0,0 +1,228 @@
+use std::fmt;
+
+use chrono::{DateTime, Offset, TimeZone as _, Utc};
+use chrono_tz::Tz;
+
+use super::date_histogram::{HOUR_MS, MINUTE_MS};
+use super::DateHistogramParseError;
+use crate::aggregation::AggregationError;
+
+/// The time zone of a date histogram.
+///
+/// Either a fixed offset from UTC, like `+01:00`, or a time zone of the IANA time zone
+/// database, like `Europe/Paris`, whose offset changes with daylight saving time. Named time
+/// zones come from the time zone database bundled with the `chrono-tz` crate, so the buckets
+/// do not depend on the system the index is searched on.
+#[derive(Clone, Copy, PartialEq)]
+pub struct TimeZone(TimeZoneKind);
+
+#[derive(Clone, Copy, PartialEq)]
+enum TimeZoneKind {
+    /// A fixed offset from UTC, in milliseconds.
+    Fixed(i64),
+    Named(Tz),
+}
+
+impl TimeZone {
+    /// The UTC time zone.
+    pub(crate) const UTC: TimeZone = TimeZone(TimeZoneKind::Fixed(0));
+
+    /// Parses a fixed UTC offset, like `+01:00` or `UTC`, or the name of a time zone of the
+    /// IANA time zone database, like `Europe/Paris`.
+    pub(crate) fn parse(input: &str) -> Result<TimeZone, AggregationError> {
+        if input.starts_with(['+', '-']) || input == "UTC" || input == "Z" {
+            let offset_ms = parse_time_zone_into_milliseconds(input)?;
+            return Ok(TimeZone(TimeZoneKind::Fixed(offset_ms)));
+        }
+        let named_time_zone = input
+            .parse::<Tz>()
+            .map_err(|_| DateHistogramParseError::InvalidTimeZone(input.to_string()))?;
+        Ok(TimeZone(TimeZoneKind::Named(named_time_zone)))
+    }
+
+    /// Returns the offset from UTC in milliseconds, if it does not change over time.
+    pub(crate) fn fixed_offset_ms(&self) -> Option<i64> {
+        match &self.0 {
+            TimeZoneKind::Fixed(offset_ms) => Some(*offset_ms),
+            TimeZoneKind::Named(_) => None,
+        }
+    }
+
+    /// Returns the offset from UTC in milliseconds at the UTC time `utc_ms`.
+    pub(crate) fn utc_offset_ms(&self, utc_ms: i64) -> i64 {
+        match &self.0 {
+            TimeZoneKind::Fixed(offset_ms) => *offset_ms,
+            TimeZoneKind::Named(named_time_zone) => {
+                // Dates beyond the range of chrono use the offset at the end of the range.
+                let utc_ms = utc_ms.clamp(
+                    DateTime::<Utc>::MIN_UTC.timestamp_millis(),
+                    DateTime::<Utc>::MAX_UTC.timestamp_millis(),
+                );
+                let utc_datetime = DateTime::from_timestamp_millis(utc_ms)
+                    .expect("timestamp clamped to the range of chrono")
+                    .naive_utc();
+                let offset = named_time_zone.offset_from_utc_datetime(&utc_datetime);
+                i64::from(offset.fix().local_minus_utc()) * 1_000
+            }
+        }
+    }
+
+    /// Converts a local time to UTC.
+    ///
+    /// Local times skipped when the offset increases are shifted by the increase, and local
+    /// times repeated when the offset decreases are resolved to one of their occurrences.
+    pub(crate) fn local_to_utc_ms(&self, local_ms: i64) -> i64 {
+        let guess_ms = local_ms - self.utc_offset_ms(local_ms);
+        local_ms - self.utc_offset_ms(guess_ms)
+    }
+}
+
+impl fmt::Debug for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            TimeZoneKind::Fixed(offset_ms) => write!(f, "TimeZone({offset_ms}ms)"),
+            TimeZoneKind::Named(named_time_zone) => {
+                write!(f, "TimeZone({})", named_time_zone.name())
+            }
+        }
+    }
+}
+
+/// Parses a fixed UTC offset, like `+01:00`, into milliseconds.
+pub(crate) fn parse_time_zone_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
+    let invalid_time_zone = || DateHistogramParseError::InvalidTimeZone(input.to_string());
+    if input == "UTC" || input == "Z" {
+        return Ok(0);
+    }
+    let (sign, offset) = if let Some(offset) = input.strip_prefix('+') {
+        (1, offset)
+    } else if let Some(offset) = input.strip_prefix('-') {
+        (-1, offset)
+    } else {
+        return Err(invalid_time_zone().into());
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "00"));
+    let parse_two_digits = |digits: &str| {
+        if digits.len() == 2 && digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            digits.parse::<i64>().ok()
+        } else {
+            None
+        }
+    };
+    let hours = parse_two_digits(hours).ok_or_else(invalid_time_zone)?;
+    let minutes = parse_two_digits(minutes).ok_or_else(invalid_time_zone)?;
+    if hours > 18 || minutes >= 60 {
+        return Err(invalid_time_zone().into());
+    }
+    Ok(sign * (hours * HOUR_MS + minutes * MINUTE_MS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc_ms(rfc3339: &str) -> i64 {
+        let datetime =
+            time::OffsetDateTime::parse(rfc3339, &time::format_description::well_known::Rfc3339)
+                .unwrap();
+        (datetime.unix_timestamp_nanos() / 1_000_000) as i64
+    }
+
+    #[test]
+    fn test_parse_time_zone_into_milliseconds() {
+        assert_eq!(parse_time_zone_into_milliseconds("UTC").unwrap(), 0);
+        assert_eq!(
+            parse_time_zone_into_milliseconds("+01:00").unwrap(),
+            3_600_000
+        );
+        assert_eq!(
+            parse_time_zone_into_milliseconds("-05:30").unwrap(),
+            -19_800_000
+        );
+        assert_eq!(parse_time_zone_into_milliseconds("+02").unwrap(), 7_200_000);
+        assert_eq!(
+            parse_time_zone_into_milliseconds("+1:00").unwrap_err(),
+            DateHistogramParseError::InvalidTimeZone("+1:00".to_string()).into()
+        );
+    }
+
+    #[test]
+    fn test_named_time_zone() {
+        let paris = TimeZone::parse("Europe/Paris").unwrap();
+        assert_eq!(paris.fixed_offset_ms(), None);
+        assert_eq!(paris.utc_offset_ms(utc_ms("2024-01-15T12:00:00Z")), HOUR_MS);
+        assert_eq!(
+            paris.utc_offset_ms(utc_ms("2024-07-15T12:00:00Z")),
+            2 * HOUR_MS
+        );
+        assert_eq!(
+            paris.utc_offset_ms(utc_ms("2050-07-15T12:00:00Z")),
+            2 * HOUR_MS
+        );
+        assert_eq!(
+            paris.local_to_utc_ms(utc_ms("2024-07-15T00:00:00Z")),
+            utc_ms("2024-07-14T22:00:00Z")
+        );
+        assert_eq!(paris, TimeZone::parse("Europe/Paris").unwrap());
+        assert_ne!(paris, TimeZone::parse("+01:00").unwrap());
+    }
+
+    #[test]
+    fn test_named_time_zone_transitions() {
+        let new_york = TimeZone::parse("America/New_York").unwrap();
+        // Daylight saving time starts on March 10th 2024 at 2am EST, and ends on November 3rd
+        // at 2am EDT.
+        assert_eq!(
+            new_york.utc_offset_ms(utc_ms("2024-03-10T06:59:59Z")),
+            -5 * HOUR_MS
+        );
+        assert_eq!(
+            new_york.utc_offset_ms(utc_ms("2024-03-10T07:00:00Z")),
+            -4 * HOUR_MS
+        );
+        assert_eq!(
+            new_york.utc_offset_ms(utc_ms("2024-11-03T05:59:59Z")),
+            -4 * HOUR_MS
+        );
+        assert_eq!(
+            new_york.utc_offset_ms(utc_ms("2024-11-03T06:00:00Z")),
+            -5 * HOUR_MS
+        );
+        // 2:30am does not exist on March 10th, and is shifted back by the hour skipped.
+        assert_eq!(
+            new_york.local_to_utc_ms(utc_ms("2024-03-10T02:30:00Z")),
+            utc_ms("2024-03-10T06:30:00Z")
+        );
+
+        // Daylight saving time spans the end of the year in the southern hemisphere.
+        let auckland = TimeZone::parse("Pacific/Auckland").unwrap();
+        assert_eq!(
+            auckland.utc_offset_ms(utc_ms("2024-01-15T00:00:00Z")),
+            13 * HOUR_MS
+        );
+        assert_eq!(
+            auckland.utc_offset_ms(utc_ms("2024-07-15T00:00:00Z")),
+            12 * HOUR_MS
+        );
+
+        let tehran = TimeZone::parse("Asia/Tehran").unwrap();
+        assert_eq!(
+            tehran.utc_offset_ms(utc_ms("2024-07-15T00:00:00Z")),
+            3 * HOUR_MS + 30 * MINUTE_MS
+        );
+
+        // Dates beyond the range of chrono use the offset at the end of the range.
+        assert_eq!(tehran.utc_offset_ms(i64::MAX), 3 * HOUR_MS + 30 * MINUTE_MS);
+        tehran.utc_offset_ms(i64::MIN);
+    }
+
+    #[test]
+    fn test_invalid_time_zone() {
+        for time_zone in ["Europe/Atlantis", "../../etc/passwd", "Europe//Paris", ""] {
+            assert_eq!(
+                TimeZone::parse(time_zone).unwrap_err(),
+                DateHistogramParseError::InvalidTimeZone(time_zone.to_string()).into()
+            );
+        }
+    }
+}
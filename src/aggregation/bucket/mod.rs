@@ -21,7 +21,12 @@
 //! - [DateHistogram](DateHistogramAggregationReq)
 //! - [Range](RangeAggregation)
 //! - [Terms](TermsAggregation)
+//! - [Filter](FilterAggregation)
+//! - [Filters](FiltersAggregation)
+//! - [Composite](CompositeAggregation)
 
+mod composite;
+mod filter;
 mod histogram;
 mod range;
 mod term_agg;
@@ -30,6 +35,8 @@ mod term_missing_agg;
 use std::collections::HashMap;
 use std::fmt;
 
+pub use composite::*;
+pub use filter::*;
 pub use histogram::*;
 pub use range::*;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -317,6 +317,7 @@ impl SegmentRangeCollector {
         limits.add_memory_consumed(
             buckets.len() as u64 * std::mem::size_of::<SegmentRangeAndBucketEntry>() as u64,
         )?;
+        limits.add_buckets_collected(buckets.len() as u64)?;
 
         Ok(SegmentRangeCollector {
             buckets,
@@ -11,12 +11,14 @@ use serde::{Deserialize, Serialize};
 
 use super::{CustomOrder, Order, OrderTarget};
 use crate::aggregation::agg_limits::MemoryConsumption;
+use crate::aggregation::agg_req::AggregationVariants;
 use crate::aggregation::agg_req_with_accessor::{
     AggregationWithAccessor, AggregationsWithAccessor,
 };
 use crate::aggregation::intermediate_agg_result::{
-    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
-    IntermediateKey, IntermediateTermBucketEntry, IntermediateTermBucketResult,
+    GlobalOrdTermBuckets, IntermediateAggregationResult, IntermediateAggregationResults,
+    IntermediateBucketResult, IntermediateKey, IntermediateTermBucketEntry,
+    IntermediateTermBucketResult,
 };
 use crate::aggregation::segment_agg_result::{
     build_segment_agg_collector, SegmentAggregationCollector,
@@ -135,11 +137,14 @@ pub struct TermsAggregation {
     /// Single value metrics like average can be addressed by its name.
     /// Multi value metrics like stats are required to address their field by name e.g.
     /// "stats.avg"
+    /// Metrics nested in a single bucket `filter` sub_aggregation are addressed with `>`, e.g.
+    /// "errors>average_price", and its document count with "errors>_count".
     ///
     /// Examples in JSON format:
     /// { "_count": "asc" }
     /// { "_key": "asc" }
     /// { "average_price": "asc" }
+    /// { "errors>_count": "desc" }
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub order: Option<CustomOrder>,
 
@@ -256,11 +261,42 @@ pub struct SegmentTermCollector {
     accessor_idx: usize,
 }
 
+/// Splits the path of a sub_aggregation into the name of the aggregation and the remaining path.
+///
+/// The remaining path is either the property of a metric aggregation, e.g. "stats.avg", or the
+/// path in a single bucket aggregation, e.g. "errors>stats.avg".
 pub(crate) fn get_agg_name_and_property(name: &str) -> (&str, &str) {
+    if let Some((agg_name, agg_property)) = name.split_once('>') {
+        if !agg_name.contains('.') {
+            return (agg_name, agg_property);
+        }
+    }
     let (agg_name, agg_property) = name.split_once('.').unwrap_or((name, ""));
     (agg_name, agg_property)
 }
 
+/// Validates that the path of a sub_aggregation used for ordering exists.
+///
+/// Single bucket aggregations can be traversed with `>`, e.g. "errors>avg_price", and their
+/// document count is addressed with `_count`, e.g. "errors>_count".
+fn validate_sub_aggregation_path(
+    path: &str,
+    sub_aggregations: &AggregationsWithAccessor,
+) -> crate::Result<()> {
+    let (agg_name, agg_property) = get_agg_name_and_property(path);
+    let agg = sub_aggregations.aggs.get(agg_name).ok_or_else(|| {
+        TantivyError::InvalidArgument(format!(
+            "could not find aggregation with name {agg_name} in metric sub_aggregations"
+        ))
+    })?;
+    match &agg.agg.agg {
+        AggregationVariants::Filter(_) if agg_property != "_count" => {
+            validate_sub_aggregation_path(agg_property, &agg.sub_aggregation)
+        }
+        _ => Ok(()),
+    }
+}
+
 impl SegmentAggregationCollector for SegmentTermCollector {
     fn add_intermediate_aggregation_result(
         self: Box<Self>,
@@ -294,6 +330,7 @@ impl SegmentAggregationCollector for SegmentTermCollector {
         let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
 
         let mem_pre = self.get_memory_consumption();
+        let num_buckets_pre = self.term_buckets.entries.len();
 
         if let Some(missing) = bucket_agg_accessor.missing_value_for_accessor {
             bucket_agg_accessor
@@ -305,8 +342,26 @@ impl SegmentAggregationCollector for SegmentTermCollector {
                 .fetch_block(docs, &bucket_agg_accessor.accessor);
         }
 
+        // With global ordinals, the buckets of `str` columns are keyed by global ordinal.
+        let global_ords: Option<&[u32]> =
+            bucket_agg_accessor
+                .global_ordinals
+                .as_ref()
+                .map(|global_ordinals| {
+                    global_ordinals.segment_mapping(bucket_agg_accessor.segment_ordinal)
+                });
+        let bucket_id = |term_id: u64| match global_ords {
+            // `u64::MAX` is the placeholder of the `missing` value.
+            Some(global_ords) if term_id != u64::MAX => global_ords[term_id as usize] as u64,
+            _ => term_id,
+        };
+
         for term_id in bucket_agg_accessor.column_block_accessor.iter_vals() {
-            let entry = self.term_buckets.entries.entry(term_id).or_default();
+            let entry = self
+                .term_buckets
+                .entries
+                .entry(bucket_id(term_id))
+                .or_default();
             *entry += 1;
         }
         // has subagg
@@ -315,6 +370,7 @@ impl SegmentAggregationCollector for SegmentTermCollector {
                 .column_block_accessor
                 .iter_docid_vals(docs, &bucket_agg_accessor.accessor)
             {
+                let term_id = bucket_id(term_id);
                 let sub_aggregations = self
                     .term_buckets
                     .sub_aggs
@@ -330,6 +386,12 @@ impl SegmentAggregationCollector for SegmentTermCollector {
                 .limits
                 .add_memory_consumed(mem_delta as u64)?;
         }
+        let num_buckets_delta = self.term_buckets.entries.len() - num_buckets_pre;
+        if num_buckets_delta > 0 {
+            bucket_agg_accessor
+                .limits
+                .add_buckets_collected(num_buckets_delta as u64)?;
+        }
 
         Ok(())
     }
@@ -366,14 +428,7 @@ impl SegmentTermCollector {
         if let Some(custom_order) = req.order.as_ref() {
             // Validate sub aggregation exists
             if let OrderTarget::SubAggregation(sub_agg_name) = &custom_order.target {
-                let (agg_name, _agg_property) = get_agg_name_and_property(sub_agg_name);
-
-                sub_aggregations.aggs.get(agg_name).ok_or_else(|| {
-                    TantivyError::InvalidArgument(format!(
-                        "could not find aggregation with name {agg_name} in metric \
-                         sub_aggregations"
-                    ))
-                })?;
+                validate_sub_aggregation_path(sub_agg_name, sub_aggregations)?;
             }
         }
 
@@ -437,6 +492,7 @@ impl SegmentTermCollector {
 
         let mut dict: FxHashMap<IntermediateKey, IntermediateTermBucketEntry> = Default::default();
         dict.reserve(entries.len());
+        let mut global_ord_buckets = None;
 
         let mut into_intermediate_bucket_entry =
             |id, doc_count| -> crate::Result<IntermediateTermBucketEntry> {
@@ -510,48 +566,84 @@ impl SegmentTermCollector {
                 entries.swap_remove(index);
             }
 
-            // Sort by term ord
-            entries.sort_unstable_by_key(|bucket| bucket.0);
-            let mut idx = 0;
-            term_dict.sorted_ords_to_term_cb(
-                entries.iter().map(|(term_id, _)| *term_id),
-                |term| {
-                    let entry = entries[idx];
-                    let intermediate_entry = into_intermediate_bucket_entry(entry.0, entry.1)
-                        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-                    dict.insert(
-                        IntermediateKey::Str(
-                            String::from_utf8(term.to_vec()).expect("could not convert to String"),
-                        ),
-                        intermediate_entry,
+            if let Some(global_ordinals) = agg_with_accessor.global_ordinals.as_ref() {
+                // The terms are resolved once the buckets of all of the segments are merged.
+                let mut global_ord_entries: FxHashMap<u32, IntermediateTermBucketEntry> =
+                    Default::default();
+                global_ord_entries.reserve(entries.len());
+                for (global_ord, doc_count) in entries {
+                    let intermediate_entry = into_intermediate_bucket_entry(global_ord, doc_count)?;
+                    global_ord_entries.insert(global_ord as u32, intermediate_entry);
+                }
+                if self.req.min_doc_count == 0 {
+                    let empty_sub_aggregation = IntermediateAggregationResults::empty_from_req(
+                        agg_with_accessor.agg.sub_aggregation(),
                     );
-                    idx += 1;
-                    Ok(())
-                },
-            )?;
-
-            if self.req.min_doc_count == 0 {
-                // TODO: Handle rev streaming for descending sorting by keys
-                let mut stream = term_dict.stream()?;
-                let empty_sub_aggregation = IntermediateAggregationResults::empty_from_req(
-                    agg_with_accessor.agg.sub_aggregation(),
-                );
-                while let Some((key, _ord)) = stream.next() {
-                    if dict.len() >= self.req.segment_size as usize {
-                        break;
+                    let global_ords =
+                        global_ordinals.segment_mapping(agg_with_accessor.segment_ordinal);
+                    for &global_ord in global_ords {
+                        if dict.len() + global_ord_entries.len() >= self.req.segment_size as usize {
+                            break;
+                        }
+                        global_ord_entries.entry(global_ord).or_insert_with(|| {
+                            IntermediateTermBucketEntry {
+                                doc_count: 0,
+                                sub_aggregation: empty_sub_aggregation.clone(),
+                            }
+                        });
                     }
+                }
+                global_ord_buckets = Some(GlobalOrdTermBuckets {
+                    global_ordinals: global_ordinals.clone(),
+                    entries: global_ord_entries,
+                });
+            } else {
+                // Sort by term ord
+                entries.sort_unstable_by_key(|bucket| bucket.0);
+                let mut idx = 0;
+                term_dict.sorted_ords_to_term_cb(
+                    entries.iter().map(|(term_id, _)| *term_id),
+                    |term| {
+                        let entry = entries[idx];
+                        let intermediate_entry = into_intermediate_bucket_entry(entry.0, entry.1)
+                            .map_err(io::Error::other)?;
+                        dict.insert(
+                            IntermediateKey::Str(
+                                String::from_utf8(term.to_vec())
+                                    .expect("could not convert to String"),
+                            ),
+                            intermediate_entry,
+                        );
+                        idx += 1;
+                        Ok(())
+                    },
+                )?;
 
-                    let key = IntermediateKey::Str(
-                        std::str::from_utf8(key)
-                            .map_err(|utf8_err| DataCorruption::comment_only(utf8_err.to_string()))?
-                            .to_string(),
+                if self.req.min_doc_count == 0 {
+                    // TODO: Handle rev streaming for descending sorting by keys
+                    let mut stream = term_dict.stream()?;
+                    let empty_sub_aggregation = IntermediateAggregationResults::empty_from_req(
+                        agg_with_accessor.agg.sub_aggregation(),
                     );
+                    while let Some((key, _ord)) = stream.next() {
+                        if dict.len() >= self.req.segment_size as usize {
+                            break;
+                        }
 
-                    dict.entry(key.clone())
-                        .or_insert_with(|| IntermediateTermBucketEntry {
-                            doc_count: 0,
-                            sub_aggregation: empty_sub_aggregation.clone(),
-                        });
+                        let key = IntermediateKey::Str(
+                            std::str::from_utf8(key)
+                                .map_err(|utf8_err| {
+                                    DataCorruption::comment_only(utf8_err.to_string())
+                                })?
+                                .to_string(),
+                        );
+
+                        dict.entry(key.clone())
+                            .or_insert_with(|| IntermediateTermBucketEntry {
+                                doc_count: 0,
+                                sub_aggregation: empty_sub_aggregation.clone(),
+                            });
+                    }
                 }
             }
         } else if self.column_type == ColumnType::DateTime {
@@ -617,6 +709,7 @@ impl SegmentTermCollector {
         Ok(IntermediateBucketResult::Terms {
             buckets: IntermediateTermBucketResult {
                 entries: dict,
+                global_ord_entries: global_ord_buckets,
                 sum_other_doc_count,
                 doc_count_error_upper_bound: term_doc_count_before_cutoff,
             },
@@ -665,15 +758,116 @@ mod tests {
     use time::{Date, Month};
 
     use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::intermediate_agg_result::{
+        IntermediateAggregationResult, IntermediateBucketResult,
+    };
     use crate::aggregation::tests::{
         exec_request, exec_request_with_query, exec_request_with_query_and_memory_limit,
         get_test_index_from_terms, get_test_index_from_values_and_terms,
     };
-    use crate::aggregation::AggregationLimitsGuard;
+    use crate::aggregation::{AggregationCollector, AggregationLimitsGuard};
+    use crate::collector::{Collector, SegmentCollector};
     use crate::indexer::NoMergePolicy;
+    use crate::query::AllQuery;
     use crate::schema::{IntoIpv6Addr, Schema, FAST, STRING};
     use crate::{Index, IndexWriter};
 
+    #[test]
+    fn terms_aggregation_global_ordinals() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING | FAST);
+        let score_field = schema_builder.add_u64_field("score", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        // The terms and their counts differ from one segment to the other, so that their term
+        // ordinals differ too.
+        let segments: [&[(&str, u64)]; 3] = [
+            &[("a", 5), ("b", 3), ("c", 1)],
+            &[("b", 4), ("d", 2), ("e", 6)],
+            &[("a", 1), ("e", 2), ("f", 9), ("", 3)],
+        ];
+        for segment in segments {
+            for &(tag, num_docs) in segment {
+                for score in 0..num_docs {
+                    if tag.is_empty() {
+                        index_writer.add_document(doc!(score_field => score))?;
+                    } else {
+                        index_writer.add_document(doc!(tag_field => tag, score_field => score))?;
+                    }
+                }
+            }
+            index_writer.commit()?;
+        }
+
+        let requests = [
+            json!({ "my_terms": { "terms": { "field": "tag" } } }),
+            json!({ "my_terms": { "terms": {
+                "field": "tag", "size": 2, "segment_size": 2, "order": { "_key": "desc" }
+            } } }),
+            json!({ "my_terms": {
+                "terms": {
+                    "field": "tag", "size": 3, "segment_size": 3, "order": { "_count": "asc" }
+                },
+                "aggs": { "avg_score": { "avg": { "field": "score" } } }
+            } }),
+            json!({ "my_terms": {
+                "terms": { "field": "tag", "order": { "avg_score": "desc" } },
+                "aggs": { "avg_score": { "avg": { "field": "score" } } }
+            } }),
+            json!({ "my_terms": { "terms": {
+                "field": "tag", "min_doc_count": 0, "missing": "zzz"
+            } } }),
+            json!({ "my_terms": {
+                "terms": { "field": "tag", "missing": "b" },
+                "aggs": { "nested_terms": { "terms": { "field": "tag" } } }
+            } }),
+        ];
+        let searcher = index.reader()?.searcher();
+        for request in requests {
+            let agg_req: Aggregations = serde_json::from_value(request)?;
+            let collector = AggregationCollector::from_aggs(agg_req.clone(), Default::default());
+            let global_ordinals_collector =
+                AggregationCollector::from_aggs(agg_req, Default::default())
+                    .with_global_ordinals(&searcher);
+            let res = searcher.search(&AllQuery, &collector)?;
+            let global_ordinals_res = searcher.search(&AllQuery, &global_ordinals_collector)?;
+            assert_eq!(
+                serde_json::to_value(&global_ordinals_res)?,
+                serde_json::to_value(&res)?
+            );
+        }
+
+        // The buckets of a segment are keyed by global ordinal, and their terms are not resolved.
+        let agg_req: Aggregations =
+            serde_json::from_value(json!({ "my_terms": { "terms": { "field": "tag" } } }))?;
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default())
+            .with_global_ordinals(&searcher);
+        let segment_reader = searcher.segment_reader(1);
+        let mut segment_collector = collector.for_segment(1, segment_reader)?;
+        for doc in 0..segment_reader.max_doc() {
+            segment_collector.collect(doc, 0.0);
+        }
+        let intermediate_res = segment_collector.harvest()?;
+        let Some(IntermediateAggregationResult::Bucket(IntermediateBucketResult::Terms {
+            buckets,
+        })) = intermediate_res.aggs_res.get("my_terms")
+        else {
+            panic!("expected an intermediate terms result");
+        };
+        assert!(buckets.entries.is_empty());
+        let global_ord_entries = buckets.global_ord_entries.as_ref().unwrap();
+        let mut doc_counts: Vec<(u32, u32)> = global_ord_entries
+            .entries
+            .iter()
+            .map(|(global_ord, entry)| (*global_ord, entry.doc_count))
+            .collect();
+        doc_counts.sort();
+        // The global ordinals of "b", "d" and "e" among "a" to "f".
+        assert_eq!(doc_counts, [(1, 4), (3, 2), (4, 6)]);
+        Ok(())
+    }
+
     #[test]
     fn terms_aggregation_test_single_segment() -> crate::Result<()> {
         terms_aggregation_test_merge_segment(true)
@@ -1058,6 +1252,72 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn terms_aggregation_test_order_filter_sub_agg() -> crate::Result<()> {
+        let segment_and_terms = vec![
+            vec![(5.0, "terma".to_string())],
+            vec![(4.0, "termb".to_string())],
+            vec![(1.0, "termc".to_string())],
+            vec![(1.0, "termc".to_string())],
+            vec![(1.0, "termc".to_string())],
+            vec![(5.0, "terma".to_string())],
+            vec![(5.0, "terma".to_string())],
+            vec![(8.0, "termb".to_string())],
+        ];
+        let index = get_test_index_from_values_and_terms(false, &segment_and_terms)?;
+
+        let terms_agg_with_order = |order: serde_json::Value| -> Aggregations {
+            serde_json::from_value(json!({
+                "my_texts": {
+                    "terms": {
+                        "field": "string_id",
+                        "order": order
+                    },
+                    "aggs": {
+                        "high": {
+                            "filter": { "query": "score:[4 TO 10]" },
+                            "aggs": {
+                                "avg_score": { "avg": { "field": "score" } }
+                            }
+                        }
+                    }
+                }
+            }))
+            .unwrap()
+        };
+
+        let res = exec_request(
+            terms_agg_with_order(json!({ "high>_count": "desc" })),
+            &index,
+        )?;
+        assert_eq!(res["my_texts"]["buckets"][0]["key"], "terma");
+        assert_eq!(res["my_texts"]["buckets"][0]["high"]["doc_count"], 3);
+        assert_eq!(res["my_texts"]["buckets"][1]["key"], "termb");
+        assert_eq!(res["my_texts"]["buckets"][1]["high"]["doc_count"], 2);
+        assert_eq!(res["my_texts"]["buckets"][2]["key"], "termc");
+        assert_eq!(res["my_texts"]["buckets"][2]["high"]["doc_count"], 0);
+
+        let res = exec_request(
+            terms_agg_with_order(json!({ "high>avg_score": "desc" })),
+            &index,
+        )?;
+        assert_eq!(res["my_texts"]["buckets"][0]["key"], "termb");
+        assert_eq!(
+            res["my_texts"]["buckets"][0]["high"]["avg_score"]["value"],
+            6.0
+        );
+        assert_eq!(res["my_texts"]["buckets"][1]["key"], "terma");
+        assert_eq!(res["my_texts"]["buckets"][2]["key"], "termc");
+
+        let res = exec_request(
+            terms_agg_with_order(json!({ "high>doesnotexist": "desc" })),
+            &index,
+        );
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn terms_aggregation_test_order_key_single_segment() -> crate::Result<()> {
         terms_aggregation_test_order_key_merge_segment(true)
@@ -2080,8 +2340,10 @@ mod tests {
         // println!("{}", serde_json::to_string_pretty(&res).unwrap());
 
         assert_eq!(res["my_bool"]["buckets"][0]["key"], "::1");
+        assert_eq!(res["my_bool"]["buckets"][0]["key_as_string"], "::1");
         assert_eq!(res["my_bool"]["buckets"][0]["doc_count"], 2);
         assert_eq!(res["my_bool"]["buckets"][1]["key"], "127.0.0.1");
+        assert_eq!(res["my_bool"]["buckets"][1]["key_as_string"], "127.0.0.1");
         assert_eq!(res["my_bool"]["buckets"][1]["doc_count"], 1);
         assert_eq!(res["my_bool"]["buckets"][2]["key"], serde_json::Value::Null);
 
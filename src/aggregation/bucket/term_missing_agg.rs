@@ -75,6 +75,7 @@ impl SegmentAggregationCollector for TermMissingAgg {
         let bucket = IntermediateBucketResult::Terms {
             buckets: IntermediateTermBucketResult {
                 entries,
+                global_ord_entries: None,
                 sum_other_doc_count: 0,
                 doc_count_error_upper_bound: 0,
             },
@@ -1,4 +1,4 @@
-use super::agg_req::Aggregations;
+use super::agg_req::{get_aggregation_depth, Aggregations};
 use super::agg_req_with_accessor::AggregationsWithAccessor;
 use super::agg_result::AggregationResults;
 use super::buf_collector::BufAggregationCollector;
@@ -6,10 +6,12 @@ use super::intermediate_agg_result::IntermediateAggregationResults;
 use super::segment_agg_result::{
     build_segment_agg_collector, AggregationLimitsGuard, SegmentAggregationCollector,
 };
-use crate::aggregation::agg_req_with_accessor::get_aggs_with_segment_accessor_and_validate;
+use crate::aggregation::agg_req_with_accessor::{
+    attach_global_ordinals, get_aggs_with_segment_accessor_and_validate,
+};
 use crate::collector::{Collector, SegmentCollector};
 use crate::index::SegmentReader;
-use crate::{DocId, SegmentOrdinal, TantivyError};
+use crate::{DocId, Searcher, SegmentOrdinal, TantivyError};
 
 /// The default max bucket count, before the aggregation fails.
 pub const DEFAULT_BUCKET_LIMIT: u32 = 65000;
@@ -23,6 +25,7 @@ pub const DEFAULT_MEMORY_LIMIT: u64 = 500_000_000;
 pub struct AggregationCollector {
     agg: Aggregations,
     limits: AggregationLimitsGuard,
+    searcher: Option<Searcher>,
 }
 
 impl AggregationCollector {
@@ -31,7 +34,25 @@ impl AggregationCollector {
     /// Aggregation fails when the limits in `AggregationLimits` is exceeded. (memory limit and
     /// bucket limit)
     pub fn from_aggs(agg: Aggregations, limits: AggregationLimitsGuard) -> Self {
-        Self { agg, limits }
+        Self {
+            agg,
+            limits,
+            searcher: None,
+        }
+    }
+
+    /// Buckets the `terms` aggregations on `str` fields by the
+    /// [`GlobalOrdinals`](crate::fastfield::GlobalOrdinals) of `searcher`.
+    ///
+    /// The term ordinals of the segments are mapped to global ordinals while collecting, the
+    /// buckets of the segments are merged on their global ordinals, and only the terms of the
+    /// returned buckets are resolved. The global ordinals
+    /// are built on the first use, and shared by all of the later aggregations on the same
+    /// searcher generation. The collector must be run on `searcher`: the segments of other
+    /// searchers are collected without global ordinals.
+    pub fn with_global_ordinals(mut self, searcher: &Searcher) -> Self {
+        self.searcher = Some(searcher.clone());
+        self
     }
 }
 
@@ -98,11 +119,12 @@ impl Collector for AggregationCollector {
         segment_local_id: crate::SegmentOrdinal,
         reader: &crate::SegmentReader,
     ) -> crate::Result<Self::Child> {
-        AggregationSegmentCollector::from_agg_req_and_reader(
+        AggregationSegmentCollector::from_agg_req_reader_and_searcher(
             &self.agg,
             reader,
             segment_local_id,
             &self.limits,
+            self.searcher.as_ref(),
         )
     }
 
@@ -122,6 +144,12 @@ impl Collector for AggregationCollector {
 fn merge_fruits(
     mut segment_fruits: Vec<crate::Result<IntermediateAggregationResults>>,
 ) -> crate::Result<IntermediateAggregationResults> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!(
+        "tantivy_merge_aggregations",
+        num_segments = segment_fruits.len()
+    )
+    .entered();
     if let Some(fruit) = segment_fruits.pop() {
         let mut fruit = fruit?;
         for next_fruit in segment_fruits {
@@ -149,8 +177,22 @@ impl AggregationSegmentCollector {
         segment_ordinal: SegmentOrdinal,
         limits: &AggregationLimitsGuard,
     ) -> crate::Result<Self> {
+        Self::from_agg_req_reader_and_searcher(agg, reader, segment_ordinal, limits, None)
+    }
+
+    fn from_agg_req_reader_and_searcher(
+        agg: &Aggregations,
+        reader: &SegmentReader,
+        segment_ordinal: SegmentOrdinal,
+        limits: &AggregationLimitsGuard,
+        searcher: Option<&Searcher>,
+    ) -> crate::Result<Self> {
+        limits.validate_depth(get_aggregation_depth(agg))?;
         let mut aggs_with_accessor =
             get_aggs_with_segment_accessor_and_validate(agg, reader, segment_ordinal, limits)?;
+        if let Some(searcher) = searcher {
+            attach_global_ordinals(&mut aggs_with_accessor, searcher, reader)?;
+        }
         let result =
             BufAggregationCollector::new(build_segment_agg_collector(&mut aggs_with_accessor)?);
         Ok(AggregationSegmentCollector {
@@ -1,14 +1,31 @@
 use time::format_description::well_known::Rfc3339;
-use time::OffsetDateTime;
+use time::{OffsetDateTime, UtcOffset};
 
 use crate::TantivyError;
 
 pub(crate) fn format_date(val: i64) -> crate::Result<String> {
-    let datetime = OffsetDateTime::from_unix_timestamp_nanos(val as i128).map_err(|err| {
+    format_date_with_offset(val, None)
+}
+
+/// Formats the date in the time zone with the given UTC offset, in milliseconds.
+pub(crate) fn format_date_with_offset(
+    val: i64,
+    utc_offset_ms: Option<i64>,
+) -> crate::Result<String> {
+    let mut datetime = OffsetDateTime::from_unix_timestamp_nanos(val as i128).map_err(|err| {
         TantivyError::InvalidArgument(format!(
             "Could not convert {val:?} to OffsetDateTime, err {err:?}"
         ))
     })?;
+    if let Some(utc_offset_ms) = utc_offset_ms {
+        let utc_offset =
+            UtcOffset::from_whole_seconds((utc_offset_ms / 1000) as i32).map_err(|err| {
+                TantivyError::InvalidArgument(format!(
+                    "Invalid UTC offset {utc_offset_ms:?}, err {err:?}"
+                ))
+            })?;
+        datetime = datetime.to_offset(utc_offset);
+    }
     let key_as_string = datetime
         .format(&Rfc3339)
         .map_err(|_err| TantivyError::InvalidArgument("Could not serialize date".to_string()))?;
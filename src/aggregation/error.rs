@@ -36,4 +36,15 @@ pub enum AggregationError {
         /// Current num buckets
         current: u32,
     },
+    /// Aggregation nesting depth exceeded
+    #[error(
+        "Aborting aggregation because depth limit was exceeded. Limit: {limit:?}, Current: \
+         {current:?}"
+    )]
+    DepthLimitExceeded {
+        /// Depth limit
+        limit: u32,
+        /// Depth of the aggregation request
+        current: u32,
+    },
 }
@@ -6,6 +6,8 @@ use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
 use std::hash::Hash;
 use std::net::Ipv6Addr;
+use std::sync::Arc;
+use std::{fmt, io};
 
 use columnar::ColumnType;
 use itertools::Itertools;
@@ -13,20 +15,26 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
 use super::agg_req::{Aggregation, AggregationVariants, Aggregations};
-use super::agg_result::{AggregationResult, BucketResult, MetricResult, RangeBucketEntry};
+use super::agg_result::{
+    AggregationResult, BucketResult, CompositeBucketEntry, FilterBucketEntry, MetricResult,
+    RangeBucketEntry,
+};
 use super::bucket::{
-    cut_off_buckets, get_agg_name_and_property, intermediate_histogram_buckets_to_final_buckets,
+    cmp_composite_keys, cmp_composite_keys_asc, cut_off_buckets, get_agg_name_and_property,
+    intermediate_histogram_buckets_to_final_buckets, CompositeAggregation, CustomOrder,
     GetDocCount, Order, OrderTarget, RangeAggregation, TermsAggregation,
 };
 use super::metric::{
-    IntermediateAverage, IntermediateCount, IntermediateExtendedStats, IntermediateMax,
-    IntermediateMin, IntermediateStats, IntermediateSum, PercentilesCollector, TopHitsTopNComputer,
+    IntermediateAverage, IntermediateCount, IntermediateExtendedStats, IntermediateGeoStats,
+    IntermediateMax, IntermediateMin, IntermediateRate, IntermediateStats, IntermediateSum,
+    IntermediateTopMetrics, PercentilesCollector, TopHitsTopNComputer,
 };
 use super::segment_agg_result::AggregationLimitsGuard;
 use super::{format_date, AggregationError, Key, SerializedKey};
 use crate::aggregation::agg_result::{AggregationResults, BucketEntries, BucketEntry};
 use crate::aggregation::bucket::TermsAggregationInternal;
 use crate::aggregation::metric::CardinalityCollector;
+use crate::fastfield::GlobalOrdinals;
 use crate::TantivyError;
 
 /// Contains the intermediate aggregation result, which is optimized to be merged with other
@@ -70,14 +78,7 @@ impl From<IntermediateKey> for Key {
     fn from(value: IntermediateKey) -> Self {
         match value {
             IntermediateKey::Str(s) => Self::Str(s),
-            IntermediateKey::IpAddr(s) => {
-                // Prefer to use the IPv4 representation if possible
-                if let Some(ip) = s.to_ipv4_mapped() {
-                    Self::Str(ip.to_string())
-                } else {
-                    Self::Str(s.to_string())
-                }
-            }
+            IntermediateKey::IpAddr(s) => Self::Str(format_ip_addr(s)),
             IntermediateKey::F64(f) => Self::F64(f),
             IntermediateKey::Bool(f) => Self::U64(f as u64),
             IntermediateKey::U64(f) => Self::U64(f),
@@ -86,6 +87,16 @@ impl From<IntermediateKey> for Key {
     }
 }
 
+/// Formats an IP address in dotted notation for IPv4 and colon notation for IPv6.
+fn format_ip_addr(ip_addr: Ipv6Addr) -> String {
+    // Prefer to use the IPv4 representation if possible
+    if let Some(ip) = ip_addr.to_ipv4_mapped() {
+        ip.to_string()
+    } else {
+        ip_addr.to_string()
+    }
+}
+
 impl Eq for IntermediateKey {}
 
 impl std::hash::Hash for IntermediateKey {
@@ -178,6 +189,77 @@ impl IntermediateAggregationResults {
         Self { aggs_res }
     }
 
+    /// Sets the duration of the date histogram bucket holding these results on the `rate`
+    /// aggregations, including the ones in sub-buckets.
+    pub(crate) fn set_rate_bucket_duration_ns(&mut self, bucket_duration_ns: f64) {
+        for agg_res in self.aggs_res.values_mut() {
+            match agg_res {
+                IntermediateAggregationResult::Metric(IntermediateMetricResult::Rate(rate)) => {
+                    rate.set_bucket_duration_ns(bucket_duration_ns);
+                }
+                IntermediateAggregationResult::Metric(_) => {}
+                IntermediateAggregationResult::Bucket(IntermediateBucketResult::Range(
+                    range_res,
+                )) => {
+                    for bucket in range_res.buckets.values_mut() {
+                        bucket
+                            .sub_aggregation
+                            .set_rate_bucket_duration_ns(bucket_duration_ns);
+                    }
+                }
+                // A nested date histogram sets the duration of its own buckets afterwards.
+                IntermediateAggregationResult::Bucket(IntermediateBucketResult::Histogram {
+                    buckets,
+                    ..
+                }) => {
+                    for bucket in buckets {
+                        bucket
+                            .sub_aggregation
+                            .set_rate_bucket_duration_ns(bucket_duration_ns);
+                    }
+                }
+                IntermediateAggregationResult::Bucket(IntermediateBucketResult::Terms {
+                    buckets,
+                }) => {
+                    let global_ord_entries = buckets
+                        .global_ord_entries
+                        .iter_mut()
+                        .flat_map(|global_ord_entries| global_ord_entries.entries.values_mut());
+                    for bucket in buckets.entries.values_mut().chain(global_ord_entries) {
+                        bucket
+                            .sub_aggregation
+                            .set_rate_bucket_duration_ns(bucket_duration_ns);
+                    }
+                }
+                IntermediateAggregationResult::Bucket(IntermediateBucketResult::Filter {
+                    bucket,
+                }) => {
+                    bucket
+                        .sub_aggregation
+                        .set_rate_bucket_duration_ns(bucket_duration_ns);
+                }
+                IntermediateAggregationResult::Bucket(IntermediateBucketResult::Filters {
+                    buckets,
+                }) => {
+                    for bucket in buckets.values_mut() {
+                        bucket
+                            .sub_aggregation
+                            .set_rate_bucket_duration_ns(bucket_duration_ns);
+                    }
+                }
+                IntermediateAggregationResult::Bucket(IntermediateBucketResult::Composite {
+                    buckets,
+                }) => {
+                    for bucket in buckets {
+                        bucket
+                            .sub_aggregation
+                            .set_rate_bucket_duration_ns(bucket_duration_ns);
+                    }
+                }
+            }
+        }
+    }
+
     /// Merge another intermediate aggregation result into this result.
     ///
     /// The order of the values need to be the same on both results. This is ensured when the same
@@ -199,6 +281,23 @@ pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult
         Range(_) => IntermediateAggregationResult::Bucket(IntermediateBucketResult::Range(
             Default::default(),
         )),
+        Filter(_) => IntermediateAggregationResult::Bucket(IntermediateBucketResult::Filter {
+            bucket: Default::default(),
+        }),
+        Filters(ref filters) => {
+            IntermediateAggregationResult::Bucket(IntermediateBucketResult::Filters {
+                buckets: filters
+                    .filters
+                    .keys()
+                    .map(|name| (name.to_string(), Default::default()))
+                    .collect(),
+            })
+        }
+        Composite(_) => {
+            IntermediateAggregationResult::Bucket(IntermediateBucketResult::Composite {
+                buckets: Vec::new(),
+            })
+        }
         Histogram(_) => {
             IntermediateAggregationResult::Bucket(IntermediateBucketResult::Histogram {
                 buckets: Vec::new(),
@@ -238,9 +337,21 @@ pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult
         TopHits(ref req) => IntermediateAggregationResult::Metric(
             IntermediateMetricResult::TopHits(TopHitsTopNComputer::new(req)),
         ),
+        TopMetrics(ref req) => IntermediateAggregationResult::Metric(
+            IntermediateMetricResult::TopMetrics(IntermediateTopMetrics::new(req)),
+        ),
         Cardinality(_) => IntermediateAggregationResult::Metric(
             IntermediateMetricResult::Cardinality(CardinalityCollector::default()),
         ),
+        GeoCentroid(_) => IntermediateAggregationResult::Metric(
+            IntermediateMetricResult::GeoCentroid(IntermediateGeoStats::default()),
+        ),
+        GeoBounds(_) => IntermediateAggregationResult::Metric(IntermediateMetricResult::GeoBounds(
+            IntermediateGeoStats::default(),
+        )),
+        Rate(_) => IntermediateAggregationResult::Metric(IntermediateMetricResult::Rate(
+            IntermediateRate::default(),
+        )),
     }
 }
 
@@ -305,8 +416,16 @@ pub enum IntermediateMetricResult {
     Sum(IntermediateSum),
     /// Intermediate top_hits result
     TopHits(TopHitsTopNComputer),
+    /// Intermediate top_metrics result
+    TopMetrics(IntermediateTopMetrics),
     /// Intermediate cardinality result
     Cardinality(CardinalityCollector),
+    /// Intermediate geo centroid result
+    GeoCentroid(IntermediateGeoStats),
+    /// Intermediate geo bounds result
+    GeoBounds(IntermediateGeoStats),
+    /// Intermediate rate result
+    Rate(IntermediateRate),
 }
 
 impl IntermediateMetricResult {
@@ -340,9 +459,22 @@ impl IntermediateMetricResult {
             IntermediateMetricResult::TopHits(top_hits) => {
                 MetricResult::TopHits(top_hits.into_final_result())
             }
+            IntermediateMetricResult::TopMetrics(top_metrics) => {
+                MetricResult::TopMetrics(top_metrics.finalize())
+            }
             IntermediateMetricResult::Cardinality(cardinality) => {
                 MetricResult::Cardinality(cardinality.finalize().into())
             }
+            IntermediateMetricResult::GeoCentroid(geo_stats) => {
+                MetricResult::GeoCentroid(geo_stats.finalize_centroid())
+            }
+            IntermediateMetricResult::GeoBounds(geo_stats) => {
+                MetricResult::GeoBounds(geo_stats.finalize_bounds())
+            }
+            IntermediateMetricResult::Rate(rate) => MetricResult::Rate(
+                rate.finalize(req.agg.as_rate().expect("unexpected metric type"))
+                    .into(),
+            ),
         }
     }
 
@@ -391,12 +523,31 @@ impl IntermediateMetricResult {
             (IntermediateMetricResult::TopHits(left), IntermediateMetricResult::TopHits(right)) => {
                 left.merge_fruits(right)?;
             }
+            (
+                IntermediateMetricResult::TopMetrics(left),
+                IntermediateMetricResult::TopMetrics(right),
+            ) => {
+                left.merge_fruits(right);
+            }
             (
                 IntermediateMetricResult::Cardinality(left),
                 IntermediateMetricResult::Cardinality(right),
             ) => {
                 left.merge_fruits(right)?;
             }
+            (
+                IntermediateMetricResult::GeoCentroid(left),
+                IntermediateMetricResult::GeoCentroid(right),
+            )
+            | (
+                IntermediateMetricResult::GeoBounds(left),
+                IntermediateMetricResult::GeoBounds(right),
+            ) => {
+                left.merge_fruits(right);
+            }
+            (IntermediateMetricResult::Rate(left), IntermediateMetricResult::Rate(right)) => {
+                left.merge_fruits(right);
+            }
             _ => {
                 panic!("incompatible fruit types in tree or missing merge_fruits handler");
             }
@@ -426,6 +577,21 @@ pub enum IntermediateBucketResult {
         /// The term buckets
         buckets: IntermediateTermBucketResult,
     },
+    /// Filter aggregation
+    Filter {
+        /// The bucket of the documents matching the filter
+        bucket: IntermediateFilterBucketEntry,
+    },
+    /// Filters aggregation
+    Filters {
+        /// The buckets, by the name of their filter
+        buckets: FxHashMap<String, IntermediateFilterBucketEntry>,
+    },
+    /// Composite aggregation
+    Composite {
+        /// The buckets, sorted by key in ascending order
+        buckets: Vec<IntermediateCompositeBucketEntry>,
+    },
 }
 
 impl IntermediateBucketResult {
@@ -509,6 +675,32 @@ impl IntermediateBucketResult {
                 req.sub_aggregation(),
                 limits,
             ),
+            IntermediateBucketResult::Filter { bucket } => Ok(BucketResult::Filter(
+                bucket.into_final_bucket_entry(req.sub_aggregation(), limits)?,
+            )),
+            IntermediateBucketResult::Filters { buckets } => {
+                let buckets = buckets
+                    .into_iter()
+                    .map(|(name, bucket)| {
+                        let bucket =
+                            bucket.into_final_bucket_entry(req.sub_aggregation(), limits)?;
+                        Ok((name, bucket))
+                    })
+                    .collect::<crate::Result<_>>()?;
+                Ok(BucketResult::Filters { buckets })
+            }
+            IntermediateBucketResult::Composite { buckets } => {
+                let composite_req = match &req.agg {
+                    AggregationVariants::Composite(composite_req) => composite_req,
+                    _ => panic!("unexpected aggregation, expected composite aggregation"),
+                };
+                intermediate_composite_buckets_to_final_result(
+                    buckets,
+                    composite_req,
+                    req.sub_aggregation(),
+                    limits,
+                )
+            }
         }
     }
 
@@ -523,6 +715,14 @@ impl IntermediateBucketResult {
                 },
             ) => {
                 merge_maps(&mut term_res_left.entries, term_res_right.entries)?;
+                match (
+                    &mut term_res_left.global_ord_entries,
+                    term_res_right.global_ord_entries,
+                ) {
+                    (Some(left), Some(right)) => merge_maps(&mut left.entries, right.entries)?,
+                    (left @ None, right) => *left = right,
+                    (Some(_), None) => {}
+                }
                 term_res_left.sum_other_doc_count += term_res_right.sum_other_doc_count;
                 term_res_left.doc_count_error_upper_bound +=
                     term_res_right.doc_count_error_upper_bound;
@@ -562,6 +762,52 @@ impl IntermediateBucketResult {
 
                 *buckets_left = buckets?;
             }
+            (
+                IntermediateBucketResult::Filter {
+                    bucket: bucket_left,
+                },
+                IntermediateBucketResult::Filter {
+                    bucket: bucket_right,
+                },
+            ) => {
+                bucket_left.merge_fruits(bucket_right)?;
+            }
+            (
+                IntermediateBucketResult::Filters {
+                    buckets: buckets_left,
+                },
+                IntermediateBucketResult::Filters {
+                    buckets: buckets_right,
+                },
+            ) => {
+                merge_maps(buckets_left, buckets_right)?;
+            }
+            (
+                IntermediateBucketResult::Composite {
+                    buckets: buckets_left,
+                },
+                IntermediateBucketResult::Composite {
+                    buckets: buckets_right,
+                },
+            ) => {
+                let buckets: Result<Vec<IntermediateCompositeBucketEntry>, TantivyError> =
+                    buckets_left
+                        .drain(..)
+                        .merge_join_by(buckets_right, |left, right| {
+                            cmp_composite_keys_asc(&left.key, &right.key)
+                        })
+                        .map(|either| match either {
+                            itertools::EitherOrBoth::Both(mut left, right) => {
+                                left.merge_fruits(right)?;
+                                Ok(left)
+                            }
+                            itertools::EitherOrBoth::Left(left) => Ok(left),
+                            itertools::EitherOrBoth::Right(right) => Ok(right),
+                        })
+                        .collect::<Result<_, _>>();
+
+                *buckets_left = buckets?;
+            }
             (IntermediateBucketResult::Range(_), _) => {
                 panic!("try merge on different types")
             }
@@ -571,6 +817,15 @@ impl IntermediateBucketResult {
             (IntermediateBucketResult::Terms { .. }, _) => {
                 panic!("try merge on different types")
             }
+            (IntermediateBucketResult::Filter { .. }, _) => {
+                panic!("try merge on different types")
+            }
+            (IntermediateBucketResult::Filters { .. }, _) => {
+                panic!("try merge on different types")
+            }
+            (IntermediateBucketResult::Composite { .. }, _) => {
+                panic!("try merge on different types")
+            }
         }
         Ok(())
     }
@@ -587,10 +842,116 @@ pub struct IntermediateRangeBucketResult {
 /// Term aggregation including error counts
 pub struct IntermediateTermBucketResult {
     pub(crate) entries: FxHashMap<IntermediateKey, IntermediateTermBucketEntry>,
+    /// The buckets of the terms of a `str` column collected with global ordinals.
+    ///
+    /// Only the `AggregationCollector` collects with global ordinals, and it never serializes
+    /// its intermediate results.
+    #[serde(skip)]
+    pub(crate) global_ord_entries: Option<GlobalOrdTermBuckets>,
     pub(crate) sum_other_doc_count: u64,
     pub(crate) doc_count_error_upper_bound: u64,
 }
 
+/// Term buckets keyed by the global ordinal of their term.
+///
+/// The terms are resolved when the result is finalized, for the buckets that are returned only.
+#[derive(Clone)]
+pub(crate) struct GlobalOrdTermBuckets {
+    pub(crate) global_ordinals: Arc<GlobalOrdinals>,
+    pub(crate) entries: FxHashMap<u32, IntermediateTermBucketEntry>,
+}
+
+impl GlobalOrdTermBuckets {
+    /// Moves the buckets of `entries` whose key is a term of the global ordinals to the
+    /// buckets keyed by global ordinal.
+    ///
+    /// Such buckets come from the `missing` value, or from the segments collected without
+    /// global ordinals.
+    fn merge_str_entries(
+        &mut self,
+        entries: &mut FxHashMap<IntermediateKey, IntermediateTermBucketEntry>,
+    ) -> crate::Result<()> {
+        let mut str_entries = FxHashMap::default();
+        for (key, entry) in std::mem::take(entries) {
+            let global_ord_opt = match &key {
+                IntermediateKey::Str(term) => self.global_ordinals.str_to_ord(term)?,
+                _ => None,
+            };
+            match global_ord_opt {
+                Some(global_ord) => {
+                    str_entries.insert(global_ord, entry);
+                }
+                None => {
+                    entries.insert(key, entry);
+                }
+            }
+        }
+        merge_maps(&mut self.entries, str_entries)
+    }
+
+    /// Returns the top `req.size` buckets, with their terms resolved, and the sum of the
+    /// document counts of the other buckets.
+    fn into_top_buckets(
+        self,
+        req: &TermsAggregationInternal,
+        sub_aggregation_req: &Aggregations,
+        limits: &mut AggregationLimitsGuard,
+    ) -> crate::Result<(Vec<BucketEntry>, u64)> {
+        // Global ordinals are sorted like their terms, so the buckets can be ordered by key
+        // before their terms are resolved.
+        let mut buckets: Vec<BucketEntry> = self
+            .entries
+            .into_iter()
+            .filter(|bucket| bucket.1.doc_count as u64 >= req.min_doc_count)
+            .map(|(global_ord, entry)| {
+                Ok(BucketEntry {
+                    key_as_string: None,
+                    key: Key::U64(global_ord as u64),
+                    doc_count: entry.doc_count as u64,
+                    sub_aggregation: entry
+                        .sub_aggregation
+                        .into_final_result_internal(sub_aggregation_req, limits)?,
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        sort_term_buckets(&mut buckets, &req.order)?;
+        let (_term_doc_count_before_cutoff, sum_other_doc_count) =
+            cut_off_buckets(&mut buckets, req.size as usize);
+
+        let mut term = String::new();
+        for bucket in &mut buckets {
+            let Key::U64(global_ord) = bucket.key else {
+                unreachable!("the buckets are keyed by global ordinal");
+            };
+            if !self
+                .global_ordinals
+                .ord_to_str(global_ord as u32, &mut term)?
+            {
+                return Err(
+                    io::Error::other(format!("global ordinal {global_ord} out of bounds")).into(),
+                );
+            }
+            bucket.key = Key::Str(term.clone());
+        }
+        Ok((buckets, sum_other_doc_count))
+    }
+}
+
+impl fmt::Debug for GlobalOrdTermBuckets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlobalOrdTermBuckets")
+            .field("field_name", &self.global_ordinals.field_name())
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+impl PartialEq for GlobalOrdTermBuckets {
+    fn eq(&self, other: &GlobalOrdTermBuckets) -> bool {
+        Arc::ptr_eq(&self.global_ordinals, &other.global_ordinals) && self.entries == other.entries
+    }
+}
+
 impl IntermediateTermBucketResult {
     pub(crate) fn into_final_result(
         self,
@@ -599,8 +960,15 @@ impl IntermediateTermBucketResult {
         limits: &mut AggregationLimitsGuard,
     ) -> crate::Result<BucketResult> {
         let req = TermsAggregationInternal::from_req(req);
-        let mut buckets: Vec<BucketEntry> = self
-            .entries
+        let mut entries = self.entries;
+        let (global_ord_buckets, global_ord_sum_other_doc_count) =
+            if let Some(mut global_ord_entries) = self.global_ord_entries {
+                global_ord_entries.merge_str_entries(&mut entries)?;
+                global_ord_entries.into_top_buckets(&req, sub_aggregation_req, limits)?
+            } else {
+                (Vec::new(), 0)
+            };
+        let mut buckets: Vec<BucketEntry> = entries
             .into_iter()
             .filter(|bucket| bucket.1.doc_count as u64 >= req.min_doc_count)
             .map(|(key, entry)| {
@@ -609,6 +977,7 @@ impl IntermediateTermBucketResult {
                         let val = if key { "true" } else { "false" };
                         Some(val.to_string())
                     }
+                    IntermediateKey::IpAddr(key) => Some(format_ip_addr(key)),
                     _ => None,
                 };
                 Ok(BucketEntry {
@@ -621,49 +990,8 @@ impl IntermediateTermBucketResult {
                 })
             })
             .collect::<crate::Result<_>>()?;
-
-        let order = req.order.order;
-        match req.order.target {
-            OrderTarget::Key => {
-                buckets.sort_by(|left, right| {
-                    if req.order.order == Order::Asc {
-                        left.key.partial_cmp(&right.key)
-                    } else {
-                        right.key.partial_cmp(&left.key)
-                    }
-                    .expect("expected type string, which is always sortable")
-                });
-            }
-            OrderTarget::Count => {
-                if req.order.order == Order::Desc {
-                    buckets.sort_unstable_by_key(|bucket| std::cmp::Reverse(bucket.doc_count()));
-                } else {
-                    buckets.sort_unstable_by_key(|bucket| bucket.doc_count());
-                }
-            }
-            OrderTarget::SubAggregation(name) => {
-                let (agg_name, agg_property) = get_agg_name_and_property(&name);
-                let mut buckets_with_val = buckets
-                    .into_iter()
-                    .map(|bucket| {
-                        let val = bucket
-                            .sub_aggregation
-                            .get_value_from_aggregation(agg_name, agg_property)?
-                            .unwrap_or(f64::MIN);
-                        Ok((bucket, val))
-                    })
-                    .collect::<crate::Result<Vec<_>>>()?;
-
-                buckets_with_val.sort_by(|(_, val1), (_, val2)| match &order {
-                    Order::Desc => val2.total_cmp(val1),
-                    Order::Asc => val1.total_cmp(val2),
-                });
-                buckets = buckets_with_val
-                    .into_iter()
-                    .map(|(bucket, _val)| bucket)
-                    .collect_vec();
-            }
-        }
+        buckets.extend(global_ord_buckets);
+        sort_term_buckets(&mut buckets, &req.order)?;
 
         // We ignore _term_doc_count_before_cutoff here, because it increases the upperbound error
         // only for terms that didn't make it into the top N.
@@ -681,12 +1009,63 @@ impl IntermediateTermBucketResult {
 
         Ok(BucketResult::Terms {
             buckets,
-            sum_other_doc_count: self.sum_other_doc_count + sum_other_doc_count,
+            sum_other_doc_count: self.sum_other_doc_count
+                + global_ord_sum_other_doc_count
+                + sum_other_doc_count,
             doc_count_error_upper_bound,
         })
     }
 }
 
+/// Sorts the buckets of a term aggregation by the given order.
+fn sort_term_buckets(
+    buckets: &mut Vec<BucketEntry>,
+    custom_order: &CustomOrder,
+) -> crate::Result<()> {
+    match &custom_order.target {
+        OrderTarget::Key => {
+            buckets.sort_by(|left, right| {
+                if custom_order.order == Order::Asc {
+                    left.key.partial_cmp(&right.key)
+                } else {
+                    right.key.partial_cmp(&left.key)
+                }
+                .expect("expected type string, which is always sortable")
+            });
+        }
+        OrderTarget::Count => {
+            if custom_order.order == Order::Desc {
+                buckets.sort_unstable_by_key(|bucket| std::cmp::Reverse(bucket.doc_count()));
+            } else {
+                buckets.sort_unstable_by_key(|bucket| bucket.doc_count());
+            }
+        }
+        OrderTarget::SubAggregation(name) => {
+            let (agg_name, agg_property) = get_agg_name_and_property(name);
+            let mut buckets_with_val = std::mem::take(buckets)
+                .into_iter()
+                .map(|bucket| {
+                    let val = bucket
+                        .sub_aggregation
+                        .get_value_from_aggregation(agg_name, agg_property)?
+                        .unwrap_or(f64::MIN);
+                    Ok((bucket, val))
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+
+            buckets_with_val.sort_by(|(_, val1), (_, val2)| match custom_order.order {
+                Order::Desc => val2.total_cmp(val1),
+                Order::Asc => val1.total_cmp(val2),
+            });
+            *buckets = buckets_with_val
+                .into_iter()
+                .map(|(bucket, _val)| bucket)
+                .collect_vec();
+        }
+    }
+    Ok(())
+}
+
 trait MergeFruits {
     fn merge_fruits(&mut self, other: Self) -> crate::Result<()>;
 }
@@ -799,6 +1178,91 @@ pub struct IntermediateTermBucketEntry {
     pub sub_aggregation: IntermediateAggregationResults,
 }
 
+/// This is the entry of a bucket of the `filter` and `filters` aggregations, which contains a
+/// count, and optionally sub_aggregations.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateFilterBucketEntry {
+    /// The number of documents in the bucket.
+    pub doc_count: u64,
+    /// The sub_aggregation in this bucket.
+    pub sub_aggregation: IntermediateAggregationResults,
+}
+
+impl IntermediateFilterBucketEntry {
+    pub(crate) fn into_final_bucket_entry(
+        self,
+        req: &Aggregations,
+        limits: &mut AggregationLimitsGuard,
+    ) -> crate::Result<FilterBucketEntry> {
+        Ok(FilterBucketEntry {
+            doc_count: self.doc_count,
+            sub_aggregation: self
+                .sub_aggregation
+                .into_final_result_internal(req, limits)?,
+        })
+    }
+}
+
+/// This is the entry of a bucket of the `composite` aggregation, which contains a key, a count,
+/// and optionally sub_aggregations.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateCompositeBucketEntry {
+    /// The values of the key, in the order of the sources.
+    pub key: Vec<IntermediateKey>,
+    /// The number of documents in the bucket.
+    pub doc_count: u64,
+    /// The sub_aggregation in this bucket.
+    pub sub_aggregation: IntermediateAggregationResults,
+}
+
+/// Converts the buckets of the `composite` aggregation to the final result.
+///
+/// The buckets are sorted in the order of the request, and truncated to its size. The
+/// `after_key` is the key of the last bucket, it is only set if the page is full, i.e. if there
+/// may be more buckets.
+fn intermediate_composite_buckets_to_final_result(
+    mut buckets: Vec<IntermediateCompositeBucketEntry>,
+    req: &CompositeAggregation,
+    sub_aggregation_req: &Aggregations,
+    limits: &mut AggregationLimitsGuard,
+) -> crate::Result<BucketResult> {
+    buckets.sort_by(|left, right| {
+        cmp_composite_keys(
+            &left.key,
+            &right.key,
+            req.sources.iter().map(|source| source.source.order()),
+        )
+    });
+    buckets.truncate(req.size as usize);
+    let into_final_key = |key: Vec<IntermediateKey>| -> FxHashMap<String, Key> {
+        req.sources
+            .iter()
+            .map(|source| source.name.clone())
+            .zip(key.into_iter().map(Key::from))
+            .collect()
+    };
+    let after_key = if buckets.len() == req.size as usize {
+        buckets
+            .last()
+            .map(|bucket| into_final_key(bucket.key.clone()))
+    } else {
+        None
+    };
+    let buckets = buckets
+        .into_iter()
+        .map(|bucket| {
+            Ok(CompositeBucketEntry {
+                key: into_final_key(bucket.key),
+                doc_count: bucket.doc_count,
+                sub_aggregation: bucket
+                    .sub_aggregation
+                    .into_final_result_internal(sub_aggregation_req, limits)?,
+            })
+        })
+        .collect::<crate::Result<_>>()?;
+    Ok(BucketResult::Composite { buckets, after_key })
+}
+
 impl MergeFruits for IntermediateTermBucketEntry {
     fn merge_fruits(&mut self, other: IntermediateTermBucketEntry) -> crate::Result<()> {
         self.doc_count += other.doc_count;
@@ -815,6 +1279,22 @@ impl MergeFruits for IntermediateRangeBucketEntry {
     }
 }
 
+impl MergeFruits for IntermediateFilterBucketEntry {
+    fn merge_fruits(&mut self, other: IntermediateFilterBucketEntry) -> crate::Result<()> {
+        self.doc_count += other.doc_count;
+        self.sub_aggregation.merge_fruits(other.sub_aggregation)?;
+        Ok(())
+    }
+}
+
+impl MergeFruits for IntermediateCompositeBucketEntry {
+    fn merge_fruits(&mut self, other: IntermediateCompositeBucketEntry) -> crate::Result<()> {
+        self.doc_count += other.doc_count;
+        self.sub_aggregation.merge_fruits(other.sub_aggregation)?;
+        Ok(())
+    }
+}
+
 impl MergeFruits for IntermediateHistogramBucketEntry {
     fn merge_fruits(&mut self, other: IntermediateHistogramBucketEntry) -> crate::Result<()> {
         self.doc_count += other.doc_count;
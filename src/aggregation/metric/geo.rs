@@ -0,0 +1,336 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateMetricResult,
+};
+use crate::aggregation::segment_agg_result::SegmentAggregationCollector;
+use crate::aggregation::*;
+use crate::DocId;
+
+/// A metric aggregation that computes the centroid of the geo points of the aggregated documents.
+/// See [`GeoCentroid`] for the returned result.
+///
+/// A geo point is a JSON object with numeric `lat` and `lon` fast fields, e.g. the `location`
+/// field of `{"location": {"lat": 48.86, "lon": 2.35}}`. The centroid is the mean of the
+/// latitudes and longitudes of the points, so that every point has the same weight.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "geo_centroid": {
+///         "field": "location"
+///     }
+///  }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoCentroidAggregation {
+    /// The field name of the geo points.
+    pub field: String,
+}
+
+impl GeoCentroidAggregation {
+    /// Creates a new [`GeoCentroidAggregation`] instance from a field name.
+    pub fn from_field_name(field_name: String) -> Self {
+        GeoCentroidAggregation { field: field_name }
+    }
+    /// Returns the field name the aggregation is computed on.
+    pub fn field_name(&self) -> &str {
+        &self.field
+    }
+}
+
+/// A metric aggregation that computes the bounding box of the geo points of the aggregated
+/// documents, e.g. to zoom a map on them.
+/// See [`GeoBounds`] for the returned result.
+///
+/// The geo points are read like in the [`GeoCentroidAggregation`]. The bounding box does not
+/// wrap around the antimeridian.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "geo_bounds": {
+///         "field": "location"
+///     }
+///  }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoBoundsAggregation {
+    /// The field name of the geo points.
+    pub field: String,
+}
+
+impl GeoBoundsAggregation {
+    /// Creates a new [`GeoBoundsAggregation`] instance from a field name.
+    pub fn from_field_name(field_name: String) -> Self {
+        GeoBoundsAggregation { field: field_name }
+    }
+    /// Returns the field name the aggregation is computed on.
+    pub fn field_name(&self) -> &str {
+        &self.field
+    }
+}
+
+/// A geo point.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    /// The latitude.
+    pub lat: f64,
+    /// The longitude.
+    pub lon: f64,
+}
+
+/// The result of the geo centroid aggregation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoCentroid {
+    /// The centroid. `None` if count equals zero.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoPoint>,
+    /// The number of geo points.
+    pub count: u64,
+}
+
+/// The result of the geo bounds aggregation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoBounds {
+    /// The bounding box. `None` if there are no geo points.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<GeoBoundingBox>,
+}
+
+/// A bounding box, defined by two of its corners.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoBoundingBox {
+    /// The north west corner.
+    pub top_left: GeoPoint,
+    /// The south east corner.
+    pub bottom_right: GeoPoint,
+}
+
+/// Intermediate result of the geo aggregations, holding the stats of the latitudes and
+/// longitudes, that can be combined with other intermediate results.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateGeoStats {
+    latitudes: IntermediateStats,
+    longitudes: IntermediateStats,
+}
+
+impl IntermediateGeoStats {
+    /// Merges the other intermediate result into self.
+    pub fn merge_fruits(&mut self, other: IntermediateGeoStats) {
+        self.latitudes.merge_fruits(other.latitudes);
+        self.longitudes.merge_fruits(other.longitudes);
+    }
+
+    /// Computes the final centroid.
+    pub fn finalize_centroid(&self) -> GeoCentroid {
+        let latitudes = self.latitudes.finalize();
+        let longitudes = self.longitudes.finalize();
+        let location = latitudes
+            .avg
+            .zip(longitudes.avg)
+            .map(|(lat, lon)| GeoPoint { lat, lon });
+        GeoCentroid {
+            location,
+            count: latitudes.count,
+        }
+    }
+
+    /// Computes the final bounding box.
+    pub fn finalize_bounds(&self) -> GeoBounds {
+        let bounds = if self.latitudes.count == 0 {
+            None
+        } else {
+            Some(GeoBoundingBox {
+                top_left: GeoPoint {
+                    lat: self.latitudes.max,
+                    lon: self.longitudes.min,
+                },
+                bottom_right: GeoPoint {
+                    lat: self.latitudes.min,
+                    lon: self.longitudes.max,
+                },
+            })
+        };
+        GeoBounds { bounds }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SegmentGeoType {
+    Centroid,
+    Bounds,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SegmentGeoCollector {
+    collecting_for: SegmentGeoType,
+    stats: IntermediateGeoStats,
+    accessor_idx: usize,
+}
+
+impl SegmentGeoCollector {
+    pub fn from_req(collecting_for: SegmentGeoType, accessor_idx: usize) -> Self {
+        Self {
+            collecting_for,
+            stats: IntermediateGeoStats::default(),
+            accessor_idx,
+        }
+    }
+}
+
+impl SegmentAggregationCollector for SegmentGeoCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+
+        let intermediate_metric_result = match self.collecting_for {
+            SegmentGeoType::Centroid => IntermediateMetricResult::GeoCentroid(self.stats),
+            SegmentGeoType::Bounds => IntermediateMetricResult::GeoBounds(self.stats),
+        };
+
+        results.push(
+            name,
+            IntermediateAggregationResult::Metric(intermediate_metric_result),
+        )?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        // The accessors are the latitude and longitude columns, see `AggregationWithAccessor`.
+        let accessors = &agg_with_accessor.aggs.values[self.accessor_idx].accessors;
+        let (lat_column, lat_type) = &accessors[0];
+        let (lon_column, lon_type) = &accessors[1];
+        for (lat, lon) in lat_column
+            .values_for_doc(doc)
+            .zip(lon_column.values_for_doc(doc))
+        {
+            self.stats
+                .latitudes
+                .collect(f64_from_fastfield_u64(lat, lat_type));
+            self.stats
+                .longitudes
+                .collect(f64_from_fastfield_u64(lon, lon_type));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        for &doc in docs {
+            self.collect(doc, agg_with_accessor)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::AggregationCollector;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{assert_nearly_equals, Index, IndexWriter};
+
+    #[test]
+    fn test_aggregation_geo_centroid_and_bounds() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let city = schema_builder.add_text_field("city", STRING | FAST);
+        let location = schema_builder.add_json_field("location", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let points = [
+            ("paris", 48.5, 2.5),
+            ("paris", 49.5, 2.0),
+            ("berlin", 52.5, 13.5),
+        ];
+        for (city_name, lat, lon) in points {
+            index_writer.add_document(doc!(
+                city => city_name,
+                location => json!({"lat": lat, "lon": lon}),
+            ))?;
+            // One segment per document.
+            index_writer.commit()?;
+        }
+        index_writer.add_document(doc!(city => "nowhere"))?;
+        index_writer.commit()?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "centroid": { "geo_centroid": { "field": "location" } },
+            "bounds": { "geo_bounds": { "field": "location" } },
+            "cities": {
+                "terms": { "field": "city", "order": { "_key": "asc" } },
+                "aggs": {
+                    "centroid": { "geo_centroid": { "field": "location" } },
+                    "bounds": { "geo_bounds": { "field": "location" } }
+                }
+            }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let searcher = index.reader()?.searcher();
+        let agg_res: AggregationResults = searcher.search(&AllQuery, &collector)?;
+        let res: Value = serde_json::to_value(&agg_res)?;
+
+        assert_eq!(res["centroid"]["count"], 3);
+        assert_nearly_equals!(
+            res["centroid"]["location"]["lat"].as_f64().unwrap(),
+            50.166666
+        );
+        assert_nearly_equals!(res["centroid"]["location"]["lon"].as_f64().unwrap(), 6.0);
+        assert_eq!(
+            res["bounds"],
+            json!({
+                "bounds": {
+                    "top_left": { "lat": 52.5, "lon": 2.0 },
+                    "bottom_right": { "lat": 48.5, "lon": 13.5 }
+                }
+            })
+        );
+
+        let buckets = &res["cities"]["buckets"];
+        assert_eq!(buckets[0]["key"], "berlin");
+        assert_eq!(
+            buckets[0]["centroid"],
+            json!({ "location": { "lat": 52.5, "lon": 13.5 }, "count": 1 })
+        );
+        assert_eq!(buckets[1]["key"], "nowhere");
+        assert_eq!(buckets[1]["centroid"], json!({ "count": 0 }));
+        assert_eq!(buckets[1]["bounds"], json!({}));
+        assert_eq!(buckets[2]["key"], "paris");
+        assert_eq!(
+            buckets[2]["centroid"],
+            json!({ "location": { "lat": 49.0, "lon": 2.25 }, "count": 2 })
+        );
+        assert_eq!(
+            buckets[2]["bounds"],
+            json!({
+                "bounds": {
+                    "top_left": { "lat": 49.5, "lon": 2.0 },
+                    "bottom_right": { "lat": 48.5, "lon": 2.5 }
+                }
+            })
+        );
+        Ok(())
+    }
+}
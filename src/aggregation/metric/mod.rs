@@ -15,17 +15,24 @@
 //! - [Sum](SumAggregation)
 //! - [Count](CountAggregation)
 //! - [Percentiles](PercentilesAggregationReq)
+//! - [GeoCentroid](GeoCentroidAggregation)
+//! - [GeoBounds](GeoBoundsAggregation)
+//! - [Rate](RateAggregation)
+//! - [TopMetrics](TopMetricsAggregationReq)
 
 mod average;
 mod cardinality;
 mod count;
 mod extended_stats;
+mod geo;
 mod max;
 mod min;
 mod percentiles;
+mod rate;
 mod stats;
 mod sum;
 mod top_hits;
+mod top_metrics;
 
 use std::collections::HashMap;
 
@@ -33,14 +40,17 @@ pub use average::*;
 pub use cardinality::*;
 pub use count::*;
 pub use extended_stats::*;
+pub use geo::*;
 pub use max::*;
 pub use min::*;
 pub use percentiles::*;
+pub use rate::*;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 pub use stats::*;
 pub use sum::*;
 pub use top_hits::*;
+pub use top_metrics::*;
 
 use crate::schema::OwnedValue;
 
@@ -79,8 +89,8 @@ pub enum PercentileValues {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 /// The entry when requesting percentiles with keyed: false
 pub struct PercentileValuesVecEntry {
-    key: f64,
-    value: f64,
+    pub(crate) key: f64,
+    pub(crate) value: f64,
 }
 
 /// Single-metric aggregations use this common result structure.
@@ -0,0 +1,284 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateMetricResult,
+};
+use crate::aggregation::segment_agg_result::SegmentAggregationCollector;
+use crate::aggregation::*;
+use crate::DocId;
+
+/// A single-value metric aggregation that computes the rate of documents, or the rate of the sum
+/// of the values of a field, per unit of time.
+///
+/// It has to be used in a [date histogram](crate::aggregation::bucket::DateHistogramAggregationReq)
+/// (possibly in another bucket aggregation within it), which provides the duration the rate is
+/// computed on. The duration of the first and last buckets is cut by the `hard_bounds` of the
+/// date histogram, so that partial buckets do not report lower rates. Outside of a date
+/// histogram, the value is `null`.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "rate": {
+///         "unit": "minute",
+///         "field": "bytes"
+///     }
+///  }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RateAggregation {
+    /// The unit of time of the rate.
+    pub unit: RateUnit,
+    /// The field name of the values to sum. If unset, the documents are counted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+}
+
+impl RateAggregation {
+    /// Creates a new [`RateAggregation`] instance counting the documents.
+    pub fn doc_count(unit: RateUnit) -> Self {
+        RateAggregation { unit, field: None }
+    }
+    /// Creates a new [`RateAggregation`] instance summing the values of a field.
+    pub fn from_field_name(unit: RateUnit, field_name: String) -> Self {
+        RateAggregation {
+            unit,
+            field: Some(field_name),
+        }
+    }
+    /// Returns the field name the aggregation is computed on, if any.
+    pub fn field_name(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+}
+
+/// The unit of time of a [`RateAggregation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateUnit {
+    /// Per second.
+    Second,
+    /// Per minute.
+    Minute,
+    /// Per hour.
+    Hour,
+    /// Per day.
+    Day,
+    /// Per week.
+    Week,
+}
+
+impl RateUnit {
+    fn as_nanos(self) -> f64 {
+        let seconds = match self {
+            RateUnit::Second => 1,
+            RateUnit::Minute => 60,
+            RateUnit::Hour => 60 * 60,
+            RateUnit::Day => 24 * 60 * 60,
+            RateUnit::Week => 7 * 24 * 60 * 60,
+        };
+        seconds as f64 * 1_000_000_000.0
+    }
+}
+
+/// Intermediate result of the rate aggregation that can be combined with other intermediate
+/// results.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateRate {
+    stats: IntermediateStats,
+    /// The duration of the date histogram bucket holding the rate, in nanoseconds. It is set
+    /// right before the bucket is finalized.
+    bucket_duration_ns: Option<f64>,
+}
+
+impl IntermediateRate {
+    /// Merges the other intermediate result into self.
+    pub fn merge_fruits(&mut self, other: IntermediateRate) {
+        self.stats.merge_fruits(other.stats);
+    }
+
+    pub(crate) fn set_bucket_duration_ns(&mut self, bucket_duration_ns: f64) {
+        self.bucket_duration_ns = Some(bucket_duration_ns);
+    }
+
+    /// Computes the final rate value.
+    pub fn finalize(&self, req: &RateAggregation) -> Option<f64> {
+        let value = if req.field.is_some() {
+            self.stats.sum
+        } else {
+            self.stats.count as f64
+        };
+        self.bucket_duration_ns
+            .filter(|bucket_duration_ns| *bucket_duration_ns > 0.0)
+            .map(|bucket_duration_ns| value * req.unit.as_nanos() / bucket_duration_ns)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SegmentRateCollector {
+    /// Sums the values of the field. `None` if the documents are counted.
+    field_collector: Option<SegmentStatsCollector>,
+    doc_count: u64,
+    accessor_idx: usize,
+}
+
+impl SegmentRateCollector {
+    pub fn from_req(field_type: ColumnType, has_field: bool, accessor_idx: usize) -> Self {
+        let field_collector = has_field.then(|| {
+            SegmentStatsCollector::from_req(field_type, SegmentStatsType::Sum, accessor_idx, None)
+        });
+        Self {
+            field_collector,
+            doc_count: 0,
+            accessor_idx,
+        }
+    }
+}
+
+impl SegmentAggregationCollector for SegmentRateCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+
+        let stats = match self.field_collector {
+            Some(field_collector) => field_collector.stats,
+            None => IntermediateStats {
+                count: self.doc_count,
+                ..Default::default()
+            },
+        };
+        let intermediate_rate = IntermediateRate {
+            stats,
+            bucket_duration_ns: None,
+        };
+
+        results.push(
+            name,
+            IntermediateAggregationResult::Metric(IntermediateMetricResult::Rate(
+                intermediate_rate,
+            )),
+        )?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        match self.field_collector.as_mut() {
+            Some(field_collector) => field_collector.collect(doc, agg_with_accessor)?,
+            None => self.doc_count += 1,
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        match self.field_collector.as_mut() {
+            Some(field_collector) => field_collector.collect_block(docs, agg_with_accessor)?,
+            None => self.doc_count += docs.len() as u64,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::AggregationCollector;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{DateTime, Index, IndexWriter};
+
+    #[test]
+    fn test_aggregation_rate() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_date_field("timestamp", FAST);
+        let bytes = schema_builder.add_u64_field("bytes", FAST);
+        let host = schema_builder.add_text_field("host", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // Minutes after midnight, bytes, host.
+        let docs = [
+            (40, 100, "a"),
+            (50, 200, "a"),
+            (70, 300, "b"),
+            (80, 400, "a"),
+            (100, 600, "b"),
+        ];
+        for (minute, num_bytes, host_name) in docs {
+            index_writer.add_document(doc!(
+                timestamp => DateTime::from_timestamp_secs(minute * 60),
+                bytes => num_bytes as u64,
+                host => host_name,
+            ))?;
+        }
+        index_writer.commit()?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "rate": { "rate": { "unit": "minute" } },
+            "per_hour": {
+                "date_histogram": {
+                    "field": "timestamp",
+                    "fixed_interval": "1h",
+                    // The first bucket starts at 30 minutes.
+                    "hard_bounds": { "min": 30 * 60 * 1000, "max": 2 * 60 * 60 * 1000 }
+                },
+                "aggs": {
+                    "docs_per_minute": { "rate": { "unit": "minute" } },
+                    "bytes_per_second": { "rate": { "unit": "second", "field": "bytes" } },
+                    "hosts": {
+                        "terms": { "field": "host", "order": { "_key": "asc" } },
+                        "aggs": {
+                            "docs_per_hour": { "rate": { "unit": "hour" } }
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let searcher = index.reader()?.searcher();
+        let agg_res: AggregationResults = searcher.search(&AllQuery, &collector)?;
+        let res: Value = serde_json::to_value(&agg_res)?;
+
+        assert_eq!(res["rate"]["value"], Value::Null);
+        let buckets = &res["per_hour"]["buckets"];
+        // 2 documents over the 30 minutes of the partial first bucket.
+        assert_eq!(buckets[0]["doc_count"], 2);
+        assert_eq!(buckets[0]["docs_per_minute"]["value"], 2.0 / 30.0);
+        assert_eq!(buckets[0]["bytes_per_second"]["value"], 300.0 / 1800.0);
+        assert_eq!(buckets[0]["hosts"]["buckets"][0]["key"], "a");
+        assert_eq!(
+            buckets[0]["hosts"]["buckets"][0]["docs_per_hour"]["value"],
+            4.0
+        );
+        // 3 documents over the full second bucket.
+        assert_eq!(buckets[1]["doc_count"], 3);
+        assert_eq!(buckets[1]["docs_per_minute"]["value"], 3.0 / 60.0);
+        assert_eq!(buckets[1]["bytes_per_second"]["value"], 1300.0 / 3600.0);
+        assert_eq!(buckets[1]["hosts"]["buckets"][1]["key"], "b");
+        assert_eq!(
+            buckets[1]["hosts"]["buckets"][1]["docs_per_hour"]["value"],
+            2.0
+        );
+        Ok(())
+    }
+}
@@ -107,10 +107,11 @@ pub struct TopHitsAggregationReq {
     version: Option<serde_json::Value>,
 }
 
+/// A sort criterion, e.g. `{ "date": "desc" }`.
 #[derive(Debug, Clone, PartialEq, Default)]
-struct KeyOrder {
-    field: String,
-    order: Order,
+pub(super) struct KeyOrder {
+    pub(super) field: String,
+    pub(super) order: Order,
 }
 
 impl Serialize for KeyOrder {
@@ -127,12 +128,11 @@ impl<'de> Deserialize<'de> for KeyOrder {
     where D: Deserializer<'de> {
         let mut key_order = <HashMap<String, Order>>::deserialize(deserializer)?.into_iter();
         let (field, order) = key_order.next().ok_or(serde::de::Error::custom(
-            "Expected exactly one key-value pair in sort parameter of top_hits, found none",
+            "Expected exactly one key-value pair in sort parameter, found none",
         ))?;
         if key_order.next().is_some() {
             return Err(serde::de::Error::custom(format!(
-                "Expected exactly one key-value pair in sort parameter of top_hits, found \
-                 {key_order:?}"
+                "Expected exactly one key-value pair in sort parameter, found {key_order:?}"
             )));
         }
         Ok(Self { field, order })
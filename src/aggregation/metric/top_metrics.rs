@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use columnar::{Column, ColumnType};
+use serde::{Deserialize, Serialize};
+
+use super::top_hits::KeyOrder;
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::bucket::Order;
+use crate::aggregation::f64_from_fastfield_u64;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateMetricResult,
+};
+use crate::aggregation::segment_agg_result::SegmentAggregationCollector;
+use crate::{DocId, TantivyError};
+
+/// # Top Metrics
+///
+/// The top metrics aggregation returns the values of some fast fields of the best document, in
+/// terms of a sort field. It answers the same questions as
+/// [`top_hits`](super::TopHitsAggregationReq), like "what is the latest price of each product?",
+/// but only keeps a single document and reads its fields once per segment, which makes it
+/// cheaper.
+///
+/// The sort field and the metric fields have to be numeric or date fast fields. Dates are
+/// returned as milliseconds since the epoch. Documents without a value for the sort field are
+/// ignored.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "top_metrics": {
+///         "metrics": [{ "field": "price" }, { "field": "stock" }],
+///         "sort": { "date": "desc" }
+///     }
+///  }
+/// ```
+///
+/// The aggregation object for each bucket will look like:
+/// ```json
+/// {
+///     "top": [
+///         {
+///             "sort": [1700000000000.0],
+///             "metrics": { "price": 9.5, "stock": 12.0 }
+///         }
+///     ]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TopMetricsAggregationReq {
+    metrics: Vec<TopMetricsField>,
+    sort: KeyOrder,
+}
+
+/// A field whose value is returned by the [`TopMetricsAggregationReq`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TopMetricsField {
+    /// The field name.
+    pub field: String,
+}
+
+impl TopMetricsAggregationReq {
+    /// Creates a new [`TopMetricsAggregationReq`] returning the values of `metric_fields` of the
+    /// best document according to `sort_field` and `order`.
+    pub fn new(metric_fields: Vec<String>, sort_field: String, order: Order) -> Self {
+        TopMetricsAggregationReq {
+            metrics: metric_fields
+                .into_iter()
+                .map(|field| TopMetricsField { field })
+                .collect(),
+            sort: KeyOrder {
+                field: sort_field,
+                order,
+            },
+        }
+    }
+
+    /// Return fields accessed by the aggregator, the sort field first.
+    pub fn field_names(&self) -> Vec<&str> {
+        std::iter::once(self.sort.field.as_str())
+            .chain(self.metrics.iter().map(|metric| metric.field.as_str()))
+            .collect()
+    }
+
+    fn order(&self) -> Order {
+        self.sort.order
+    }
+}
+
+/// The top metrics aggregation result.
+///
+/// The main reason for wrapping it in `top` is to match elasticsearch output structure.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TopMetricsResult {
+    /// The best document, if any document has a value for the sort field.
+    pub top: Vec<TopMetricsEntry>,
+}
+
+impl TopMetricsResult {
+    pub(crate) fn get_value(&self, agg_property: &str) -> crate::Result<Option<f64>> {
+        let Some(entry) = self.top.first() else {
+            return Ok(None);
+        };
+        entry.metrics.get(agg_property).copied().ok_or_else(|| {
+            TantivyError::InvalidArgument(format!(
+                "Unknown property {agg_property} on top_metrics aggregation"
+            ))
+        })
+    }
+}
+
+/// The sort value and metric values of a document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TopMetricsEntry {
+    /// The value of the sort field.
+    pub sort: Vec<f64>,
+    /// The values of the metric fields, by field name.
+    pub metrics: HashMap<String, Option<f64>>,
+}
+
+impl TopMetricsEntry {
+    fn sort_value(&self) -> f64 {
+        self.sort[0]
+    }
+}
+
+/// Intermediate result of the top metrics aggregation that can be combined with other
+/// intermediate results.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateTopMetrics {
+    order: Order,
+    top: Option<TopMetricsEntry>,
+}
+
+impl IntermediateTopMetrics {
+    /// Creates an empty intermediate result.
+    pub fn new(req: &TopMetricsAggregationReq) -> Self {
+        IntermediateTopMetrics {
+            order: req.order(),
+            top: None,
+        }
+    }
+
+    /// Merges the other intermediate result into self.
+    pub fn merge_fruits(&mut self, other: IntermediateTopMetrics) {
+        let Some(other_top) = other.top else {
+            return;
+        };
+        let is_better = match &self.top {
+            Some(top) => is_better(self.order, other_top.sort_value(), top.sort_value()),
+            None => true,
+        };
+        if is_better {
+            self.top = Some(other_top);
+        }
+    }
+
+    /// Computes the final result.
+    pub fn finalize(self) -> TopMetricsResult {
+        TopMetricsResult {
+            top: self.top.into_iter().collect(),
+        }
+    }
+}
+
+/// Ties are resolved in favor of the current document.
+fn is_better(order: Order, sort_value: f64, current_sort_value: f64) -> bool {
+    match order {
+        Order::Asc => sort_value < current_sort_value,
+        Order::Desc => sort_value > current_sort_value,
+    }
+}
+
+fn value_to_f64(value: u64, column_type: &ColumnType) -> f64 {
+    let value = f64_from_fastfield_u64(value, column_type);
+    if *column_type == ColumnType::DateTime {
+        // Nanoseconds to milliseconds.
+        value / 1_000_000.0
+    } else {
+        value
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentTopMetricsCollector {
+    order: Order,
+    /// The sort value and id of the best document.
+    top: Option<(f64, DocId)>,
+    accessor_idx: usize,
+}
+
+impl SegmentTopMetricsCollector {
+    pub fn from_req(req: &TopMetricsAggregationReq, accessor_idx: usize) -> Self {
+        Self {
+            order: req.order(),
+            top: None,
+            accessor_idx,
+        }
+    }
+
+    #[inline]
+    fn collect_with(&mut self, doc: DocId, sort_accessor: &(Column<u64>, ColumnType)) {
+        let (sort_column, sort_column_type) = sort_accessor;
+        let Some(value) = sort_column.values_for_doc(doc).next() else {
+            return;
+        };
+        let sort_value = value_to_f64(value, sort_column_type);
+        let is_better = match self.top {
+            Some((top_sort_value, _)) => is_better(self.order, sort_value, top_sort_value),
+            None => true,
+        };
+        if is_better {
+            self.top = Some((sort_value, doc));
+        }
+    }
+}
+
+impl SegmentAggregationCollector for SegmentTopMetricsCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let agg_with_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+        let top_metrics_req = agg_with_accessor
+            .agg
+            .agg
+            .as_top_metrics()
+            .expect("aggregation request must be of type top metrics");
+
+        let mut intermediate_top_metrics = IntermediateTopMetrics::new(top_metrics_req);
+        if let Some((sort_value, doc)) = self.top {
+            // The metric values are only read for the best document of the segment.
+            let metrics = top_metrics_req
+                .metrics
+                .iter()
+                .zip(&agg_with_accessor.accessors[1..])
+                .map(|(metric, (column, column_type))| {
+                    let value = column
+                        .values_for_doc(doc)
+                        .next()
+                        .map(|value| value_to_f64(value, column_type));
+                    (metric.field.clone(), value)
+                })
+                .collect();
+            intermediate_top_metrics.top = Some(TopMetricsEntry {
+                sort: vec![sort_value],
+                metrics,
+            });
+        }
+
+        results.push(
+            name,
+            IntermediateAggregationResult::Metric(IntermediateMetricResult::TopMetrics(
+                intermediate_top_metrics,
+            )),
+        )
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        // The sort field is the first accessor, see `AggregationWithAccessor`.
+        let sort_accessor = &agg_with_accessor.aggs.values[self.accessor_idx].accessors[0];
+        self.collect_with(doc, sort_accessor);
+        Ok(())
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let sort_accessor = &agg_with_accessor.aggs.values[self.accessor_idx].accessors[0];
+        for &doc in docs {
+            self.collect_with(doc, sort_accessor);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::AggregationCollector;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{DateTime, Index, IndexWriter};
+
+    #[test]
+    fn test_aggregation_top_metrics() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let product = schema_builder.add_text_field("product", STRING | FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let stock = schema_builder.add_i64_field("stock", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // Product, seconds, price, stock.
+        let docs = [
+            ("apple", 10, 1.5, Some(7)),
+            ("apple", 30, 2.5, None),
+            ("pear", 20, 3.0, Some(4)),
+            ("apple", 20, 2.0, Some(3)),
+            ("pear", 10, 4.0, Some(9)),
+        ];
+        for (product_name, seconds, product_price, product_stock) in docs {
+            let mut doc = doc!(
+                product => product_name,
+                date => DateTime::from_timestamp_secs(seconds),
+                price => product_price,
+            );
+            if let Some(product_stock) = product_stock {
+                doc.add_i64(stock, product_stock);
+            }
+            index_writer.add_document(doc)?;
+            // One segment per document.
+            index_writer.commit()?;
+        }
+        index_writer.add_document(doc!(product => "pear", price => 0.5))?;
+        index_writer.commit()?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "cheapest": {
+                "top_metrics": {
+                    "metrics": [{ "field": "date" }],
+                    "sort": { "price": "asc" }
+                }
+            },
+            "products": {
+                "terms": {
+                    "field": "product",
+                    "order": { "latest.price": "desc" }
+                },
+                "aggs": {
+                    "latest": {
+                        "top_metrics": {
+                            "metrics": [{ "field": "price" }, { "field": "stock" }],
+                            "sort": { "date": "desc" }
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let searcher = index.reader()?.searcher();
+        let agg_res: AggregationResults = searcher.search(&AllQuery, &collector)?;
+        let res: Value = serde_json::to_value(&agg_res)?;
+
+        assert_eq!(
+            res["cheapest"],
+            json!({ "top": [{ "sort": [0.5], "metrics": { "date": null } }] })
+        );
+        let buckets = &res["products"]["buckets"];
+        assert_eq!(buckets[0]["key"], "pear");
+        assert_eq!(
+            buckets[0]["latest"],
+            json!({ "top": [{ "sort": [20000.0], "metrics": { "price": 3.0, "stock": 4.0 } }] })
+        );
+        assert_eq!(buckets[1]["key"], "apple");
+        assert_eq!(
+            buckets[1]["latest"],
+            json!({ "top": [{ "sort": [30000.0], "metrics": { "price": 2.5, "stock": null } }] })
+        );
+        Ok(())
+    }
+}
@@ -41,6 +41,9 @@
 //!     - [DateHistogram](bucket::DateHistogramAggregationReq)
 //!     - [Range](bucket::RangeAggregation)
 //!     - [Terms](bucket::TermsAggregation)
+//!     - [Filter](bucket::FilterAggregation)
+//!     - [Filters](bucket::FiltersAggregation)
+//!     - [Composite](bucket::CompositeAggregation)
 //! - [Metric](metric)
 //!     - [Average](metric::AverageAggregation)
 //!     - [Stats](metric::StatsAggregation)
@@ -51,7 +54,11 @@
 //!     - [Count](metric::CountAggregation)
 //!     - [Percentiles](metric::PercentilesAggregationReq)
 //!     - [Cardinality](metric::CardinalityAggregationReq)
+//!     - [GeoCentroid](metric::GeoCentroidAggregation)
+//!     - [GeoBounds](metric::GeoBoundsAggregation)
+//!     - [Rate](metric::RateAggregation)
 //!     - [TopHits](metric::TopHitsAggregationReq)
+//!     - [TopMetrics](metric::TopMetricsAggregationReq)
 //!
 //! # Example
 //! Compute the average metric, by building [`agg_req::Aggregations`], which is built from an
@@ -154,7 +161,7 @@ pub use collector::{
     DEFAULT_BUCKET_LIMIT,
 };
 use columnar::{ColumnType, MonotonicallyMappableToU64};
-pub(crate) use date::format_date;
+pub(crate) use date::{format_date, format_date_with_offset};
 pub use error::AggregationError;
 use itertools::Itertools;
 use serde::de::{self, Visitor};
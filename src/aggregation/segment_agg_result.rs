@@ -8,7 +8,10 @@ use std::fmt::Debug;
 pub(crate) use super::agg_limits::AggregationLimitsGuard;
 use super::agg_req::AggregationVariants;
 use super::agg_req_with_accessor::{AggregationWithAccessor, AggregationsWithAccessor};
-use super::bucket::{SegmentHistogramCollector, SegmentRangeCollector, SegmentTermCollector};
+use super::bucket::{
+    SegmentCompositeCollector, SegmentFilterCollector, SegmentHistogramCollector,
+    SegmentRangeCollector, SegmentTermCollector,
+};
 use super::intermediate_agg_result::IntermediateAggregationResults;
 use super::metric::{
     AverageAggregation, CountAggregation, ExtendedStatsAggregation, MaxAggregation, MinAggregation,
@@ -17,8 +20,9 @@ use super::metric::{
 };
 use crate::aggregation::bucket::TermMissingAgg;
 use crate::aggregation::metric::{
-    CardinalityAggregationReq, SegmentCardinalityCollector, SegmentExtendedStatsCollector,
-    TopHitsSegmentCollector,
+    CardinalityAggregationReq, RateAggregation, SegmentCardinalityCollector,
+    SegmentExtendedStatsCollector, SegmentGeoCollector, SegmentGeoType, SegmentRateCollector,
+    SegmentTopMetricsCollector, TopHitsSegmentCollector,
 };
 
 pub(crate) trait SegmentAggregationCollector: CollectorClone + Debug {
@@ -119,6 +123,19 @@ pub(crate) fn build_single_agg_segment_collector(
             req.field_type,
             accessor_idx,
         )?)),
+        Filter(_) | Filters(_) => Ok(Box::new(SegmentFilterCollector::from_req_and_validate(
+            req.filters_matching_docs.len(),
+            &mut req.sub_aggregation,
+            &mut req.limits,
+            accessor_idx,
+        )?)),
+        Composite(composite) => Ok(Box::new(SegmentCompositeCollector::from_req_and_validate(
+            composite,
+            &mut req.sub_aggregation,
+            &req.accessors,
+            &req.str_dict_columns,
+            accessor_idx,
+        )?)),
         Average(AverageAggregation { missing, .. }) => {
             Ok(Box::new(SegmentStatsCollector::from_req(
                 req.field_type,
@@ -172,9 +189,26 @@ pub(crate) fn build_single_agg_segment_collector(
             accessor_idx,
             req.segment_ordinal,
         ))),
+        TopMetrics(top_metrics_req) => Ok(Box::new(SegmentTopMetricsCollector::from_req(
+            top_metrics_req,
+            accessor_idx,
+        ))),
         Cardinality(CardinalityAggregationReq { missing, .. }) => Ok(Box::new(
             SegmentCardinalityCollector::from_req(req.field_type, accessor_idx, missing),
         )),
+        GeoCentroid(_) => Ok(Box::new(SegmentGeoCollector::from_req(
+            SegmentGeoType::Centroid,
+            accessor_idx,
+        ))),
+        GeoBounds(_) => Ok(Box::new(SegmentGeoCollector::from_req(
+            SegmentGeoType::Bounds,
+            accessor_idx,
+        ))),
+        Rate(RateAggregation { field, .. }) => Ok(Box::new(SegmentRateCollector::from_req(
+            req.field_type,
+            field.is_some(),
+            accessor_idx,
+        ))),
     }
 }
 
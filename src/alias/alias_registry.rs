@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::directory::error::OpenReadError;
+#[cfg(feature = "mmap")]
+use crate::directory::MmapDirectory;
+use crate::directory::{Directory, Lock};
+use crate::query::QueryParser;
+use crate::{MultiSearcher, Searcher, TantivyError};
+
+/// File holding the aliases of an [`AliasRegistry`] in its directory.
+pub static ALIASES_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new("aliases.json"));
+
+/// Serializes the updates of the aliases, possibly made by several processes.
+static ALIASES_LOCK: Lazy<Lock> = Lazy::new(|| Lock {
+    filepath: PathBuf::from(".tantivy-aliases.lock"),
+    is_blocking: true,
+});
+
+/// An index targeted by an alias.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AliasTarget {
+    /// Path of the directory of the index.
+    pub index_path: PathBuf,
+    /// Query restricting the documents of the index visible through the alias, in the syntax
+    /// of the [`QueryParser`]. Its terms must be prefixed with their field, e.g. `level:warn`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+}
+
+impl AliasTarget {
+    /// Creates a target exposing all of the documents of the index in `index_path`.
+    pub fn new<P: Into<PathBuf>>(index_path: P) -> AliasTarget {
+        AliasTarget {
+            index_path: index_path.into(),
+            filter: None,
+        }
+    }
+
+    /// Restricts the documents of the index visible through the alias to the documents
+    /// matching `filter`.
+    #[must_use]
+    pub fn with_filter<S: Into<String>>(mut self, filter: S) -> AliasTarget {
+        self.filter = Some(filter.into());
+        self
+    }
+}
+
+/// A catalog of aliases, each naming one or more indexes.
+///
+/// The aliases are stored in the [`ALIASES_FILEPATH`] file of a directory, which is rewritten
+/// atomically on every update: a reader of the catalog sees either the previous or the new
+/// targets of an alias. Updates take a lock on the directory, so that the registries of several
+/// processes can update different aliases concurrently.
+///
+/// See the [module documentation](super) for an example.
+pub struct AliasRegistry {
+    directory: Box<dyn Directory>,
+    aliases: BTreeMap<String, Vec<AliasTarget>>,
+}
+
+impl AliasRegistry {
+    /// Opens the registry stored in `directory`. The registry is empty if the directory does
+    /// not hold any alias yet.
+    pub fn open<D: Into<Box<dyn Directory>>>(directory: D) -> crate::Result<AliasRegistry> {
+        let directory = directory.into();
+        let aliases = load_aliases(directory.as_ref())?;
+        Ok(AliasRegistry { directory, aliases })
+    }
+
+    /// Opens the registry stored in the directory `directory_path`.
+    #[cfg(feature = "mmap")]
+    pub fn open_in_dir<P: AsRef<Path>>(directory_path: P) -> crate::Result<AliasRegistry> {
+        AliasRegistry::open(MmapDirectory::open(directory_path)?)
+    }
+
+    /// Reloads the aliases, to see the updates made by other registries.
+    pub fn reload(&mut self) -> crate::Result<()> {
+        self.aliases = load_aliases(self.directory.as_ref())?;
+        Ok(())
+    }
+
+    /// Returns the names of the aliases, in alphabetical order.
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.aliases.keys().map(String::as_str)
+    }
+
+    /// Returns the targets of `alias`, if it exists.
+    pub fn targets(&self, alias: &str) -> Option<&[AliasTarget]> {
+        self.aliases.get(alias).map(Vec::as_slice)
+    }
+
+    /// Points `alias` to `targets`, creating the alias if needed.
+    ///
+    /// Returns an error if `targets` is empty.
+    pub fn set_alias(&mut self, alias: &str, targets: Vec<AliasTarget>) -> crate::Result<()> {
+        if targets.is_empty() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The alias {alias:?} must have at least one target"
+            )));
+        }
+        self.update(|aliases| {
+            aliases.insert(alias.to_string(), targets);
+        })
+    }
+
+    /// Removes `alias`, returning its targets if it existed.
+    pub fn remove_alias(&mut self, alias: &str) -> crate::Result<Option<Vec<AliasTarget>>> {
+        let mut removed_targets = None;
+        self.update(|aliases| {
+            removed_targets = aliases.remove(alias);
+        })?;
+        Ok(removed_targets)
+    }
+
+    /// Applies `update` to the latest aliases of the directory and saves them.
+    fn update(
+        &mut self,
+        update: impl FnOnce(&mut BTreeMap<String, Vec<AliasTarget>>),
+    ) -> crate::Result<()> {
+        let _aliases_lock = self.directory.acquire_lock(&ALIASES_LOCK)?;
+        let mut aliases = load_aliases(self.directory.as_ref())?;
+        update(&mut aliases);
+        let aliases_json = serde_json::to_vec_pretty(&aliases)
+            .map_err(|err| TantivyError::InternalError(err.to_string()))?;
+        self.directory
+            .atomic_write(&ALIASES_FILEPATH, &aliases_json)?;
+        self.aliases = aliases;
+        Ok(())
+    }
+
+    /// Resolves `alias` into a [`MultiSearcher`] over its targets, in order, with the filter of
+    /// each target.
+    ///
+    /// `open_searcher` returns the searcher of the index in a given path. Applications
+    /// typically keep an [`IndexReader`](crate::IndexReader) per index, and return its current
+    /// searcher. The filters are parsed with the tokenizers of the index of the first target.
+    ///
+    /// Returns an error if the alias does not exist.
+    pub fn resolve_with<F>(&self, alias: &str, mut open_searcher: F) -> crate::Result<MultiSearcher>
+    where F: FnMut(&Path) -> crate::Result<Searcher> {
+        let targets = self
+            .targets(alias)
+            .ok_or_else(|| TantivyError::InvalidArgument(format!("Unknown alias {alias:?}")))?;
+        let searchers = targets
+            .iter()
+            .map(|target| open_searcher(&target.index_path))
+            .collect::<crate::Result<Vec<Searcher>>>()?;
+        let mut multi_searcher = MultiSearcher::new(searchers)?;
+        let query_parser = QueryParser::for_index(multi_searcher.searcher().index(), Vec::new());
+        for (index_ord, target) in targets.iter().enumerate() {
+            if let Some(filter) = target.filter.as_ref() {
+                let filter_query = query_parser.parse_query(filter)?;
+                multi_searcher = multi_searcher.with_filter(index_ord, filter_query);
+            }
+        }
+        Ok(multi_searcher)
+    }
+
+    /// Resolves `alias` into a [`MultiSearcher`], opening a reader on each of the indexes it
+    /// targets.
+    ///
+    /// Opening readers is expensive: applications searching an alias repeatedly should keep
+    /// the readers open, and use [`AliasRegistry::resolve_with`].
+    #[cfg(feature = "mmap")]
+    pub fn resolve(&self, alias: &str) -> crate::Result<MultiSearcher> {
+        self.resolve_with(alias, |index_path| {
+            Ok(crate::Index::open_in_dir(index_path)?.reader()?.searcher())
+        })
+    }
+}
+
+fn load_aliases(directory: &dyn Directory) -> crate::Result<BTreeMap<String, Vec<AliasTarget>>> {
+    match directory.atomic_read(&ALIASES_FILEPATH) {
+        Ok(aliases_json) => serde_json::from_slice(&aliases_json).map_err(|err| {
+            TantivyError::DataCorruption(crate::error::DataCorruption::new(
+                ALIASES_FILEPATH.to_path_buf(),
+                format!("Failed to deserialize the aliases: {err}"),
+            ))
+        }),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(BTreeMap::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use super::{AliasRegistry, AliasTarget};
+    use crate::collector::Count;
+    use crate::directory::RamDirectory;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, TantivyError};
+
+    #[test]
+    fn test_alias_registry() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let level = schema_builder.add_text_field("level", STRING);
+        let schema = schema_builder.build();
+        let mut indexes: HashMap<PathBuf, Index> = HashMap::new();
+        for (index_path, levels) in [("logs-1", ["info", "warn"]), ("logs-2", ["info", "info"])] {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for level_value in levels {
+                index_writer.add_document(doc!(level => level_value))?;
+            }
+            index_writer.commit()?;
+            indexes.insert(PathBuf::from(index_path), index);
+        }
+        let open_searcher = |index_path: &Path| Ok(indexes[index_path].reader()?.searcher());
+
+        let directory = RamDirectory::create();
+        let mut registry = AliasRegistry::open(directory.clone())?;
+        assert_eq!(registry.aliases().count(), 0);
+        assert!(matches!(
+            registry.set_alias("logs-current", Vec::new()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            registry.resolve_with("logs-current", open_searcher),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        registry.set_alias("logs-current", vec![AliasTarget::new("logs-1")])?;
+        let multi_searcher = registry.resolve_with("logs-current", open_searcher)?;
+        assert_eq!(multi_searcher.search(&AllQuery, &Count)?, 2);
+
+        // Another registry sees the switch of the alias once reloaded.
+        let mut other_registry = AliasRegistry::open(directory.clone())?;
+        let targets = vec![
+            AliasTarget::new("logs-1").with_filter("level:warn"),
+            AliasTarget::new("logs-2"),
+        ];
+        other_registry.set_alias("logs-current", targets.clone())?;
+        other_registry.set_alias("logs-old", vec![AliasTarget::new("logs-1")])?;
+        assert_eq!(registry.targets("logs-current").unwrap().len(), 1);
+        registry.reload()?;
+        assert_eq!(registry.targets("logs-current"), Some(&targets[..]));
+        assert_eq!(
+            registry.aliases().collect::<Vec<_>>(),
+            vec!["logs-current", "logs-old"]
+        );
+        let multi_searcher = registry.resolve_with("logs-current", open_searcher)?;
+        assert_eq!(multi_searcher.search(&AllQuery, &Count)?, 3);
+        assert!(multi_searcher.filter(0).is_some());
+        assert!(multi_searcher.filter(1).is_none());
+
+        assert_eq!(
+            registry.remove_alias("logs-old")?,
+            Some(vec![AliasTarget::new("logs-1")])
+        );
+        assert_eq!(registry.remove_alias("logs-old")?, None);
+        let registry = AliasRegistry::open(directory)?;
+        assert_eq!(registry.aliases().collect::<Vec<_>>(), vec!["logs-current"]);
+        Ok(())
+    }
+}
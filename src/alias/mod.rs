@@ -0,0 +1,54 @@
+//! Aliases naming a set of indexes.
+//!
+//! An alias maps a stable name, like `logs-current`, to one or more indexes, each with
+//! an optional filter query. Searches go through the alias, which can be switched to
+//! other indexes atomically, e.g. when a new time partition or a reindexed copy of an index
+//! goes live.
+//!
+//! The aliases are stored in an [`AliasRegistry`], a small catalog file kept in a
+//! [`Directory`](crate::Directory). An alias is resolved into a
+//! [`MultiSearcher`](crate::MultiSearcher) over its indexes.
+//!
+//! ```rust
+//! use tantivy::alias::{AliasRegistry, AliasTarget};
+//! use tantivy::collector::Count;
+//! use tantivy::query::AllQuery;
+//! use tantivy::schema::{Schema, STRING};
+//! use tantivy::{doc, Index, IndexWriter};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let level = schema_builder.add_text_field("level", STRING);
+//! let schema = schema_builder.build();
+//!
+//! let root = tempfile::TempDir::new()?;
+//! for (index_name, levels) in [("logs-1", ["info", "warn"]), ("logs-2", ["info", "info"])] {
+//!     let index_path = root.path().join(index_name);
+//!     std::fs::create_dir(&index_path)?;
+//!     let index = Index::create_in_dir(&index_path, schema.clone())?;
+//!     let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+//!     for level_value in levels {
+//!         index_writer.add_document(doc!(level => level_value))?;
+//!     }
+//!     index_writer.commit()?;
+//! }
+//!
+//! let mut aliases = AliasRegistry::open_in_dir(root.path())?;
+//! aliases.set_alias("logs-current", vec![AliasTarget::new(root.path().join("logs-1"))])?;
+//! assert_eq!(aliases.resolve("logs-current")?.search(&AllQuery, &Count)?, 2);
+//!
+//! aliases.set_alias(
+//!     "logs-current",
+//!     vec![
+//!         AliasTarget::new(root.path().join("logs-1")).with_filter("level:warn"),
+//!         AliasTarget::new(root.path().join("logs-2")),
+//!     ],
+//! )?;
+//! assert_eq!(aliases.resolve("logs-current")?.search(&AllQuery, &Count)?, 3);
+//! # Ok(())
+//! # }
+//! ```
+
+mod alias_registry;
+
+pub use self::alias_registry::{AliasRegistry, AliasTarget, ALIASES_FILEPATH};
@@ -0,0 +1,460 @@
+//! C ABI, to build bindings for other languages on top of a small, stable surface.
+//!
+//! The data exchanged with the caller is JSON, so that bindings do not need to mirror the
+//! internal types of tantivy:
+//! - the schema is given as its JSON serialization, see [`Schema`],
+//! - documents are given as newline-delimited JSON objects, see [`index_jsonl`],
+//! - a search request is a JSON object with the query text and optionally the default fields,
+//!   the number of hits, the offset, and [aggregations](crate::aggregation), for instance
+//!   `{"query": "title:shoes", "limit": 10, "aggregations": {...}}`,
+//! - the search response is a JSON object with the number of matching documents, the hits with
+//!   their score and stored fields, and the aggregation results.
+//!
+//! Strings are NUL-terminated and UTF-8 encoded. The strings returned by tantivy, including the
+//! error messages, are owned by the caller and must be released with [`tantivy_string_free`].
+//! Functions report errors through their return value, and write the error message into
+//! `error_out` if it is not null.
+//!
+//! The functions are exported by any `cdylib` or `staticlib` crate depending on tantivy with the
+//! `capi` feature.
+
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::agg_req::Aggregations;
+use crate::aggregation::agg_result::AggregationResults;
+use crate::aggregation::AggregationCollector;
+use crate::collector::{Count, TopDocs};
+use crate::ingest::{index_jsonl, IngestOptions};
+use crate::query::QueryParser;
+use crate::schema::{Document, FieldType, NamedFieldDocument, Schema};
+use crate::{Index, IndexReader, IndexWriter, ReloadPolicy, Score, TantivyDocument, TantivyError};
+
+/// Memory budget of the index writer of a [`TantivyIndex`].
+const WRITER_MEMORY_BUDGET_IN_BYTES: usize = 50_000_000;
+
+/// Opaque handle on an index, with its reader and writer.
+///
+/// The handle can be shared across threads.
+pub struct TantivyIndex {
+    index: Index,
+    reader: IndexReader,
+    index_writer: Mutex<Option<IndexWriter>>,
+}
+
+impl TantivyIndex {
+    fn new(index: Index) -> crate::Result<TantivyIndex> {
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(TantivyIndex {
+            index,
+            reader,
+            index_writer: Mutex::new(None),
+        })
+    }
+
+    fn add_documents(&self, docs_jsonl: &str) -> crate::Result<u64> {
+        let mut index_writer_opt = self.index_writer.lock()?;
+        let index_writer = match index_writer_opt.as_mut() {
+            Some(index_writer) => index_writer,
+            None => index_writer_opt.insert(self.index.writer(WRITER_MEMORY_BUDGET_IN_BYTES)?),
+        };
+        let report = index_jsonl(
+            index_writer,
+            docs_jsonl.as_bytes(),
+            IngestOptions::default(),
+        )?;
+        Ok(report.num_docs_indexed)
+    }
+
+    fn commit(&self) -> crate::Result<()> {
+        let mut index_writer_opt = self.index_writer.lock()?;
+        if let Some(index_writer) = index_writer_opt.as_mut() {
+            index_writer.commit()?;
+        }
+        self.reader.reload()
+    }
+
+    fn search(&self, request: SearchRequest) -> crate::Result<SearchResponse> {
+        let schema = self.index.schema();
+        let default_fields = if request.default_fields.is_empty() {
+            schema
+                .fields()
+                .filter(|(_, field_entry)| {
+                    field_entry.is_indexed()
+                        && matches!(
+                            field_entry.field_type(),
+                            FieldType::Str(_) | FieldType::JsonObject(_)
+                        )
+                })
+                .map(|(field, _)| field)
+                .collect()
+        } else {
+            request
+                .default_fields
+                .iter()
+                .map(|field_name| schema.get_field(field_name))
+                .collect::<crate::Result<Vec<_>>>()?
+        };
+        let query_parser = QueryParser::for_index(&self.index, default_fields);
+        let query = query_parser.parse_query(&request.query)?;
+        let top_docs_opt = (request.limit > 0)
+            .then(|| TopDocs::with_limit(request.limit).and_offset(request.offset));
+        let aggregation_collector_opt = request
+            .aggregations
+            .map(|aggregations| AggregationCollector::from_aggs(aggregations, Default::default()));
+        let searcher = self.reader.searcher();
+        let (count, top_docs_opt, aggregation_results_opt) =
+            searcher.search(&query, &(Count, top_docs_opt, aggregation_collector_opt))?;
+        let mut hits = Vec::new();
+        for (score, doc_address) in top_docs_opt.unwrap_or_default() {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            hits.push(SearchHit {
+                score,
+                doc: doc.to_named_doc(&schema),
+            });
+        }
+        Ok(SearchResponse {
+            count,
+            hits,
+            aggregations: aggregation_results_opt,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SearchRequest {
+    query: String,
+    /// Fields searched by the terms of the query without a field. Defaults to all of the
+    /// indexed text and JSON fields.
+    #[serde(default)]
+    default_fields: Vec<String>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    aggregations: Option<Aggregations>,
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+#[derive(Serialize)]
+struct SearchHit {
+    score: Score,
+    doc: NamedFieldDocument,
+}
+
+#[derive(Serialize)]
+struct SearchResponse {
+    count: usize,
+    hits: Vec<SearchHit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aggregations: Option<AggregationResults>,
+}
+
+/// Runs `f`, turning errors and panics into `error_value` and an error message written to
+/// `error_out`.
+unsafe fn ffi_call<T>(
+    error_out: *mut *mut c_char,
+    error_value: T,
+    f: impl FnOnce() -> crate::Result<T>,
+) -> T {
+    let error_message = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error.to_string(),
+        Err(_) => "tantivy panicked".to_string(),
+    };
+    if !error_out.is_null() {
+        *error_out = into_c_string(error_message);
+    }
+    error_value
+}
+
+fn into_c_string(text: String) -> *mut c_char {
+    // Interior NUL bytes cannot be represented in a C string.
+    let text = text.replace('\0', "");
+    CString::new(text)
+        .expect("NUL bytes were removed")
+        .into_raw()
+}
+
+unsafe fn str_arg<'a>(arg: *const c_char, arg_name: &str) -> crate::Result<&'a str> {
+    if arg.is_null() {
+        return Err(TantivyError::InvalidArgument(format!(
+            "{arg_name} must not be null"
+        )));
+    }
+    CStr::from_ptr(arg)
+        .to_str()
+        .map_err(|_| TantivyError::InvalidArgument(format!("{arg_name} is not valid UTF-8")))
+}
+
+unsafe fn index_arg<'a>(index: *const TantivyIndex) -> crate::Result<&'a TantivyIndex> {
+    index
+        .as_ref()
+        .ok_or_else(|| TantivyError::InvalidArgument("index must not be null".to_string()))
+}
+
+/// Creates an index with the given JSON schema, in the directory `path`, or in RAM if `path`
+/// is null.
+///
+/// Returns null on error. The index must be released with [`tantivy_index_free`].
+///
+/// # Safety
+///
+/// `schema_json` and `path` must be null or valid NUL-terminated strings, and `error_out` must
+/// be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_create(
+    schema_json: *const c_char,
+    path: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut TantivyIndex {
+    ffi_call(error_out, ptr::null_mut(), || {
+        let schema: Schema = serde_json::from_str(str_arg(schema_json, "schema_json")?)
+            .map_err(|err| TantivyError::InvalidArgument(format!("Invalid schema: {err}")))?;
+        let index = if path.is_null() {
+            Index::create_in_ram(schema)
+        } else {
+            Index::create_in_dir(str_arg(path, "path")?, schema)?
+        };
+        Ok(Box::into_raw(Box::new(TantivyIndex::new(index)?)))
+    })
+}
+
+/// Opens the index in the directory `path`.
+///
+/// Returns null on error. The index must be released with [`tantivy_index_free`].
+///
+/// # Safety
+///
+/// `path` must be null or a valid NUL-terminated string, and `error_out` must be null or a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_open(
+    path: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut TantivyIndex {
+    ffi_call(error_out, ptr::null_mut(), || {
+        let index = Index::open_in_dir(str_arg(path, "path")?)?;
+        Ok(Box::into_raw(Box::new(TantivyIndex::new(index)?)))
+    })
+}
+
+/// Releases an index, discarding the documents added since the last commit.
+///
+/// # Safety
+///
+/// `index` must be null or a pointer returned by [`tantivy_index_create`] or
+/// [`tantivy_index_open`], which is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_free(index: *mut TantivyIndex) {
+    if !index.is_null() {
+        drop(Box::from_raw(index));
+    }
+}
+
+/// Adds documents given as newline-delimited JSON objects.
+///
+/// The documents are searchable after [`tantivy_index_commit`]. Returns the number of documents
+/// added, or -1 on error. On error, the documents preceding the invalid one may have been added.
+///
+/// # Safety
+///
+/// `index` must be null or a valid index, `docs_jsonl` must be null or a valid NUL-terminated
+/// string, and `error_out` must be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_add_documents(
+    index: *const TantivyIndex,
+    docs_jsonl: *const c_char,
+    error_out: *mut *mut c_char,
+) -> i64 {
+    ffi_call(error_out, -1, || {
+        let index = index_arg(index)?;
+        let num_docs = index.add_documents(str_arg(docs_jsonl, "docs_jsonl")?)?;
+        Ok(num_docs as i64)
+    })
+}
+
+/// Commits the documents added to the index, and makes them searchable.
+///
+/// Returns 0, or -1 on error.
+///
+/// # Safety
+///
+/// `index` must be null or a valid index, and `error_out` must be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_commit(
+    index: *const TantivyIndex,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    ffi_call(error_out, -1, || {
+        index_arg(index)?.commit()?;
+        Ok(0)
+    })
+}
+
+/// Runs a search request given as JSON, and returns the response as JSON.
+///
+/// Returns null on error. The response must be released with [`tantivy_string_free`].
+///
+/// # Safety
+///
+/// `index` must be null or a valid index, `request_json` must be null or a valid
+/// NUL-terminated string, and `error_out` must be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_search(
+    index: *const TantivyIndex,
+    request_json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    ffi_call(error_out, ptr::null_mut(), || {
+        let index = index_arg(index)?;
+        let request: SearchRequest = serde_json::from_str(str_arg(request_json, "request_json")?)
+            .map_err(|err| {
+            TantivyError::InvalidArgument(format!("Invalid search request: {err}"))
+        })?;
+        let response = index.search(request)?;
+        let response_json =
+            serde_json::to_string(&response).expect("search response encoding failed");
+        Ok(into_c_string(response_json))
+    })
+}
+
+/// Releases a string returned by tantivy.
+///
+/// # Safety
+///
+/// `text` must be null or a string returned by tantivy, which is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_char, CStr, CString};
+    use std::ptr;
+
+    use super::*;
+    use crate::schema::{Schema, FAST, STORED, STRING, TEXT};
+
+    fn c_string(text: &str) -> CString {
+        CString::new(text).unwrap()
+    }
+
+    unsafe fn take_string(text: *mut c_char) -> String {
+        assert!(!text.is_null());
+        let owned = CStr::from_ptr(text).to_str().unwrap().to_string();
+        tantivy_string_free(text);
+        owned
+    }
+
+    #[test]
+    fn test_capi() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_text_field("brand", STRING | FAST);
+        let schema_json = c_string(&serde_json::to_string(&schema_builder.build()).unwrap());
+        unsafe {
+            let mut error = ptr::null_mut();
+            let index = tantivy_index_create(schema_json.as_ptr(), ptr::null(), &mut error);
+            assert!(!index.is_null());
+            assert!(error.is_null());
+
+            let docs = c_string(
+                "{\"title\": \"red shoes\", \"brand\": \"acme\"}\n\n{\"title\": \"blue shoes\", \
+                 \"brand\": \"acme\"}\n{\"title\": \"red hat\", \"brand\": \"other\"}\n",
+            );
+            assert_eq!(
+                tantivy_index_add_documents(index, docs.as_ptr(), &mut error),
+                3
+            );
+            let invalid_docs = c_string("not json\n");
+            assert_eq!(
+                tantivy_index_add_documents(index, invalid_docs.as_ptr(), &mut error),
+                -1
+            );
+            assert!(!take_string(error).is_empty());
+            error = ptr::null_mut();
+            assert_eq!(tantivy_index_commit(index, &mut error), 0);
+
+            let request = c_string(
+                r#"{"query": "shoes", "limit": 1, "aggregations": {"brands": {"terms": {"field": "brand"}}}}"#,
+            );
+            let response = tantivy_index_search(index, request.as_ptr(), &mut error);
+            let response: serde_json::Value = serde_json::from_str(&take_string(response)).unwrap();
+            assert_eq!(response["count"], 2);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            assert!(response["hits"][0]["doc"]["title"][0]
+                .as_str()
+                .unwrap()
+                .ends_with("shoes"));
+            assert_eq!(
+                response["aggregations"]["brands"]["buckets"][0]["key"],
+                "acme"
+            );
+            assert_eq!(
+                response["aggregations"]["brands"]["buckets"][0]["doc_count"],
+                2
+            );
+
+            let request = c_string(r#"{"query": "title:(", "limit": 1}"#);
+            let response = tantivy_index_search(index, request.as_ptr(), &mut error);
+            assert!(response.is_null());
+            assert!(!take_string(error).is_empty());
+            let response = tantivy_index_search(index, request.as_ptr(), ptr::null_mut());
+            assert!(response.is_null());
+
+            tantivy_index_free(index);
+            assert_eq!(tantivy_index_commit(ptr::null(), ptr::null_mut()), -1);
+        }
+    }
+
+    #[test]
+    fn test_capi_open() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        let schema_json = c_string(&serde_json::to_string(&schema_builder.build()).unwrap());
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let path = c_string(tempdir.path().to_str().unwrap());
+        unsafe {
+            let index = tantivy_index_create(schema_json.as_ptr(), path.as_ptr(), ptr::null_mut());
+            let docs = c_string("{\"title\": \"red shoes\"}");
+            assert_eq!(
+                tantivy_index_add_documents(index, docs.as_ptr(), ptr::null_mut()),
+                1
+            );
+            assert_eq!(tantivy_index_commit(index, ptr::null_mut()), 0);
+            tantivy_index_free(index);
+
+            // The directory already holds an index.
+            let mut error = ptr::null_mut();
+            let index = tantivy_index_create(schema_json.as_ptr(), path.as_ptr(), &mut error);
+            assert!(index.is_null());
+            assert!(!take_string(error).is_empty());
+
+            let index = tantivy_index_open(path.as_ptr(), ptr::null_mut());
+            assert!(!index.is_null());
+            let request = c_string(r#"{"query": "shoes", "default_fields": ["title"]}"#);
+            let response = tantivy_index_search(index, request.as_ptr(), ptr::null_mut());
+            let response: serde_json::Value = serde_json::from_str(&take_string(response)).unwrap();
+            assert_eq!(response["count"], 1);
+            assert_eq!(response["hits"][0]["doc"]["title"][0], "red shoes");
+            assert!(response.get("aggregations").is_none());
+            tantivy_index_free(index);
+        }
+    }
+}
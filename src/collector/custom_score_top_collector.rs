@@ -69,6 +69,10 @@ where
         false
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.collector.memory_estimate()
+    }
+
     fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> crate::Result<Self::Fruit> {
         self.collector.merge_fruits(segment_fruits)
     }
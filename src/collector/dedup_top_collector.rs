@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use columnar::{Column, ColumnType};
+
+use crate::collector::top_collector::TopCollector;
+use crate::collector::{Collector, SegmentCollector, TopNComputer};
+use crate::schema::Type;
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// The column types whose `u64` representation does not depend on the segment.
+const DEDUP_KEY_COLUMN_TYPES: [ColumnType; 5] = [
+    ColumnType::U64,
+    ColumnType::I64,
+    ColumnType::F64,
+    ColumnType::Bool,
+    ColumnType::DateTime,
+];
+
+/// Collects the top documents by score, keeping only the best document per value of a
+/// numerical fast field.
+///
+/// See [`TopDocs::dedup_by_field`](crate::collector::TopDocs::dedup_by_field).
+pub(crate) struct DedupTopCollector {
+    field: String,
+    collector: TopCollector<Score>,
+}
+
+impl DedupTopCollector {
+    pub(crate) fn new(field: String, collector: TopCollector<Score>) -> DedupTopCollector {
+        DedupTopCollector { field, collector }
+    }
+
+    fn key_column(&self, segment_reader: &SegmentReader) -> crate::Result<Option<Column<u64>>> {
+        let schema = segment_reader.schema();
+        let (field, json_path) = schema
+            .find_field(&self.field)
+            .ok_or_else(|| TantivyError::FieldNotFound(self.field.clone()))?;
+        let field_entry = schema.get_field_entry(field);
+        let value_type = field_entry.field_type().value_type();
+        if !json_path.is_empty() && value_type != Type::Json {
+            return Err(TantivyError::FieldNotFound(self.field.clone()));
+        }
+        if !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a fast field.",
+                field_entry.name()
+            )));
+        }
+        // The term ordinals of text and bytes columns are local to the segment.
+        if !matches!(
+            value_type,
+            Type::U64 | Type::I64 | Type::F64 | Type::Bool | Type::Date | Type::Json
+        ) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} of type {value_type:?} cannot be used as a dedup key.",
+                field_entry.name()
+            )));
+        }
+        let key_column_opt = segment_reader
+            .fast_fields()
+            .u64_lenient_for_type(Some(&DEDUP_KEY_COLUMN_TYPES), &self.field)?
+            .map(|(column, _)| column);
+        Ok(key_column_opt)
+    }
+}
+
+impl Collector for DedupTopCollector {
+    type Fruit = Vec<(Score, DocAddress)>;
+
+    type Child = DedupTopSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<DedupTopSegmentCollector> {
+        Ok(DedupTopSegmentCollector {
+            segment_ord: segment_local_id,
+            key_column_opt: self.key_column(segment_reader)?,
+            best_per_key: HashMap::new(),
+            top_without_key: TopNComputer::new(self.collector.limit + self.collector.offset),
+            limit: self.collector.limit + self.collector.offset,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn memory_estimate(&self) -> usize {
+        self.collector.memory_estimate()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(Score, DocAddress, Option<u64>)>>,
+    ) -> crate::Result<Vec<(Score, DocAddress)>> {
+        // The same key may be the best of several segments.
+        let mut best_per_key: HashMap<u64, (Score, DocAddress)> = HashMap::new();
+        let mut docs_without_key = Vec::new();
+        for (score, doc_address, key_opt) in segment_fruits.into_iter().flatten() {
+            let Some(key) = key_opt else {
+                docs_without_key.push((score, doc_address));
+                continue;
+            };
+            keep_best(&mut best_per_key, key, score, doc_address);
+        }
+        self.collector
+            .merge_fruits(vec![best_per_key.into_values().collect(), docs_without_key])
+    }
+}
+
+/// Keeps the document ranked first by [`TopDocs`](crate::collector::TopDocs) for the key:
+/// the highest score, then the lowest address.
+fn keep_best<D: PartialOrd + Copy>(
+    best_per_key: &mut HashMap<u64, (Score, D)>,
+    key: u64,
+    score: Score,
+    doc: D,
+) {
+    best_per_key
+        .entry(key)
+        .and_modify(|(best_score, best_doc)| {
+            if score > *best_score || (score == *best_score && doc < *best_doc) {
+                *best_score = score;
+                *best_doc = doc;
+            }
+        })
+        .or_insert((score, doc));
+}
+
+/// Segment collector associated with the [`DedupTopCollector`].
+pub struct DedupTopSegmentCollector {
+    segment_ord: SegmentOrdinal,
+    key_column_opt: Option<Column<u64>>,
+    best_per_key: HashMap<u64, (Score, DocId)>,
+    top_without_key: TopNComputer<Score, DocId>,
+    limit: usize,
+}
+
+impl SegmentCollector for DedupTopSegmentCollector {
+    type Fruit = Vec<(Score, DocAddress, Option<u64>)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let key_opt = self
+            .key_column_opt
+            .as_ref()
+            .and_then(|key_column| key_column.first(doc));
+        match key_opt {
+            Some(key) => keep_best(&mut self.best_per_key, key, score, doc),
+            None => self.top_without_key.push(score, doc),
+        }
+    }
+
+    fn harvest(self) -> Vec<(Score, DocAddress, Option<u64>)> {
+        // The best document of a key among the top `limit` keys of all segments is
+        // necessarily among the top `limit` keys of its segment.
+        let mut top_n: TopNComputer<Score, (DocId, Option<u64>)> = TopNComputer::new(self.limit);
+        for (key, (score, doc)) in self.best_per_key {
+            top_n.push(score, (doc, Some(key)));
+        }
+        for comparable_doc in self.top_without_key.into_vec() {
+            top_n.push(comparable_doc.feature, (comparable_doc.doc, None));
+        }
+        let segment_ord = self.segment_ord;
+        top_n
+            .into_sorted_vec()
+            .into_iter()
+            .map(|comparable_doc| {
+                let (doc_id, key_opt) = comparable_doc.doc;
+                (
+                    comparable_doc.feature,
+                    DocAddress::new(segment_ord, doc_id),
+                    key_opt,
+                )
+            })
+            .collect()
+    }
+}
@@ -127,6 +127,10 @@ where
         self.collector.requires_scoring()
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.collector.memory_estimate()
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<TCollector::Child as SegmentCollector>::Fruit>,
@@ -281,6 +285,10 @@ where
         self.collector.requires_scoring()
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.collector.memory_estimate()
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<TCollector::Child as SegmentCollector>::Fruit>,
@@ -1,6 +1,4 @@
-use std::sync::Arc;
-
-use columnar::ColumnValues;
+use columnar::Column;
 use fastdivide::DividerU64;
 
 use crate::collector::{Collector, SegmentCollector};
@@ -87,15 +85,17 @@ impl HistogramComputer {
 }
 pub struct SegmentHistogramCollector {
     histogram_computer: HistogramComputer,
-    column_u64: Arc<dyn ColumnValues<u64>>,
+    column_u64: Column<u64>,
 }
 
 impl SegmentCollector for SegmentHistogramCollector {
     type Fruit = Vec<u64>;
 
     fn collect(&mut self, doc: DocId, _score: Score) {
-        let value = self.column_u64.get_val(doc);
-        self.histogram_computer.add_value(value);
+        // Documents without a value are ignored.
+        if let Some(value) = self.column_u64.first(doc) {
+            self.histogram_computer.add_value(value);
+        }
     }
 
     fn harvest(self) -> Self::Fruit {
@@ -113,10 +113,9 @@ impl Collector for HistogramCollector {
         segment: &crate::SegmentReader,
     ) -> crate::Result<Self::Child> {
         let column_opt = segment.fast_fields().u64_lenient(&self.field)?;
-        let (column, _column_type) = column_opt.ok_or_else(|| FastFieldNotAvailableError {
+        let (column_u64, _column_type) = column_opt.ok_or_else(|| FastFieldNotAvailableError {
             field_name: self.field.clone(),
         })?;
-        let column_u64 = column.first_or_default_col(0u64);
         Ok(SegmentHistogramCollector {
             histogram_computer: HistogramComputer {
                 counts: vec![0; self.num_buckets],
@@ -274,6 +273,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_histogram_ignores_missing_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val_field = schema_builder.add_u64_field("val_field", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut writer = index.writer_for_tests()?;
+        writer.add_document(doc!(val_field=>0u64))?;
+        writer.add_document(doc!())?;
+        writer.add_document(doc!(val_field=>5u64))?;
+        writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let histogram_collector = HistogramCollector::new("val_field".to_string(), 0u64, 5u64, 2);
+        let histogram = searcher.search(&AllQuery, &histogram_collector)?;
+        assert_eq!(histogram, vec![1, 1]);
+        Ok(())
+    }
+
     #[test]
     fn test_histogram_dates() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::query::{EnableScoring, Query, Scorer};
+use crate::{DocAddress, DocId, DocSet, Score, SegmentOrdinal, SegmentReader};
+
+/// `MatchedQueriesCollector` wraps a top documents collector, such as
+/// [`TopDocs`](crate::collector::TopDocs), and reports for each collected hit the names of the
+/// [named queries](crate::query::NamedQuery) of the query it matches.
+///
+/// The named queries are only evaluated on the hits collected in each segment, by seeking
+/// their scorers, so that the cost does not depend on the number of matching documents.
+/// The names of each hit are in the order the named queries appear in the query.
+///
+/// ```rust
+/// use tantivy::collector::{MatchedQueriesCollector, TopDocs};
+/// use tantivy::query::{BooleanQuery, NamedQuery, Occur, Query, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, STRING, TEXT};
+/// use tantivy::{doc, DocAddress, Index, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let brand = schema_builder.add_text_field("brand", STRING);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "running shoes", brand => "acme"))?;
+/// index_writer.add_document(doc!(title => "shoes", brand => "other"))?;
+/// index_writer.commit()?;
+///
+/// let term_query = |term: Term| -> Box<dyn Query> {
+///     Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+/// };
+/// let query = BooleanQuery::new(vec![
+///     (Occur::Must, term_query(Term::from_field_text(title, "shoes"))),
+///     (
+///         Occur::Should,
+///         Box::new(NamedQuery::new(
+///             "preferred_brand",
+///             term_query(Term::from_field_text(brand, "acme")),
+///         )),
+///     ),
+/// ]);
+/// let searcher = index.reader()?.searcher();
+/// let collector = MatchedQueriesCollector::new(&query, TopDocs::with_limit(2));
+/// let top_docs = searcher.search(&query, &collector)?;
+///
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+/// assert_eq!(top_docs[0].2, ["preferred_brand"]);
+/// assert!(top_docs[1].2.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+pub struct MatchedQueriesCollector<TCollector> {
+    named_queries: Vec<(String, Box<dyn Query>)>,
+    collector: TCollector,
+}
+
+impl<TCollector> MatchedQueriesCollector<TCollector> {
+    /// Creates a collector reporting the named queries of `query` matched by the hits of
+    /// `collector`.
+    ///
+    /// `query` should be the query the search is run with.
+    pub fn new(query: &dyn Query, collector: TCollector) -> MatchedQueriesCollector<TCollector> {
+        let mut named_queries = Vec::new();
+        query.named_queries(&mut |name, named_query| {
+            named_queries.push((name.to_string(), named_query.box_clone()));
+        });
+        MatchedQueriesCollector {
+            named_queries,
+            collector,
+        }
+    }
+}
+
+impl<TCollector, TScore> Collector for MatchedQueriesCollector<TCollector>
+where
+    TCollector: Collector<Fruit = Vec<(TScore, DocAddress)>>,
+    TCollector::Child: SegmentCollector<Fruit = Vec<(TScore, DocAddress)>>,
+    TScore: 'static + Send,
+{
+    type Fruit = Vec<(TScore, DocAddress, Vec<String>)>;
+
+    type Child = MatchedQueriesSegmentCollector<TCollector::Child>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let enable_scoring = EnableScoring::disabled_from_schema(segment_reader.schema());
+        let named_scorers = self
+            .named_queries
+            .iter()
+            .map(|(_, named_query)| {
+                named_query
+                    .weight(enable_scoring)?
+                    .scorer(segment_reader, 1.0)
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(MatchedQueriesSegmentCollector {
+            segment_collector: self
+                .collector
+                .for_segment(segment_local_id, segment_reader)?,
+            named_scorers,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn memory_estimate(&self) -> usize {
+        self.collector.memory_estimate()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<(Vec<(TScore, DocAddress)>, HashMap<DocAddress, Vec<usize>>)>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut matched_per_doc: HashMap<DocAddress, Vec<usize>> = HashMap::new();
+        let mut child_fruits = Vec::with_capacity(segment_fruits.len());
+        for (child_fruit, matched_per_segment_doc) in segment_fruits {
+            matched_per_doc.extend(matched_per_segment_doc);
+            child_fruits.push(child_fruit);
+        }
+        let fruit = self
+            .collector
+            .merge_fruits(child_fruits)?
+            .into_iter()
+            .map(|(score, doc_address)| {
+                let names = matched_per_doc
+                    .get(&doc_address)
+                    .map(|matched| {
+                        matched
+                            .iter()
+                            .map(|&ord| self.named_queries[ord].0.clone())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (score, doc_address, names)
+            })
+            .collect();
+        Ok(fruit)
+    }
+}
+
+/// Segment collector associated with the [`MatchedQueriesCollector`].
+pub struct MatchedQueriesSegmentCollector<TSegmentCollector> {
+    segment_collector: TSegmentCollector,
+    named_scorers: Vec<Box<dyn Scorer>>,
+}
+
+impl<TSegmentCollector, TScore> SegmentCollector
+    for MatchedQueriesSegmentCollector<TSegmentCollector>
+where
+    TSegmentCollector: SegmentCollector<Fruit = Vec<(TScore, DocAddress)>>,
+    TScore: 'static + Send,
+{
+    /// The hits of the segment, and the ordinals of the named queries matched by each of them.
+    type Fruit = (Vec<(TScore, DocAddress)>, HashMap<DocAddress, Vec<usize>>);
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.segment_collector.collect(doc, score);
+    }
+
+    fn harvest(mut self) -> Self::Fruit {
+        let hits = self.segment_collector.harvest();
+        let mut doc_addresses: Vec<DocAddress> =
+            hits.iter().map(|(_, doc_address)| *doc_address).collect();
+        doc_addresses.sort_unstable();
+        let mut matched_per_doc: HashMap<DocAddress, Vec<usize>> = HashMap::new();
+        for (ord, named_scorer) in self.named_scorers.iter_mut().enumerate() {
+            for &doc_address in &doc_addresses {
+                let doc = doc_address.doc_id;
+                if named_scorer.doc() < doc {
+                    named_scorer.seek(doc);
+                }
+                if named_scorer.doc() == doc {
+                    matched_per_doc.entry(doc_address).or_default().push(ord);
+                }
+            }
+        }
+        (hits, matched_per_doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchedQueriesCollector;
+    use crate::collector::TopDocs;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{
+        BooleanQuery, BoostQuery, DisjunctionMaxQuery, NamedQuery, Occur, Query, TermQuery,
+    };
+    use crate::schema::{Field, IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, Term};
+
+    fn term_query(field: Field, text: &str) -> Box<dyn Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(field, text),
+            IndexRecordOption::WithFreqs,
+        ))
+    }
+
+    fn named(name: &str, query: Box<dyn Query>) -> Box<dyn Query> {
+        Box::new(NamedQuery::new(name, query))
+    }
+
+    #[test]
+    fn test_matched_queries_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let color = schema_builder.add_text_field("color", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(title => "red shoes shoes", color => "red"))?;
+        index_writer.add_document(doc!(title => "shoes", color => "blue"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "blue shoes", color => "blue"))?;
+        index_writer.add_document(doc!(title => "shoes", color => "red"))?;
+        index_writer.add_document(doc!(title => "hat", color => "red"))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(title, "blue"));
+        index_writer.commit()?;
+
+        let colors = DisjunctionMaxQuery::new(vec![
+            named("red", term_query(color, "red")),
+            Box::new(BoostQuery::new(
+                named("blue", term_query(color, "blue")),
+                2.0,
+            )),
+        ]);
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, named("shoes", term_query(title, "shoes"))),
+            (Occur::Should, named("color", Box::new(colors))),
+            (Occur::MustNot, named("hat", term_query(title, "hat"))),
+        ]);
+        let mut names = Vec::new();
+        query.named_queries(&mut |name, _| names.push(name));
+        assert_eq!(names, ["shoes", "color", "red", "blue", "hat"]);
+
+        let searcher = index.reader()?.searcher();
+        let segment_ord = |num_docs: u32| -> u32 {
+            searcher
+                .segment_readers()
+                .iter()
+                .position(|segment_reader| segment_reader.max_doc() == num_docs)
+                .unwrap() as u32
+        };
+        let (first, second) = (segment_ord(2), segment_ord(3));
+        let collector = MatchedQueriesCollector::new(&query, TopDocs::with_limit(10));
+        let mut top_docs = searcher.search(&query, &collector)?;
+        top_docs.sort_by_key(|(_, doc_address, _)| *doc_address);
+        let mut expected = vec![
+            (DocAddress::new(first, 0), vec!["shoes", "color", "red"]),
+            (DocAddress::new(first, 1), vec!["shoes", "color", "blue"]),
+            (DocAddress::new(second, 1), vec!["shoes", "color", "red"]),
+        ];
+        expected.sort();
+        let matched: Vec<(DocAddress, Vec<&str>)> = top_docs
+            .iter()
+            .map(|(_, doc_address, names)| {
+                (*doc_address, names.iter().map(String::as_str).collect())
+            })
+            .collect();
+        assert_eq!(matched, expected);
+
+        // The names are reported for the hits returned after the offset.
+        let collector = MatchedQueriesCollector::new(&query, TopDocs::with_limit(1).and_offset(2));
+        let top_docs = searcher.search(&query, &collector)?;
+        assert_eq!(top_docs.len(), 1);
+        assert_eq!(top_docs[0].2.len(), 3);
+
+        // Without named queries, no names are reported.
+        let collector =
+            MatchedQueriesCollector::new(&*term_query(title, "shoes"), TopDocs::with_limit(10));
+        let top_docs = searcher.search(&query, &collector)?;
+        assert!(top_docs.iter().all(|(_, _, names)| names.is_empty()));
+        Ok(())
+    }
+}
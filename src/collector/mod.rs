@@ -103,10 +103,16 @@ pub use self::top_score_collector::{TopDocs, TopNComputer};
 mod custom_score_top_collector;
 pub use self::custom_score_top_collector::{CustomScorer, CustomSegmentScorer};
 
+mod dedup_top_collector;
+
 mod tweak_score_top_collector;
 pub use self::tweak_score_top_collector::{ScoreSegmentTweaker, ScoreTweaker};
 mod facet_collector;
 pub use self::facet_collector::{FacetCollector, FacetCounts};
+mod term_count_collector;
+pub use self::term_count_collector::{
+    TermCountCollector, TermCountSegmentCollector, TermCounts,
+};
 use crate::query::Weight;
 
 mod docset_collector;
@@ -115,6 +121,11 @@ pub use self::docset_collector::DocSetCollector;
 mod filter_collector_wrapper;
 pub use self::filter_collector_wrapper::{BytesFilterCollector, FilterCollector};
 
+mod matched_queries_collector;
+pub use self::matched_queries_collector::{
+    MatchedQueriesCollector, MatchedQueriesSegmentCollector,
+};
+
 /// `Fruit` is the type for the result of our collection.
 /// e.g. `usize` for the `Count` collector.
 pub trait Fruit: Send + downcast_rs::Downcast {}
@@ -156,6 +167,15 @@ pub trait Collector: Sync + Send {
     /// Returns true iff the collector requires to compute scores for documents.
     fn requires_scoring(&self) -> bool;
 
+    /// Estimates the number of bytes the segment collector of each segment buffers until the
+    /// fruits are merged, e.g. the heap of the top documents.
+    ///
+    /// The estimate is reserved against the [`MemoryBudget`](crate::MemoryBudget) of the
+    /// searcher, if any. It defaults to 0.
+    fn memory_estimate(&self) -> usize {
+        0
+    }
+
     /// Combines the fruit associated with the collection of each segments
     /// into one fruit.
     fn merge_fruits(
@@ -243,6 +263,10 @@ impl<TCollector: Collector> Collector for Option<TCollector> {
             .unwrap_or(false)
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.as_ref().map_or(0, Collector::memory_estimate)
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -313,6 +337,10 @@ where
         self.0.requires_scoring() || self.1.requires_scoring()
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.0.memory_estimate() + self.1.memory_estimate()
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -373,6 +401,10 @@ where
         self.0.requires_scoring() || self.1.requires_scoring() || self.2.requires_scoring()
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.0.memory_estimate() + self.1.memory_estimate() + self.2.memory_estimate()
+    }
+
     fn merge_fruits(
         &self,
         children: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -443,6 +475,13 @@ where
             || self.3.requires_scoring()
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.0.memory_estimate()
+            + self.1.memory_estimate()
+            + self.2.memory_estimate()
+            + self.3.memory_estimate()
+    }
+
     fn merge_fruits(
         &self,
         children: Vec<<Self::Child as SegmentCollector>::Fruit>,
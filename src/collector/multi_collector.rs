@@ -29,6 +29,10 @@ impl<TCollector: Collector> Collector for CollectorWrapper<TCollector> {
         self.0.requires_scoring()
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.0.memory_estimate()
+    }
+
     fn merge_fruits(
         &self,
         children: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -214,6 +218,14 @@ impl Collector for MultiCollector<'_> {
             .any(Collector::requires_scoring)
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.collector_wrappers
+            .iter()
+            .map(Deref::deref)
+            .map(Collector::memory_estimate)
+            .sum()
+    }
+
     fn merge_fruits(&self, segments_multifruits: Vec<MultiFruit>) -> crate::Result<MultiFruit> {
         let mut segment_fruits_list: Vec<Vec<Box<dyn Fruit>>> = (0..self.collector_wrappers.len())
             .map(|_| Vec::with_capacity(segments_multifruits.len()))
@@ -0,0 +1,237 @@
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+use columnar::StrColumn;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::GlobalOrdinals;
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Counts the documents per term of a `str` fast field.
+///
+/// The documents are counted per term ordinal in each segment, and the counts are merged on
+/// the [`GlobalOrdinals`] of the field, so that no string is hashed or compared while
+/// collecting. The strings are only resolved for the terms returned by
+/// [`TermCounts::top_k`].
+///
+/// The global ordinals must have been built by the searcher the collector is used with, see
+/// [`Searcher::global_ordinals`](crate::Searcher::global_ordinals).
+///
+/// ```rust
+/// use tantivy::collector::TermCountCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST, STRING};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let color = schema_builder.add_text_field("color", STRING | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(color => "red"))?;
+/// index_writer.add_document(doc!(color => "blue"))?;
+/// index_writer.add_document(doc!(color => "red"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let collector = TermCountCollector::new(searcher.global_ordinals("color")?);
+/// let term_counts = searcher.search(&AllQuery, &collector)?;
+/// assert_eq!(
+///     term_counts.top_k(1)?,
+///     vec![("red".to_string(), 2)]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct TermCountCollector {
+    global_ordinals: Arc<GlobalOrdinals>,
+}
+
+impl TermCountCollector {
+    /// Creates a collector counting the documents per term of the field of the global
+    /// ordinals.
+    pub fn new(global_ordinals: Arc<GlobalOrdinals>) -> TermCountCollector {
+        TermCountCollector { global_ordinals }
+    }
+}
+
+/// Segment collector for [`TermCountCollector`].
+pub struct TermCountSegmentCollector {
+    column_opt: Option<StrColumn>,
+    // Counts per term ordinal of the segment.
+    counts: Vec<u64>,
+    global_ordinals: Arc<GlobalOrdinals>,
+    segment_ord: SegmentOrdinal,
+}
+
+impl SegmentCollector for TermCountSegmentCollector {
+    /// The non-zero counts, per global ordinal.
+    type Fruit = Vec<(u32, u64)>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let Some(column) = self.column_opt.as_ref() else {
+            return;
+        };
+        for term_ord in column.term_ords(doc) {
+            self.counts[term_ord as usize] += 1;
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let segment_mapping = self.global_ordinals.segment_mapping(self.segment_ord);
+        self.counts
+            .into_iter()
+            .zip(segment_mapping)
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, &global_ord)| (global_ord, count))
+            .collect()
+    }
+}
+
+impl Collector for TermCountCollector {
+    type Fruit = TermCounts;
+    type Child = TermCountSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        if segment_local_id as usize >= self.global_ordinals.num_segments()
+            || self.global_ordinals.segment_id(segment_local_id) != segment.segment_id()
+        {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The global ordinals of field {:?} were built by another searcher",
+                self.global_ordinals.field_name()
+            )));
+        }
+        let column_opt = self.global_ordinals.column(segment_local_id).cloned();
+        let num_terms = column_opt
+            .as_ref()
+            .map(|column| column.num_terms())
+            .unwrap_or(0);
+        Ok(TermCountSegmentCollector {
+            column_opt,
+            counts: vec![0; num_terms],
+            global_ordinals: self.global_ordinals.clone(),
+            segment_ord: segment_local_id,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_counts: Vec<Vec<(u32, u64)>>) -> crate::Result<TermCounts> {
+        let mut counts = vec![0u64; self.global_ordinals.num_terms()];
+        for (global_ord, count) in segment_counts.into_iter().flatten() {
+            counts[global_ord as usize] += count;
+        }
+        Ok(TermCounts {
+            global_ordinals: self.global_ordinals.clone(),
+            counts,
+        })
+    }
+}
+
+/// The document counts per term computed by [`TermCountCollector`].
+pub struct TermCounts {
+    global_ordinals: Arc<GlobalOrdinals>,
+    counts: Vec<u64>,
+}
+
+impl TermCounts {
+    /// Returns the document counts, indexed by global ordinal.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Returns the `k` terms with the highest document counts, with their counts.
+    ///
+    /// Terms are sorted by decreasing count, ties by term. Terms without documents are
+    /// ignored. Only the strings of the returned terms are resolved.
+    pub fn top_k(&self, k: usize) -> crate::Result<Vec<(String, u64)>> {
+        let mut top: Vec<(Reverse<u64>, u32)> = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(global_ord, count)| (Reverse(*count), global_ord as u32))
+            .collect();
+        if k < top.len() {
+            top.select_nth_unstable(k);
+            top.truncate(k);
+        }
+        top.sort_unstable();
+        top.into_iter()
+            .map(|(Reverse(count), global_ord)| {
+                let mut term = String::new();
+                self.global_ordinals.ord_to_str(global_ord, &mut term)?;
+                Ok((term, count))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TermCountCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_term_count_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let color = schema_builder.add_text_field("color", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(color => "red", color => "blue"))?;
+        index_writer.add_document(doc!(color => "green"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(color => "blue"))?;
+        index_writer.add_document(doc!(color => "red"))?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(color => "red"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let collector = TermCountCollector::new(searcher.global_ordinals("color")?);
+        let term_counts = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(term_counts.counts(), &[2, 1, 3]);
+        assert_eq!(
+            term_counts.top_k(2)?,
+            vec![("red".to_string(), 3), ("blue".to_string(), 2)]
+        );
+        assert_eq!(term_counts.top_k(10)?.len(), 3);
+
+        let query = TermQuery::new(
+            Term::from_field_text(color, "blue"),
+            IndexRecordOption::Basic,
+        );
+        let term_counts = searcher.search(&query, &collector)?;
+        assert_eq!(
+            term_counts.top_k(10)?,
+            vec![("blue".to_string(), 2), ("red".to_string(), 1)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_term_count_collector_other_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let color = schema_builder.add_text_field("color", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(color => "red"))?;
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let collector = TermCountCollector::new(reader.searcher().global_ordinals("color")?);
+        index_writer.add_document(doc!(color => "blue"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        assert!(reader.searcher().search(&AllQuery, &collector).is_err());
+        Ok(())
+    }
+}
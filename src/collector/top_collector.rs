@@ -99,6 +99,11 @@ where T: PartialOrd + Clone
         self
     }
 
+    /// Returns the number of bytes of the heap of each segment collector.
+    pub fn memory_estimate(&self) -> usize {
+        TopNComputer::<T, DocId>::buffer_num_bytes(self.limit + self.offset)
+    }
+
     pub fn merge_fruits(
         &self,
         children: Vec<Vec<(T, DocAddress)>>,
@@ -2,11 +2,12 @@ use std::fmt;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
-use columnar::ColumnValues;
+use columnar::{Column, ColumnValues, DynamicColumn, HasAssociatedColumnType};
 use serde::{Deserialize, Serialize};
 
 use super::Collector;
 use crate::collector::custom_score_top_collector::CustomScoreTopCollector;
+use crate::collector::dedup_top_collector::DedupTopCollector;
 use crate::collector::top_collector::{ComparableDoc, TopCollector, TopSegmentCollector};
 use crate::collector::tweak_score_top_collector::TweakedScoreTopCollector;
 use crate::collector::{
@@ -14,6 +15,7 @@ use crate::collector::{
 };
 use crate::fastfield::{FastFieldNotAvailableError, FastValue};
 use crate::query::Weight;
+use crate::schema::Type;
 use crate::{DocAddress, DocId, Order, Score, SegmentOrdinal, SegmentReader, TantivyError};
 
 struct FastFieldConvertCollector<
@@ -41,8 +43,14 @@ where
         segment: &SegmentReader,
     ) -> crate::Result<Self::Child> {
         let schema = segment.schema();
-        let field = schema.get_field(&self.field)?;
+        let (field, json_path) = schema
+            .find_field(&self.field)
+            .ok_or_else(|| TantivyError::FieldNotFound(self.field.clone()))?;
         let field_entry = schema.get_field_entry(field);
+        let is_json_field = field_entry.field_type().value_type() == Type::Json;
+        if !json_path.is_empty() && !is_json_field {
+            return Err(TantivyError::FieldNotFound(self.field.clone()));
+        }
         if !field_entry.is_fast() {
             return Err(TantivyError::SchemaError(format!(
                 "Field {:?} is not a fast field.",
@@ -51,7 +59,9 @@ where
         }
         let schema_type = TFastValue::to_type();
         let requested_type = field_entry.field_type().value_type();
-        if schema_type != requested_type {
+        // The values of a JSON path can have any type: documents without a value
+        // of the requested type are ranked last.
+        if schema_type != requested_type && !is_json_field {
             return Err(TantivyError::SchemaError(format!(
                 "Field {:?} is of type {schema_type:?}!={requested_type:?}",
                 field_entry.name()
@@ -64,6 +74,10 @@ where
         self.collector.requires_scoring()
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.collector.memory_estimate()
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -185,6 +199,35 @@ impl CustomScorer<u64> for ScorerByField {
     }
 }
 
+struct ScorerByFastValue<TFastValue> {
+    field: String,
+    order: Order,
+    fast_value: PhantomData<TFastValue>,
+}
+
+impl<TFastValue> CustomScorer<u64> for ScorerByFastValue<TFastValue>
+where
+    TFastValue: FastValue + HasAssociatedColumnType,
+    DynamicColumn: Into<Option<Column<TFastValue>>>,
+{
+    type Child = ScorerByFastFieldReader;
+
+    fn segment_scorer(&self, segment_reader: &SegmentReader) -> crate::Result<Self::Child> {
+        // The column of a JSON path may have another numerical type in this segment,
+        // or not exist at all if no document of the segment has a value.
+        let sort_column: Column<u64> = segment_reader
+            .fast_fields()
+            .column_opt_coerced::<TFastValue>(&self.field)?
+            .map(Column::to_u64_monotonic)
+            .unwrap_or_else(|| Column::build_empty_column(segment_reader.max_doc()));
+        let default_value = if self.order.is_asc() { u64::MAX } else { 0u64 };
+        Ok(ScorerByFastFieldReader {
+            sort_column: sort_column.first_or_default_col(default_value),
+            order: self.order.clone(),
+        })
+    }
+}
+
 impl TopDocs {
     /// Creates a top score collector, with a number of documents equal to "limit".
     ///
@@ -239,6 +282,55 @@ impl TopDocs {
         TopDocs(self.0.and_offset(offset))
     }
 
+    /// Only keeps the highest ranked document for each value of the given fast field, e.g.
+    /// the hash of a canonical URL.
+    ///
+    /// The deduplication happens while collecting, so that the requested number of documents
+    /// is still returned if enough distinct values match. Documents without a value are never
+    /// deduplicated.
+    ///
+    /// The field has to be a numerical, boolean or date fast field, or a JSON path to such
+    /// values. Text and bytes fields are not supported, as their fast field values are only
+    /// comparable within a segment. An error is returned at the moment of search otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::QueryParser;
+    /// use tantivy::schema::{Schema, FAST, TEXT};
+    /// use tantivy::{doc, DocAddress, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let url_hash = schema_builder.add_u64_field("url_hash", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    ///
+    /// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(title => "Diary, diary", url_hash => 1u64))?;
+    /// index_writer.add_document(doc!(title => "The Diary", url_hash => 1u64))?;
+    /// index_writer.add_document(doc!(title => "The Diary of a Young Girl", url_hash => 2u64))?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+    /// let top_docs_by_url = TopDocs::with_limit(2).dedup_by_field("url_hash");
+    /// let top_docs = searcher.search(&query, &top_docs_by_url)?;
+    ///
+    /// assert_eq!(top_docs.len(), 2);
+    /// assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+    /// assert_eq!(top_docs[1].1, DocAddress::new(0, 2));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dedup_by_field(
+        self,
+        field: impl ToString,
+    ) -> impl Collector<Fruit = Vec<(Score, DocAddress)>> {
+        DedupTopCollector::new(field.to_string(), self.0)
+    }
+
     /// Set top-K to rank documents by a given fast field.
     ///
     /// If the field is not a fast or does not exist, this method returns successfully (it is not
@@ -330,6 +422,10 @@ impl TopDocs {
     /// method does not panic, but an explicit error will be returned at the moment of
     /// collection.
     ///
+    /// The field can also be a path of a JSON fast field, e.g. `attributes.price`. Numerical
+    /// values are converted to the requested type, and documents without a value of that type
+    /// are ranked last.
+    ///
     /// Note that this method is a generic. The requested fast field type will be often
     /// inferred in your code by the rust compiler.
     ///
@@ -399,9 +495,17 @@ impl TopDocs {
         order: Order,
     ) -> impl Collector<Fruit = Vec<(TFastValue, DocAddress)>>
     where
-        TFastValue: FastValue,
+        TFastValue: FastValue + HasAssociatedColumnType,
+        DynamicColumn: Into<Option<Column<TFastValue>>>,
     {
-        let u64_collector = self.order_by_u64_field(fast_field.to_string(), order.clone());
+        let u64_collector = CustomScoreTopCollector::new(
+            ScorerByFastValue {
+                field: fast_field.to_string(),
+                order: order.clone(),
+                fast_value: PhantomData,
+            },
+            self.0.into_tscore(),
+        );
         FastFieldConvertCollector {
             collector: u64_collector,
             field: fast_field.to_string(),
@@ -650,6 +754,10 @@ impl Collector for TopDocs {
         true
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.0.memory_estimate()
+    }
+
     fn merge_fruits(
         &self,
         child_fruits: Vec<Vec<(Score, DocAddress)>>,
@@ -802,6 +910,12 @@ where
         }
     }
 
+    /// Returns the number of bytes of the buffer allocated by a `TopNComputer` of `top_n`
+    /// documents.
+    pub(crate) fn buffer_num_bytes(top_n: usize) -> usize {
+        top_n.max(1) * 2 * std::mem::size_of::<ComparableDoc<Score, D, R>>()
+    }
+
     /// Push a new document to the top n.
     /// If the document is below the current threshold, it will be ignored.
     #[inline]
@@ -863,9 +977,12 @@ where
 
 #[cfg(test)]
 mod tests {
+    use serde_json::json;
+
     use super::{TopDocs, TopNComputer};
     use crate::collector::top_collector::ComparableDoc;
     use crate::collector::Collector;
+    use crate::indexer::NoMergePolicy;
     use crate::query::{AllQuery, Query, QueryParser};
     use crate::schema::{Field, Schema, FAST, STORED, TEXT};
     use crate::time::format_description::well_known::Rfc3339;
@@ -1072,6 +1189,71 @@ mod tests {
         TopDocs::with_limit(0);
     }
 
+    #[test]
+    fn test_top_collector_dedup_by_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let url_hash = schema_builder.add_i64_field("url_hash", FAST);
+        schema_builder.add_text_field("title", TEXT | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text => "beer beer beer", url_hash => 1i64))?;
+        index_writer.add_document(doc!(text => "beer beer", url_hash => -2i64))?;
+        index_writer.add_document(doc!(text => "beer"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "beer beer beer beer", url_hash => -2i64))?;
+        index_writer.add_document(doc!(text => "beer beer beer", url_hash => 1i64))?;
+        index_writer.add_document(doc!(text => "beer", url_hash => 3i64))?;
+        index_writer.add_document(doc!(text => "beer"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("beer")?;
+
+        let doc_addresses = |collector: &dyn Fn() -> TopDocs| -> crate::Result<Vec<DocAddress>> {
+            let top_docs = searcher.search(&query, &collector().dedup_by_field("url_hash"))?;
+            Ok(top_docs
+                .into_iter()
+                .map(|(_, doc_address)| doc_address)
+                .collect())
+        };
+        let (first, second) = if searcher.segment_reader(0).max_doc() == 3 {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        // The key -2 is kept in the second segment. The two documents of key 1 have the same
+        // score: the one with the lowest address is kept. The documents without a key are
+        // all kept.
+        let mut expected = vec![
+            DocAddress::new(second, 0),
+            DocAddress::new(first, 0).min(DocAddress::new(second, 1)),
+            DocAddress::new(first, 2),
+            DocAddress::new(second, 2),
+            DocAddress::new(second, 3),
+        ];
+        // Ties are broken by doc address.
+        expected[2..].sort();
+        assert_eq!(doc_addresses(&|| TopDocs::with_limit(10))?, expected);
+        assert_eq!(doc_addresses(&|| TopDocs::with_limit(3))?, expected[..3]);
+        assert_eq!(
+            doc_addresses(&|| TopDocs::with_limit(2).and_offset(2))?,
+            expected[2..4]
+        );
+
+        assert!(searcher
+            .search(&query, &TopDocs::with_limit(3).dedup_by_field("title"))
+            .is_err());
+        assert!(searcher
+            .search(&query, &TopDocs::with_limit(3).dedup_by_field("text"))
+            .is_err());
+        assert!(searcher
+            .search(&query, &TopDocs::with_limit(3).dedup_by_field("missing"))
+            .is_err());
+        Ok(())
+    }
+
     const TITLE: &str = "title";
     const SIZE: &str = "size";
 
@@ -1214,6 +1396,54 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_top_field_collector_json_path() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let attributes = schema_builder.add_json_field("attributes", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(attributes => json!({"price": 10})))?;
+        index_writer.add_document(doc!(attributes => json!({"color": "red"})))?;
+        index_writer.add_document(doc!(attributes => json!({"price": 3})))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(attributes => json!({"price": 7.5})))?;
+        index_writer.add_document(doc!(attributes => json!({"price": "cheap"})))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let top_collector =
+            TopDocs::with_limit(3).order_by_fast_field("attributes.price", Order::Desc);
+        let top_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &top_collector)?;
+        assert_eq!(
+            &top_docs[..],
+            &[
+                (10.0, DocAddress::new(0, 0)),
+                (7.5, DocAddress::new(1, 0)),
+                (3.0, DocAddress::new(0, 2)),
+            ]
+        );
+
+        let top_collector =
+            TopDocs::with_limit(3).order_by_fast_field("attributes.price", Order::Asc);
+        let top_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &top_collector)?;
+        let prices: Vec<f64> = top_docs.iter().map(|(price, _)| *price).collect();
+        assert_eq!(prices, [3.0, 7.5, 10.0]);
+
+        let top_collector =
+            TopDocs::with_limit(3).order_by_fast_field::<f64>("attributes.missing", Order::Desc);
+        assert_eq!(searcher.search(&AllQuery, &top_collector)?.len(), 3);
+
+        let top_collector =
+            TopDocs::with_limit(3).order_by_fast_field::<f64>("missing.price", Order::Desc);
+        assert!(matches!(
+            searcher.search(&AllQuery, &top_collector),
+            Err(crate::TantivyError::FieldNotFound(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     #[should_panic]
     fn test_field_does_not_exist() {
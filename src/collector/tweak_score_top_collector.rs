@@ -71,6 +71,10 @@ where
         true
     }
 
+    fn memory_estimate(&self) -> usize {
+        self.collector.memory_estimate()
+    }
+
     fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> Result<Self::Fruit> {
         self.collector.merge_fruits(segment_fruits)
     }
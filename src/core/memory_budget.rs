@@ -0,0 +1,274 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use common::ByteCount;
+
+/// Error returned when a [`MemoryBudget`] cannot fit a reservation.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Memory budget exceeded. Limit: {limit:?}, Used: {used:?}, Requested: {requested:?}")]
+pub struct MemoryBudgetExceeded {
+    /// Limit of the budget.
+    pub limit: ByteCount,
+    /// Memory reserved against the budget when the reservation was attempted.
+    pub used: ByteCount,
+    /// Memory which could not be reserved.
+    pub requested: ByteCount,
+}
+
+struct InnerMemoryBudget {
+    limit: u64,
+    used: AtomicU64,
+}
+
+/// A budget of memory shared by the searches, the aggregations and the doc store caches
+/// it is attached to.
+///
+/// Cloning a budget is cheap and the clones share the same accounting, so that a single budget
+/// can bound the memory of all of the [`IndexReader`](crate::IndexReader)s and aggregation
+/// requests of a process:
+/// - the doc store caches of the searchers of a reader built with
+///   [`IndexReaderBuilder::memory_budget`](crate::IndexReaderBuilder::memory_budget) stop
+///   caching blocks when the budget is exhausted,
+/// - the searches of these searchers fail with [`TantivyError::MemoryBudgetExceeded`] when the
+///   buffers of their collectors, as estimated by
+///   [`Collector::memory_estimate`](crate::collector::Collector::memory_estimate), do not fit
+///   in the budget,
+/// - the aggregations whose limits were built with [`AggregationLimitsGuard::with_memory_budget`]
+///   fail with a memory limit error when their buckets do not fit in the budget.
+///
+/// [`TantivyError::MemoryBudgetExceeded`]: crate::TantivyError::MemoryBudgetExceeded
+/// [`AggregationLimitsGuard::with_memory_budget`]:
+///     crate::aggregation::AggregationLimitsGuard::with_memory_budget
+#[derive(Clone)]
+pub struct MemoryBudget {
+    inner: Arc<InnerMemoryBudget>,
+}
+
+impl fmt::Debug for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryBudget")
+            .field("limit", &self.limit())
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
+impl MemoryBudget {
+    /// Creates a budget of `limit_num_bytes` bytes.
+    pub fn new(limit_num_bytes: u64) -> MemoryBudget {
+        MemoryBudget {
+            inner: Arc::new(InnerMemoryBudget {
+                limit: limit_num_bytes,
+                used: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Returns the limit of the budget.
+    pub fn limit(&self) -> ByteCount {
+        self.inner.limit.into()
+    }
+
+    /// Returns the memory currently reserved against the budget.
+    pub fn used(&self) -> ByteCount {
+        self.inner.used.load(Ordering::Relaxed).into()
+    }
+
+    /// Returns the memory which can still be reserved.
+    pub fn available(&self) -> ByteCount {
+        self.inner
+            .limit
+            .saturating_sub(self.inner.used.load(Ordering::Relaxed))
+            .into()
+    }
+
+    /// Reserves `num_bytes` bytes, released when the returned reservation is dropped.
+    pub fn try_reserve(&self, num_bytes: u64) -> Result<MemoryReservation, MemoryBudgetExceeded> {
+        let reservation = self.empty_reservation();
+        reservation.try_grow(num_bytes)?;
+        Ok(reservation)
+    }
+
+    /// Returns a reservation of 0 bytes, to be grown as memory gets allocated.
+    pub fn empty_reservation(&self) -> MemoryReservation {
+        MemoryReservation {
+            budget: self.clone(),
+            num_bytes: AtomicU64::new(0),
+        }
+    }
+
+    fn try_acquire(&self, num_bytes: u64) -> Result<(), MemoryBudgetExceeded> {
+        self.inner
+            .used
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |used| {
+                used.checked_add(num_bytes)
+                    .filter(|&new_used| new_used <= self.inner.limit)
+            })
+            .map(|_| ())
+            .map_err(|used| MemoryBudgetExceeded {
+                limit: self.limit(),
+                used: used.into(),
+                requested: num_bytes.into(),
+            })
+    }
+
+    fn release(&self, num_bytes: u64) {
+        self.inner.used.fetch_sub(num_bytes, Ordering::AcqRel);
+    }
+}
+
+/// Memory reserved against a [`MemoryBudget`].
+///
+/// The reserved memory is given back to the budget when the reservation is dropped.
+pub struct MemoryReservation {
+    budget: MemoryBudget,
+    num_bytes: AtomicU64,
+}
+
+impl MemoryReservation {
+    /// Returns the budget the memory is reserved against.
+    pub fn budget(&self) -> &MemoryBudget {
+        &self.budget
+    }
+
+    /// Returns the number of bytes reserved.
+    pub fn num_bytes(&self) -> ByteCount {
+        self.num_bytes.load(Ordering::Relaxed).into()
+    }
+
+    /// Reserves `num_bytes` more bytes, or fails without reserving anything if they do not fit in
+    /// the budget.
+    pub fn try_grow(&self, num_bytes: u64) -> Result<(), MemoryBudgetExceeded> {
+        self.budget.try_acquire(num_bytes)?;
+        self.num_bytes.fetch_add(num_bytes, Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// Gives `num_bytes` bytes of the reservation back to the budget.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `num_bytes` bytes are reserved.
+    pub fn shrink(&self, num_bytes: u64) {
+        // The reservation is left untouched if the check fails, so that dropping it during the
+        // unwinding releases the right amount.
+        if let Err(previous_num_bytes) =
+            self.num_bytes
+                .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |previous_num_bytes| {
+                    previous_num_bytes.checked_sub(num_bytes)
+                })
+        {
+            panic!(
+                "Cannot shrink a reservation of {previous_num_bytes} bytes by {num_bytes} bytes"
+            );
+        }
+        self.budget.release(num_bytes);
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.budget.release(*self.num_bytes.get_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryBudget;
+    use crate::collector::TopDocs;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STORED, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, TantivyError};
+
+    #[test]
+    fn test_memory_budget() {
+        let budget = MemoryBudget::new(100);
+        let reservation = budget.try_reserve(60).unwrap();
+        assert_eq!(budget.used().get_bytes(), 60);
+        assert_eq!(budget.available().get_bytes(), 40);
+        let err = budget.clone().try_reserve(50).err().unwrap();
+        assert_eq!(err.used.get_bytes(), 60);
+        assert_eq!(err.requested.get_bytes(), 50);
+        assert_eq!(budget.used().get_bytes(), 60);
+        reservation.try_grow(40).unwrap();
+        assert!(reservation.try_grow(1).is_err());
+        assert_eq!(reservation.num_bytes().get_bytes(), 100);
+        reservation.shrink(30);
+        assert_eq!(budget.used().get_bytes(), 70);
+        drop(reservation);
+        assert_eq!(budget.used().get_bytes(), 0);
+        assert!(budget.try_reserve(100).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot shrink a reservation of 10 bytes by 11 bytes")]
+    fn test_memory_reservation_over_shrink_panics() {
+        let budget = MemoryBudget::new(100);
+        let reservation = budget.try_reserve(10).unwrap();
+        reservation.shrink(11);
+    }
+
+    #[test]
+    fn test_memory_reservation_over_shrink_keeps_budget_consistent() {
+        let budget = MemoryBudget::new(100);
+        let other_reservation = budget.try_reserve(30).unwrap();
+        let reservation = budget.try_reserve(10).unwrap();
+        let shrink_res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            reservation.shrink(11);
+        }));
+        assert!(shrink_res.is_err());
+        assert_eq!(reservation.num_bytes().get_bytes(), 10);
+        assert_eq!(budget.used().get_bytes(), 40);
+        drop(reservation);
+        assert_eq!(budget.used().get_bytes(), 30);
+        drop(other_reservation);
+        assert_eq!(budget.used().get_bytes(), 0);
+    }
+
+    #[test]
+    fn test_memory_budget_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for doc_id in 0..100 {
+            index_writer
+                .add_document(doc!(text => format!("lorem ipsum dolor sit amet {doc_id}")))?;
+        }
+        index_writer.commit()?;
+
+        let memory_budget = MemoryBudget::new(1_000);
+        let searcher = index
+            .reader_builder()
+            .memory_budget(memory_budget.clone())
+            .try_into()?
+            .searcher();
+        let err = searcher
+            .search(&AllQuery, &TopDocs::with_limit(1_000))
+            .unwrap_err();
+        assert!(matches!(err, TantivyError::MemoryBudgetExceeded(_)));
+        assert_eq!(
+            searcher.search(&AllQuery, &TopDocs::with_limit(10))?.len(),
+            10
+        );
+        assert_eq!(memory_budget.used().get_bytes(), 0);
+        // The block of the doc store does not fit in the budget, and is not cached.
+        let _doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        assert_eq!(searcher.doc_store_cache_stats().num_entries, 0);
+        assert_eq!(memory_budget.used().get_bytes(), 0);
+
+        let memory_budget = MemoryBudget::new(1_000_000);
+        let searcher = index
+            .reader_builder()
+            .memory_budget(memory_budget.clone())
+            .try_into()?
+            .searcher();
+        let _doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        assert_eq!(searcher.doc_store_cache_stats().num_entries, 1);
+        assert!(memory_budget.used().get_bytes() > 1_000);
+        drop(searcher);
+        assert_eq!(memory_budget.used().get_bytes(), 0);
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 mod executor;
 #[doc(hidden)]
 pub mod json_utils;
+mod memory_budget;
+mod multi_searcher;
 pub mod searcher;
 
 use std::path::Path;
@@ -8,6 +10,8 @@ use std::path::Path;
 use once_cell::sync::Lazy;
 
 pub use self::executor::Executor;
+pub use self::memory_budget::{MemoryBudget, MemoryBudgetExceeded, MemoryReservation};
+pub use self::multi_searcher::{IndexDocAddress, MultiSearcher};
 pub use self::searcher::{Searcher, SearcherGeneration};
 
 /// The meta file contains all the information about the list of segments and the schema
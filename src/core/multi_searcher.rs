@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::collector::Collector;
+use crate::index::SegmentId;
+use crate::query::{
+    intersect_scorers, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::document::DocumentDeserialize;
+use crate::schema::{Schema, Term};
+use crate::snippet::HighlightPattern;
+use crate::{
+    DocAddress, DocId, DocSet, Executor, Score, Searcher, SegmentOrdinal, SegmentReader,
+    TantivyError,
+};
+
+/// Address of a document in one of the indexes of a [`MultiSearcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct IndexDocAddress {
+    /// Ordinal of the index, in the searchers the `MultiSearcher` was created with.
+    pub index_ord: usize,
+    /// Address of the document in the searcher of the index.
+    pub doc_address: DocAddress,
+}
+
+/// Searches several indexes sharing the same schema at once, e.g. the partitions of an index
+/// split by time or by tenant.
+///
+/// The segments of all of the indexes are searched as if they belonged to a single
+/// [`Searcher`]: collectors see the segments of all of the indexes, so top-k and aggregation
+/// results are merged across indexes, and BM25 statistics are computed over all of the
+/// indexes.
+///
+/// The [`DocAddress`]es returned by the collectors refer to the segments of the
+/// `MultiSearcher`, whose ordinals follow the order of the indexes. They can be mapped back to
+/// the searcher of their index with [`MultiSearcher::index_doc_address`].
+///
+/// A filter can be attached to each index with [`MultiSearcher::with_filter`], restricting
+/// the documents of the index that queries can match.
+///
+/// Only the segments of the searchers are shared: the doc store cache, the
+/// [`SlowQueryLog`](crate::SlowQueryLog) and the [`MemoryBudget`](crate::MemoryBudget) of
+/// their reader do not apply to the `MultiSearcher`.
+pub struct MultiSearcher {
+    searchers: Vec<Searcher>,
+    // Searcher over the segments of all of the searchers.
+    searcher: Searcher,
+    // Ordinal, in `searcher`, of the first segment of each searcher.
+    segment_ord_offsets: Vec<SegmentOrdinal>,
+    // Filter of each searcher.
+    filters: Vec<Option<Arc<dyn Query>>>,
+}
+
+impl MultiSearcher {
+    /// Creates a `MultiSearcher` over the given searchers.
+    ///
+    /// Returns an error if no searcher is given, or if the schemas of the searchers differ.
+    /// The index of the first searcher is the [`Searcher::index()`] of the
+    /// [merged searcher](MultiSearcher::searcher), whose tokenizers are used to parse queries.
+    pub fn new(searchers: Vec<Searcher>) -> crate::Result<MultiSearcher> {
+        let Some(first_searcher) = searchers.first() else {
+            return Err(TantivyError::InvalidArgument(
+                "A MultiSearcher requires at least one searcher".to_string(),
+            ));
+        };
+        let mut segment_readers = Vec::new();
+        let mut segment_ord_offsets = Vec::with_capacity(searchers.len());
+        for (index_ord, searcher) in searchers.iter().enumerate() {
+            if searcher.schema() != first_searcher.schema() {
+                return Err(TantivyError::SchemaError(format!(
+                    "The schema of the index #{index_ord} differs from the schema of the index #0"
+                )));
+            }
+            segment_ord_offsets.push(segment_readers.len() as SegmentOrdinal);
+            segment_readers.extend_from_slice(searcher.segment_readers());
+        }
+        let searcher =
+            Searcher::from_segment_readers(first_searcher.index().clone(), segment_readers)?;
+        let filters = vec![None; searchers.len()];
+        Ok(MultiSearcher {
+            searchers,
+            searcher,
+            segment_ord_offsets,
+            filters,
+        })
+    }
+
+    /// Restricts the documents of the index `index_ord` matched by the queries of the
+    /// `MultiSearcher` to the documents matching `filter`.
+    ///
+    /// The filter does not contribute to the score of the documents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index_ord` is out of bounds.
+    #[must_use]
+    pub fn with_filter(mut self, index_ord: usize, filter: Box<dyn Query>) -> MultiSearcher {
+        self.filters[index_ord] = Some(Arc::from(filter));
+        self
+    }
+
+    /// Returns the filter of the index `index_ord`, if any.
+    pub fn filter(&self, index_ord: usize) -> Option<&dyn Query> {
+        self.filters[index_ord].as_deref()
+    }
+
+    // Wraps `query` to apply the filters of the indexes, if any.
+    fn filtered_query(&self, query: &dyn Query) -> Option<IndexFilteredQuery> {
+        if self.filters.iter().all(Option::is_none) {
+            return None;
+        }
+        let mut filters = Vec::new();
+        let mut segment_filter_ords = HashMap::new();
+        for (searcher, filter_opt) in self.searchers.iter().zip(&self.filters) {
+            let Some(filter) = filter_opt else {
+                continue;
+            };
+            for segment_reader in searcher.segment_readers() {
+                segment_filter_ords.insert(segment_reader.segment_id(), filters.len());
+            }
+            filters.push(filter.clone());
+        }
+        Some(IndexFilteredQuery {
+            query: Arc::from(query.box_clone()),
+            filters,
+            segment_filter_ords: Arc::new(segment_filter_ords),
+        })
+    }
+
+    /// Returns the searchers of the indexes.
+    pub fn searchers(&self) -> &[Searcher] {
+        &self.searchers
+    }
+
+    /// Returns the searcher over the segments of all of the indexes, e.g. to generate snippets
+    /// or to explain the score of a document.
+    pub fn searcher(&self) -> &Searcher {
+        &self.searcher
+    }
+
+    /// Returns the schema shared by the indexes.
+    pub fn schema(&self) -> &Schema {
+        self.searcher.schema()
+    }
+
+    /// Returns the overall number of documents of the indexes.
+    pub fn num_docs(&self) -> u64 {
+        self.searcher.num_docs()
+    }
+
+    /// Runs a query on the segments of all of the indexes, see [`Searcher::search`].
+    pub fn search<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        match self.filtered_query(query) {
+            Some(filtered_query) => self.searcher.search(&filtered_query, collector),
+            None => self.searcher.search(query, collector),
+        }
+    }
+
+    /// Same as [`MultiSearcher::search`], with the segments collected on `executor`.
+    pub fn search_with_executor<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        executor: &Executor,
+    ) -> crate::Result<C::Fruit> {
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(&self.searcher)
+        } else {
+            EnableScoring::disabled_from_searcher(&self.searcher)
+        };
+        match self.filtered_query(query) {
+            Some(filtered_query) => self.searcher.search_with_executor(
+                &filtered_query,
+                collector,
+                executor,
+                enabled_scoring,
+            ),
+            None => self
+                .searcher
+                .search_with_executor(query, collector, executor, enabled_scoring),
+        }
+    }
+
+    /// Fetches a document given its address in the `MultiSearcher`.
+    pub fn doc<D: DocumentDeserialize>(&self, doc_address: DocAddress) -> crate::Result<D> {
+        self.searcher.doc(doc_address)
+    }
+
+    /// Maps the address of a document in the `MultiSearcher` to its index, and to its address
+    /// in the searcher of the index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the segment ordinal of `doc_address` is out of bounds.
+    pub fn index_doc_address(&self, doc_address: DocAddress) -> IndexDocAddress {
+        assert!(
+            (doc_address.segment_ord as usize) < self.searcher.segment_readers().len(),
+            "Segment ordinal {} is out of bounds",
+            doc_address.segment_ord
+        );
+        let index_ord = self
+            .segment_ord_offsets
+            .partition_point(|&segment_ord_offset| segment_ord_offset <= doc_address.segment_ord)
+            - 1;
+        IndexDocAddress {
+            index_ord,
+            doc_address: DocAddress::new(
+                doc_address.segment_ord - self.segment_ord_offsets[index_ord],
+                doc_address.doc_id,
+            ),
+        }
+    }
+
+    /// Maps the address of a document in the searcher of one of the indexes to its address in
+    /// the `MultiSearcher`.
+    pub fn doc_address(&self, index_doc_address: IndexDocAddress) -> DocAddress {
+        let IndexDocAddress {
+            index_ord,
+            doc_address,
+        } = index_doc_address;
+        DocAddress::new(
+            self.segment_ord_offsets[index_ord] + doc_address.segment_ord,
+            doc_address.doc_id,
+        )
+    }
+}
+
+/// Intersects a query, in the segments of the filtered indexes, with the filter of the index.
+#[derive(Clone)]
+struct IndexFilteredQuery {
+    query: Arc<dyn Query>,
+    filters: Vec<Arc<dyn Query>>,
+    // Ordinal of the filter of the segments of the filtered indexes.
+    segment_filter_ords: Arc<HashMap<SegmentId, usize>>,
+}
+
+impl fmt::Debug for IndexFilteredQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IndexFilteredQuery")
+            .field("query", &self.query)
+            .field("filters", &self.filters)
+            .finish()
+    }
+}
+
+impl Query for IndexFilteredQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let filter_scoring = EnableScoring::disabled_from_schema(enable_scoring.schema());
+        let filter_weights = self
+            .filters
+            .iter()
+            .map(|filter| filter.weight(filter_scoring))
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(Box::new(IndexFilteredWeight {
+            weight: self.query.weight(enable_scoring)?,
+            filter_weights,
+            segment_filter_ords: self.segment_filter_ords.clone(),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor);
+    }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        self.query.highlight_patterns(visitor);
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        self.query.named_queries(visitor);
+    }
+}
+
+struct IndexFilteredWeight {
+    weight: Box<dyn Weight>,
+    filter_weights: Vec<Box<dyn Weight>>,
+    segment_filter_ords: Arc<HashMap<SegmentId, usize>>,
+}
+
+impl IndexFilteredWeight {
+    fn filter_weight(&self, reader: &SegmentReader) -> Option<&dyn Weight> {
+        let filter_ord = *self.segment_filter_ords.get(&reader.segment_id())?;
+        Some(self.filter_weights[filter_ord].as_ref())
+    }
+}
+
+impl Weight for IndexFilteredWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader, boost)?;
+        let Some(filter_weight) = self.filter_weight(reader) else {
+            return Ok(scorer);
+        };
+        let filter_scorer = filter_weight.scorer(reader, 1.0)?;
+        Ok(intersect_scorers(vec![
+            scorer,
+            Box::new(ConstScorer::new(filter_scorer, 0.0)),
+        ]))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        if let Some(filter_weight) = self.filter_weight(reader) {
+            if filter_weight.scorer(reader, 1.0)?.seek(doc) != doc {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Document #({doc}) does not match"
+                )));
+            }
+        }
+        self.weight.explain(reader, doc)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        if self.filter_weight(reader).is_none() {
+            return self.weight.count(reader);
+        }
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if let Some(alive_bitset) = reader.alive_bitset() {
+            Ok(scorer.count(alive_bitset))
+        } else {
+            Ok(scorer.count_including_deleted())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexDocAddress, MultiSearcher};
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::AggregationCollector;
+    use crate::collector::{Count, TopDocs};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Value, FAST, STORED, STRING, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, Searcher, TantivyDocument, TantivyError, Term};
+
+    fn create_searcher(schema: &Schema, segments: &[&[(&str, &str)]]) -> crate::Result<Searcher> {
+        let title = schema.get_field("title").unwrap();
+        let tenant = schema.get_field("tenant").unwrap();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment in segments {
+            for &(title_value, tenant_value) in segment.iter() {
+                index_writer.add_document(doc!(title => title_value, tenant => tenant_value))?;
+            }
+            index_writer.commit()?;
+        }
+        Ok(index.reader()?.searcher())
+    }
+
+    #[test]
+    fn test_multi_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_text_field("tenant", STRING | FAST);
+        let schema = schema_builder.build();
+        let searcher_a = create_searcher(
+            &schema,
+            &[
+                &[("red apple", "a")],
+                &[("green apple", "a"), ("pear", "a")],
+            ],
+        )?;
+        let searcher_b =
+            create_searcher(&schema, &[&[("apple apple apple", "b"), ("banana", "b")]])?;
+        let multi_searcher = MultiSearcher::new(vec![searcher_a, searcher_b])?;
+        assert_eq!(multi_searcher.num_docs(), 5);
+        assert_eq!(multi_searcher.searcher().segment_readers().len(), 3);
+
+        let apple_query = TermQuery::new(
+            Term::from_field_text(title, "apple"),
+            IndexRecordOption::WithFreqs,
+        );
+        assert_eq!(multi_searcher.search(&apple_query, &Count)?, 3);
+        let top_docs = multi_searcher.search(&apple_query, &TopDocs::with_limit(2))?;
+        assert_eq!(top_docs.len(), 2);
+        let (_, best_doc_address) = top_docs[0];
+        let best_doc: TantivyDocument = multi_searcher.doc(best_doc_address)?;
+        assert_eq!(
+            best_doc.get_first(title).unwrap().as_str(),
+            Some("apple apple apple")
+        );
+        let index_doc_address = multi_searcher.index_doc_address(best_doc_address);
+        assert_eq!(
+            index_doc_address,
+            IndexDocAddress {
+                index_ord: 1,
+                doc_address: DocAddress::new(0, 0),
+            }
+        );
+        assert_eq!(
+            multi_searcher.doc_address(index_doc_address),
+            best_doc_address
+        );
+        let doc: TantivyDocument =
+            multi_searcher.searchers()[1].doc(index_doc_address.doc_address)?;
+        assert_eq!(doc, best_doc);
+        for segment_ord in 0..3 {
+            let doc_address = DocAddress::new(segment_ord, 0);
+            let index_doc_address = multi_searcher.index_doc_address(doc_address);
+            assert_eq!(index_doc_address.index_ord, [0, 0, 1][segment_ord as usize]);
+            assert_eq!(multi_searcher.doc_address(index_doc_address), doc_address);
+        }
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "tenants": { "terms": { "field": "tenant" } }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let agg_res: AggregationResults = multi_searcher.search(&AllQuery, &collector)?;
+        let agg_res_json = serde_json::to_value(&agg_res)?;
+        assert_eq!(
+            agg_res_json["tenants"]["buckets"],
+            json!([
+                { "key": "a", "doc_count": 3 },
+                { "key": "b", "doc_count": 2 },
+            ])
+        );
+
+        // Only the bananas of the second index are visible.
+        let banana_query = TermQuery::new(
+            Term::from_field_text(title, "banana"),
+            IndexRecordOption::Basic,
+        );
+        let multi_searcher = multi_searcher.with_filter(1, Box::new(banana_query));
+        assert_eq!(multi_searcher.search(&apple_query, &Count)?, 2);
+        let top_docs = multi_searcher.search(&AllQuery, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_searcher_requires_same_schema() -> crate::Result<()> {
+        assert!(matches!(
+            MultiSearcher::new(Vec::new()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_text_field("tenant", STRING | FAST);
+        let schema = schema_builder.build();
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("tenant", STRING | FAST);
+        let other_schema = schema_builder.build();
+        let searchers = vec![
+            create_searcher(&schema, &[])?,
+            create_searcher(&other_schema, &[])?,
+        ];
+        assert!(matches!(
+            MultiSearcher::new(searchers),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -1,16 +1,22 @@
-use std::collections::BTreeMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::{fmt, io};
 
 use crate::collector::Collector;
-use crate::core::Executor;
+use crate::core::{Executor, MemoryBudget};
+use crate::fastfield::GlobalOrdinals;
 use crate::index::{SegmentId, SegmentReader};
-use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use crate::query::{AllQuery, Bm25StatisticsProvider, EnableScoring, Query};
+use crate::reader::{ResultCache, SlowQueryLog, WarmupPlan};
 use crate::schema::document::DocumentDeserialize;
-use crate::schema::{Schema, Term};
+use crate::schema::{Field, Schema, Term};
 use crate::space_usage::SearcherSpaceUsage;
-use crate::store::{CacheStats, StoreReader};
-use crate::{DocAddress, Index, Opstamp, TrackedObject};
+use crate::store::{BlockCache, CacheStats, StoreReader, DOCSTORE_CACHE_CAPACITY};
+use crate::suggest::{SuggestOptions, TermSuggestion};
+use crate::term_vectors::TermVectorEntry;
+use crate::vector::KnnCollector;
+use crate::{DocAddress, DocId, Index, Inventory, Opstamp, Score, TantivyError, TrackedObject};
 
 /// Identifies the searcher generation accessed by a [`Searcher`].
 ///
@@ -71,6 +77,28 @@ pub struct Searcher {
 }
 
 impl Searcher {
+    /// Creates a `Searcher` over segments that may belong to different indexes
+    /// sharing the same schema.
+    ///
+    /// `index` is the index returned by [`Searcher::index()`].
+    pub(crate) fn from_segment_readers(
+        index: Index,
+        segment_readers: Vec<SegmentReader>,
+    ) -> crate::Result<Searcher> {
+        let generation = Inventory::new().track(SearcherGeneration::from_segment_readers(
+            &segment_readers,
+            0,
+        ));
+        let searcher_inner = SearcherInner::new(
+            index.schema(),
+            index,
+            segment_readers,
+            generation,
+            SearcherSettings::default(),
+        )?;
+        Ok(Arc::new(searcher_inner).into())
+    }
+
     /// Returns the `Index` associated with the `Searcher`
     pub fn index(&self) -> &Index {
         &self.inner.index
@@ -90,20 +118,99 @@ impl Searcher {
         store_reader.get(doc_address.doc_id)
     }
 
-    /// The cache stats for the underlying store reader.
+    /// Fetches the values of the given stored fields of a document.
     ///
-    /// Aggregates the sum for each segment store reader.
-    pub fn doc_store_cache_stats(&self) -> CacheStats {
-        let cache_stats: CacheStats = self
+    /// Unlike [`Searcher::doc`], the values of the other fields are skipped over
+    /// without being deserialized, which is cheaper when only a few fields of large
+    /// documents are needed. The returned document only contains the requested fields.
+    pub fn doc_fields<D: DocumentDeserialize>(
+        &self,
+        doc_address: DocAddress,
+        fields: &[Field],
+    ) -> crate::Result<D> {
+        let store_reader = &self.inner.store_readers[doc_address.segment_ord as usize];
+        store_reader.get_fields(doc_address.doc_id, fields)
+    }
+
+    /// Returns the term vector of a document for the given field, see [`crate::term_vectors`].
+    ///
+    /// The term vector is empty if the document has no value for the field. Returns an error if
+    /// the field has no term vectors.
+    pub fn term_vector(
+        &self,
+        doc_address: DocAddress,
+        field: Field,
+    ) -> crate::Result<Vec<TermVectorEntry>> {
+        let segment_reader = self.segment_reader(doc_address.segment_ord);
+        let Some(term_vector_reader) = segment_reader.term_vectors_readers().get_field(field)?
+        else {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Field {:?} has no term vectors",
+                self.schema().get_field_name(field)
+            )));
+        };
+        Ok(term_vector_reader.term_vector(doc_address.doc_id)?)
+    }
+
+    /// Returns the [`GlobalOrdinals`] of the `str` fast field `field_name`.
+    ///
+    /// The global ordinals are built the first time they are requested for a field, by merging
+    /// the dictionaries of the segments, and are then shared by the clones of this searcher.
+    /// They are dropped with the searcher generation, as the segments they map change on reload.
+    pub fn global_ordinals(&self, field_name: &str) -> crate::Result<Arc<GlobalOrdinals>> {
+        if let Some(global_ordinals) = self.inner.global_ordinals.lock().unwrap().get(field_name) {
+            return Ok(global_ordinals.clone());
+        }
+        // Built without holding the lock, concurrent builds for the same field keep the first.
+        let global_ordinals = Arc::new(GlobalOrdinals::build(self.segment_readers(), field_name)?);
+        Ok(self
             .inner
-            .store_readers
-            .iter()
-            .map(|reader| reader.cache_stats())
-            .sum();
-        cache_stats
+            .global_ordinals
+            .lock()
+            .unwrap()
+            .entry(field_name.to_string())
+            .or_insert(global_ordinals)
+            .clone())
+    }
+
+    /// Loads the doc store blocks of some documents into the doc store cache, so that
+    /// fetching them afterwards does not hit the directory.
+    ///
+    /// The missing blocks of each segment are read in a single batch, see
+    /// [`StoreReader::prefetch`]. This is typically called with the addresses returned by a
+    /// collector like [`TopDocs`](crate::collector::TopDocs), before fetching the documents.
+    pub fn prefetch_docs(&self, doc_addresses: &[DocAddress]) -> crate::Result<()> {
+        let mut doc_ids_per_segment: Vec<Vec<DocId>> =
+            vec![Vec::new(); self.segment_readers().len()];
+        for doc_address in doc_addresses {
+            doc_ids_per_segment[doc_address.segment_ord as usize].push(doc_address.doc_id);
+        }
+        for (store_reader, doc_ids) in self.inner.store_readers.iter().zip(doc_ids_per_segment) {
+            if !doc_ids.is_empty() {
+                store_reader.prefetch(&doc_ids)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The cache stats for the underlying store readers.
+    ///
+    /// The store readers of all the segments of the searcher share the same
+    /// block cache, whose capacity is set with
+    /// [`IndexReaderBuilder::doc_store_cache_num_blocks`](crate::IndexReaderBuilder::doc_store_cache_num_blocks).
+    pub fn doc_store_cache_stats(&self) -> CacheStats {
+        self.inner.doc_store_block_cache.stats()
     }
 
     /// Fetches a document in an asynchronous manner.
+    ///
+    /// The doc store block of the document is read with the async API of the
+    /// [`Directory`](crate::Directory) file handles, and decompressed on the
+    /// [search executor](crate::Index::set_multithread_executor) of the index.
+    /// With directories whose file handles do not support async reads, the block is
+    /// read on the search executor as well.
+    ///
+    /// With the default single thread executor, decompression happens on the calling task.
     #[cfg(feature = "quickwit")]
     pub async fn doc_async<D: DocumentDeserialize>(
         &self,
@@ -114,6 +221,16 @@ impl Searcher {
         store_reader.get_async(doc_address.doc_id, executor).await
     }
 
+    /// Loads the components selected by a [`WarmupPlan`] ahead of the first queries.
+    pub fn warm_up(&self, warmup_plan: &WarmupPlan) -> crate::Result<()> {
+        warmup_plan.warm_up(self)
+    }
+
+    /// Returns the store reader of a segment, which shares the doc store cache of the searcher.
+    pub(crate) fn store_reader(&self, segment_ord: u32) -> &StoreReader {
+        &self.inner.store_readers[segment_ord as usize]
+    }
+
     /// Access the schema associated with the index of this searcher.
     pub fn schema(&self) -> &Schema {
         &self.inner.schema
@@ -140,6 +257,20 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Suggests terms of the given text field close to `term`, e.g. to propose spelling
+    /// corrections for a query.
+    ///
+    /// The suggestions are ranked by edit distance, then by decreasing document frequency.
+    /// See [`suggest`](crate::suggest) for more details.
+    pub fn suggest_terms(
+        &self,
+        field: Field,
+        term: &str,
+        options: &SuggestOptions,
+    ) -> crate::Result<Vec<TermSuggestion>> {
+        crate::suggest::suggest_terms(self, field, term, options)
+    }
+
     /// Return the overall number of documents containing
     /// the given term in an asynchronous manner.
     #[cfg(feature = "quickwit")]
@@ -185,6 +316,65 @@ impl Searcher {
         self.search_with_statistics_provider(query, collector, self)
     }
 
+    /// Returns the number of documents matching `query`.
+    ///
+    /// This is a dedicated counting path, faster than running the
+    /// [`Count`](crate::collector::Count) collector through [`search`](Searcher::search): the
+    /// query is executed with scoring disabled, no document is pushed to a collector, and the
+    /// matches of each segment are counted by [`Weight::count`](crate::query::Weight::count).
+    /// Term queries are then counted from their document frequency when the segment has no
+    /// deletes, and bitsets with popcounts.
+    ///
+    /// The segments are counted on the search executor of the index.
+    pub fn count_query(&self, query: &dyn Query) -> crate::Result<u64> {
+        let weight = query.weight(EnableScoring::disabled_from_searcher(self))?;
+        let executor = self.inner.index.search_executor();
+        let segment_counts = executor.map(
+            |segment_reader| weight.count(segment_reader),
+            self.segment_readers().iter(),
+        )?;
+        Ok(segment_counts.into_iter().map(u64::from).sum())
+    }
+
+    /// Same as [`search(...)`](Searcher::search), but the result is served from the cache
+    /// enabled with [`IndexReaderBuilder::result_cache`](crate::IndexReaderBuilder::result_cache)
+    /// when the same search already ran on this searcher generation.
+    ///
+    /// Results are keyed on the searcher generation, the `Debug` representation of the query
+    /// and `collector_key`, which must identify the collector and its parameters, e.g.
+    /// `"top_docs:10"` or the aggregation request. Without a cache, this is a plain search.
+    pub fn search_cached<C>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        collector_key: &str,
+    ) -> crate::Result<C::Fruit>
+    where
+        C: Collector,
+        C::Fruit: Clone,
+    {
+        match self.inner.settings.result_cache.as_ref() {
+            Some(result_cache) => result_cache.get_or_search(
+                self.generation().generation_id(),
+                query,
+                collector_key,
+                || self.search(query, collector),
+            ),
+            None => self.search(query, collector),
+        }
+    }
+
+    /// The stats of the cache of the results of [`Searcher::search_cached`], shared by all of
+    /// the searchers of the reader.
+    pub fn result_cache_stats(&self) -> CacheStats {
+        self.inner
+            .settings
+            .result_cache
+            .as_ref()
+            .map(ResultCache::stats)
+            .unwrap_or_default()
+    }
+
     /// Same as [`search(...)`](Searcher::search) but allows specifying
     /// a [Bm25StatisticsProvider].
     ///
@@ -217,6 +407,18 @@ impl Searcher {
     /// Also, keep in my multithreading a single query on several
     /// threads will not improve your throughput. It can actually
     /// hurt it. It will however, decrease the average response time.
+    ///
+    /// With the `tracing` feature, the search is wrapped in a `DEBUG` level `tantivy_search`
+    /// span, with child spans for the creation of the weight, the collection of each segment
+    /// and the merge of the segment fruits.
+    ///
+    /// The search is reported to the [`SlowQueryLog`] of the reader, if any, when it exceeds
+    /// its threshold.
+    ///
+    /// If the reader has a [`MemoryBudget`], the search fails with
+    /// [`TantivyError::MemoryBudgetExceeded`](crate::TantivyError::MemoryBudgetExceeded) when
+    /// the [memory estimate](Collector::memory_estimate) of the collector for all of the
+    /// segments does not fit in the budget.
     pub fn search_with_executor<C: Collector>(
         &self,
         query: &dyn Query,
@@ -224,15 +426,93 @@ impl Searcher {
         executor: &Executor,
         enabled_scoring: EnableScoring,
     ) -> crate::Result<C::Fruit> {
-        let weight = query.weight(enabled_scoring)?;
         let segment_readers = self.segment_readers();
+        // Released once the fruits of the segments are merged.
+        let _memory_reservation_opt = self
+            .inner
+            .settings
+            .memory_budget
+            .as_ref()
+            .map(|memory_budget| {
+                memory_budget
+                    .try_reserve(collector.memory_estimate() as u64 * segment_readers.len() as u64)
+            })
+            .transpose()?;
+        let slow_query_log_opt = self.inner.settings.slow_query_log.as_ref();
+        // Searches are only timed for the slow query log.
+        let search_start_opt = slow_query_log_opt.map(|_| Instant::now());
+        #[cfg(feature = "tracing")]
+        let search_span = tracing::debug_span!(
+            "tantivy_search",
+            num_segments = segment_readers.len(),
+            num_docs = self.num_docs()
+        )
+        .entered();
+        let weight = {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("tantivy_weight").entered();
+            query.weight(enabled_scoring)?
+        };
+        // Segments may be collected on other threads, where the search span is not entered.
+        #[cfg(feature = "tracing")]
+        let search_span_id = search_span.id();
         let fruits = executor.map(
             |(segment_ord, segment_reader)| {
-                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+                #[cfg(feature = "tracing")]
+                let _span = tracing::debug_span!(
+                    parent: search_span_id.clone(),
+                    "tantivy_collect_segment",
+                    segment_ord,
+                    num_docs = segment_reader.num_docs()
+                )
+                .entered();
+                let segment_start_opt = search_start_opt.map(|_| Instant::now());
+                let fruit = collector.collect_segment(
+                    weight.as_ref(),
+                    segment_ord as u32,
+                    segment_reader,
+                )?;
+                Ok((fruit, segment_start_opt.map(|start| start.elapsed())))
             },
             segment_readers.iter().enumerate(),
         )?;
-        collector.merge_fruits(fruits)
+        let (fruits, segment_durations): (Vec<_>, Vec<_>) = fruits.into_iter().unzip();
+        let fruit = {
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::debug_span!("tantivy_merge_fruits", num_segments = fruits.len()).entered();
+            collector.merge_fruits(fruits)?
+        };
+        if let (Some(slow_query_log), Some(search_start)) = (slow_query_log_opt, search_start_opt) {
+            let segment_durations: Vec<Duration> =
+                segment_durations.into_iter().flatten().collect();
+            slow_query_log.record(
+                query,
+                std::any::type_name::<C>(),
+                weight.as_ref(),
+                segment_readers,
+                &segment_durations,
+                search_start.elapsed(),
+            );
+        }
+        Ok(fruit)
+    }
+
+    /// Returns the `k` documents whose vector in `field` are the most similar to
+    /// `query_vector`, sorted by decreasing similarity.
+    ///
+    /// The search is exact: the similarity is computed for the vector of every document
+    /// matching `filter_query`, or of every document if no filter is given.
+    /// `field` has to be a vector field, see [`crate::vector`].
+    pub fn knn(
+        &self,
+        field: Field,
+        query_vector: &[f32],
+        k: usize,
+        filter_query: Option<&dyn Query>,
+    ) -> crate::Result<Vec<(Score, DocAddress)>> {
+        let collector = KnnCollector::new(field, query_vector.to_vec(), k);
+        self.search(filter_query.unwrap_or(&AllQuery), &collector)
     }
 
     /// Summarize total space usage of this searcher.
@@ -260,7 +540,30 @@ pub(crate) struct SearcherInner {
     index: Index,
     segment_readers: Vec<SegmentReader>,
     store_readers: Vec<StoreReader>,
+    doc_store_block_cache: Arc<BlockCache>,
     generation: TrackedObject<SearcherGeneration>,
+    global_ordinals: Mutex<HashMap<String, Arc<GlobalOrdinals>>>,
+    settings: SearcherSettings,
+}
+
+/// Settings shared by the searchers of an [`IndexReader`](crate::IndexReader).
+#[derive(Clone)]
+pub(crate) struct SearcherSettings {
+    pub doc_store_cache_num_blocks: usize,
+    pub slow_query_log: Option<SlowQueryLog>,
+    pub memory_budget: Option<MemoryBudget>,
+    pub result_cache: Option<ResultCache>,
+}
+
+impl Default for SearcherSettings {
+    fn default() -> SearcherSettings {
+        SearcherSettings {
+            doc_store_cache_num_blocks: DOCSTORE_CACHE_CAPACITY,
+            slow_query_log: None,
+            memory_budget: None,
+            result_cache: None,
+        }
+    }
 }
 
 impl SearcherInner {
@@ -270,7 +573,7 @@ impl SearcherInner {
         index: Index,
         segment_readers: Vec<SegmentReader>,
         generation: TrackedObject<SearcherGeneration>,
-        doc_store_cache_num_blocks: usize,
+        settings: SearcherSettings,
     ) -> io::Result<SearcherInner> {
         assert_eq!(
             &segment_readers
@@ -280,9 +583,17 @@ impl SearcherInner {
             generation.segments(),
             "Set of segments referenced by this Searcher and its SearcherGeneration must match"
         );
+        let doc_store_block_cache = Arc::new(match settings.memory_budget.as_ref() {
+            Some(memory_budget) => {
+                BlockCache::with_memory_budget(settings.doc_store_cache_num_blocks, memory_budget)
+            }
+            None => BlockCache::new(settings.doc_store_cache_num_blocks),
+        });
         let store_readers: Vec<StoreReader> = segment_readers
             .iter()
-            .map(|segment_reader| segment_reader.get_store_reader(doc_store_cache_num_blocks))
+            .map(|segment_reader| {
+                segment_reader.get_store_reader_with_block_cache(doc_store_block_cache.clone())
+            })
             .collect::<io::Result<Vec<_>>>()?;
 
         Ok(SearcherInner {
@@ -290,7 +601,10 @@ impl SearcherInner {
             index,
             segment_readers,
             store_readers,
+            doc_store_block_cache,
             generation,
+            global_ordinals: Mutex::default(),
+            settings,
         })
     }
 }
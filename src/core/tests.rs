@@ -3,12 +3,12 @@ use crate::directory::{RamDirectory, WatchCallback};
 use crate::index::SegmentId;
 use crate::indexer::{LogMergePolicy, NoMergePolicy};
 use crate::postings::Postings;
-use crate::query::TermQuery;
-use crate::schema::{Field, IndexRecordOption, Schema, INDEXED, STRING, TEXT};
+use crate::query::{AllQuery, Query, RegexQuery, TermQuery};
+use crate::schema::{Field, IndexRecordOption, Schema, INDEXED, STORED, STRING, TEXT};
 use crate::tokenizer::TokenizerManager;
 use crate::{
-    Directory, DocSet, Index, IndexBuilder, IndexReader, IndexSettings, IndexWriter, ReloadPolicy,
-    TantivyDocument, Term,
+    Directory, DocAddress, DocSet, Index, IndexBuilder, IndexReader, IndexSettings, IndexWriter,
+    ReloadPolicy, TantivyDocument, Term,
 };
 
 #[test]
@@ -466,3 +466,123 @@ fn test_non_text_json_term_freq_bitpacked() {
         assert_eq!(postings.term_freq(), 1u32);
     }
 }
+
+#[test]
+fn test_searcher_doc_fields() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let body = schema_builder.add_text_field("body", TEXT | STORED);
+    let count = schema_builder.add_u64_field("count", STORED);
+    let schema = schema_builder.build();
+    let index = Index::create_in_ram(schema);
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.add_document(doc!(
+        title => "hello",
+        body => "a long body that is not needed",
+        count => 7u64,
+    ))?;
+    index_writer.add_document(doc!(body => "no title"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+
+    let doc: TantivyDocument = searcher.doc_fields(DocAddress::new(0, 0), &[count, title])?;
+    assert_eq!(doc, doc!(title => "hello", count => 7u64));
+    let doc: TantivyDocument = searcher.doc_fields(DocAddress::new(0, 1), &[title])?;
+    assert_eq!(doc.field_values().count(), 0);
+    let doc: TantivyDocument = searcher.doc_fields(DocAddress::new(0, 1), &[body])?;
+    assert_eq!(doc, searcher.doc::<TantivyDocument>(DocAddress::new(0, 1))?);
+    Ok(())
+}
+
+#[test]
+fn test_searcher_count_query() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for i in 0..200 {
+        let parity = if i % 2 == 0 { "even" } else { "odd" };
+        index_writer.add_document(doc!(text => format!("{parity} doc{i}")))?;
+        if i % 70 == 0 {
+            index_writer.commit()?;
+        }
+    }
+    index_writer.delete_term(Term::from_field_text(text, "doc4"));
+    index_writer.delete_term(Term::from_field_text(text, "doc5"));
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+
+    let queries: Vec<Box<dyn Query>> = vec![
+        Box::new(AllQuery),
+        Box::new(TermQuery::new(
+            Term::from_field_text(text, "even"),
+            IndexRecordOption::Basic,
+        )),
+        Box::new(RegexQuery::from_pattern("doc1.*", text)?),
+        Box::new(RegexQuery::from_pattern("nomatch.*", text)?),
+    ];
+    for query in &queries {
+        let count = searcher.search(query.as_ref(), &Count)?;
+        assert_eq!(searcher.count_query(query.as_ref())?, count as u64);
+    }
+    assert_eq!(searcher.count_query(queries[1].as_ref())?, 99);
+    Ok(())
+}
+
+#[test]
+fn test_searcher_shared_doc_store_cache() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for segment in 0..3 {
+        index_writer.add_document(doc!(text => format!("first doc of segment {segment}")))?;
+        index_writer.add_document(doc!(text => format!("second doc of segment {segment}")))?;
+        index_writer.commit()?;
+    }
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .doc_store_cache_num_blocks(2)
+        .try_into()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.segment_readers().len(), 3);
+    for segment_ord in 0..3 {
+        for doc_id in 0..2 {
+            searcher.doc::<TantivyDocument>(DocAddress::new(segment_ord, doc_id))?;
+        }
+    }
+    let cache_stats = searcher.doc_store_cache_stats();
+    assert_eq!(cache_stats.num_entries, 2);
+    assert_eq!(cache_stats.cache_hits, 3);
+    assert_eq!(cache_stats.cache_misses, 3);
+
+    // A new searcher generation starts with an empty cache.
+    index_writer.add_document(doc!(text => "another doc"))?;
+    index_writer.commit()?;
+    reader.reload()?;
+    let cache_stats = reader.searcher().doc_store_cache_stats();
+    assert_eq!(cache_stats.num_entries, 0);
+    assert_eq!(cache_stats.cache_misses, 0);
+    Ok(())
+}
+
+#[cfg(feature = "quickwit")]
+#[test]
+fn test_searcher_doc_async() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT | STORED);
+    let mut index = Index::create_in_ram(schema_builder.build());
+    index.set_multithread_executor(2)?;
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.add_document(doc!(text => "hello"))?;
+    index_writer.add_document(doc!(text => "happy tax payer"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let doc_address = DocAddress::new(0, 1);
+    let doc: TantivyDocument = futures::executor::block_on(searcher.doc_async(doc_address))?;
+    assert_eq!(doc, searcher.doc::<TantivyDocument>(doc_address)?);
+    Ok(())
+}
@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::{fmt, io};
+
+use async_trait::async_trait;
+use common::HasLen;
+use futures_util::future::try_join_all;
+
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileHandle, Lock, OwnedBytes, WatchCallback, WatchCallbackList,
+    WatchHandle, WritePtr, INDEX_WRITER_LOCK,
+};
+use crate::index::{Index, Segment, SegmentReader};
+
+/// A file of an [`AsyncDirectory`].
+#[async_trait]
+pub trait AsyncFileHandle: 'static + Send + Sync + HasLen + fmt::Debug {
+    /// Reads a slice of bytes.
+    ///
+    /// This method may panic if the range requested is invalid.
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes>;
+}
+
+/// A read-only directory whose files can only be read asynchronously, typically because
+/// they are stored over the network.
+///
+/// Contrary to a [`Directory`], an `AsyncDirectory` does not need to fake synchronous IO.
+/// An index stored in an `AsyncDirectory` is opened with [`Index::open_async`].
+#[async_trait]
+pub trait AsyncDirectory: 'static + Send + Sync + fmt::Debug {
+    /// Opens a file and returns a boxed `AsyncFileHandle`.
+    async fn get_file_handle_async(
+        &self,
+        path: &Path,
+    ) -> Result<Arc<dyn AsyncFileHandle>, OpenReadError>;
+
+    /// Reads the full content of a file that may be updated with atomic writes,
+    /// such as `meta.json`.
+    async fn atomic_read_async(&self, path: &Path) -> Result<Vec<u8>, OpenReadError>;
+}
+
+/// Data that a synchronous read found missing, and that has to be fetched asynchronously.
+enum MissingData {
+    AtomicFile(PathBuf),
+    FileHandle(PathBuf),
+    Range(PathBuf, Range<usize>),
+}
+
+/// The missing data recorded while an index is being opened.
+///
+/// Recording only happens while the index is opened, as the reads of data that was not fetched
+/// simply fail afterwards.
+type MissingDataLog = Arc<Mutex<Option<Vec<MissingData>>>>;
+
+fn record_missing(missing_data_log: &MissingDataLog, missing_data: MissingData) {
+    if let Some(missing_data_list) = missing_data_log.lock().unwrap().as_mut() {
+        missing_data_list.push(missing_data);
+    }
+}
+
+fn not_fetched_error(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::WouldBlock,
+        format!("{path:?} has not been fetched: it has to be read with an async read path first"),
+    )
+}
+
+/// A file of an [`AsyncDirectory`], with the byte ranges fetched so far.
+struct AsyncDirectoryFile {
+    path: PathBuf,
+    file_handle: Arc<dyn AsyncFileHandle>,
+    fetched_ranges: RwLock<Vec<(Range<usize>, OwnedBytes)>>,
+    missing_data_log: MissingDataLog,
+}
+
+impl fmt::Debug for AsyncDirectoryFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AsyncDirectoryFile({:?})", self.path)
+    }
+}
+
+impl HasLen for AsyncDirectoryFile {
+    fn len(&self) -> usize {
+        self.file_handle.len()
+    }
+}
+
+impl AsyncDirectoryFile {
+    fn get_fetched(&self, range: &Range<usize>) -> Option<OwnedBytes> {
+        if range.is_empty() {
+            return Some(OwnedBytes::empty());
+        }
+        let fetched_ranges = self.fetched_ranges.read().unwrap();
+        let (fetched_range, bytes) = fetched_ranges.iter().find(|(fetched_range, _)| {
+            fetched_range.start <= range.start && range.end <= fetched_range.end
+        })?;
+        Some(bytes.slice(range.start - fetched_range.start..range.end - fetched_range.start))
+    }
+}
+
+#[async_trait]
+impl FileHandle for AsyncDirectoryFile {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if let Some(bytes) = self.get_fetched(&range) {
+            return Ok(bytes);
+        }
+        record_missing(
+            &self.missing_data_log,
+            MissingData::Range(self.path.clone(), range),
+        );
+        Err(not_fetched_error(&self.path))
+    }
+
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if let Some(bytes) = self.get_fetched(&range) {
+            return Ok(bytes);
+        }
+        let bytes = self.file_handle.read_bytes_async(range.clone()).await?;
+        self.fetched_ranges
+            .write()
+            .unwrap()
+            .push((range, bytes.clone()));
+        Ok(bytes)
+    }
+}
+
+#[derive(Default)]
+struct FetchedFiles {
+    // `None` if the file does not exist.
+    atomic_files: HashMap<PathBuf, Option<Vec<u8>>>,
+    file_handles: HashMap<PathBuf, Option<Arc<AsyncDirectoryFile>>>,
+}
+
+/// A read-only [`Directory`] over an [`AsyncDirectory`], that only serves the data fetched
+/// so far.
+///
+/// Reading data that was not fetched fails with an [`io::ErrorKind::WouldBlock`] error.
+#[derive(Clone)]
+struct AsyncDirectoryAdapter {
+    async_directory: Arc<dyn AsyncDirectory>,
+    fetched_files: Arc<RwLock<FetchedFiles>>,
+    missing_data_log: MissingDataLog,
+    watch_router: Arc<WatchCallbackList>,
+}
+
+impl fmt::Debug for AsyncDirectoryAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AsyncDirectoryAdapter({:?})", self.async_directory)
+    }
+}
+
+impl AsyncDirectoryAdapter {
+    fn new(async_directory: Arc<dyn AsyncDirectory>) -> AsyncDirectoryAdapter {
+        AsyncDirectoryAdapter {
+            async_directory,
+            fetched_files: Default::default(),
+            missing_data_log: Default::default(),
+            watch_router: Default::default(),
+        }
+    }
+
+    async fn fetch(&self, missing_data: MissingData) -> crate::Result<()> {
+        match missing_data {
+            MissingData::AtomicFile(path) => {
+                let data = match self.async_directory.atomic_read_async(&path).await {
+                    Ok(data) => Some(data),
+                    Err(OpenReadError::FileDoesNotExist(_)) => None,
+                    Err(error) => return Err(error.into()),
+                };
+                let mut fetched_files = self.fetched_files.write().unwrap();
+                fetched_files.atomic_files.insert(path, data);
+            }
+            MissingData::FileHandle(path) => {
+                let file = match self.async_directory.get_file_handle_async(&path).await {
+                    Ok(file_handle) => Some(Arc::new(AsyncDirectoryFile {
+                        path: path.clone(),
+                        file_handle,
+                        fetched_ranges: Default::default(),
+                        missing_data_log: self.missing_data_log.clone(),
+                    })),
+                    Err(OpenReadError::FileDoesNotExist(_)) => None,
+                    Err(error) => return Err(error.into()),
+                };
+                let mut fetched_files = self.fetched_files.write().unwrap();
+                fetched_files.file_handles.insert(path, file);
+            }
+            MissingData::Range(path, range) => {
+                let file = self.fetched_files.read().unwrap().file_handles[&path].clone();
+                if let Some(file) = file {
+                    file.read_bytes_async(range).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the index and all of its segments, fetching the data they read until none is
+    /// missing.
+    ///
+    /// Each attempt to open the segments records the data it finds missing, which is then
+    /// fetched concurrently for all segments.
+    async fn open_index(self) -> crate::Result<Index> {
+        *self.missing_data_log.lock().unwrap() = Some(Vec::new());
+        loop {
+            let open_result = Index::open(self.clone()).and_then(|index| {
+                let segment_open_results: Vec<crate::Result<()>> = index
+                    .searchable_segments()?
+                    .iter()
+                    .map(open_segment)
+                    .collect();
+                for segment_open_result in segment_open_results {
+                    segment_open_result?;
+                }
+                Ok(index)
+            });
+            let missing_data_list = self
+                .missing_data_log
+                .lock()
+                .unwrap()
+                .replace(Vec::new())
+                .unwrap_or_default();
+            if missing_data_list.is_empty() {
+                *self.missing_data_log.lock().unwrap() = None;
+                return open_result;
+            }
+            try_join_all(
+                missing_data_list
+                    .into_iter()
+                    .map(|missing_data| self.fetch(missing_data)),
+            )
+            .await?;
+        }
+    }
+
+    fn read_only_error(&self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "an index opened from an async directory is read-only",
+        )
+    }
+}
+
+/// Opens a segment, as well as the components that are opened lazily but are needed to
+/// search it: its doc store, and the inverted index and fieldnorms of its fields.
+fn open_segment(segment: &Segment) -> crate::Result<()> {
+    let segment_reader = SegmentReader::open(segment)?;
+    // All components are opened, even after an error, so that the data they miss is
+    // fetched at once.
+    let mut open_results: Vec<crate::Result<()>> = vec![segment_reader
+        .get_store_reader(0)
+        .map(drop)
+        .map_err(Into::into)];
+    for (field, field_entry) in segment.schema().fields() {
+        if field_entry.is_indexed() {
+            open_results.push(segment_reader.inverted_index(field).map(drop));
+        }
+        if field_entry.has_fieldnorms() {
+            open_results.push(segment_reader.get_fieldnorms_reader(field).map(drop));
+        }
+    }
+    open_results.into_iter().collect()
+}
+
+impl Directory for AsyncDirectoryAdapter {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let fetched_files = self.fetched_files.read().unwrap();
+        match fetched_files.file_handles.get(path) {
+            Some(Some(file)) => Ok(file.clone()),
+            Some(None) => Err(OpenReadError::FileDoesNotExist(path.to_path_buf())),
+            None => {
+                record_missing(
+                    &self.missing_data_log,
+                    MissingData::FileHandle(path.to_path_buf()),
+                );
+                Err(OpenReadError::wrap_io_error(
+                    not_fetched_error(path),
+                    path.to_path_buf(),
+                ))
+            }
+        }
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        Err(DeleteError::IoError {
+            io_error: Arc::new(self.read_only_error()),
+            filepath: path.to_path_buf(),
+        })
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        match self.get_file_handle(path) {
+            Ok(_) => Ok(true),
+            Err(OpenReadError::FileDoesNotExist(_)) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        Err(OpenWriteError::wrap_io_error(
+            self.read_only_error(),
+            path.to_path_buf(),
+        ))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let fetched_files = self.fetched_files.read().unwrap();
+        match fetched_files.atomic_files.get(path) {
+            Some(Some(data)) => Ok(data.clone()),
+            Some(None) => Err(OpenReadError::FileDoesNotExist(path.to_path_buf())),
+            None => {
+                record_missing(
+                    &self.missing_data_log,
+                    MissingData::AtomicFile(path.to_path_buf()),
+                );
+                Err(OpenReadError::wrap_io_error(
+                    not_fetched_error(path),
+                    path.to_path_buf(),
+                ))
+            }
+        }
+    }
+
+    fn atomic_write(&self, _path: &Path, _data: &[u8]) -> io::Result<()> {
+        Err(self.read_only_error())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        // Nothing is ever written, so readers do not need to lock anything.
+        if lock.filepath == INDEX_WRITER_LOCK.filepath {
+            return Err(LockError::wrap_io_error(self.read_only_error()));
+        }
+        Ok(DirectoryLock::from(Box::new(())))
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(self.watch_router.subscribe(watch_callback))
+    }
+}
+
+/// Opens an index stored in an [`AsyncDirectory`].
+pub(crate) async fn open_index_async(
+    async_directory: Arc<dyn AsyncDirectory>,
+) -> crate::Result<Index> {
+    AsyncDirectoryAdapter::new(async_directory)
+        .open_index()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::collector::Count;
+    use crate::directory::{FileSlice, RamDirectory};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use crate::{DocAddress, DocSet, IndexWriter, TantivyDocument, Term, TERMINATED};
+
+    #[derive(Debug)]
+    struct RamAsyncFile(FileSlice);
+
+    impl HasLen for RamAsyncFile {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[async_trait]
+    impl AsyncFileHandle for RamAsyncFile {
+        async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+            self.0.read_bytes_slice(range)
+        }
+    }
+
+    #[derive(Debug)]
+    struct RamAsyncDirectory(RamDirectory);
+
+    #[async_trait]
+    impl AsyncDirectory for RamAsyncDirectory {
+        async fn get_file_handle_async(
+            &self,
+            path: &Path,
+        ) -> Result<Arc<dyn AsyncFileHandle>, OpenReadError> {
+            Ok(Arc::new(RamAsyncFile(self.0.open_read(path)?)))
+        }
+
+        async fn atomic_read_async(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+            self.0.atomic_read(path)
+        }
+    }
+
+    #[test]
+    fn test_open_index_async() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let directory = RamDirectory::create();
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "hello happy tax payer"))?;
+        index_writer.add_document(doc!(text_field => "goodbye"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "hello again"))?;
+        index_writer.delete_term(Term::from_field_text(text_field, "goodbye"));
+        index_writer.commit()?;
+
+        let index = block_on(Index::open_async(RamAsyncDirectory(directory)))?;
+        assert!(index.writer_for_tests::<TantivyDocument>().is_err());
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+
+        let term = Term::from_field_text(text_field, "hello");
+        let query = TermQuery::new(term.clone(), IndexRecordOption::Basic);
+        // The postings have not been fetched yet.
+        assert!(searcher.search(&query, &Count).is_err());
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(text_field)?;
+            let mut postings =
+                block_on(inverted_index.read_postings_async(&term, IndexRecordOption::Basic))?
+                    .unwrap();
+            assert_eq!(postings.doc(), 0);
+            assert_eq!(postings.advance(), TERMINATED);
+            block_on(inverted_index.warm_postings(&term, false))?;
+        }
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+
+        let doc_address = DocAddress::new(0, 0);
+        assert!(searcher.doc::<TantivyDocument>(doc_address).is_err());
+        let doc: TantivyDocument = block_on(searcher.doc_async(doc_address))?;
+        assert_eq!(doc, doc!(text_field => "hello happy tax payer"));
+        Ok(())
+    }
+}
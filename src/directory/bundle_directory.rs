@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::{BinarySerializable, CountingWriter, HasLen, VInt};
+
+use crate::core::META_FILEPATH;
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileHandle, FileSlice, Lock, WatchCallback, WatchHandle, WritePtr,
+    INDEX_WRITER_LOCK, META_LOCK,
+};
+use crate::Index;
+
+/// The magic number at the end of a bundle.
+const BUNDLE_MAGIC_NUMBER: u32 = 0x7462_646c;
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+/// The footer of a bundle is made of the offset of its file table, its format version and
+/// its magic number.
+const BUNDLE_FOOTER_LEN: usize = 8 + 4 + 4;
+
+fn read_only_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "bundle directories are read-only",
+    )
+}
+
+/// Writes the files of the last commit of an index, and its `meta.json`, into a bundle.
+///
+/// A bundle is the concatenation of the files, followed by a table of their paths and byte
+/// ranges, and by a fixed size footer pointing to the table.
+pub(crate) fn write_bundle(index: &Index, output: &mut dyn Write) -> crate::Result<()> {
+    // Prevents the files of the commit from getting garbage collected while they are copied.
+    let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
+    let metas = index.load_metas()?;
+    let mut meta_data = serde_json::to_vec_pretty(&metas)?;
+    writeln!(&mut meta_data)?;
+
+    let mut segment_file_paths: Vec<PathBuf> = metas
+        .segments
+        .iter()
+        .flat_map(|segment_meta| segment_meta.list_files())
+        .collect();
+    segment_file_paths.sort();
+
+    let mut output = CountingWriter::wrap(output);
+    let mut file_table: Vec<(PathBuf, u64, u64)> = Vec::with_capacity(segment_file_paths.len() + 1);
+    // Segment files are copied with their footer, which the managed directory strips off.
+    let directory = index.directory().underlying_directory();
+    for path in segment_file_paths {
+        // Some of the files listed for a segment may not exist, e.g. its delete file.
+        if !directory.exists(&path)? {
+            continue;
+        }
+        let start = output.written_bytes();
+        let file_slice = directory.open_read(&path)?;
+        for chunk in file_slice.stream_file_chunks() {
+            output.write_all(chunk?.as_slice())?;
+        }
+        file_table.push((path, start, output.written_bytes() - start));
+    }
+    let start = output.written_bytes();
+    output.write_all(&meta_data)?;
+    file_table.push((META_FILEPATH.to_path_buf(), start, meta_data.len() as u64));
+
+    let file_table_offset = output.written_bytes();
+    VInt(file_table.len() as u64).serialize(&mut output)?;
+    for (path, start, num_bytes) in file_table {
+        path.to_string_lossy().into_owned().serialize(&mut output)?;
+        VInt(start).serialize(&mut output)?;
+        VInt(num_bytes).serialize(&mut output)?;
+    }
+    file_table_offset.serialize(&mut output)?;
+    BUNDLE_FORMAT_VERSION.serialize(&mut output)?;
+    BUNDLE_MAGIC_NUMBER.serialize(&mut output)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// A read-only [`Directory`] serving the files of a bundle, written with
+/// [`Index::write_bundle`].
+///
+/// The bundle is read through a [`FileSlice`], so it may be memory mapped, read from a local
+/// file with [`FileSlice::open`], or loaded in memory, e.g. after being downloaded in a
+/// browser.
+///
+/// Searchers can be opened on the index of a bundle, but writers cannot.
+#[derive(Clone)]
+pub struct BundleDirectory {
+    files: Arc<HashMap<PathBuf, FileSlice>>,
+}
+
+impl fmt::Debug for BundleDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BundleDirectory({} files)", self.files.len())
+    }
+}
+
+impl BundleDirectory {
+    /// Opens a bundle.
+    ///
+    /// Only the file table of the bundle is read.
+    pub fn open(bundle: FileSlice) -> io::Result<BundleDirectory> {
+        if bundle.len() < BUNDLE_FOOTER_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "bundle is too small to contain a footer",
+            ));
+        }
+        let (body, footer) = bundle.split_from_end(BUNDLE_FOOTER_LEN);
+        let footer_bytes = footer.read_bytes()?;
+        let mut footer_data = footer_bytes.as_slice();
+        let file_table_offset = u64::deserialize(&mut footer_data)? as usize;
+        let format_version = u32::deserialize(&mut footer_data)?;
+        let magic_number = u32::deserialize(&mut footer_data)?;
+        if magic_number != BUNDLE_MAGIC_NUMBER {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a bundle"));
+        }
+        if format_version != BUNDLE_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported bundle format version {format_version}"),
+            ));
+        }
+        if file_table_offset > body.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "bundle file table offset is out of bounds",
+            ));
+        }
+        let file_table_bytes = body.slice_from(file_table_offset).read_bytes()?;
+        let mut file_table_data = file_table_bytes.as_slice();
+        let num_files = VInt::deserialize_u64(&mut file_table_data)?;
+        let mut files = HashMap::new();
+        for _ in 0..num_files {
+            let path = PathBuf::from(String::deserialize(&mut file_table_data)?);
+            let start = VInt::deserialize_u64(&mut file_table_data)? as usize;
+            let num_bytes = VInt::deserialize_u64(&mut file_table_data)? as usize;
+            let Some(end) = start
+                .checked_add(num_bytes)
+                .filter(|end| *end <= file_table_offset)
+            else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("bundle file {path:?} is out of bounds"),
+                ));
+            };
+            files.insert(path, body.slice(start..end));
+        }
+        Ok(BundleDirectory {
+            files: Arc::new(files),
+        })
+    }
+
+    fn file(&self, path: &Path) -> Result<&FileSlice, OpenReadError> {
+        self.files
+            .get(path)
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(path.to_path_buf()))
+    }
+}
+
+impl Directory for BundleDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        Ok(Arc::new(self.file(path)?.clone()))
+    }
+
+    fn open_read(&self, path: &Path) -> Result<FileSlice, OpenReadError> {
+        self.file(path).cloned()
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        Err(DeleteError::IoError {
+            io_error: Arc::new(read_only_error()),
+            filepath: path.to_path_buf(),
+        })
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        Ok(self.files.contains_key(path))
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        Err(OpenWriteError::wrap_io_error(
+            read_only_error(),
+            path.to_path_buf(),
+        ))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let bytes = self
+            .file(path)?
+            .read_bytes()
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(bytes.as_slice().to_vec())
+    }
+
+    fn atomic_write(&self, _path: &Path, _data: &[u8]) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        // The files of a bundle never change, so readers do not need to lock anything.
+        if lock.filepath == INDEX_WRITER_LOCK.filepath {
+            return Err(LockError::wrap_io_error(read_only_error()));
+        }
+        Ok(DirectoryLock::from(Box::new(())))
+    }
+
+    fn watch(&self, _watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(WatchHandle::empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, Value, STORED, STRING};
+    use crate::{DocAddress, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_bundle_directory() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..10 {
+            let tag = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(tag_field => tag))?;
+        }
+        index_writer.commit()?;
+        index_writer.add_document(doc!(tag_field => "even"))?;
+        index_writer.delete_term(Term::from_field_text(tag_field, "odd"));
+        index_writer.commit()?;
+        // Uncommitted documents are not bundled.
+        index_writer.add_document(doc!(tag_field => "even"))?;
+
+        let mut bundle = Vec::new();
+        index.write_bundle(&mut bundle)?;
+        let directory = BundleDirectory::open(FileSlice::from(bundle))?;
+        let bundle_index = Index::open(directory.clone())?;
+        let searcher = bundle_index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 6);
+        let query = TermQuery::new(
+            Term::from_field_text(tag_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 6);
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        assert_eq!(
+            doc.get_first(tag_field).and_then(|value| value.as_str()),
+            Some("even")
+        );
+
+        assert!(bundle_index.writer_for_tests::<TantivyDocument>().is_err());
+        assert!(directory.atomic_write(&META_FILEPATH, b"").is_err());
+        assert!(directory.delete(&META_FILEPATH).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bundle_directory_corrupted() {
+        assert!(BundleDirectory::open(FileSlice::from(vec![0u8; 4])).is_err());
+        assert!(BundleDirectory::open(FileSlice::from(vec![0u8; 64])).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use std::{fmt, io, thread};
@@ -11,7 +12,7 @@ use crate::directory::{FileHandle, FileSlice, WatchCallback, WatchHandle, WriteP
 /// Retry the logic of acquiring locks is pretty simple.
 /// We just retry `n` times after a given `duratio`, both
 /// depending on the type of lock.
-struct RetryPolicy {
+pub(crate) struct RetryPolicy {
     num_retries: usize,
     wait_in_ms: u64,
 }
@@ -24,7 +25,7 @@ impl RetryPolicy {
         }
     }
 
-    fn wait_and_retry(&mut self) -> bool {
+    pub(crate) fn wait_and_retry(&mut self) -> bool {
         if self.num_retries == 0 {
             false
         } else {
@@ -39,8 +40,38 @@ impl RetryPolicy {
 /// The `DirectoryLock` is an object that represents a file lock.
 ///
 /// It is associated with a lock file, that gets deleted on `Drop.`
-#[expect(dead_code)]
-pub struct DirectoryLock(Box<dyn Send + Sync + 'static>);
+pub struct DirectoryLock {
+    _guard: Box<dyn Send + Sync + 'static>,
+    lost: Arc<AtomicBool>,
+}
+
+impl DirectoryLock {
+    /// Creates a lock which may be lost while it is held, e.g. a lease which could not be
+    /// renewed in time.
+    ///
+    /// The lock holder sets `lost` to `true` as soon as the lock is no longer guaranteed to be
+    /// exclusive. The [`IndexWriter`](crate::IndexWriter) holding the lock then refuses to
+    /// commit, and returns [`LockError::LockLost`].
+    pub fn with_lost_flag<T: Send + Sync + 'static>(
+        guard: Box<T>,
+        lost: Arc<AtomicBool>,
+    ) -> DirectoryLock {
+        DirectoryLock {
+            _guard: guard,
+            lost,
+        }
+    }
+
+    /// Returns `true` if the lock was lost while being held.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+
+    /// Returns the flag set when the lock gets lost.
+    pub(crate) fn lost_flag(&self) -> Arc<AtomicBool> {
+        self.lost.clone()
+    }
+}
 
 struct DirectoryLockGuard {
     directory: Box<dyn Directory>,
@@ -49,7 +80,7 @@ struct DirectoryLockGuard {
 
 impl<T: Send + Sync + 'static> From<Box<T>> for DirectoryLock {
     fn from(underlying: Box<T>) -> Self {
-        DirectoryLock(underlying)
+        DirectoryLock::with_lost_flag(underlying, Arc::default())
     }
 }
 
@@ -86,7 +117,7 @@ fn try_acquire_lock(
     })))
 }
 
-fn retry_policy(is_blocking: bool) -> RetryPolicy {
+pub(crate) fn retry_policy(is_blocking: bool) -> RetryPolicy {
     if is_blocking {
         RetryPolicy {
             num_retries: 100,
@@ -97,6 +128,30 @@ fn retry_policy(is_blocking: bool) -> RetryPolicy {
     }
 }
 
+/// Acquires a lock by creating its lock file in the directory, which is deleted when the
+/// lock is dropped.
+pub(crate) fn acquire_lock_file(
+    directory: Box<dyn Directory>,
+    lock: &Lock,
+) -> Result<DirectoryLock, LockError> {
+    let mut retry_policy = retry_policy(lock.is_blocking);
+    loop {
+        match try_acquire_lock(&lock.filepath, &*directory) {
+            Ok(result) => {
+                return Ok(result);
+            }
+            Err(TryAcquireLockError::FileExists) => {
+                if !retry_policy.wait_and_retry() {
+                    return Err(LockError::LockBusy);
+                }
+            }
+            Err(TryAcquireLockError::IoError(io_error)) => {
+                return Err(LockError::IoError(io_error));
+            }
+        }
+    }
+}
+
 /// Write-once read many (WORM) abstraction for where
 /// tantivy's data should be stored.
 ///
@@ -104,6 +159,9 @@ fn retry_policy(is_blocking: bool) -> RetryPolicy {
 ///
 /// - The [`MMapDirectory`][crate::directory::MmapDirectory], this should be your default choice.
 /// - The [`RamDirectory`][crate::directory::RamDirectory], which should be used mostly for tests.
+///
+/// With the `object-store` feature, the `ObjectStoreDirectory` serves an index directly from
+/// cloud storage.
 pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     /// Opens a file and returns a boxed `FileHandle`.
     ///
@@ -179,6 +237,15 @@ pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     /// The file may or may not previously exist.
     fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()>;
 
+    /// Returns the path of a file on the local filesystem, if the directory stores its files
+    /// there.
+    ///
+    /// This lets files be hard linked rather than copied, e.g. by
+    /// [`Index::create_snapshot`](crate::Index::create_snapshot).
+    fn local_path(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+
     /// Sync the directory.
     ///
     /// This call is required to ensure that newly created files are
@@ -189,23 +256,7 @@ pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     ///
     /// The method is blocking or not depending on the [`Lock`] object.
     fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
-        let box_directory = self.box_clone();
-        let mut retry_policy = retry_policy(lock.is_blocking);
-        loop {
-            match try_acquire_lock(&lock.filepath, &*box_directory) {
-                Ok(result) => {
-                    return Ok(result);
-                }
-                Err(TryAcquireLockError::FileExists) => {
-                    if !retry_policy.wait_and_retry() {
-                        return Err(LockError::LockBusy);
-                    }
-                }
-                Err(TryAcquireLockError::IoError(io_error)) => {
-                    return Err(LockError::IoError(io_error));
-                }
-            }
-        }
+        acquire_lock_file(self.box_clone(), lock)
     }
 
     /// Registers a callback that will be called whenever a change on the `meta.json`
@@ -232,7 +283,8 @@ pub trait DirectoryClone {
 }
 
 impl<T> DirectoryClone for T
-where T: 'static + Directory + Clone
+where
+    T: 'static + Directory + Clone,
 {
     fn box_clone(&self) -> Box<dyn Directory> {
         Box::new(self.clone())
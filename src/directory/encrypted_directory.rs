@@ -0,0 +1,526 @@
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use aes_gcm::aead::{AeadCore, AeadInPlace, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce, Tag};
+use common::HasLen;
+
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, Lock, OwnedBytes, TerminatingWrite,
+    WatchCallback, WatchHandle, WritePtr,
+};
+
+const MAGIC: [u8; 4] = *b"tenc";
+const FORMAT_VERSION: u8 = 1;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + NONCE_LEN;
+const DEFAULT_CHUNK_SIZE: usize = 16_384;
+
+/// The callback returning the key a file is encrypted with, given its path.
+type KeyCallback = dyn Fn(&Path) -> io::Result<[u8; 32]> + Send + Sync;
+
+/// The cleartext header of an encrypted file.
+struct Header {
+    chunk_size: usize,
+    nonce: [u8; NONCE_LEN],
+}
+
+impl Header {
+    fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4] = FORMAT_VERSION;
+        bytes[5..9].copy_from_slice(&(self.chunk_size as u32).to_le_bytes());
+        bytes[9..].copy_from_slice(&self.nonce);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> io::Result<Header> {
+        if bytes[..4] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an encrypted file",
+            ));
+        }
+        if bytes[4] != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported encrypted file format version {}", bytes[4]),
+            ));
+        }
+        let chunk_size = u32::from_le_bytes(bytes[5..9].try_into().unwrap()) as usize;
+        if chunk_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid encrypted file chunk size",
+            ));
+        }
+        Ok(Header {
+            chunk_size,
+            nonce: bytes[9..HEADER_LEN].try_into().unwrap(),
+        })
+    }
+}
+
+/// Encrypts and decrypts the chunks of a file.
+///
+/// The nonce of a chunk is the nonce of the file with its ordinal xored in, and the
+/// associated data flags the last chunk, so that chunks can neither be reordered nor
+/// truncated without the decryption failing.
+struct ChunkCipher {
+    cipher: Aes256Gcm,
+    header: Header,
+}
+
+impl ChunkCipher {
+    fn new(key: &[u8; 32], header: Header) -> ChunkCipher {
+        ChunkCipher {
+            cipher: Aes256Gcm::new(key.into()),
+            header,
+        }
+    }
+
+    fn chunk_nonce(&self, chunk_ord: usize) -> Nonce<<Aes256Gcm as AeadCore>::NonceSize> {
+        let mut nonce = self.header.nonce;
+        for (nonce_byte, ord_byte) in nonce[4..].iter_mut().zip((chunk_ord as u64).to_le_bytes()) {
+            *nonce_byte ^= ord_byte;
+        }
+        nonce.into()
+    }
+
+    fn encrypt(&self, chunk_ord: usize, is_last: bool, chunk: &mut [u8]) -> io::Result<Tag> {
+        self.cipher
+            .encrypt_in_place_detached(&self.chunk_nonce(chunk_ord), &[is_last as u8], chunk)
+            .map_err(|_| io::Error::other("failed to encrypt chunk"))
+    }
+
+    fn decrypt(
+        &self,
+        chunk_ord: usize,
+        is_last: bool,
+        chunk: &mut [u8],
+        tag: &[u8],
+    ) -> io::Result<()> {
+        self.cipher
+            .decrypt_in_place_detached(
+                &self.chunk_nonce(chunk_ord),
+                &[is_last as u8],
+                chunk,
+                Tag::from_slice(tag),
+            )
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "failed to decrypt chunk: wrong key, or corrupted or truncated file",
+                )
+            })
+    }
+}
+
+/// A [`Directory`] wrapper encrypting all the files at rest.
+///
+/// Files are encrypted with AES-256-GCM by chunks of 16KB (by default), so that a range of a
+/// file can be read and decrypted without reading the whole file. Each file has its own
+/// random nonce, stored in a cleartext header along with the chunk size, and the key of each
+/// file is returned by a callback, given the path of the file. The callback is called every
+/// time a file is opened, and it may return different keys for different files, e.g. to
+/// rotate keys. Files that were tampered with or truncated fail to be opened or read.
+///
+/// As the last chunk of a file is only written when its writer is terminated, flushing a
+/// writer does not make the last, partial chunk readable. Tantivy always terminates the files
+/// it writes before reading them.
+///
+/// Locks are handled by the wrapped directory, and lock files are not encrypted.
+#[derive(Clone)]
+pub struct EncryptedDirectory {
+    directory: Box<dyn Directory>,
+    key_callback: Arc<KeyCallback>,
+    chunk_size: usize,
+}
+
+impl fmt::Debug for EncryptedDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedDirectory({:?})", self.directory)
+    }
+}
+
+impl EncryptedDirectory {
+    /// Wraps a directory, with `key_callback` returning the 256 bits key of each file.
+    pub fn new(
+        directory: Box<dyn Directory>,
+        key_callback: impl Fn(&Path) -> io::Result<[u8; 32]> + Send + Sync + 'static,
+    ) -> EncryptedDirectory {
+        EncryptedDirectory {
+            directory,
+            key_callback: Arc::new(key_callback),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Sets the size of the chunks new files are encrypted by.
+    ///
+    /// Reading any range of a file requires to read and decrypt the chunks it overlaps, while
+    /// each chunk adds 16 bytes of authentication tag to the file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk size is 0 or does not fit in 32 bits.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> EncryptedDirectory {
+        assert!(
+            chunk_size > 0 && chunk_size <= u32::MAX as usize,
+            "invalid chunk size {chunk_size}"
+        );
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    fn new_writer<W: TerminatingWrite>(
+        &self,
+        path: &Path,
+        underlying: W,
+    ) -> io::Result<EncryptedWriter<W>> {
+        let key = (self.key_callback)(path)?;
+        let header = Header {
+            chunk_size: self.chunk_size,
+            nonce: Aes256Gcm::generate_nonce(&mut OsRng).into(),
+        };
+        Ok(EncryptedWriter {
+            cipher: ChunkCipher::new(&key, header),
+            underlying,
+            chunk: Vec::with_capacity(self.chunk_size),
+            num_chunks: 0,
+            is_terminated: false,
+        })
+    }
+
+    fn open_file(
+        &self,
+        path: &Path,
+        underlying: Arc<dyn FileHandle>,
+    ) -> io::Result<Arc<dyn FileHandle>> {
+        // Files whose writer did not write anything yet are empty.
+        if underlying.len() == 0 {
+            return Ok(Arc::new(OwnedBytes::empty()));
+        }
+        if underlying.len() < HEADER_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "encrypted file is truncated",
+            ));
+        }
+        let header = Header::from_bytes(underlying.read_bytes(0..HEADER_LEN)?.as_slice())?;
+        let chunk_stride = header.chunk_size + TAG_LEN;
+        let encrypted_len = underlying.len() - HEADER_LEN;
+        let last_chunk_len = encrypted_len % chunk_stride;
+        if last_chunk_len < TAG_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "encrypted file is truncated",
+            ));
+        }
+        let num_chunks = encrypted_len / chunk_stride + 1;
+        let len = (num_chunks - 1) * header.chunk_size + last_chunk_len - TAG_LEN;
+        let key = (self.key_callback)(path)?;
+        let file = EncryptedFile {
+            cipher: ChunkCipher::new(&key, header),
+            underlying,
+            num_chunks,
+            len,
+        };
+        // Only the last chunk is flagged as such, so it is checked right away to detect
+        // truncated files, even if it is not read afterwards.
+        file.read_chunks(num_chunks - 1..num_chunks)?;
+        Ok(Arc::new(file))
+    }
+}
+
+/// A file of an [`EncryptedDirectory`], decrypted on read.
+struct EncryptedFile {
+    cipher: ChunkCipher,
+    underlying: Arc<dyn FileHandle>,
+    num_chunks: usize,
+    len: usize,
+}
+
+impl fmt::Debug for EncryptedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedFile({:?})", self.underlying)
+    }
+}
+
+impl HasLen for EncryptedFile {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl EncryptedFile {
+    /// Reads and decrypts a range of chunks.
+    fn read_chunks(&self, chunk_ords: Range<usize>) -> io::Result<Vec<u8>> {
+        let chunk_stride = self.cipher.header.chunk_size + TAG_LEN;
+        let encrypted_range = HEADER_LEN + chunk_ords.start * chunk_stride
+            ..(HEADER_LEN + chunk_ords.end * chunk_stride).min(self.underlying.len());
+        let encrypted = self.underlying.read_bytes(encrypted_range)?;
+        let mut decrypted = Vec::with_capacity(encrypted.len());
+        for (chunk_ord, encrypted_chunk) in
+            chunk_ords.zip(encrypted.as_slice().chunks(chunk_stride))
+        {
+            let (ciphertext, tag) = encrypted_chunk.split_at(encrypted_chunk.len() - TAG_LEN);
+            let chunk_start = decrypted.len();
+            decrypted.extend_from_slice(ciphertext);
+            let is_last = chunk_ord + 1 == self.num_chunks;
+            self.cipher
+                .decrypt(chunk_ord, is_last, &mut decrypted[chunk_start..], tag)?;
+        }
+        Ok(decrypted)
+    }
+}
+
+impl FileHandle for EncryptedFile {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let chunk_size = self.cipher.header.chunk_size;
+        let first_chunk_ord = range.start / chunk_size;
+        let end_chunk_ord = (range.end - 1) / chunk_size + 1;
+        let decrypted = self.read_chunks(first_chunk_ord..end_chunk_ord)?;
+        let start = range.start - first_chunk_ord * chunk_size;
+        Ok(OwnedBytes::new(decrypted).slice(start..start + range.len()))
+    }
+}
+
+/// Writes a file of an [`EncryptedDirectory`], by chunks.
+struct EncryptedWriter<W: TerminatingWrite> {
+    cipher: ChunkCipher,
+    underlying: W,
+    // The chunk being written. It is never full: full chunks are written right away.
+    chunk: Vec<u8>,
+    num_chunks: usize,
+    is_terminated: bool,
+}
+
+impl<W: TerminatingWrite> EncryptedWriter<W> {
+    fn write_chunk(&mut self, is_last: bool) -> io::Result<()> {
+        // The header is only written along with the first chunk, so that files that are
+        // opened but not written to remain empty.
+        if self.num_chunks == 0 {
+            self.underlying.write_all(&self.cipher.header.to_bytes())?;
+        }
+        let tag = self
+            .cipher
+            .encrypt(self.num_chunks, is_last, &mut self.chunk)?;
+        self.underlying.write_all(&self.chunk)?;
+        self.underlying.write_all(&tag)?;
+        self.chunk.clear();
+        self.num_chunks += 1;
+        Ok(())
+    }
+}
+
+impl<W: TerminatingWrite> Drop for EncryptedWriter<W> {
+    fn drop(&mut self) {
+        if !self.is_terminated && (self.num_chunks > 0 || !self.chunk.is_empty()) {
+            warn!(
+                "An encrypted file got dropped without being terminated. Its last chunk is lost. \
+                 This also occurs when the indexer crashed, so you may want to check the logs for \
+                 the root cause."
+            )
+        }
+    }
+}
+
+impl<W: TerminatingWrite> Write for EncryptedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let chunk_size = self.cipher.header.chunk_size;
+        let mut remaining = buf;
+        while !remaining.is_empty() {
+            let num_bytes = remaining.len().min(chunk_size - self.chunk.len());
+            self.chunk.extend_from_slice(&remaining[..num_bytes]);
+            remaining = &remaining[num_bytes..];
+            if self.chunk.len() == chunk_size {
+                self.write_chunk(false)?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // The partial chunk being written can only be written once the file is terminated.
+        self.underlying.flush()
+    }
+}
+
+impl<W: TerminatingWrite> TerminatingWrite for EncryptedWriter<W> {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.write_chunk(true)?;
+        self.is_terminated = true;
+        self.underlying.terminate_ref(token)
+    }
+}
+
+impl Directory for EncryptedDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let underlying = self.directory.get_file_handle(path)?;
+        self.open_file(path, underlying)
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.directory.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.directory.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let underlying = self.directory.open_write(path)?;
+        let writer = self
+            .new_writer(path, underlying)
+            .map_err(|io_error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(BufWriter::new(Box::new(writer)))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let data = OwnedBytes::new(self.directory.atomic_read(path)?);
+        let to_open_read_error =
+            |io_error: io::Error| OpenReadError::wrap_io_error(io_error, path.to_path_buf());
+        let file = self
+            .open_file(path, Arc::new(data))
+            .map_err(to_open_read_error)?;
+        let decrypted = file.read_bytes(0..file.len()).map_err(to_open_read_error)?;
+        Ok(decrypted.as_slice().to_vec())
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let mut encrypted = Vec::new();
+        let mut writer = self.new_writer(path, &mut encrypted)?;
+        writer.write_all(data)?;
+        writer.terminate()?;
+        self.directory.atomic_write(path, &encrypted)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.directory.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.directory.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.directory.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Count;
+    use crate::directory::RamDirectory;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, Value, STORED, STRING};
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, Term};
+
+    fn encrypted_directory(directory: &RamDirectory, key: [u8; 32]) -> EncryptedDirectory {
+        EncryptedDirectory::new(Box::new(directory.clone()), move |_| Ok(key)).with_chunk_size(4)
+    }
+
+    #[test]
+    fn test_encrypted_directory_range_reads() -> crate::Result<()> {
+        let ram_directory = RamDirectory::create();
+        let directory = encrypted_directory(&ram_directory, [1u8; 32]);
+        let data: Vec<u8> = (0..=41u8).collect();
+        for len in [0, 1, 4, 8, 42] {
+            let path = format!("file{len}");
+            let mut writer = directory.open_write(Path::new(&path))?;
+            writer.write_all(&data[..len])?;
+            writer.terminate()?;
+            let file = directory.open_read(Path::new(&path))?;
+            assert_eq!(file.len(), len);
+            for start in 0..len {
+                for end in start..=len {
+                    let bytes = file.read_bytes_slice(start..end)?;
+                    assert_eq!(bytes.as_slice(), &data[start..end]);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_directory_at_rest() -> crate::Result<()> {
+        let ram_directory = RamDirectory::create();
+        let directory = encrypted_directory(&ram_directory, [1u8; 32]);
+        let path = Path::new("file");
+        let mut writer = directory.open_write(path)?;
+        writer.write_all(b"secret data")?;
+        writer.terminate()?;
+        directory.atomic_write(Path::new("meta"), b"secret meta")?;
+
+        let encrypted = ram_directory.open_read(path)?.read_bytes()?;
+        assert!(!encrypted
+            .as_slice()
+            .windows(6)
+            .any(|window| window == b"secret"));
+        let encrypted_meta = ram_directory.atomic_read(Path::new("meta"))?;
+        assert!(!encrypted_meta.windows(6).any(|window| window == b"secret"));
+        assert_eq!(directory.atomic_read(Path::new("meta"))?, b"secret meta");
+
+        // Files cannot be read with the wrong key, nor opened once truncated.
+        let wrong_key_directory = encrypted_directory(&ram_directory, [2u8; 32]);
+        assert!(wrong_key_directory.open_read(path).is_err());
+        assert!(wrong_key_directory.atomic_read(Path::new("meta")).is_err());
+        for (truncated_path, num_truncated_bytes) in
+            [("truncated_chunk", 4 + TAG_LEN), ("truncated", 3)]
+        {
+            let truncated_path = Path::new(truncated_path);
+            let mut writer = ram_directory.open_write(truncated_path)?;
+            writer.write_all(&encrypted.as_slice()[..encrypted.len() - num_truncated_bytes])?;
+            writer.terminate()?;
+            assert!(directory.open_read(truncated_path).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_directory_index() -> crate::Result<()> {
+        let ram_directory = RamDirectory::create();
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING | STORED);
+        let schema = schema_builder.build();
+
+        let directory = encrypted_directory(&ram_directory, [1u8; 32]);
+        let index = Index::create(directory, schema, Default::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..100 {
+            let tag = if i % 4 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(tag_field => tag))?;
+        }
+        index_writer.commit()?;
+        drop(index_writer);
+
+        let index = Index::open(encrypted_directory(&ram_directory, [1u8; 32]))?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(tag_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 25);
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        assert_eq!(
+            doc.get_first(tag_field).and_then(|v| v.as_str()),
+            Some("even")
+        );
+
+        assert!(Index::open(ram_directory.clone()).is_err());
+        assert!(Index::open(encrypted_directory(&ram_directory, [2u8; 32])).is_err());
+        Ok(())
+    }
+}
@@ -20,6 +20,10 @@ pub enum LockError {
     /// Trying to acquire a lock failed with an `IoError`
     #[error("Failed to acquire the lock due to an io:Error.")]
     IoError(Arc<io::Error>),
+    /// The lock was held, but got lost, e.g. because its lease could not be renewed before
+    /// expiring. Another process may have acquired it since.
+    #[error("The lock was lost while being held, possibly to a different process.")]
+    LockLost,
 }
 
 impl LockError {
@@ -195,6 +199,25 @@ pub enum DeleteError {
     },
 }
 
+/// Error returned by the writes of a [`QuotaDirectory`](crate::directory::QuotaDirectory)
+/// that would exceed its quota.
+///
+/// It is wrapped in the returned [`io::Error`], and can be retrieved with
+/// `io_error.get_ref().and_then(|error| error.downcast_ref::<QuotaExceededError>())`.
+#[derive(Debug, Clone, Error)]
+#[error(
+    "Directory quota exceeded: writing {num_bytes} bytes would bring the usage from {usage} bytes \
+     over the limit of {limit} bytes."
+)]
+pub struct QuotaExceededError {
+    /// The quota of the directory, in bytes.
+    pub limit: u64,
+    /// The number of bytes used before the write.
+    pub usage: u64,
+    /// The number of bytes of the rejected write.
+    pub num_bytes: u64,
+}
+
 impl From<Incompatibility> for OpenReadError {
     fn from(incompatibility: Incompatibility) -> Self {
         OpenReadError::IncompatibleIndex(incompatibility)
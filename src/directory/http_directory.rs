@@ -0,0 +1,422 @@
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fmt, io};
+
+use async_trait::async_trait;
+use common::HasLen;
+use futures_util::future::try_join_all;
+use lru::LruCache;
+
+use crate::directory::error::OpenReadError;
+use crate::directory::{AsyncDirectory, AsyncFileHandle, OwnedBytes};
+
+const DEFAULT_CHUNK_SIZE: usize = 64 << 10;
+const DEFAULT_CACHE_SIZE: usize = 32 << 20;
+
+/// Sends the HTTP requests of an [`HttpDirectory`].
+///
+/// Tantivy does not depend on an HTTP client: in a browser, this is typically implemented with
+/// the `fetch` API, natively with any HTTP client library. On `wasm32-unknown-unknown`, the
+/// futures of the `fetch` API are not `Send`: as the target is single-threaded, they can be
+/// wrapped, for instance with the `send_wrapper` crate.
+#[async_trait]
+pub trait HttpClient: 'static + Send + Sync + fmt::Debug {
+    /// Returns the length of the resource at `url`, typically with a `HEAD` request, or `None`
+    /// if it does not exist.
+    async fn content_length(&self, url: &str) -> io::Result<Option<usize>>;
+
+    /// Fetches the resource at `url`, or only the given byte range of it with a `Range` header.
+    ///
+    /// Returns `None` if the resource does not exist. A server ignoring the `Range` header and
+    /// returning the full resource is supported.
+    async fn get(&self, url: &str, range: Option<Range<usize>>) -> io::Result<Option<OwnedBytes>>;
+}
+
+#[derive(Clone, Debug, bon::Builder)]
+/// Options of an [`HttpDirectory`].
+pub struct HttpDirectoryOptions {
+    #[builder(default = DEFAULT_CHUNK_SIZE)]
+    /// The size of the chunks the files are fetched and cached by.
+    chunk_size: usize,
+    #[builder(default = DEFAULT_CACHE_SIZE)]
+    /// The overall size of the chunks kept in memory. 0 disables the cache.
+    cache_size: usize,
+}
+
+impl Default for HttpDirectoryOptions {
+    fn default() -> Self {
+        HttpDirectoryOptions::builder().build()
+    }
+}
+
+struct InnerDirectory {
+    base_url: String,
+    client: Arc<dyn HttpClient>,
+    chunk_size: usize,
+    chunk_cache: Option<Mutex<LruCache<(PathBuf, usize), OwnedBytes>>>,
+}
+
+impl InnerDirectory {
+    fn url(&self, path: &Path) -> String {
+        path.iter().fold(self.base_url.clone(), |mut url, part| {
+            url.push('/');
+            url.push_str(&part.to_string_lossy());
+            url
+        })
+    }
+}
+
+/// A read-only [`AsyncDirectory`] fetching the files of an index with HTTP range requests,
+/// so that a static index hosted on a web server or a CDN can be searched without
+/// downloading it entirely, including client-side from a browser.
+///
+/// Files are fetched by chunks of a fixed size: the chunks missing to read a range are fetched
+/// with one request per run of consecutive chunks, and are kept in an in-memory LRU cache.
+///
+/// The index is opened with [`Index::open_async`](crate::Index::open_async). To search it from
+/// `wasm32-unknown-unknown`, tantivy is compiled without its default features, and with the
+/// `wasm` and `quickwit` features; the reader must use [`ReloadPolicy::Manual`] as no thread
+/// can be spawned.
+///
+/// [`ReloadPolicy::Manual`]: crate::ReloadPolicy::Manual
+#[derive(Clone)]
+pub struct HttpDirectory {
+    inner: Arc<InnerDirectory>,
+}
+
+impl fmt::Debug for HttpDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HttpDirectory({})", self.inner.base_url)
+    }
+}
+
+impl HttpDirectory {
+    /// Opens a directory whose files are served under `base_url`.
+    pub fn open(
+        base_url: &str,
+        client: Arc<dyn HttpClient>,
+        options: HttpDirectoryOptions,
+    ) -> io::Result<HttpDirectory> {
+        if options.chunk_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the chunk size must be positive",
+            ));
+        }
+        let chunk_cache = NonZeroUsize::new(options.cache_size / options.chunk_size)
+            .map(|num_chunks| Mutex::new(LruCache::new(num_chunks)));
+        Ok(HttpDirectory {
+            inner: Arc::new(InnerDirectory {
+                base_url: base_url.trim_end_matches('/').to_string(),
+                client,
+                chunk_size: options.chunk_size,
+                chunk_cache,
+            }),
+        })
+    }
+}
+
+#[async_trait]
+impl AsyncDirectory for HttpDirectory {
+    async fn get_file_handle_async(
+        &self,
+        path: &Path,
+    ) -> Result<Arc<dyn AsyncFileHandle>, OpenReadError> {
+        let url = self.inner.url(path);
+        let len = self
+            .inner
+            .client
+            .content_length(&url)
+            .await
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(path.to_path_buf()))?;
+        Ok(Arc::new(HttpFile {
+            directory: self.inner.clone(),
+            path: path.to_path_buf(),
+            url,
+            len,
+        }))
+    }
+
+    async fn atomic_read_async(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let data = self
+            .inner
+            .client
+            .get(&self.inner.url(path), None)
+            .await
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(path.to_path_buf()))?;
+        Ok(data.as_slice().to_vec())
+    }
+}
+
+/// A file of an [`HttpDirectory`].
+struct HttpFile {
+    directory: Arc<InnerDirectory>,
+    path: PathBuf,
+    url: String,
+    len: usize,
+}
+
+impl fmt::Debug for HttpFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HttpFile({:?}, len={})", self.path, self.len)
+    }
+}
+
+impl HasLen for HttpFile {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl HttpFile {
+    fn byte_range(&self, chunk_ords: Range<usize>) -> Range<usize> {
+        let chunk_size = self.directory.chunk_size;
+        chunk_ords.start * chunk_size..(chunk_ords.end * chunk_size).min(self.len)
+    }
+
+    fn get_cached(&self, chunk_ord: usize) -> Option<OwnedBytes> {
+        let chunk_cache = self.directory.chunk_cache.as_ref()?;
+        let key = (self.path.clone(), chunk_ord);
+        chunk_cache.lock().unwrap().get(&key).cloned()
+    }
+
+    fn put_cached(&self, chunk_ord: usize, chunk: OwnedBytes) {
+        if let Some(chunk_cache) = &self.directory.chunk_cache {
+            chunk_cache
+                .lock()
+                .unwrap()
+                .put((self.path.clone(), chunk_ord), chunk);
+        }
+    }
+
+    /// Fetches the consecutive chunks `chunk_ords`.
+    async fn fetch_chunks(&self, chunk_ords: Range<usize>) -> io::Result<Vec<OwnedBytes>> {
+        let byte_range = self.byte_range(chunk_ords.clone());
+        let mut bytes = self
+            .directory
+            .client
+            .get(&self.url, Some(byte_range.clone()))
+            .await?
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{:?} does not exist anymore", self.path),
+                )
+            })?;
+        if bytes.len() == self.len && byte_range.len() != self.len {
+            // The server ignored the range.
+            bytes = bytes.slice(byte_range.clone());
+        }
+        if bytes.len() != byte_range.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Expected {} bytes for the range {byte_range:?} of {:?}, got {}",
+                    byte_range.len(),
+                    self.path,
+                    bytes.len()
+                ),
+            ));
+        }
+        Ok(chunk_ords
+            .map(|chunk_ord| {
+                let chunk_range = self.byte_range(chunk_ord..chunk_ord + 1);
+                bytes
+                    .slice(chunk_range.start - byte_range.start..chunk_range.end - byte_range.start)
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl AsyncFileHandle for HttpFile {
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let chunk_size = self.directory.chunk_size;
+        let chunk_ords = range.start / chunk_size..(range.end - 1) / chunk_size + 1;
+        let mut chunks: Vec<Option<OwnedBytes>> = chunk_ords
+            .clone()
+            .map(|chunk_ord| self.get_cached(chunk_ord))
+            .collect();
+        // Runs of consecutive missing chunks are fetched with a single request.
+        let mut missing_runs: Vec<Range<usize>> = Vec::new();
+        for chunk_ord in chunk_ords.clone() {
+            if chunks[chunk_ord - chunk_ords.start].is_some() {
+                continue;
+            }
+            match missing_runs.last_mut() {
+                Some(missing_run) if missing_run.end == chunk_ord => missing_run.end += 1,
+                _ => missing_runs.push(chunk_ord..chunk_ord + 1),
+            }
+        }
+        let fetched_runs = try_join_all(
+            missing_runs
+                .iter()
+                .map(|missing_run| self.fetch_chunks(missing_run.clone())),
+        )
+        .await?;
+        for (missing_run, fetched_chunks) in missing_runs.into_iter().zip(fetched_runs) {
+            for (chunk_ord, chunk) in missing_run.zip(fetched_chunks) {
+                self.put_cached(chunk_ord, chunk.clone());
+                chunks[chunk_ord - chunk_ords.start] = Some(chunk);
+            }
+        }
+        let chunks_start = chunk_ords.start * chunk_size;
+        let mut data = Vec::with_capacity(range.len());
+        for chunk in chunks.into_iter().flatten() {
+            data.extend_from_slice(chunk.as_slice());
+        }
+        Ok(OwnedBytes::new(data).slice(range.start - chunks_start..range.end - chunks_start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::collector::Count;
+    use crate::directory::{Directory, RamDirectory};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, Term};
+
+    /// Serves the files of a `RamDirectory` under `http://cdn/index`.
+    #[derive(Debug, Default)]
+    struct RamHttpClient {
+        directory: RamDirectory,
+        ignore_ranges: bool,
+        num_range_requests: AtomicUsize,
+    }
+
+    impl RamHttpClient {
+        fn path<'a>(&self, url: &'a str) -> Option<&'a Path> {
+            url.strip_prefix("http://cdn/index/").map(Path::new)
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for RamHttpClient {
+        async fn content_length(&self, url: &str) -> io::Result<Option<usize>> {
+            let Some(path) = self.path(url) else {
+                return Ok(None);
+            };
+            match self.directory.open_read(path) {
+                Ok(file_slice) => Ok(Some(file_slice.len())),
+                Err(OpenReadError::FileDoesNotExist(_)) => Ok(None),
+                Err(error) => Err(io::Error::other(error)),
+            }
+        }
+
+        async fn get(
+            &self,
+            url: &str,
+            range: Option<Range<usize>>,
+        ) -> io::Result<Option<OwnedBytes>> {
+            let Some(path) = self.path(url) else {
+                return Ok(None);
+            };
+            let file_slice = match self.directory.open_read(path) {
+                Ok(file_slice) => file_slice,
+                Err(OpenReadError::FileDoesNotExist(_)) => return Ok(None),
+                Err(error) => return Err(io::Error::other(error)),
+            };
+            let bytes = match range {
+                Some(range) if !self.ignore_ranges => {
+                    self.num_range_requests.fetch_add(1, Ordering::SeqCst);
+                    file_slice.read_bytes_slice(range)?
+                }
+                _ => file_slice.read_bytes()?,
+            };
+            Ok(Some(bytes))
+        }
+    }
+
+    fn create_index(directory: RamDirectory) -> crate::Result<Schema> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let index = Index::create(directory, schema.clone(), Default::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..100 {
+            index_writer.add_document(doc!(text_field => format!("hello {i}")))?;
+        }
+        index_writer.commit()?;
+        Ok(schema)
+    }
+
+    fn search(client: Arc<RamHttpClient>, options: HttpDirectoryOptions) -> crate::Result<()> {
+        let http_directory = HttpDirectory::open("http://cdn/index/", client, options)?;
+        let index = block_on(Index::open_async(http_directory))?;
+        let text_field = index.schema().get_field("text")?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 100);
+        let term = Term::from_field_text(text_field, "hello");
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(text_field)?;
+            block_on(inverted_index.warm_postings(&term, false))?;
+        }
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        assert_eq!(searcher.search(&query, &Count)?, 100);
+        let doc: TantivyDocument = block_on(searcher.doc_async(DocAddress::new(0, 42)))?;
+        assert_eq!(doc, doc!(text_field => "hello 42"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_directory() -> crate::Result<()> {
+        let client = Arc::new(RamHttpClient::default());
+        create_index(client.directory.clone())?;
+        let options = HttpDirectoryOptions::builder().chunk_size(100).build();
+        search(client.clone(), options)?;
+        let num_range_requests = client.num_range_requests.load(Ordering::SeqCst);
+        assert!(num_range_requests > 0);
+
+        // Without the cache, the chunks shared by the reads are fetched again.
+        let client = Arc::new(RamHttpClient {
+            directory: client.directory.clone(),
+            ..Default::default()
+        });
+        let options = HttpDirectoryOptions::builder()
+            .chunk_size(100)
+            .cache_size(0)
+            .build();
+        search(client.clone(), options)?;
+        assert!(client.num_range_requests.load(Ordering::SeqCst) > num_range_requests);
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_directory_ignoring_ranges() -> crate::Result<()> {
+        let client = Arc::new(RamHttpClient {
+            ignore_ranges: true,
+            ..Default::default()
+        });
+        create_index(client.directory.clone())?;
+        search(client, HttpDirectoryOptions::default())
+    }
+
+    #[test]
+    fn test_http_directory_missing_index() {
+        let client = Arc::new(RamHttpClient::default());
+        let http_directory =
+            HttpDirectory::open("http://cdn/index", client, HttpDirectoryOptions::default())
+                .unwrap();
+        assert!(block_on(Index::open_async(http_directory)).is_err());
+        let options = HttpDirectoryOptions::builder().chunk_size(0).build();
+        assert!(HttpDirectory::open(
+            "http://cdn/index",
+            Arc::new(RamHttpClient::default()),
+            options
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "quickwit")]
+use async_trait::async_trait;
+use common::{HasLen, OwnedBytes};
+
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, Lock, TerminatingWrite, WatchCallback,
+    WatchHandle, WritePtr,
+};
+use crate::index::SegmentComponent;
+
+/// IO metrics of a group of files of an [`InstrumentedDirectory`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IoMetrics {
+    /// Number of reads.
+    pub num_reads: u64,
+    /// Number of bytes read.
+    pub bytes_read: u64,
+    /// Cumulated duration of the reads.
+    pub read_latency: Duration,
+    /// Duration of the slowest read.
+    pub max_read_latency: Duration,
+    /// Number of bytes written.
+    pub bytes_written: u64,
+    /// Number of file handles currently open.
+    pub num_open_file_handles: u64,
+}
+
+impl IoMetrics {
+    /// Returns the mean duration of the reads, or `None` if nothing was read.
+    pub fn mean_read_latency(&self) -> Option<Duration> {
+        if self.num_reads == 0 {
+            return None;
+        }
+        Some(Duration::from_nanos(
+            (self.read_latency.as_nanos() / self.num_reads as u128) as u64,
+        ))
+    }
+
+    fn merge(&mut self, other: &IoMetrics) {
+        self.num_reads += other.num_reads;
+        self.bytes_read += other.bytes_read;
+        self.read_latency += other.read_latency;
+        self.max_read_latency = self.max_read_latency.max(other.max_read_latency);
+        self.bytes_written += other.bytes_written;
+        self.num_open_file_handles += other.num_open_file_handles;
+    }
+}
+
+/// Snapshot of the IO metrics of an [`InstrumentedDirectory`], broken down by file type.
+#[derive(Clone, Debug, Default)]
+pub struct DirectoryIoMetrics {
+    per_component: HashMap<SegmentComponent, IoMetrics>,
+    other: IoMetrics,
+}
+
+impl DirectoryIoMetrics {
+    /// Returns the IO metrics of all the files of the directory.
+    pub fn total(&self) -> IoMetrics {
+        let mut total = self.other;
+        for metrics in self.per_component.values() {
+            total.merge(metrics);
+        }
+        total
+    }
+
+    /// Returns the IO metrics of the files of a given segment component.
+    pub fn component(&self, component: SegmentComponent) -> IoMetrics {
+        self.per_component
+            .get(&component)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the IO metrics of the files that do not belong to a segment, like `meta.json`.
+    pub fn other(&self) -> IoMetrics {
+        self.other
+    }
+}
+
+#[derive(Default)]
+struct IoCounters {
+    num_reads: AtomicU64,
+    bytes_read: AtomicU64,
+    read_latency_nanos: AtomicU64,
+    max_read_latency_nanos: AtomicU64,
+    bytes_written: AtomicU64,
+    num_open_file_handles: AtomicU64,
+}
+
+impl IoCounters {
+    fn record_read(&self, num_bytes: usize, latency: Duration) {
+        let latency_nanos = latency.as_nanos() as u64;
+        self.num_reads.fetch_add(1, Ordering::Relaxed);
+        self.bytes_read
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        self.read_latency_nanos
+            .fetch_add(latency_nanos, Ordering::Relaxed);
+        self.max_read_latency_nanos
+            .fetch_max(latency_nanos, Ordering::Relaxed);
+    }
+
+    fn record_write(&self, num_bytes: usize) {
+        self.bytes_written
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> IoMetrics {
+        IoMetrics {
+            num_reads: self.num_reads.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            read_latency: Duration::from_nanos(self.read_latency_nanos.load(Ordering::Relaxed)),
+            max_read_latency: Duration::from_nanos(
+                self.max_read_latency_nanos.load(Ordering::Relaxed),
+            ),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            num_open_file_handles: self.num_open_file_handles.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct DirectoryCounters {
+    per_component: HashMap<SegmentComponent, Arc<IoCounters>>,
+    other: Arc<IoCounters>,
+}
+
+impl DirectoryCounters {
+    fn new() -> DirectoryCounters {
+        DirectoryCounters {
+            per_component: SegmentComponent::iterator()
+                .map(|&component| (component, Arc::default()))
+                .collect(),
+            other: Arc::default(),
+        }
+    }
+
+    fn for_path(&self, path: &Path) -> &Arc<IoCounters> {
+        SegmentComponent::from_path(path)
+            .and_then(|component| self.per_component.get(&component))
+            .unwrap_or(&self.other)
+    }
+}
+
+/// A [`Directory`] wrapper recording the IO of the files of a directory, for debugging slow
+/// searches and indexing in production.
+///
+/// The number of bytes read and written, the latency of the reads and the number of open
+/// file handles are recorded per [`SegmentComponent`], and returned by
+/// [`InstrumentedDirectory::metrics`].
+///
+/// With the `tracing` feature, every read and atomic operation is also wrapped in a `DEBUG`
+/// level [`tracing`](https://docs.rs/tracing) span, recording the file and the number of
+/// bytes.
+#[derive(Clone)]
+pub struct InstrumentedDirectory {
+    directory: Box<dyn Directory>,
+    counters: Arc<DirectoryCounters>,
+}
+
+impl fmt::Debug for InstrumentedDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "InstrumentedDirectory({:?})", self.directory)
+    }
+}
+
+impl InstrumentedDirectory {
+    /// Wraps a directory, recording its IO.
+    pub fn wrap(directory: Box<dyn Directory>) -> InstrumentedDirectory {
+        InstrumentedDirectory {
+            directory,
+            counters: Arc::new(DirectoryCounters::new()),
+        }
+    }
+
+    /// Returns a snapshot of the IO metrics of the directory.
+    ///
+    /// The metrics are cumulated since the directory was wrapped, except for the number of
+    /// open file handles.
+    pub fn metrics(&self) -> DirectoryIoMetrics {
+        DirectoryIoMetrics {
+            per_component: self
+                .counters
+                .per_component
+                .iter()
+                .map(|(&component, counters)| (component, counters.snapshot()))
+                .collect(),
+            other: self.counters.other.snapshot(),
+        }
+    }
+}
+
+impl Directory for InstrumentedDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let underlying = self.directory.get_file_handle(path)?;
+        let counters = self.counters.for_path(path).clone();
+        counters
+            .num_open_file_handles
+            .fetch_add(1, Ordering::Relaxed);
+        Ok(Arc::new(InstrumentedFile {
+            path: path.to_path_buf(),
+            underlying,
+            counters,
+        }))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.directory.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.directory.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        // Bytes are counted as they leave the buffer, so the buffer of the underlying writer
+        // is moved in front of the counting writer.
+        let underlying = self
+            .directory
+            .open_write(path)?
+            .into_inner()
+            .map_err(|error| {
+                OpenWriteError::wrap_io_error(error.into_error(), path.to_path_buf())
+            })?;
+        Ok(BufWriter::new(Box::new(InstrumentedWriter {
+            counters: self.counters.for_path(path).clone(),
+            underlying,
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("tantivy_atomic_read", file = %path.display()).entered();
+        let start = Instant::now();
+        let data = self.directory.atomic_read(path)?;
+        self.counters
+            .for_path(path)
+            .record_read(data.len(), start.elapsed());
+        Ok(data)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "tantivy_atomic_write",
+            file = %path.display(),
+            num_bytes = data.len()
+        )
+        .entered();
+        self.directory.atomic_write(path, data)?;
+        self.counters.for_path(path).record_write(data.len());
+        Ok(())
+    }
+
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        self.directory.local_path(path)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.directory.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.directory.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.directory.watch(watch_callback)
+    }
+}
+
+/// A file handle recording its reads.
+struct InstrumentedFile {
+    path: PathBuf,
+    underlying: Arc<dyn FileHandle>,
+    counters: Arc<IoCounters>,
+}
+
+impl fmt::Debug for InstrumentedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "InstrumentedFile({:?}, {:?})",
+            self.path, self.underlying
+        )
+    }
+}
+
+impl Drop for InstrumentedFile {
+    fn drop(&mut self) {
+        self.counters
+            .num_open_file_handles
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl HasLen for InstrumentedFile {
+    fn len(&self) -> usize {
+        self.underlying.len()
+    }
+}
+
+#[cfg_attr(feature = "quickwit", async_trait)]
+impl FileHandle for InstrumentedFile {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "tantivy_read",
+            file = %self.path.display(),
+            num_bytes = range.len()
+        )
+        .entered();
+        let start = Instant::now();
+        let bytes = self.underlying.read_bytes(range)?;
+        self.counters.record_read(bytes.len(), start.elapsed());
+        Ok(bytes)
+    }
+
+    fn read_bytes_batch(&self, ranges: &[Range<usize>]) -> io::Result<Vec<OwnedBytes>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "tantivy_read_batch",
+            file = %self.path.display(),
+            num_reads = ranges.len(),
+            num_bytes = ranges.iter().map(|range| range.len()).sum::<usize>()
+        )
+        .entered();
+        let start = Instant::now();
+        let slices = self.underlying.read_bytes_batch(ranges)?;
+        let num_bytes = slices.iter().map(|slice| slice.len()).sum();
+        self.counters.record_read(num_bytes, start.elapsed());
+        Ok(slices)
+    }
+
+    #[cfg(feature = "quickwit")]
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        let start = Instant::now();
+        let bytes = self.underlying.read_bytes_async(range).await?;
+        self.counters.record_read(bytes.len(), start.elapsed());
+        Ok(bytes)
+    }
+}
+
+/// A writer counting the bytes written into a file.
+struct InstrumentedWriter {
+    counters: Arc<IoCounters>,
+    underlying: Box<dyn TerminatingWrite>,
+}
+
+impl Write for InstrumentedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.underlying.write(buf)?;
+        self.counters.record_write(num_bytes);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl TerminatingWrite for InstrumentedWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.underlying.terminate_ref(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::TopDocs;
+    use crate::directory::RamDirectory;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument};
+
+    #[test]
+    fn test_instrumented_directory_file() -> io::Result<()> {
+        let directory = InstrumentedDirectory::wrap(Box::<RamDirectory>::default());
+        let path = Path::new("seg.store");
+        let mut write = directory.open_write(path).unwrap();
+        write.write_all(&[1u8; 100])?;
+        write.terminate()?;
+        let file_handle = directory.get_file_handle(path).unwrap();
+        let store_metrics = directory.metrics().component(SegmentComponent::Store);
+        assert_eq!(store_metrics.bytes_written, 100);
+        assert_eq!(store_metrics.num_open_file_handles, 1);
+        assert_eq!(store_metrics.mean_read_latency(), None);
+
+        file_handle.read_bytes(0..10)?;
+        file_handle.read_bytes_batch(&[10..20, 30..35])?;
+        let store_metrics = directory.metrics().component(SegmentComponent::Store);
+        assert_eq!(store_metrics.num_reads, 2);
+        assert_eq!(store_metrics.bytes_read, 25);
+        assert!(store_metrics.max_read_latency <= store_metrics.read_latency);
+
+        drop(file_handle);
+        let metrics = directory.metrics();
+        assert_eq!(
+            metrics
+                .component(SegmentComponent::Store)
+                .num_open_file_handles,
+            0
+        );
+        assert_eq!(metrics.other(), IoMetrics::default());
+        assert_eq!(metrics.total().bytes_read, 25);
+        Ok(())
+    }
+
+    #[test]
+    fn test_instrumented_directory_index() -> crate::Result<()> {
+        let directory = InstrumentedDirectory::wrap(Box::<RamDirectory>::default());
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "hello"))?;
+        index_writer.commit()?;
+        let metrics = directory.metrics();
+        assert!(metrics.component(SegmentComponent::Store).bytes_written > 0);
+        assert!(metrics.component(SegmentComponent::Postings).bytes_written > 0);
+        // meta.json and .managed.json
+        assert!(metrics.other().bytes_written > 0);
+
+        let searcher = index.reader()?.searcher();
+        let top_docs = searcher.search(&AllQuery, &TopDocs::with_limit(1))?;
+        let _doc: TantivyDocument = searcher.doc(top_docs[0].1)?;
+        let metrics = directory.metrics();
+        assert!(metrics.component(SegmentComponent::Store).num_reads > 0);
+        assert!(
+            metrics
+                .component(SegmentComponent::Store)
+                .num_open_file_handles
+                > 0
+        );
+        assert!(metrics.other().num_reads > 0);
+        Ok(())
+    }
+}
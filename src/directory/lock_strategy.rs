@@ -0,0 +1,724 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fmt, io};
+
+use common::TerminatingWrite;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
+
+use crate::directory::directory::retry_policy;
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileHandle, FileSlice, Lock, WatchCallback, WatchHandle, WritePtr,
+};
+
+/// A strategy to acquire the locks of a directory.
+///
+/// The strategy of a directory is chosen by wrapping it in a [`LockingDirectory`].
+///
+/// The default locking of the directories, e.g. the `flock` based locks of the
+/// [`MmapDirectory`](crate::directory::MmapDirectory), is not reliable on network
+/// filesystems: two writers on two different hosts may both believe they own the
+/// [`INDEX_WRITER_LOCK`](crate::directory::INDEX_WRITER_LOCK), and corrupt the index.
+/// [`LeaseLockStrategy`] and [`CallbackLockStrategy`] are meant for these setups.
+pub trait LockStrategy: Send + Sync + fmt::Debug + 'static {
+    /// Acquires `lock` in `directory`.
+    ///
+    /// The lock is released when the returned [`DirectoryLock`] is dropped.
+    /// Blocking locks should be retried for a while before returning
+    /// [`LockError::LockBusy`].
+    fn acquire_lock(
+        &self,
+        directory: &dyn Directory,
+        lock: &Lock,
+    ) -> Result<DirectoryLock, LockError>;
+}
+
+/// Locks with the locking mechanism of the directory itself.
+///
+/// This is the behavior of directories which are not wrapped in a [`LockingDirectory`]:
+/// OS file locks for the [`MmapDirectory`](crate::directory::MmapDirectory), lock files for
+/// most other directories.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileLockStrategy;
+
+impl LockStrategy for FileLockStrategy {
+    fn acquire_lock(
+        &self,
+        directory: &dyn Directory,
+        lock: &Lock,
+    ) -> Result<DirectoryLock, LockError> {
+        directory.acquire_lock(lock)
+    }
+}
+
+/// The content of a lease file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Lease {
+    owner: String,
+    expires_at_ms: u64,
+}
+
+impl Lease {
+    fn is_expired(&self, now_ms: u64) -> bool {
+        self.expires_at_ms <= now_ms
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Returns the current time, in milliseconds since the epoch.
+type Clock = dyn Fn() -> u64 + Send + Sync;
+
+/// Locks with lease files, which stay safe on network filesystems such as NFS.
+///
+/// A lease file is created exclusively, and records its owner and the time at which the lease
+/// expires. While the lock is held, a heartbeat thread renews the lease every third of the
+/// lease duration. The lease file is deleted when the lock is dropped.
+///
+/// If the lease turns out to be owned by another process, or could not be renewed before
+/// expiring, the lock is flagged as lost: the [`IndexWriter`](crate::IndexWriter) holding it
+/// refuses to commit and fails with [`LockError::LockLost`].
+///
+/// Unlike the lock files of a crashed process, which have to be removed manually, an expired
+/// lease is taken over by the next process acquiring the lock. After taking over a lease, the
+/// lock is only granted if the lease still belongs to the process after the verification
+/// delay, so that two processes racing for the same expired lease cannot both win.
+///
+/// The clocks of the hosts sharing the directory are expected to be synchronized to well
+/// within the lease duration.
+#[derive(Clone)]
+pub struct LeaseLockStrategy {
+    lease_duration: Duration,
+    verification_delay: Duration,
+    clock: Arc<Clock>,
+}
+
+impl fmt::Debug for LeaseLockStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeaseLockStrategy")
+            .field("lease_duration", &self.lease_duration)
+            .field("verification_delay", &self.verification_delay)
+            .finish()
+    }
+}
+
+impl Default for LeaseLockStrategy {
+    fn default() -> LeaseLockStrategy {
+        LeaseLockStrategy::new(Duration::from_secs(30))
+    }
+}
+
+impl LeaseLockStrategy {
+    /// Creates a `LeaseLockStrategy` granting leases of `lease_duration`.
+    ///
+    /// A lock held by a process which crashed is available again after at most
+    /// `lease_duration`.
+    pub fn new(lease_duration: Duration) -> LeaseLockStrategy {
+        LeaseLockStrategy {
+            lease_duration,
+            verification_delay: Duration::from_millis(50).min(lease_duration / 10),
+            clock: Arc::new(now_ms),
+        }
+    }
+
+    /// Replaces the system clock used to compute and check the expiry of the leases.
+    #[cfg(test)]
+    fn with_clock(mut self, clock: impl Fn() -> u64 + Send + Sync + 'static) -> LeaseLockStrategy {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Sets the delay after which a newly acquired lease is checked to still be owned.
+    ///
+    /// It should exceed the time it takes for a write to become visible to the other hosts
+    /// sharing the directory. Defaults to 50ms.
+    pub fn with_verification_delay(mut self, verification_delay: Duration) -> LeaseLockStrategy {
+        self.verification_delay = verification_delay;
+        self
+    }
+
+    fn new_lease(&self, owner: &str) -> Lease {
+        Lease {
+            owner: owner.to_string(),
+            expires_at_ms: (self.clock)() + self.lease_duration.as_millis() as u64,
+        }
+    }
+
+    /// Creates the lease file, failing if it already exists.
+    ///
+    /// Returns `Ok(false)` if the lease file already exists.
+    fn create_lease(
+        &self,
+        directory: &dyn Directory,
+        path: &Path,
+        lease: &Lease,
+    ) -> Result<bool, LockError> {
+        let mut write = match directory.open_write(path) {
+            Ok(write) => write,
+            Err(OpenWriteError::FileAlreadyExists(_)) => return Ok(false),
+            Err(OpenWriteError::IoError { io_error, .. }) => {
+                return Err(LockError::IoError(io_error));
+            }
+        };
+        let lease_json = serde_json::to_vec(lease)
+            .map_err(|serde_error| LockError::wrap_io_error(serde_error.into()))?;
+        write
+            .write_all(&lease_json)
+            .map_err(LockError::wrap_io_error)?;
+        write.terminate().map_err(LockError::wrap_io_error)?;
+        Ok(true)
+    }
+
+    /// Removes the lease file if it expired, or if its content is still unreadable after the
+    /// verification delay, e.g. because its owner crashed before writing it.
+    fn remove_expired_lease(
+        &self,
+        directory: &dyn Directory,
+        path: &Path,
+    ) -> Result<(), LockError> {
+        let lease = match read_lease(directory, path)? {
+            LeaseState::Missing => return Ok(()),
+            LeaseState::Held(lease) => lease,
+            LeaseState::Unreadable => {
+                thread::sleep(self.verification_delay);
+                if !matches!(read_lease(directory, path)?, LeaseState::Unreadable) {
+                    return Ok(());
+                }
+                return delete_lease(directory, path);
+            }
+        };
+        if !lease.is_expired((self.clock)()) {
+            return Ok(());
+        }
+        // The lease is deleted only if it did not get renewed in the meantime.
+        if let LeaseState::Held(current_lease) = read_lease(directory, path)? {
+            if current_lease == lease {
+                delete_lease(directory, path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn try_acquire_lock(
+        &self,
+        directory: &dyn Directory,
+        path: &Path,
+    ) -> Result<Option<DirectoryLock>, LockError> {
+        let owner = uuid::Uuid::new_v4().to_string();
+        let lease = self.new_lease(&owner);
+        if !self.create_lease(directory, path, &lease)? {
+            self.remove_expired_lease(directory, path)?;
+            if !self.create_lease(directory, path, &lease)? {
+                return Ok(None);
+            }
+        }
+        // Another process taking over the same expired lease may have deleted our lease and
+        // created its own.
+        thread::sleep(self.verification_delay);
+        if !is_owned_by(directory, path, &owner)? {
+            return Ok(None);
+        }
+        let (stop_sender, stop_receiver) = crossbeam_channel::bounded(1);
+        let lost = Arc::new(AtomicBool::new(false));
+        let heartbeat_directory = directory.box_clone();
+        let heartbeat_path = path.to_path_buf();
+        let heartbeat_owner = owner.clone();
+        let heartbeat_lost = lost.clone();
+        let expires_at_ms = lease.expires_at_ms;
+        let strategy = self.clone();
+        let heartbeat = thread::Builder::new()
+            .name("tantivy-lease-heartbeat".to_string())
+            .spawn(move || {
+                strategy.heartbeat(
+                    &*heartbeat_directory,
+                    &heartbeat_path,
+                    &heartbeat_owner,
+                    expires_at_ms,
+                    stop_receiver,
+                    &heartbeat_lost,
+                )
+            })
+            .map_err(LockError::wrap_io_error)?;
+        let guard = LeaseGuard {
+            directory: directory.box_clone(),
+            path: path.to_path_buf(),
+            owner,
+            stop_sender,
+            heartbeat: Some(heartbeat),
+        };
+        Ok(Some(DirectoryLock::with_lost_flag(Box::new(guard), lost)))
+    }
+
+    /// Renews the lease until the lock gets released.
+    ///
+    /// If the lease is lost in the meantime, `lost` is set and the lease is not renewed anymore.
+    fn heartbeat(
+        &self,
+        directory: &dyn Directory,
+        path: &Path,
+        owner: &str,
+        mut expires_at_ms: u64,
+        stop_receiver: Receiver<()>,
+        lost: &AtomicBool,
+    ) {
+        let renew_interval = self.lease_duration / 3;
+        while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(renew_interval) {
+            if expires_at_ms <= (self.clock)() {
+                error!("The lease of the lock {path:?} expired before it could be renewed.");
+                lost.store(true, Ordering::Release);
+                return;
+            }
+            match is_owned_by(directory, path, owner) {
+                Ok(true) => {}
+                Ok(false) => {
+                    error!("Lost the lease of the lock {path:?}, it is not renewed anymore.");
+                    lost.store(true, Ordering::Release);
+                    return;
+                }
+                Err(lock_error) => {
+                    // The lease is still ours until it expires: renewing it blindly is safe.
+                    warn!("Failed to read the lease of the lock {path:?}: {lock_error:?}");
+                }
+            }
+            let lease = self.new_lease(owner);
+            let lease_json = match serde_json::to_vec(&lease) {
+                Ok(lease_json) => lease_json,
+                Err(serde_error) => {
+                    error!("Failed to serialize the lease of the lock {path:?}: {serde_error:?}");
+                    lost.store(true, Ordering::Release);
+                    return;
+                }
+            };
+            match directory.atomic_write(path, &lease_json) {
+                Ok(()) => expires_at_ms = lease.expires_at_ms,
+                Err(io_error) => {
+                    warn!("Failed to renew the lease of the lock {path:?}: {io_error:?}")
+                }
+            }
+        }
+    }
+}
+
+impl LockStrategy for LeaseLockStrategy {
+    fn acquire_lock(
+        &self,
+        directory: &dyn Directory,
+        lock: &Lock,
+    ) -> Result<DirectoryLock, LockError> {
+        let mut retry_policy = retry_policy(lock.is_blocking);
+        loop {
+            if let Some(directory_lock) = self.try_acquire_lock(directory, &lock.filepath)? {
+                return Ok(directory_lock);
+            }
+            if !retry_policy.wait_and_retry() {
+                return Err(LockError::LockBusy);
+            }
+        }
+    }
+}
+
+enum LeaseState {
+    Missing,
+    Unreadable,
+    Held(Lease),
+}
+
+fn read_lease(directory: &dyn Directory, path: &Path) -> Result<LeaseState, LockError> {
+    match directory.atomic_read(path) {
+        Ok(lease_json) => Ok(serde_json::from_slice(&lease_json)
+            .map(LeaseState::Held)
+            .unwrap_or(LeaseState::Unreadable)),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(LeaseState::Missing),
+        Err(open_read_error) => Err(LockError::wrap_io_error(io::Error::other(open_read_error))),
+    }
+}
+
+fn is_owned_by(directory: &dyn Directory, path: &Path, owner: &str) -> Result<bool, LockError> {
+    Ok(matches!(read_lease(directory, path)?, LeaseState::Held(lease) if lease.owner == owner))
+}
+
+fn delete_lease(directory: &dyn Directory, path: &Path) -> Result<(), LockError> {
+    match directory.delete(path) {
+        Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => Ok(()),
+        Err(DeleteError::IoError { io_error, .. }) => Err(LockError::IoError(io_error)),
+    }
+}
+
+struct LeaseGuard {
+    directory: Box<dyn Directory>,
+    path: PathBuf,
+    owner: String,
+    stop_sender: Sender<()>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl Drop for LeaseGuard {
+    fn drop(&mut self) {
+        let _ = self.stop_sender.send(());
+        if let Some(heartbeat) = self.heartbeat.take() {
+            let _ = heartbeat.join();
+        }
+        match is_owned_by(&*self.directory, &self.path, &self.owner) {
+            Ok(true) => {
+                if let Err(lock_error) = delete_lease(&*self.directory, &self.path) {
+                    error!("Failed to remove the lease file. {lock_error:?}");
+                }
+            }
+            Ok(false) => {}
+            Err(lock_error) => error!("Failed to read the lease file. {lock_error:?}"),
+        }
+    }
+}
+
+type LockCallback = dyn Fn(&Lock) -> Result<DirectoryLock, LockError> + Send + Sync;
+
+/// Delegates the locks to an external coordinator, e.g. ZooKeeper, etcd or a database.
+///
+/// The callback acquires the lock from the coordinator and returns a [`DirectoryLock`]
+/// releasing it on drop, which can be built from any boxed guard with
+/// `DirectoryLock::from(Box::new(guard))`. It is responsible for waiting for blocking locks
+/// and for returning [`LockError::LockBusy`] for busy non-blocking locks.
+#[derive(Clone)]
+pub struct CallbackLockStrategy {
+    callback: Arc<LockCallback>,
+}
+
+impl fmt::Debug for CallbackLockStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CallbackLockStrategy")
+    }
+}
+
+impl CallbackLockStrategy {
+    /// Creates a `CallbackLockStrategy` acquiring the locks with `callback`.
+    pub fn new<F>(callback: F) -> CallbackLockStrategy
+    where
+        F: Fn(&Lock) -> Result<DirectoryLock, LockError> + Send + Sync + 'static,
+    {
+        CallbackLockStrategy {
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+impl LockStrategy for CallbackLockStrategy {
+    fn acquire_lock(
+        &self,
+        _directory: &dyn Directory,
+        lock: &Lock,
+    ) -> Result<DirectoryLock, LockError> {
+        (self.callback)(lock)
+    }
+}
+
+/// A [`Directory`] acquiring its locks with a [`LockStrategy`].
+///
+/// All of the other operations are delegated to the wrapped directory.
+#[derive(Clone)]
+pub struct LockingDirectory {
+    directory: Box<dyn Directory>,
+    lock_strategy: Arc<dyn LockStrategy>,
+}
+
+impl fmt::Debug for LockingDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "LockingDirectory({:?}, {:?})",
+            self.directory, self.lock_strategy
+        )
+    }
+}
+
+impl LockingDirectory {
+    /// Wraps a directory, so that its locks are acquired with `lock_strategy`.
+    pub fn wrap<S: LockStrategy>(
+        directory: Box<dyn Directory>,
+        lock_strategy: S,
+    ) -> LockingDirectory {
+        LockingDirectory {
+            directory,
+            lock_strategy: Arc::new(lock_strategy),
+        }
+    }
+}
+
+impl Directory for LockingDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        self.directory.get_file_handle(path)
+    }
+
+    fn open_read(&self, path: &Path) -> Result<FileSlice, OpenReadError> {
+        self.directory.open_read(path)
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.directory.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.directory.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        self.directory.open_write(path)
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.directory.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.directory.atomic_write(path, data)
+    }
+
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        self.directory.local_path(path)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.directory.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.lock_strategy.acquire_lock(&*self.directory, lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.directory.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    use super::*;
+    use crate::directory::{RamDirectory, INDEX_WRITER_LOCK};
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument, TantivyError};
+
+    /// A clock which only moves when told to, shared by all of the hosts of a test.
+    #[derive(Clone)]
+    struct ManualClock(Arc<AtomicU64>);
+
+    impl ManualClock {
+        fn new() -> ManualClock {
+            ManualClock(Arc::new(AtomicU64::new(1_000_000)))
+        }
+
+        fn now_ms(&self) -> u64 {
+            self.0.load(Ordering::SeqCst)
+        }
+
+        fn advance(&self, duration_ms: u64) {
+            self.0.fetch_add(duration_ms, Ordering::SeqCst);
+        }
+    }
+
+    fn lease_directory(
+        ram_directory: &RamDirectory,
+        lease_duration_ms: u64,
+        clock: &ManualClock,
+    ) -> LockingDirectory {
+        let clock = clock.clone();
+        LockingDirectory::wrap(
+            Box::new(ram_directory.clone()),
+            LeaseLockStrategy::new(Duration::from_millis(lease_duration_ms))
+                .with_verification_delay(Duration::from_millis(10))
+                .with_clock(move || clock.now_ms()),
+        )
+    }
+
+    fn lease_expiry(ram_directory: &RamDirectory) -> u64 {
+        match read_lease(ram_directory, &INDEX_WRITER_LOCK.filepath).unwrap() {
+            LeaseState::Held(lease) => lease.expires_at_ms,
+            _ => panic!("Expected a lease"),
+        }
+    }
+
+    /// Polls `condition` until it holds, giving up after a few seconds.
+    fn wait_until(mut condition: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "Timed out");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_lease_lock_exclusive_across_hosts() {
+        // Two hosts sharing the same directory.
+        let ram_directory = RamDirectory::create();
+        let clock = ManualClock::new();
+        let host_a = lease_directory(&ram_directory, 300, &clock);
+        let host_b = lease_directory(&ram_directory, 300, &clock);
+        let lock_a = host_a.acquire_lock(&INDEX_WRITER_LOCK).unwrap();
+        let initial_expiry = lease_expiry(&ram_directory);
+        assert_eq!(initial_expiry, clock.now_ms() + 300);
+        // The heartbeat keeps the lease alive past its initial duration.
+        clock.advance(200);
+        wait_until(|| lease_expiry(&ram_directory) == initial_expiry + 200);
+        clock.advance(200);
+        assert!(matches!(
+            host_b.acquire_lock(&INDEX_WRITER_LOCK),
+            Err(LockError::LockBusy)
+        ));
+        assert!(!lock_a.is_lost());
+        drop(lock_a);
+        assert!(!ram_directory.exists(&INDEX_WRITER_LOCK.filepath).unwrap());
+        assert!(host_b.acquire_lock(&INDEX_WRITER_LOCK).is_ok());
+    }
+
+    #[test]
+    fn test_lease_lock_takes_over_expired_lease() {
+        let ram_directory = RamDirectory::create();
+        let clock = ManualClock::new();
+        let crashed_lease = Lease {
+            owner: "crashed".to_string(),
+            expires_at_ms: clock.now_ms() - 1_000,
+        };
+        ram_directory
+            .atomic_write(
+                &INDEX_WRITER_LOCK.filepath,
+                &serde_json::to_vec(&crashed_lease).unwrap(),
+            )
+            .unwrap();
+        let directory = lease_directory(&ram_directory, 10_000, &clock);
+        let _lock = directory.acquire_lock(&INDEX_WRITER_LOCK).unwrap();
+        assert!(matches!(
+            read_lease(&ram_directory, &INDEX_WRITER_LOCK.filepath).unwrap(),
+            LeaseState::Held(lease) if lease.owner != "crashed"
+        ));
+        // An unexpired lease is not taken over.
+        assert!(directory.acquire_lock(&INDEX_WRITER_LOCK).is_err());
+    }
+
+    #[test]
+    fn test_lease_lock_lost_on_expiry() {
+        let ram_directory = RamDirectory::create();
+        let clock = ManualClock::new();
+        let directory = lease_directory(&ram_directory, 300, &clock);
+        let lock = directory.acquire_lock(&INDEX_WRITER_LOCK).unwrap();
+        // The heartbeat did not get a chance to renew the lease before it expired.
+        clock.advance(1_000);
+        wait_until(|| lock.is_lost());
+    }
+
+    #[test]
+    fn test_lease_lock_lost_stops_commits() -> crate::Result<()> {
+        let ram_directory = RamDirectory::create();
+        let clock = ManualClock::new();
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create(
+            lease_directory(&ram_directory, 300, &clock),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "hello"))?;
+        index_writer.commit()?;
+        // Another host takes over the lease, as if this host had been paused for too long.
+        let other_host_lease = Lease {
+            owner: "other_host".to_string(),
+            expires_at_ms: clock.now_ms() + 10_000,
+        };
+        ram_directory.atomic_write(
+            &INDEX_WRITER_LOCK.filepath,
+            &serde_json::to_vec(&other_host_lease).unwrap(),
+        )?;
+        wait_until(|| index_writer.prepare_commit().is_err());
+        index_writer.add_document(doc!(text_field => "world"))?;
+        assert!(matches!(
+            index_writer.commit(),
+            Err(TantivyError::LockFailure(LockError::LockLost, _))
+        ));
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        // The lease of the other host is left alone.
+        drop(index_writer);
+        assert!(matches!(
+            read_lease(&ram_directory, &INDEX_WRITER_LOCK.filepath).unwrap(),
+            LeaseState::Held(lease) if lease.owner == "other_host"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lease_lock_index_writer() -> crate::Result<()> {
+        let ram_directory = RamDirectory::create();
+        let clock = ManualClock::new();
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create(
+            lease_directory(&ram_directory, 1_000, &clock),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "hello"))?;
+        index_writer.commit()?;
+        let other_host_index = Index::open(lease_directory(&ram_directory, 1_000, &clock))?;
+        assert!(other_host_index
+            .writer_for_tests::<TantivyDocument>()
+            .is_err());
+        assert_eq!(other_host_index.reader()?.searcher().num_docs(), 1);
+        drop(index_writer);
+        assert!(other_host_index
+            .writer_for_tests::<TantivyDocument>()
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_callback_lock_strategy() {
+        struct CoordinatorLock {
+            held_locks: Arc<Mutex<HashSet<PathBuf>>>,
+            filepath: PathBuf,
+        }
+        impl Drop for CoordinatorLock {
+            fn drop(&mut self) {
+                self.held_locks.lock().unwrap().remove(&self.filepath);
+            }
+        }
+        let held_locks: Arc<Mutex<HashSet<PathBuf>>> = Arc::default();
+        let held_locks_clone = held_locks.clone();
+        let lock_strategy = CallbackLockStrategy::new(move |lock: &Lock| {
+            if !held_locks_clone
+                .lock()
+                .unwrap()
+                .insert(lock.filepath.clone())
+            {
+                return Err(LockError::LockBusy);
+            }
+            Ok(DirectoryLock::from(Box::new(CoordinatorLock {
+                held_locks: held_locks_clone.clone(),
+                filepath: lock.filepath.clone(),
+            })))
+        });
+        let directory = LockingDirectory::wrap(Box::new(RamDirectory::create()), lock_strategy);
+        let lock = directory.acquire_lock(&INDEX_WRITER_LOCK).unwrap();
+        assert!(held_locks
+            .lock()
+            .unwrap()
+            .contains(&INDEX_WRITER_LOCK.filepath));
+        // No lock file is created in the directory.
+        assert!(!directory.exists(&INDEX_WRITER_LOCK.filepath).unwrap());
+        assert!(directory.acquire_lock(&INDEX_WRITER_LOCK).is_err());
+        drop(lock);
+        assert!(directory.acquire_lock(&INDEX_WRITER_LOCK).is_ok());
+    }
+}
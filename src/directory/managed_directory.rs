@@ -53,7 +53,10 @@ fn save_managed_paths(
     directory: &dyn Directory,
     wlock: &RwLockWriteGuard<'_, MetaInformation>,
 ) -> io::Result<()> {
-    let mut w = serde_json::to_vec(&wlock.managed_paths)?;
+    // The paths are sorted so that the file only depends on the set of managed paths.
+    let mut managed_paths: Vec<&PathBuf> = wlock.managed_paths.iter().collect();
+    managed_paths.sort();
+    let mut w = serde_json::to_vec(&managed_paths)?;
     writeln!(&mut w)?;
     directory.atomic_write(&MANAGED_FILEPATH, &w[..])?;
     Ok(())
@@ -210,7 +213,7 @@ impl ManagedDirectory {
     /// File starting by "." are reserved to locks.
     /// They are not managed and cannot be subjected
     /// to garbage collection.
-    fn register_file_as_managed(&self, filepath: &Path) -> io::Result<()> {
+    pub(crate) fn register_file_as_managed(&self, filepath: &Path) -> io::Result<()> {
         // Files starting by "." (e.g. lock files) are not managed.
         if !is_managed(filepath) {
             return Ok(());
@@ -256,6 +259,11 @@ impl ManagedDirectory {
         Ok(footer.crc() == crc)
     }
 
+    /// Returns the wrapped directory, which serves files with their footer.
+    pub(crate) fn underlying_directory(&self) -> &dyn Directory {
+        self.directory.as_ref()
+    }
+
     /// List all managed files
     pub fn list_managed_files(&self) -> HashSet<PathBuf> {
         let managed_paths = self
@@ -319,6 +327,10 @@ impl Directory for ManagedDirectory {
         self.directory.watch(watch_callback)
     }
 
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        self.directory.local_path(path)
+    }
+
     fn sync_directory(&self) -> io::Result<()> {
         self.directory.sync_directory()?;
         Ok(())
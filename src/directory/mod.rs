@@ -1,14 +1,29 @@
 //! WORM (Write Once Read Many) directory abstraction.
 
+#[cfg(feature = "quickwit")]
+mod async_directory;
+mod bundle_directory;
 #[cfg(feature = "mmap")]
 mod mmap_directory;
 
 mod directory;
 mod directory_lock;
+#[cfg(feature = "encryption")]
+mod encrypted_directory;
 mod file_watcher;
+#[cfg(feature = "quickwit")]
+mod http_directory;
+mod instrumented_directory;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod io_uring_directory;
+mod lock_strategy;
 pub mod footer;
 mod managed_directory;
+#[cfg(feature = "object-store")]
+mod object_store_directory;
+mod quota_directory;
 mod ram_directory;
+mod read_only_directory;
 mod watch_event_router;
 
 /// Errors specific to the directory module.
@@ -22,10 +37,28 @@ use std::path::PathBuf;
 pub use common::file_slice::{FileHandle, FileSlice};
 pub use common::{AntiCallToken, OwnedBytes, TerminatingWrite};
 
+#[cfg(feature = "quickwit")]
+pub(crate) use self::async_directory::open_index_async;
+#[cfg(feature = "quickwit")]
+pub use self::async_directory::{AsyncDirectory, AsyncFileHandle};
+pub use self::bundle_directory::BundleDirectory;
+pub(crate) use self::bundle_directory::write_bundle;
 pub(crate) use self::composite_file::{CompositeFile, CompositeWrite};
 pub use self::directory::{Directory, DirectoryClone, DirectoryLock};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
+#[cfg(feature = "quickwit")]
+pub use self::http_directory::{HttpClient, HttpDirectory, HttpDirectoryOptions};
+#[cfg(feature = "encryption")]
+pub use self::encrypted_directory::EncryptedDirectory;
+pub use self::quota_directory::{DirectoryUsage, QuotaDirectory};
+pub use self::instrumented_directory::{DirectoryIoMetrics, InstrumentedDirectory, IoMetrics};
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub use self::io_uring_directory::IoUringDirectory;
+pub use self::lock_strategy::{
+    CallbackLockStrategy, FileLockStrategy, LeaseLockStrategy, LockStrategy, LockingDirectory,
+};
 pub use self::ram_directory::RamDirectory;
+pub use self::read_only_directory::ReadOnlyDirectory;
 pub use self::watch_event_router::{WatchCallback, WatchCallbackList, WatchHandle};
 
 /// Outcome of the Garbage collection
@@ -48,6 +81,10 @@ pub use memmap2::Advice;
 pub use self::managed_directory::ManagedDirectory;
 #[cfg(feature = "mmap")]
 pub use self::mmap_directory::MmapDirectory;
+#[cfg(feature = "object-store")]
+pub use self::object_store_directory::{
+    ObjectStoreDirectory, ObjectStoreDirectoryMode, ObjectStoreDirectoryOptions,
+};
 
 /// Write object for Directory.
 ///
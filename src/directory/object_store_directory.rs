@@ -0,0 +1,567 @@
+use std::io::{self, BufWriter, Write};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fmt, fs};
+
+use async_trait::async_trait;
+use common::HasLen;
+use lru::LruCache;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutMode, PutPayload};
+use tokio::runtime::Handle;
+
+use crate::core::META_FILEPATH;
+use crate::directory::directory::acquire_lock_file;
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, Lock, OwnedBytes, TerminatingWrite,
+    WatchCallback, WatchCallbackList, WatchHandle, WritePtr, INDEX_WRITER_LOCK,
+};
+#[cfg(feature = "quickwit")]
+use crate::directory::{AsyncDirectory, AsyncFileHandle};
+
+const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+const DEFAULT_MEMORY_CACHE_SIZE: usize = 256 << 20;
+
+/// Whether an [`ObjectStoreDirectory`] accepts writes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ObjectStoreDirectoryMode {
+    /// All writes fail. This is the mode to serve an index built elsewhere.
+    #[default]
+    ReadOnly,
+    /// Files are written to the object store, and their blocks are put in the block cache.
+    WriteThrough,
+}
+
+#[derive(Clone, Debug, bon::Builder)]
+/// Options of an [`ObjectStoreDirectory`].
+pub struct ObjectStoreDirectoryOptions {
+    #[builder(default)]
+    /// Whether the directory accepts writes.
+    mode: ObjectStoreDirectoryMode,
+    #[builder(default = DEFAULT_BLOCK_SIZE)]
+    /// The size of the blocks the files are read and cached by.
+    block_size: usize,
+    #[builder(default = DEFAULT_MEMORY_CACHE_SIZE)]
+    /// The overall size of the blocks kept in memory. 0 disables the memory cache.
+    memory_cache_size: usize,
+    /// A local directory where the blocks are cached, in addition to the memory cache.
+    ///
+    /// The disk cache is not bounded: index files never change once written, so its size is
+    /// at most the size of the index. It must not be shared by directories with different
+    /// object stores or prefixes.
+    disk_cache_dir: Option<PathBuf>,
+}
+
+impl Default for ObjectStoreDirectoryOptions {
+    fn default() -> Self {
+        ObjectStoreDirectoryOptions::builder().build()
+    }
+}
+
+/// Caches the blocks of the files of an [`ObjectStoreDirectory`], in memory and optionally
+/// on the local disk.
+struct BlockCache {
+    block_size: usize,
+    memory: Option<Mutex<LruCache<(PathBuf, usize), OwnedBytes>>>,
+    disk_dir: Option<PathBuf>,
+}
+
+impl BlockCache {
+    fn disk_path(&self, path: &Path, block_ord: usize) -> Option<PathBuf> {
+        let file_name = path.to_string_lossy().replace(['/', '\\'], "_");
+        let disk_dir = self.disk_dir.as_ref()?;
+        Some(disk_dir.join(format!("{file_name}.{block_ord}")))
+    }
+
+    fn get(&self, path: &Path, block_ord: usize) -> Option<OwnedBytes> {
+        let key = (path.to_path_buf(), block_ord);
+        if let Some(memory) = &self.memory {
+            if let Some(block) = memory.lock().unwrap().get(&key) {
+                return Some(block.clone());
+            }
+        }
+        let block = OwnedBytes::new(fs::read(self.disk_path(path, block_ord)?).ok()?);
+        if let Some(memory) = &self.memory {
+            memory.lock().unwrap().put(key, block.clone());
+        }
+        Some(block)
+    }
+
+    fn put(&self, path: &Path, block_ord: usize, block: OwnedBytes) {
+        if let Some(disk_path) = self.disk_path(path, block_ord) {
+            // The disk cache is best effort: a block that fails to be written is simply
+            // fetched again from the object store.
+            if let Err(io_error) = fs::write(&disk_path, block.as_slice()) {
+                warn!("Failed to write block to the disk cache {disk_path:?}: {io_error:?}");
+            }
+        }
+        if let Some(memory) = &self.memory {
+            memory
+                .lock()
+                .unwrap()
+                .put((path.to_path_buf(), block_ord), block);
+        }
+    }
+}
+
+struct InnerDirectory {
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+    runtime: Handle,
+    mode: ObjectStoreDirectoryMode,
+    block_cache: BlockCache,
+    watch_router: WatchCallbackList,
+}
+
+impl InnerDirectory {
+    fn object_path(&self, path: &Path) -> ObjectPath {
+        path.iter().fold(self.prefix.clone(), |object_path, part| {
+            object_path.child(part.to_string_lossy().as_ref())
+        })
+    }
+
+    fn check_writable(&self) -> io::Result<()> {
+        if self.mode == ObjectStoreDirectoryMode::ReadOnly {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the object store directory is read-only",
+            ));
+        }
+        Ok(())
+    }
+
+    fn put(&self, path: &Path, data: Vec<u8>) -> io::Result<()> {
+        let object_path = self.object_path(path);
+        self.runtime
+            .block_on(self.store.put(&object_path, PutPayload::from(data)))?;
+        Ok(())
+    }
+}
+
+/// A Directory backed by an [`ObjectStore`], such as S3, GCS or Azure Blob Storage,
+/// so that an index can be served directly from cloud storage.
+///
+/// Files are read by blocks of a fixed size, with range requests. Blocks are cached in
+/// memory and, optionally, on the local disk. The calls to the object store are run on a
+/// tokio runtime, given by its [`Handle`]: the methods of the directory must not be called
+/// from within this runtime.
+///
+/// Object stores do not notify changes: the `watch` callbacks are only called on commits
+/// made through this directory. Readers of an index written elsewhere should reload
+/// manually.
+#[derive(Clone)]
+pub struct ObjectStoreDirectory {
+    inner: Arc<InnerDirectory>,
+}
+
+impl fmt::Debug for ObjectStoreDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ObjectStoreDirectory({}, {})",
+            self.inner.store, self.inner.prefix
+        )
+    }
+}
+
+impl ObjectStoreDirectory {
+    /// Opens a directory made of the objects of `store` under `prefix`.
+    pub fn open(
+        store: Arc<dyn ObjectStore>,
+        prefix: ObjectPath,
+        runtime: Handle,
+        options: ObjectStoreDirectoryOptions,
+    ) -> io::Result<ObjectStoreDirectory> {
+        if options.block_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the block size must be positive",
+            ));
+        }
+        if let Some(disk_cache_dir) = &options.disk_cache_dir {
+            fs::create_dir_all(disk_cache_dir)?;
+        }
+        let block_cache = BlockCache {
+            block_size: options.block_size,
+            memory: NonZeroUsize::new(options.memory_cache_size / options.block_size)
+                .map(|num_blocks| Mutex::new(LruCache::new(num_blocks))),
+            disk_dir: options.disk_cache_dir,
+        };
+        Ok(ObjectStoreDirectory {
+            inner: Arc::new(InnerDirectory {
+                store,
+                prefix,
+                runtime,
+                mode: options.mode,
+                block_cache,
+                watch_router: WatchCallbackList::default(),
+            }),
+        })
+    }
+}
+
+/// A file of an [`ObjectStoreDirectory`].
+struct ObjectStoreFile {
+    directory: Arc<InnerDirectory>,
+    path: PathBuf,
+    object_path: ObjectPath,
+    len: usize,
+}
+
+impl fmt::Debug for ObjectStoreFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ObjectStoreFile({:?}, len={})", self.path, self.len)
+    }
+}
+
+impl HasLen for ObjectStoreFile {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl ObjectStoreFile {
+    fn block_range(&self, block_ord: usize) -> Range<usize> {
+        let block_size = self.directory.block_cache.block_size;
+        block_ord * block_size..((block_ord + 1) * block_size).min(self.len)
+    }
+
+    /// Reads the blocks covering `range`, fetching the ones that are not cached in a single
+    /// call to the object store.
+    async fn read_blocks(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let block_cache = &self.directory.block_cache;
+        let block_ords =
+            range.start / block_cache.block_size..(range.end - 1) / block_cache.block_size + 1;
+        let mut blocks: Vec<Option<OwnedBytes>> = block_ords
+            .clone()
+            .map(|block_ord| block_cache.get(&self.path, block_ord))
+            .collect();
+        let missing_block_ords: Vec<usize> = block_ords
+            .clone()
+            .filter(|block_ord| blocks[block_ord - block_ords.start].is_none())
+            .collect();
+        if !missing_block_ords.is_empty() {
+            let byte_ranges: Vec<Range<usize>> = missing_block_ords
+                .iter()
+                .map(|&block_ord| self.block_range(block_ord))
+                .collect();
+            let fetched_blocks = self
+                .directory
+                .store
+                .get_ranges(&self.object_path, &byte_ranges)
+                .await?;
+            for (block_ord, bytes) in missing_block_ords.into_iter().zip(fetched_blocks) {
+                let block = OwnedBytes::new(bytes.to_vec());
+                block_cache.put(&self.path, block_ord, block.clone());
+                blocks[block_ord - block_ords.start] = Some(block);
+            }
+        }
+        let blocks_start = block_ords.start * block_cache.block_size;
+        let mut data = Vec::with_capacity(range.len());
+        for block in blocks.into_iter().flatten() {
+            data.extend_from_slice(block.as_slice());
+        }
+        Ok(OwnedBytes::new(data).slice(range.start - blocks_start..range.end - blocks_start))
+    }
+}
+
+#[async_trait]
+impl FileHandle for ObjectStoreFile {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        self.directory.runtime.block_on(self.read_blocks(range))
+    }
+
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        self.read_blocks(range).await
+    }
+}
+
+/// Writer associated with the [`ObjectStoreDirectory`].
+///
+/// The file is buffered in memory, and sent to the object store when flushed.
+struct ObjectStoreWriter {
+    path: PathBuf,
+    directory: Arc<InnerDirectory>,
+    data: Vec<u8>,
+    is_flushed: bool,
+}
+
+impl Drop for ObjectStoreWriter {
+    fn drop(&mut self) {
+        if !self.is_flushed {
+            warn!(
+                "You forgot to flush {:?} before its writer got Drop. Do not rely on drop. This \
+                 also occurs when the indexer crashed, so you may want to check the logs for the \
+                 root cause.",
+                self.path
+            )
+        }
+    }
+}
+
+impl Write for ObjectStoreWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.is_flushed = false;
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.is_flushed {
+            return Ok(());
+        }
+        self.directory.put(&self.path, self.data.clone())?;
+        let block_cache = &self.directory.block_cache;
+        for (block_ord, block) in self.data.chunks(block_cache.block_size).enumerate() {
+            block_cache.put(&self.path, block_ord, OwnedBytes::new(block.to_vec()));
+        }
+        self.is_flushed = true;
+        Ok(())
+    }
+}
+
+impl TerminatingWrite for ObjectStoreWriter {
+    fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+fn to_open_read_error(error: object_store::Error, path: &Path) -> OpenReadError {
+    match error {
+        object_store::Error::NotFound { .. } => OpenReadError::FileDoesNotExist(path.to_path_buf()),
+        error => OpenReadError::wrap_io_error(error.into(), path.to_path_buf()),
+    }
+}
+
+impl ObjectStoreDirectory {
+    async fn open_file(&self, path: &Path) -> Result<ObjectStoreFile, OpenReadError> {
+        let object_path = self.inner.object_path(path);
+        let object_meta = self
+            .inner
+            .store
+            .head(&object_path)
+            .await
+            .map_err(|error| to_open_read_error(error, path))?;
+        Ok(ObjectStoreFile {
+            directory: self.inner.clone(),
+            path: path.to_path_buf(),
+            object_path,
+            len: object_meta.size,
+        })
+    }
+
+    async fn read_atomic_file(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let object_path = self.inner.object_path(path);
+        // Files written with `atomic_write` may change: they are never cached.
+        let get_result = self
+            .inner
+            .store
+            .get(&object_path)
+            .await
+            .map_err(|error| to_open_read_error(error, path))?;
+        let bytes = get_result
+            .bytes()
+            .await
+            .map_err(|error| to_open_read_error(error, path))?;
+        Ok(bytes.to_vec())
+    }
+}
+
+#[cfg(feature = "quickwit")]
+#[async_trait]
+impl AsyncFileHandle for ObjectStoreFile {
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        self.read_blocks(range).await
+    }
+}
+
+#[cfg(feature = "quickwit")]
+#[async_trait]
+impl AsyncDirectory for ObjectStoreDirectory {
+    async fn get_file_handle_async(
+        &self,
+        path: &Path,
+    ) -> Result<Arc<dyn AsyncFileHandle>, OpenReadError> {
+        let file = self.open_file(path).await?;
+        Ok(Arc::new(file))
+    }
+
+    async fn atomic_read_async(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.read_atomic_file(path).await
+    }
+}
+
+impl Directory for ObjectStoreDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let file = self.inner.runtime.block_on(self.open_file(path))?;
+        Ok(Arc::new(file))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        let to_delete_error = |io_error: io::Error| DeleteError::IoError {
+            io_error: Arc::new(io_error),
+            filepath: path.to_path_buf(),
+        };
+        self.inner.check_writable().map_err(to_delete_error)?;
+        // Object stores do not report whether the deleted object existed.
+        let exists = self.exists(path).map_err(|error| match error {
+            OpenReadError::IoError { io_error, .. } => DeleteError::IoError {
+                io_error,
+                filepath: path.to_path_buf(),
+            },
+            error => to_delete_error(io::Error::other(error)),
+        })?;
+        if !exists {
+            return Err(DeleteError::FileDoesNotExist(path.to_path_buf()));
+        }
+        let object_path = self.inner.object_path(path);
+        self.inner
+            .runtime
+            .block_on(self.inner.store.delete(&object_path))
+            .map_err(|error| to_delete_error(error.into()))
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        match self.get_file_handle(path) {
+            Ok(_) => Ok(true),
+            Err(OpenReadError::FileDoesNotExist(_)) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let to_open_write_error =
+            |io_error: io::Error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf());
+        self.inner.check_writable().map_err(to_open_write_error)?;
+        // The file is created right away, atomically, so that it can be used as a lock.
+        let object_path = self.inner.object_path(path);
+        self.inner
+            .runtime
+            .block_on(self.inner.store.put_opts(
+                &object_path,
+                PutPayload::default(),
+                PutMode::Create.into(),
+            ))
+            .map_err(|error| match error {
+                object_store::Error::AlreadyExists { .. } => {
+                    OpenWriteError::FileAlreadyExists(path.to_path_buf())
+                }
+                error => to_open_write_error(error.into()),
+            })?;
+        let writer = ObjectStoreWriter {
+            path: path.to_path_buf(),
+            directory: self.inner.clone(),
+            data: Vec::new(),
+            is_flushed: true,
+        };
+        Ok(BufWriter::new(Box::new(writer)))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.inner.runtime.block_on(self.read_atomic_file(path))
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.inner.check_writable()?;
+        // Object store writes are atomic.
+        self.inner.put(path, data.to_vec())?;
+        if path == *META_FILEPATH {
+            drop(self.inner.watch_router.broadcast());
+        }
+        Ok(())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        if self.inner.mode == ObjectStoreDirectoryMode::WriteThrough {
+            return acquire_lock_file(Box::new(self.clone()), lock);
+        }
+        // Nothing is ever written through a read-only directory, so readers do not need to
+        // lock anything.
+        if lock.filepath == INDEX_WRITER_LOCK.filepath {
+            return Err(LockError::wrap_io_error(
+                self.inner.check_writable().unwrap_err(),
+            ));
+        }
+        Ok(DirectoryLock::from(Box::new(())))
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(self.inner.watch_router.subscribe(watch_callback))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STORED, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_object_store_directory_index() -> crate::Result<()> {
+        let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING | STORED);
+        let schema = schema_builder.build();
+
+        let options = ObjectStoreDirectoryOptions::builder()
+            .mode(ObjectStoreDirectoryMode::WriteThrough)
+            .build();
+        let directory = ObjectStoreDirectory::open(
+            store.clone(),
+            "indexes/test".into(),
+            runtime.handle().clone(),
+            options,
+        )?;
+        let index = Index::create(directory, schema, Default::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..100 {
+            let tag = if i % 4 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(tag_field => tag))?;
+        }
+        index_writer.commit()?;
+        drop(index_writer);
+
+        let cache_dir = tempfile::TempDir::new()?;
+        let options = ObjectStoreDirectoryOptions::builder()
+            .block_size(64)
+            .memory_cache_size(0)
+            .disk_cache_dir(cache_dir.path().to_path_buf())
+            .build();
+        let directory = ObjectStoreDirectory::open(
+            store,
+            "indexes/test".into(),
+            runtime.handle().clone(),
+            options,
+        )?;
+        let index = Index::open(directory.clone())?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(tag_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 25);
+        assert!(fs::read_dir(cache_dir.path())?.count() > 0);
+        // The blocks are now read from the disk cache.
+        assert_eq!(searcher.search(&query, &Count)?, 25);
+
+        assert!(directory.atomic_write(Path::new("file"), b"data").is_err());
+        assert!(directory.open_write(Path::new("file")).is_err());
+        assert!(index.writer_for_tests::<crate::TantivyDocument>().is_err());
+        Ok(())
+    }
+}
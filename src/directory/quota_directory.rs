@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use common::ByteCount;
+
+use crate::core::{MANAGED_FILEPATH, META_FILEPATH};
+use crate::directory::error::{
+    DeleteError, LockError, OpenReadError, OpenWriteError, QuotaExceededError,
+};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, FileSlice, Lock, TerminatingWrite,
+    WatchCallback, WatchHandle, WritePtr,
+};
+use crate::error::DataCorruption;
+use crate::index::SegmentComponent;
+
+/// Disk usage of a [`QuotaDirectory`], broken down by file type.
+#[derive(Clone, Debug, Default)]
+pub struct DirectoryUsage {
+    total: ByteCount,
+    per_component: HashMap<SegmentComponent, ByteCount>,
+    other: ByteCount,
+}
+
+impl DirectoryUsage {
+    /// Returns the number of bytes used by all the files of the directory.
+    pub fn total(&self) -> ByteCount {
+        self.total
+    }
+
+    /// Returns the number of bytes used by the files of a given segment component.
+    pub fn component(&self, component: SegmentComponent) -> ByteCount {
+        self.per_component
+            .get(&component)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of bytes used by the files that do not belong to a segment, like
+    /// `meta.json`.
+    pub fn other(&self) -> ByteCount {
+        self.other
+    }
+}
+
+struct QuotaState {
+    limit: u64,
+    file_sizes: HashMap<PathBuf, u64>,
+    usage: u64,
+}
+
+impl QuotaState {
+    /// Accounts for `num_bytes` more bytes in a file, or returns an error if that would exceed
+    /// the quota.
+    fn reserve(&mut self, path: &Path, num_bytes: u64) -> Result<(), QuotaExceededError> {
+        if self.usage.saturating_add(num_bytes) > self.limit {
+            return Err(QuotaExceededError {
+                limit: self.limit,
+                usage: self.usage,
+                num_bytes,
+            });
+        }
+        self.usage += num_bytes;
+        *self.file_sizes.entry(path.to_path_buf()).or_default() += num_bytes;
+        Ok(())
+    }
+
+    fn release(&mut self, path: &Path, num_bytes: u64) {
+        if let Some(file_size) = self.file_sizes.get_mut(path) {
+            let num_bytes = num_bytes.min(*file_size);
+            *file_size -= num_bytes;
+            self.usage -= num_bytes;
+        }
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(file_size) = self.file_sizes.remove(path) {
+            self.usage -= file_size;
+        }
+    }
+}
+
+fn quota_exceeded_io_error(error: QuotaExceededError) -> io::Error {
+    io::Error::other(error)
+}
+
+/// A [`Directory`] wrapper enforcing a limit on the number of bytes stored in a directory.
+///
+/// Writes that would bring the size of the files of the directory over the limit fail with
+/// an [`io::Error`] wrapping a [`QuotaExceededError`]. Files that are deleted free up their
+/// bytes, so an index that exceeded its quota can get back under it once its segments get
+/// merged or its files garbage collected.
+///
+/// Files replaced with [`Directory::atomic_write`] may always shrink, so that commits that do
+/// not add any data, e.g. deletes-only commits, are not blocked by a full quota.
+///
+/// The usage of the directory, broken down by [`SegmentComponent`], is returned by
+/// [`QuotaDirectory::usage`].
+#[derive(Clone)]
+pub struct QuotaDirectory {
+    directory: Box<dyn Directory>,
+    state: Arc<Mutex<QuotaState>>,
+}
+
+impl fmt::Debug for QuotaDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        write!(
+            f,
+            "QuotaDirectory({:?}, {}/{} bytes)",
+            self.directory, state.usage, state.limit
+        )
+    }
+}
+
+impl QuotaDirectory {
+    /// Wraps a directory, limiting the number of bytes it stores to `limit`.
+    ///
+    /// The initial usage of the directory is computed from the files of the index it holds,
+    /// i.e. the files listed in its `.managed.json` file, the `.managed.json` file itself and
+    /// `meta.json`. Other files, that were not written by tantivy, are not accounted for.
+    pub fn wrap(directory: Box<dyn Directory>, limit: u64) -> crate::Result<QuotaDirectory> {
+        let mut paths: HashSet<PathBuf> = match directory.atomic_read(&MANAGED_FILEPATH) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|e| {
+                DataCorruption::new(
+                    MANAGED_FILEPATH.to_path_buf(),
+                    format!("Managed file cannot be deserialized: {e:?}. "),
+                )
+            })?,
+            Err(OpenReadError::FileDoesNotExist(_)) => HashSet::new(),
+            Err(open_read_error) => return Err(open_read_error.into()),
+        };
+        paths.insert(MANAGED_FILEPATH.to_path_buf());
+        paths.insert(META_FILEPATH.to_path_buf());
+        let mut file_sizes: HashMap<PathBuf, u64> = HashMap::with_capacity(paths.len());
+        for path in paths {
+            match directory.get_file_handle(&path) {
+                Ok(file_handle) => {
+                    file_sizes.insert(path, file_handle.len() as u64);
+                }
+                // Files that are listed as managed may have been deleted already.
+                Err(OpenReadError::FileDoesNotExist(_)) => {}
+                Err(open_read_error) => return Err(open_read_error.into()),
+            }
+        }
+        let usage = file_sizes.values().sum();
+        Ok(QuotaDirectory {
+            directory,
+            state: Arc::new(Mutex::new(QuotaState {
+                limit,
+                file_sizes,
+                usage,
+            })),
+        })
+    }
+
+    /// Returns the maximum number of bytes the directory may store.
+    pub fn limit(&self) -> u64 {
+        self.state.lock().unwrap().limit
+    }
+
+    /// Changes the maximum number of bytes the directory may store.
+    ///
+    /// Lowering the limit below the current usage does not delete anything, but all the
+    /// writes adding data will fail until enough files are deleted.
+    pub fn set_limit(&self, limit: u64) {
+        self.state.lock().unwrap().limit = limit;
+    }
+
+    /// Returns the current disk usage of the directory.
+    pub fn usage(&self) -> DirectoryUsage {
+        let state = self.state.lock().unwrap();
+        let mut usage = DirectoryUsage {
+            total: ByteCount::from(state.usage),
+            ..Default::default()
+        };
+        for (path, &file_size) in &state.file_sizes {
+            match SegmentComponent::from_path(path) {
+                Some(component) => {
+                    *usage.per_component.entry(component).or_default() += file_size.into();
+                }
+                None => usage.other += file_size.into(),
+            }
+        }
+        usage
+    }
+}
+
+impl Directory for QuotaDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        self.directory.get_file_handle(path)
+    }
+
+    fn open_read(&self, path: &Path) -> Result<FileSlice, OpenReadError> {
+        self.directory.open_read(path)
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.directory.delete(path)?;
+        self.state.lock().unwrap().remove(path);
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.directory.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        // The quota writer checks every write, so the buffer of the underlying writer is
+        // moved in front of it.
+        let underlying = self
+            .directory
+            .open_write(path)?
+            .into_inner()
+            .map_err(|error| {
+                OpenWriteError::wrap_io_error(error.into_error(), path.to_path_buf())
+            })?;
+        self.state
+            .lock()
+            .unwrap()
+            .file_sizes
+            .insert(path.to_path_buf(), 0);
+        Ok(BufWriter::new(Box::new(QuotaWriter {
+            path: path.to_path_buf(),
+            state: self.state.clone(),
+            underlying,
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.directory.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let previous_size = state.file_sizes.get(path).copied().unwrap_or(0);
+        let new_size = data.len() as u64;
+        if new_size > previous_size {
+            let num_bytes = new_size - previous_size;
+            if state.usage.saturating_add(num_bytes) > state.limit {
+                return Err(quota_exceeded_io_error(QuotaExceededError {
+                    limit: state.limit,
+                    usage: state.usage,
+                    num_bytes,
+                }));
+            }
+        }
+        // The lock is held during the write, so that concurrent writes cannot both pass the
+        // check.
+        self.directory.atomic_write(path, data)?;
+        state.usage = state.usage - previous_size + new_size;
+        state.file_sizes.insert(path.to_path_buf(), new_size);
+        Ok(())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.directory.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.directory.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.directory.watch(watch_callback)
+    }
+}
+
+struct QuotaWriter {
+    path: PathBuf,
+    state: Arc<Mutex<QuotaState>>,
+    underlying: Box<dyn TerminatingWrite>,
+}
+
+impl Write for QuotaWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = buf.len() as u64;
+        self.state
+            .lock()
+            .unwrap()
+            .reserve(&self.path, num_bytes)
+            .map_err(quota_exceeded_io_error)?;
+        let written = self.underlying.write(buf);
+        let num_written_bytes = written.as_ref().map(|&len| len as u64).unwrap_or(0);
+        if num_written_bytes < num_bytes {
+            self.state
+                .lock()
+                .unwrap()
+                .release(&self.path, num_bytes - num_written_bytes);
+        }
+        written
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl TerminatingWrite for QuotaWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.underlying.terminate_ref(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::directory::RamDirectory;
+    use crate::schema::{Schema, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyError};
+
+    fn quota_exceeded_error(error: &TantivyError) -> Option<&QuotaExceededError> {
+        let io_error = match error {
+            TantivyError::IoError(io_error) => io_error.as_ref(),
+            TantivyError::OpenWriteError(OpenWriteError::IoError { io_error, .. }) => {
+                io_error.as_ref()
+            }
+            _ => return None,
+        };
+        io_error.get_ref()?.downcast_ref::<QuotaExceededError>()
+    }
+
+    #[test]
+    fn test_quota_directory_write() -> io::Result<()> {
+        let directory = QuotaDirectory::wrap(Box::<RamDirectory>::default(), 10).unwrap();
+        let path = Path::new("seg.idx");
+        let mut write = directory.open_write(path).unwrap();
+        write.write_all(&[1u8; 8])?;
+        write.flush()?;
+        write.write_all(&[1u8; 3])?;
+        let io_error = write.flush().unwrap_err();
+        let error = io_error
+            .get_ref()
+            .and_then(|error| error.downcast_ref::<QuotaExceededError>())
+            .unwrap();
+        assert_eq!((error.limit, error.usage, error.num_bytes), (10, 8, 3));
+        drop(write);
+        assert_eq!(directory.usage().total(), 8);
+        assert_eq!(directory.usage().component(SegmentComponent::Postings), 8);
+
+        assert!(directory
+            .atomic_write(Path::new("meta.json"), &[0u8; 3])
+            .is_err());
+        directory.atomic_write(Path::new("meta.json"), &[0u8; 2])?;
+        assert_eq!(directory.usage().other(), 2);
+        directory.set_limit(5);
+        // Shrinking a file is allowed even when the quota is exceeded.
+        directory.atomic_write(Path::new("meta.json"), &[0u8; 1])?;
+        directory.delete(path).unwrap();
+        assert_eq!(directory.usage().total(), 1);
+        assert_eq!(directory.usage().component(SegmentComponent::Postings), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_quota_directory_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let ram_directory = RamDirectory::default();
+        let directory = QuotaDirectory::wrap(Box::new(ram_directory.clone()), 1_000_000)?;
+        let index = Index::create(directory.clone(), schema, Default::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for _ in 0..10 {
+            index_writer.add_document(doc!(text_field => "hello happy tax payer"))?;
+        }
+        index_writer.commit()?;
+
+        let usage = directory.usage();
+        assert!(usage.total() > 0);
+        assert!(usage.component(SegmentComponent::Terms) > 0);
+        assert!(usage.component(SegmentComponent::Store) > 0);
+        assert_eq!(usage.component(SegmentComponent::TempStore), 0);
+        assert!(usage.other() > 0);
+        let component_usage: ByteCount = SegmentComponent::iterator()
+            .map(|&component| usage.component(component))
+            .sum();
+        assert_eq!(component_usage + usage.other(), usage.total());
+
+        // Re-wrapping the directory computes the same usage.
+        let rewrapped_directory = QuotaDirectory::wrap(Box::new(ram_directory), 1_000_000)?;
+        assert_eq!(rewrapped_directory.usage().total(), usage.total());
+
+        directory.set_limit(usage.total().get_bytes() + 10);
+        let text = "some more text ".repeat(100);
+        index_writer.add_document(doc!(text_field => text))?;
+        let error = index_writer.commit().unwrap_err();
+        let quota_exceeded_error = quota_exceeded_error(&error).unwrap();
+        assert_eq!(quota_exceeded_error.limit, usage.total().get_bytes() + 10);
+        Ok(())
+    }
+}
@@ -200,14 +200,14 @@ impl Directory for RamDirectory {
     fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
         let mut fs = self.fs.write().unwrap();
         let path_buf = PathBuf::from(path);
-        let vec_writer = VecWriter::new(path_buf.clone(), self.clone());
-        let exists = fs.write(path_buf.clone(), &[]);
-        // force the creation of the file to mimic the MMap directory.
-        if exists {
-            Err(OpenWriteError::FileAlreadyExists(path_buf))
-        } else {
-            Ok(BufWriter::new(Box::new(vec_writer)))
+        // An existing file is left untouched, to mimic the MMap directory.
+        if fs.exists(&path_buf) {
+            return Err(OpenWriteError::FileAlreadyExists(path_buf));
         }
+        // force the creation of the file to mimic the MMap directory.
+        fs.write(path_buf.clone(), &[]);
+        let vec_writer = VecWriter::new(path_buf, self.clone());
+        Ok(BufWriter::new(Box::new(vec_writer)))
     }
 
     fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
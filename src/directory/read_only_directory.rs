@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, io};
+
+use crate::core::META_FILEPATH;
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileHandle, FileSlice, Lock, WatchCallback, WatchHandle, WritePtr,
+    INDEX_WRITER_LOCK,
+};
+
+fn read_only_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "the directory is opened in read-only mode",
+    )
+}
+
+/// A [`Directory`] wrapper which never writes anything to the wrapped directory.
+///
+/// The `meta.json` file is read once, when the directory is wrapped: the index stays pinned
+/// to that commit, and the directory never notifies its watchers.
+///
+/// Writes fail with an [`io::ErrorKind::PermissionDenied`] error. Acquiring the
+/// [`INDEX_WRITER_LOCK`] fails right away, without creating any lock file, and the other
+/// locks are granted without touching the directory. This makes it possible to serve an
+/// index from a read-only volume, from a container with a read-only root filesystem, or from
+/// WORM storage.
+///
+/// Since the readers do not lock anything, the files of the pinned commit must not get
+/// garbage collected by a writer while the index is opened.
+#[derive(Clone)]
+pub struct ReadOnlyDirectory {
+    directory: Box<dyn Directory>,
+    meta_data: Arc<Vec<u8>>,
+}
+
+impl fmt::Debug for ReadOnlyDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ReadOnlyDirectory({:?})", self.directory)
+    }
+}
+
+impl ReadOnlyDirectory {
+    /// Wraps a directory, pinning the commit of its current `meta.json` file.
+    pub fn wrap(directory: Box<dyn Directory>) -> Result<ReadOnlyDirectory, OpenReadError> {
+        let meta_data = directory.atomic_read(&META_FILEPATH)?;
+        Ok(ReadOnlyDirectory {
+            directory,
+            meta_data: Arc::new(meta_data),
+        })
+    }
+}
+
+impl Directory for ReadOnlyDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        self.directory.get_file_handle(path)
+    }
+
+    fn open_read(&self, path: &Path) -> Result<FileSlice, OpenReadError> {
+        self.directory.open_read(path)
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        Err(DeleteError::IoError {
+            io_error: Arc::new(read_only_error()),
+            filepath: path.to_path_buf(),
+        })
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.directory.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        Err(OpenWriteError::wrap_io_error(
+            read_only_error(),
+            path.to_path_buf(),
+        ))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        if path == *META_FILEPATH {
+            return Ok(self.meta_data.as_ref().clone());
+        }
+        self.directory.atomic_read(path)
+    }
+
+    fn atomic_write(&self, _path: &Path, _data: &[u8]) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        self.directory.local_path(path)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        if lock.filepath == INDEX_WRITER_LOCK.filepath {
+            return Err(LockError::wrap_io_error(read_only_error()));
+        }
+        Ok(DirectoryLock::from(Box::new(())))
+    }
+
+    fn watch(&self, _watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(WatchHandle::empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::directory::RamDirectory;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy, TantivyDocument, TantivyError};
+
+    #[test]
+    fn test_read_only_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let ram_directory = RamDirectory::create();
+        let index = Index::create(
+            ram_directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag_field => "a"))?;
+        index_writer.commit()?;
+
+        let read_only_index = Index::open_read_only(ram_directory.clone())?;
+        let reader = read_only_index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert!(matches!(
+            read_only_index.writer_for_tests::<TantivyDocument>(),
+            Err(TantivyError::LockFailure(..))
+        ));
+
+        // Later commits are not visible through the pinned index.
+        index_writer.add_document(doc!(tag_field => "b"))?;
+        let opstamp = index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert_eq!(
+            Index::open_read_only(ram_directory.clone())?
+                .load_metas()?
+                .opstamp,
+            opstamp
+        );
+        assert_eq!(
+            Index::open_read_only_at(ram_directory.clone(), opstamp)?
+                .reader()?
+                .searcher()
+                .num_docs(),
+            2
+        );
+        assert!(matches!(
+            Index::open_read_only_at(ram_directory.clone(), opstamp - 1),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_only_directory_never_writes() -> crate::Result<()> {
+        let ram_directory = RamDirectory::create();
+        ram_directory.atomic_write(&META_FILEPATH, b"meta")?;
+        let directory = ReadOnlyDirectory::wrap(Box::new(ram_directory.clone()))?;
+        let mem_usage = ram_directory.total_mem_usage();
+        assert!(directory.open_write(Path::new("file")).is_err());
+        assert!(directory.atomic_write(Path::new("file"), b"data").is_err());
+        assert!(directory.delete(&META_FILEPATH).is_err());
+        assert!(directory.acquire_lock(&INDEX_WRITER_LOCK).is_err());
+        let _meta_lock = directory.acquire_lock(&crate::directory::META_LOCK)?;
+        assert_eq!(ram_directory.total_mem_usage(), mem_usage);
+        assert!(!ram_directory.exists(&crate::directory::META_LOCK.filepath)?);
+
+        ram_directory.atomic_write(&META_FILEPATH, b"new meta")?;
+        assert_eq!(directory.atomic_read(&META_FILEPATH)?, b"meta");
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_read_only_mmap_directory() -> crate::Result<()> {
+        use crate::directory::MmapDirectory;
+
+        fn list_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+            let mut files = std::fs::read_dir(path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+            files.sort();
+            Ok(files)
+        }
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_dir(temp_dir.path(), schema_builder.build())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag_field => "a"))?;
+        index_writer.commit()?;
+        drop(index_writer);
+        let files = list_files(temp_dir.path())?;
+
+        let read_only_index = Index::open_read_only(MmapDirectory::open(temp_dir.path())?)?;
+        let reader = read_only_index.reader()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert!(read_only_index
+            .writer_for_tests::<TantivyDocument>()
+            .is_err());
+        assert_eq!(list_files(temp_dir.path())?, files);
+        Ok(())
+    }
+}
@@ -117,6 +117,297 @@ mod ram_directory_tests {
     }
 }
 
+mod quota_directory_tests {
+    use crate::directory::{QuotaDirectory, RamDirectory};
+
+    type DirectoryImpl = QuotaDirectory;
+
+    fn make_directory() -> DirectoryImpl {
+        QuotaDirectory::wrap(Box::<RamDirectory>::default(), 1_000_000).unwrap()
+    }
+
+    #[test]
+    fn test_simple() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_simple(&directory)
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_delete() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_directory_delete(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
+mod instrumented_directory_tests {
+    use crate::directory::{InstrumentedDirectory, RamDirectory};
+
+    type DirectoryImpl = InstrumentedDirectory;
+
+    fn make_directory() -> DirectoryImpl {
+        InstrumentedDirectory::wrap(Box::<RamDirectory>::default())
+    }
+
+    #[test]
+    fn test_simple() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_simple(&directory)
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_delete() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_directory_delete(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
+mod locking_directory_tests {
+    use std::time::Duration;
+
+    use crate::directory::{LeaseLockStrategy, LockingDirectory, RamDirectory};
+
+    type DirectoryImpl = LockingDirectory;
+
+    fn make_directory() -> DirectoryImpl {
+        LockingDirectory::wrap(
+            Box::<RamDirectory>::default(),
+            LeaseLockStrategy::new(Duration::from_secs(1)),
+        )
+    }
+
+    #[test]
+    fn test_simple() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_simple(&directory)
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_delete() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_directory_delete(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
+#[cfg(feature = "encryption")]
+mod encrypted_directory_tests {
+    use crate::directory::{EncryptedDirectory, RamDirectory};
+
+    type DirectoryImpl = EncryptedDirectory;
+
+    // `test_simple` and `test_directory_delete` are not run: they read files that are only
+    // flushed, while the last chunk of an encrypted file is written when it is terminated.
+    fn make_directory() -> DirectoryImpl {
+        EncryptedDirectory::new(Box::<RamDirectory>::default(), |_| Ok([3u8; 32]))
+            .with_chunk_size(3)
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
+#[cfg(feature = "object-store")]
+mod object_store_directory_tests {
+    use std::sync::Arc;
+
+    use object_store::memory::InMemory;
+    use once_cell::sync::Lazy;
+    use tokio::runtime::Runtime;
+
+    use crate::directory::{
+        ObjectStoreDirectory, ObjectStoreDirectoryMode, ObjectStoreDirectoryOptions,
+    };
+
+    type DirectoryImpl = ObjectStoreDirectory;
+
+    static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    });
+
+    fn make_directory() -> DirectoryImpl {
+        let options = ObjectStoreDirectoryOptions::builder()
+            .mode(ObjectStoreDirectoryMode::WriteThrough)
+            .block_size(3)
+            .build();
+        ObjectStoreDirectory::open(
+            Arc::new(InMemory::new()),
+            "index".into(),
+            RUNTIME.handle().clone(),
+            options,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_simple() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_simple(&directory)
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_delete() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_directory_delete(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
 fn test_simple(directory: &dyn Directory) -> crate::Result<()> {
     let test_path: &'static Path = Path::new("some_path_for_test");
     let mut write_file = directory.open_write(test_path)?;
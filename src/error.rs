@@ -7,6 +7,7 @@ use std::{fmt, io};
 use thiserror::Error;
 
 use crate::aggregation::AggregationError;
+use crate::core::MemoryBudgetExceeded;
 use crate::directory::error::{
     Incompatibility, LockError, OpenDirectoryError, OpenReadError, OpenWriteError,
 };
@@ -110,6 +111,9 @@ pub enum TantivyError {
     #[error("Deserialize error: {0}")]
     /// An error occurred while attempting to deserialize a document.
     DeserializeError(DeserializeError),
+    /// A request did not fit in its memory budget.
+    #[error(transparent)]
+    MemoryBudgetExceeded(#[from] MemoryBudgetExceeded),
 }
 
 impl From<io::Error> for TantivyError {
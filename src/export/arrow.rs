@@ -0,0 +1,370 @@
+//! Export of fast field columns as Arrow [`RecordBatch`]es.
+//!
+//! Each segment is exported as a `RecordBatch` with one row per document and one column per
+//! requested fast field, so that engines such as DataFusion or Polars can run analytics
+//! directly on the columnar storage of tantivy.
+//!
+//! Values are decoded from the fast field codecs into Arrow buffers. Single-valued columns
+//! are exported as nullable arrays, multivalued columns as lists.
+//!
+//! Aggregation results are flattened into a `RecordBatch` with one row per bucket by
+//! [`aggregation_record_batch`], so that BI tools do not have to traverse their nested JSON.
+//!
+//! ```rust
+//! use arrow_array::cast::AsArray;
+//! use arrow_array::types::UInt64Type;
+//! use tantivy::export::arrow::record_batches;
+//! use tantivy::query::TermQuery;
+//! use tantivy::schema::{IndexRecordOption, Schema, FAST, STRING};
+//! use tantivy::{doc, Index, IndexWriter, Term};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let category = schema_builder.add_text_field("category", STRING | FAST);
+//! let price = schema_builder.add_u64_field("price", FAST);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+//! index_writer.add_document(doc!(category => "book", price => 10u64))?;
+//! index_writer.add_document(doc!(category => "toy", price => 25u64))?;
+//! index_writer.add_document(doc!(category => "book", price => 12u64))?;
+//! index_writer.commit()?;
+//!
+//! let searcher = index.reader()?.searcher();
+//! let books = TermQuery::new(
+//!     Term::from_field_text(category, "book"),
+//!     IndexRecordOption::Basic,
+//! );
+//! let batches = record_batches(&searcher, &["category", "price"], Some(&books))?;
+//! assert_eq!(batches.len(), 1);
+//! let prices = batches[0].column(1).as_primitive::<UInt64Type>();
+//! assert_eq!(prices.values().to_vec(), vec![10, 12]);
+//! # Ok(())
+//! # }
+//! ```
+
+mod aggregation;
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    ArrayBuilder, BinaryBuilder, BooleanBuilder, Float64Builder, Int64Builder, ListBuilder,
+    StringBuilder, TimestampNanosecondBuilder, UInt64Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch, RecordBatchOptions};
+use arrow_schema::{ArrowError, Field as ArrowField, Schema as ArrowSchema};
+use columnar::{BytesColumn, Cardinality, Column};
+
+use crate::query::{EnableScoring, Query};
+use crate::schema::FieldType;
+use crate::{DateTime, DocId, Searcher, SegmentReader, TantivyError};
+
+pub use self::aggregation::aggregation_record_batch;
+
+fn arrow_error(err: ArrowError) -> TantivyError {
+    TantivyError::InternalError(format!("Failed to build Arrow record batch: {err}"))
+}
+
+/// Builds the Arrow array of the values of `column` for the documents `doc_ids`.
+///
+/// A missing column is exported as nulls, or empty lists. `as_list` forces the array to be a
+/// list (`Some(true)`) or not (`Some(false)`), whatever the cardinality of the column.
+/// `append` pushes a value, or a null, to the builder of the values.
+fn column_to_array<T, B>(
+    field_name: &str,
+    column_opt: Option<&Column<T>>,
+    doc_ids: &[DocId],
+    as_list: Option<bool>,
+    mut builder: B,
+    mut append: impl FnMut(&mut B, Option<T>),
+) -> crate::Result<ArrayRef>
+where
+    T: PartialOrd + Copy + Debug + Send + Sync + 'static,
+    B: ArrayBuilder,
+{
+    let is_multivalued =
+        column_opt.is_some_and(|column| column.get_cardinality() == Cardinality::Multivalued);
+    if is_multivalued && as_list == Some(false) {
+        return Err(TantivyError::SchemaError(format!(
+            "Fast field {field_name:?} is multivalued, and cannot be exported as a single value."
+        )));
+    }
+    if as_list.unwrap_or(is_multivalued) {
+        let mut list_builder = ListBuilder::new(builder);
+        for &doc_id in doc_ids {
+            if let Some(column) = column_opt {
+                for val in column.values_for_doc(doc_id) {
+                    append(list_builder.values(), Some(val));
+                }
+            }
+            list_builder.append(true);
+        }
+        Ok(Arc::new(list_builder.finish()))
+    } else {
+        let mut vals = vec![None; doc_ids.len()];
+        if let Some(column) = column_opt {
+            column.first_vals(doc_ids, &mut vals);
+        }
+        for val in vals {
+            append(&mut builder, val);
+        }
+        Ok(builder.finish())
+    }
+}
+
+/// Builds the Arrow array of the values of the fast field `field_name` for the documents
+/// `doc_ids` of a segment.
+///
+/// See [`column_to_array`] for `as_list`.
+pub(crate) fn field_to_array(
+    segment_reader: &SegmentReader,
+    field_name: &str,
+    doc_ids: &[DocId],
+    as_list: Option<bool>,
+) -> crate::Result<ArrayRef> {
+    let schema = segment_reader.schema();
+    let field = schema.get_field(field_name)?;
+    let field_entry = schema.get_field_entry(field);
+    if !field_entry.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {field_name:?} is not a fast field."
+        )));
+    }
+    let fast_fields = segment_reader.fast_fields();
+    match field_entry.field_type() {
+        FieldType::U64(_) => column_to_array(
+            field_name,
+            fast_fields.column_opt::<u64>(field_name)?.as_ref(),
+            doc_ids,
+            as_list,
+            UInt64Builder::new(),
+            |builder, val| builder.append_option(val),
+        ),
+        FieldType::I64(_) => column_to_array(
+            field_name,
+            fast_fields.column_opt::<i64>(field_name)?.as_ref(),
+            doc_ids,
+            as_list,
+            Int64Builder::new(),
+            |builder, val| builder.append_option(val),
+        ),
+        FieldType::F64(_) => column_to_array(
+            field_name,
+            fast_fields.column_opt::<f64>(field_name)?.as_ref(),
+            doc_ids,
+            as_list,
+            Float64Builder::new(),
+            |builder, val| builder.append_option(val),
+        ),
+        FieldType::Bool(_) => column_to_array(
+            field_name,
+            fast_fields.column_opt::<bool>(field_name)?.as_ref(),
+            doc_ids,
+            as_list,
+            BooleanBuilder::new(),
+            |builder, val| builder.append_option(val),
+        ),
+        FieldType::Date(_) => column_to_array(
+            field_name,
+            fast_fields.column_opt::<DateTime>(field_name)?.as_ref(),
+            doc_ids,
+            as_list,
+            TimestampNanosecondBuilder::new(),
+            |builder, val| builder.append_option(val.map(|date| date.into_timestamp_nanos())),
+        ),
+        FieldType::Str(_) => {
+            let str_column_opt = fast_fields.str(field_name)?;
+            let mut buffer = String::new();
+            column_to_array(
+                field_name,
+                str_column_opt.as_ref().map(|str_column| str_column.ords()),
+                doc_ids,
+                as_list,
+                StringBuilder::new(),
+                |builder, term_ord| match (term_ord, &str_column_opt) {
+                    (Some(term_ord), Some(str_column))
+                        if str_column.ord_to_str(term_ord, &mut buffer).is_ok() =>
+                    {
+                        builder.append_value(&buffer)
+                    }
+                    _ => builder.append_null(),
+                },
+            )
+        }
+        FieldType::Bytes(_) => {
+            let bytes_column_opt = fast_fields.bytes(field_name)?;
+            let mut buffer = Vec::new();
+            column_to_array(
+                field_name,
+                bytes_column_opt.as_ref().map(BytesColumn::ords),
+                doc_ids,
+                as_list,
+                BinaryBuilder::new(),
+                |builder, term_ord| match (term_ord, &bytes_column_opt) {
+                    (Some(term_ord), Some(bytes_column))
+                        if bytes_column.ord_to_bytes(term_ord, &mut buffer).is_ok() =>
+                    {
+                        builder.append_value(&buffer)
+                    }
+                    _ => builder.append_null(),
+                },
+            )
+        }
+        field_type => Err(TantivyError::InvalidArgument(format!(
+            "Fast field {field_name:?} of type {:?} cannot be exported to Arrow.",
+            field_type.value_type()
+        ))),
+    }
+}
+
+/// Exports the fast fields `field_names` of the documents `doc_ids` of a segment,
+/// as a `RecordBatch` with one row per document.
+///
+/// `doc_ids` has to be sorted. Deleted documents are not filtered out.
+/// Returns an error if one of the fields is not a fast field, or has an unsupported type
+/// (IP addresses and JSON fields).
+pub fn record_batch_for_docs(
+    segment_reader: &SegmentReader,
+    field_names: &[&str],
+    doc_ids: &[DocId],
+) -> crate::Result<RecordBatch> {
+    debug_assert!(doc_ids.windows(2).all(|docs| docs[0] < docs[1]));
+    let mut arrow_fields = Vec::with_capacity(field_names.len());
+    let mut columns = Vec::with_capacity(field_names.len());
+    for &field_name in field_names {
+        let array = field_to_array(segment_reader, field_name, doc_ids, None)?;
+        arrow_fields.push(ArrowField::new(field_name, array.data_type().clone(), true));
+        columns.push(array);
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(doc_ids.len()));
+    RecordBatch::try_new_with_options(Arc::new(ArrowSchema::new(arrow_fields)), columns, &options)
+        .map_err(arrow_error)
+}
+
+/// Exports the fast fields `field_names` of the documents of all segments, as one
+/// `RecordBatch` per segment.
+///
+/// If `query` is given, only the documents matching it are exported. Deleted documents are
+/// never exported.
+pub fn record_batches(
+    searcher: &Searcher,
+    field_names: &[&str],
+    query: Option<&dyn Query>,
+) -> crate::Result<Vec<RecordBatch>> {
+    let weight_opt = query
+        .map(|query| query.weight(EnableScoring::disabled_from_searcher(searcher)))
+        .transpose()?;
+    let mut record_batches = Vec::with_capacity(searcher.segment_readers().len());
+    for segment_reader in searcher.segment_readers() {
+        let doc_ids: Vec<DocId> = if let Some(weight) = &weight_opt {
+            let mut doc_ids = Vec::new();
+            let alive_bitset = segment_reader.alive_bitset();
+            weight.for_each_no_score(segment_reader, &mut |docs| {
+                doc_ids.extend(docs.iter().copied().filter(|&doc| {
+                    alive_bitset.map_or(true, |alive_bitset| alive_bitset.is_alive(doc))
+                }));
+            })?;
+            doc_ids
+        } else {
+            segment_reader.doc_ids_alive().collect()
+        };
+        record_batches.push(record_batch_for_docs(
+            segment_reader,
+            field_names,
+            &doc_ids,
+        )?);
+    }
+    Ok(record_batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, Int64Type, TimestampNanosecondType, UInt64Type};
+    use arrow_array::Array;
+
+    use super::*;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, FAST, INDEXED, STRING};
+    use crate::{DateTime, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_record_batches() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST | INDEXED);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let score = schema_builder.add_f64_field("score", FAST);
+        let active = schema_builder.add_bool_field("active", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let payload = schema_builder.add_bytes_field("payload", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            id => 0u64,
+            delta => -3i64,
+            score => 0.5f64,
+            active => true,
+            date => DateTime::from_timestamp_secs(10),
+            tag => "a",
+            tag => "b",
+            payload => vec![1u8, 2],
+        ))?;
+        index_writer.add_document(doc!(id => 1u64, tag => "c"))?;
+        index_writer.add_document(doc!(id => 2u64, delta => 7i64, score => 1.5f64))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(id, 2));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let field_names = ["id", "delta", "score", "active", "date", "tag", "payload"];
+        let batches = record_batches(&searcher, &field_names, None)?;
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 7);
+        let ids = batch.column(0).as_primitive::<UInt64Type>();
+        assert_eq!(ids.values().to_vec(), vec![0, 1]);
+        let deltas = batch.column(1).as_primitive::<Int64Type>();
+        assert_eq!(deltas.value(0), -3);
+        assert!(deltas.is_null(1));
+        assert_eq!(batch.column(2).as_primitive::<Float64Type>().value(0), 0.5);
+        assert!(batch.column(3).as_boolean().value(0));
+        let dates = batch.column(4).as_primitive::<TimestampNanosecondType>();
+        assert_eq!(dates.value(0), 10_000_000_000);
+        let tags = batch.column(5).as_list::<i32>();
+        let first_tags = tags.value(0);
+        let first_tags = first_tags.as_string::<i32>();
+        assert_eq!(
+            first_tags.iter().flatten().collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(tags.value(1).as_string::<i32>().value(0), "c");
+        let payloads = batch.column(6).as_binary::<i32>();
+        assert_eq!(payloads.value(0), &[1u8, 2]);
+        assert!(payloads.is_null(1));
+
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let query = query_parser.parse_query("id:>=1")?;
+        let batches = record_batches(&searcher, &["id"], Some(query.as_ref()))?;
+        let ids = batches[0].column(0).as_primitive::<UInt64Type>();
+        assert_eq!(ids.values().to_vec(), vec![1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_batches_invalid_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("indexed", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(record_batches(&searcher, &["indexed"], None).is_err());
+        assert!(record_batches(&searcher, &["missing"], None).is_err());
+        let batches = record_batches(&searcher, &[], None)?;
+        assert_eq!(batches[0].num_rows(), 1);
+        Ok(())
+    }
+}
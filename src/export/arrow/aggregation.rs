@@ -0,0 +1,592 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::builder::{Float64Builder, Int64Builder, StringBuilder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch, RecordBatchOptions};
+use arrow_schema::{Field as ArrowField, Schema as ArrowSchema};
+
+use super::arrow_error;
+use crate::aggregation::agg_result::{
+    AggregationResult, AggregationResults, BucketEntries, BucketResult, MetricResult,
+};
+use crate::aggregation::metric::PercentileValues;
+use crate::aggregation::Key;
+use crate::TantivyError;
+
+/// A cell of a flattened aggregation result.
+#[derive(Clone)]
+enum Cell<'a> {
+    Key(Cow<'a, Key>),
+    Count(u64),
+    Metric(Option<f64>),
+}
+
+/// A row of a flattened aggregation result, as `(column name, cell)` pairs.
+type Row<'a> = Vec<(String, Cell<'a>)>;
+
+/// Exports the aggregation `name` of `results` as a flat `RecordBatch`.
+///
+/// A bucket aggregation gets one row per bucket, with:
+/// - the key of the bucket in the column `name`, and its document count in `name.doc_count`,
+/// - for range buckets, the bounds of the range in `name.from` and `name.to`,
+/// - for composite buckets, the value of each source of the key in `name.source` instead of
+///   `name`,
+/// - the metric sub-aggregations of the bucket, in a column named after the sub-aggregation,
+///   or in `sub_aggregation.count`, `sub_aggregation.avg`... for multi-value metrics, and in
+///   `sub_aggregation.95.0`... for percentiles.
+///
+/// A bucket sub-aggregation is flattened the same way: each of its buckets gets a row, with
+/// the key and metrics of its parent bucket repeated. Only one bucket sub-aggregation per
+/// bucket can be flattened. A metric aggregation gets a single row.
+///
+/// Keys are exported as strings, or as numbers if all of the keys of the column are numbers.
+/// Returns an error if `name` is not in `results`, or if it contains top hits.
+pub fn aggregation_record_batch(
+    results: &AggregationResults,
+    name: &str,
+) -> crate::Result<RecordBatch> {
+    let result = results
+        .0
+        .get(name)
+        .ok_or_else(|| TantivyError::InvalidArgument(format!("Unknown aggregation {name:?}")))?;
+    let mut rows = Vec::new();
+    flatten(vec![(name, result)], Vec::new(), &mut rows)?;
+    rows_to_record_batch(rows)
+}
+
+/// Appends the rows of the aggregations `aggregations` of a bucket, whose previous columns are
+/// `row`.
+fn flatten<'a>(
+    aggregations: Vec<(&'a str, &'a AggregationResult)>,
+    mut row: Row<'a>,
+    rows: &mut Vec<Row<'a>>,
+) -> crate::Result<()> {
+    let mut bucket_aggregation: Option<(&str, &BucketResult)> = None;
+    for (name, result) in aggregations {
+        match result {
+            AggregationResult::MetricResult(metric_result) => {
+                append_metric_cells(name, metric_result, &mut row)?
+            }
+            AggregationResult::BucketResult(bucket_result) => {
+                if bucket_aggregation.replace((name, bucket_result)).is_some() {
+                    return Err(TantivyError::InvalidArgument(
+                        "Only one bucket aggregation per bucket can be exported to Arrow"
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+    }
+    let Some((name, bucket_result)) = bucket_aggregation else {
+        rows.push(row);
+        return Ok(());
+    };
+    let buckets = bucket_rows(name, bucket_result);
+    if buckets.is_empty() {
+        rows.push(row);
+        return Ok(());
+    }
+    for (bucket_row, sub_aggregation) in buckets {
+        let mut sub_aggregations: Vec<(&str, &AggregationResult)> = sub_aggregation
+            .0
+            .iter()
+            .map(|(name, result)| (name.as_str(), result))
+            .collect();
+        sub_aggregations.sort_by_key(|(name, _)| *name);
+        let mut child_row = row.clone();
+        child_row.extend(bucket_row);
+        flatten(sub_aggregations, child_row, rows)?;
+    }
+    Ok(())
+}
+
+/// Returns the cells of each bucket of `bucket_result`, with its sub-aggregations.
+fn bucket_rows<'a>(
+    name: &str,
+    bucket_result: &'a BucketResult,
+) -> Vec<(Row<'a>, &'a AggregationResults)> {
+    match bucket_result {
+        BucketResult::Terms { buckets, .. } => buckets
+            .iter()
+            .map(|bucket| {
+                let row = bucket_cells(name, Cow::Borrowed(&bucket.key), bucket.doc_count);
+                (row, &bucket.sub_aggregation)
+            })
+            .collect(),
+        BucketResult::Histogram { buckets } => {
+            let mut buckets: Vec<_> = match buckets {
+                BucketEntries::Vec(buckets) => buckets.iter().collect(),
+                BucketEntries::HashMap(buckets) => buckets.values().collect(),
+            };
+            buckets.sort_by(|left, right| key_to_f64(&left.key).total_cmp(&key_to_f64(&right.key)));
+            buckets
+                .into_iter()
+                .map(|bucket| {
+                    let row = bucket_cells(name, Cow::Borrowed(&bucket.key), bucket.doc_count);
+                    (row, &bucket.sub_aggregation)
+                })
+                .collect()
+        }
+        BucketResult::Range { buckets } => {
+            let mut buckets: Vec<_> = match buckets {
+                BucketEntries::Vec(buckets) => buckets.iter().collect(),
+                BucketEntries::HashMap(buckets) => buckets.values().collect(),
+            };
+            buckets.sort_by(|left, right| {
+                let left_from = left.from.unwrap_or(f64::NEG_INFINITY);
+                left_from.total_cmp(&right.from.unwrap_or(f64::NEG_INFINITY))
+            });
+            buckets
+                .into_iter()
+                .map(|bucket| {
+                    let mut row = bucket_cells(name, Cow::Borrowed(&bucket.key), bucket.doc_count);
+                    row.push((format!("{name}.from"), Cell::Metric(bucket.from)));
+                    row.push((format!("{name}.to"), Cell::Metric(bucket.to)));
+                    (row, &bucket.sub_aggregation)
+                })
+                .collect()
+        }
+        BucketResult::Filter(bucket) => {
+            let row = vec![(format!("{name}.doc_count"), Cell::Count(bucket.doc_count))];
+            vec![(row, &bucket.sub_aggregation)]
+        }
+        BucketResult::Filters { buckets } => {
+            let mut buckets: Vec<_> = buckets.iter().collect();
+            buckets.sort_by_key(|(filter_name, _)| *filter_name);
+            buckets
+                .into_iter()
+                .map(|(filter_name, bucket)| {
+                    let key = Cow::Owned(Key::Str(filter_name.clone()));
+                    let row = bucket_cells(name, key, bucket.doc_count);
+                    (row, &bucket.sub_aggregation)
+                })
+                .collect()
+        }
+        BucketResult::Composite { buckets, .. } => buckets
+            .iter()
+            .map(|bucket| {
+                let mut key: Vec<_> = bucket.key.iter().collect();
+                key.sort_by_key(|(source_name, _)| *source_name);
+                let mut row: Row = key
+                    .into_iter()
+                    .map(|(source_name, val)| {
+                        (format!("{name}.{source_name}"), Cell::Key(Cow::Borrowed(val)))
+                    })
+                    .collect();
+                row.push((format!("{name}.doc_count"), Cell::Count(bucket.doc_count)));
+                (row, &bucket.sub_aggregation)
+            })
+            .collect(),
+    }
+}
+
+fn bucket_cells<'a>(name: &str, key: Cow<'a, Key>, doc_count: u64) -> Row<'a> {
+    vec![
+        (name.to_string(), Cell::Key(key)),
+        (format!("{name}.doc_count"), Cell::Count(doc_count)),
+    ]
+}
+
+fn key_to_f64(key: &Key) -> f64 {
+    match key {
+        Key::Str(_) => f64::NAN,
+        Key::I64(val) => *val as f64,
+        Key::U64(val) => *val as f64,
+        Key::F64(val) => *val,
+    }
+}
+
+fn append_metric_cells(
+    name: &str,
+    metric_result: &MetricResult,
+    row: &mut Row<'_>,
+) -> crate::Result<()> {
+    let append = |row: &mut Row<'_>, property: &str, cell: Cell<'static>| {
+        row.push((format!("{name}.{property}"), cell));
+    };
+    match metric_result {
+        MetricResult::Average(single_metric)
+        | MetricResult::Count(single_metric)
+        | MetricResult::Max(single_metric)
+        | MetricResult::Min(single_metric)
+        | MetricResult::Sum(single_metric)
+        | MetricResult::Cardinality(single_metric)
+        | MetricResult::Rate(single_metric) => {
+            row.push((name.to_string(), Cell::Metric(single_metric.value)));
+        }
+        MetricResult::Stats(stats) => {
+            append(row, "count", Cell::Count(stats.count));
+            append(row, "sum", Cell::Metric(Some(stats.sum)));
+            append(row, "min", Cell::Metric(stats.min));
+            append(row, "max", Cell::Metric(stats.max));
+            append(row, "avg", Cell::Metric(stats.avg));
+        }
+        MetricResult::ExtendedStats(extended_stats) => {
+            append(row, "count", Cell::Count(extended_stats.count));
+            append(row, "sum", Cell::Metric(Some(extended_stats.sum)));
+            append(row, "min", Cell::Metric(extended_stats.min));
+            append(row, "max", Cell::Metric(extended_stats.max));
+            append(row, "avg", Cell::Metric(extended_stats.avg));
+            append(
+                row,
+                "sum_of_squares",
+                Cell::Metric(extended_stats.sum_of_squares),
+            );
+            append(row, "variance", Cell::Metric(extended_stats.variance));
+            append(
+                row,
+                "std_deviation",
+                Cell::Metric(extended_stats.std_deviation),
+            );
+        }
+        MetricResult::Percentiles(percentiles) => {
+            let mut values: Vec<(f64, f64)> = match &percentiles.values {
+                PercentileValues::Vec(entries) => entries
+                    .iter()
+                    .map(|entry| (entry.key, entry.value))
+                    .collect(),
+                PercentileValues::HashMap(entries) => entries
+                    .iter()
+                    .map(|(key, value)| (key.parse().unwrap_or(f64::NAN), *value))
+                    .collect(),
+            };
+            values.sort_by(|left, right| left.0.total_cmp(&right.0));
+            for (percent, value) in values {
+                append(row, &format!("{percent:?}"), Cell::Metric(Some(value)));
+            }
+        }
+        MetricResult::GeoCentroid(geo_centroid) => {
+            let location = geo_centroid.location.as_ref();
+            append(row, "count", Cell::Count(geo_centroid.count));
+            append(row, "lat", Cell::Metric(location.map(|point| point.lat)));
+            append(row, "lon", Cell::Metric(location.map(|point| point.lon)));
+        }
+        MetricResult::GeoBounds(geo_bounds) => {
+            let bounds = geo_bounds.bounds.as_ref();
+            let top_left = bounds.map(|bounds| bounds.top_left);
+            let bottom_right = bounds.map(|bounds| bounds.bottom_right);
+            append(row, "top", Cell::Metric(top_left.map(|point| point.lat)));
+            append(row, "left", Cell::Metric(top_left.map(|point| point.lon)));
+            append(
+                row,
+                "bottom",
+                Cell::Metric(bottom_right.map(|point| point.lat)),
+            );
+            append(
+                row,
+                "right",
+                Cell::Metric(bottom_right.map(|point| point.lon)),
+            );
+        }
+        MetricResult::TopMetrics(top_metrics) => {
+            if let Some(top) = top_metrics.top.first() {
+                append(row, "sort", Cell::Metric(top.sort.first().copied()));
+                let mut metrics: Vec<(&String, &Option<f64>)> = top.metrics.iter().collect();
+                metrics.sort_by_key(|(field, _)| *field);
+                for (field, value) in metrics {
+                    append(row, field, Cell::Metric(*value));
+                }
+            }
+        }
+        MetricResult::TopHits(_) => {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The top hits aggregation {name:?} cannot be exported to Arrow"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn rows_to_record_batch(rows: Vec<Row<'_>>) -> crate::Result<RecordBatch> {
+    let mut column_names: Vec<String> = Vec::new();
+    let mut column_ords: HashMap<String, usize> = HashMap::new();
+    for row in &rows {
+        for (column_name, _) in row {
+            if !column_ords.contains_key(column_name) {
+                column_ords.insert(column_name.clone(), column_names.len());
+                column_names.push(column_name.clone());
+            }
+        }
+    }
+    let mut columns: Vec<Vec<Option<Cell>>> = vec![vec![None; rows.len()]; column_names.len()];
+    for (row_ord, row) in rows.iter().enumerate() {
+        for (column_name, cell) in row {
+            columns[column_ords[column_name]][row_ord] = Some(cell.clone());
+        }
+    }
+    let mut arrow_fields = Vec::with_capacity(column_names.len());
+    let mut arrays = Vec::with_capacity(column_names.len());
+    for (column_name, cells) in column_names.iter().zip(&columns) {
+        let array = cells_to_array(column_name, cells)?;
+        arrow_fields.push(ArrowField::new(
+            column_name,
+            array.data_type().clone(),
+            true,
+        ));
+        arrays.push(array);
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+    RecordBatch::try_new_with_options(Arc::new(ArrowSchema::new(arrow_fields)), arrays, &options)
+        .map_err(arrow_error)
+}
+
+fn cells_to_array(column_name: &str, cells: &[Option<Cell>]) -> crate::Result<ArrayRef> {
+    let present_cells = || cells.iter().flatten();
+    if present_cells().all(|cell| matches!(cell, Cell::Count(_))) {
+        let mut builder = UInt64Builder::with_capacity(cells.len());
+        for cell in cells {
+            builder.append_option(match cell {
+                Some(Cell::Count(count)) => Some(*count),
+                _ => None,
+            });
+        }
+        return Ok(Arc::new(builder.finish()));
+    }
+    if present_cells().all(|cell| matches!(cell, Cell::Metric(_))) {
+        let mut builder = Float64Builder::with_capacity(cells.len());
+        for cell in cells {
+            builder.append_option(match cell {
+                Some(Cell::Metric(value)) => *value,
+                _ => None,
+            });
+        }
+        return Ok(Arc::new(builder.finish()));
+    }
+    let keys: Vec<Option<&Key>> = cells
+        .iter()
+        .map(|cell| match cell {
+            Some(Cell::Key(key)) => Ok(Some(key.as_ref())),
+            None => Ok(None),
+            Some(_) => Err(TantivyError::InvalidArgument(format!(
+                "The column {column_name:?} mixes bucket keys and metrics"
+            ))),
+        })
+        .collect::<crate::Result<_>>()?;
+    let present_keys = || keys.iter().flatten();
+    if present_keys().all(|key| matches!(key, Key::U64(_))) {
+        let mut builder = UInt64Builder::with_capacity(keys.len());
+        for key in &keys {
+            builder.append_option(match key {
+                Some(Key::U64(val)) => Some(*val),
+                _ => None,
+            });
+        }
+        Ok(Arc::new(builder.finish()))
+    } else if present_keys().all(|key| matches!(key, Key::I64(_))) {
+        let mut builder = Int64Builder::with_capacity(keys.len());
+        for key in &keys {
+            builder.append_option(match key {
+                Some(Key::I64(val)) => Some(*val),
+                _ => None,
+            });
+        }
+        Ok(Arc::new(builder.finish()))
+    } else if present_keys().all(|key| !matches!(key, Key::Str(_))) {
+        let mut builder = Float64Builder::with_capacity(keys.len());
+        for key in &keys {
+            builder.append_option(key.map(key_to_f64));
+        }
+        Ok(Arc::new(builder.finish()))
+    } else {
+        let mut builder = StringBuilder::with_capacity(keys.len(), 0);
+        for key in &keys {
+            builder.append_option(key.map(Key::to_string));
+        }
+        Ok(Arc::new(builder.finish()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, UInt64Type};
+
+    use super::aggregation_record_batch;
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::{AggregationCollector, AggregationLimitsGuard};
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_aggregation_record_batch() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for (category_value, price_value) in [
+            ("book", 10.0),
+            ("book", 12.0),
+            ("toy", 25.0),
+            ("book", 31.0),
+        ] {
+            index_writer.add_document(doc!(category => category_value, price => price_value))?;
+        }
+        index_writer.commit()?;
+
+        let aggregations: Aggregations = serde_json::from_value(serde_json::json!({
+            "categories": {
+                "terms": {"field": "category"},
+                "aggs": {
+                    "avg_price": {"avg": {"field": "price"}},
+                    "prices": {
+                        "histogram": {"field": "price", "interval": 20.0},
+                        "aggs": {"price_stats": {"stats": {"field": "price"}}}
+                    }
+                }
+            },
+            "max_price": {"max": {"field": "price"}}
+        }))
+        .unwrap();
+        let collector =
+            AggregationCollector::from_aggs(aggregations, AggregationLimitsGuard::default());
+        let results: AggregationResults =
+            index.reader()?.searcher().search(&AllQuery, &collector)?;
+
+        let record_batch = aggregation_record_batch(&results, "categories")?;
+        let column_names: Vec<&str> = record_batch
+            .schema_ref()
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect();
+        assert_eq!(
+            column_names,
+            [
+                "categories",
+                "categories.doc_count",
+                "avg_price",
+                "prices",
+                "prices.doc_count",
+                "price_stats.count",
+                "price_stats.sum",
+                "price_stats.min",
+                "price_stats.max",
+                "price_stats.avg",
+            ]
+        );
+        assert_eq!(record_batch.num_rows(), 3);
+        let categories = record_batch.column(0).as_string::<i32>();
+        assert_eq!(
+            categories.iter().collect::<Vec<_>>(),
+            [Some("book"), Some("book"), Some("toy")]
+        );
+        let doc_counts = record_batch.column(1).as_primitive::<UInt64Type>();
+        assert_eq!(doc_counts.values().to_vec(), vec![3, 3, 1]);
+        let avg_prices = record_batch.column(2).as_primitive::<Float64Type>();
+        assert_eq!(
+            avg_prices.values().to_vec(),
+            vec![53.0 / 3.0, 53.0 / 3.0, 25.0]
+        );
+        let prices = record_batch.column(3).as_primitive::<Float64Type>();
+        assert_eq!(prices.values().to_vec(), vec![0.0, 20.0, 20.0]);
+        let price_counts = record_batch.column(5).as_primitive::<UInt64Type>();
+        assert_eq!(price_counts.values().to_vec(), vec![2, 1, 1]);
+
+        let record_batch = aggregation_record_batch(&results, "max_price")?;
+        assert_eq!(record_batch.num_rows(), 1);
+        assert_eq!(
+            record_batch
+                .column(0)
+                .as_primitive::<Float64Type>()
+                .value(0),
+            31.0
+        );
+        assert!(aggregation_record_batch(&results, "unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filters_aggregation_record_batch() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for (category_value, price_value) in [("book", 10.0), ("toy", 25.0), ("book", 30.0)] {
+            index_writer.add_document(doc!(category => category_value, price => price_value))?;
+        }
+        index_writer.commit()?;
+
+        let aggregations: Aggregations = serde_json::from_value(serde_json::json!({
+            "books": {
+                "filter": {"query": "category:book"},
+                "aggs": {"avg_price": {"avg": {"field": "price"}}}
+            },
+            "by_category": {
+                "filters": {
+                    "filters": {
+                        "toys": {"query": "category:toy"},
+                        "books": {"query": "category:book"}
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let collector =
+            AggregationCollector::from_aggs(aggregations, AggregationLimitsGuard::default());
+        let results: AggregationResults =
+            index.reader()?.searcher().search(&AllQuery, &collector)?;
+
+        let record_batch = aggregation_record_batch(&results, "books")?;
+        assert_eq!(record_batch.num_rows(), 1);
+        let doc_counts = record_batch.column(0).as_primitive::<UInt64Type>();
+        assert_eq!(doc_counts.values().to_vec(), vec![2]);
+        let avg_prices = record_batch.column(1).as_primitive::<Float64Type>();
+        assert_eq!(avg_prices.values().to_vec(), vec![20.0]);
+
+        let record_batch = aggregation_record_batch(&results, "by_category")?;
+        let filter_names = record_batch.column(0).as_string::<i32>();
+        assert_eq!(
+            filter_names.iter().collect::<Vec<_>>(),
+            [Some("books"), Some("toys")]
+        );
+        let doc_counts = record_batch.column(1).as_primitive::<UInt64Type>();
+        assert_eq!(doc_counts.values().to_vec(), vec![2, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_aggregation_record_batch() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for (category_value, price_value) in [("book", 10.0), ("toy", 25.0), ("book", 30.0)] {
+            index_writer.add_document(doc!(category => category_value, price => price_value))?;
+        }
+        index_writer.commit()?;
+
+        let aggregations: Aggregations = serde_json::from_value(serde_json::json!({
+            "pages": {
+                "composite": {
+                    "sources": [
+                        {"category": {"terms": {"field": "category"}}},
+                        {"price": {"histogram": {"field": "price", "interval": 20.0}}}
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+        let collector =
+            AggregationCollector::from_aggs(aggregations, AggregationLimitsGuard::default());
+        let results: AggregationResults =
+            index.reader()?.searcher().search(&AllQuery, &collector)?;
+
+        let record_batch = aggregation_record_batch(&results, "pages")?;
+        let categories = record_batch.column(0).as_string::<i32>();
+        assert_eq!(
+            categories.iter().collect::<Vec<_>>(),
+            [Some("book"), Some("book"), Some("toy")]
+        );
+        let prices = record_batch.column(1).as_primitive::<Float64Type>();
+        assert_eq!(prices.values().to_vec(), vec![0.0, 20.0, 20.0]);
+        let doc_counts = record_batch.column(2).as_primitive::<UInt64Type>();
+        assert_eq!(doc_counts.values().to_vec(), vec![1, 1, 1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,702 @@
+//! DataFusion [`TableProvider`] over the fast fields of an index.
+//!
+//! A [`TantivyTableProvider`] exposes each fast field of an index as a column of a table, so
+//! that it can be queried with SQL and joined with other tables. Filters are pushed down to
+//! tantivy queries, and only the columns of the matching documents are decoded:
+//! - comparisons of a column with a literal, `BETWEEN` and `IN` lists become range queries on
+//!   the fast field,
+//! - `IS NOT NULL` becomes an exists query,
+//! - `tantivy_match('query')` runs a full text query, written in the
+//!   [query parser](crate::query::QueryParser) syntax, see [`match_udf()`],
+//! - `AND` and `OR` of the above become boolean queries.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! use datafusion::arrow::array::AsArray;
+//! use datafusion::arrow::datatypes::UInt64Type;
+//! use datafusion::prelude::SessionContext;
+//! use tantivy::export::datafusion::{match_udf, TantivyTableProvider};
+//! use tantivy::schema::{Schema, FAST, STRING, TEXT};
+//! use tantivy::{doc, Index, IndexWriter};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let category = schema_builder.add_text_field("category", STRING | FAST);
+//! let price = schema_builder.add_u64_field("price", FAST);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+//! index_writer.add_document(doc!(title => "running shoes", category => "shoes", price => 80u64))?;
+//! index_writer.add_document(doc!(title => "trail shoes", category => "shoes", price => 120u64))?;
+//! index_writer.add_document(doc!(title => "running tops", category => "pants", price => 30u64))?;
+//! index_writer.commit()?;
+//!
+//! let ctx = SessionContext::new();
+//! ctx.register_udf(match_udf());
+//! ctx.register_table("products", Arc::new(TantivyTableProvider::try_new(index.reader()?)?))?;
+//! let sql = "SELECT category, SUM(price) AS total FROM products \
+//!            WHERE tantivy_match('title:running') GROUP BY category ORDER BY category";
+//! let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+//! let batches = runtime.block_on(async { ctx.sql(sql).await?.collect().await })?;
+//! let totals = batches[0].column(1).as_primitive::<UInt64Type>();
+//! assert_eq!(totals.values().to_vec(), vec![30, 80]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use arrow_array::{RecordBatch, RecordBatchOptions};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef, TimeUnit};
+use async_trait::async_trait;
+use datafusion::catalog::Session;
+use datafusion::common::{plan_err, DataFusionError, ScalarValue};
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::Result as DataFusionResult;
+use datafusion::logical_expr::expr::InList;
+use datafusion::logical_expr::{
+    Between, BinaryExpr, ColumnarValue, Expr, Operator, ScalarUDF, ScalarUDFImpl, Signature,
+    TableProviderFilterPushDown, Volatility,
+};
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+
+use crate::export::arrow::field_to_array;
+use crate::query::{
+    AllQuery, BooleanQuery, EnableScoring, ExistsQuery, Query, QueryParser, RangeQuery,
+};
+use crate::schema::{Field, FieldType, Schema};
+use crate::{DateTime, DocId, IndexReader, TantivyError, Term};
+
+/// Name of the function running a full text query, see [`match_udf()`].
+pub const MATCH_FUNCTION_NAME: &str = "tantivy_match";
+
+/// Returns the `tantivy_match(query)` function, which has to be registered in the DataFusion
+/// session to run full text queries on a [`TantivyTableProvider`].
+///
+/// `query` is parsed by a [`QueryParser`], whose default fields are the indexed text fields,
+/// unless set with [`TantivyTableProvider::set_default_fields()`].
+///
+/// The function is only evaluated by tantivy, when it is pushed down to the scan of a
+/// tantivy table: its use elsewhere, for instance in a disjunction with a filter that cannot
+/// be pushed down, returns an error.
+pub fn match_udf() -> ScalarUDF {
+    ScalarUDF::new_from_impl(MatchUdf {
+        signature: Signature::exact(vec![DataType::Utf8], Volatility::Immutable),
+    })
+}
+
+#[derive(Debug)]
+struct MatchUdf {
+    signature: Signature,
+}
+
+impl ScalarUDFImpl for MatchUdf {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        MATCH_FUNCTION_NAME
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DataFusionResult<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn invoke_batch(
+        &self,
+        _args: &[ColumnarValue],
+        _number_rows: usize,
+    ) -> DataFusionResult<ColumnarValue> {
+        plan_err!(
+            "{MATCH_FUNCTION_NAME}() can only be used in filters pushed down to a tantivy table"
+        )
+    }
+}
+
+fn datafusion_error(err: TantivyError) -> DataFusionError {
+    DataFusionError::External(Box::new(err))
+}
+
+/// Returns the Arrow type of the single values of a fast field, or `None` if the type of the
+/// field cannot be exported to Arrow.
+fn arrow_data_type(field_type: &FieldType) -> Option<DataType> {
+    let data_type = match field_type {
+        FieldType::U64(_) => DataType::UInt64,
+        FieldType::I64(_) => DataType::Int64,
+        FieldType::F64(_) => DataType::Float64,
+        FieldType::Bool(_) => DataType::Boolean,
+        FieldType::Date(_) => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        FieldType::Str(_) => DataType::Utf8,
+        FieldType::Bytes(_) => DataType::Binary,
+        _ => return None,
+    };
+    Some(data_type)
+}
+
+/// DataFusion [`TableProvider`] with one column per fast field of an index, and one row per
+/// document.
+///
+/// Each scan runs on the latest [`Searcher`](crate::Searcher) of the reader, with one
+/// partition per segment. The fast fields that are multivalued in one of the segments when the
+/// provider is created are exposed as lists.
+pub struct TantivyTableProvider {
+    reader: IndexReader,
+    arrow_schema: SchemaRef,
+    query_parser: QueryParser,
+}
+
+impl fmt::Debug for TantivyTableProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TantivyTableProvider")
+            .field("schema", &self.arrow_schema)
+            .finish()
+    }
+}
+
+impl TantivyTableProvider {
+    /// Creates a table provider over the fast fields of the index of `reader`.
+    ///
+    /// Fast fields of unsupported types (IP addresses, facets and JSON fields) are skipped.
+    pub fn try_new(reader: IndexReader) -> crate::Result<TantivyTableProvider> {
+        let searcher = reader.searcher();
+        let schema = searcher.schema();
+        let mut arrow_fields = Vec::new();
+        for (_, field_entry) in schema.fields() {
+            if !field_entry.is_fast() {
+                continue;
+            }
+            let Some(data_type) = arrow_data_type(field_entry.field_type()) else {
+                continue;
+            };
+            let mut is_multivalued = false;
+            for segment_reader in searcher.segment_readers() {
+                let array = field_to_array(segment_reader, field_entry.name(), &[], None)?;
+                is_multivalued |= matches!(array.data_type(), DataType::List(_));
+            }
+            let data_type = if is_multivalued {
+                DataType::List(Arc::new(ArrowField::new_list_field(data_type, true)))
+            } else {
+                data_type
+            };
+            arrow_fields.push(ArrowField::new(field_entry.name(), data_type, true));
+        }
+        let default_fields = schema
+            .fields()
+            .filter(|(_, field_entry)| {
+                field_entry.is_indexed() && matches!(field_entry.field_type(), FieldType::Str(_))
+            })
+            .map(|(field, _)| field)
+            .collect();
+        let query_parser = QueryParser::for_index(searcher.index(), default_fields);
+        Ok(TantivyTableProvider {
+            reader,
+            arrow_schema: Arc::new(ArrowSchema::new(arrow_fields)),
+            query_parser,
+        })
+    }
+
+    /// Sets the fields searched by the `tantivy_match()` queries that do not specify a field.
+    pub fn set_default_fields(&mut self, default_fields: Vec<Field>) {
+        self.query_parser = QueryParser::for_index(self.reader.searcher().index(), default_fields);
+    }
+
+    /// Converts a DataFusion filter to a tantivy query.
+    ///
+    /// Returns `None` if the filter cannot be pushed down.
+    fn filter_to_query(
+        &self,
+        schema: &Schema,
+        filter: &Expr,
+    ) -> crate::Result<Option<Box<dyn Query>>> {
+        let query: Box<dyn Query> = match filter {
+            Expr::BinaryExpr(BinaryExpr { left, op, right })
+                if matches!(op, Operator::And | Operator::Or) =>
+            {
+                let (Some(left), Some(right)) = (
+                    self.filter_to_query(schema, left)?,
+                    self.filter_to_query(schema, right)?,
+                ) else {
+                    return Ok(None);
+                };
+                if *op == Operator::And {
+                    Box::new(BooleanQuery::intersection(vec![left, right]))
+                } else {
+                    Box::new(BooleanQuery::union(vec![left, right]))
+                }
+            }
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let (column, literal, op) = match (left.as_ref(), right.as_ref()) {
+                    (Expr::Column(column), Expr::Literal(literal)) => (column, literal, *op),
+                    (Expr::Literal(literal), Expr::Column(column)) => {
+                        let Some(op) = op.swap() else {
+                            return Ok(None);
+                        };
+                        (column, literal, op)
+                    }
+                    _ => return Ok(None),
+                };
+                let Some(term) = self.column_term(schema, &column.name, literal) else {
+                    return Ok(None);
+                };
+                let (lower_bound, upper_bound) = match op {
+                    Operator::Eq => (Bound::Included(term.clone()), Bound::Included(term)),
+                    Operator::Lt => (Bound::Unbounded, Bound::Excluded(term)),
+                    Operator::LtEq => (Bound::Unbounded, Bound::Included(term)),
+                    Operator::Gt => (Bound::Excluded(term), Bound::Unbounded),
+                    Operator::GtEq => (Bound::Included(term), Bound::Unbounded),
+                    _ => return Ok(None),
+                };
+                Box::new(RangeQuery::new(lower_bound, upper_bound))
+            }
+            Expr::Between(Between {
+                expr,
+                negated: false,
+                low,
+                high,
+            }) => {
+                let (Expr::Column(column), Expr::Literal(low), Expr::Literal(high)) =
+                    (expr.as_ref(), low.as_ref(), high.as_ref())
+                else {
+                    return Ok(None);
+                };
+                let (Some(low), Some(high)) = (
+                    self.column_term(schema, &column.name, low),
+                    self.column_term(schema, &column.name, high),
+                ) else {
+                    return Ok(None);
+                };
+                Box::new(RangeQuery::new(Bound::Included(low), Bound::Included(high)))
+            }
+            Expr::InList(InList {
+                expr,
+                list,
+                negated: false,
+            }) => {
+                let Expr::Column(column) = expr.as_ref() else {
+                    return Ok(None);
+                };
+                let mut term_queries: Vec<Box<dyn Query>> = Vec::with_capacity(list.len());
+                for item in list {
+                    let Expr::Literal(literal) = item else {
+                        return Ok(None);
+                    };
+                    let Some(term) = self.column_term(schema, &column.name, literal) else {
+                        return Ok(None);
+                    };
+                    term_queries.push(Box::new(RangeQuery::new(
+                        Bound::Included(term.clone()),
+                        Bound::Included(term),
+                    )));
+                }
+                Box::new(BooleanQuery::union(term_queries))
+            }
+            Expr::IsNotNull(expr) => {
+                let Expr::Column(column) = expr.as_ref() else {
+                    return Ok(None);
+                };
+                if self.scalar_column_field(schema, &column.name).is_none() {
+                    return Ok(None);
+                }
+                Box::new(ExistsQuery::new(column.name.clone(), false))
+            }
+            Expr::Column(column) => {
+                let Some(term) =
+                    self.column_term(schema, &column.name, &ScalarValue::Boolean(Some(true)))
+                else {
+                    return Ok(None);
+                };
+                Box::new(RangeQuery::new(
+                    Bound::Included(term.clone()),
+                    Bound::Included(term),
+                ))
+            }
+            Expr::ScalarFunction(scalar_function)
+                if scalar_function.func.name() == MATCH_FUNCTION_NAME =>
+            {
+                let [Expr::Literal(ScalarValue::Utf8(Some(query)))] = &scalar_function.args[..]
+                else {
+                    return Ok(None);
+                };
+                self.query_parser.parse_query(query)?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(query))
+    }
+
+    /// Returns the field of a column holding single values.
+    fn scalar_column_field(&self, schema: &Schema, column_name: &str) -> Option<Field> {
+        let arrow_field = self.arrow_schema.field_with_name(column_name).ok()?;
+        if matches!(arrow_field.data_type(), DataType::List(_)) {
+            return None;
+        }
+        schema.get_field(column_name).ok()
+    }
+
+    /// Converts a literal compared to a column to a term of the field of the column.
+    ///
+    /// Returns `None` if the literal cannot be represented exactly in the type of the field.
+    fn column_term(
+        &self,
+        schema: &Schema,
+        column_name: &str,
+        literal: &ScalarValue,
+    ) -> Option<Term> {
+        let field = self.scalar_column_field(schema, column_name)?;
+        let term = match (schema.get_field_entry(field).field_type(), literal) {
+            (FieldType::U64(_), _) => {
+                Term::from_field_u64(field, integer(literal)?.try_into().ok()?)
+            }
+            (FieldType::I64(_), _) => {
+                Term::from_field_i64(field, integer(literal)?.try_into().ok()?)
+            }
+            (FieldType::F64(_), ScalarValue::Float64(Some(val))) => {
+                Term::from_field_f64(field, *val)
+            }
+            (FieldType::F64(_), ScalarValue::Float32(Some(val))) => {
+                Term::from_field_f64(field, f64::from(*val))
+            }
+            (FieldType::Bool(_), ScalarValue::Boolean(Some(val))) => {
+                Term::from_field_bool(field, *val)
+            }
+            (FieldType::Date(_), _) => Term::from_field_date(
+                field,
+                DateTime::from_timestamp_nanos(timestamp_nanos(literal)?),
+            ),
+            (
+                FieldType::Str(_),
+                ScalarValue::Utf8(Some(text))
+                | ScalarValue::LargeUtf8(Some(text))
+                | ScalarValue::Utf8View(Some(text)),
+            ) => Term::from_field_text(field, text),
+            _ => return None,
+        };
+        Some(term)
+    }
+
+    fn filters_to_query(&self, schema: &Schema, filters: &[Expr]) -> crate::Result<Box<dyn Query>> {
+        let mut queries = Vec::with_capacity(filters.len());
+        for filter in filters {
+            let query = self.filter_to_query(schema, filter)?.ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "Filter {filter} cannot be pushed down to tantivy."
+                ))
+            })?;
+            queries.push(query);
+        }
+        if queries.is_empty() {
+            return Ok(Box::new(AllQuery));
+        }
+        Ok(Box::new(BooleanQuery::intersection(queries)))
+    }
+
+    fn record_batches(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> crate::Result<(SchemaRef, Vec<Vec<RecordBatch>>)> {
+        let column_ords: Vec<usize> = match projection {
+            Some(projection) => projection.clone(),
+            None => (0..self.arrow_schema.fields().len()).collect(),
+        };
+        let projected_schema = Arc::new(
+            self.arrow_schema
+                .project(&column_ords)
+                .map_err(|err| TantivyError::InvalidArgument(err.to_string()))?,
+        );
+        let searcher = self.reader.searcher();
+        let query = self.filters_to_query(searcher.schema(), filters)?;
+        let weight = query.weight(EnableScoring::disabled_from_searcher(&searcher))?;
+        let mut num_remaining_docs = limit.unwrap_or(usize::MAX);
+        let mut partitions = Vec::with_capacity(searcher.segment_readers().len());
+        for segment_reader in searcher.segment_readers() {
+            if num_remaining_docs == 0 {
+                break;
+            }
+            let mut doc_ids: Vec<DocId> = Vec::new();
+            let alive_bitset = segment_reader.alive_bitset();
+            weight.for_each_no_score(segment_reader, &mut |docs| {
+                doc_ids.extend(docs.iter().copied().filter(|&doc| {
+                    alive_bitset.map_or(true, |alive_bitset| alive_bitset.is_alive(doc))
+                }));
+            })?;
+            doc_ids.truncate(num_remaining_docs);
+            num_remaining_docs -= doc_ids.len();
+            let columns = projected_schema
+                .fields()
+                .iter()
+                .map(|arrow_field| {
+                    let as_list = matches!(arrow_field.data_type(), DataType::List(_));
+                    field_to_array(segment_reader, arrow_field.name(), &doc_ids, Some(as_list))
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+            let options = RecordBatchOptions::new().with_row_count(Some(doc_ids.len()));
+            let record_batch =
+                RecordBatch::try_new_with_options(projected_schema.clone(), columns, &options)
+                    .map_err(|err| TantivyError::InternalError(err.to_string()))?;
+            partitions.push(vec![record_batch]);
+        }
+        if partitions.is_empty() {
+            partitions.push(Vec::new());
+        }
+        Ok((projected_schema, partitions))
+    }
+}
+
+/// Returns the value of an integer literal.
+fn integer(literal: &ScalarValue) -> Option<i128> {
+    let val = match *literal {
+        ScalarValue::Int8(Some(val)) => i128::from(val),
+        ScalarValue::Int16(Some(val)) => i128::from(val),
+        ScalarValue::Int32(Some(val)) => i128::from(val),
+        ScalarValue::Int64(Some(val)) => i128::from(val),
+        ScalarValue::UInt8(Some(val)) => i128::from(val),
+        ScalarValue::UInt16(Some(val)) => i128::from(val),
+        ScalarValue::UInt32(Some(val)) => i128::from(val),
+        ScalarValue::UInt64(Some(val)) => i128::from(val),
+        _ => return None,
+    };
+    Some(val)
+}
+
+/// Returns the value of a timestamp literal, in nanoseconds.
+fn timestamp_nanos(literal: &ScalarValue) -> Option<i64> {
+    match *literal {
+        ScalarValue::TimestampSecond(Some(secs), _) => secs.checked_mul(1_000_000_000),
+        ScalarValue::TimestampMillisecond(Some(millis), _) => millis.checked_mul(1_000_000),
+        ScalarValue::TimestampMicrosecond(Some(micros), _) => micros.checked_mul(1_000),
+        ScalarValue::TimestampNanosecond(Some(nanos), _) => Some(nanos),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl TableProvider for TantivyTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.arrow_schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        let (projected_schema, partitions) = self
+            .record_batches(projection, filters, limit)
+            .map_err(datafusion_error)?;
+        let memory_exec = MemoryExec::try_new(&partitions, projected_schema, None)?;
+        Ok(Arc::new(memory_exec))
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> DataFusionResult<Vec<TableProviderFilterPushDown>> {
+        let searcher = self.reader.searcher();
+        filters
+            .iter()
+            .map(|filter| {
+                let query_opt = self
+                    .filter_to_query(searcher.schema(), filter)
+                    .map_err(datafusion_error)?;
+                Ok(if query_opt.is_some() {
+                    TableProviderFilterPushDown::Exact
+                } else {
+                    TableProviderFilterPushDown::Unsupported
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Int64Type, UInt64Type};
+    use arrow_array::{Array, RecordBatch};
+    use datafusion::prelude::SessionContext;
+
+    use super::{match_udf, TantivyTableProvider};
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::{DateTime, Index, IndexWriter, Term};
+
+    fn run_sql(ctx: &SessionContext, sql: &str) -> datafusion::error::Result<Vec<RecordBatch>> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async { ctx.sql(sql).await?.collect().await })
+    }
+
+    fn u64_values(batches: &[RecordBatch], column: usize) -> Vec<u64> {
+        batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(column)
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect()
+    }
+
+    fn explain(ctx: &SessionContext, sql: &str) -> String {
+        let batches = run_sql(ctx, &format!("EXPLAIN {sql}")).unwrap();
+        let plans = batches[0].column(1).as_string::<i32>();
+        plans.iter().flatten().collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn test_table_provider() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST | INDEXED);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(
+            id => 0u64,
+            title => "running shoes",
+            category => "shoes",
+            delta => -3i64,
+            date => DateTime::from_timestamp_secs(10),
+            tag => "a",
+            tag => "b",
+        ))?;
+        index_writer.add_document(doc!(
+            id => 1u64,
+            title => "trail shoes",
+            category => "shoes",
+            date => DateTime::from_timestamp_secs(20),
+        ))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(
+            id => 2u64,
+            title => "running tops",
+            category => "tops",
+            delta => 5i64,
+        ))?;
+        index_writer.add_document(doc!(id => 3u64, title => "hat", category => "hats"))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(id, 3));
+        index_writer.commit()?;
+
+        let provider = TantivyTableProvider::try_new(index.reader()?)?;
+        let arrow_schema = datafusion::datasource::TableProvider::schema(&provider);
+        let column_names: Vec<&str> = arrow_schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect();
+        assert_eq!(column_names, ["id", "category", "delta", "date", "tag"]);
+        assert!(matches!(
+            arrow_schema.field(4).data_type(),
+            arrow_schema::DataType::List(_)
+        ));
+        let ctx = SessionContext::new();
+        ctx.register_udf(match_udf());
+        ctx.register_table("products", Arc::new(provider)).unwrap();
+
+        let ids = |sql: &str| -> Vec<u64> {
+            let mut ids = u64_values(&run_sql(&ctx, sql).unwrap(), 0);
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids("SELECT id FROM products"), [0, 1, 2]);
+        assert_eq!(
+            ids("SELECT id FROM products WHERE tantivy_match('running')"),
+            [0, 2]
+        );
+        assert_eq!(
+            ids("SELECT id FROM products WHERE category = 'shoes' AND id > 0"),
+            [1]
+        );
+        assert_eq!(
+            ids("SELECT id FROM products WHERE category IN ('tops', 'hats') OR delta < 0"),
+            [0, 2]
+        );
+        assert_eq!(
+            ids("SELECT id FROM products WHERE id BETWEEN 1 AND 2"),
+            [1, 2]
+        );
+        assert_eq!(
+            ids("SELECT id FROM products WHERE delta IS NOT NULL"),
+            [0, 2]
+        );
+        assert_eq!(
+            ids("SELECT id FROM products WHERE date >= TIMESTAMP '1970-01-01T00:00:15'"),
+            [1]
+        );
+        // `delta <> 5` is not pushed down, and evaluated by DataFusion.
+        assert_eq!(
+            ids("SELECT id FROM products WHERE tantivy_match('shoes') AND delta <> 5"),
+            [0]
+        );
+
+        let pushed_down = explain(
+            &ctx,
+            "SELECT id FROM products WHERE tantivy_match('running') AND delta <> 5",
+        );
+        assert!(pushed_down.contains("full_filters=[tantivy_match(Utf8(\"running\"))]"));
+        assert!(pushed_down.contains("Filter: products.delta != Int64(5)"));
+
+        let batches = run_sql(
+            &ctx,
+            "SELECT delta, tag FROM products WHERE category = 'shoes' ORDER BY id",
+        )
+        .unwrap();
+        let deltas = batches[0].column(0).as_primitive::<Int64Type>();
+        assert_eq!(deltas.value(0), -3);
+        assert!(deltas.is_null(1));
+        let tags = batches[0].column(1).as_list::<i32>();
+        assert_eq!(tags.value(0).as_string::<i32>().value(1), "b");
+        assert!(tags.value(1).is_empty());
+
+        let batches = run_sql(&ctx, "SELECT COUNT(*) FROM products").unwrap();
+        assert_eq!(batches[0].column(0).as_primitive::<Int64Type>().value(0), 3);
+        let batches = run_sql(&ctx, "SELECT id FROM products LIMIT 1").unwrap();
+        assert_eq!(u64_values(&batches, 0).len(), 1);
+
+        // The query parser errors are reported.
+        assert!(run_sql(
+            &ctx,
+            "SELECT id FROM products WHERE tantivy_match('missing:a')"
+        )
+        .is_err());
+        // `tantivy_match` cannot be evaluated by DataFusion.
+        assert!(run_sql(
+            &ctx,
+            "SELECT id FROM products WHERE tantivy_match('shoes') OR delta <> 5"
+        )
+        .is_err());
+        Ok(())
+    }
+}
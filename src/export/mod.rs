@@ -0,0 +1,12 @@
+//! Helpers to export the content of an index to other formats.
+//!
+//! Formats live behind their own feature flag:
+//! - `arrow`: export of fast field columns and of aggregation results as Arrow `RecordBatch`es,
+//!   see the `arrow` module.
+//! - `datafusion`: a DataFusion `TableProvider` running SQL on the fast field columns, see the
+//!   `datafusion` module.
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "datafusion")]
+pub mod datafusion;
@@ -0,0 +1,222 @@
+use std::io;
+
+use columnar::{StrColumn, TermMerger, TermsWithSegmentOrd};
+
+use crate::index::{SegmentId, SegmentReader};
+use crate::{SegmentOrdinal, TantivyError};
+
+/// Maps the term ordinals of a `str` fast field in every segment of a searcher to global
+/// ordinals, shared by all of the segments.
+///
+/// The term ordinals of a `str` column are local to its segment: the same string usually has a
+/// different ordinal in each segment. The global ordinal of a term is its rank among the sorted
+/// terms of all of the segments, so that collectors can bucket documents on small integers
+/// across segments, and only resolve the strings of the buckets they return.
+///
+/// Global ordinals are built with [`Searcher::global_ordinals`](crate::Searcher::global_ordinals),
+/// which builds them the first time they are requested for a field, and shares them with the
+/// later calls on the same searcher generation.
+pub struct GlobalOrdinals {
+    field_name: String,
+    segment_ids: Vec<SegmentId>,
+    // For each segment, the column of the field, if the segment has one.
+    columns: Vec<Option<StrColumn>>,
+    // For each segment, the global ordinal of each of its term ordinals.
+    segment_to_global_ords: Vec<Vec<u32>>,
+    // For each global ordinal, a segment containing the term, with its term ordinal.
+    global_to_segment_ords: Vec<(SegmentOrdinal, u64)>,
+}
+
+impl GlobalOrdinals {
+    /// Builds the global ordinals of the `str` fast field `field_name` for the given segments.
+    ///
+    /// The segments without a `str` column for the field have no terms.
+    pub fn build(
+        segment_readers: &[SegmentReader],
+        field_name: &str,
+    ) -> crate::Result<GlobalOrdinals> {
+        let columns = segment_readers
+            .iter()
+            .map(|segment_reader| segment_reader.fast_fields().str(field_name))
+            .collect::<crate::Result<Vec<Option<StrColumn>>>>()?;
+        let mut segment_to_global_ords: Vec<Vec<u32>> = columns
+            .iter()
+            .map(|column_opt| {
+                column_opt
+                    .as_ref()
+                    .map(|column| vec![0; column.num_terms()])
+                    .unwrap_or_default()
+            })
+            .collect();
+        let term_streams = columns
+            .iter()
+            .enumerate()
+            .filter_map(|(segment_ord, column_opt)| {
+                let column = column_opt.as_ref()?;
+                Some(
+                    column
+                        .dictionary()
+                        .stream()
+                        .map(|terms| TermsWithSegmentOrd { terms, segment_ord }),
+                )
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut global_to_segment_ords: Vec<(SegmentOrdinal, u64)> = Vec::new();
+        let mut term_merger = TermMerger::new(term_streams);
+        while term_merger.advance() {
+            let global_ord = u32::try_from(global_to_segment_ords.len()).map_err(|_| {
+                TantivyError::InvalidArgument(format!(
+                    "Field {field_name:?} has too many terms for global ordinals"
+                ))
+            })?;
+            let mut first_segment_ord_opt = None;
+            for (segment_ord, term_ord) in term_merger.matching_segments() {
+                segment_to_global_ords[segment_ord][term_ord as usize] = global_ord;
+                first_segment_ord_opt.get_or_insert((segment_ord as SegmentOrdinal, term_ord));
+            }
+            global_to_segment_ords.push(first_segment_ord_opt.unwrap());
+        }
+        Ok(GlobalOrdinals {
+            field_name: field_name.to_string(),
+            segment_ids: segment_readers
+                .iter()
+                .map(SegmentReader::segment_id)
+                .collect(),
+            columns,
+            segment_to_global_ords,
+            global_to_segment_ords,
+        })
+    }
+
+    /// Returns the name of the field.
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    /// Returns the number of distinct terms over all of the segments.
+    pub fn num_terms(&self) -> usize {
+        self.global_to_segment_ords.len()
+    }
+
+    /// Returns the number of segments.
+    pub fn num_segments(&self) -> usize {
+        self.segment_ids.len()
+    }
+
+    /// Returns the id of the segment with the given ordinal.
+    pub fn segment_id(&self, segment_ord: SegmentOrdinal) -> SegmentId {
+        self.segment_ids[segment_ord as usize]
+    }
+
+    /// Returns the column of the field in the given segment, if it has one.
+    pub fn column(&self, segment_ord: SegmentOrdinal) -> Option<&StrColumn> {
+        self.columns[segment_ord as usize].as_ref()
+    }
+
+    /// Returns the global ordinals of the terms of the given segment, indexed by their term
+    /// ordinal.
+    pub fn segment_mapping(&self, segment_ord: SegmentOrdinal) -> &[u32] {
+        &self.segment_to_global_ords[segment_ord as usize]
+    }
+
+    /// Returns the global ordinal of the term ordinal `term_ord` of the given segment.
+    #[inline]
+    pub fn global_ord(&self, segment_ord: SegmentOrdinal, term_ord: u64) -> u32 {
+        self.segment_to_global_ords[segment_ord as usize][term_ord as usize]
+    }
+
+    /// Returns the global ordinal of `term`, if one of the segments contains it.
+    pub fn str_to_ord(&self, term: &str) -> io::Result<Option<u32>> {
+        for (segment_ord, column_opt) in self.columns.iter().enumerate() {
+            let Some(column) = column_opt else {
+                continue;
+            };
+            if let Some(term_ord) = column.dictionary().term_ord(term)? {
+                return Ok(Some(
+                    self.segment_to_global_ords[segment_ord][term_ord as usize],
+                ));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolves the term of a global ordinal into `output`.
+    ///
+    /// Returns false if the global ordinal is out of bounds.
+    pub fn ord_to_str(&self, global_ord: u32, output: &mut String) -> io::Result<bool> {
+        let Some(&(segment_ord, term_ord)) = self.global_to_segment_ords.get(global_ord as usize)
+        else {
+            output.clear();
+            return Ok(false);
+        };
+        let column = self.columns[segment_ord as usize]
+            .as_ref()
+            .expect("a segment holding a term has a column");
+        column.ord_to_str(term_ord, output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_global_ordinals() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let color = schema_builder.add_text_field("color", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(color => "red"))?;
+        index_writer.add_document(doc!(color => "green"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(color => "blue"))?;
+        index_writer.add_document(doc!(color => "red"))?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let global_ordinals = searcher.global_ordinals("color")?;
+        assert_eq!(global_ordinals.num_terms(), 3);
+        let mut term = String::new();
+        let terms: Vec<String> = (0..3)
+            .map(|global_ord| {
+                global_ordinals.ord_to_str(global_ord, &mut term).unwrap();
+                term.clone()
+            })
+            .collect();
+        assert_eq!(terms, ["blue", "green", "red"]);
+        assert!(!global_ordinals.ord_to_str(3, &mut term)?);
+        assert_eq!(global_ordinals.str_to_ord("green")?, Some(1));
+        assert_eq!(global_ordinals.str_to_ord("red")?, Some(2));
+        assert_eq!(global_ordinals.str_to_ord("purple")?, None);
+
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let segment_ord = segment_ord as u32;
+            assert_eq!(
+                global_ordinals.segment_id(segment_ord),
+                segment_reader.segment_id()
+            );
+            let Some(column) = global_ordinals.column(segment_ord) else {
+                assert!(global_ordinals.segment_mapping(segment_ord).is_empty());
+                continue;
+            };
+            for term_ord in 0..column.num_terms() as u64 {
+                let mut segment_term = String::new();
+                column.ord_to_str(term_ord, &mut segment_term)?;
+                let global_ord = global_ordinals.global_ord(segment_ord, term_ord);
+                global_ordinals.ord_to_str(global_ord, &mut term)?;
+                assert_eq!(segment_term, term);
+            }
+        }
+        // Global ordinals are built once per searcher generation.
+        assert!(std::sync::Arc::ptr_eq(
+            &global_ordinals,
+            &searcher.global_ordinals("color")?
+        ));
+        assert_eq!(searcher.global_ordinals("missing")?.num_terms(), 0);
+        Ok(())
+    }
+}
@@ -17,15 +17,21 @@
 //! Fast fields are stored in with [different codecs](columnar). The best codec is detected
 //! automatically, when serializing.
 //!
+//! Sparse fields do not pay for the documents without a value: only the values present are
+//! stored, along with an index of the documents having a value. This index is itself encoded
+//! as a list of doc ids or as a bitset, depending on the density of each block of documents.
+//!
 //! Read access performance is comparable to that of an array lookup.
 
-pub use columnar::Column;
 use columnar::MonotonicallyMappableToU64;
+pub use columnar::{Column, ColumnStatistics};
 
 pub use self::alive_bitset::{intersect_alive_bitsets, write_alive_bitset, AliveBitSet};
 pub use self::error::{FastFieldNotAvailableError, Result};
 pub use self::facet_reader::FacetReader;
+pub use self::global_ordinals::GlobalOrdinals;
 pub use self::readers::FastFieldReaders;
+pub use self::warmer::FastFieldWarmer;
 pub use self::writer::FastFieldsWriter;
 use crate::schema::Type;
 use crate::DateTime;
@@ -33,7 +39,9 @@ use crate::DateTime;
 mod alive_bitset;
 mod error;
 mod facet_reader;
+mod global_ordinals;
 mod readers;
+mod warmer;
 mod writer;
 
 /// Trait for types that are allowed for fast fields:
@@ -75,12 +83,16 @@ impl FastValue for DateTime {
 #[cfg(test)]
 mod tests {
 
+    use std::io;
     use std::net::Ipv6Addr;
     use std::ops::{Range, RangeInclusive};
     use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
-    use columnar::StrColumn;
-    use common::{ByteCount, DateTimePrecision, HasLen, TerminatingWrite};
+    use columnar::column_values::{ColumnStats, VecColumn};
+    use columnar::{ColumnValues, StrColumn};
+    use common::{ByteCount, DateTimePrecision, HasLen, OwnedBytes, TerminatingWrite};
     use once_cell::sync::Lazy;
     use rand::prelude::SliceRandom;
     use rand::rngs::StdRng;
@@ -91,8 +103,8 @@ mod tests {
     use crate::index::SegmentId;
     use crate::merge_policy::NoMergePolicy;
     use crate::schema::{
-        DateOptions, Facet, FacetOptions, Field, JsonObjectOptions, Schema, SchemaBuilder,
-        TantivyDocument, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
+        DateOptions, Facet, FacetOptions, Field, JsonObjectOptions, NumericOptions, Schema,
+        SchemaBuilder, TantivyDocument, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
     };
     use crate::time::OffsetDateTime;
     use crate::tokenizer::{LowerCaser, RawTokenizer, TextAnalyzer, TokenizerManager};
@@ -132,7 +144,7 @@ mod tests {
         }
         let file = directory.open_read(path).unwrap();
 
-        assert_eq!(file.len(), 80);
+        assert_eq!(file.len(), 114);
         let fast_field_readers = FastFieldReaders::open(file, SCHEMA.clone()).unwrap();
         let column = fast_field_readers
             .u64("field")
@@ -182,7 +194,7 @@ mod tests {
             write.terminate().unwrap();
         }
         let file = directory.open_read(path).unwrap();
-        assert_eq!(file.len(), 108);
+        assert_eq!(file.len(), 142);
         let fast_field_readers = FastFieldReaders::open(file, SCHEMA.clone()).unwrap();
         let col = fast_field_readers
             .u64("field")
@@ -215,7 +227,7 @@ mod tests {
             write.terminate().unwrap();
         }
         let file = directory.open_read(path).unwrap();
-        assert_eq!(file.len(), 81);
+        assert_eq!(file.len(), 115);
         let fast_field_readers = FastFieldReaders::open(file, SCHEMA.clone()).unwrap();
         let fast_field_reader = fast_field_readers
             .u64("field")
@@ -247,7 +259,7 @@ mod tests {
             write.terminate().unwrap();
         }
         let file = directory.open_read(path).unwrap();
-        assert_eq!(file.len(), 4476);
+        assert_eq!(file.len(), 4510);
         {
             let fast_field_readers = FastFieldReaders::open(file, SCHEMA.clone()).unwrap();
             let col = fast_field_readers
@@ -280,7 +292,7 @@ mod tests {
             write.terminate().unwrap();
         }
         let file = directory.open_read(path).unwrap();
-        assert_eq!(file.len(), 252);
+        assert_eq!(file.len(), 286);
 
         {
             let fast_field_readers = FastFieldReaders::open(file, schema).unwrap();
@@ -774,7 +786,7 @@ mod tests {
             write.terminate().unwrap();
         }
         let file = directory.open_read(path).unwrap();
-        assert_eq!(file.len(), 84);
+        assert_eq!(file.len(), 118);
         let fast_field_readers = FastFieldReaders::open(file, schema).unwrap();
         let bool_col = fast_field_readers.bool("field_bool").unwrap();
         assert_eq!(bool_col.first(0), Some(true));
@@ -806,7 +818,7 @@ mod tests {
             write.terminate().unwrap();
         }
         let file = directory.open_read(path).unwrap();
-        assert_eq!(file.len(), 96);
+        assert_eq!(file.len(), 130);
         let readers = FastFieldReaders::open(file, schema).unwrap();
         let bool_col = readers.bool("field_bool").unwrap();
         for i in 0..25 {
@@ -831,7 +843,7 @@ mod tests {
             write.terminate().unwrap();
         }
         let file = directory.open_read(path).unwrap();
-        assert_eq!(file.len(), 86);
+        assert_eq!(file.len(), 120);
         let fastfield_readers = FastFieldReaders::open(file, schema).unwrap();
         let col = fastfield_readers.bool("field_bool").unwrap();
         assert_eq!(col.first(0), None);
@@ -1272,6 +1284,122 @@ mod tests {
         assert!(text_fast_field.ords().values_for_doc(1u32).eq([0]));
     }
 
+    #[test]
+    fn test_fast_field_missing_values() -> crate::Result<()> {
+        use std::ops::Bound;
+
+        use crate::collector::{Count, TopDocs};
+        use crate::query::{AllQuery, RangeQuery};
+        use crate::{DocAddress, Order, Term};
+
+        let mut schema_builder = Schema::builder();
+        let val_field = schema_builder.add_u64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(val_field=>0u64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(val_field=>3u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let column = searcher.segment_reader(0).fast_fields().u64("val")?;
+        assert_eq!(column.first(0), Some(0));
+        assert_eq!(column.first(1), None);
+        assert_eq!(column.first(2), Some(3));
+
+        let range_query = RangeQuery::new(
+            Bound::Included(Term::from_field_u64(val_field, 0)),
+            Bound::Included(Term::from_field_u64(val_field, 10)),
+        );
+        assert_eq!(searcher.search(&range_query, &Count)?, 2);
+
+        // Documents without a value come last, whatever the order.
+        for (order, expected_docs) in [(Order::Asc, [0, 2, 1]), (Order::Desc, [2, 0, 1])] {
+            let top_docs = searcher.search(
+                &AllQuery,
+                &TopDocs::with_limit(3).order_by_u64_field("val", order),
+            )?;
+            let docs: Vec<u32> = top_docs
+                .iter()
+                .map(|(_, DocAddress { doc_id, .. })| *doc_id)
+                .collect();
+            assert_eq!(docs, expected_docs);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparse_fast_field_is_compact() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let dense_field = schema_builder.add_u64_field("dense", FAST);
+        let sparse_field = schema_builder.add_u64_field("sparse", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for doc_id in 0..100_000u64 {
+            let val = (doc_id * 7_919) % 1_000_003;
+            let mut doc = doc!(dense_field=>val);
+            // Less than 1% of the documents have a sparse value.
+            if doc_id % 200 == 0 {
+                doc.add_u64(sparse_field, val);
+            }
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+
+        let dense_num_bytes = fast_fields.column_num_bytes("dense")?;
+        let sparse_num_bytes = fast_fields.column_num_bytes("sparse")?;
+        assert!(sparse_num_bytes.get_bytes() * 50 < dense_num_bytes.get_bytes());
+
+        let sparse_column = fast_fields.u64("sparse")?;
+        assert_eq!(
+            sparse_column.get_cardinality(),
+            columnar::Cardinality::Optional
+        );
+        assert_eq!(sparse_column.first(0), Some(0));
+        assert_eq!(sparse_column.first(1), None);
+        assert_eq!(
+            sparse_column.first(99_800),
+            Some(99_800 * 7_919 % 1_000_003)
+        );
+        assert_eq!(sparse_column.values.num_vals(), 500);
+        Ok(())
+    }
+
+    #[test]
+    fn test_str_fast_field_ord_lookups() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let city_field = schema_builder.add_text_field("city", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(city_field=>"paris"))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(city_field=>"berlin"))?;
+        index_writer.add_document(doc!(city_field=>"paris"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let str_column = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .str("city")?
+            .unwrap();
+        let mut term_ords = [Some(u64::MAX); 4];
+        str_column.first_term_ords(&[0, 1, 2, 3], &mut term_ords);
+        assert_eq!(term_ords, [Some(1), None, Some(0), Some(1)]);
+
+        let mut terms = Vec::new();
+        assert!(str_column.sorted_ords_to_str(0..2, |term| {
+            terms.push(term.to_string());
+            Ok(())
+        })?);
+        assert_eq!(terms, ["berlin", "paris"]);
+        assert!(!str_column.sorted_ords_to_bytes([1, 2].into_iter(), |_| Ok(()))?);
+        Ok(())
+    }
+
     #[test]
     fn test_shadowing_fast_field_with_expand_dots() {
         let mut schema_builder = Schema::builder();
@@ -1303,4 +1431,93 @@ mod tests {
         let vals: Vec<i64> = column.values_for_doc(0u32).collect();
         assert_eq!(&vals, &[33]);
     }
+
+    /// Codec storing the values as is, counting the columns it serialized.
+    struct PlainCodec;
+
+    static PLAIN_CODEC_NUM_COLUMNS: AtomicUsize = AtomicUsize::new(0);
+
+    struct PlainCodecEstimator;
+
+    impl columnar::ColumnCodecEstimator for PlainCodecEstimator {
+        fn collect(&mut self, _value: u64) {}
+
+        fn estimate(&self, stats: &ColumnStats) -> Option<u64> {
+            Some(8 * stats.num_rows as u64)
+        }
+
+        fn serialize(
+            &self,
+            _stats: &ColumnStats,
+            vals: &mut dyn Iterator<Item = u64>,
+            wrt: &mut dyn io::Write,
+        ) -> io::Result<()> {
+            PLAIN_CODEC_NUM_COLUMNS.fetch_add(1, Ordering::SeqCst);
+            for val in vals {
+                wrt.write_all(&val.to_le_bytes())?;
+            }
+            Ok(())
+        }
+    }
+
+    impl columnar::CustomColumnCodec for PlainCodec {
+        fn name(&self) -> &str {
+            "plain"
+        }
+
+        fn estimator(&self) -> Box<dyn columnar::ColumnCodecEstimator> {
+            Box::new(PlainCodecEstimator)
+        }
+
+        fn load(&self, bytes: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>> {
+            let vals: Vec<u64> = bytes
+                .as_slice()
+                .chunks_exact(8)
+                .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+                .collect();
+            Ok(Arc::new(VecColumn::from(vals)))
+        }
+    }
+
+    #[test]
+    fn test_fast_field_custom_codec() -> crate::Result<()> {
+        columnar::register_column_codec(Arc::new(PlainCodec));
+        let mut schema_builder = Schema::builder();
+        let num_field =
+            schema_builder.add_i64_field("num", NumericOptions::from(FAST).set_fast_codec("plain"));
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(num_field=>-3i64))?;
+        index_writer.add_document(doc!(num_field=>1_000i64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(num_field=>7i64))?;
+        index_writer.commit()?;
+        assert_eq!(PLAIN_CODEC_NUM_COLUMNS.load(Ordering::SeqCst), 2);
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        assert_eq!(PLAIN_CODEC_NUM_COLUMNS.load(Ordering::SeqCst), 3);
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let column = searcher.segment_reader(0).fast_fields().i64("num")?;
+        let vals: Vec<i64> = (0..3).flat_map(|doc| column.values_for_doc(doc)).collect();
+        assert_eq!(vals, vec![-3, 1_000, 7]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_unregistered_codec() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field(
+            "num",
+            NumericOptions::from(FAST).set_fast_codec("unregistered"),
+        );
+        assert!(matches!(
+            FastFieldsWriter::from_schema(&schema_builder.build()),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+    }
 }
@@ -1,9 +1,10 @@
+use std::collections::HashMap;
 use std::io;
 use std::net::Ipv6Addr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use columnar::{
-    BytesColumn, Column, ColumnType, ColumnValues, ColumnarReader, DynamicColumn,
+    BytesColumn, Column, ColumnStatistics, ColumnType, ColumnValues, ColumnarReader, DynamicColumn,
     DynamicColumnHandle, HasAssociatedColumnType, StrColumn,
 };
 use common::ByteCount;
@@ -16,18 +17,83 @@ use crate::TantivyError;
 
 /// Provides access to all of the BitpackedFastFieldReader.
 ///
-/// Internally, `FastFieldReaders` have preloaded fast field readers,
-/// and just wraps several `HashMap`.
+/// Opening the fast fields of a segment only reads the dictionary of its columns.
+/// Columns are opened lazily, the first time they are accessed, and are then kept open
+/// for the lifetime of the segment reader. Columns can also be opened ahead of time with
+/// [`FastFieldReaders::warm`], or with a [`FastFieldWarmer`](crate::fastfield::FastFieldWarmer).
 #[derive(Clone)]
 pub struct FastFieldReaders {
     columnar: Arc<ColumnarReader>,
     schema: Schema,
+    // Columns opened so far, for each column name.
+    opened_columns: Arc<RwLock<HashMap<String, Vec<DynamicColumn>>>>,
 }
 
 impl FastFieldReaders {
     pub(crate) fn open(fast_field_file: FileSlice, schema: Schema) -> io::Result<FastFieldReaders> {
         let columnar = Arc::new(ColumnarReader::open(fast_field_file)?);
-        Ok(FastFieldReaders { columnar, schema })
+        Ok(FastFieldReaders {
+            columnar,
+            schema,
+            opened_columns: Default::default(),
+        })
+    }
+
+    /// Returns all of the columns associated with a resolved column name, opening them if
+    /// they were not opened yet.
+    fn open_columns(&self, column_name: &str) -> io::Result<Vec<DynamicColumn>> {
+        if let Some(columns) = self.opened_columns.read().unwrap().get(column_name) {
+            return Ok(columns.clone());
+        }
+        let columns = self
+            .columnar
+            .read_columns(column_name)?
+            .iter()
+            .map(DynamicColumnHandle::open)
+            .collect::<io::Result<Vec<DynamicColumn>>>()?;
+        self.opened_columns
+            .write()
+            .unwrap()
+            .insert(column_name.to_string(), columns.clone());
+        Ok(columns)
+    }
+
+    fn open_column(
+        &self,
+        field_name: &str,
+        column_type: ColumnType,
+    ) -> crate::Result<Option<DynamicColumn>> {
+        let Some(resolved_field_name) = self.resolve_field(field_name)? else {
+            return Ok(None);
+        };
+        Ok(self
+            .open_columns(&resolved_field_name)?
+            .into_iter()
+            .find(|column| column.column_type() == column_type))
+    }
+
+    /// Opens the columns of the given fast fields ahead of time, so that the first
+    /// queries accessing them do not pay for it.
+    ///
+    /// Field names follow the same rules as [`FastFieldReaders::column_opt`], and may
+    /// refer to paths within JSON fields. Names without any matching column are ignored.
+    pub fn warm(&self, field_names: &[&str]) -> crate::Result<()> {
+        for field_name in field_names {
+            if let Some(resolved_field_name) = self.resolve_field(field_name)? {
+                self.open_columns(&resolved_field_name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of columns opened so far.
+    pub fn num_opened_columns(&self) -> usize {
+        self.opened_columns
+            .read()
+            .unwrap()
+            .values()
+            .map(Vec::len)
+            .sum()
     }
 
     fn resolve_field(&self, column_name: &str) -> crate::Result<Option<String>> {
@@ -120,15 +186,42 @@ impl FastFieldReaders {
         T: HasAssociatedColumnType,
         DynamicColumn: Into<Option<Column<T>>>,
     {
-        let Some(dynamic_column_handle) =
-            self.dynamic_column_handle(field_name, T::column_type())?
-        else {
+        let Some(dynamic_column) = self.open_column(field_name, T::column_type())? else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
         Ok(dynamic_column.into())
     }
 
+    /// Returns a typed column associated to a given field name, converting numerical columns
+    /// to the requested numerical type if needed.
+    ///
+    /// This is useful for paths of JSON fields: the type of their column depends on the values
+    /// found in each segment. For instance, `attributes.price` may be a `u64` column in a segment
+    /// and a `f64` column in another one.
+    ///
+    /// Returns `None` if no column can be converted to the requested type.
+    pub fn column_opt_coerced<T>(&self, field_name: &str) -> crate::Result<Option<Column<T>>>
+    where
+        T: HasAssociatedColumnType,
+        DynamicColumn: Into<Option<Column<T>>>,
+    {
+        let Some(numerical_type) = T::column_type().numerical_type() else {
+            return self.column_opt(field_name);
+        };
+        let Some(resolved_field_name) = self.resolve_field(field_name)? else {
+            return Ok(None);
+        };
+        for dynamic_column in self.open_columns(&resolved_field_name)? {
+            if !dynamic_column.is_numerical() {
+                continue;
+            }
+            if let Some(dynamic_column) = dynamic_column.coerce_numerical(numerical_type) {
+                return Ok(dynamic_column.into());
+            }
+        }
+        Ok(None)
+    }
+
     /// Returns the number of `bytes` associated with a column.
     ///
     /// Returns 0 if the column does not exist.
@@ -197,23 +290,17 @@ impl FastFieldReaders {
 
     /// Returns a `str` column.
     pub fn str(&self, field_name: &str) -> crate::Result<Option<StrColumn>> {
-        let Some(dynamic_column_handle) =
-            self.dynamic_column_handle(field_name, ColumnType::Str)?
-        else {
+        let Some(dynamic_column) = self.open_column(field_name, ColumnType::Str)? else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
         Ok(dynamic_column.into())
     }
 
     /// Returns a `bytes` column.
     pub fn bytes(&self, field_name: &str) -> crate::Result<Option<BytesColumn>> {
-        let Some(dynamic_column_handle) =
-            self.dynamic_column_handle(field_name, ColumnType::Bytes)?
-        else {
+        let Some(dynamic_column) = self.open_column(field_name, ColumnType::Bytes)? else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
         Ok(dynamic_column.into())
     }
 
@@ -234,6 +321,24 @@ impl FastFieldReaders {
         Ok(dynamic_column_handle_opt)
     }
 
+    /// Returns the statistics of the column of the given type associated with `field_name`.
+    ///
+    /// The statistics (cardinality, null count, number of values and min/max values) are
+    /// computed when the segment is serialized, and do not require opening the column.
+    ///
+    /// Returns `Ok(None)` if there is no such column, or if the segment was written by a
+    /// version of tantivy that did not store column statistics.
+    pub fn column_statistics(
+        &self,
+        field_name: &str,
+        column_type: ColumnType,
+    ) -> crate::Result<Option<ColumnStatistics>> {
+        let column_statistics_opt = self
+            .dynamic_column_handle(field_name, column_type)?
+            .and_then(|column_handle| column_handle.statistics());
+        Ok(column_statistics_opt)
+    }
+
     /// Returns all `dynamic_column_handle` that match the given field name.
     pub fn dynamic_column_handles(
         &self,
@@ -385,10 +490,14 @@ impl FastFieldReaders {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Weak};
+
     use columnar::ColumnType;
 
+    use crate::fastfield::FastFieldWarmer;
+
     use crate::schema::{JsonObjectOptions, Schema, FAST};
-    use crate::{Index, IndexWriter, TantivyDocument};
+    use crate::{Index, IndexReader, IndexWriter, TantivyDocument, Warmer};
 
     #[test]
     fn test_fast_field_reader_resolve_with_dynamic_internal() {
@@ -518,4 +627,51 @@ mod tests {
             .unwrap();
         assert_eq!(foo_subcolumns.len(), 0);
     }
+
+    #[test]
+    fn test_fast_field_reader_lazy_columns() {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let json = schema_builder.add_json_field("json", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        for i in 0..10u64 {
+            let mut attributes = serde_json::Map::new();
+            for attr in 0..50 {
+                attributes.insert(format!("attr{attr}"), json!(i * attr));
+            }
+            index_writer
+                .add_document(doc!(id => i, json => serde_json::Value::Object(attributes)))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+
+        let warmer = Arc::new(FastFieldWarmer::new(vec![
+            "json.attr3".to_string(),
+            "json.missing".to_string(),
+        ]));
+        let reader: IndexReader = index
+            .reader_builder()
+            .warmers(vec![Arc::downgrade(&warmer) as Weak<dyn Warmer>])
+            .try_into()
+            .unwrap();
+        let searcher = reader.searcher();
+        let fast_fields = searcher.segment_reader(0u32).fast_fields();
+        assert_eq!(fast_fields.columnar().num_columns(), 51);
+        assert_eq!(fast_fields.num_opened_columns(), 1);
+
+        let id_column = fast_fields.u64("id").unwrap();
+        assert_eq!(id_column.first(3), Some(3));
+        assert_eq!(fast_fields.num_opened_columns(), 2);
+        let attr_column = fast_fields.column_opt_coerced::<f64>("json.attr3").unwrap();
+        assert_eq!(attr_column.unwrap().first(2), Some(6.0));
+        fast_fields.u64("id").unwrap();
+        assert_eq!(fast_fields.num_opened_columns(), 2);
+        // Clones of the segment reader share the opened columns.
+        let segment_reader = searcher.segment_reader(0u32).clone();
+        segment_reader.fast_fields().warm(&["json.attr7"]).unwrap();
+        assert_eq!(fast_fields.num_opened_columns(), 3);
+        assert!(segment_reader.fast_fields().warm(&["unknown"]).is_ok());
+    }
 }
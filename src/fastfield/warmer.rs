@@ -0,0 +1,32 @@
+use crate::{Searcher, SearcherGeneration, Warmer};
+
+/// [`Warmer`] opening the columns of a list of fast fields in every new segment.
+///
+/// Fast field columns are opened lazily, the first time a query accesses them. Registering
+/// this warmer with [`IndexReaderBuilder::warmers`](crate::IndexReaderBuilder::warmers)
+/// moves this cost to the reload of the reader, for the fields known to be used.
+pub struct FastFieldWarmer {
+    field_names: Vec<String>,
+}
+
+impl FastFieldWarmer {
+    /// Creates a warmer for the given fast fields.
+    ///
+    /// Field names may refer to paths within JSON fields, see
+    /// [`FastFieldReaders::warm`](crate::fastfield::FastFieldReaders::warm).
+    pub fn new(field_names: Vec<String>) -> FastFieldWarmer {
+        FastFieldWarmer { field_names }
+    }
+}
+
+impl Warmer for FastFieldWarmer {
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()> {
+        let field_names: Vec<&str> = self.field_names.iter().map(String::as_str).collect();
+        for segment_reader in searcher.segment_readers() {
+            segment_reader.fast_fields().warm(&field_names)?;
+        }
+        Ok(())
+    }
+
+    fn garbage_collect(&self, _live_generations: &[&SearcherGeneration]) {}
+}
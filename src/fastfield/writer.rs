@@ -79,6 +79,21 @@ impl FastFieldsWriter {
                     per_field_tokenizer[field_id.field_id() as usize] = Some(text_analyzer);
                 }
             }
+            let fast_codec_opt = match field_entry.field_type() {
+                FieldType::U64(numeric_options)
+                | FieldType::I64(numeric_options)
+                | FieldType::F64(numeric_options) => numeric_options.get_fast_codec(),
+                FieldType::Date(date_options) => date_options.get_fast_codec(),
+                _ => None,
+            };
+            if let Some(codec_name) = fast_codec_opt {
+                let codec = columnar::get_column_codec(codec_name).ok_or_else(|| {
+                    TantivyError::InvalidArgument(format!(
+                        "Column codec {codec_name:?} is not registered"
+                    ))
+                })?;
+                columnar_writer.set_column_codec(field_entry.name(), codec);
+            }
 
             let sort_values_within_row = value_type == Type::Facet;
             if let Some(column_type) = value_type_to_column_type(value_type) {
@@ -1,9 +1,8 @@
 use std::collections::HashSet;
-use std::fmt;
-#[cfg(feature = "mmap")]
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread::available_parallelism;
+use std::{fmt, io};
 
 use super::segment::Segment;
 use super::segment_reader::merge_field_meta_data;
@@ -12,19 +11,28 @@ use crate::core::{Executor, META_FILEPATH};
 use crate::directory::error::OpenReadError;
 #[cfg(feature = "mmap")]
 use crate::directory::MmapDirectory;
-use crate::directory::{Directory, ManagedDirectory, RamDirectory, INDEX_WRITER_LOCK};
+use crate::directory::{
+    Directory, ManagedDirectory, RamDirectory, ReadOnlyDirectory, INDEX_WRITER_LOCK,
+};
 use crate::error::{DataCorruption, TantivyError};
-use crate::index::{IndexMeta, SegmentId, SegmentMeta, SegmentMetaInventory};
+use crate::index::{
+    IndexInspection, IndexMeta, IndexRepairReport, IndexValidationReport, NearRealTimeState,
+    SegmentId, SegmentMeta, SegmentMetaInventory, SnapshotManifest, SnapshotResult,
+};
 use crate::indexer::index_writer::{
     IndexWriterOptions, MAX_NUM_THREAD, MEMORY_BUDGET_NUM_BYTES_MIN,
 };
 use crate::indexer::segment_updater::save_metas;
-use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
+use crate::indexer::{
+    IndexWriter, OfflineSegmentBuilder, OfflineSegmentBuilderOptions, SingleSegmentIndexWriter,
+};
+use crate::query::{Bm25Similarity, Similarity, SimilarityManager};
 use crate::reader::{IndexReader, IndexReaderBuilder};
 use crate::schema::document::Document;
 use crate::schema::{Field, FieldType, Schema};
+use crate::store::StoreFamily;
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
-use crate::SegmentReader;
+use crate::{Opstamp, SegmentReader};
 
 fn load_metas(
     directory: &dyn Directory,
@@ -106,6 +114,7 @@ pub struct IndexBuilder {
     index_settings: IndexSettings,
     tokenizer_manager: TokenizerManager,
     fast_field_tokenizer_manager: TokenizerManager,
+    similarity_manager: SimilarityManager,
 }
 impl Default for IndexBuilder {
     fn default() -> Self {
@@ -120,6 +129,7 @@ impl IndexBuilder {
             index_settings: IndexSettings::default(),
             tokenizer_manager: TokenizerManager::default(),
             fast_field_tokenizer_manager: TokenizerManager::default(),
+            similarity_manager: SimilarityManager::default(),
         }
     }
 
@@ -149,6 +159,12 @@ impl IndexBuilder {
         self
     }
 
+    /// Set the similarities.
+    pub fn similarities(mut self, similarities: SimilarityManager) -> Self {
+        self.similarity_manager = similarities;
+        self
+    }
+
     /// Creates a new index using the [`RamDirectory`].
     ///
     /// The index will be allocated in anonymous memory.
@@ -192,6 +208,19 @@ impl IndexBuilder {
         Ok(index_simple_writer)
     }
 
+    /// Creates a new index and returns an [`OfflineSegmentBuilder`] writing
+    /// a single segment into it.
+    ///
+    /// It expects an originally empty directory.
+    pub fn offline_segment_builder<D: Document>(
+        self,
+        dir: impl Into<Box<dyn Directory>>,
+        options: OfflineSegmentBuilderOptions,
+    ) -> crate::Result<OfflineSegmentBuilder<D>> {
+        let index = self.create(dir)?;
+        OfflineSegmentBuilder::new(index, options)
+    }
+
     /// Creates a new index in a temp directory.
     ///
     /// The index will use the [`MmapDirectory`] in a newly created directory.
@@ -222,6 +251,7 @@ impl IndexBuilder {
         }
         let mut index = Index::open(dir)?;
         index.set_tokenizers(self.tokenizer_manager.clone());
+        index.set_similarities(self.similarity_manager.clone());
         if index.schema() == self.get_expect_schema()? {
             Ok(index)
         } else {
@@ -232,7 +262,8 @@ impl IndexBuilder {
     }
 
     fn validate(&self) -> crate::Result<()> {
-        if let Some(_schema) = self.schema.as_ref() {
+        if let Some(schema) = self.schema.as_ref() {
+            StoreFamily::from_settings(&self.index_settings.docstore_families, schema)?;
             Ok(())
         } else {
             Err(TantivyError::InvalidArgument(
@@ -258,6 +289,7 @@ impl IndexBuilder {
         let mut index = Index::open_from_metas(directory, &metas, SegmentMetaInventory::default());
         index.set_tokenizers(self.tokenizer_manager);
         index.set_fast_field_tokenizers(self.fast_field_tokenizer_manager);
+        index.set_similarities(self.similarity_manager);
         Ok(index)
     }
 }
@@ -271,7 +303,9 @@ pub struct Index {
     executor: Executor,
     tokenizers: TokenizerManager,
     fast_field_tokenizers: TokenizerManager,
+    similarities: SimilarityManager,
     inventory: SegmentMetaInventory,
+    near_real_time: Arc<NearRealTimeState>,
 }
 
 impl Index {
@@ -389,8 +423,10 @@ impl Index {
             schema,
             tokenizers: TokenizerManager::default(),
             fast_field_tokenizers: TokenizerManager::default(),
+            similarities: SimilarityManager::default(),
             executor: Executor::single_thread(),
             inventory,
+            near_real_time: Arc::default(),
         }
     }
 
@@ -414,6 +450,40 @@ impl Index {
         &self.fast_field_tokenizers
     }
 
+    /// Setter for the similarity manager.
+    pub fn set_similarities(&mut self, similarities: SimilarityManager) {
+        self.similarities = similarities;
+    }
+
+    /// Accessor for the similarity manager.
+    pub fn similarities(&self) -> &SimilarityManager {
+        &self.similarities
+    }
+
+    /// Get the similarity a specific field is scored with.
+    ///
+    /// Fields which are not indexed as text are scored with BM25.
+    pub fn similarity_for_field(&self, field: Field) -> crate::Result<Arc<dyn Similarity>> {
+        let field_entry = self.schema.get_field_entry(field);
+        let indexing_options_opt = match field_entry.field_type() {
+            FieldType::JsonObject(options) => options.get_text_indexing_options(),
+            FieldType::Str(options) => options.get_indexing_options(),
+            _ => None,
+        };
+        let Some(indexing_options) = indexing_options_opt else {
+            return Ok(Arc::new(Bm25Similarity::default()));
+        };
+        self.similarities
+            .get(indexing_options.similarity())
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "No Similarity {:?} found for field {:?}",
+                    indexing_options.similarity(),
+                    field_entry.name()
+                ))
+            })
+    }
+
     /// Get the tokenizer associated with a specific field.
     pub fn tokenizer_for_field(&self, field: Field) -> crate::Result<TextAnalyzer> {
         let field_entry = self.schema.get_field_entry(field);
@@ -497,6 +567,12 @@ impl Index {
         Ok(merge_field_meta_data(fields_metadata, &self.schema()))
     }
 
+    /// Segments published by the `IndexWriter` through
+    /// [`IndexWriter::refresh()`] and not committed yet.
+    pub(crate) fn near_real_time(&self) -> &NearRealTimeState {
+        &self.near_real_time
+    }
+
     /// Creates a new segment_meta (Advanced user only).
     ///
     /// As long as the `SegmentMeta` lives, the files associated with the
@@ -516,6 +592,54 @@ impl Index {
         Ok(index)
     }
 
+    /// Opens the index in strictly read-only mode.
+    ///
+    /// Nothing is ever written to the directory, not even lock files, and the index stays
+    /// pinned to the commit of the `meta.json` file at the time it is opened. Opening an
+    /// [`IndexWriter`] fails right away. See [`ReadOnlyDirectory`].
+    pub fn open_read_only<T: Into<Box<dyn Directory>>>(directory: T) -> crate::Result<Index> {
+        let directory = ReadOnlyDirectory::wrap(directory.into())?;
+        Index::open(directory)
+    }
+
+    /// Opens the index in strictly read-only mode, like [`Index::open_read_only`], checking
+    /// that its last commit is the one of the given `opstamp`.
+    ///
+    /// Returns an error if the index was committed since, or is not at that commit yet.
+    pub fn open_read_only_at<T: Into<Box<dyn Directory>>>(
+        directory: T,
+        opstamp: Opstamp,
+    ) -> crate::Result<Index> {
+        let index = Index::open_read_only(directory)?;
+        let index_opstamp = index.load_metas()?.opstamp;
+        if index_opstamp != opstamp {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The last commit of the index has the opstamp {index_opstamp}, not the \
+                 requested opstamp {opstamp}"
+            )));
+        }
+        Ok(index)
+    }
+
+    /// Opens an index stored in an [`AsyncDirectory`](crate::directory::AsyncDirectory),
+    /// without blocking on IO.
+    ///
+    /// The data needed to open the index and its segments is fetched asynchronously. The
+    /// returned index is a read-only snapshot of the last commit.
+    ///
+    /// The data read afterwards has to be fetched with the async read paths first, e.g.
+    /// [`InvertedIndexReader::warm_postings`](crate::InvertedIndexReader::warm_postings),
+    /// [`InvertedIndexReader::read_postings_async`](crate::InvertedIndexReader::read_postings_async)
+    /// or [`Searcher::doc_async`](crate::Searcher::doc_async): synchronous reads of data that
+    /// was not fetched fail with an [`io::ErrorKind::WouldBlock`](std::io::ErrorKind::WouldBlock)
+    /// error.
+    #[cfg(feature = "quickwit")]
+    pub async fn open_async<D: crate::directory::AsyncDirectory>(
+        directory: D,
+    ) -> crate::Result<Index> {
+        crate::directory::open_index_async(Arc::new(directory)).await
+    }
+
     /// Reads the index meta file from the directory.
     pub fn load_metas(&self) -> crate::Result<IndexMeta> {
         load_metas(self.directory(), &self.inventory)
@@ -671,6 +795,69 @@ impl Index {
         &mut self.directory
     }
 
+    /// Writes the last commit of the index into a single read-only bundle file, to be served
+    /// with a [`BundleDirectory`](crate::directory::BundleDirectory).
+    ///
+    /// The bundle contains the files of the searchable segments and the `meta.json` of the
+    /// commit. Garbage collection is blocked while the files are copied, so that the index
+    /// can keep being written to.
+    pub fn write_bundle<W: io::Write>(&self, mut output: W) -> crate::Result<()> {
+        crate::directory::write_bundle(self, &mut output)
+    }
+
+    /// Creates a snapshot of the last commit of the index in a directory of the local
+    /// filesystem, which has to be empty or not exist yet.
+    ///
+    /// The snapshot holds exactly the files of the commit, its `meta.json` and a
+    /// [`SnapshotManifest`](crate::index::SnapshotManifest) listing them, so that it can be
+    /// opened as an index. Garbage collection is blocked while the snapshot is created, but
+    /// the index can keep being written to.
+    ///
+    /// Files are hard linked when the index is stored in the same filesystem, which is safe
+    /// since tantivy never modifies a file once written, and copied otherwise.
+    pub fn create_snapshot<P: AsRef<Path>>(&self, path: P) -> crate::Result<SnapshotResult> {
+        crate::index::create_snapshot(self, path.as_ref(), false)
+    }
+
+    /// Updates a snapshot of the index created with [`Index::create_snapshot`] to its last
+    /// commit.
+    ///
+    /// The files already in the snapshot are reused, so that only the segments created
+    /// since the previous snapshot get transferred. The files of the previous snapshot that
+    /// are not part of the new one are deleted once the new one is complete.
+    pub fn create_incremental_snapshot<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> crate::Result<SnapshotResult> {
+        crate::index::create_snapshot(self, path.as_ref(), true)
+    }
+
+    /// Checks a snapshot created with [`Index::create_snapshot`]: its manifest has to match
+    /// its `meta.json`, and all of its files their size and checksum in the manifest.
+    ///
+    /// Returns the manifest of the snapshot.
+    pub fn verify_snapshot<P: AsRef<Path>>(snapshot_path: P) -> crate::Result<SnapshotManifest> {
+        crate::index::verify_snapshot(snapshot_path.as_ref())
+    }
+
+    /// Restores a snapshot created with [`Index::create_snapshot`] into a directory, and opens
+    /// the restored index.
+    ///
+    /// The manifest of the snapshot is validated first, and each file is checked against its
+    /// size and checksum while it is copied. The `meta.json` of the snapshot is installed last,
+    /// so that the directory only switches to the restored commit once all of its files are
+    /// in place. If the directory already holds an index, its files get garbage collected by
+    /// the next index writer.
+    ///
+    /// Restoring fails if an index writer is open on the directory.
+    #[cfg(feature = "mmap")]
+    pub fn restore_from_snapshot<P: AsRef<Path>, Q: AsRef<Path>>(
+        snapshot_path: P,
+        directory_path: Q,
+    ) -> crate::Result<Index> {
+        crate::index::restore_from_snapshot(snapshot_path.as_ref(), directory_path.as_ref())
+    }
+
     /// Reads the meta.json and returns the list of
     /// `SegmentMeta` from the last commit.
     pub fn searchable_segment_metas(&self) -> crate::Result<Vec<SegmentMeta>> {
@@ -686,6 +873,14 @@ impl Index {
             .collect())
     }
 
+    /// Returns the metadata of the last commit: its segments with their document counts and
+    /// files, the settings and the schema of the index.
+    ///
+    /// This saves tools from parsing the `meta.json` file, and the report can be serialized.
+    pub fn inspect(&self) -> crate::Result<IndexInspection> {
+        crate::index::inspect_index(self)
+    }
+
     /// Returns the set of corrupted files
     pub fn validate_checksum(&self) -> crate::Result<HashSet<PathBuf>> {
         let managed_files = self.directory.list_managed_files();
@@ -705,6 +900,38 @@ impl Index {
         }
         Ok(damaged_files)
     }
+
+    /// Checks the integrity of the last commit of the index, in the manner of Lucene's
+    /// `CheckIndex`.
+    ///
+    /// For each segment, the checksums of its files are verified first. The content of the
+    /// segments whose files are intact is then cross-checked:
+    /// - the postings of each term are sorted and match its doc freq,
+    /// - the fieldnorms cover all documents, and match the term frequencies of text fields,
+    /// - the fast field columns and the doc store have one entry per document,
+    /// - the alive bitset matches the number of deleted documents of the segment meta.
+    ///
+    /// The inconsistencies are gathered in the returned report, rather than returned as an
+    /// error. This reads all of the postings of the index, and can take a while.
+    pub fn validate(&self) -> crate::Result<IndexValidationReport> {
+        crate::index::validate_index(self)
+    }
+
+    /// Repairs an index whose last commit has damaged segments, by removing them from the
+    /// index.
+    ///
+    /// The segments failing [`Index::validate`] are dropped and the `meta.json` is rewritten
+    /// without them. Tantivy only keeps the files of the last commit, so older commits cannot
+    /// be restored instead. The report lists the documents lost, along with the stored fields
+    /// that could still be salvaged from the doc store of the dropped segments.
+    ///
+    /// The index is left as is if all of its segments are valid.
+    ///
+    /// # Errors
+    /// If an `IndexWriter` is working on the index, returns `TantivyError::LockFailure`.
+    pub fn repair(&self) -> crate::Result<IndexRepairReport> {
+        crate::index::repair_index(self)
+    }
 }
 
 impl fmt::Debug for Index {
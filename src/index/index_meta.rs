@@ -141,6 +141,9 @@ impl SegmentMeta {
             SegmentComponent::TempStore => ".store.temp".to_string(),
             SegmentComponent::FastFields => ".fast".to_string(),
             SegmentComponent::FieldNorms => ".fieldnorm".to_string(),
+            SegmentComponent::Points => ".points".to_string(),
+            SegmentComponent::Vectors => ".vec".to_string(),
+            SegmentComponent::TermVectors => ".tv".to_string(),
             SegmentComponent::Delete => format!(".{}.del", self.delete_opstamp().unwrap_or(0)),
         });
         PathBuf::from(path)
@@ -258,6 +261,28 @@ pub struct IndexSettings {
     #[serde(default = "default_docstore_blocksize")]
     /// The size of each block that will be compressed and written to disk
     pub docstore_blocksize: usize,
+    /// Groups of stored fields which are compressed separately from the other stored fields,
+    /// with their own compression settings.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub docstore_families: Vec<DocStoreFamily>,
+}
+
+/// A group of stored fields, kept in their own blocks of the doc store.
+///
+/// The stored fields which do not belong to any family are compressed according to
+/// [`IndexSettings::docstore_compression`] and [`IndexSettings::docstore_blocksize`].
+/// Families make it possible, for instance, to compress large bodies with a high-ratio
+/// compressor while keeping small metadata fields fast to decompress.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct DocStoreFamily {
+    /// The names of the stored fields of the family.
+    pub fields: Vec<String>,
+    /// The `Compressor` used to compress the blocks of the family.
+    #[serde(default)]
+    pub compression: Compressor,
+    /// The size of each block of the family that will be compressed and written to disk
+    #[serde(default = "default_docstore_blocksize")]
+    pub blocksize: usize,
 }
 
 /// Must be a function to be compatible with serde defaults
@@ -271,6 +296,7 @@ impl Default for IndexSettings {
             docstore_compression: Compressor::default(),
             docstore_blocksize: default_docstore_blocksize(),
             docstore_compress_dedicated_thread: true,
+            docstore_families: Vec::new(),
         }
     }
 }
@@ -433,9 +459,11 @@ mod tests {
             index_settings: IndexSettings {
                 docstore_compression: crate::store::Compressor::Zstd(ZstdCompressor {
                     compression_level: Some(4),
+                    dictionary_size: None,
                 }),
                 docstore_blocksize: 1_000_000,
                 docstore_compress_dedicated_thread: true,
+                docstore_families: Vec::new(),
             },
             segments: Vec::new(),
             schema,
@@ -499,7 +527,8 @@ mod tests {
             IndexSettings {
                 docstore_compression: Compressor::default(),
                 docstore_compress_dedicated_thread: true,
-                docstore_blocksize: 16_384
+                docstore_blocksize: 16_384,
+                docstore_families: Vec::new(),
             }
         );
         {
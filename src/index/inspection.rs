@@ -0,0 +1,177 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Index, IndexSettings, SegmentComponent, SegmentId, SegmentMeta};
+use crate::schema::Schema;
+use crate::Opstamp;
+
+/// A file of a segment.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SegmentFileInspection {
+    /// Component stored in the file.
+    pub component: SegmentComponent,
+    /// Path of the file, relative to the directory of the index.
+    pub path: PathBuf,
+    /// Size of the file, in bytes, including its footer.
+    pub num_bytes: u64,
+}
+
+/// Metadata of a segment, as returned by [`Index::inspect`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SegmentInspection {
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Number of documents of the segment, including the deleted ones.
+    pub max_doc: u32,
+    /// Number of alive documents.
+    pub num_docs: u32,
+    /// Number of deleted documents.
+    pub num_deleted_docs: u32,
+    /// Opstamp of the last delete operation applied to the segment, if any.
+    pub delete_opstamp: Option<Opstamp>,
+    /// Files of the segment. Optional components without a file are omitted.
+    pub files: Vec<SegmentFileInspection>,
+    /// Total size of the files of the segment, in bytes.
+    pub num_bytes: u64,
+}
+
+/// Metadata of the last commit of an index, as returned by [`Index::inspect`].
+///
+/// The report can be serialized, e.g. to be printed as JSON by tools.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IndexInspection {
+    /// Opstamp of the commit.
+    pub opstamp: Opstamp,
+    /// Payload of the commit, if any.
+    pub payload: Option<String>,
+    /// Settings of the index.
+    pub index_settings: IndexSettings,
+    /// Schema of the index.
+    pub schema: Schema,
+    /// Number of alive documents, over all of the segments.
+    pub num_docs: u64,
+    /// Number of deleted documents, over all of the segments.
+    pub num_deleted_docs: u64,
+    /// Total size of the files of the segments, in bytes.
+    pub num_bytes: u64,
+    /// Metadata of each segment.
+    pub segments: Vec<SegmentInspection>,
+}
+
+pub(crate) fn inspect_index(index: &Index) -> crate::Result<IndexInspection> {
+    let metas = index.load_metas()?;
+    let segments = metas
+        .segments
+        .iter()
+        .map(|segment_meta| inspect_segment(index, segment_meta))
+        .collect::<crate::Result<Vec<SegmentInspection>>>()?;
+    Ok(IndexInspection {
+        opstamp: metas.opstamp,
+        payload: metas.payload,
+        index_settings: metas.index_settings,
+        schema: metas.schema,
+        num_docs: segments
+            .iter()
+            .map(|segment| u64::from(segment.num_docs))
+            .sum(),
+        num_deleted_docs: segments
+            .iter()
+            .map(|segment| u64::from(segment.num_deleted_docs))
+            .sum(),
+        num_bytes: segments.iter().map(|segment| segment.num_bytes).sum(),
+        segments,
+    })
+}
+
+fn inspect_segment(index: &Index, segment_meta: &SegmentMeta) -> crate::Result<SegmentInspection> {
+    // The underlying directory serves the files with their footer.
+    let directory = index.directory().underlying_directory();
+    let mut files = Vec::new();
+    for &component in SegmentComponent::iterator() {
+        match component {
+            SegmentComponent::TempStore => continue,
+            SegmentComponent::Delete if !segment_meta.has_deletes() => continue,
+            _ => {}
+        }
+        let path = segment_meta.relative_path(component);
+        if !directory.exists(&path)? {
+            continue;
+        }
+        let num_bytes = directory.open_read(&path)?.num_bytes().get_bytes();
+        files.push(SegmentFileInspection {
+            component,
+            path,
+            num_bytes,
+        });
+    }
+    Ok(SegmentInspection {
+        segment_id: segment_meta.id(),
+        max_doc: segment_meta.max_doc(),
+        num_docs: segment_meta.num_docs(),
+        num_deleted_docs: segment_meta.num_deleted_docs(),
+        delete_opstamp: segment_meta.delete_opstamp(),
+        num_bytes: files.iter().map(|file| file.num_bytes).sum(),
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::{IndexInspection, SegmentComponent};
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, STORED, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_inspect_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for tag_value in ["keep", "drop", "keep"] {
+            index_writer.add_document(doc!(tag => tag_value))?;
+        }
+        index_writer.commit()?;
+        index_writer.add_document(doc!(tag => "keep"))?;
+        index_writer.delete_term(Term::from_field_text(tag, "drop"));
+        let mut prepared_commit = index_writer.prepare_commit()?;
+        prepared_commit.set_payload("second");
+        let opstamp = prepared_commit.commit()?;
+
+        let inspection = index.inspect()?;
+        assert_eq!(inspection.opstamp, opstamp);
+        assert_eq!(inspection.payload.as_deref(), Some("second"));
+        assert_eq!(inspection.schema, index.schema());
+        assert_eq!(inspection.num_docs, 3);
+        assert_eq!(inspection.num_deleted_docs, 1);
+        assert_eq!(inspection.segments.len(), 2);
+        let segment = inspection
+            .segments
+            .iter()
+            .find(|segment| segment.max_doc == 3)
+            .unwrap();
+        assert_eq!(segment.num_docs, 2);
+        assert_eq!(segment.num_deleted_docs, 1);
+        assert_eq!(segment.delete_opstamp, Some(opstamp));
+        let components: Vec<SegmentComponent> =
+            segment.files.iter().map(|file| file.component).collect();
+        assert!(components.contains(&SegmentComponent::Store));
+        assert!(components.contains(&SegmentComponent::Delete));
+        assert!(segment.files.iter().all(|file| file.num_bytes > 0));
+        assert_eq!(
+            inspection.num_bytes,
+            inspection
+                .segments
+                .iter()
+                .map(|segment| segment.num_bytes)
+                .sum::<u64>()
+        );
+
+        let json = serde_json::to_string(&inspection)?;
+        let deserialized: IndexInspection = serde_json::from_str(&json)?;
+        assert_eq!(deserialized.segments[1].files, inspection.segments[1].files);
+        Ok(())
+    }
+}
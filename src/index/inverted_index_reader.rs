@@ -1,4 +1,6 @@
 use std::io;
+#[cfg(feature = "quickwit")]
+use std::sync::Arc;
 
 use common::json_path_writer::JSON_END_OF_PATH;
 use common::BinarySerializable;
@@ -181,6 +183,7 @@ impl InvertedIndexReader {
         Ok(SegmentPostings::from_block_postings(
             block_postings,
             position_reader,
+            self.record_option.has_offsets(),
         ))
     }
 
@@ -225,6 +228,44 @@ impl InvertedIndexReader {
         self.termdict.get_async(term.serialized_value_bytes()).await
     }
 
+    /// Returns the postings of a term, reading them asynchronously.
+    ///
+    /// This is the async version of [`InvertedIndexReader::read_postings`].
+    pub async fn read_postings_async(
+        &self,
+        term: &Term,
+        option: IndexRecordOption,
+    ) -> io::Result<Option<SegmentPostings>> {
+        let Some(term_info) = self.get_term_info_async(term).await? else {
+            return Ok(None);
+        };
+        let option = option.downgrade(self.record_option);
+        let postings_data = self
+            .postings_file_slice
+            .read_bytes_slice_async(term_info.postings_range.clone())
+            .await?;
+        let block_postings = BlockSegmentPostings::open(
+            term_info.doc_freq,
+            FileSlice::new(Arc::new(postings_data)),
+            self.record_option,
+            option,
+        )?;
+        let position_reader = if option.has_positions() {
+            let positions_data = self
+                .positions_file_slice
+                .read_bytes_slice_async(term_info.positions_range.clone())
+                .await?;
+            Some(PositionReader::open(positions_data)?)
+        } else {
+            None
+        };
+        Ok(Some(SegmentPostings::from_block_postings(
+            block_postings,
+            position_reader,
+            self.record_option.has_offsets(),
+        )))
+    }
+
     async fn get_term_range_async<'a, A: Automaton + 'a>(
         &'a self,
         terms: impl std::ops::RangeBounds<Term>,
@@ -4,17 +4,35 @@
 
 mod index;
 mod index_meta;
+mod inspection;
 mod inverted_index_reader;
+mod near_real_time;
+mod repair;
 mod segment;
 mod segment_component;
 mod segment_id;
 mod segment_reader;
+mod snapshot;
+mod validation;
 
 pub use self::index::{Index, IndexBuilder};
 pub(crate) use self::index_meta::SegmentMetaInventory;
-pub use self::index_meta::{IndexMeta, IndexSettings, Order, SegmentMeta};
+pub use self::index_meta::{DocStoreFamily, IndexMeta, IndexSettings, Order, SegmentMeta};
+pub(crate) use self::inspection::inspect_index;
+pub use self::inspection::{IndexInspection, SegmentFileInspection, SegmentInspection};
 pub use self::inverted_index_reader::InvertedIndexReader;
+pub(crate) use self::near_real_time::NearRealTimeState;
+pub(crate) use self::repair::repair_index;
+pub use self::repair::{DroppedSegment, IndexRepairReport};
 pub use self::segment::Segment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
 pub use self::segment_reader::{FieldMetadata, SegmentReader};
+#[cfg(feature = "mmap")]
+pub(crate) use self::snapshot::restore_from_snapshot;
+pub(crate) use self::snapshot::{create_snapshot, verify_snapshot};
+pub use self::snapshot::{
+    SnapshotFile, SnapshotManifest, SnapshotResult, SNAPSHOT_MANIFEST_FILEPATH,
+};
+pub(crate) use self::validation::validate_index;
+pub use self::validation::{IndexValidationReport, SegmentValidationReport, ValidationIssue};
@@ -0,0 +1,53 @@
+use std::sync::RwLock;
+
+use crate::directory::{WatchCallback, WatchCallbackList, WatchHandle};
+use crate::index::SegmentMeta;
+use crate::{FutureResult, Opstamp};
+
+/// Set of segments published by [`IndexWriter::refresh()`](crate::IndexWriter::refresh).
+///
+/// These segments are searchable but not durable: they are not part of
+/// the `meta.json` file until the next commit.
+#[derive(Clone)]
+pub(crate) struct NearRealTimeSnapshot {
+    pub opstamp: Opstamp,
+    pub segment_metas: Vec<SegmentMeta>,
+}
+
+/// Shared state between an `IndexWriter` and the `IndexReader`s of the same `Index`,
+/// used to expose uncommitted segments to searchers.
+#[derive(Default)]
+pub(crate) struct NearRealTimeState {
+    snapshot: RwLock<Option<NearRealTimeSnapshot>>,
+    callbacks: WatchCallbackList,
+}
+
+impl NearRealTimeState {
+    /// Publishes a new snapshot and notifies the subscribed readers.
+    pub fn publish(&self, opstamp: Opstamp, segment_metas: Vec<SegmentMeta>) -> FutureResult<()> {
+        *self.snapshot.write().unwrap() = Some(NearRealTimeSnapshot {
+            opstamp,
+            segment_metas,
+        });
+        self.callbacks.broadcast()
+    }
+
+    /// Drops the current snapshot, if any, and notifies the subscribed readers.
+    ///
+    /// Readers are notified asynchronously.
+    pub fn clear(&self) {
+        if self.snapshot.write().unwrap().take().is_some() {
+            drop(self.callbacks.broadcast());
+        }
+    }
+
+    /// Returns the last published snapshot.
+    pub fn snapshot(&self) -> Option<NearRealTimeSnapshot> {
+        self.snapshot.read().unwrap().clone()
+    }
+
+    /// Registers a callback called every time a snapshot is published or cleared.
+    pub fn watch(&self, watch_callback: WatchCallback) -> WatchHandle {
+        self.callbacks.subscribe(watch_callback)
+    }
+}
@@ -0,0 +1,219 @@
+use crate::directory::{Directory, INDEX_WRITER_LOCK};
+use crate::fastfield::AliveBitSet;
+use crate::index::{
+    validate_index, Index, Segment, SegmentComponent, SegmentId, SegmentMeta, ValidationIssue,
+};
+use crate::indexer::segment_updater::save_metas;
+use crate::schema::TantivyDocument;
+use crate::store::{SalvagedDoc, StoreReader};
+use crate::{DocId, Opstamp, TantivyError};
+
+/// A segment dropped by [`Index::repair`].
+#[derive(Debug)]
+pub struct DroppedSegment {
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Inconsistencies that caused the segment to be dropped.
+    pub issues: Vec<ValidationIssue>,
+    /// Ids, within the segment, of the documents which are not searchable anymore.
+    ///
+    /// If the alive bitset of the segment is damaged, its deleted documents are listed too.
+    pub lost_doc_ids: Vec<DocId>,
+    /// Lost documents whose stored fields could still be read from the doc store, e.g. to
+    /// index them again.
+    pub salvaged_docs: Vec<(DocId, TantivyDocument)>,
+}
+
+/// Outcome of [`Index::repair`].
+#[derive(Debug)]
+pub struct IndexRepairReport {
+    /// Opstamp of the repaired commit.
+    pub opstamp: Opstamp,
+    /// Segments kept in the index.
+    pub kept_segments: Vec<SegmentId>,
+    /// Segments removed from the index, with the documents lost.
+    pub dropped_segments: Vec<DroppedSegment>,
+}
+
+impl IndexRepairReport {
+    /// Returns the number of documents lost by the repair.
+    pub fn num_lost_docs(&self) -> usize {
+        self.dropped_segments
+            .iter()
+            .map(|segment| segment.lost_doc_ids.len())
+            .sum()
+    }
+}
+
+/// Removes the segments failing validation from the last commit of the index.
+pub(crate) fn repair_index(index: &Index) -> crate::Result<IndexRepairReport> {
+    let _index_writer_lock = index
+        .directory()
+        .acquire_lock(&INDEX_WRITER_LOCK)
+        .map_err(|err| {
+            TantivyError::LockFailure(
+                err,
+                Some("An index cannot be repaired while an `IndexWriter` works on it.".to_string()),
+            )
+        })?;
+    let mut metas = index.load_metas()?;
+    let validation_report = validate_index(index)?;
+    let mut kept_segment_metas = Vec::new();
+    let mut dropped_segments = Vec::new();
+    for (segment_meta, segment_report) in metas.segments.iter().zip(validation_report.segments) {
+        if segment_report.is_valid() {
+            kept_segment_metas.push(segment_meta.clone());
+        } else {
+            dropped_segments.push(salvage_segment(index, segment_meta, segment_report.issues));
+        }
+    }
+    let kept_segments = kept_segment_metas.iter().map(SegmentMeta::id).collect();
+    if !dropped_segments.is_empty() {
+        // The files of the dropped segments are garbage collected by the next `IndexWriter`.
+        metas.segments = kept_segment_metas;
+        save_metas(&metas, index.directory())?;
+    }
+    Ok(IndexRepairReport {
+        opstamp: metas.opstamp,
+        kept_segments,
+        dropped_segments,
+    })
+}
+
+fn salvage_segment(
+    index: &Index,
+    segment_meta: &SegmentMeta,
+    issues: Vec<ValidationIssue>,
+) -> DroppedSegment {
+    let segment = index.segment(segment_meta.clone());
+    let is_damaged = |component: SegmentComponent| {
+        let path = segment_meta.relative_path(component);
+        issues.iter().any(|issue| {
+            matches!(issue, ValidationIssue::MissingFile(damaged_path)
+                | ValidationIssue::CorruptedFile(damaged_path) if *damaged_path == path)
+        })
+    };
+    let alive_bitset_opt = if segment_meta.has_deletes() && !is_damaged(SegmentComponent::Delete) {
+        open_alive_bitset(&segment).ok()
+    } else {
+        None
+    };
+    let lost_doc_ids: Vec<DocId> = (0..segment_meta.max_doc())
+        .filter(|&doc_id| {
+            alive_bitset_opt
+                .as_ref()
+                .map_or(true, |alive_bitset| alive_bitset.is_alive(doc_id))
+        })
+        .collect();
+    // Only the damaged blocks of the doc store are lost.
+    let salvaged_docs = if let Ok(store_reader) = open_store(&segment) {
+        store_reader
+            .iter_salvage::<TantivyDocument>(alive_bitset_opt.as_ref())
+            .filter_map(|salvaged_doc| match salvaged_doc {
+                SalvagedDoc::Doc(doc_id, doc) => Some((doc_id, doc)),
+                SalvagedDoc::Lost(_) => None,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    DroppedSegment {
+        segment_id: segment_meta.id(),
+        issues,
+        lost_doc_ids,
+        salvaged_docs,
+    }
+}
+
+fn open_alive_bitset(segment: &Segment) -> crate::Result<AliveBitSet> {
+    let alive_bitset_data = segment.open_read(SegmentComponent::Delete)?.read_bytes()?;
+    Ok(AliveBitSet::open(alive_bitset_data))
+}
+
+fn open_store(segment: &Segment) -> crate::Result<StoreReader> {
+    let store_file = segment.open_read(SegmentComponent::Store)?;
+    Ok(StoreReader::open(store_file, 0)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::directory::{Directory, RamDirectory};
+    use crate::index::SegmentComponent;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, Value, STORED, STRING, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_repair_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let directory = RamDirectory::default();
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment_ord in 0..3 {
+            for doc_ord in 0..4 {
+                let title_value = format!("segment {segment_ord} doc {doc_ord}");
+                let tag_value = if doc_ord == 0 { "drop" } else { "keep" };
+                index_writer.add_document(doc!(title => title_value, tag => tag_value))?;
+            }
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_text(tag, "drop"));
+        index_writer.commit()?;
+        // Repairing requires the index writer lock.
+        assert!(index.repair().is_err());
+        drop(index_writer);
+
+        let report = index.repair()?;
+        assert_eq!(report.kept_segments.len(), 3);
+        assert!(report.dropped_segments.is_empty());
+
+        let (damaged_segment_id, postings_path) = {
+            let segment_meta = &index.searchable_segment_metas()?[1];
+            (
+                segment_meta.id(),
+                segment_meta.relative_path(SegmentComponent::Postings),
+            )
+        };
+        let mut postings_data = directory.open_read(&postings_path)?.read_bytes()?.to_vec();
+        postings_data[0] ^= 0xFF;
+        directory.atomic_write(&postings_path, &postings_data)?;
+
+        let report = index.repair()?;
+        assert_eq!(report.kept_segments.len(), 2);
+        assert_eq!(report.dropped_segments.len(), 1);
+        let dropped_segment = &report.dropped_segments[0];
+        assert_eq!(dropped_segment.segment_id, damaged_segment_id);
+        assert_eq!(dropped_segment.lost_doc_ids, vec![1, 2, 3]);
+        assert_eq!(report.num_lost_docs(), 3);
+        let salvaged_titles: Vec<&str> = dropped_segment
+            .salvaged_docs
+            .iter()
+            .map(|(_, doc): &(_, TantivyDocument)| doc.get_first(title).unwrap().as_str().unwrap())
+            .collect();
+        assert_eq!(
+            salvaged_titles,
+            vec!["segment 1 doc 1", "segment 1 doc 2", "segment 1 doc 3"]
+        );
+
+        assert!(index.validate()?.is_valid());
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 6);
+        // The index can be written to again.
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "new"))?;
+        index_writer.commit()?;
+        index_writer.garbage_collect_files().wait()?;
+        assert!(!directory.exists(&postings_path)?);
+        Ok(())
+    }
+}
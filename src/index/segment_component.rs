@@ -1,11 +1,14 @@
+use std::path::Path;
 use std::slice;
 
+use serde::{Deserialize, Serialize};
+
 /// Enum describing each component of a tantivy segment.
 ///
 /// Each component is stored in its own file,
 /// using the pattern `segment_uuid`.`component_extension`,
 /// except the delete component that takes an `segment_uuid`.`delete_opstamp`.`component_extension`
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum SegmentComponent {
     /// Postings (or inverted list). Sorted lists of document ids, associated with terms
     Postings,
@@ -16,6 +19,12 @@ pub enum SegmentComponent {
     /// Stores the sum  of the length (in terms) of each field for each document.
     /// Field norms are stored as a special u64 fast field.
     FieldNorms,
+    /// Points index of the numeric and date fields, used to run range queries.
+    Points,
+    /// Dense vectors of the vector fields.
+    Vectors,
+    /// Term vectors of the text fields with term vectors.
+    TermVectors,
     /// Dictionary associating `Term`s to `TermInfo`s which is
     /// simply an address into the `postings` file and the `positions` file.
     Terms,
@@ -33,11 +42,14 @@ pub enum SegmentComponent {
 impl SegmentComponent {
     /// Iterates through the components.
     pub fn iterator() -> slice::Iter<'static, SegmentComponent> {
-        static SEGMENT_COMPONENTS: [SegmentComponent; 8] = [
+        static SEGMENT_COMPONENTS: [SegmentComponent; 11] = [
             SegmentComponent::Postings,
             SegmentComponent::Positions,
             SegmentComponent::FastFields,
             SegmentComponent::FieldNorms,
+            SegmentComponent::Points,
+            SegmentComponent::Vectors,
+            SegmentComponent::TermVectors,
             SegmentComponent::Terms,
             SegmentComponent::Store,
             SegmentComponent::TempStore,
@@ -45,4 +57,26 @@ impl SegmentComponent {
         ];
         SEGMENT_COMPONENTS.iter()
     }
+
+    /// Returns the component a file belongs to, given its path, or `None` if it is not a
+    /// segment file.
+    pub(crate) fn from_path(path: &Path) -> Option<SegmentComponent> {
+        let file_name = path.file_name()?.to_str()?;
+        let (_segment_id, extension) = file_name.split_once('.')?;
+        let component = match extension {
+            "idx" => SegmentComponent::Postings,
+            "pos" => SegmentComponent::Positions,
+            "term" => SegmentComponent::Terms,
+            "store" => SegmentComponent::Store,
+            "store.temp" => SegmentComponent::TempStore,
+            "fast" => SegmentComponent::FastFields,
+            "fieldnorm" => SegmentComponent::FieldNorms,
+            "points" => SegmentComponent::Points,
+            "vec" => SegmentComponent::Vectors,
+            "tv" => SegmentComponent::TermVectors,
+            extension if extension.ends_with(".del") => SegmentComponent::Delete,
+            _ => return None,
+        };
+        Some(component)
+    }
 }
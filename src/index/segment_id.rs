@@ -49,6 +49,11 @@ impl SegmentId {
         SegmentId(create_uuid())
     }
 
+    /// Builds a `SegmentId` from its 128 bits.
+    pub(crate) fn from_u128(id: u128) -> SegmentId {
+        SegmentId(Uuid::from_u128(id))
+    }
+
     /// Returns a shorter identifier of the segment.
     ///
     /// We are using UUID4, so only 6 bits are fixed,
@@ -12,10 +12,13 @@ use crate::fastfield::{intersect_alive_bitsets, AliveBitSet, FacetReader, FastFi
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
 use crate::index::{InvertedIndexReader, Segment, SegmentComponent, SegmentId};
 use crate::json_utils::json_path_sep_to_dot;
+use crate::points::PointsReaders;
 use crate::schema::{Field, IndexRecordOption, Schema, Type};
 use crate::space_usage::SegmentSpaceUsage;
-use crate::store::StoreReader;
+use crate::store::{BlockCache, StoreReader};
+use crate::term_vectors::TermVectorsReaders;
 use crate::termdict::TermDictionary;
+use crate::vector::VectorsReaders;
 use crate::{DocId, Opstamp};
 
 /// Entry point to access all of the datastructures of the `Segment`
@@ -25,6 +28,9 @@ use crate::{DocId, Opstamp};
 /// - store
 /// - fast field readers
 /// - field norm reader
+/// - points reader
+/// - vectors reader
+/// - term vectors reader
 ///
 /// The segment reader has a very low memory footprint,
 /// as close to all of the memory data is mmapped.
@@ -43,6 +49,9 @@ pub struct SegmentReader {
     positions_composite: CompositeFile,
     fast_fields_readers: FastFieldReaders,
     fieldnorm_readers: FieldNormReaders,
+    points_readers: PointsReaders,
+    vectors_readers: VectorsReaders,
+    term_vectors_readers: TermVectorsReaders,
 
     store_file: FileSlice,
     alive_bitset_opt: Option<AliveBitSet>,
@@ -131,6 +140,28 @@ impl SegmentReader {
         &self.fieldnorm_readers
     }
 
+    /// Accessor to the segment's points index readers.
+    ///
+    /// Only the fields set as points have a points index, see
+    /// [`PointRangeQuery`](crate::query::PointRangeQuery).
+    pub fn points_readers(&self) -> &PointsReaders {
+        &self.points_readers
+    }
+
+    /// Accessor to the segment's dense vectors readers.
+    ///
+    /// Only the vector fields have vectors, see [`Searcher::knn`](crate::Searcher::knn).
+    pub fn vectors_readers(&self) -> &VectorsReaders {
+        &self.vectors_readers
+    }
+
+    /// Accessor to the segment's term vectors readers.
+    ///
+    /// Only the fields with term vectors have term vectors, see [`crate::term_vectors`].
+    pub fn term_vectors_readers(&self) -> &TermVectorsReaders {
+        &self.term_vectors_readers
+    }
+
     /// Accessor to the segment's [`StoreReader`](crate::store::StoreReader).
     ///
     /// `cache_num_blocks` sets the number of decompressed blocks to be cached in an LRU.
@@ -139,6 +170,15 @@ impl SegmentReader {
         StoreReader::open(self.store_file.clone(), cache_num_blocks)
     }
 
+    /// Accessor to the segment's [`StoreReader`](crate::store::StoreReader), caching its
+    /// decompressed blocks in a [`BlockCache`] that may be shared with other segments.
+    pub fn get_store_reader_with_block_cache(
+        &self,
+        block_cache: Arc<BlockCache>,
+    ) -> io::Result<StoreReader> {
+        StoreReader::open_with_block_cache(self.store_file.clone(), block_cache)
+    }
+
     /// Open a new segment for reading.
     pub fn open(segment: &Segment) -> crate::Result<SegmentReader> {
         Self::open_with_custom_alive_set(segment, None)
@@ -173,6 +213,24 @@ impl SegmentReader {
         let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())?;
         let fieldnorm_data = segment.open_read(SegmentComponent::FieldNorms)?;
         let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
+        // Segments written before the points index existed do not have a points file.
+        let points_readers = if let Ok(points_data) = segment.open_read(SegmentComponent::Points) {
+            PointsReaders::open(points_data)?
+        } else {
+            PointsReaders::empty()
+        };
+        let vectors_readers = if let Ok(vectors_data) = segment.open_read(SegmentComponent::Vectors)
+        {
+            VectorsReaders::open(vectors_data)?
+        } else {
+            VectorsReaders::empty()
+        };
+        let term_vectors_readers =
+            if let Ok(term_vectors_data) = segment.open_read(SegmentComponent::TermVectors) {
+                TermVectorsReaders::open(term_vectors_data)?
+            } else {
+                TermVectorsReaders::empty()
+            };
 
         let original_bitset = if segment.meta().has_deletes() {
             let alive_doc_file_slice = segment.open_read(SegmentComponent::Delete)?;
@@ -198,6 +256,9 @@ impl SegmentReader {
             postings_composite,
             fast_fields_readers,
             fieldnorm_readers,
+            points_readers,
+            vectors_readers,
+            term_vectors_readers,
             segment_id: segment.id(),
             delete_opstamp: segment.meta().delete_opstamp(),
             store_file,
@@ -285,6 +346,11 @@ impl SegmentReader {
         Ok(inv_idx_reader)
     }
 
+    /// Returns the file of the term dictionary of a field, if it has one.
+    pub(crate) fn termdict_file(&self, field: Field) -> Option<FileSlice> {
+        self.termdict_composite.open_read(field)
+    }
+
     /// Returns the list of fields that have been indexed in the segment.
     /// The field list includes the field defined in the schema as well as the fields
     /// that have been indexed as a part of a JSON field.
@@ -415,7 +481,14 @@ impl SegmentReader {
     }
 
     /// Summarize total space usage of this segment.
+    ///
+    /// The space usage of each stored field is estimated by decompressing a few blocks of the
+    /// doc store.
     pub fn space_usage(&self) -> io::Result<SegmentSpaceUsage> {
+        let store_reader = self.get_store_reader(0)?;
+        let store_space_usage = store_reader
+            .space_usage()
+            .with_fields(store_reader.fields_space_usage()?);
         Ok(SegmentSpaceUsage::new(
             self.num_docs(),
             self.termdict_composite.space_usage(),
@@ -423,7 +496,10 @@ impl SegmentReader {
             self.positions_composite.space_usage(),
             self.fast_fields_readers.space_usage(self.schema())?,
             self.fieldnorm_readers.space_usage(),
-            self.get_store_reader(0)?.space_usage(),
+            self.points_readers.space_usage(),
+            self.vectors_readers.space_usage(),
+            self.term_vectors_readers.space_usage(),
+            store_space_usage,
             self.alive_bitset_opt
                 .as_ref()
                 .map(AliveBitSet::space_usage)
@@ -0,0 +1,584 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crc32fast::Hasher;
+use serde::{Deserialize, Serialize};
+
+use crate::core::META_FILEPATH;
+use crate::directory::{Directory, META_LOCK};
+#[cfg(feature = "mmap")]
+use crate::directory::{ManagedDirectory, MmapDirectory, INDEX_WRITER_LOCK};
+use crate::error::DataCorruption;
+use crate::index::{IndexMeta, SegmentComponent, SegmentMetaInventory};
+use crate::{Index, Opstamp, TantivyError};
+
+/// Name of the manifest file written in the directory of a snapshot.
+pub const SNAPSHOT_MANIFEST_FILEPATH: &str = "snapshot.json";
+
+const COPY_BUFFER_LEN: usize = 64 * 1024;
+
+/// A file of a snapshot.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotFile {
+    /// Path of the file, relative to the directory of the snapshot.
+    pub path: PathBuf,
+    /// Size of the file, in bytes.
+    pub num_bytes: u64,
+    /// CRC32 checksum of the content of the file.
+    pub checksum: u32,
+}
+
+/// Manifest of a snapshot, listing exactly the files of the commit it holds.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Opstamp of the commit of the snapshot.
+    pub opstamp: Opstamp,
+    /// Files of the snapshot, including its `meta.json`.
+    pub files: Vec<SnapshotFile>,
+}
+
+/// Outcome of the creation of a snapshot.
+#[derive(Debug)]
+pub struct SnapshotResult {
+    /// Manifest of the snapshot, also written in the directory of the snapshot.
+    pub manifest: SnapshotManifest,
+    /// Files that were hard linked or copied into the directory of the snapshot.
+    pub transferred_files: Vec<PathBuf>,
+    /// Files that were already in the directory of the snapshot, and were kept as is.
+    pub reused_files: Vec<PathBuf>,
+    /// Files of the previous snapshot that are not part of the new one, and were deleted.
+    pub deleted_files: Vec<PathBuf>,
+}
+
+fn read_manifest(target: &Path) -> crate::Result<Option<SnapshotManifest>> {
+    match fs::read(target.join(SNAPSHOT_MANIFEST_FILEPATH)) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(io_error) if io_error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(io_error) => Err(io_error.into()),
+    }
+}
+
+fn read_existing_manifest(snapshot: &Path) -> crate::Result<SnapshotManifest> {
+    read_manifest(snapshot)?.ok_or_else(|| {
+        TantivyError::InvalidArgument(format!("{snapshot:?} does not contain a snapshot"))
+    })
+}
+
+/// Writes a file through a temporary file, so that it is either fully written or not
+/// modified.
+fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let mut file = File::create(&temp_path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)
+}
+
+/// Copies the content of a reader into a writer, and returns its length and checksum.
+fn copy_with_checksum<R: Read, W: Write>(mut reader: R, writer: &mut W) -> io::Result<(u64, u32)> {
+    let mut hasher = Hasher::new();
+    let mut buffer = vec![0u8; COPY_BUFFER_LEN];
+    let mut num_bytes = 0u64;
+    loop {
+        let len = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(io_error) if io_error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(io_error) => return Err(io_error),
+        };
+        hasher.update(&buffer[..len]);
+        writer.write_all(&buffer[..len])?;
+        num_bytes += len as u64;
+    }
+    Ok((num_bytes, hasher.finalize()))
+}
+
+fn file_checksum(path: &Path) -> io::Result<u32> {
+    let (_num_bytes, checksum) = copy_with_checksum(File::open(path)?, &mut io::sink())?;
+    Ok(checksum)
+}
+
+/// Hard links a file of a directory to `target_path` if the directory stores it on the local
+/// filesystem, and copies it otherwise.
+fn transfer_file(directory: &dyn Directory, path: &Path, target_path: &Path) -> crate::Result<()> {
+    let local_path = directory.local_path(path);
+    if let Some(local_path) = &local_path {
+        // Hard linking fails across filesystems.
+        if fs::hard_link(local_path, target_path).is_ok() {
+            return Ok(());
+        }
+    }
+    let mut file = File::create(target_path)?;
+    if let Some(local_path) = &local_path {
+        io::copy(&mut File::open(local_path)?, &mut file)?;
+    } else {
+        let file_slice = directory.open_read(path)?;
+        for chunk in file_slice.stream_file_chunks() {
+            file.write_all(chunk?.as_slice())?;
+        }
+    }
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Creates a snapshot of the last commit of an index in the `target` directory.
+///
+/// With `incremental`, the segment files that are already in the target directory are kept,
+/// and the files of the previous snapshot that are not part of the new one are deleted.
+/// Otherwise, the target directory has to be empty.
+pub(crate) fn create_snapshot(
+    index: &Index,
+    target: &Path,
+    incremental: bool,
+) -> crate::Result<SnapshotResult> {
+    let previous_manifest = if incremental {
+        read_manifest(target)?
+    } else {
+        if target.exists() && fs::read_dir(target)?.next().is_some() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Snapshot directory {target:?} is not empty"
+            )));
+        }
+        None
+    };
+    let previous_files: HashMap<&Path, &SnapshotFile> = previous_manifest
+        .iter()
+        .flat_map(|manifest| &manifest.files)
+        .map(|file| (file.path.as_path(), file))
+        .collect();
+    fs::create_dir_all(target)?;
+
+    // Loading the metas registers their segments in the inventory of the index, which pins
+    // their files: the garbage collector keeps them as long as `metas` is alive. The lock
+    // only prevents a garbage collection from running between the read of the `meta.json`
+    // and this registration, and is released before the files are copied.
+    let metas = {
+        let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
+        index.load_metas()?
+    };
+    let mut meta_data = serde_json::to_vec_pretty(&metas)?;
+    writeln!(&mut meta_data)?;
+
+    let mut segment_file_paths: Vec<PathBuf> = metas
+        .segments
+        .iter()
+        .flat_map(|segment_meta| segment_meta.list_files())
+        .collect();
+    segment_file_paths.sort();
+
+    let mut files = Vec::with_capacity(segment_file_paths.len() + 1);
+    let mut transferred_files = Vec::new();
+    let mut reused_files = Vec::new();
+    // Segment files are transferred with their footer, which the managed directory strips off.
+    let directory = index.directory().underlying_directory();
+    for path in segment_file_paths {
+        // Some of the files listed for a segment may not exist, e.g. its delete file.
+        if !directory.exists(&path)? {
+            continue;
+        }
+        let num_bytes = directory.get_file_handle(&path)?.len() as u64;
+        let target_path = target.join(&path);
+        let checksum = match fs::metadata(&target_path) {
+            // Segment files are never modified, so a file with the same name and size is the
+            // same file.
+            Ok(metadata) if metadata.len() == num_bytes => {
+                reused_files.push(path.clone());
+                match previous_files.get(path.as_path()) {
+                    Some(previous_file) if previous_file.num_bytes == num_bytes => {
+                        previous_file.checksum
+                    }
+                    _ => file_checksum(&target_path)?,
+                }
+            }
+            Ok(_) => {
+                fs::remove_file(&target_path)?;
+                transfer_file(directory, &path, &target_path)?;
+                transferred_files.push(path.clone());
+                file_checksum(&target_path)?
+            }
+            Err(io_error) if io_error.kind() == io::ErrorKind::NotFound => {
+                transfer_file(directory, &path, &target_path)?;
+                transferred_files.push(path.clone());
+                file_checksum(&target_path)?
+            }
+            Err(io_error) => return Err(io_error.into()),
+        };
+        files.push(SnapshotFile {
+            path,
+            num_bytes,
+            checksum,
+        });
+    }
+    // The `meta.json` and the manifest are written last, so that the previous snapshot stays
+    // consistent until the new one is complete.
+    write_atomically(&target.join(*META_FILEPATH), &meta_data)?;
+    files.push(SnapshotFile {
+        path: META_FILEPATH.to_path_buf(),
+        num_bytes: meta_data.len() as u64,
+        checksum: crc32fast::hash(&meta_data),
+    });
+    let manifest = SnapshotManifest {
+        opstamp: metas.opstamp,
+        files,
+    };
+    let mut manifest_data = serde_json::to_vec_pretty(&manifest)?;
+    writeln!(&mut manifest_data)?;
+    write_atomically(&target.join(SNAPSHOT_MANIFEST_FILEPATH), &manifest_data)?;
+
+    let mut deleted_files = Vec::new();
+    if let Some(previous_manifest) = &previous_manifest {
+        let paths: HashSet<&Path> = manifest
+            .files
+            .iter()
+            .map(|file| file.path.as_path())
+            .collect();
+        for file in &previous_manifest.files {
+            if paths.contains(file.path.as_path()) {
+                continue;
+            }
+            match fs::remove_file(target.join(&file.path)) {
+                Ok(()) => deleted_files.push(file.path.clone()),
+                Err(io_error) if io_error.kind() == io::ErrorKind::NotFound => {}
+                Err(io_error) => return Err(io_error.into()),
+            }
+        }
+    }
+    Ok(SnapshotResult {
+        manifest,
+        transferred_files,
+        reused_files,
+        deleted_files,
+    })
+}
+
+fn check_file(file: &SnapshotFile, num_bytes: u64, checksum: u32) -> crate::Result<()> {
+    if num_bytes != file.num_bytes || checksum != file.checksum {
+        return Err(DataCorruption::new(
+            file.path.clone(),
+            format!(
+                "Snapshot file has {num_bytes} bytes and checksum {checksum:08x}, expected {} \
+                 bytes and checksum {:08x}.",
+                file.num_bytes, file.checksum
+            ),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Checks that a manifest lists exactly the files of the commit of its `meta.json`, and
+/// returns the content of the `meta.json`.
+fn validate_manifest(snapshot: &Path, manifest: &SnapshotManifest) -> crate::Result<Vec<u8>> {
+    let manifest_path = snapshot.join(SNAPSHOT_MANIFEST_FILEPATH);
+    let corrupted_manifest = |comment: String| DataCorruption::new(manifest_path.clone(), comment);
+    for file in &manifest.files {
+        // Restoring a snapshot must not write outside of the target directory.
+        let mut components = file.path.components();
+        if !matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        ) {
+            return Err(corrupted_manifest(format!("Invalid file path {:?}.", file.path)).into());
+        }
+    }
+    let meta_file = manifest
+        .files
+        .iter()
+        .find(|file| file.path == *META_FILEPATH)
+        .ok_or_else(|| corrupted_manifest("The manifest does not list a meta.json.".to_string()))?;
+    let meta_data = fs::read(snapshot.join(*META_FILEPATH))?;
+    check_file(
+        meta_file,
+        meta_data.len() as u64,
+        crc32fast::hash(&meta_data),
+    )?;
+    let metas = IndexMeta::deserialize(
+        &String::from_utf8_lossy(&meta_data),
+        &SegmentMetaInventory::default(),
+    )?;
+    if metas.opstamp != manifest.opstamp {
+        return Err(corrupted_manifest(format!(
+            "The manifest opstamp {} does not match the meta.json opstamp {}.",
+            manifest.opstamp, metas.opstamp
+        ))
+        .into());
+    }
+    let manifest_paths: HashSet<&Path> = manifest
+        .files
+        .iter()
+        .map(|file| file.path.as_path())
+        .collect();
+    let mut segment_file_paths = HashSet::new();
+    for segment_meta in &metas.segments {
+        // A missing delete file would resurrect deleted documents.
+        if segment_meta.has_deletes() {
+            let delete_path = segment_meta.relative_path(SegmentComponent::Delete);
+            if !manifest_paths.contains(delete_path.as_path()) {
+                return Err(
+                    corrupted_manifest(format!("Missing delete file {delete_path:?}.")).into(),
+                );
+            }
+        }
+        segment_file_paths.extend(segment_meta.list_files());
+    }
+    for path in manifest_paths {
+        if path != *META_FILEPATH && !segment_file_paths.contains(path) {
+            return Err(corrupted_manifest(format!(
+                "File {path:?} does not belong to the commit of the snapshot."
+            ))
+            .into());
+        }
+    }
+    Ok(meta_data)
+}
+
+/// Checks the manifest of a snapshot, and the size and checksum of all of its files.
+pub(crate) fn verify_snapshot(snapshot: &Path) -> crate::Result<SnapshotManifest> {
+    let manifest = read_existing_manifest(snapshot)?;
+    validate_manifest(snapshot, &manifest)?;
+    for file in &manifest.files {
+        let file_path = snapshot.join(&file.path);
+        let (num_bytes, checksum) = copy_with_checksum(File::open(file_path)?, &mut io::sink())?;
+        check_file(file, num_bytes, checksum)?;
+    }
+    Ok(manifest)
+}
+
+/// Restores a snapshot into the `target` directory, and opens the restored index.
+///
+/// The files are copied into a temporary directory and checked against the manifest, before
+/// being moved into the target directory. The files of an index already in the target
+/// directory are left untouched if the snapshot turns out to be corrupted. The `meta.json` of
+/// the snapshot is installed last, so that the target directory switches atomically to the
+/// restored commit.
+#[cfg(feature = "mmap")]
+pub(crate) fn restore_from_snapshot(snapshot: &Path, target: &Path) -> crate::Result<Index> {
+    let manifest = read_existing_manifest(snapshot)?;
+    let meta_data = validate_manifest(snapshot, &manifest)?;
+    fs::create_dir_all(target)?;
+    let mmap_directory = MmapDirectory::open(target)?;
+    let managed_directory = ManagedDirectory::wrap(Box::new(mmap_directory.clone()))?;
+    // Prevents index writers from committing to the target directory while it is restored.
+    let index_writer_lock = managed_directory.acquire_lock(&INDEX_WRITER_LOCK)?;
+    // Deleted on drop, if the restore fails.
+    let staging_dir = tempfile::Builder::new()
+        .prefix(".restore-")
+        .tempdir_in(target)?;
+    let segment_files: Vec<&SnapshotFile> = manifest
+        .files
+        .iter()
+        .filter(|file| file.path != *META_FILEPATH)
+        .collect();
+    for file in &segment_files {
+        let mut output = File::create(staging_dir.path().join(&file.path))?;
+        let (num_bytes, checksum) =
+            copy_with_checksum(File::open(snapshot.join(&file.path))?, &mut output)?;
+        output.sync_all()?;
+        check_file(file, num_bytes, checksum)?;
+    }
+    for file in &segment_files {
+        // Files are registered before being moved, so that they get garbage collected if the
+        // restore fails.
+        managed_directory.register_file_as_managed(&file.path)?;
+        fs::rename(staging_dir.path().join(&file.path), target.join(&file.path))?;
+    }
+    managed_directory.sync_directory()?;
+    managed_directory.atomic_write(&META_FILEPATH, &meta_data)?;
+    drop(index_writer_lock);
+    Index::open(mmap_directory)
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod tests {
+    use super::*;
+    use crate::collector::Count;
+    use crate::directory::MmapDirectory;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STORED, STRING};
+    use crate::{IndexWriter, Term};
+
+    fn num_docs(path: &Path) -> crate::Result<usize> {
+        let index = Index::open(MmapDirectory::open(path)?)?;
+        index.reader()?.searcher().search(&AllQuery, &Count)
+    }
+
+    #[test]
+    fn test_snapshot() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..5 {
+            let tag = if i % 2 == 0 { "keep" } else { "drop" };
+            index_writer.add_document(doc!(tag_field => tag))?;
+        }
+        index_writer.commit()?;
+
+        let snapshot_dir = tempfile::TempDir::new()?;
+        let snapshot = index.create_snapshot(snapshot_dir.path())?;
+        assert!(snapshot.reused_files.is_empty());
+        assert!(!snapshot.transferred_files.is_empty());
+        assert_eq!(num_docs(snapshot_dir.path())?, 5);
+        // Full snapshots require an empty directory.
+        assert!(index.create_snapshot(snapshot_dir.path()).is_err());
+
+        for _ in 0..3 {
+            index_writer.add_document(doc!(tag_field => "second"))?;
+        }
+        index_writer.delete_term(Term::from_field_text(tag_field, "drop"));
+        index_writer.commit()?;
+        let incremental_snapshot = index.create_incremental_snapshot(snapshot_dir.path())?;
+        // The segment files of the first commit are reused, but its delete file is new.
+        assert_eq!(
+            incremental_snapshot.reused_files.len(),
+            snapshot.transferred_files.len()
+        );
+        assert!(incremental_snapshot.deleted_files.is_empty());
+        assert_eq!(num_docs(snapshot_dir.path())?, 6);
+
+        index_writer.delete_term(Term::from_field_text(tag_field, "second"));
+        index_writer.commit()?;
+        let incremental_snapshot = index.create_incremental_snapshot(snapshot_dir.path())?;
+        // The second segment is entirely deleted, and its files are removed from the snapshot.
+        assert!(incremental_snapshot.transferred_files.is_empty());
+        assert!(!incremental_snapshot.deleted_files.is_empty());
+        assert_eq!(num_docs(snapshot_dir.path())?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_hard_links() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index_dir = tempfile::TempDir::new()?;
+        let index = Index::create_in_dir(index_dir.path(), schema_builder.build())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag_field => "hello"))?;
+        index_writer.commit()?;
+
+        let snapshot_dir = tempfile::TempDir::new()?;
+        let snapshot = index.create_snapshot(snapshot_dir.path())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let path = &snapshot.transferred_files[0];
+            let metadata = std::fs::metadata(snapshot_dir.path().join(path))?;
+            // The file is shared with the index.
+            assert_eq!(metadata.nlink(), 2);
+        }
+        assert_eq!(num_docs(snapshot_dir.path())?, 1);
+        Ok(())
+    }
+
+    fn create_index_with_deletes() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..10 {
+            let tag = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(tag_field => tag))?;
+        }
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(tag_field, "odd"));
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_restore_from_snapshot() -> crate::Result<()> {
+        let index = create_index_with_deletes()?;
+        let snapshot_dir = tempfile::TempDir::new()?;
+        let snapshot = index.create_snapshot(snapshot_dir.path())?;
+        let manifest = Index::verify_snapshot(snapshot_dir.path())?;
+        assert_eq!(manifest.files, snapshot.manifest.files);
+
+        let restore_dir = tempfile::TempDir::new()?;
+        let restored_index = Index::restore_from_snapshot(snapshot_dir.path(), restore_dir.path())?;
+        assert_eq!(num_docs(restore_dir.path())?, 5);
+
+        // Restoring over an existing index replaces its commit.
+        let mut index_writer: IndexWriter = restored_index.writer_for_tests()?;
+        index_writer.delete_all_documents()?;
+        index_writer.commit()?;
+        assert!(Index::restore_from_snapshot(snapshot_dir.path(), restore_dir.path()).is_err());
+        drop(index_writer);
+        Index::restore_from_snapshot(snapshot_dir.path(), restore_dir.path())?;
+        assert_eq!(num_docs(restore_dir.path())?, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_from_corrupted_snapshot() -> crate::Result<()> {
+        let index = create_index_with_deletes()?;
+        let snapshot_dir = tempfile::TempDir::new()?;
+        let snapshot = index.create_snapshot(snapshot_dir.path())?;
+
+        let store_path = snapshot
+            .manifest
+            .files
+            .iter()
+            .map(|file| &file.path)
+            .find(|path| path.extension() == Some("store".as_ref()))
+            .unwrap();
+        let mut store_data = fs::read(snapshot_dir.path().join(store_path))?;
+        store_data[0] ^= 1;
+        fs::write(snapshot_dir.path().join(store_path), &store_data)?;
+        assert!(Index::verify_snapshot(snapshot_dir.path()).is_err());
+        let restore_dir = tempfile::TempDir::new()?;
+        assert!(Index::restore_from_snapshot(snapshot_dir.path(), restore_dir.path()).is_err());
+        // The restored commit does not get installed.
+        assert!(!restore_dir.path().join(*META_FILEPATH).exists());
+        // The temporary directory of the restore is removed.
+        for entry in fs::read_dir(restore_dir.path())? {
+            assert!(!entry?
+                .file_name()
+                .to_string_lossy()
+                .starts_with(".restore-"));
+        }
+
+        // Manifests cannot point outside of the snapshot.
+        let mut manifest = snapshot.manifest.clone();
+        manifest.files[0].path = PathBuf::from("../escape");
+        fs::write(
+            snapshot_dir.path().join(SNAPSHOT_MANIFEST_FILEPATH),
+            serde_json::to_vec(&manifest)?,
+        )?;
+        assert!(Index::verify_snapshot(snapshot_dir.path()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_corrupted_snapshot_over_index() -> crate::Result<()> {
+        let index = create_index_with_deletes()?;
+        let snapshot_dir = tempfile::TempDir::new()?;
+        let snapshot = index.create_snapshot(snapshot_dir.path())?;
+        let restore_dir = tempfile::TempDir::new()?;
+        Index::restore_from_snapshot(snapshot_dir.path(), restore_dir.path())?;
+
+        let store_path = snapshot
+            .manifest
+            .files
+            .iter()
+            .map(|file| &file.path)
+            .find(|path| path.extension() == Some("store".as_ref()))
+            .unwrap();
+        let restored_store_data = fs::read(restore_dir.path().join(store_path))?;
+        let mut store_data = fs::read(snapshot_dir.path().join(store_path))?;
+        store_data[0] ^= 1;
+        fs::write(snapshot_dir.path().join(store_path), &store_data)?;
+        assert!(Index::restore_from_snapshot(snapshot_dir.path(), restore_dir.path()).is_err());
+        // The files of the index in the target directory are not overwritten.
+        assert_eq!(
+            fs::read(restore_dir.path().join(store_path))?,
+            restored_store_data
+        );
+        assert_eq!(num_docs(restore_dir.path())?, 5);
+        Ok(())
+    }
+}
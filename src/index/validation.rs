@@ -0,0 +1,564 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use common::json_path_writer::JSON_PATH_SEGMENT_SEP_STR;
+
+use crate::directory::Directory;
+use crate::docset::{DocSet, TERMINATED};
+use crate::fieldnorm::FieldNormReader;
+use crate::index::{SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
+use crate::postings::Postings;
+use crate::schema::{Field, FieldType, IndexRecordOption};
+use crate::{DocId, Index, Opstamp};
+
+/// Inconsistency found by [`Index::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// A file of the segment is missing.
+    MissingFile(PathBuf),
+    /// The content of a file does not match the checksum of its footer.
+    CorruptedFile(PathBuf),
+    /// The segment could not be opened.
+    UnreadableSegment(String),
+    /// The alive bitset does not match the segment meta.
+    AliveBitSet(String),
+    /// The postings of a field are inconsistent.
+    Postings {
+        /// Name of the field.
+        field: String,
+        /// Description of the inconsistency.
+        message: String,
+    },
+    /// The fieldnorms of a field do not match its postings or the number of documents.
+    FieldNorms {
+        /// Name of the field.
+        field: String,
+        /// Description of the inconsistency.
+        message: String,
+    },
+    /// A fast field column does not have one row per document.
+    FastFields(String),
+    /// The doc store does not have one entry per document.
+    DocStore(String),
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::MissingFile(path) => write!(f, "Missing file {path:?}"),
+            ValidationIssue::CorruptedFile(path) => write!(f, "Checksum mismatch in {path:?}"),
+            ValidationIssue::UnreadableSegment(message) => {
+                write!(f, "Failed to open segment: {message}")
+            }
+            ValidationIssue::AliveBitSet(message) => write!(f, "Alive bitset: {message}"),
+            ValidationIssue::Postings { field, message } => {
+                write!(f, "Postings of {field:?}: {message}")
+            }
+            ValidationIssue::FieldNorms { field, message } => {
+                write!(f, "Fieldnorms of {field:?}: {message}")
+            }
+            ValidationIssue::FastFields(message) => write!(f, "Fast fields: {message}"),
+            ValidationIssue::DocStore(message) => write!(f, "Doc store: {message}"),
+        }
+    }
+}
+
+/// Outcome of the validation of a segment.
+#[derive(Clone, Debug)]
+pub struct SegmentValidationReport {
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Number of documents of the segment, including the deleted ones.
+    pub max_doc: u32,
+    /// Number of deleted documents, according to the segment meta.
+    pub num_deleted_docs: u32,
+    /// Number of terms checked, over all of the indexed fields.
+    pub num_terms: u64,
+    /// Number of (term, document) postings checked.
+    pub num_postings: u64,
+    /// Number of fast field columns checked.
+    pub num_columns: usize,
+    /// Inconsistencies found in the segment.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl SegmentValidationReport {
+    /// Returns true if no inconsistency was found in the segment.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Outcome of [`Index::validate`].
+#[derive(Clone, Debug)]
+pub struct IndexValidationReport {
+    /// Opstamp of the validated commit.
+    pub opstamp: Opstamp,
+    /// Report of each segment of the commit.
+    pub segments: Vec<SegmentValidationReport>,
+}
+
+impl IndexValidationReport {
+    /// Returns true if no inconsistency was found in the index.
+    pub fn is_valid(&self) -> bool {
+        self.segments.iter().all(SegmentValidationReport::is_valid)
+    }
+
+    /// Iterates over the inconsistencies found, with the segment they were found in.
+    pub fn issues(&self) -> impl Iterator<Item = (SegmentId, &ValidationIssue)> + '_ {
+        self.segments.iter().flat_map(|segment| {
+            segment
+                .issues
+                .iter()
+                .map(move |issue| (segment.segment_id, issue))
+        })
+    }
+}
+
+/// Validates all of the segments of the last commit of the index.
+pub(crate) fn validate_index(index: &Index) -> crate::Result<IndexValidationReport> {
+    let metas = index.load_metas()?;
+    let segments = metas
+        .segments
+        .iter()
+        .map(|segment_meta| validate_segment(index, segment_meta))
+        .collect::<crate::Result<Vec<_>>>()?;
+    Ok(IndexValidationReport {
+        opstamp: metas.opstamp,
+        segments,
+    })
+}
+
+fn validate_segment(
+    index: &Index,
+    segment_meta: &SegmentMeta,
+) -> crate::Result<SegmentValidationReport> {
+    let mut report = SegmentValidationReport {
+        segment_id: segment_meta.id(),
+        max_doc: segment_meta.max_doc(),
+        num_deleted_docs: segment_meta.num_deleted_docs(),
+        num_terms: 0,
+        num_postings: 0,
+        num_columns: 0,
+        issues: Vec::new(),
+    };
+    validate_files(index, segment_meta, &mut report.issues)?;
+    // Decoding damaged files could panic: the content of the segment is only checked once its
+    // files are known to be intact.
+    if !report.issues.is_empty() {
+        return Ok(report);
+    }
+    let segment_reader = match SegmentReader::open(&index.segment(segment_meta.clone())) {
+        Ok(segment_reader) => segment_reader,
+        Err(error) => {
+            report
+                .issues
+                .push(ValidationIssue::UnreadableSegment(error.to_string()));
+            return Ok(report);
+        }
+    };
+    validate_alive_bitset(&segment_reader, segment_meta, &mut report.issues);
+    validate_inverted_index(&segment_reader, &mut report);
+    validate_fast_fields(&segment_reader, &mut report);
+    validate_doc_store(&segment_reader, &mut report.issues);
+    Ok(report)
+}
+
+fn validate_files(
+    index: &Index,
+    segment_meta: &SegmentMeta,
+    issues: &mut Vec<ValidationIssue>,
+) -> crate::Result<()> {
+    let directory = index.directory();
+    for &component in SegmentComponent::iterator() {
+        let is_required = match component {
+            SegmentComponent::TempStore => continue,
+            SegmentComponent::Delete if !segment_meta.has_deletes() => continue,
+            // Segments written by older versions do not have these files.
+            SegmentComponent::Positions
+            | SegmentComponent::Points
+            | SegmentComponent::Vectors
+            | SegmentComponent::TermVectors => false,
+            _ => true,
+        };
+        let path = segment_meta.relative_path(component);
+        if !directory.exists(&path)? {
+            if is_required {
+                issues.push(ValidationIssue::MissingFile(path));
+            }
+            continue;
+        }
+        // A file too short to hold a footer fails to open.
+        if !directory.validate_checksum(&path).unwrap_or(false) {
+            issues.push(ValidationIssue::CorruptedFile(path));
+        }
+    }
+    Ok(())
+}
+
+fn validate_alive_bitset(
+    segment_reader: &SegmentReader,
+    segment_meta: &SegmentMeta,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let Some(alive_bitset) = segment_reader.alive_bitset() else {
+        return;
+    };
+    let max_doc = segment_reader.max_doc();
+    let bitset_max_value = alive_bitset.bitset().max_value();
+    if bitset_max_value != max_doc {
+        issues.push(ValidationIssue::AliveBitSet(format!(
+            "The bitset covers {bitset_max_value} documents, but the segment has {max_doc}."
+        )));
+        return;
+    }
+    let num_deleted_docs = max_doc.saturating_sub(alive_bitset.num_alive_docs() as u32);
+    if num_deleted_docs != segment_meta.num_deleted_docs() {
+        issues.push(ValidationIssue::AliveBitSet(format!(
+            "The bitset has {num_deleted_docs} deleted documents, but the segment meta has {}.",
+            segment_meta.num_deleted_docs()
+        )));
+    }
+}
+
+fn validate_inverted_index(segment_reader: &SegmentReader, report: &mut SegmentValidationReport) {
+    let schema = segment_reader.schema();
+    for (field, field_entry) in schema.fields() {
+        let field_name = field_entry.name().to_string();
+        let fieldnorm_reader_opt = if field_entry.has_fieldnorms() {
+            match validate_fieldnorms_len(segment_reader, field) {
+                Ok(fieldnorm_reader_opt) => fieldnorm_reader_opt,
+                Err(message) => {
+                    report.issues.push(ValidationIssue::FieldNorms {
+                        field: field_name.clone(),
+                        message,
+                    });
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let Some(record_option) = field_entry.field_type().get_index_record_option() else {
+            continue;
+        };
+        let num_tokens_per_doc = match validate_postings(segment_reader, field, report) {
+            Ok(num_tokens_per_doc) => num_tokens_per_doc,
+            Err(message) => {
+                report.issues.push(ValidationIssue::Postings {
+                    field: field_name,
+                    message,
+                });
+                continue;
+            }
+        };
+        // The fieldnorm of a text field is its number of tokens. Other fields count their
+        // values, which can map to several terms (e.g. the ancestors of a facet).
+        if !matches!(field_entry.field_type(), FieldType::Str(_)) {
+            continue;
+        }
+        let Some(fieldnorm_reader) = fieldnorm_reader_opt else {
+            continue;
+        };
+        if let Err(message) = validate_fieldnorms_against_postings(
+            &fieldnorm_reader,
+            &num_tokens_per_doc,
+            record_option,
+        ) {
+            report.issues.push(ValidationIssue::FieldNorms {
+                field: field_name,
+                message,
+            });
+        }
+    }
+}
+
+fn validate_fieldnorms_len(
+    segment_reader: &SegmentReader,
+    field: Field,
+) -> Result<Option<FieldNormReader>, String> {
+    let Some(fieldnorm_reader) = segment_reader
+        .fieldnorms_readers()
+        .get_field(field)
+        .map_err(|error| error.to_string())?
+    else {
+        return Ok(None);
+    };
+    let max_doc = segment_reader.max_doc();
+    if fieldnorm_reader.num_docs() != max_doc {
+        return Err(format!(
+            "{} fieldnorms for {max_doc} documents.",
+            fieldnorm_reader.num_docs()
+        ));
+    }
+    Ok(Some(fieldnorm_reader))
+}
+
+/// Checks that the postings of each term of the field are sorted, within the segment and
+/// consistent with the doc freq of the term.
+///
+/// Returns the sum of the term frequencies of each document.
+fn validate_postings(
+    segment_reader: &SegmentReader,
+    field: Field,
+    report: &mut SegmentValidationReport,
+) -> Result<Vec<u32>, String> {
+    let max_doc = segment_reader.max_doc();
+    let inverted_index = segment_reader
+        .inverted_index(field)
+        .map_err(|error| error.to_string())?;
+    let mut num_tokens_per_doc = vec![0u32; max_doc as usize];
+    let mut term_stream = inverted_index
+        .terms()
+        .stream()
+        .map_err(|error| error.to_string())?;
+    while term_stream.advance() {
+        report.num_terms += 1;
+        let term_info = term_stream.value();
+        let describe_term = || String::from_utf8_lossy(term_stream.key()).to_string();
+        let mut postings = inverted_index
+            .read_postings_from_terminfo(term_info, IndexRecordOption::WithFreqs)
+            .map_err(|error| format!("Failed to read term {:?}: {error}", describe_term()))?;
+        let mut doc_freq = 0u32;
+        let mut previous_doc_opt: Option<DocId> = None;
+        let mut doc = postings.doc();
+        while doc != TERMINATED {
+            if doc >= max_doc {
+                return Err(format!(
+                    "Term {:?} has document {doc}, beyond the {max_doc} documents of the segment.",
+                    describe_term()
+                ));
+            }
+            if previous_doc_opt.is_some_and(|previous_doc| previous_doc >= doc) {
+                return Err(format!(
+                    "The documents of term {:?} are not sorted.",
+                    describe_term()
+                ));
+            }
+            let term_freq = postings.term_freq();
+            if term_freq == 0 {
+                return Err(format!(
+                    "Term {:?} has a null term frequency in document {doc}.",
+                    describe_term()
+                ));
+            }
+            num_tokens_per_doc[doc as usize] += term_freq;
+            doc_freq += 1;
+            previous_doc_opt = Some(doc);
+            doc = postings.advance();
+        }
+        if doc_freq != term_info.doc_freq {
+            return Err(format!(
+                "Term {:?} has {doc_freq} documents, but a doc freq of {}.",
+                describe_term(),
+                term_info.doc_freq
+            ));
+        }
+        report.num_postings += u64::from(doc_freq);
+    }
+    Ok(num_tokens_per_doc)
+}
+
+fn validate_fieldnorms_against_postings(
+    fieldnorm_reader: &FieldNormReader,
+    num_tokens_per_doc: &[u32],
+    record_option: IndexRecordOption,
+) -> Result<(), String> {
+    let mut num_mismatches = 0u32;
+    let mut first_mismatch_opt: Option<(DocId, u32)> = None;
+    for (doc, &num_tokens) in num_tokens_per_doc.iter().enumerate() {
+        let doc = doc as DocId;
+        let expected_fieldnorm_id = FieldNormReader::fieldnorm_to_id(num_tokens);
+        let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
+        // Without term frequencies, the postings only give the number of distinct terms.
+        let is_consistent = if record_option.has_freq() {
+            expected_fieldnorm_id == fieldnorm_id
+        } else {
+            expected_fieldnorm_id <= fieldnorm_id
+        };
+        if !is_consistent {
+            num_mismatches += 1;
+            first_mismatch_opt.get_or_insert((doc, num_tokens));
+        }
+    }
+    if let Some((doc, num_tokens)) = first_mismatch_opt {
+        return Err(format!(
+            "{num_mismatches} documents do not match their postings, e.g. document {doc} has a \
+             fieldnorm of {} for {num_tokens} tokens.",
+            fieldnorm_reader.fieldnorm(doc)
+        ));
+    }
+    Ok(())
+}
+
+fn validate_fast_fields(segment_reader: &SegmentReader, report: &mut SegmentValidationReport) {
+    let max_doc = segment_reader.max_doc();
+    let columnar = segment_reader.fast_fields().columnar();
+    if columnar.num_docs() != max_doc {
+        report.issues.push(ValidationIssue::FastFields(format!(
+            "The columnar has {} rows for {max_doc} documents.",
+            columnar.num_docs()
+        )));
+        return;
+    }
+    let columns = match columnar.list_columns() {
+        Ok(columns) => columns,
+        Err(io_error) => {
+            report
+                .issues
+                .push(ValidationIssue::FastFields(io_error.to_string()));
+            return;
+        }
+    };
+    for (column_name, column_handle) in columns {
+        report.num_columns += 1;
+        let column_name = column_name.replace(JSON_PATH_SEGMENT_SEP_STR, ".");
+        let num_docs = match column_handle.open() {
+            Ok(column) => column.num_docs(),
+            Err(io_error) => {
+                report.issues.push(ValidationIssue::FastFields(format!(
+                    "Failed to open column {column_name:?}: {io_error}"
+                )));
+                continue;
+            }
+        };
+        if num_docs != max_doc {
+            report.issues.push(ValidationIssue::FastFields(format!(
+                "Column {column_name:?} has {num_docs} rows for {max_doc} documents."
+            )));
+        }
+    }
+}
+
+fn validate_doc_store(segment_reader: &SegmentReader, issues: &mut Vec<ValidationIssue>) {
+    let max_doc = segment_reader.max_doc();
+    let store_reader = match segment_reader.get_store_reader(0) {
+        Ok(store_reader) => store_reader,
+        Err(io_error) => {
+            issues.push(ValidationIssue::DocStore(io_error.to_string()));
+            return;
+        }
+    };
+    // Every document has an entry, possibly empty, in the blocks of every family.
+    for family_ord in 0..=store_reader.family_layout().len() {
+        let mut num_docs = 0;
+        for checkpoint in store_reader.family_block_checkpoints(family_ord) {
+            if checkpoint.doc_range.start != num_docs {
+                issues.push(ValidationIssue::DocStore(format!(
+                    "The blocks of family {family_ord} are not contiguous at document {}.",
+                    checkpoint.doc_range.start
+                )));
+                return;
+            }
+            num_docs = checkpoint.doc_range.end;
+        }
+        if num_docs != max_doc {
+            issues.push(ValidationIssue::DocStore(format!(
+                "Family {family_ord} has {num_docs} documents, but the segment has {max_doc}."
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::directory::{Directory, RamDirectory};
+    use crate::index::{SegmentComponent, ValidationIssue};
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, FAST, INDEXED, STORED, STRING, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, Term};
+
+    fn build_index(directory: RamDirectory) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let num = schema_builder.add_u64_field("num", INDEXED | FAST);
+        let attributes = schema_builder.add_json_field("attributes", TEXT | FAST);
+        let index = Index::create(directory, schema_builder.build(), IndexSettings::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..20u64 {
+            let tag_value = if i % 3 == 0 { "drop" } else { "keep" };
+            index_writer.add_document(doc!(
+                title => "the quick brown fox jumps over the lazy dog the end",
+                tag => tag_value,
+                num => i,
+            ))?;
+            if i % 7 == 0 {
+                index_writer.add_document(doc!(
+                    attributes => serde_json::json!({"color": "red", "size": i}),
+                ))?;
+                index_writer.commit()?;
+            }
+        }
+        index_writer.delete_term(Term::from_field_text(tag, "drop"));
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_validate_index() -> crate::Result<()> {
+        let index = build_index(RamDirectory::default())?;
+        let report = index.validate()?;
+        assert!(
+            report.is_valid(),
+            "{:?}",
+            report.issues().collect::<Vec<_>>()
+        );
+        assert_eq!(report.segments.len(), 4);
+        assert_eq!(
+            report
+                .segments
+                .iter()
+                .map(|segment| segment.num_deleted_docs)
+                .sum::<u32>(),
+            7
+        );
+        assert!(report.segments.iter().all(|segment| segment.num_terms > 0
+            && segment.num_postings >= u64::from(segment.max_doc)
+            && segment.num_columns > 0));
+
+        let segment_ids = index.searchable_segment_ids()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.merge(&segment_ids).wait()?;
+        let report = index.validate()?;
+        assert!(
+            report.is_valid(),
+            "{:?}",
+            report.issues().collect::<Vec<_>>()
+        );
+        assert_eq!(report.segments.len(), 1);
+        assert_eq!(report.segments[0].max_doc, 16);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_index_damaged_files() -> crate::Result<()> {
+        let directory = RamDirectory::default();
+        let index = build_index(directory.clone())?;
+        let segment_metas = index.searchable_segment_metas()?;
+
+        let store_path = segment_metas[0].relative_path(SegmentComponent::Store);
+        let mut store_data = directory.open_read(&store_path)?.read_bytes()?.to_vec();
+        store_data[0] ^= 0xFF;
+        directory.atomic_write(&store_path, &store_data)?;
+        let fieldnorms_path = segment_metas[1].relative_path(SegmentComponent::FieldNorms);
+        directory.delete(&fieldnorms_path).unwrap();
+
+        let report = index.validate()?;
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.segments[0].issues,
+            vec![ValidationIssue::CorruptedFile(store_path)]
+        );
+        assert_eq!(
+            report.segments[1].issues,
+            vec![ValidationIssue::MissingFile(fieldnorms_path)]
+        );
+        assert!(report.segments[2..]
+            .iter()
+            .all(|segment| segment.is_valid()));
+        assert_eq!(report.issues().count(), 2);
+        Ok(())
+    }
+}
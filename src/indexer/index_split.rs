@@ -0,0 +1,258 @@
+use std::hash::Hasher;
+
+use common::{BitSet, OwnedBytes};
+use fnv::FnvHasher;
+
+use crate::directory::Directory;
+use crate::fastfield::{write_alive_bitset, AliveBitSet};
+use crate::indexer::merge_filtered_segments;
+use crate::{DocId, Index, SegmentReader, TantivyError};
+
+/// Routes the documents of a segment to a shard.
+///
+/// Given a document of the segment, returns the ordinal of its shard.
+pub type SegmentShardRouter = Box<dyn Fn(DocId) -> usize>;
+
+/// Returns a router for [`split_index`] hashing the first value of the fast field
+/// `field_name`.
+///
+/// Strings and bytes are hashed on their content, and other types on their `u64`
+/// representation, so that a given value is routed to the same shard regardless
+/// of the segment it belongs to.
+/// Documents without any value for this field are routed to the shard `0`.
+///
+/// Returns an error if `num_shards` is `0`.
+pub fn route_by_fast_field_hash(
+    field_name: &str,
+    num_shards: usize,
+) -> crate::Result<impl Fn(&SegmentReader) -> crate::Result<SegmentShardRouter>> {
+    if num_shards == 0 {
+        return Err(TantivyError::InvalidArgument(
+            "At least one shard is required to route documents".to_string(),
+        ));
+    }
+    let field_name = field_name.to_string();
+    let router = move |segment_reader: &SegmentReader| -> crate::Result<SegmentShardRouter> {
+        let fast_fields = segment_reader.fast_fields();
+        if let Some(bytes_column) = fast_fields
+            .str(&field_name)?
+            .map(Into::into)
+            .or(fast_fields.bytes(&field_name)?)
+        {
+            return Ok(Box::new(move |doc: DocId| {
+                let Some(term_ord) = bytes_column.term_ords(doc).next() else {
+                    return 0;
+                };
+                let mut term_bytes = Vec::new();
+                if !bytes_column
+                    .ord_to_bytes(term_ord, &mut term_bytes)
+                    .unwrap_or(false)
+                {
+                    return 0;
+                }
+                let mut hasher = FnvHasher::default();
+                hasher.write(&term_bytes);
+                (hasher.finish() % num_shards as u64) as usize
+            }));
+        }
+        if let Some((column, _)) = fast_fields.u64_lenient(&field_name)? {
+            return Ok(Box::new(move |doc: DocId| {
+                let Some(val) = column.first(doc) else {
+                    return 0;
+                };
+                let mut hasher = FnvHasher::default();
+                hasher.write_u64(val);
+                (hasher.finish() % num_shards as u64) as usize
+            }));
+        }
+        Ok(Box::new(|_doc| 0))
+    };
+    Ok(router)
+}
+
+/// Splits the last commit of `index` into as many new indices as there are
+/// `output_directories`.
+///
+/// `router` is called once per segment of `index`, and returns the function
+/// assigning a shard, that is an index in `output_directories`, to each of its documents.
+/// [`route_by_fast_field_hash`] covers the common case of routing on the hash of a field.
+///
+/// The segments are rewritten via a merge: the text of the documents is not analyzed again.
+/// Each resulting index contains at most one segment, and is committed.
+///
+/// `output_directories` are expected to be empty.
+///
+/// # Warning
+/// This function does NOT take the `IndexWriter` lock of `index`.
+pub fn split_index<R>(
+    index: &Index,
+    output_directories: Vec<Box<dyn Directory>>,
+    router: R,
+) -> crate::Result<Vec<Index>>
+where
+    R: Fn(&SegmentReader) -> crate::Result<SegmentShardRouter>,
+{
+    let num_shards = output_directories.len();
+    if num_shards == 0 {
+        return Err(TantivyError::InvalidArgument(
+            "At least one output directory is required to split an index".to_string(),
+        ));
+    }
+    let segments = index.searchable_segments()?;
+
+    // shard_bitsets[shard_ord][segment_ord] are the docs of the segment routed to the shard.
+    let mut shard_bitsets: Vec<Vec<BitSet>> = (0..num_shards)
+        .map(|_| {
+            segments
+                .iter()
+                .map(|segment| BitSet::with_max_value(segment.meta().max_doc()))
+                .collect()
+        })
+        .collect();
+    for (segment_ord, segment) in segments.iter().enumerate() {
+        let segment_reader = SegmentReader::open(segment)?;
+        let segment_router = router(&segment_reader)?;
+        for doc in segment_reader.doc_ids_alive() {
+            let shard_ord = segment_router(doc);
+            if shard_ord >= num_shards {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Document routed to shard {shard_ord}, but there are only {num_shards} shards"
+                )));
+            }
+            shard_bitsets[shard_ord][segment_ord].insert(doc);
+        }
+    }
+
+    let mut shard_indices = Vec::with_capacity(num_shards);
+    for (output_directory, segment_bitsets) in output_directories.into_iter().zip(shard_bitsets) {
+        let mut shard_segments = Vec::new();
+        let mut shard_alive_bitsets = Vec::new();
+        for (segment, bitset) in segments.iter().zip(segment_bitsets) {
+            if bitset.len() == 0 {
+                continue;
+            }
+            shard_segments.push(segment.clone());
+            shard_alive_bitsets.push(Some(to_alive_bitset(&bitset)?));
+        }
+        let shard_index = if shard_segments.is_empty() {
+            Index::create(output_directory, index.schema(), index.settings().clone())?
+        } else {
+            merge_filtered_segments(
+                &shard_segments,
+                index.settings().clone(),
+                shard_alive_bitsets,
+                output_directory,
+            )?
+        };
+        shard_indices.push(shard_index);
+    }
+    Ok(shard_indices)
+}
+
+fn to_alive_bitset(bitset: &BitSet) -> crate::Result<AliveBitSet> {
+    let mut buffer = Vec::new();
+    write_alive_bitset(bitset, &mut buffer)?;
+    Ok(AliveBitSet::open(OwnedBytes::new(buffer)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Count;
+    use crate::directory::RamDirectory;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING, TEXT};
+    use crate::{IndexWriter, Term};
+
+    fn test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING | FAST);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..20 {
+            let tenant_name = format!("tenant{}", i % 5);
+            index_writer.add_document(doc!(tenant => tenant_name, text => "hello"))?;
+            if i % 7 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.delete_term(Term::from_field_text(tenant, "tenant0"));
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn num_docs(index: &Index) -> crate::Result<usize> {
+        let text = index.schema().get_field("text").unwrap();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        index.reader()?.searcher().search(&query, &Count)
+    }
+
+    #[test]
+    fn test_split_index_by_fast_field_hash() -> crate::Result<()> {
+        let index = test_index()?;
+        let output_directories: Vec<Box<dyn Directory>> = (0..3)
+            .map(|_| Box::new(RamDirectory::default()) as Box<dyn Directory>)
+            .collect();
+        let shards = split_index(
+            &index,
+            output_directories,
+            route_by_fast_field_hash("tenant", 3)?,
+        )?;
+        assert_eq!(shards.len(), 3);
+        let mut total_num_docs = 0;
+        for shard in &shards {
+            assert!(shard.searchable_segments()?.len() <= 1);
+            total_num_docs += num_docs(shard)?;
+            // All of the documents of a tenant land in the same shard.
+            let searcher = shard.reader()?.searcher();
+            let tenant = shard.schema().get_field("tenant").unwrap();
+            for tenant_ord in 1..5 {
+                let term = Term::from_field_text(tenant, &format!("tenant{tenant_ord}"));
+                let count =
+                    searcher.search(&TermQuery::new(term, IndexRecordOption::Basic), &Count)?;
+                assert!(count == 0 || count == 4);
+            }
+        }
+        assert_eq!(total_num_docs, 16);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_index_custom_router() -> crate::Result<()> {
+        let index = test_index()?;
+        let output_directories: Vec<Box<dyn Directory>> = vec![
+            Box::new(RamDirectory::default()),
+            Box::new(RamDirectory::default()),
+        ];
+        // Everything goes to the second shard.
+        let shards = split_index(&index, output_directories, |_segment_reader| {
+            Ok(Box::new(|_doc| 1))
+        })?;
+        assert_eq!(num_docs(&shards[0])?, 0);
+        assert_eq!(num_docs(&shards[1])?, 16);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_index_invalid_shard() -> crate::Result<()> {
+        let index = test_index()?;
+        let output_directories: Vec<Box<dyn Directory>> = vec![Box::new(RamDirectory::default())];
+        let split_res = split_index(&index, output_directories, |_segment_reader| {
+            Ok(Box::new(|_doc| 1))
+        });
+        assert!(matches!(split_res, Err(TantivyError::InvalidArgument(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_route_by_fast_field_hash_no_shards() {
+        assert!(matches!(
+            route_by_fast_field_hash("tenant", 0),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+    }
+}
@@ -2,11 +2,13 @@ use std::ops::Range;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Instant;
 
 use common::BitSet;
 use smallvec::smallvec;
 
 use super::operation::{AddOperation, UserOperation};
+use super::segment_id_generator::SegmentIdGenerator;
 use super::segment_updater::SegmentUpdater;
 use super::{AddBatch, AddBatchReceiver, AddBatchSender, PreparedCommit};
 use crate::directory::{DirectoryLock, GarbageCollectionResult, TerminatingWrite};
@@ -15,13 +17,17 @@ use crate::fastfield::write_alive_bitset;
 use crate::index::{Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
+use crate::indexer::index_writer_metrics::{IndexWriterEventCallback, IndexWriterMetrics};
 use crate::indexer::index_writer_status::IndexWriterStatus;
+use crate::indexer::merger::IndexMerger;
 use crate::indexer::operation::DeleteOperation;
 use crate::indexer::stamper::Stamper;
-use crate::indexer::{MergePolicy, SegmentEntry, SegmentWriter};
+use crate::indexer::{
+    MergePolicy, MergeScheduler, NoMergePolicy, SegmentEntry, SegmentSerializer, SegmentWriter,
+};
 use crate::query::{EnableScoring, Query, TermQuery};
-use crate::schema::document::Document;
-use crate::schema::{IndexRecordOption, TantivyDocument, Term};
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, FieldType, IndexRecordOption, Schema, TantivyDocument, Term};
 use crate::{FutureResult, Opstamp};
 
 // Size of the margin for the `memory_arena`. A segment is closed when the remaining memory
@@ -60,6 +66,34 @@ pub struct IndexWriterOptions {
     #[builder(default = 4)]
     /// Defines the number of merger threads to use.
     num_merge_threads: usize,
+    /// The number of segments, committed or not, above which adding documents blocks
+    /// until the ongoing merges complete.
+    ///
+    /// This makes it possible for bulk loaders to avoid an unbounded buildup of segments
+    /// when indexing outpaces merging. Adding documents never blocks if no merge is ongoing.
+    /// By default, there is no limit.
+    max_num_segments: Option<usize>,
+    #[builder(default)]
+    /// If true, the same sequence of operations always produces byte-identical index files,
+    /// which makes it possible to cache index builds or to address them by content.
+    ///
+    /// Segment ids are derived from the last commit opstamp instead of being random,
+    /// and documents get their doc ids in the order in which they are added.
+    /// This requires a single worker thread, and disables the merge policy by
+    /// default: background merges run concurrently with indexing, so their
+    /// outcome is not reproducible. Explicit calls to [`IndexWriter::merge`]
+    /// remain reproducible as long as their result is waited for before
+    /// any other operation.
+    deterministic: bool,
+    #[builder(default)]
+    /// If true, when an indexer thread reaches its memory budget, the documents
+    /// buffered so far are written to a temporary run on disk, and indexing goes on
+    /// in the same segment. The runs are merged into a single segment when the segment
+    /// is flushed, on commit.
+    ///
+    /// This makes it possible to index large batches with a small memory budget
+    /// without producing many small segments.
+    spill_to_disk: bool,
 }
 
 /// `IndexWriter` is the user entry-point to add document to an index.
@@ -92,6 +126,54 @@ pub struct IndexWriter<D: Document = TantivyDocument> {
     committed_opstamp: Opstamp,
 }
 
+/// Returns the term identifying `document`, given the unique key of the schema.
+fn unique_key_term<D: Document>(
+    schema: &Schema,
+    key_field: Field,
+    document: &D,
+) -> crate::Result<Term> {
+    let key_field_entry = schema.get_field_entry(key_field);
+    let mut key_term = None;
+    for (field, value) in document.iter_fields_and_values() {
+        if field != key_field {
+            continue;
+        }
+        if key_term.is_some() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The document has several values for its unique key {}",
+                key_field_entry.name()
+            )));
+        }
+        let term_opt = match key_field_entry.field_type() {
+            FieldType::U64(_) => value
+                .as_u64()
+                .map(|val| Term::from_field_u64(key_field, val)),
+            FieldType::I64(_) => value
+                .as_i64()
+                .map(|val| Term::from_field_i64(key_field, val)),
+            FieldType::Bytes(_) => value
+                .as_bytes()
+                .map(|bytes| Term::from_field_bytes(key_field, bytes)),
+            FieldType::Str(_) => value
+                .as_str()
+                .map(|text| Term::from_field_text(key_field, text)),
+            _ => None,
+        };
+        key_term = Some(term_opt.ok_or_else(|| {
+            TantivyError::InvalidArgument(format!(
+                "Invalid value for the unique key {}",
+                key_field_entry.name()
+            ))
+        })?);
+    }
+    key_term.ok_or_else(|| {
+        TantivyError::InvalidArgument(format!(
+            "The document does not have a value for its unique key {}",
+            key_field_entry.name()
+        ))
+    })
+}
+
 fn compute_deleted_bitset(
     alive_bitset: &mut BitSet,
     segment_reader: &SegmentReader,
@@ -181,18 +263,41 @@ pub(crate) fn advance_deletes(
 
 fn index_documents<D: Document>(
     memory_budget: usize,
-    segment: Segment,
+    spill_to_disk: bool,
+    mut segment: Segment,
     grouped_document_iterator: &mut dyn Iterator<Item = AddBatch<D>>,
     segment_updater: &SegmentUpdater,
     mut delete_cursor: DeleteCursor,
 ) -> crate::Result<()> {
+    let metrics = segment_updater.metrics();
+    let mut reported_mem_usage = 0;
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
+    // Runs spilled to disk, with the opstamps of their documents.
+    let mut runs: Vec<(Segment, Vec<Opstamp>)> = Vec::new();
     for document_group in grouped_document_iterator {
+        let num_docs = document_group.len() as u64;
         for doc in document_group {
             segment_writer.add_document(doc)?;
         }
+        metrics.record_docs_indexed(num_docs);
         let mem_usage = segment_writer.mem_usage();
+        metrics.record_memory_usage(&mut reported_mem_usage, mem_usage);
         if mem_usage >= memory_budget - MARGIN_IN_BYTES {
+            if spill_to_disk {
+                info!(
+                    "Buffer limit reached, spilling run with maxdoc={}.",
+                    segment_writer.max_doc()
+                );
+                let run_segment = segment_updater.new_segment();
+                let run_segment_writer =
+                    SegmentWriter::for_segment(memory_budget, run_segment.clone())?;
+                let full_segment_writer =
+                    std::mem::replace(&mut segment_writer, run_segment_writer);
+                let full_segment = std::mem::replace(&mut segment, run_segment);
+                runs.push(finalize_run(full_segment_writer, full_segment)?);
+                metrics.record_memory_usage(&mut reported_mem_usage, segment_writer.mem_usage());
+                continue;
+            }
             info!(
                 "Buffer limit reached, flushing segment with maxdoc={}.",
                 segment_writer.max_doc()
@@ -202,29 +307,87 @@ fn index_documents<D: Document>(
     }
 
     if !segment_updater.is_alive() {
+        metrics.record_memory_usage(&mut reported_mem_usage, 0);
         return Ok(());
     }
-
-    let max_doc = segment_writer.max_doc();
-
-    // this is ensured by the call to peek before starting
-    // the worker thread.
-    assert!(max_doc > 0);
-
-    let doc_opstamps: Vec<Opstamp> = segment_writer.finalize()?;
-
-    let segment_with_max_doc = segment.with_max_doc(max_doc);
+    let flush_start = Instant::now();
+    #[cfg(feature = "tracing")]
+    let flush_span = tracing::debug_span!(
+        "tantivy_flush_segment",
+        num_docs = tracing::field::Empty,
+        num_runs = runs.len(),
+        mem_usage_bytes = segment_writer.mem_usage()
+    )
+    .entered();
+
+    let (segment_with_max_doc, doc_opstamps) = if runs.is_empty() {
+        let max_doc = segment_writer.max_doc();
+
+        // this is ensured by the call to peek before starting
+        // the worker thread.
+        assert!(max_doc > 0);
+
+        let doc_opstamps_res = segment_writer.finalize();
+        metrics.record_memory_usage(&mut reported_mem_usage, 0);
+        let doc_opstamps: Vec<Opstamp> = doc_opstamps_res?;
+        (segment.with_max_doc(max_doc), doc_opstamps)
+    } else {
+        let last_run_res = if segment_writer.max_doc() > 0 {
+            finalize_run(segment_writer, segment).map(Some)
+        } else {
+            Ok(None)
+        };
+        metrics.record_memory_usage(&mut reported_mem_usage, 0);
+        runs.extend(last_run_res?);
+        merge_runs(segment_updater, runs)?
+    };
 
     let alive_bitset_opt = apply_deletes(&segment_with_max_doc, &mut delete_cursor, &doc_opstamps)?;
 
     let meta = segment_with_max_doc.meta().clone();
     meta.untrack_temp_docstore();
+    metrics.record_segment_flushed(&meta, flush_start.elapsed());
+    #[cfg(feature = "tracing")]
+    {
+        flush_span.record("num_docs", meta.max_doc());
+        flush_span.exit();
+    }
     // update segment_updater inventory to remove tempstore
     let segment_entry = SegmentEntry::new(meta, delete_cursor, alive_bitset_opt);
     segment_updater.schedule_add_segment(segment_entry).wait()?;
     Ok(())
 }
 
+/// Serializes a run of documents spilled to disk, returning its segment and
+/// the opstamps of its documents.
+fn finalize_run(
+    segment_writer: SegmentWriter,
+    segment: Segment,
+) -> crate::Result<(Segment, Vec<Opstamp>)> {
+    let max_doc = segment_writer.max_doc();
+    let doc_opstamps = segment_writer.finalize()?;
+    let segment = segment.with_max_doc(max_doc);
+    segment.meta().untrack_temp_docstore();
+    Ok((segment, doc_opstamps))
+}
+
+/// Merges the runs spilled to disk into a single segment, keeping the documents in order.
+///
+/// The files of the runs are removed by the next garbage collection.
+fn merge_runs(
+    segment_updater: &SegmentUpdater,
+    runs: Vec<(Segment, Vec<Opstamp>)>,
+) -> crate::Result<(Segment, Vec<Opstamp>)> {
+    let (run_segments, run_doc_opstamps): (Vec<Segment>, Vec<Vec<Opstamp>>) =
+        runs.into_iter().unzip();
+    let segment = segment_updater.new_segment();
+    let merger = IndexMerger::open(segment.schema(), &run_segments)?;
+    let segment_serializer = SegmentSerializer::for_segment(segment.clone())?;
+    let max_doc = merger.write(segment_serializer)?;
+    let doc_opstamps: Vec<Opstamp> = run_doc_opstamps.into_iter().flatten().collect();
+    Ok((segment.with_max_doc(max_doc), doc_opstamps))
+}
+
 /// `doc_opstamps` is required to be non-empty.
 fn apply_deletes(
     segment: &Segment,
@@ -299,6 +462,13 @@ impl<D: Document> IndexWriter<D> {
             let err_msg = "At least one worker thread is required, got 0".to_string();
             return Err(TantivyError::InvalidArgument(err_msg));
         }
+        if options.deterministic && options.num_worker_threads != 1 {
+            let err_msg = format!(
+                "Deterministic indexing requires a single worker thread, got {}",
+                options.num_worker_threads
+            );
+            return Err(TantivyError::InvalidArgument(err_msg));
+        }
 
         let (document_sender, document_receiver) =
             crossbeam_channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
@@ -314,7 +484,12 @@ impl<D: Document> IndexWriter<D> {
             stamper.clone(),
             &delete_queue.cursor(),
             options.num_merge_threads,
+            SegmentIdGenerator::new(options.deterministic, current_opstamp),
+            directory_lock.lost_flag(),
         )?;
+        if options.deterministic {
+            segment_updater.set_merge_policy(Box::new(NoMergePolicy));
+        }
 
         let mut index_writer = Self {
             _directory_lock: Some(directory_lock),
@@ -394,7 +569,7 @@ impl<D: Document> IndexWriter<D> {
     /// These will not be garbage collected as long as an instance object of
     /// `SegmentMeta` object associated with the new `Segment` is "alive".
     pub fn new_segment(&self) -> Segment {
-        self.index.new_segment()
+        self.segment_updater.new_segment()
     }
 
     fn operation_receiver(&self) -> crate::Result<AddBatchReceiver<D>> {
@@ -420,7 +595,7 @@ impl<D: Document> IndexWriter<D> {
         let mut delete_cursor = self.delete_queue.cursor();
 
         let mem_budget = self.options.memory_budget_per_thread;
-        let index = self.index.clone();
+        let spill_to_disk = self.options.spill_to_disk;
         let join_handle: JoinHandle<crate::Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
             .spawn(move || {
@@ -449,7 +624,8 @@ impl<D: Document> IndexWriter<D> {
 
                     index_documents(
                         mem_budget,
-                        index.new_segment(),
+                        spill_to_disk,
+                        segment_updater.new_segment(),
                         &mut document_iterator,
                         &segment_updater,
                         delete_cursor.clone(),
@@ -471,6 +647,55 @@ impl<D: Document> IndexWriter<D> {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Accessor to the merge scheduler.
+    pub fn get_merge_scheduler(&self) -> Arc<dyn MergeScheduler> {
+        self.segment_updater.get_merge_scheduler()
+    }
+
+    /// Setter for the merge scheduler, which decides which pending merge starts
+    /// when a merge thread becomes available.
+    pub fn set_merge_scheduler(&self, merge_scheduler: Box<dyn MergeScheduler>) {
+        self.segment_updater.set_merge_scheduler(merge_scheduler);
+    }
+
+    /// Prevents merges from starting, e.g. to give all of the resources to indexing or
+    /// search for a while.
+    ///
+    /// Running merges complete, and merges scheduled in the meantime wait until
+    /// [`IndexWriter::resume_merging()`] is called. Waiting for the merging threads
+    /// resumes merging.
+    pub fn pause_merging(&self) {
+        self.segment_updater.pause_merging();
+    }
+
+    /// Lets the merges paused by [`IndexWriter::pause_merging()`] start.
+    pub fn resume_merging(&self) {
+        self.segment_updater.resume_merging();
+    }
+
+    /// Returns true if merging is paused.
+    pub fn is_merging_paused(&self) -> bool {
+        self.segment_updater.is_merging_paused()
+    }
+
+    /// Returns a snapshot of the metrics of the indexing and merging threads.
+    pub fn metrics(&self) -> IndexWriterMetrics {
+        self.segment_updater
+            .metrics()
+            .snapshot(self.segment_updater.num_pending_merges())
+    }
+
+    /// Sets a callback receiving the [`IndexWriterEvent`](crate::indexer::IndexWriterEvent)s,
+    /// such as segment flushes and merge completions.
+    ///
+    /// The callback is called from the indexing and merging threads, and should
+    /// return quickly.
+    pub fn set_event_callback(&self, event_callback: IndexWriterEventCallback) {
+        self.segment_updater
+            .metrics()
+            .set_event_callback(Some(event_callback));
+    }
+
     fn start_workers(&mut self) -> crate::Result<()> {
         for _ in 0..self.options.num_worker_threads {
             self.add_indexing_worker()?;
@@ -575,6 +800,10 @@ impl<D: Document> IndexWriter<D> {
             .expect("The IndexWriter does not have any lock. This is a bug, please report.");
 
         let new_index_writer = IndexWriter::new(&self.index, self.options.clone(), directory_lock)?;
+        new_index_writer
+            .segment_updater
+            .metrics()
+            .set_event_callback(self.segment_updater.metrics().event_callback());
 
         // the current `self` is dropped right away because of this call.
         //
@@ -594,6 +823,55 @@ impl<D: Document> IndexWriter<D> {
         Ok(self.committed_opstamp)
     }
 
+    /// Cuts the indexing queue and waits for the indexing workers to flush
+    /// their current segment. New workers are spawned to replace them.
+    fn flush_workers(&mut self) -> crate::Result<()> {
+        // this will drop the current document channel
+        // and recreate a new one.
+        self.recreate_document_channel();
+
+        let former_workers_join_handle = std::mem::take(&mut self.workers_join_handle);
+
+        for worker_handle in former_workers_join_handle {
+            let indexing_worker_result = worker_handle
+                .join()
+                .map_err(|e| TantivyError::ErrorInThread(format!("{e:?}")))?;
+            indexing_worker_result?;
+            self.add_indexing_worker()?;
+        }
+        Ok(())
+    }
+
+    /// Makes all of the operations added so far searchable, without committing them.
+    ///
+    /// Like `prepare_commit()`, pending documents are flushed into new segments and
+    /// deletes are applied. The resulting segments are then published to the
+    /// [`IndexReader`](crate::IndexReader)s of this index, but the `meta.json` file is
+    /// left untouched: a refresh skips the directory sync and does not make
+    /// anything durable. After a crash or a `rollback()`, the index reverts
+    /// to its last commit.
+    ///
+    /// Readers with the [`ReloadPolicy::OnCommitWithDelay`](crate::ReloadPolicy) policy
+    /// are reloaded before this method returns. Readers with the `Manual` policy
+    /// will see the refreshed segments on their next call to `reload()`.
+    ///
+    /// Returns the opstamp up to which operations are visible.
+    pub fn refresh(&mut self) -> crate::Result<Opstamp> {
+        info!("Refreshing");
+        self.flush_workers()?;
+        let refresh_opstamp = self.stamper.stamp();
+        let segment_metas = self
+            .segment_updater
+            .schedule_refresh(refresh_opstamp)
+            .wait()?;
+        self.index
+            .near_real_time()
+            .publish(refresh_opstamp, segment_metas)
+            .wait()?;
+        info!("Refreshed {}", refresh_opstamp);
+        Ok(refresh_opstamp)
+    }
+
     /// Prepares a commit.
     ///
     /// Calling `prepare_commit()` will cut the indexing
@@ -628,19 +906,8 @@ impl<D: Document> IndexWriter<D> {
         // committed segments.
         info!("Preparing commit");
 
-        // this will drop the current document channel
-        // and recreate a new one.
-        self.recreate_document_channel();
-
-        let former_workers_join_handle = std::mem::take(&mut self.workers_join_handle);
-
-        for worker_handle in former_workers_join_handle {
-            let indexing_worker_result = worker_handle
-                .join()
-                .map_err(|e| TantivyError::ErrorInThread(format!("{e:?}")))?;
-            indexing_worker_result?;
-            self.add_indexing_worker()?;
-        }
+        self.segment_updater.check_lock()?;
+        self.flush_workers()?;
 
         let commit_opstamp = self.stamper.stamp();
         let prepared_commit = PreparedCommit::new(self, commit_opstamp);
@@ -724,12 +991,39 @@ impl<D: Document> IndexWriter<D> {
     /// The opstamp is an increasing `u64` that can
     /// be used by the client to align commits with its own
     /// document queue.
+    ///
+    /// The unique key of the schema, if any, is not checked: the document is added even if
+    /// another document has the same key. Use [`IndexWriter::upsert`] to replace it instead.
     pub fn add_document(&self, document: D) -> crate::Result<Opstamp> {
         let opstamp = self.stamper.stamp();
         self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])?;
         Ok(opstamp)
     }
 
+    /// Adds a document, replacing any previous document with the same unique key.
+    ///
+    /// The deletion of the previous document and the addition of the new one are
+    /// atomic: a commit contains both or none of them. Like other deletes, the deletion
+    /// also applies to segments that are being merged.
+    ///
+    /// Returns an error if the schema has no unique key
+    /// (see [`SchemaBuilder::set_unique_key`](crate::schema::SchemaBuilder::set_unique_key)),
+    /// or if the document does not have exactly one value for it.
+    ///
+    /// Only `upsert` enforces the uniqueness of the key: the documents added with
+    /// [`IndexWriter::add_document`] are not checked, and are not deduplicated by merges.
+    pub fn upsert(&self, document: D) -> crate::Result<Opstamp> {
+        let schema = self.index.schema();
+        let key_field = schema.unique_key_field().ok_or_else(|| {
+            TantivyError::InvalidArgument("The schema does not have a unique key".to_string())
+        })?;
+        let key_term = unique_key_term(&schema, key_field, &document)?;
+        self.run([
+            UserOperation::Delete(key_term),
+            UserOperation::Add(document),
+        ])
+    }
+
     /// Gets a range of stamps from the stamper and "pops" the last stamp
     /// from the range returning a tuple of the last optstamp and the popped
     /// range.
@@ -795,7 +1089,34 @@ impl<D: Document> IndexWriter<D> {
         Ok(batch_opstamp)
     }
 
+    /// Applies backpressure if the number of segments exceeds
+    /// [`IndexWriterOptions`]' `max_num_segments`, by waiting for the ongoing merges.
+    fn wait_for_segment_backpressure(&self) {
+        let Some(max_num_segments) = self.options.max_num_segments else {
+            return;
+        };
+        while self.segment_updater.num_segments() > max_num_segments {
+            let num_pending_merges = self.segment_updater.num_pending_merges();
+            if num_pending_merges == 0 || self.segment_updater.is_merging_paused() {
+                // No merge can bring the number of segments down: blocking would be a deadlock.
+                return;
+            }
+            self.segment_updater
+                .wait_num_pending_merges_below(num_pending_merges);
+        }
+    }
+
+    /// Blocks until all of the ongoing merges are completed.
+    ///
+    /// Contrary to [`IndexWriter::wait_merging_threads()`], the `IndexWriter`
+    /// remains usable. As merges may trigger new merges, this method keeps
+    /// waiting until the merge policy finds no merge opportunity.
+    pub fn wait_merging_threads_idle(&self) -> crate::Result<()> {
+        self.segment_updater.wait_merging_thread()
+    }
+
     fn send_add_documents_batch(&self, add_ops: AddBatch<D>) -> crate::Result<()> {
+        self.wait_for_segment_backpressure();
         if self.index_writer_status.is_alive() && self.operation_sender.send(add_ops).is_ok() {
             Ok(())
         } else {
@@ -807,6 +1128,8 @@ impl<D: Document> IndexWriter<D> {
 impl<D: Document> Drop for IndexWriter<D> {
     fn drop(&mut self) {
         self.segment_updater.kill();
+        // Uncommitted segments do not outlive the writer that created them.
+        self.index.near_real_time().clear();
         self.drop_sender();
         for work in self.workers_join_handle.drain(..) {
             let _ = work.join();
@@ -818,6 +1141,7 @@ impl<D: Document> Drop for IndexWriter<D> {
 mod tests {
     use std::collections::{HashMap, HashSet};
     use std::net::Ipv6Addr;
+    use std::sync::{Arc, Mutex};
 
     use columnar::{Column, MonotonicallyMappableToU128};
     use itertools::Itertools;
@@ -828,7 +1152,9 @@ mod tests {
     use crate::directory::error::LockError;
     use crate::error::*;
     use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
-    use crate::indexer::{IndexWriterOptions, NoMergePolicy};
+    use crate::indexer::{
+        FifoMergeScheduler, IndexWriterEvent, IndexWriterOptions, LogMergePolicy, NoMergePolicy,
+    };
     use crate::query::{QueryParser, TermQuery};
     use crate::schema::{
         self, Facet, FacetOptions, IndexRecordOption, IpAddrOptions, JsonObjectOptions,
@@ -1060,6 +1386,522 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_refresh_and_rollback() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let num_docs_containing = |s: &str| {
+            let searcher = reader.searcher();
+            let term = Term::from_field_text(text_field, s);
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a"))?;
+        index_writer.add_document(doc!(text_field=>"b"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"c"))?;
+        index_writer.delete_term(Term::from_field_text(text_field, "a"));
+        index_writer.refresh()?;
+        // Nothing was committed.
+        assert_eq!(index.load_metas()?.segments.len(), 1);
+        reader.reload()?;
+        assert_eq!(num_docs_containing("a"), 0);
+        assert_eq!(num_docs_containing("b"), 1);
+        assert_eq!(num_docs_containing("c"), 1);
+
+        index_writer.rollback()?;
+        reader.reload()?;
+        assert_eq!(num_docs_containing("a"), 1);
+        assert_eq!(num_docs_containing("b"), 1);
+        assert_eq!(num_docs_containing("c"), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_refresh_then_commit() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        // `OnCommitWithDelay` readers are reloaded by `refresh()` itself.
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a"))?;
+        index_writer.refresh()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        index_writer.add_document(doc!(text_field=>"b"))?;
+        index_writer.refresh()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        index_writer.commit()?;
+        assert_eq!(index.load_metas()?.segments.len(), 2);
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_writer_metrics() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        index_writer.set_event_callback(Arc::new(move |event: &IndexWriterEvent| {
+            events_clone.lock().unwrap().push(event.clone());
+        }));
+        assert_eq!(index_writer.metrics().num_docs_indexed, 0);
+        assert_eq!(index_writer.metrics().last_flush_duration, None);
+
+        index_writer.add_document(doc!(text_field=>"a"))?;
+        index_writer.add_document(doc!(text_field=>"b"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"c"))?;
+        index_writer.commit()?;
+
+        let metrics = index_writer.metrics();
+        assert_eq!(metrics.num_docs_indexed, 3);
+        assert_eq!(metrics.num_segments_flushed, 2);
+        assert_eq!(metrics.memory_usage_bytes, 0);
+        assert!(metrics.last_flush_duration.is_some());
+        assert_eq!(metrics.num_merges, 0);
+
+        let segment_ids = index.searchable_segment_ids()?;
+        let merged_segment_meta = index_writer.merge(&segment_ids).wait()?.unwrap();
+        let metrics = index_writer.metrics();
+        assert_eq!(metrics.num_pending_merges, 0);
+        assert_eq!(metrics.num_merges, 1);
+        assert!(metrics.merge_bytes_written > 0);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0],
+            IndexWriterEvent::SegmentFlushed { num_docs: 2, .. }
+        ));
+        match &events[2] {
+            IndexWriterEvent::MergeCompleted {
+                merged_segment_ids,
+                segment_id,
+                num_bytes,
+                ..
+            } => {
+                assert_eq!(merged_segment_ids, &segment_ids);
+                assert_eq!(*segment_id, Some(merged_segment_meta.id()));
+                assert_eq!(*num_bytes, metrics.merge_bytes_written);
+            }
+            _ => panic!("Expected a merge event"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_num_segments_backpressure() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .max_num_segments(2)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        // Without any ongoing merge, adding documents does not block.
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for _ in 0..4 {
+            index_writer.add_document(doc!(text_field=>"a"))?;
+            index_writer.commit()?;
+        }
+        assert_eq!(index.searchable_segment_ids()?.len(), 4);
+
+        let mut merge_policy = LogMergePolicy::default();
+        merge_policy.set_min_num_segments(2);
+        index_writer.set_merge_policy(Box::new(merge_policy));
+        for _ in 0..4 {
+            index_writer.add_document(doc!(text_field=>"a"))?;
+            index_writer.commit()?;
+        }
+        index_writer.wait_merging_threads_idle()?;
+        assert_eq!(index_writer.metrics().num_pending_merges, 0);
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+
+        // The writer is still usable.
+        index_writer.add_document(doc!(text_field=>"a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 9);
+        Ok(())
+    }
+
+    /// A directory holding the merges, whose segments are written from a merge thread, until
+    /// its gate is opened.
+    #[derive(Clone, Debug, Default)]
+    struct MergeGateDirectory {
+        directory: crate::directory::RamDirectory,
+        is_open: Arc<(Mutex<bool>, std::sync::Condvar)>,
+    }
+
+    impl MergeGateDirectory {
+        fn open_gate(&self) {
+            let (is_open, condvar) = &*self.is_open;
+            *is_open.lock().unwrap() = true;
+            condvar.notify_all();
+        }
+    }
+
+    impl crate::Directory for MergeGateDirectory {
+        fn get_file_handle(
+            &self,
+            path: &std::path::Path,
+        ) -> Result<Arc<dyn crate::directory::FileHandle>, crate::directory::error::OpenReadError>
+        {
+            self.directory.get_file_handle(path)
+        }
+
+        fn delete(
+            &self,
+            path: &std::path::Path,
+        ) -> Result<(), crate::directory::error::DeleteError> {
+            self.directory.delete(path)
+        }
+
+        fn exists(
+            &self,
+            path: &std::path::Path,
+        ) -> Result<bool, crate::directory::error::OpenReadError> {
+            self.directory.exists(path)
+        }
+
+        fn open_write(
+            &self,
+            path: &std::path::Path,
+        ) -> Result<crate::directory::WritePtr, crate::directory::error::OpenWriteError> {
+            let is_merge_thread = std::thread::current()
+                .name()
+                .is_some_and(|name| name.starts_with("merge_thread_"));
+            if is_merge_thread {
+                let (is_open, condvar) = &*self.is_open;
+                let _is_open = condvar
+                    .wait_while(is_open.lock().unwrap(), |is_open| !*is_open)
+                    .unwrap();
+            }
+            self.directory.open_write(path)
+        }
+
+        fn atomic_read(
+            &self,
+            path: &std::path::Path,
+        ) -> Result<Vec<u8>, crate::directory::error::OpenReadError> {
+            self.directory.atomic_read(path)
+        }
+
+        fn atomic_write(&self, path: &std::path::Path, data: &[u8]) -> std::io::Result<()> {
+            self.directory.atomic_write(path, data)
+        }
+
+        fn sync_directory(&self) -> std::io::Result<()> {
+            self.directory.sync_directory()
+        }
+
+        fn watch(
+            &self,
+            watch_callback: crate::directory::WatchCallback,
+        ) -> crate::Result<crate::directory::WatchHandle> {
+            self.directory.watch(watch_callback)
+        }
+    }
+
+    #[test]
+    fn test_max_num_segments_backpressure_blocks_until_merged() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let directory = MergeGateDirectory::default();
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .max_num_segments(2)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        let mut merge_policy = LogMergePolicy::default();
+        merge_policy.set_min_num_segments(3);
+        index_writer.set_merge_policy(Box::new(merge_policy));
+        for _ in 0..3 {
+            index_writer.add_document(doc!(text_field=>"a"))?;
+            index_writer.commit()?;
+        }
+        // The third commit started a merge of the 3 segments, held by the directory.
+        assert_eq!(index_writer.metrics().num_pending_merges, 1);
+        assert_eq!(index.searchable_segment_ids()?.len(), 3);
+
+        let (added_sender, added_receiver) = crossbeam_channel::bounded(1);
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let add_res = index_writer.add_document(doc!(text_field=>"a"));
+                added_sender.send(add_res).unwrap();
+            });
+            // The writer thread blocks while there are more than 2 segments.
+            assert!(added_receiver
+                .recv_timeout(std::time::Duration::from_millis(500))
+                .is_err());
+            // It unblocks once the merge has brought the number of segments down.
+            directory.open_gate();
+            let add_res = added_receiver
+                .recv_timeout(std::time::Duration::from_secs(30))
+                .expect("adding a document should unblock once the merge is done");
+            assert!(add_res.is_ok());
+        });
+
+        index_writer.commit()?;
+        index_writer.wait_merging_threads_idle()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 2);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deterministic_indexing() -> crate::Result<()> {
+        use std::path::{Path, PathBuf};
+
+        use crate::directory::{Directory, RamDirectory};
+
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let schema = schema_builder.build();
+        let build_index = || -> crate::Result<Vec<(PathBuf, Vec<u8>)>> {
+            let directory = RamDirectory::create();
+            let index = Index::create(directory.clone(), schema.clone(), Default::default())?;
+            let options = IndexWriterOptions::builder().deterministic(true).build();
+            let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+            for commit_id in 0..3u64 {
+                for doc_id in 0..10u64 {
+                    let id = commit_id * 10 + doc_id;
+                    index_writer
+                        .add_document(doc!(text_field=>format!("doc {id}"), id_field=>id))?;
+                }
+                index_writer.delete_term(Term::from_field_u64(id_field, commit_id));
+                index_writer.commit()?;
+            }
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.commit()?;
+            index_writer.wait_merging_threads()?;
+            let mut paths: Vec<PathBuf> = index
+                .directory()
+                .list_managed_files()
+                .into_iter()
+                .chain([PathBuf::from("meta.json"), PathBuf::from(".managed.json")])
+                .collect();
+            paths.sort();
+            paths.dedup();
+            paths
+                .into_iter()
+                .map(|path| {
+                    let data = directory.atomic_read(Path::new(&path))?;
+                    Ok((path, data))
+                })
+                .collect()
+        };
+        let files = build_index()?;
+        assert!(files.len() > 2);
+        assert_eq!(files, build_index()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deterministic_indexing_requires_single_thread() {
+        let index = Index::create_in_ram(schema::Schema::builder().build());
+        let options = IndexWriterOptions::builder()
+            .deterministic(true)
+            .num_worker_threads(2)
+            .build();
+        assert!(matches!(
+            index.writer_with_options::<TantivyDocument>(options),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_upsert() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        schema_builder.set_unique_key(id_field);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.upsert(doc!(id_field=>"a", text_field=>"v1"))?;
+        index_writer.upsert(doc!(id_field=>"b", text_field=>"v1"))?;
+        index_writer.commit()?;
+        index_writer.upsert(doc!(id_field=>"a", text_field=>"v2"))?;
+        index_writer.upsert(doc!(id_field=>"c", text_field=>"v2"))?;
+        index_writer.upsert(doc!(id_field=>"c", text_field=>"v3"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(count("text:v1")?, 1);
+        assert_eq!(count("id:a AND text:v2")?, 1);
+        assert_eq!(count("id:c AND text:v3")?, 1);
+
+        assert!(matches!(
+            index_writer.upsert(doc!(text_field=>"v4")),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer.upsert(doc!(id_field=>"a", id_field=>"b")),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        // Only `upsert` enforces the uniqueness of the key.
+        index_writer.add_document(doc!(id_field=>"b", text_field=>"v4"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let id_b = query_parser.parse_query("id:b")?;
+        assert_eq!(searcher.search(&id_b, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_upsert_requires_unique_key() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        assert!(matches!(
+            index_writer.upsert(doc!(id_field=>1u64)),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_upsert_during_merge() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let version_field = schema_builder.add_u64_field("version", INDEXED);
+        schema_builder.set_unique_key(id_field);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0..2u64 {
+            for _ in 0..10 {
+                index_writer.upsert(doc!(id_field=>id, version_field=>0u64))?;
+            }
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        let merge_future = index_writer.merge(&segment_ids);
+        index_writer.upsert(doc!(id_field=>0u64, version_field=>1u64))?;
+        index_writer.upsert(doc!(id_field=>1u64, version_field=>1u64))?;
+        merge_future.wait()?;
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        let version_1 = TermQuery::new(
+            Term::from_field_u64(version_field, 1),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&version_1, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pause_merging() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.set_merge_scheduler(Box::new(FifoMergeScheduler));
+        assert_eq!(
+            format!("{:?}", index_writer.get_merge_scheduler()),
+            "FifoMergeScheduler"
+        );
+        for _ in 0..2 {
+            index_writer.add_document(doc!(text_field=>"a"))?;
+            index_writer.commit()?;
+        }
+        index_writer.pause_merging();
+        assert!(index_writer.is_merging_paused());
+        let segment_ids = index.searchable_segment_ids()?;
+        let merge_future = index_writer.merge(&segment_ids);
+        assert_eq!(index_writer.metrics().num_pending_merges, 1);
+        assert_eq!(index.searchable_segment_ids()?.len(), 2);
+
+        index_writer.resume_merging();
+        assert!(!index_writer.is_merging_paused());
+        merge_future.wait()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+
+        // Waiting for the merging threads resumes merging.
+        index_writer.add_document(doc!(text_field=>"a"))?;
+        index_writer.commit()?;
+        index_writer.pause_merging();
+        drop(index_writer.merge(&index.searchable_segment_ids()?));
+        index_writer.wait_merging_threads()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_spill_to_disk() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = IndexWriterOptions::builder()
+            .memory_budget_per_thread(MEMORY_BUDGET_NUM_BYTES_MIN)
+            .spill_to_disk(true)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        let num_docs = 100_000u64;
+        for id in 0..num_docs {
+            index_writer.add_document(doc!(id_field=>id, text_field=>format!("hello term{id}")))?;
+        }
+        index_writer.delete_term(Term::from_field_u64(id_field, 5));
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), num_docs - 1);
+        let ids: Column<u64> = searcher.segment_reader(0).fast_fields().u64("id")?;
+        let expected_ids: Vec<u64> = (0..num_docs).collect();
+        assert_eq!(ids.values.iter().collect::<Vec<u64>>(), expected_ids);
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "term99999"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&term_query, &Count)?, 1);
+        // The runs were garbage collected.
+        let num_postings_files = index
+            .directory()
+            .list_managed_files()
+            .iter()
+            .filter(|path| path.extension() == Some("idx".as_ref()))
+            .count();
+        assert_eq!(num_postings_files, 1);
+        Ok(())
+    }
+
     #[test]
     fn test_merge_on_empty_segments_single_segment() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -0,0 +1,207 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use common::HasLen;
+
+use crate::directory::Directory;
+use crate::index::{Index, SegmentId, SegmentMeta};
+
+/// Snapshot of the metrics of an [`IndexWriter`](crate::IndexWriter).
+///
+/// Counters start at zero when the `IndexWriter` is created, and are reset on rollback.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexWriterMetrics {
+    /// Number of documents processed by the indexing workers.
+    pub num_docs_indexed: u64,
+    /// Average number of documents indexed per second since the creation of the writer.
+    pub docs_per_sec: f64,
+    /// Memory currently used by the indexing workers to buffer documents.
+    pub memory_usage_bytes: u64,
+    /// Number of merges that are scheduled or running.
+    pub num_pending_merges: usize,
+    /// Number of merges that completed successfully.
+    pub num_merges: u64,
+    /// Number of bytes of the segments written by merges.
+    pub merge_bytes_written: u64,
+    /// Number of segments flushed by the indexing workers.
+    pub num_segments_flushed: u64,
+    /// Cumulated time spent by the indexing workers serializing segments.
+    pub total_flush_duration: Duration,
+    /// Time spent serializing the last flushed segment.
+    pub last_flush_duration: Option<Duration>,
+}
+
+/// Event emitted by the [`IndexWriter`](crate::IndexWriter).
+///
+/// See [`IndexWriter::set_event_callback()`](crate::IndexWriter::set_event_callback).
+#[derive(Clone, Debug)]
+pub enum IndexWriterEvent {
+    /// An indexing worker flushed a new segment.
+    SegmentFlushed {
+        /// Id of the new segment.
+        segment_id: SegmentId,
+        /// Number of documents in the new segment.
+        num_docs: u32,
+        /// Time spent serializing the segment.
+        duration: Duration,
+    },
+    /// A merge completed.
+    MergeCompleted {
+        /// Ids of the merged segments.
+        merged_segment_ids: Vec<SegmentId>,
+        /// Id of the resulting segment, `None` if all of the documents were deleted.
+        segment_id: Option<SegmentId>,
+        /// Number of bytes of the resulting segment.
+        num_bytes: u64,
+        /// Time spent merging.
+        duration: Duration,
+    },
+}
+
+/// Callback receiving the [`IndexWriterEvent`]s.
+///
+/// It is called from the indexing and merging threads, and should return quickly.
+pub type IndexWriterEventCallback = Arc<dyn Fn(&IndexWriterEvent) + Send + Sync>;
+
+/// Collects the metrics of an `IndexWriter`.
+///
+/// It is shared between the writer, its indexing workers and the segment updater.
+pub(crate) struct IndexWriterMetricsRecorder {
+    start: Instant,
+    num_docs_indexed: AtomicU64,
+    memory_usage_bytes: AtomicU64,
+    num_merges: AtomicU64,
+    merge_bytes_written: AtomicU64,
+    num_segments_flushed: AtomicU64,
+    total_flush_duration_micros: AtomicU64,
+    last_flush_duration_micros: AtomicU64,
+    event_callback: RwLock<Option<IndexWriterEventCallback>>,
+}
+
+impl Default for IndexWriterMetricsRecorder {
+    fn default() -> Self {
+        IndexWriterMetricsRecorder {
+            start: Instant::now(),
+            num_docs_indexed: AtomicU64::default(),
+            memory_usage_bytes: AtomicU64::default(),
+            num_merges: AtomicU64::default(),
+            merge_bytes_written: AtomicU64::default(),
+            num_segments_flushed: AtomicU64::default(),
+            total_flush_duration_micros: AtomicU64::default(),
+            last_flush_duration_micros: AtomicU64::default(),
+            event_callback: RwLock::default(),
+        }
+    }
+}
+
+impl IndexWriterMetricsRecorder {
+    pub fn snapshot(&self, num_pending_merges: usize) -> IndexWriterMetrics {
+        let num_docs_indexed = self.num_docs_indexed.load(Ordering::Relaxed);
+        let elapsed_secs = self.start.elapsed().as_secs_f64();
+        let docs_per_sec = if elapsed_secs > 0.0 {
+            num_docs_indexed as f64 / elapsed_secs
+        } else {
+            0.0
+        };
+        let num_segments_flushed = self.num_segments_flushed.load(Ordering::Relaxed);
+        let last_flush_duration = if num_segments_flushed > 0 {
+            Some(Duration::from_micros(
+                self.last_flush_duration_micros.load(Ordering::Relaxed),
+            ))
+        } else {
+            None
+        };
+        IndexWriterMetrics {
+            num_docs_indexed,
+            docs_per_sec,
+            memory_usage_bytes: self.memory_usage_bytes.load(Ordering::Relaxed),
+            num_pending_merges,
+            num_merges: self.num_merges.load(Ordering::Relaxed),
+            merge_bytes_written: self.merge_bytes_written.load(Ordering::Relaxed),
+            num_segments_flushed,
+            total_flush_duration: Duration::from_micros(
+                self.total_flush_duration_micros.load(Ordering::Relaxed),
+            ),
+            last_flush_duration,
+        }
+    }
+
+    pub fn event_callback(&self) -> Option<IndexWriterEventCallback> {
+        self.event_callback.read().unwrap().clone()
+    }
+
+    pub fn set_event_callback(&self, event_callback: Option<IndexWriterEventCallback>) {
+        *self.event_callback.write().unwrap() = event_callback;
+    }
+
+    fn emit(&self, event: IndexWriterEvent) {
+        if let Some(event_callback) = self.event_callback() {
+            event_callback(&event);
+        }
+    }
+
+    pub fn record_docs_indexed(&self, num_docs: u64) {
+        self.num_docs_indexed.fetch_add(num_docs, Ordering::Relaxed);
+    }
+
+    /// Updates the memory usage of a worker, given the value it previously reported.
+    pub fn record_memory_usage(&self, previous_mem_usage: &mut usize, mem_usage: usize) {
+        if mem_usage >= *previous_mem_usage {
+            self.memory_usage_bytes
+                .fetch_add((mem_usage - *previous_mem_usage) as u64, Ordering::Relaxed);
+        } else {
+            self.memory_usage_bytes
+                .fetch_sub((*previous_mem_usage - mem_usage) as u64, Ordering::Relaxed);
+        }
+        *previous_mem_usage = mem_usage;
+    }
+
+    pub fn record_segment_flushed(&self, segment_meta: &SegmentMeta, duration: Duration) {
+        let duration_micros = duration.as_micros() as u64;
+        self.total_flush_duration_micros
+            .fetch_add(duration_micros, Ordering::Relaxed);
+        self.last_flush_duration_micros
+            .store(duration_micros, Ordering::Relaxed);
+        self.num_segments_flushed.fetch_add(1, Ordering::Relaxed);
+        self.emit(IndexWriterEvent::SegmentFlushed {
+            segment_id: segment_meta.id(),
+            num_docs: segment_meta.max_doc(),
+            duration,
+        });
+    }
+
+    pub fn record_merge_completed(
+        &self,
+        index: &Index,
+        merged_segment_ids: &[SegmentId],
+        segment_meta_opt: Option<&SegmentMeta>,
+        duration: Duration,
+    ) -> u64 {
+        let num_bytes = segment_meta_opt
+            .map(|segment_meta| segment_num_bytes(index, segment_meta))
+            .unwrap_or(0);
+        self.num_merges.fetch_add(1, Ordering::Relaxed);
+        self.merge_bytes_written
+            .fetch_add(num_bytes, Ordering::Relaxed);
+        self.emit(IndexWriterEvent::MergeCompleted {
+            merged_segment_ids: merged_segment_ids.to_vec(),
+            segment_id: segment_meta_opt.map(SegmentMeta::id),
+            num_bytes,
+            duration,
+        });
+        num_bytes
+    }
+}
+
+/// Sums the size of the files of a segment.
+///
+/// Missing files are ignored.
+fn segment_num_bytes(index: &Index, segment_meta: &SegmentMeta) -> u64 {
+    segment_meta
+        .list_files()
+        .iter()
+        .filter_map(|path| index.directory().open_read(path).ok())
+        .map(|file_slice| file_slice.len() as u64)
+        .sum()
+}
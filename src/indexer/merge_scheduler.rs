@@ -0,0 +1,200 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+use std::marker;
+use std::sync::{Arc, Mutex};
+
+use rayon::ThreadPool;
+
+use crate::index::SegmentMeta;
+
+/// The `MergeScheduler` decides in which order pending merges run.
+///
+/// Merges run on the merge threads of the `IndexWriter`, see
+/// [`IndexWriterOptions`](crate::IndexWriterOptions). When all of them are busy,
+/// the pending merge with the highest priority is the next one to start.
+pub trait MergeScheduler: marker::Send + marker::Sync + Debug {
+    /// Returns the priority of the merge of the given segments.
+    ///
+    /// Merges with the same priority start in the order in which they were scheduled.
+    fn priority(&self, segments: &[SegmentMeta]) -> i64;
+}
+
+/// Starts merges in the order in which they were scheduled.
+#[derive(Debug, Clone, Default)]
+pub struct FifoMergeScheduler;
+
+impl MergeScheduler for FifoMergeScheduler {
+    fn priority(&self, _segments: &[SegmentMeta]) -> i64 {
+        0
+    }
+}
+
+/// Starts the merges with the fewest documents first, so that small merges
+/// do not wait for huge ones to complete.
+#[derive(Debug, Clone, Default)]
+pub struct SmallestMergeFirstScheduler;
+
+impl MergeScheduler for SmallestMergeFirstScheduler {
+    fn priority(&self, segments: &[SegmentMeta]) -> i64 {
+        let num_docs: u64 = segments
+            .iter()
+            .map(|segment| segment.num_docs() as u64)
+            .sum();
+        -(num_docs as i64)
+    }
+}
+
+type MergeJob = Box<dyn FnOnce() + Send>;
+
+struct QueuedMerge {
+    priority: i64,
+    seq: Reverse<u64>,
+    job: MergeJob,
+}
+
+impl PartialEq for QueuedMerge {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedMerge {}
+
+impl PartialOrd for QueuedMerge {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedMerge {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.priority, self.seq).cmp(&(other.priority, other.seq))
+    }
+}
+
+#[derive(Default)]
+struct MergeQueueState {
+    queued_merges: BinaryHeap<QueuedMerge>,
+    num_scheduled: u64,
+    paused: bool,
+}
+
+/// Merges waiting for a merge thread.
+///
+/// Every time a merge is scheduled, a task is spawned on the thread pool. When it starts,
+/// this task runs the queued merge with the highest priority, which is not necessarily
+/// the merge that caused it to be spawned.
+#[derive(Clone)]
+pub(crate) struct MergeQueue {
+    state: Arc<Mutex<MergeQueueState>>,
+    thread_pool: Arc<ThreadPool>,
+}
+
+impl MergeQueue {
+    pub fn new(thread_pool: ThreadPool) -> MergeQueue {
+        MergeQueue {
+            state: Default::default(),
+            thread_pool: Arc::new(thread_pool),
+        }
+    }
+
+    pub fn schedule(&self, priority: i64, job: MergeJob) {
+        let mut state = self.state.lock().unwrap();
+        let seq = Reverse(state.num_scheduled);
+        state.num_scheduled += 1;
+        state.queued_merges.push(QueuedMerge { priority, seq, job });
+        let paused = state.paused;
+        drop(state);
+        if !paused {
+            self.spawn_runner();
+        }
+    }
+
+    fn spawn_runner(&self) {
+        let merge_queue = self.clone();
+        self.thread_pool.spawn(move || merge_queue.run_next());
+    }
+
+    fn run_next(&self) {
+        let job_opt = {
+            let mut state = self.state.lock().unwrap();
+            if state.paused {
+                // The merge stays queued, `resume` will spawn a new runner for it.
+                None
+            } else {
+                state
+                    .queued_merges
+                    .pop()
+                    .map(|queued_merge| queued_merge.job)
+            }
+        };
+        if let Some(job) = job_opt {
+            job();
+        }
+    }
+
+    pub fn pause(&self) {
+        self.state.lock().unwrap().paused = true;
+    }
+
+    pub fn resume(&self) {
+        let num_queued_merges = {
+            let mut state = self.state.lock().unwrap();
+            state.paused = false;
+            state.queued_merges.len()
+        };
+        for _ in 0..num_queued_merges {
+            self.spawn_runner();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    /// Drops the merges that have not started yet.
+    pub fn clear(&self) {
+        let queued_merges = std::mem::take(&mut self.state.lock().unwrap().queued_merges);
+        // The jobs are dropped outside of the lock.
+        drop(queued_merges);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use rayon::ThreadPoolBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_merge_queue_priority() {
+        let thread_pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let merge_queue = MergeQueue::new(thread_pool);
+        merge_queue.pause();
+        assert!(merge_queue.is_paused());
+        let (sender, receiver) = mpsc::channel();
+        for (job_id, priority) in [(0, -10), (1, 5), (2, -1), (3, 5)] {
+            let sender = sender.clone();
+            merge_queue.schedule(priority, Box::new(move || sender.send(job_id).unwrap()));
+        }
+        drop(sender);
+        merge_queue.resume();
+        let job_ids: Vec<usize> = receiver.iter().collect();
+        assert_eq!(job_ids, vec![1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn test_merge_queue_clear() {
+        let thread_pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let merge_queue = MergeQueue::new(thread_pool);
+        merge_queue.pause();
+        let (sender, receiver) = mpsc::channel::<()>();
+        merge_queue.schedule(0, Box::new(move || sender.send(()).unwrap()));
+        merge_queue.clear();
+        merge_queue.resume();
+        assert!(receiver.recv().is_err());
+    }
+}
@@ -15,11 +15,14 @@ use crate::fieldnorm::{FieldNormReader, FieldNormReaders, FieldNormsSerializer,
 use crate::index::{Segment, SegmentComponent, SegmentReader};
 use crate::indexer::doc_id_mapping::{MappingType, SegmentDocIdMapping};
 use crate::indexer::SegmentSerializer;
+use crate::points::{PointsSerializer, PointsWriter};
 use crate::postings::{InvertedIndexSerializer, Postings, SegmentPostings};
 use crate::schema::{value_type_to_column_type, Field, FieldType, Schema};
-use crate::store::StoreWriter;
+use crate::store::{DocStoreVersion, StoreWriter, DOC_STORE_VERSION};
+use crate::term_vectors::{TermVectorsSerializer, TermVectorsWriter};
 use crate::termdict::{TermMerger, TermOrdinal};
-use crate::{DocAddress, DocId, InvertedIndexReader};
+use crate::vector::{VectorsSerializer, VectorsWriter};
+use crate::{DocAddress, DocId, InvertedIndexReader, TantivyDocument};
 
 /// Segment's max doc must be `< MAX_DOC_LIMIT`.
 ///
@@ -217,6 +220,106 @@ impl IndexMerger {
         Ok(())
     }
 
+    fn write_points(
+        &self,
+        mut points_serializer: PointsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        let fields = PointsWriter::fields_with_points(&self.schema);
+        if fields.is_empty() {
+            points_serializer.close()?;
+            return Ok(());
+        }
+        // For each segment, maps the old doc ids to the new doc ids.
+        // Deleted documents are mapped to `None`.
+        let mut old_to_new_doc_ids: Vec<Vec<Option<DocId>>> = self
+            .readers
+            .iter()
+            .map(|reader| vec![None; reader.max_doc() as usize])
+            .collect();
+        for (new_doc_id, old_doc_addr) in doc_id_mapping.iter_old_doc_addrs().enumerate() {
+            old_to_new_doc_ids[old_doc_addr.segment_ord as usize][old_doc_addr.doc_id as usize] =
+                Some(new_doc_id as DocId);
+        }
+        let mut points: Vec<(u64, DocId)> = Vec::new();
+        for field in fields {
+            points.clear();
+            for (reader, old_to_new_doc_id) in self.readers.iter().zip(&old_to_new_doc_ids) {
+                let Some(points_reader) = reader.points_readers().get_field(field)? else {
+                    continue;
+                };
+                points.extend(points_reader.iter().filter_map(|(value, old_doc_id)| {
+                    let new_doc_id = old_to_new_doc_id[old_doc_id as usize]?;
+                    Some((value, new_doc_id))
+                }));
+            }
+            points_serializer.serialize_field(field, &mut points[..])?;
+        }
+        points_serializer.close()?;
+        Ok(())
+    }
+
+    fn write_vectors(
+        &self,
+        mut vectors_serializer: VectorsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        let mut docs: Vec<DocId> = Vec::new();
+        let mut values: Vec<f32> = Vec::new();
+        let mut vector: Vec<f32> = Vec::new();
+        for (field, vector_options) in VectorsWriter::vector_fields(&self.schema) {
+            docs.clear();
+            values.clear();
+            let vector_readers = self
+                .readers
+                .iter()
+                .map(|reader| reader.vectors_readers().get_field(field))
+                .collect::<crate::Result<Vec<_>>>()?;
+            for (new_doc_id, old_doc_addr) in doc_id_mapping.iter_old_doc_addrs().enumerate() {
+                let Some(vector_reader) = &vector_readers[old_doc_addr.segment_ord as usize] else {
+                    continue;
+                };
+                if vector_reader.read_doc_vector(old_doc_addr.doc_id, &mut vector) {
+                    docs.push(new_doc_id as DocId);
+                    values.extend_from_slice(&vector);
+                }
+            }
+            vectors_serializer.serialize_field(field, vector_options, &docs, &values)?;
+        }
+        vectors_serializer.close()?;
+        Ok(())
+    }
+
+    fn write_term_vectors(
+        &self,
+        mut term_vectors_serializer: TermVectorsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        let mut doc_starts: Vec<u64> = Vec::new();
+        let mut data: Vec<u8> = Vec::new();
+        for field in TermVectorsWriter::term_vector_fields(&self.schema) {
+            doc_starts.clear();
+            data.clear();
+            let term_vector_readers = self
+                .readers
+                .iter()
+                .map(|reader| reader.term_vectors_readers().get_field(field))
+                .collect::<crate::Result<Vec<_>>>()?;
+            // The term vector of a document is self-contained, so it is copied as is.
+            for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                doc_starts.push(data.len() as u64);
+                if let Some(term_vector_reader) =
+                    &term_vector_readers[old_doc_addr.segment_ord as usize]
+                {
+                    data.extend_from_slice(term_vector_reader.doc_bytes(old_doc_addr.doc_id));
+                }
+            }
+            term_vectors_serializer.serialize_field(field, &doc_starts, &data)?;
+        }
+        term_vectors_serializer.close()?;
+        Ok(())
+    }
+
     fn write_fast_fields(
         &self,
         fast_field_wrt: &mut WritePtr,
@@ -441,7 +544,13 @@ impl IndexMerger {
                         // we make sure to only write the term if
                         // there is at least one document.
                         let term_freq = if has_term_freq {
-                            segment_postings.positions(&mut positions_buffer);
+                            if segment_postings_option.has_offsets() {
+                                // The offsets are delta-encoded per document, so they can be
+                                // copied as is.
+                                segment_postings.raw_positions(&mut positions_buffer);
+                            } else {
+                                segment_postings.positions(&mut positions_buffer);
+                            }
                             segment_postings.term_freq()
                         } else {
                             // The positions_buffer may contain positions from the previous term
@@ -451,8 +560,16 @@ impl IndexMerger {
                             0u32
                         };
 
-                        let delta_positions = delta_computer.compute_delta(&positions_buffer);
-                        field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions);
+                        if segment_postings_option.has_offsets() {
+                            field_serializer.write_doc(
+                                remapped_doc_id,
+                                term_freq,
+                                &positions_buffer,
+                            );
+                        } else {
+                            let delta_positions = delta_computer.compute_delta(&positions_buffer);
+                            field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions);
+                        }
                     }
 
                     doc = segment_postings.advance();
@@ -492,7 +609,15 @@ impl IndexMerger {
 
         for reader in &self.readers {
             let store_reader = reader.get_store_reader(1)?;
-            if reader.has_deletes()
+            if store_reader.doc_store_version() < DocStoreVersion::V3 {
+                // The documents are serialized differently in older doc stores, so they
+                // cannot be copied over as is.
+                for doc_res in store_reader.iter::<TantivyDocument>(reader.alive_bitset()) {
+                    store_writer.store(&doc_res?, &self.schema)?;
+                }
+            } else if reader.has_deletes()
+                    // The blocks of older doc stores do not end with a checksum.
+                    || store_reader.doc_store_version() != DOC_STORE_VERSION
                     // If there is not enough data in the store, we avoid stacking in order to
                     // avoid creating many small blocks in the doc store. Once we have 5 full blocks,
                     // we start stacking. In the worst case 2/7 of the blocks would be very small.
@@ -508,6 +633,11 @@ impl IndexMerger {
                     // take 7 in order to not walk over all checkpoints.
                     || store_reader.block_checkpoints().take(7).count() < 6
                     || store_reader.decompressor() != store_writer.compressor().into()
+                    // Blocks compressed with a dictionary can only be read with the dictionary
+                    // of their own doc store.
+                    || store_reader.has_dictionary()
+                    // The blocks of each family are only stacked onto the same family.
+                    || store_reader.family_layout() != store_writer.family_layout()
             {
                 for doc_bytes_res in store_reader.iter_raw(reader.alive_bitset()) {
                     let doc_bytes = doc_bytes_res?;
@@ -531,6 +661,18 @@ impl IndexMerger {
         if let Some(fieldnorms_serializer) = serializer.extract_fieldnorms_serializer() {
             self.write_fieldnorms(fieldnorms_serializer, &doc_id_mapping)?;
         }
+        debug!("write-points");
+        if let Some(points_serializer) = serializer.extract_points_serializer() {
+            self.write_points(points_serializer, &doc_id_mapping)?;
+        }
+        debug!("write-vectors");
+        if let Some(vectors_serializer) = serializer.extract_vectors_serializer() {
+            self.write_vectors(vectors_serializer, &doc_id_mapping)?;
+        }
+        debug!("write-term-vectors");
+        if let Some(term_vectors_serializer) = serializer.extract_term_vectors_serializer() {
+            self.write_term_vectors(term_vectors_serializer, &doc_id_mapping)?;
+        }
         debug!("write-postings");
         let fieldnorm_data = serializer
             .segment()
@@ -10,16 +10,22 @@ pub(crate) mod path_to_unordered_id;
 pub(crate) mod doc_id_mapping;
 mod doc_opstamp_mapping;
 mod flat_map_with_buffer;
+mod index_split;
 pub(crate) mod index_writer;
+mod index_writer_metrics;
 pub(crate) mod index_writer_status;
 mod log_merge_policy;
 mod merge_index_test;
 mod merge_operation;
 pub(crate) mod merge_policy;
+mod merge_scheduler;
 pub(crate) mod merger;
+mod offline_segment_builder;
 pub(crate) mod operation;
 pub(crate) mod prepared_commit;
+mod reindex;
 mod segment_entry;
+mod segment_id_generator;
 mod segment_manager;
 mod segment_register;
 pub(crate) mod segment_serializer;
@@ -31,13 +37,22 @@ mod stamper;
 use crossbeam_channel as channel;
 use smallvec::SmallVec;
 
+pub use self::index_split::{route_by_fast_field_hash, split_index, SegmentShardRouter};
 pub use self::index_writer::{IndexWriter, IndexWriterOptions};
+pub use self::index_writer_metrics::{
+    IndexWriterEvent, IndexWriterEventCallback, IndexWriterMetrics,
+};
 pub use self::log_merge_policy::LogMergePolicy;
 pub use self::merge_operation::MergeOperation;
 pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
+pub use self::merge_scheduler::{FifoMergeScheduler, MergeScheduler, SmallestMergeFirstScheduler};
+pub use self::offline_segment_builder::{OfflineSegmentBuilder, OfflineSegmentBuilderOptions};
 use self::operation::AddOperation;
 pub use self::operation::UserOperation;
 pub use self::prepared_commit::PreparedCommit;
+pub use self::reindex::{
+    copy_fields_by_name, reindex, ReindexOptions, ReindexProgress, ReindexProgressCallback,
+};
 pub use self::segment_entry::SegmentEntry;
 pub(crate) use self::segment_serializer::SegmentSerializer;
 pub use self::segment_updater::{merge_filtered_segments, merge_indices};
@@ -47,6 +62,9 @@ pub use self::single_segment_index_writer::SingleSegmentIndexWriter;
 /// Alias for the default merge policy, which is the `LogMergePolicy`.
 pub type DefaultMergePolicy = LogMergePolicy;
 
+/// Alias for the default merge scheduler, which is the `SmallestMergeFirstScheduler`.
+pub type DefaultMergeScheduler = SmallestMergeFirstScheduler;
+
 // Batch of documents.
 // Most of the time, users will send operation one-by-one, but it can be useful to
 // send them as a small block to ensure that
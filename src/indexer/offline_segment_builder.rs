@@ -0,0 +1,309 @@
+use std::marker::PhantomData;
+
+use columnar::MonotonicallyMappableToU64;
+
+use crate::core::META_FILEPATH;
+use crate::directory::Directory;
+use crate::index::Order;
+use crate::indexer::index_writer::{
+    MARGIN_IN_BYTES, MEMORY_BUDGET_NUM_BYTES_MAX, MEMORY_BUDGET_NUM_BYTES_MIN,
+};
+use crate::indexer::merger::IndexMerger;
+use crate::indexer::operation::AddOperation;
+use crate::indexer::segment_updater::save_metas;
+use crate::indexer::{SegmentSerializer, SegmentWriter};
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, FieldType};
+use crate::{Index, IndexMeta, Opstamp, Segment, TantivyDocument, TantivyError};
+
+#[derive(Clone, bon::Builder)]
+/// Options of an [`OfflineSegmentBuilder`].
+pub struct OfflineSegmentBuilderOptions {
+    #[builder(default = 500_000_000)]
+    /// The memory budget of the builder.
+    ///
+    /// Documents are buffered in memory up to this budget. Beyond it, the buffered
+    /// documents are written to disk as a run, and the runs are concatenated into
+    /// the final segment when the builder is finalized.
+    memory_budget: usize,
+    /// Name of a `u64`, `i64`, `f64` or date field by which the documents are expected
+    /// to be sorted.
+    ///
+    /// If set, every document must have a value for this field, and adding a document
+    /// that breaks the order returns an error. Doc ids of the final segment follow
+    /// the order in which documents were added.
+    sort_by_field: Option<String>,
+    #[builder(default = Order::Asc)]
+    /// The order of the documents along `sort_by_field`.
+    sort_order: Order,
+}
+
+impl Default for OfflineSegmentBuilderOptions {
+    fn default() -> Self {
+        OfflineSegmentBuilderOptions::builder().build()
+    }
+}
+
+/// Builds an index made of a single segment from a stream of documents,
+/// without any indexing or merging thread.
+///
+/// This is meant for batch pipelines building large immutable indexes: the documents
+/// are expected to be pre-sorted, and are indexed in that order. Memory usage is bounded
+/// by the memory budget. If all of the documents fit in the budget, they are serialized
+/// straight into the final segment. Otherwise, buffered documents spill to disk as runs,
+/// and the runs are stacked into the final segment in a single pass.
+///
+/// Sorted input does not make it possible to stream every document into a single segment:
+/// the inverted index is serialized term by term, and a term may appear in the first and
+/// the last documents, so the postings of all of the documents would have to stay in
+/// memory until the end. The final pass is what merges the terms of the runs, within the
+/// memory budget. What sorted input saves is the doc id remapping: the runs are stacked
+/// in the order in which they were written, so doc stores and fast fields are concatenated
+/// and postings are merged without sorting documents.
+///
+/// It expects an originally empty directory.
+pub struct OfflineSegmentBuilder<D: Document = TantivyDocument> {
+    index: Index,
+    memory_budget: usize,
+    sort_by_field: Option<(Field, Order)>,
+    last_sort_value: Option<u64>,
+    segment_writer: SegmentWriter,
+    segment: Segment,
+    runs: Vec<Segment>,
+    opstamp: Opstamp,
+    _phantom: PhantomData<D>,
+}
+
+impl<D: Document> OfflineSegmentBuilder<D> {
+    /// Creates a new builder writing into `index`.
+    pub fn new(index: Index, options: OfflineSegmentBuilderOptions) -> crate::Result<Self> {
+        if options.memory_budget < MEMORY_BUDGET_NUM_BYTES_MIN {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The memory budget must be at least {MEMORY_BUDGET_NUM_BYTES_MIN}."
+            )));
+        }
+        if options.memory_budget >= MEMORY_BUDGET_NUM_BYTES_MAX {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The memory budget cannot exceed {MEMORY_BUDGET_NUM_BYTES_MAX}"
+            )));
+        }
+        let sort_by_field = options
+            .sort_by_field
+            .as_deref()
+            .map(|field_name| {
+                let schema = index.schema();
+                let field = schema.get_field(field_name)?;
+                match schema.get_field_entry(field).field_type() {
+                    FieldType::U64(_)
+                    | FieldType::I64(_)
+                    | FieldType::F64(_)
+                    | FieldType::Date(_) => Ok((field, options.sort_order.clone())),
+                    _ => Err(TantivyError::InvalidArgument(format!(
+                        "Cannot sort by field `{field_name}`: only u64, i64, f64 and date fields \
+                         are supported"
+                    ))),
+                }
+            })
+            .transpose()?;
+        let segment = index.new_segment();
+        let segment_writer = SegmentWriter::for_segment(options.memory_budget, segment.clone())?;
+        Ok(OfflineSegmentBuilder {
+            index,
+            memory_budget: options.memory_budget,
+            sort_by_field,
+            last_sort_value: None,
+            segment_writer,
+            segment,
+            runs: Vec::new(),
+            opstamp: 0,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the memory currently used to buffer documents.
+    pub fn mem_usage(&self) -> usize {
+        self.segment_writer.mem_usage()
+    }
+
+    /// Returns the number of runs spilled to disk so far.
+    pub fn num_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Adds a document.
+    ///
+    /// If a sort field is configured, the document must come after the previously
+    /// added documents along this field.
+    pub fn add_document(&mut self, document: D) -> crate::Result<()> {
+        if let Some((field, order)) = self.sort_by_field.as_ref() {
+            let sort_value = sort_value(&document, *field).ok_or_else(|| {
+                TantivyError::InvalidArgument(
+                    "Document is missing a value for the sort field".to_string(),
+                )
+            })?;
+            if let Some(last_sort_value) = self.last_sort_value {
+                let is_sorted = if order.is_asc() {
+                    last_sort_value <= sort_value
+                } else {
+                    last_sort_value >= sort_value
+                };
+                if !is_sorted {
+                    return Err(TantivyError::InvalidArgument(
+                        "Documents are not sorted by the sort field".to_string(),
+                    ));
+                }
+            }
+            self.last_sort_value = Some(sort_value);
+        }
+        let opstamp = self.opstamp;
+        self.opstamp += 1;
+        self.segment_writer
+            .add_document(AddOperation { opstamp, document })?;
+        if self.segment_writer.mem_usage() >= self.memory_budget - MARGIN_IN_BYTES {
+            self.spill_run()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered documents to disk as a new run.
+    fn spill_run(&mut self) -> crate::Result<()> {
+        info!(
+            "Buffer limit reached, spilling run with maxdoc={}.",
+            self.segment_writer.max_doc()
+        );
+        let segment = self.index.new_segment();
+        let segment_writer = SegmentWriter::for_segment(self.memory_budget, segment.clone())?;
+        let full_segment_writer = std::mem::replace(&mut self.segment_writer, segment_writer);
+        let full_segment = std::mem::replace(&mut self.segment, segment);
+        let run = finalize_segment(full_segment_writer, full_segment)?;
+        self.runs.push(run);
+        Ok(())
+    }
+
+    /// Writes the final segment, and commits the index.
+    pub fn finalize(mut self) -> crate::Result<Index> {
+        let last_run = finalize_segment(self.segment_writer, self.segment)?;
+        if self.runs.is_empty() || last_run.meta().max_doc() > 0 {
+            self.runs.push(last_run);
+        }
+        let segment = if self.runs.len() == 1 {
+            self.runs.pop().unwrap()
+        } else {
+            let segment = self.index.new_segment();
+            let merger = IndexMerger::open(self.index.schema(), &self.runs)?;
+            let segment_serializer = SegmentSerializer::for_segment(segment.clone())?;
+            let max_doc = merger.write(segment_serializer)?;
+            segment.with_max_doc(max_doc)
+        };
+        let index_meta = IndexMeta {
+            index_settings: self.index.settings().clone(),
+            segments: vec![segment.meta().clone()],
+            schema: self.index.schema(),
+            opstamp: 0,
+            payload: None,
+        };
+        save_metas(&index_meta, self.index.directory())?;
+        self.index.directory().sync_directory()?;
+        // Removes the runs.
+        let mut living_files = segment.meta().list_files();
+        living_files.insert(META_FILEPATH.to_path_buf());
+        self.index
+            .directory_mut()
+            .garbage_collect(|| living_files)?;
+        Ok(self.index)
+    }
+}
+
+/// Returns the first value of `field` in `document`, mapped to a `u64` preserving its order.
+fn sort_value<D: Document>(document: &D, field: Field) -> Option<u64> {
+    document
+        .iter_fields_and_values()
+        .filter(|(doc_field, _)| *doc_field == field)
+        .find_map(|(_, value)| {
+            value
+                .as_u64()
+                .or_else(|| value.as_i64().map(i64::to_u64))
+                .or_else(|| value.as_f64().map(f64::to_u64))
+                .or_else(|| value.as_datetime().map(|datetime| datetime.to_u64()))
+        })
+}
+
+fn finalize_segment(segment_writer: SegmentWriter, segment: Segment) -> crate::Result<Segment> {
+    let max_doc = segment_writer.max_doc();
+    segment_writer.finalize()?;
+    let segment = segment.with_max_doc(max_doc);
+    segment.meta().untrack_temp_docstore();
+    Ok(segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STORED, TEXT};
+    use crate::{DocAddress, Term};
+
+    #[test]
+    fn test_offline_segment_builder_multiple_runs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_u64_field("timestamp", INDEXED | FAST | STORED);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = OfflineSegmentBuilderOptions::builder()
+            .memory_budget(MEMORY_BUDGET_NUM_BYTES_MIN)
+            .sort_by_field("timestamp".to_string())
+            .build();
+        let mut builder: OfflineSegmentBuilder = OfflineSegmentBuilder::new(index, options)?;
+        let num_docs = 100_000u64;
+        for i in 0..num_docs {
+            builder.add_document(doc!(timestamp => i, text => format!("hello term{i}")))?;
+        }
+        assert!(builder.num_runs() > 0);
+        let index = builder.finalize()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), num_docs);
+        // Documents keep the order in which they were added.
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, 70_000))?;
+        assert_eq!(doc.get_first(timestamp).unwrap().as_u64(), Some(70_000));
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, num_docs as usize);
+        // Only the files of the final segment remain.
+        let segment_id = searcher.segment_readers()[0].segment_id();
+        for path in index.directory().list_managed_files() {
+            let path_str = path.to_string_lossy();
+            assert!(path_str == "meta.json" || path_str.starts_with(&segment_id.uuid_string()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_offline_segment_builder_rejects_unsorted_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_i64_field("timestamp", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = OfflineSegmentBuilderOptions::builder()
+            .sort_by_field("timestamp".to_string())
+            .sort_order(Order::Desc)
+            .build();
+        let mut builder: OfflineSegmentBuilder = OfflineSegmentBuilder::new(index, options)?;
+        builder.add_document(doc!(timestamp => 3i64))?;
+        builder.add_document(doc!(timestamp => -1i64))?;
+        assert!(matches!(
+            builder.add_document(doc!(timestamp => 2i64)),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            builder.add_document(doc!()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let index = builder.finalize()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 2);
+        Ok(())
+    }
+}
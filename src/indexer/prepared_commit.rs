@@ -46,6 +46,9 @@ impl<'a, D: Document> PreparedCommit<'a, D> {
     /// At this point deletes have not been flushed yet.
     pub fn commit_future(self) -> FutureResult<Opstamp> {
         info!("committing {}", self.opstamp);
+        if let Err(lock_error) = self.index_writer.segment_updater().check_lock() {
+            return FutureResult::from(lock_error);
+        }
         self.index_writer
             .segment_updater()
             .schedule_commit(self.opstamp, self.payload)
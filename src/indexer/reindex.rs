@@ -0,0 +1,280 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::core::Executor;
+use crate::indexer::IndexWriterOptions;
+use crate::schema::{Field, Schema, TantivyDocument};
+use crate::store::DOCSTORE_CACHE_CAPACITY;
+use crate::{Index, IndexWriter, SegmentReader};
+
+/// The progress callback is called every time this many documents have been read.
+const PROGRESS_REPORT_NUM_DOCS: u64 = 10_000;
+
+/// Snapshot of the progress of a [`reindex`] operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReindexProgress {
+    /// Number of alive documents in the source index.
+    pub num_docs_total: u64,
+    /// Number of documents read from the source doc store so far.
+    pub num_docs_read: u64,
+    /// Number of documents sent to the destination index so far.
+    ///
+    /// This may be lower than `num_docs_read` if the transform filtered some documents out.
+    pub num_docs_indexed: u64,
+}
+
+/// Callback receiving the [`ReindexProgress`] of a [`reindex`] operation.
+pub type ReindexProgressCallback = Arc<dyn Fn(ReindexProgress) + Send + Sync>;
+
+#[derive(Clone, bon::Builder)]
+/// Options of a [`reindex`] operation.
+pub struct ReindexOptions {
+    #[builder(default = 1)]
+    /// The number of threads reading and transforming the documents of the source index.
+    ///
+    /// Segments of the source index are processed in parallel.
+    num_reader_threads: usize,
+    #[builder(default = IndexWriterOptions::builder().build())]
+    /// Options of the `IndexWriter` used to populate the destination index.
+    writer_options: IndexWriterOptions,
+    /// Called regularly with the progress of the operation, and once when it is over.
+    progress_callback: Option<ReindexProgressCallback>,
+}
+
+impl Default for ReindexOptions {
+    fn default() -> Self {
+        ReindexOptions::builder().build()
+    }
+}
+
+/// Returns a transform for [`reindex`] copying all of the values of the fields
+/// of `src_schema` into the field with the same name in `dst_schema`.
+///
+/// Fields that do not exist in `dst_schema` are dropped.
+pub fn copy_fields_by_name(
+    src_schema: &Schema,
+    dst_schema: &Schema,
+) -> impl Fn(TantivyDocument) -> Option<TantivyDocument> + Send + Sync {
+    let field_mapping: Vec<Option<Field>> = src_schema
+        .fields()
+        .map(|(_, field_entry)| dst_schema.get_field(field_entry.name()).ok())
+        .collect();
+    move |src_doc: TantivyDocument| {
+        let mut dst_doc = TantivyDocument::default();
+        for (src_field, value) in src_doc.field_values() {
+            if let Some(dst_field) = field_mapping[src_field.field_id() as usize] {
+                dst_doc.add_field_value(dst_field, value);
+            }
+        }
+        Some(dst_doc)
+    }
+}
+
+struct ProgressTracker {
+    num_docs_total: u64,
+    num_docs_read: AtomicU64,
+    num_docs_indexed: AtomicU64,
+    callback_opt: Option<ReindexProgressCallback>,
+}
+
+impl ProgressTracker {
+    fn progress(&self) -> ReindexProgress {
+        ReindexProgress {
+            num_docs_total: self.num_docs_total,
+            num_docs_read: self.num_docs_read.load(Ordering::Relaxed),
+            num_docs_indexed: self.num_docs_indexed.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record(&self, indexed: bool) {
+        if indexed {
+            self.num_docs_indexed.fetch_add(1, Ordering::Relaxed);
+        }
+        let num_docs_read = self.num_docs_read.fetch_add(1, Ordering::Relaxed) + 1;
+        if num_docs_read % PROGRESS_REPORT_NUM_DOCS == 0 {
+            self.report();
+        }
+    }
+
+    fn report(&self) {
+        if let Some(callback) = self.callback_opt.as_ref() {
+            callback(self.progress());
+        }
+    }
+}
+
+fn reindex_segment<F>(
+    segment_reader: &SegmentReader,
+    index_writer: &IndexWriter,
+    transform: &F,
+    progress_tracker: &ProgressTracker,
+) -> crate::Result<()>
+where
+    F: Fn(TantivyDocument) -> Option<TantivyDocument>,
+{
+    let store_reader = segment_reader.get_store_reader(DOCSTORE_CACHE_CAPACITY)?;
+    for doc_res in store_reader.iter::<TantivyDocument>(segment_reader.alive_bitset()) {
+        let dst_doc_opt = transform(doc_res?);
+        let indexed = dst_doc_opt.is_some();
+        if let Some(dst_doc) = dst_doc_opt {
+            index_writer.add_document(dst_doc)?;
+        }
+        progress_tracker.record(indexed);
+    }
+    Ok(())
+}
+
+/// Rebuilds `dst_index` from the documents stored in `src_index`.
+///
+/// This is typically used after a change of schema or of analyzers: the stored
+/// documents of the last commit of `src_index` are read, passed through `transform`,
+/// and indexed into `dst_index`, which is then committed.
+/// `transform` receives documents following the schema of `src_index`, and should
+/// return documents following the schema of `dst_index`, or `None` to skip a document.
+/// [`copy_fields_by_name`] can be used when no other change than the schema is needed.
+///
+/// Only stored fields can be recovered: fields that are not stored in `src_index` will
+/// be missing from the documents passed to `transform`.
+///
+/// This function acquires the writer lock on `dst_index`. It does not require
+/// the writer lock of `src_index`, and only reads its last commit.
+pub fn reindex<F>(
+    src_index: &Index,
+    dst_index: &Index,
+    transform: F,
+    options: ReindexOptions,
+) -> crate::Result<ReindexProgress>
+where
+    F: Fn(TantivyDocument) -> Option<TantivyDocument> + Sync,
+{
+    let segment_readers: Vec<SegmentReader> = src_index
+        .searchable_segments()?
+        .iter()
+        .map(SegmentReader::open)
+        .collect::<crate::Result<_>>()?;
+    let num_docs_total = segment_readers
+        .iter()
+        .map(|segment_reader| segment_reader.num_docs() as u64)
+        .sum();
+    let progress_tracker = ProgressTracker {
+        num_docs_total,
+        num_docs_read: AtomicU64::default(),
+        num_docs_indexed: AtomicU64::default(),
+        callback_opt: options.progress_callback,
+    };
+
+    let mut index_writer: IndexWriter = dst_index.writer_with_options(options.writer_options)?;
+    let executor = if options.num_reader_threads > 1 {
+        Executor::multi_thread(options.num_reader_threads, "tantivy-reindex-")?
+    } else {
+        Executor::single_thread()
+    };
+    executor.map(
+        |segment_reader| {
+            reindex_segment(segment_reader, &index_writer, &transform, &progress_tracker)
+        },
+        segment_readers.iter(),
+    )?;
+    index_writer.commit()?;
+    index_writer.wait_merging_threads()?;
+
+    progress_tracker.report();
+    Ok(progress_tracker.progress())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Value, STORED, STRING, TEXT};
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_reindex_change_analyzer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STRING | STORED);
+        schema_builder.add_u64_field("dropped", STORED);
+        let src_index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = src_index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "Hello World"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "hello tantivy"))?;
+        index_writer.add_document(doc!(title => "goodbye"))?;
+        index_writer.delete_term(Term::from_field_text(title, "goodbye"));
+        index_writer.commit()?;
+
+        let mut schema_builder = Schema::builder();
+        let dst_title = schema_builder.add_text_field("title", TEXT | STORED);
+        let dst_index = Index::create_in_ram(schema_builder.build());
+        let progress_reports = Arc::new(Mutex::new(Vec::new()));
+        let progress_reports_clone = progress_reports.clone();
+        let options = ReindexOptions::builder()
+            .num_reader_threads(2)
+            .progress_callback(Arc::new(move |progress| {
+                progress_reports_clone.lock().unwrap().push(progress);
+            }))
+            .build();
+        let progress = reindex(
+            &src_index,
+            &dst_index,
+            copy_fields_by_name(&src_index.schema(), &dst_index.schema()),
+            options,
+        )?;
+        let expected_progress = ReindexProgress {
+            num_docs_total: 2,
+            num_docs_read: 2,
+            num_docs_indexed: 2,
+        };
+        assert_eq!(progress, expected_progress);
+        assert_eq!(*progress_reports.lock().unwrap(), vec![expected_progress]);
+
+        let searcher = dst_index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(dst_title, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindex_with_transform() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let num = schema_builder.add_u64_field("num", STORED);
+        let src_index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = src_index.writer_for_tests()?;
+        for i in 0..10u64 {
+            index_writer.add_document(doc!(num => i))?;
+        }
+        index_writer.commit()?;
+
+        let mut schema_builder = Schema::builder();
+        let parity = schema_builder.add_text_field("parity", STRING);
+        let dst_index = Index::create_in_ram(schema_builder.build());
+        let progress = reindex(
+            &src_index,
+            &dst_index,
+            |src_doc: TantivyDocument| {
+                let val = src_doc.get_first(num)?.as_u64()?;
+                if val == 0 {
+                    return None;
+                }
+                let parity_str = if val % 2 == 0 { "even" } else { "odd" };
+                Some(doc!(parity => parity_str))
+            },
+            ReindexOptions::default(),
+        )?;
+        assert_eq!(progress.num_docs_read, 10);
+        assert_eq!(progress.num_docs_indexed, 9);
+        let searcher = dst_index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(parity, "even"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 4);
+        Ok(())
+    }
+}
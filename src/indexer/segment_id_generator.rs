@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::directory::Directory;
+use crate::index::{Index, Segment, SegmentId};
+use crate::Opstamp;
+
+/// Assigns the ids of the segments created by an `IndexWriter`.
+pub(crate) enum SegmentIdGenerator {
+    /// Random ids.
+    Random,
+    /// The ids are made of the opstamp of the last commit when the generator was created,
+    /// followed by a counter.
+    ///
+    /// Given the same sequence of operations, the same ids are generated.
+    Deterministic {
+        committed_opstamp: Opstamp,
+        counter: AtomicU64,
+    },
+}
+
+impl SegmentIdGenerator {
+    pub fn new(deterministic: bool, committed_opstamp: Opstamp) -> SegmentIdGenerator {
+        if deterministic {
+            SegmentIdGenerator::Deterministic {
+                committed_opstamp,
+                counter: AtomicU64::new(0),
+            }
+        } else {
+            SegmentIdGenerator::Random
+        }
+    }
+
+    /// Creates a new segment of `index`.
+    pub fn new_segment(&self, index: &Index) -> Segment {
+        match self {
+            SegmentIdGenerator::Random => index.new_segment(),
+            SegmentIdGenerator::Deterministic {
+                committed_opstamp,
+                counter,
+            } => loop {
+                // Ids may already be taken by the files of segments that were rolled back.
+                let id = (u128::from(*committed_opstamp) << 64)
+                    | u128::from(counter.fetch_add(1, Ordering::SeqCst));
+                let segment = index.segment(index.new_segment_meta(SegmentId::from_u128(id), 0));
+                let is_taken = segment
+                    .meta()
+                    .list_files()
+                    .iter()
+                    .any(|path| index.directory().exists(path).unwrap_or(true));
+                if !is_taken {
+                    return segment;
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Schema;
+
+    #[test]
+    fn test_deterministic_segment_ids() {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let generator = SegmentIdGenerator::new(true, 3);
+        let ids: Vec<String> = (0..2)
+            .map(|_| generator.new_segment(&index).id().uuid_string())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "00000000000000030000000000000000",
+                "00000000000000030000000000000001"
+            ]
+        );
+    }
+}
@@ -98,6 +98,12 @@ impl SegmentManager {
         segment_entries
     }
 
+    /// Returns the number of segments (committed or uncommitted)
+    pub fn num_segments(&self) -> usize {
+        let registers_lock = self.read();
+        registers_lock.uncommitted.num_segments() + registers_lock.committed.num_segments()
+    }
+
     // Lock poisoning should never happen :
     // The lock is acquired and released within this class,
     // and the operations cannot panic.
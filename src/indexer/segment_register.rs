@@ -55,17 +55,26 @@ impl SegmentRegister {
             .collect()
     }
 
+    pub fn num_segments(&self) -> usize {
+        self.segment_states.len()
+    }
+
     pub fn segment_ids(&self) -> Vec<SegmentId> {
         self.segment_states.keys().cloned().collect()
     }
 
+    /// Returns the segment entries, sorted by segment id.
     pub fn segment_entries(&self) -> Vec<SegmentEntry> {
-        self.segment_states.values().cloned().collect()
+        let mut segment_entries: Vec<SegmentEntry> =
+            self.segment_states.values().cloned().collect();
+        segment_entries.sort_by_key(|segment_entry| segment_entry.segment_id());
+        segment_entries
     }
 
+    /// Returns the segment metas, sorted by segment id.
     pub fn segment_metas(&self) -> Vec<SegmentMeta> {
-        self.segment_states
-            .values()
+        self.segment_entries()
+            .into_iter()
             .map(|segment_entry| segment_entry.meta().clone())
             .collect()
     }
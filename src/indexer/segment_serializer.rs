@@ -3,8 +3,11 @@ use common::TerminatingWrite;
 use crate::directory::WritePtr;
 use crate::fieldnorm::FieldNormsSerializer;
 use crate::index::{Segment, SegmentComponent};
+use crate::points::PointsSerializer;
 use crate::postings::InvertedIndexSerializer;
-use crate::store::StoreWriter;
+use crate::store::{StoreFamily, StoreWriter};
+use crate::term_vectors::TermVectorsSerializer;
+use crate::vector::VectorsSerializer;
 
 /// Segment serializer is in charge of laying out on disk
 /// the data accumulated and sorted by the `SegmentWriter`.
@@ -13,6 +16,9 @@ pub struct SegmentSerializer {
     pub(crate) store_writer: StoreWriter,
     fast_field_write: WritePtr,
     fieldnorms_serializer: Option<FieldNormsSerializer>,
+    points_serializer: Option<PointsSerializer>,
+    vectors_serializer: Option<VectorsSerializer>,
+    term_vectors_serializer: Option<TermVectorsSerializer>,
     postings_serializer: InvertedIndexSerializer,
 }
 
@@ -21,11 +27,14 @@ impl SegmentSerializer {
     pub fn for_segment(mut segment: Segment) -> crate::Result<SegmentSerializer> {
         let settings = segment.index().settings().clone();
         let store_writer = {
+            let families =
+                StoreFamily::from_settings(&settings.docstore_families, &segment.schema())?;
             let store_write = segment.open_write(SegmentComponent::Store)?;
-            StoreWriter::new(
+            StoreWriter::with_families(
                 store_write,
                 settings.docstore_compression,
                 settings.docstore_blocksize,
+                families,
                 settings.docstore_compress_dedicated_thread,
             )?
         };
@@ -35,12 +44,24 @@ impl SegmentSerializer {
         let fieldnorms_write = segment.open_write(SegmentComponent::FieldNorms)?;
         let fieldnorms_serializer = FieldNormsSerializer::from_write(fieldnorms_write)?;
 
+        let points_write = segment.open_write(SegmentComponent::Points)?;
+        let points_serializer = PointsSerializer::from_write(points_write)?;
+
+        let vectors_write = segment.open_write(SegmentComponent::Vectors)?;
+        let vectors_serializer = VectorsSerializer::from_write(vectors_write)?;
+
+        let term_vectors_write = segment.open_write(SegmentComponent::TermVectors)?;
+        let term_vectors_serializer = TermVectorsSerializer::from_write(term_vectors_write)?;
+
         let postings_serializer = InvertedIndexSerializer::open(&mut segment)?;
         Ok(SegmentSerializer {
             segment,
             store_writer,
             fast_field_write,
             fieldnorms_serializer: Some(fieldnorms_serializer),
+            points_serializer: Some(points_serializer),
+            vectors_serializer: Some(vectors_serializer),
+            term_vectors_serializer: Some(term_vectors_serializer),
             postings_serializer,
         })
     }
@@ -71,6 +92,27 @@ impl SegmentSerializer {
         self.fieldnorms_serializer.take()
     }
 
+    /// Extract the points serializer.
+    ///
+    /// Note the points serializer can only be extracted once.
+    pub fn extract_points_serializer(&mut self) -> Option<PointsSerializer> {
+        self.points_serializer.take()
+    }
+
+    /// Extract the vectors serializer.
+    ///
+    /// Note the vectors serializer can only be extracted once.
+    pub fn extract_vectors_serializer(&mut self) -> Option<VectorsSerializer> {
+        self.vectors_serializer.take()
+    }
+
+    /// Extract the term vectors serializer.
+    ///
+    /// Note the term vectors serializer can only be extracted once.
+    pub fn extract_term_vectors_serializer(&mut self) -> Option<TermVectorsSerializer> {
+        self.term_vectors_serializer.take()
+    }
+
     /// Accessor to the `StoreWriter`.
     pub fn get_store_writer(&mut self) -> &mut StoreWriter {
         &mut self.store_writer
@@ -81,6 +123,15 @@ impl SegmentSerializer {
         if let Some(fieldnorms_serializer) = self.extract_fieldnorms_serializer() {
             fieldnorms_serializer.close()?;
         }
+        if let Some(points_serializer) = self.extract_points_serializer() {
+            points_serializer.close()?;
+        }
+        if let Some(vectors_serializer) = self.extract_vectors_serializer() {
+            vectors_serializer.close()?;
+        }
+        if let Some(term_vectors_serializer) = self.extract_term_vectors_serializer() {
+            term_vectors_serializer.close()?;
+        }
         self.fast_field_write.terminate()?;
         self.postings_serializer.close()?;
         self.store_writer.close()?;
@@ -6,23 +6,28 @@ use std::ops::Deref;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
+use super::merge_scheduler::MergeQueue;
+use super::segment_id_generator::SegmentIdGenerator;
 use super::segment_manager::SegmentManager;
 use crate::core::META_FILEPATH;
+use crate::directory::error::LockError;
 use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
 use crate::fastfield::AliveBitSet;
 use crate::index::{Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta};
 use crate::indexer::delete_queue::DeleteCursor;
 use crate::indexer::index_writer::advance_deletes;
+use crate::indexer::index_writer_metrics::IndexWriterMetricsRecorder;
 use crate::indexer::merge_operation::MergeOperationInventory;
 use crate::indexer::merger::IndexMerger;
 use crate::indexer::segment_manager::SegmentsStatus;
 use crate::indexer::stamper::Stamper;
 use crate::indexer::{
-    DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
-    SegmentSerializer,
+    DefaultMergePolicy, DefaultMergeScheduler, MergeCandidate, MergeOperation, MergePolicy,
+    MergeScheduler, SegmentEntry, SegmentSerializer,
 };
 use crate::{FutureResult, Opstamp, TantivyError};
 
@@ -75,6 +80,8 @@ impl Deref for SegmentUpdater {
 fn garbage_collect_files(
     segment_updater: SegmentUpdater,
 ) -> crate::Result<GarbageCollectionResult> {
+    // The files of the index may now belong to the process which acquired the lost lock.
+    segment_updater.check_lock()?;
     info!("Running garbage collection");
     let mut index = segment_updater.index.clone();
     index
@@ -86,6 +93,7 @@ fn garbage_collect_files(
 /// This function happens in the calling thread and is computationally expensive.
 fn merge(
     index: &Index,
+    segment_id_generator: &SegmentIdGenerator,
     mut segment_entries: Vec<SegmentEntry>,
     target_opstamp: Opstamp,
 ) -> crate::Result<Option<SegmentEntry>> {
@@ -98,7 +106,7 @@ fn merge(
     }
 
     // first we need to apply deletes to our segment.
-    let merged_segment = index.new_segment();
+    let merged_segment = segment_id_generator.new_segment(index);
 
     // First we apply all of the delete to the merged segment, up to the target opstamp.
     for segment_entry in &mut segment_entries {
@@ -259,14 +267,19 @@ pub(crate) struct InnerSegmentUpdater {
     // the unique active `SegmentUpdater`.
     active_index_meta: RwLock<Arc<IndexMeta>>,
     pool: ThreadPool,
-    merge_thread_pool: ThreadPool,
+    merge_queue: MergeQueue,
 
     index: Index,
     segment_manager: SegmentManager,
     merge_policy: RwLock<Arc<dyn MergePolicy>>,
+    merge_scheduler: RwLock<Arc<dyn MergeScheduler>>,
     killed: AtomicBool,
+    // Set when the lock of the `IndexWriter` gets lost.
+    lock_lost: Arc<AtomicBool>,
     stamper: Stamper,
     merge_operations: MergeOperationInventory,
+    metrics: IndexWriterMetricsRecorder,
+    segment_id_generator: SegmentIdGenerator,
 }
 
 impl SegmentUpdater {
@@ -275,6 +288,8 @@ impl SegmentUpdater {
         stamper: Stamper,
         delete_cursor: &DeleteCursor,
         num_merge_threads: usize,
+        segment_id_generator: SegmentIdGenerator,
+        lock_lost: Arc<AtomicBool>,
     ) -> crate::Result<SegmentUpdater> {
         let segments = index.searchable_segment_metas()?;
         let segment_manager = SegmentManager::from_segments(segments, delete_cursor);
@@ -309,16 +324,46 @@ impl SegmentUpdater {
         Ok(SegmentUpdater(Arc::new(InnerSegmentUpdater {
             active_index_meta: RwLock::new(Arc::new(index_meta)),
             pool,
-            merge_thread_pool,
+            merge_queue: MergeQueue::new(merge_thread_pool),
             index,
             segment_manager,
             merge_policy: RwLock::new(Arc::new(DefaultMergePolicy::default())),
+            merge_scheduler: RwLock::new(Arc::new(DefaultMergeScheduler::default())),
             killed: AtomicBool::new(false),
+            lock_lost,
             stamper,
             merge_operations: Default::default(),
+            metrics: Default::default(),
+            segment_id_generator,
         })))
     }
 
+    /// Creates a new segment, to be filled by an indexing worker or a merge.
+    pub(crate) fn new_segment(&self) -> Segment {
+        self.segment_id_generator.new_segment(&self.index)
+    }
+
+    pub(crate) fn metrics(&self) -> &IndexWriterMetricsRecorder {
+        &self.metrics
+    }
+
+    /// Returns the number of merges that are scheduled or running.
+    pub(crate) fn num_pending_merges(&self) -> usize {
+        self.merge_operations.len()
+    }
+
+    /// Returns the number of segments (committed or uncommitted) managed by the updater.
+    pub(crate) fn num_segments(&self) -> usize {
+        self.segment_manager.num_segments()
+    }
+
+    /// Blocks until the number of merges that are scheduled or running goes below
+    /// `num_pending_merges`.
+    pub(crate) fn wait_num_pending_merges_below(&self, num_pending_merges: usize) {
+        self.merge_operations
+            .wait_until_predicate(|count| count < num_pending_merges);
+    }
+
     pub fn get_merge_policy(&self) -> Arc<dyn MergePolicy> {
         self.merge_policy.read().unwrap().clone()
     }
@@ -328,6 +373,27 @@ impl SegmentUpdater {
         *self.merge_policy.write().unwrap() = arc_merge_policy;
     }
 
+    pub fn get_merge_scheduler(&self) -> Arc<dyn MergeScheduler> {
+        self.merge_scheduler.read().unwrap().clone()
+    }
+
+    pub fn set_merge_scheduler(&self, merge_scheduler: Box<dyn MergeScheduler>) {
+        *self.merge_scheduler.write().unwrap() = Arc::from(merge_scheduler);
+    }
+
+    /// Prevents merges from starting, until `resume_merging` is called.
+    pub(crate) fn pause_merging(&self) {
+        self.merge_queue.pause();
+    }
+
+    pub(crate) fn resume_merging(&self) {
+        self.merge_queue.resume();
+    }
+
+    pub(crate) fn is_merging_paused(&self) -> bool {
+        self.merge_queue.is_paused()
+    }
+
     fn schedule_task<T: 'static + Send, F: FnOnce() -> crate::Result<T> + 'static + Send>(
         &self,
         task: F,
@@ -361,12 +427,28 @@ impl SegmentUpdater {
 
     pub fn kill(&mut self) {
         self.killed.store(true, Ordering::Release);
+        // The merges that did not start yet would be discarded anyway.
+        self.merge_queue.clear();
     }
 
     pub fn is_alive(&self) -> bool {
         !self.killed.load(Ordering::Acquire)
     }
 
+    /// Returns an error if the lock of the `IndexWriter` was lost.
+    ///
+    /// Another process may be writing to the index by now, so nothing must be committed or
+    /// deleted anymore.
+    pub(crate) fn check_lock(&self) -> crate::Result<()> {
+        if self.lock_lost.load(Ordering::Acquire) {
+            return Err(TantivyError::LockFailure(
+                LockError::LockLost,
+                Some("The IndexWriter lost its lock on the index.".to_string()),
+            ));
+        }
+        Ok(())
+    }
+
     /// Apply deletes up to the target opstamp to all segments.
     ///
     /// The method returns copies of the segment entries,
@@ -386,6 +468,7 @@ impl SegmentUpdater {
         commit_message: Option<String>,
     ) -> crate::Result<()> {
         if self.is_alive() {
+            self.check_lock()?;
             let index = &self.index;
             let directory = index.directory();
             let mut committed_segment_metas = self.segment_manager.committed_segment_metas();
@@ -448,12 +531,30 @@ impl SegmentUpdater {
             let segment_entries = segment_updater.purge_deletes(opstamp)?;
             segment_updater.segment_manager.commit(segment_entries);
             segment_updater.save_metas(opstamp, payload)?;
+            // The committed segments supersede any near real-time snapshot.
+            segment_updater.index.near_real_time().clear();
             let _ = garbage_collect_files(segment_updater.clone());
             segment_updater.consider_merge_options();
             Ok(opstamp)
         })
     }
 
+    /// Applies the deletes up to `opstamp` and returns the metas of all of the segments,
+    /// committed or not, without touching the `meta.json` file.
+    pub(crate) fn schedule_refresh(&self, opstamp: Opstamp) -> FutureResult<Vec<SegmentMeta>> {
+        let segment_updater: SegmentUpdater = self.clone();
+        self.schedule_task(move || {
+            let segment_entries = segment_updater.purge_deletes(opstamp)?;
+            let mut segment_metas: Vec<SegmentMeta> = segment_entries
+                .iter()
+                .map(|segment_entry| segment_entry.meta().clone())
+                .collect();
+            // Same heuristic as in `save_metas`.
+            segment_metas.sort_by_key(|segment_meta| -(segment_meta.max_doc() as i32));
+            Ok(segment_metas)
+        })
+    }
+
     fn store_meta(&self, index_meta: &IndexMeta) {
         *self.active_index_meta.write().unwrap() = Arc::new(index_meta.clone());
     }
@@ -508,19 +609,38 @@ impl SegmentUpdater {
             }
         };
 
-        info!("Starting merge  - {:?}", merge_operation.segment_ids());
+        info!("Scheduling merge  - {:?}", merge_operation.segment_ids());
+
+        let segment_metas: Vec<SegmentMeta> = segment_entries
+            .iter()
+            .map(|segment_entry| segment_entry.meta().clone())
+            .collect();
+        let priority = self.get_merge_scheduler().priority(&segment_metas);
 
         let (scheduled_result, merging_future_send) =
             FutureResult::create("Merge operation failed.");
 
-        self.merge_thread_pool.spawn(move || {
+        let merge_job = move || {
             // The fact that `merge_operation` is moved here is important.
             // Its lifetime is used to track how many merging thread are currently running,
             // as well as which segment is currently in merge and therefore should not be
             // candidate for another merge.
+            let merge_start = Instant::now();
+            #[cfg(feature = "tracing")]
+            let merge_span = tracing::debug_span!(
+                "tantivy_merge",
+                num_segments = segment_entries.len(),
+                num_docs = segment_entries
+                    .iter()
+                    .map(|segment_entry| u64::from(segment_entry.meta().num_docs()))
+                    .sum::<u64>(),
+                num_bytes = tracing::field::Empty
+            )
+            .entered();
             let merge_panic_res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                 merge(
                     &segment_updater.index,
+                    &segment_updater.segment_id_generator,
                     segment_entries,
                     merge_operation.target_opstamp(),
                 )
@@ -547,7 +667,18 @@ impl SegmentUpdater {
             };
             match merge_res {
                 Ok(after_merge_segment_entry) => {
+                    let merged_segment_ids = merge_operation.segment_ids().to_vec();
                     let res = segment_updater.end_merge(merge_operation, after_merge_segment_entry);
+                    if let Ok(after_merge_segment_meta) = res.as_ref() {
+                        let _num_bytes = segment_updater.metrics.record_merge_completed(
+                            &segment_updater.index,
+                            &merged_segment_ids,
+                            after_merge_segment_meta.as_ref(),
+                            merge_start.elapsed(),
+                        );
+                        #[cfg(feature = "tracing")]
+                        merge_span.record("num_bytes", _num_bytes);
+                    }
                     let _send_result = merging_future_send.send(res);
                 }
                 Err(merge_error) => {
@@ -562,7 +693,8 @@ impl SegmentUpdater {
                     let _send_result = merging_future_send.send(Err(merge_error));
                 }
             }
-        });
+        };
+        self.merge_queue.schedule(priority, Box::new(merge_job));
 
         scheduled_result
     }
@@ -695,6 +827,8 @@ impl SegmentUpdater {
     /// Obsolete files will eventually be cleaned up
     /// by the directory garbage collector.
     pub fn wait_merging_thread(&self) -> crate::Result<()> {
+        // Paused merges would never complete.
+        self.resume_merging();
         self.merge_operations.wait_until_empty();
         Ok(())
     }
@@ -9,13 +9,16 @@ use crate::fieldnorm::{FieldNormReaders, FieldNormsWriter};
 use crate::index::{Segment, SegmentComponent};
 use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::json_utils::{index_json_value, IndexingPositionsPerPath};
+use crate::points::PointsWriter;
 use crate::postings::{
     compute_table_memory_size, serialize_postings, IndexingContext, IndexingPosition,
     PerFieldPostingsWriter, PostingsWriter,
 };
 use crate::schema::document::{Document, Value};
 use crate::schema::{FieldEntry, FieldType, Schema, Term, DATE_TIME_PRECISION_INDEXED};
-use crate::tokenizer::{FacetTokenizer, PreTokenizedStream, TextAnalyzer, Tokenizer};
+use crate::term_vectors::{RecordingTokenStream, TermVectorsWriter};
+use crate::tokenizer::{FacetTokenizer, PreTokenizedStream, TextAnalyzer, Token, Tokenizer};
+use crate::vector::VectorsWriter;
 use crate::{DocId, Opstamp, TantivyError};
 
 /// Computes the initial size of the hash table.
@@ -51,11 +54,15 @@ pub struct SegmentWriter {
     pub(crate) segment_serializer: SegmentSerializer,
     pub(crate) fast_field_writers: FastFieldsWriter,
     pub(crate) fieldnorms_writer: FieldNormsWriter,
+    pub(crate) points_writer: PointsWriter,
+    pub(crate) vectors_writer: VectorsWriter,
+    pub(crate) term_vectors_writer: TermVectorsWriter,
     pub(crate) json_path_writer: JsonPathWriter,
     pub(crate) json_positions_per_path: IndexingPositionsPerPath,
     pub(crate) doc_opstamps: Vec<Opstamp>,
     per_field_text_analyzers: Vec<TextAnalyzer>,
     term_buffer: Term,
+    term_vector_tokens: Vec<Token>,
     schema: Schema,
 }
 
@@ -103,6 +110,9 @@ impl SegmentWriter {
             ctx: IndexingContext::new(table_size),
             per_field_postings_writers,
             fieldnorms_writer: FieldNormsWriter::for_schema(&schema),
+            points_writer: PointsWriter::for_schema(&schema),
+            vectors_writer: VectorsWriter::for_schema(&schema),
+            term_vectors_writer: TermVectorsWriter::for_schema(&schema),
             json_path_writer: JsonPathWriter::default(),
             json_positions_per_path: IndexingPositionsPerPath::default(),
             segment_serializer,
@@ -113,6 +123,7 @@ impl SegmentWriter {
             doc_opstamps: Vec::with_capacity(1_000),
             per_field_text_analyzers,
             term_buffer: Term::with_capacity(16),
+            term_vector_tokens: Vec::new(),
             schema,
         })
     }
@@ -123,12 +134,16 @@ impl SegmentWriter {
     /// be used afterwards.
     pub fn finalize(mut self) -> crate::Result<Vec<u64>> {
         self.fieldnorms_writer.fill_up_to_max_doc(self.max_doc);
+        self.term_vectors_writer.fill_up_to_max_doc(self.max_doc);
         remap_and_write(
             self.schema,
             &self.per_field_postings_writers,
             self.ctx,
             self.fast_field_writers,
             &self.fieldnorms_writer,
+            self.points_writer,
+            &self.vectors_writer,
+            &self.term_vectors_writer,
             self.segment_serializer,
         )?;
         Ok(self.doc_opstamps)
@@ -139,6 +154,9 @@ impl SegmentWriter {
     pub fn mem_usage(&self) -> usize {
         self.ctx.mem_usage()
             + self.fieldnorms_writer.mem_usage()
+            + self.points_writer.mem_usage()
+            + self.vectors_writer.mem_usage()
+            + self.term_vectors_writer.mem_usage()
             + self.fast_field_writers.mem_usage()
             + self.segment_serializer.mem_usage()
     }
@@ -192,27 +210,59 @@ impl SegmentWriter {
                 }
                 FieldType::Str(_) => {
                     let mut indexing_position = IndexingPosition::default();
+                    let has_term_vectors = self.term_vectors_writer.has_term_vectors(field);
                     for value in values {
                         let value = value.as_value();
 
-                        let mut token_stream = if let Some(text) = value.as_str() {
+                        let (mut token_stream, text_len) = if let Some(text) = value.as_str() {
                             let text_analyzer =
                                 &mut self.per_field_text_analyzers[field.field_id() as usize];
-                            text_analyzer.token_stream(text)
+                            (text_analyzer.token_stream(text), text.len())
                         } else if let Some(tok_str) = value.into_pre_tokenized_text() {
-                            BoxTokenStream::new(PreTokenizedStream::from(*tok_str.clone()))
+                            let text_len = tok_str.text.len();
+                            (
+                                BoxTokenStream::new(PreTokenizedStream::from(*tok_str.clone())),
+                                text_len,
+                            )
                         } else {
                             continue;
                         };
 
                         assert!(term_buffer.is_empty());
-                        postings_writer.index_text(
-                            doc_id,
-                            &mut *token_stream,
-                            term_buffer,
-                            ctx,
-                            &mut indexing_position,
-                        );
+                        if has_term_vectors {
+                            let start_position = indexing_position.end_position;
+                            self.term_vector_tokens.clear();
+                            let mut recording_token_stream = RecordingTokenStream::new(
+                                &mut *token_stream,
+                                &mut self.term_vector_tokens,
+                            );
+                            postings_writer.index_text(
+                                doc_id,
+                                &mut recording_token_stream,
+                                term_buffer,
+                                ctx,
+                                &mut indexing_position,
+                            );
+                            self.term_vectors_writer.record_tokens(
+                                field,
+                                start_position,
+                                indexing_position.start_offset,
+                                &self.term_vector_tokens,
+                            );
+                        } else {
+                            postings_writer.index_text(
+                                doc_id,
+                                &mut *token_stream,
+                                term_buffer,
+                                ctx,
+                                &mut indexing_position,
+                            );
+                        }
+                        // The values of the field are separated by a space.
+                        indexing_position.start_offset += text_len as u32 + 1;
+                    }
+                    if has_term_vectors {
+                        self.term_vectors_writer.end_field(doc_id, field)?;
                     }
                     if field_entry.has_fieldnorms() {
                         self.fieldnorms_writer
@@ -350,8 +400,10 @@ impl SegmentWriter {
         add_operation: AddOperation<D>,
     ) -> crate::Result<()> {
         let AddOperation { document, opstamp } = add_operation;
+        self.vectors_writer.add_document(self.max_doc, &document)?;
         self.doc_opstamps.push(opstamp);
         self.fast_field_writers.add_document(&document)?;
+        self.points_writer.add_document(self.max_doc, &document);
         self.index_document(&document)?;
         let doc_writer = self.segment_serializer.get_store_writer();
         doc_writer.store(&document, &self.schema)?;
@@ -385,18 +437,31 @@ impl SegmentWriter {
 /// to the `SegmentSerializer`.
 ///
 /// `doc_id_map` is used to map to the new doc_id order.
+#[expect(clippy::too_many_arguments)]
 fn remap_and_write(
     schema: Schema,
     per_field_postings_writers: &PerFieldPostingsWriter,
     ctx: IndexingContext,
     fast_field_writers: FastFieldsWriter,
     fieldnorms_writer: &FieldNormsWriter,
+    points_writer: PointsWriter,
+    vectors_writer: &VectorsWriter,
+    term_vectors_writer: &TermVectorsWriter,
     mut serializer: SegmentSerializer,
 ) -> crate::Result<()> {
     debug!("remap-and-write");
     if let Some(fieldnorms_serializer) = serializer.extract_fieldnorms_serializer() {
         fieldnorms_writer.serialize(fieldnorms_serializer)?;
     }
+    if let Some(points_serializer) = serializer.extract_points_serializer() {
+        points_writer.serialize(points_serializer)?;
+    }
+    if let Some(vectors_serializer) = serializer.extract_vectors_serializer() {
+        vectors_writer.serialize(vectors_serializer)?;
+    }
+    if let Some(term_vectors_serializer) = serializer.extract_term_vectors_serializer() {
+        term_vectors_writer.serialize(term_vectors_serializer)?;
+    }
     let fieldnorm_data = serializer
         .segment()
         .open_read(SegmentComponent::FieldNorms)?;
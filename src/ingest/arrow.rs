@@ -0,0 +1,436 @@
+//! Indexing of Arrow [`RecordBatch`]es.
+//!
+//! Rows of a `RecordBatch` are indexed directly from the Arrow columns, through
+//! [`ArrowRow`], without building an intermediary [`TantivyDocument`](crate::TantivyDocument).
+//!
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+//! use tantivy::ingest::arrow::{add_record_batch, schema_from_arrow, ArrowRow};
+//! use tantivy::{Index, IndexWriter};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let batch = RecordBatch::try_from_iter(vec![
+//!     ("title", Arc::new(StringArray::from(vec!["hello", "world"])) as ArrayRef),
+//!     ("count", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+//! ])
+//! .unwrap();
+//! let index = Index::create_in_ram(schema_from_arrow(&batch.schema())?);
+//! let mut index_writer: IndexWriter<ArrowRow> = index.writer(15_000_000)?;
+//! add_record_batch(&index_writer, &batch)?;
+//! index_writer.commit()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Schema as ArrowSchema, TimeUnit};
+use common::DateTime;
+
+use crate::indexer::UserOperation;
+use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
+use crate::schema::{Field, Schema, FAST, INDEXED, STORED, TEXT};
+use crate::{IndexWriter, Opstamp, TantivyError};
+
+/// Returns true if the values of an Arrow column of type `data_type` can be indexed.
+fn is_supported_leaf_type(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::Boolean
+            | DataType::Timestamp(_, _)
+            | DataType::Date32
+            | DataType::Date64
+            | DataType::Binary
+            | DataType::LargeBinary
+    )
+}
+
+/// Returns the type of the values of an Arrow column, looking through lists.
+fn leaf_type(data_type: &DataType) -> &DataType {
+    match data_type {
+        DataType::List(item) | DataType::LargeList(item) => item.data_type(),
+        _ => data_type,
+    }
+}
+
+fn unsupported_type_error(column_name: &str, data_type: &DataType) -> TantivyError {
+    TantivyError::InvalidArgument(format!(
+        "Arrow column `{column_name}` has unsupported type {data_type}"
+    ))
+}
+
+/// Builds a tantivy schema with one field per column of `arrow_schema`.
+///
+/// - strings are mapped to `TEXT | STORED` text fields,
+/// - integers, floats, booleans, timestamps and dates are mapped to `INDEXED | STORED | FAST`
+///   fields of the corresponding type,
+/// - binaries are mapped to `INDEXED | STORED | FAST` bytes fields,
+/// - lists of the types above are mapped to multivalued fields.
+///
+/// Other types are rejected. To customize the options of the fields, build the schema
+/// by hand: [`add_record_batch`] only relies on the names of the fields.
+pub fn schema_from_arrow(arrow_schema: &ArrowSchema) -> crate::Result<Schema> {
+    let mut schema_builder = Schema::builder();
+    for arrow_field in arrow_schema.fields() {
+        let name = arrow_field.name();
+        match leaf_type(arrow_field.data_type()) {
+            DataType::Utf8 | DataType::LargeUtf8 => {
+                schema_builder.add_text_field(name, TEXT | STORED);
+            }
+            DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+                schema_builder.add_i64_field(name, INDEXED | STORED | FAST);
+            }
+            DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+                schema_builder.add_u64_field(name, INDEXED | STORED | FAST);
+            }
+            DataType::Float32 | DataType::Float64 => {
+                schema_builder.add_f64_field(name, INDEXED | STORED | FAST);
+            }
+            DataType::Boolean => {
+                schema_builder.add_bool_field(name, INDEXED | STORED | FAST);
+            }
+            DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64 => {
+                schema_builder.add_date_field(name, INDEXED | STORED | FAST);
+            }
+            DataType::Binary | DataType::LargeBinary => {
+                schema_builder.add_bytes_field(name, INDEXED | STORED | FAST);
+            }
+            data_type => return Err(unsupported_type_error(name, data_type)),
+        }
+    }
+    Ok(schema_builder.build())
+}
+
+/// A row of a [`RecordBatch`], seen as a [`Document`].
+///
+/// Values are read lazily from the Arrow columns. Null values are skipped.
+#[derive(Clone, Debug)]
+pub struct ArrowRow {
+    columns: Arc<[(Field, ArrayRef)]>,
+    row: usize,
+}
+
+impl Document for ArrowRow {
+    type Value<'a> = ArrowValue<'a>;
+    type FieldsValuesIter<'a> = ArrowRowIter<'a>;
+
+    fn iter_fields_and_values(&self) -> Self::FieldsValuesIter<'_> {
+        ArrowRowIter {
+            columns: self.columns.iter(),
+            row: self.row,
+            list_values: None,
+        }
+    }
+}
+
+/// Iterator over the non-null values of an [`ArrowRow`].
+///
+/// The elements of list columns are returned as separate values of the same field.
+pub struct ArrowRowIter<'a> {
+    columns: std::slice::Iter<'a, (Field, ArrayRef)>,
+    row: usize,
+    list_values: Option<(Field, &'a dyn Array, Range<usize>)>,
+}
+
+impl<'a> Iterator for ArrowRowIter<'a> {
+    type Item = (Field, ArrowValue<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((field, values, rows)) = self.list_values.as_mut() {
+                if let Some(row) = rows.find(|&row| values.is_valid(row)) {
+                    return Some((
+                        *field,
+                        ArrowValue {
+                            array: *values,
+                            row,
+                        },
+                    ));
+                }
+                self.list_values = None;
+            }
+            let (field, array) = self.columns.next()?;
+            if !array.is_valid(self.row) {
+                continue;
+            }
+            match array.data_type() {
+                DataType::List(_) => {
+                    let list = array.as_list::<i32>();
+                    let offsets = list.value_offsets();
+                    let rows = offsets[self.row] as usize..offsets[self.row + 1] as usize;
+                    self.list_values = Some((*field, list.values().as_ref(), rows));
+                }
+                DataType::LargeList(_) => {
+                    let list = array.as_list::<i64>();
+                    let offsets = list.value_offsets();
+                    let rows = offsets[self.row] as usize..offsets[self.row + 1] as usize;
+                    self.list_values = Some((*field, list.values().as_ref(), rows));
+                }
+                _ => {
+                    let value = ArrowValue {
+                        array: array.as_ref(),
+                        row: self.row,
+                    };
+                    return Some((*field, value));
+                }
+            }
+        }
+    }
+}
+
+/// A value of an Arrow array.
+#[derive(Clone, Copy, Debug)]
+pub struct ArrowValue<'a> {
+    array: &'a dyn Array,
+    row: usize,
+}
+
+impl<'a> Value<'a> for ArrowValue<'a> {
+    type ArrayIter = std::iter::Empty<Self>;
+    type ObjectIter = std::iter::Empty<(&'a str, Self)>;
+
+    fn as_value(&self) -> ReferenceValue<'a, Self> {
+        let array = self.array;
+        let row = self.row;
+        if array.is_null(row) {
+            return ReferenceValueLeaf::Null.into();
+        }
+        let leaf: ReferenceValueLeaf<'a> = match array.data_type() {
+            DataType::Utf8 => array.as_string::<i32>().value(row).into(),
+            DataType::LargeUtf8 => array.as_string::<i64>().value(row).into(),
+            DataType::Int8 => (array.as_primitive::<Int8Type>().value(row) as i64).into(),
+            DataType::Int16 => (array.as_primitive::<Int16Type>().value(row) as i64).into(),
+            DataType::Int32 => (array.as_primitive::<Int32Type>().value(row) as i64).into(),
+            DataType::Int64 => array.as_primitive::<Int64Type>().value(row).into(),
+            DataType::UInt8 => (array.as_primitive::<UInt8Type>().value(row) as u64).into(),
+            DataType::UInt16 => (array.as_primitive::<UInt16Type>().value(row) as u64).into(),
+            DataType::UInt32 => (array.as_primitive::<UInt32Type>().value(row) as u64).into(),
+            DataType::UInt64 => array.as_primitive::<UInt64Type>().value(row).into(),
+            DataType::Float32 => (array.as_primitive::<Float32Type>().value(row) as f64).into(),
+            DataType::Float64 => array.as_primitive::<Float64Type>().value(row).into(),
+            DataType::Boolean => array.as_boolean().value(row).into(),
+            DataType::Timestamp(time_unit, _) => {
+                let timestamp = timestamp_value(array, row);
+                let date_time = match time_unit {
+                    TimeUnit::Second => DateTime::from_timestamp_secs(timestamp),
+                    TimeUnit::Millisecond => DateTime::from_timestamp_millis(timestamp),
+                    TimeUnit::Microsecond => DateTime::from_timestamp_micros(timestamp),
+                    TimeUnit::Nanosecond => DateTime::from_timestamp_nanos(timestamp),
+                };
+                date_time.into()
+            }
+            DataType::Date32 => {
+                let num_days = array
+                    .as_primitive::<arrow_array::types::Date32Type>()
+                    .value(row) as i64;
+                DateTime::from_timestamp_secs(num_days * 86_400).into()
+            }
+            DataType::Date64 => {
+                let timestamp_millis = array
+                    .as_primitive::<arrow_array::types::Date64Type>()
+                    .value(row);
+                DateTime::from_timestamp_millis(timestamp_millis).into()
+            }
+            DataType::Binary => array.as_binary::<i32>().value(row).into(),
+            DataType::LargeBinary => array.as_binary::<i64>().value(row).into(),
+            // Columns are checked by `add_record_batch`.
+            _ => ReferenceValueLeaf::Null,
+        };
+        leaf.into()
+    }
+}
+
+fn timestamp_value(array: &dyn Array, row: usize) -> i64 {
+    use arrow_array::types::{
+        TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+        TimestampSecondType,
+    };
+    match array.data_type() {
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            array.as_primitive::<TimestampSecondType>().value(row)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            array.as_primitive::<TimestampMillisecondType>().value(row)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            array.as_primitive::<TimestampMicrosecondType>().value(row)
+        }
+        _ => array.as_primitive::<TimestampNanosecondType>().value(row),
+    }
+}
+
+/// Adds all of the rows of `record_batch` to the index, as one batch of operations.
+///
+/// Columns are matched to the fields of the schema by name. Columns without a matching
+/// field are ignored. An error is returned if a matching column has an unsupported type.
+///
+/// Returns the opstamp of the last document added.
+pub fn add_record_batch(
+    index_writer: &IndexWriter<ArrowRow>,
+    record_batch: &RecordBatch,
+) -> crate::Result<Opstamp> {
+    let schema = index_writer.index().schema();
+    let mut columns = Vec::new();
+    for (arrow_field, array) in record_batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(record_batch.columns())
+    {
+        let Ok(field) = schema.get_field(arrow_field.name()) else {
+            continue;
+        };
+        if !is_supported_leaf_type(leaf_type(arrow_field.data_type())) {
+            return Err(unsupported_type_error(
+                arrow_field.name(),
+                arrow_field.data_type(),
+            ));
+        }
+        columns.push((field, array.clone()));
+    }
+    let columns: Arc<[(Field, ArrayRef)]> = columns.into();
+    index_writer.run((0..record_batch.num_rows()).map(|row| {
+        UserOperation::Add(ArrowRow {
+            columns: columns.clone(),
+            row,
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::builder::{ListBuilder, StringBuilder};
+    use arrow_array::{
+        BooleanArray, Float64Array, Int64Array, StringArray, StructArray,
+        TimestampMillisecondArray, UInt32Array,
+    };
+    use arrow_schema::Field as ArrowField;
+
+    use super::*;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{QueryParser, TermQuery};
+    use crate::schema::IndexRecordOption;
+    use crate::{Index, TantivyDocument, Term};
+
+    fn test_batch() -> RecordBatch {
+        let mut tags_builder = ListBuilder::new(StringBuilder::new());
+        tags_builder.values().append_value("red");
+        tags_builder.values().append_value("blue");
+        tags_builder.append(true);
+        tags_builder.append(false);
+        tags_builder.values().append_value("green");
+        tags_builder.append(true);
+        RecordBatch::try_from_iter(vec![
+            (
+                "title",
+                Arc::new(StringArray::from(vec![
+                    Some("hello world"),
+                    None,
+                    Some("hello"),
+                ])) as ArrayRef,
+            ),
+            (
+                "count",
+                Arc::new(Int64Array::from(vec![-1, 2, 3])) as ArrayRef,
+            ),
+            (
+                "size",
+                Arc::new(UInt32Array::from(vec![10, 20, 30])) as ArrayRef,
+            ),
+            (
+                "score",
+                Arc::new(Float64Array::from(vec![0.5, 1.5, 2.5])) as ArrayRef,
+            ),
+            (
+                "active",
+                Arc::new(BooleanArray::from(vec![true, false, true])) as ArrayRef,
+            ),
+            (
+                "timestamp",
+                Arc::new(TimestampMillisecondArray::from(vec![1_000, 2_000, 3_000])) as ArrayRef,
+            ),
+            ("tags", Arc::new(tags_builder.finish()) as ArrayRef),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_index_record_batch() -> crate::Result<()> {
+        let batch = test_batch();
+        let schema = schema_from_arrow(&batch.schema())?;
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter<ArrowRow> = index.writer_for_tests()?;
+        add_record_batch(&index_writer, &batch)?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let title = schema.get_field("title")?;
+        let hello_query = TermQuery::new(
+            Term::from_field_text(title, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&hello_query, &Count)?, 2);
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(count("count:[2 TO 5]")?, 2);
+        assert_eq!(count("size:[15 TO 40]")?, 2);
+        assert_eq!(count("active:true")?, 2);
+        assert_eq!(count("tags:red AND tags:blue")?, 1);
+        assert_eq!(count("tags:green")?, 1);
+
+        let top_docs = searcher.search(&hello_query, &TopDocs::with_limit(1))?;
+        let doc: TantivyDocument = searcher.doc(top_docs[0].1)?;
+        let timestamp = schema.get_field("timestamp")?;
+        assert!(doc.get_first(timestamp).unwrap().as_datetime().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_arrow_type() -> crate::Result<()> {
+        let struct_array = StructArray::from(vec![(
+            Arc::new(ArrowField::new("a", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![1])) as ArrayRef,
+        )]);
+        let batch =
+            RecordBatch::try_from_iter(vec![("nested", Arc::new(struct_array) as ArrayRef)])
+                .unwrap();
+        assert!(matches!(
+            schema_from_arrow(&batch.schema()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        // Columns without a matching field are ignored.
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter<ArrowRow> = index.writer_for_tests()?;
+        add_record_batch(&index_writer, &batch)?;
+        index_writer.commit()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        Ok(())
+    }
+}
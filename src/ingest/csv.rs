@@ -0,0 +1,180 @@
+//! Indexing of CSV data.
+//!
+//! The header row gives the field names. Empty cells are ignored.
+//! The functions below take a [`csv::Reader`], so that the delimiter, quoting,
+//! etc. can be configured with a [`csv::ReaderBuilder`].
+
+use std::io::Read;
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::jsonl::json_object_to_doc;
+use super::schema_inference::{InferredType, SchemaInference};
+use super::{IngestOptions, IngestReport, SchemaInferenceOptions};
+use crate::schema::{FieldType, Schema};
+use crate::{IndexWriter, TantivyError};
+
+fn csv_error(err: csv::Error) -> TantivyError {
+    TantivyError::InvalidArgument(format!("Failed to read CSV data: {err}"))
+}
+
+/// Infers a schema from the header and the first records of `reader`.
+///
+/// Invalid records are ignored.
+pub fn infer_csv_schema<R: Read>(
+    mut reader: csv::Reader<R>,
+    options: SchemaInferenceOptions,
+) -> crate::Result<Schema> {
+    let headers = reader.headers().map_err(csv_error)?.clone();
+    let mut schema_inference = SchemaInference::default();
+    for header in &headers {
+        schema_inference.record_field(header);
+    }
+    for record in reader
+        .records()
+        .take(options.max_num_records())
+        .filter_map(Result::ok)
+    {
+        for (header, cell) in headers.iter().zip(record.iter()) {
+            if !cell.is_empty() {
+                schema_inference.record_value(header, InferredType::from_text(cell))?;
+            }
+        }
+    }
+    Ok(schema_inference.build(&options))
+}
+
+/// Converts a cell to the JSON value expected for a field of type `field_type`.
+fn cell_to_json(field_type: Option<&FieldType>, cell: &str) -> JsonValue {
+    if let Some(FieldType::U64(_) | FieldType::I64(_) | FieldType::F64(_) | FieldType::Bool(_)) =
+        field_type
+    {
+        if let Ok(json_value @ (JsonValue::Number(_) | JsonValue::Bool(_))) =
+            serde_json::from_str(cell)
+        {
+            return json_value;
+        }
+    }
+    JsonValue::String(cell.to_string())
+}
+
+/// Indexes the records of `reader`.
+///
+/// The documents are not committed.
+pub fn index_csv<R: Read>(
+    index_writer: &IndexWriter,
+    mut reader: csv::Reader<R>,
+    options: IngestOptions,
+) -> crate::Result<IngestReport> {
+    let schema = index_writer.index().schema();
+    let headers = reader.headers().map_err(csv_error)?.clone();
+    let field_types: Vec<Option<&FieldType>> = headers
+        .iter()
+        .map(|header| {
+            let field = schema.get_field(header).ok()?;
+            Some(schema.get_field_entry(field).field_type())
+        })
+        .collect();
+    let mut report = IngestReport::default();
+    for (record_ord, record_res) in reader.records().enumerate() {
+        let record_num = record_ord as u64 + 1;
+        let doc_res = record_res
+            .map_err(|err| format!("Invalid CSV record: {err}"))
+            .and_then(|record| {
+                let mut json_obj = Map::new();
+                for ((header, field_type), cell) in
+                    headers.iter().zip(&field_types).zip(record.iter())
+                {
+                    if !cell.is_empty() {
+                        json_obj.insert(header.to_string(), cell_to_json(*field_type, cell));
+                    }
+                }
+                json_object_to_doc(&schema, json_obj, &options)
+            });
+        match doc_res {
+            Ok(doc) => {
+                index_writer.add_document(doc)?;
+                report.num_docs_indexed += 1;
+            }
+            Err(message) => report.record_error(options.on_error(), record_num, message)?,
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Count;
+    use crate::ingest::OnError;
+    use crate::query::QueryParser;
+    use crate::schema::Type;
+    use crate::Index;
+
+    const CSV: &str = "title,count,active,ts
+hello world,3,true,2024-01-02T03:04:05Z
+hello,-1,,2024-01-03T03:04:05Z
+goodbye,oops,false,
+";
+
+    #[test]
+    fn test_infer_csv_schema() -> crate::Result<()> {
+        let schema = infer_csv_schema(
+            csv::Reader::from_reader(CSV.as_bytes()),
+            SchemaInferenceOptions::builder().max_num_records(2).build(),
+        )?;
+        let field_types: Vec<Type> = schema
+            .fields()
+            .map(|(_, field_entry)| field_entry.field_type().value_type())
+            .collect();
+        assert_eq!(
+            field_types,
+            vec![Type::Str, Type::I64, Type::Bool, Type::Date]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_csv() -> crate::Result<()> {
+        let schema = infer_csv_schema(
+            csv::Reader::from_reader(CSV.as_bytes()),
+            SchemaInferenceOptions::builder().max_num_records(2).build(),
+        )?;
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let options = IngestOptions::builder().on_error(OnError::Skip).build();
+        let report = index_csv(
+            &index_writer,
+            csv::Reader::from_reader(CSV.as_bytes()),
+            options,
+        )?;
+        index_writer.commit()?;
+        assert_eq!(report.num_docs_indexed, 2);
+        assert_eq!(report.num_errors, 1);
+        assert_eq!(report.errors[0].record_num, 3);
+
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(count("title:hello")?, 2);
+        assert_eq!(count("active:true")?, 1);
+        assert_eq!(count("count:[0 TO 10]")?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_csv_with_delimiter() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", crate::schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        let reader = csv::ReaderBuilder::new()
+            .delimiter(b';')
+            .from_reader("title;other\nhello;1\n".as_bytes());
+        let report = index_csv(&index_writer, reader, IngestOptions::default())?;
+        assert_eq!(report.num_docs_indexed, 1);
+        Ok(())
+    }
+}
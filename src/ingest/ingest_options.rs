@@ -0,0 +1,88 @@
+/// Maximum number of errors kept in an [`IngestReport`].
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/// What to do when a record cannot be indexed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnError {
+    /// Stops the ingestion and returns the error.
+    #[default]
+    Abort,
+    /// Skips the record, and reports the error in the [`IngestReport`].
+    Skip,
+}
+
+#[derive(Clone, Debug, bon::Builder)]
+/// Options of the ingestion of JSONL and CSV data.
+pub struct IngestOptions {
+    #[builder(default)]
+    /// What to do with records that cannot be indexed: invalid records,
+    /// or records with values that cannot be converted to the type of their field.
+    ///
+    /// Values are converted following the options of their field, see for instance
+    /// [`NumericOptions::set_coerce()`](crate::schema::NumericOptions::set_coerce).
+    on_error: OnError,
+    #[builder(default)]
+    /// If true, records with a key that is not a field of the schema are rejected.
+    /// Otherwise, such keys are ignored.
+    reject_unknown_fields: bool,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        IngestOptions::builder().build()
+    }
+}
+
+impl IngestOptions {
+    pub(crate) fn on_error(&self) -> OnError {
+        self.on_error
+    }
+
+    pub(crate) fn reject_unknown_fields(&self) -> bool {
+        self.reject_unknown_fields
+    }
+}
+
+/// Error on a record that could not be indexed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IngestError {
+    /// Position of the record, starting at 1: line number for JSONL, record number for CSV.
+    pub record_num: u64,
+    /// Description of the error.
+    pub message: String,
+}
+
+/// Summary of an ingestion.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IngestReport {
+    /// Number of documents sent to the `IndexWriter`.
+    pub num_docs_indexed: u64,
+    /// Number of records skipped because of an error.
+    pub num_errors: u64,
+    /// The first errors encountered, in order.
+    pub errors: Vec<IngestError>,
+}
+
+impl IngestReport {
+    /// Records an error according to `on_error`.
+    pub(crate) fn record_error(
+        &mut self,
+        on_error: OnError,
+        record_num: u64,
+        message: String,
+    ) -> crate::Result<()> {
+        if on_error == OnError::Abort {
+            return Err(crate::TantivyError::InvalidArgument(format!(
+                "Record {record_num}: {message}"
+            )));
+        }
+        self.num_errors += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(IngestError {
+                record_num,
+                message,
+            });
+        }
+        Ok(())
+    }
+}
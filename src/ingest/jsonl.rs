@@ -0,0 +1,224 @@
+//! Indexing of newline-delimited JSON.
+//!
+//! Each non-empty line is a JSON object mapping field names to values,
+//! or arrays of values for multivalued fields.
+
+use std::io::BufRead;
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::schema_inference::{InferredType, SchemaInference};
+use super::{IngestOptions, IngestReport, SchemaInferenceOptions};
+use crate::schema::Schema;
+use crate::{IndexWriter, TantivyDocument};
+
+fn json_value_type(json_value: &JsonValue) -> Option<InferredType> {
+    match json_value {
+        JsonValue::Null | JsonValue::Array(_) => None,
+        JsonValue::Bool(_) => Some(InferredType::Bool),
+        JsonValue::Number(number) => {
+            if number.is_u64() {
+                Some(InferredType::U64)
+            } else if number.is_i64() {
+                Some(InferredType::I64)
+            } else {
+                Some(InferredType::F64)
+            }
+        }
+        JsonValue::String(text) => match InferredType::from_text(text) {
+            InferredType::Date => Some(InferredType::Date),
+            _ => Some(InferredType::Str),
+        },
+        JsonValue::Object(_) => Some(InferredType::Json),
+    }
+}
+
+/// Infers a schema from the first records of `reader`.
+///
+/// Invalid lines are ignored.
+pub fn infer_jsonl_schema<R: BufRead>(
+    reader: R,
+    options: SchemaInferenceOptions,
+) -> crate::Result<Schema> {
+    let mut schema_inference = SchemaInference::default();
+    let json_objs = reader
+        .lines()
+        .filter(|line_res| {
+            line_res
+                .as_ref()
+                .map(|line| !line.trim().is_empty())
+                .unwrap_or(true)
+        })
+        .take(options.max_num_records());
+    for line_res in json_objs {
+        let Ok(json_obj) = serde_json::from_str::<Map<String, JsonValue>>(&line_res?) else {
+            continue;
+        };
+        for (field_name, json_value) in &json_obj {
+            schema_inference.record_field(field_name);
+            let json_values = match json_value {
+                JsonValue::Array(json_items) => json_items.as_slice(),
+                _ => std::slice::from_ref(json_value),
+            };
+            for inferred_type in json_values.iter().filter_map(json_value_type) {
+                schema_inference.record_value(field_name, inferred_type)?;
+            }
+        }
+    }
+    Ok(schema_inference.build(&options))
+}
+
+/// Builds a document from a JSON object, ignoring `null` values.
+pub(crate) fn json_object_to_doc(
+    schema: &Schema,
+    mut json_obj: Map<String, JsonValue>,
+    options: &IngestOptions,
+) -> Result<TantivyDocument, String> {
+    if options.reject_unknown_fields() {
+        if let Some(field_name) = json_obj
+            .keys()
+            .find(|field_name| schema.get_field(field_name).is_err())
+        {
+            return Err(format!("Unknown field `{field_name}`"));
+        }
+    }
+    json_obj.retain(|_, json_value| !json_value.is_null());
+    for json_value in json_obj.values_mut() {
+        if let JsonValue::Array(json_items) = json_value {
+            json_items.retain(|json_item| !json_item.is_null());
+        }
+    }
+    TantivyDocument::from_json_object(schema, json_obj).map_err(|err| err.to_string())
+}
+
+/// Indexes the records of `reader`, one JSON object per line.
+///
+/// The documents are not committed.
+pub fn index_jsonl<R: BufRead>(
+    index_writer: &IndexWriter,
+    reader: R,
+    options: IngestOptions,
+) -> crate::Result<IngestReport> {
+    let schema = index_writer.index().schema();
+    let mut report = IngestReport::default();
+    for (line_ord, line_res) in reader.lines().enumerate() {
+        let line = line_res?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_num = line_ord as u64 + 1;
+        let doc_res = serde_json::from_str::<Map<String, JsonValue>>(&line)
+            .map_err(|err| format!("Invalid JSON object: {err}"))
+            .and_then(|json_obj| json_object_to_doc(&schema, json_obj, &options));
+        match doc_res {
+            Ok(doc) => {
+                index_writer.add_document(doc)?;
+                report.num_docs_indexed += 1;
+            }
+            Err(message) => report.record_error(options.on_error(), line_num, message)?,
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Count;
+    use crate::ingest::{IngestError, OnError};
+    use crate::query::QueryParser;
+    use crate::schema::Type;
+    use crate::{Index, TantivyError};
+
+    const JSONL: &str = r#"{"title": "hello world", "count": 3, "tags": ["a", "b"]}
+{"title": "hello", "count": -1, "ts": "2024-01-02T03:04:05Z", "attrs": {"k": "v"}}
+
+{"title": null, "count": 2.5}
+"#;
+
+    #[test]
+    fn test_infer_jsonl_schema() -> crate::Result<()> {
+        let schema = infer_jsonl_schema(JSONL.as_bytes(), SchemaInferenceOptions::default())?;
+        let field_type = |name: &str| {
+            schema
+                .get_field_entry(schema.get_field(name).unwrap())
+                .field_type()
+                .value_type()
+        };
+        assert_eq!(field_type("title"), Type::Str);
+        assert_eq!(field_type("count"), Type::F64);
+        assert_eq!(field_type("tags"), Type::Str);
+        assert_eq!(field_type("ts"), Type::Date);
+        assert_eq!(field_type("attrs"), Type::Json);
+
+        let options = SchemaInferenceOptions::builder().max_num_records(1).build();
+        let schema = infer_jsonl_schema(JSONL.as_bytes(), options)?;
+        assert!(schema.get_field("ts").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_jsonl() -> crate::Result<()> {
+        let schema = infer_jsonl_schema(JSONL.as_bytes(), SchemaInferenceOptions::default())?;
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let report = index_jsonl(&index_writer, JSONL.as_bytes(), IngestOptions::default())?;
+        index_writer.commit()?;
+        assert_eq!(report.num_docs_indexed, 3);
+        assert_eq!(report.num_errors, 0);
+
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(count("title:hello")?, 2);
+        assert_eq!(count("tags:b")?, 1);
+        assert_eq!(count("attrs.k:v")?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_jsonl_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("count", crate::schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        let jsonl = "{\"count\": 1}\nnot json\n{\"count\": \"2\"}\n{\"count\": 3, \"other\": 1}\n";
+
+        assert!(matches!(
+            index_jsonl(&index_writer, jsonl.as_bytes(), IngestOptions::default()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        let options = IngestOptions::builder()
+            .on_error(OnError::Skip)
+            .reject_unknown_fields(true)
+            .build();
+        let report = index_jsonl(&index_writer, jsonl.as_bytes(), options)?;
+        assert_eq!(report.num_docs_indexed, 1);
+        assert_eq!(report.num_errors, 3);
+        let record_nums: Vec<u64> = report
+            .errors
+            .iter()
+            .map(|error: &IngestError| error.record_num)
+            .collect();
+        assert_eq!(record_nums, vec![2, 3, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_jsonl_coerce() -> crate::Result<()> {
+        let jsonl = "{\"count\": 1}\n{\"count\": \"2\"}\n";
+        let options = SchemaInferenceOptions::builder()
+            .max_num_records(1)
+            .coerce(true)
+            .build();
+        let schema = infer_jsonl_schema(jsonl.as_bytes(), options)?;
+        let index = Index::create_in_ram(schema);
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        let report = index_jsonl(&index_writer, jsonl.as_bytes(), IngestOptions::default())?;
+        assert_eq!(report.num_docs_indexed, 2);
+        Ok(())
+    }
+}
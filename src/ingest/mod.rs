@@ -0,0 +1,27 @@
+//! Helpers to bulk-index data coming from other formats.
+//!
+//! - newline-delimited JSON, see [`index_jsonl`].
+//!
+//! Other formats live behind their own feature flag:
+//! - `csv`: indexing of CSV data, see `index_csv`.
+//! - `arrow`: indexing of Arrow `RecordBatch`es, see the `arrow` module.
+//! - `parquet`: indexing of Parquet files, see the `parquet` module.
+//!
+//! The schema can be given, or inferred from a sample of the records with
+//! [`infer_jsonl_schema`] or `infer_csv_schema`.
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "csv")]
+mod csv;
+mod ingest_options;
+mod jsonl;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+mod schema_inference;
+
+#[cfg(feature = "csv")]
+pub use self::csv::{index_csv, infer_csv_schema};
+pub use self::ingest_options::{IngestError, IngestOptions, IngestReport, OnError};
+pub use self::jsonl::{index_jsonl, infer_jsonl_schema};
+pub use self::schema_inference::SchemaInferenceOptions;
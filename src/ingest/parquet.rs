@@ -0,0 +1,247 @@
+//! Indexing of Parquet files.
+//!
+//! Row groups are decoded to Arrow `RecordBatch`es one batch at a time, and indexed
+//! with [`add_record_batch`]. Only the columns matching a field of the schema
+//! are decoded.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow_array::{BooleanArray, RecordBatch};
+use parquet::arrow::arrow_reader::{ArrowPredicateFn, ParquetRecordBatchReaderBuilder, RowFilter};
+use parquet::arrow::ProjectionMask;
+use parquet::errors::ParquetError;
+use parquet::file::reader::ChunkReader;
+
+use crate::ingest::arrow::{add_record_batch, ArrowRow};
+use crate::{IndexWriter, TantivyError};
+
+/// Selects the rows of a Parquet file to index.
+///
+/// The predicate receives batches made of the `columns` it declares, and returns
+/// for each row whether it should be indexed. Only the rows passing the predicate
+/// are decoded for the other columns.
+#[derive(Clone)]
+pub struct ParquetRowPredicate {
+    columns: Vec<String>,
+    predicate: Arc<dyn Fn(&RecordBatch) -> BooleanArray + Send + Sync>,
+}
+
+impl ParquetRowPredicate {
+    /// Creates a predicate reading the columns named `columns`.
+    pub fn new<F>(columns: Vec<String>, predicate: F) -> Self
+    where F: Fn(&RecordBatch) -> BooleanArray + Send + Sync + 'static {
+        ParquetRowPredicate {
+            columns,
+            predicate: Arc::new(predicate),
+        }
+    }
+}
+
+#[derive(Clone, bon::Builder)]
+/// Options of [`index_parquet`].
+pub struct ParquetIngestOptions {
+    /// Names of the columns to index.
+    ///
+    /// By default, all of the columns matching a field of the schema are indexed.
+    columns: Option<Vec<String>>,
+    /// Ordinals of the row groups to index. By default, all of the row groups are indexed.
+    row_groups: Option<Vec<usize>>,
+    /// Selects the rows to index. By default, all of the rows are indexed.
+    row_predicate: Option<ParquetRowPredicate>,
+    #[builder(default = 8_192)]
+    /// Number of rows decoded and sent to the `IndexWriter` at once.
+    batch_size: usize,
+}
+
+impl Default for ParquetIngestOptions {
+    fn default() -> Self {
+        ParquetIngestOptions::builder().build()
+    }
+}
+
+fn parquet_error(err: ParquetError) -> TantivyError {
+    TantivyError::InvalidArgument(format!("Failed to read Parquet data: {err}"))
+}
+
+/// Returns the ordinals of the root columns named `column_names`.
+fn root_column_ords<'a>(
+    builder: &ParquetRecordBatchReaderBuilder<impl ChunkReader>,
+    column_names: impl IntoIterator<Item = &'a str>,
+) -> crate::Result<Vec<usize>> {
+    column_names
+        .into_iter()
+        .map(|column_name| {
+            builder.schema().index_of(column_name).map_err(|_| {
+                TantivyError::FieldNotFound(format!("Parquet column `{column_name}` not found"))
+            })
+        })
+        .collect()
+}
+
+/// Indexes the rows of a Parquet file, and returns the number of rows indexed.
+///
+/// `reader` is typically a [`std::fs::File`]. Columns are matched to the fields of
+/// the schema by name, see [`add_record_batch`]. The documents are not committed.
+pub fn index_parquet<R: ChunkReader + 'static>(
+    index_writer: &IndexWriter<ArrowRow>,
+    reader: R,
+    options: ParquetIngestOptions,
+) -> crate::Result<u64> {
+    let schema = index_writer.index().schema();
+    let mut builder = ParquetRecordBatchReaderBuilder::try_new(reader).map_err(parquet_error)?;
+    let column_names: Vec<String> = if let Some(columns) = options.columns {
+        columns
+    } else {
+        builder
+            .schema()
+            .fields()
+            .iter()
+            .map(|arrow_field| arrow_field.name().clone())
+            .filter(|column_name| schema.get_field(column_name).is_ok())
+            .collect()
+    };
+    let column_ords: HashSet<usize> =
+        root_column_ords(&builder, column_names.iter().map(String::as_str))?
+            .into_iter()
+            .collect();
+    let projection = ProjectionMask::roots(builder.parquet_schema(), column_ords);
+    if let Some(row_predicate) = options.row_predicate {
+        let predicate_column_ords =
+            root_column_ords(&builder, row_predicate.columns.iter().map(String::as_str))?;
+        let predicate_projection =
+            ProjectionMask::roots(builder.parquet_schema(), predicate_column_ords);
+        let predicate = row_predicate.predicate;
+        let arrow_predicate = ArrowPredicateFn::new(predicate_projection, move |record_batch| {
+            Ok(predicate(&record_batch))
+        });
+        builder = builder.with_row_filter(RowFilter::new(vec![Box::new(arrow_predicate)]));
+    }
+    if let Some(row_groups) = options.row_groups {
+        builder = builder.with_row_groups(row_groups);
+    }
+    let record_batch_reader = builder
+        .with_projection(projection)
+        .with_batch_size(options.batch_size)
+        .build()
+        .map_err(parquet_error)?;
+    let mut num_rows = 0u64;
+    for record_batch_res in record_batch_reader {
+        let record_batch = record_batch_res.map_err(|err| {
+            TantivyError::InvalidArgument(format!("Failed to read Parquet data: {err}"))
+        })?;
+        num_rows += record_batch.num_rows() as u64;
+        add_record_batch(index_writer, &record_batch)?;
+    }
+    Ok(num_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use arrow_array::{ArrayRef, Int64Array, StringArray};
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+
+    use super::*;
+    use crate::collector::Count;
+    use crate::ingest::arrow::schema_from_arrow;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::IndexRecordOption;
+    use crate::{Index, Term};
+
+    fn write_parquet_file() -> File {
+        let levels: Vec<&str> = (0..100)
+            .map(|i| if i % 10 == 0 { "error" } else { "info" })
+            .collect();
+        let record_batch = RecordBatch::try_from_iter(vec![
+            ("level", Arc::new(StringArray::from(levels)) as ArrayRef),
+            (
+                "code",
+                Arc::new(Int64Array::from((0..100).collect::<Vec<i64>>())) as ArrayRef,
+            ),
+            (
+                "message",
+                Arc::new(StringArray::from(vec!["disk full"; 100])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let mut file = tempfile::tempfile().unwrap();
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(30)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(&mut file, record_batch.schema(), Some(properties)).unwrap();
+        writer.write(&record_batch).unwrap();
+        writer.close().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_index_parquet() -> crate::Result<()> {
+        let file = write_parquet_file();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file.try_clone()?).unwrap();
+        let index = Index::create_in_ram(schema_from_arrow(builder.schema())?);
+        let mut index_writer: IndexWriter<ArrowRow> = index.writer_for_tests()?;
+        let num_rows = index_parquet(&index_writer, file, ParquetIngestOptions::default())?;
+        index_writer.commit()?;
+        assert_eq!(num_rows, 100);
+        assert_eq!(index.reader()?.searcher().num_docs(), 100);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_parquet_with_projection_and_predicate() -> crate::Result<()> {
+        let file = write_parquet_file();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file.try_clone()?).unwrap();
+        let schema = schema_from_arrow(builder.schema())?;
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter<ArrowRow> = index.writer_for_tests()?;
+        let row_predicate = ParquetRowPredicate::new(vec!["level".to_string()], |record_batch| {
+            let levels = record_batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap();
+            levels
+                .iter()
+                .map(|level| Some(level == Some("error")))
+                .collect()
+        });
+        let options = ParquetIngestOptions::builder()
+            .columns(vec!["level".to_string(), "code".to_string()])
+            .row_groups(vec![0, 1])
+            .row_predicate(row_predicate)
+            .build();
+        let num_rows = index_parquet(&index_writer, file, options)?;
+        index_writer.commit()?;
+        // Rows 0, 10, ..., 50 are in the first two row groups.
+        assert_eq!(num_rows, 6);
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 6);
+        let message = schema.get_field("message")?;
+        let message_query = TermQuery::new(
+            Term::from_field_text(message, "disk"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&message_query, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_parquet_unknown_column() -> crate::Result<()> {
+        let file = write_parquet_file();
+        let index = Index::create_in_ram(crate::schema::Schema::builder().build());
+        let index_writer: IndexWriter<ArrowRow> = index.writer_for_tests()?;
+        let options = ParquetIngestOptions::builder()
+            .columns(vec!["missing".to_string()])
+            .build();
+        assert!(matches!(
+            index_parquet(&index_writer, file, options),
+            Err(TantivyError::FieldNotFound(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::schema::{
+    DateOptions, NumericOptions, Schema, TextOptions, FAST, INDEXED, STORED, TEXT,
+};
+use crate::TantivyError;
+
+#[derive(Clone, Debug, bon::Builder)]
+/// Options of the inference of a schema from JSONL or CSV data.
+pub struct SchemaInferenceOptions {
+    #[builder(default = 1_000)]
+    /// Number of records sampled to infer the schema.
+    max_num_records: usize,
+    #[builder(default)]
+    /// If true, the inferred fields coerce values of the wrong type, e.g. the string `"3"`
+    /// is accepted by a numeric field, and numbers are accepted by text fields.
+    coerce: bool,
+}
+
+impl Default for SchemaInferenceOptions {
+    fn default() -> Self {
+        SchemaInferenceOptions::builder().build()
+    }
+}
+
+impl SchemaInferenceOptions {
+    pub(crate) fn max_num_records(&self) -> usize {
+        self.max_num_records
+    }
+}
+
+/// Type of a field, as inferred from the values seen so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum InferredType {
+    Bool,
+    U64,
+    I64,
+    F64,
+    Date,
+    Str,
+    Json,
+}
+
+impl InferredType {
+    /// Returns the type of a scalar given as text.
+    pub fn from_text(text: &str) -> InferredType {
+        if text == "true" || text == "false" {
+            InferredType::Bool
+        } else if text.parse::<u64>().is_ok() {
+            InferredType::U64
+        } else if text.parse::<i64>().is_ok() {
+            InferredType::I64
+        } else if text.parse::<f64>().is_ok() {
+            InferredType::F64
+        } else if OffsetDateTime::parse(text, &Rfc3339).is_ok() {
+            InferredType::Date
+        } else {
+            InferredType::Str
+        }
+    }
+
+    /// Returns the narrowest type accepting values of both types,
+    /// or `None` if there are none.
+    fn merge(self, other: InferredType) -> Option<InferredType> {
+        use InferredType::*;
+        match (self, other) {
+            _ if self == other => Some(self),
+            (U64, I64) | (I64, U64) => Some(I64),
+            (U64 | I64, F64) | (F64, U64 | I64) => Some(F64),
+            (Json, _) | (_, Json) => None,
+            _ => Some(Str),
+        }
+    }
+}
+
+/// Accumulates the types of the values of each field.
+#[derive(Default)]
+pub(crate) struct SchemaInference {
+    // Field names, in the order in which they were first seen.
+    field_names: Vec<String>,
+    field_types: HashMap<String, Option<InferredType>>,
+}
+
+impl SchemaInference {
+    /// Records a field, without any value.
+    pub fn record_field(&mut self, field_name: &str) {
+        if !self.field_types.contains_key(field_name) {
+            self.field_names.push(field_name.to_string());
+            self.field_types.insert(field_name.to_string(), None);
+        }
+    }
+
+    /// Records a value of a field.
+    pub fn record_value(
+        &mut self,
+        field_name: &str,
+        inferred_type: InferredType,
+    ) -> crate::Result<()> {
+        self.record_field(field_name);
+        let field_type = self.field_types.get_mut(field_name).unwrap();
+        let merged_type = match *field_type {
+            Some(previous_type) => previous_type.merge(inferred_type).ok_or_else(|| {
+                TantivyError::SchemaError(format!(
+                    "Field `{field_name}` mixes objects and values of type {inferred_type:?}"
+                ))
+            })?,
+            None => inferred_type,
+        };
+        *field_type = Some(merged_type);
+        Ok(())
+    }
+
+    /// Builds the schema. Fields without any value are text fields.
+    ///
+    /// Text values are `TEXT | STORED`, objects are `TEXT | STORED` JSON fields, and
+    /// other values are `INDEXED | STORED | FAST`.
+    pub fn build(self, options: &SchemaInferenceOptions) -> Schema {
+        let mut schema_builder = Schema::builder();
+        let numeric_options = || {
+            let numeric_options: NumericOptions = (INDEXED | STORED | FAST).into();
+            if options.coerce {
+                numeric_options.set_coerce()
+            } else {
+                numeric_options
+            }
+        };
+        for field_name in &self.field_names {
+            match self.field_types[field_name].unwrap_or(InferredType::Str) {
+                InferredType::Bool => {
+                    schema_builder.add_bool_field(field_name, numeric_options());
+                }
+                InferredType::U64 => {
+                    schema_builder.add_u64_field(field_name, numeric_options());
+                }
+                InferredType::I64 => {
+                    schema_builder.add_i64_field(field_name, numeric_options());
+                }
+                InferredType::F64 => {
+                    schema_builder.add_f64_field(field_name, numeric_options());
+                }
+                InferredType::Date => {
+                    let date_options: DateOptions = (INDEXED | STORED | FAST).into();
+                    schema_builder.add_date_field(field_name, date_options);
+                }
+                InferredType::Str => {
+                    let text_options: TextOptions = TEXT | STORED;
+                    let text_options = if options.coerce {
+                        text_options.set_coerce()
+                    } else {
+                        text_options
+                    };
+                    schema_builder.add_text_field(field_name, text_options);
+                }
+                InferredType::Json => {
+                    schema_builder.add_json_field(field_name, TEXT | STORED);
+                }
+            }
+        }
+        schema_builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Type;
+
+    #[test]
+    fn test_inferred_type_from_text() {
+        assert_eq!(InferredType::from_text("true"), InferredType::Bool);
+        assert_eq!(InferredType::from_text("12"), InferredType::U64);
+        assert_eq!(InferredType::from_text("-12"), InferredType::I64);
+        assert_eq!(InferredType::from_text("1.5"), InferredType::F64);
+        assert_eq!(
+            InferredType::from_text("2024-01-02T03:04:05Z"),
+            InferredType::Date
+        );
+        assert_eq!(InferredType::from_text("hello"), InferredType::Str);
+    }
+
+    #[test]
+    fn test_schema_inference() -> crate::Result<()> {
+        let mut schema_inference = SchemaInference::default();
+        schema_inference.record_value("count", InferredType::U64)?;
+        schema_inference.record_value("count", InferredType::I64)?;
+        schema_inference.record_value("score", InferredType::U64)?;
+        schema_inference.record_value("score", InferredType::F64)?;
+        schema_inference.record_value("misc", InferredType::Bool)?;
+        schema_inference.record_value("misc", InferredType::Date)?;
+        schema_inference.record_field("empty");
+        schema_inference.record_value("attributes", InferredType::Json)?;
+        assert!(schema_inference
+            .record_value("attributes", InferredType::Str)
+            .is_err());
+        let schema = schema_inference.build(&SchemaInferenceOptions::default());
+        let field_types: Vec<(&str, Type)> = schema
+            .fields()
+            .map(|(_, field_entry)| (field_entry.name(), field_entry.field_type().value_type()))
+            .collect();
+        assert_eq!(
+            field_types,
+            vec![
+                ("count", Type::I64),
+                ("score", Type::F64),
+                ("misc", Type::Str),
+                ("empty", Type::Str),
+                ("attributes", Type::Json),
+            ]
+        );
+        Ok(())
+    }
+}
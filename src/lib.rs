@@ -184,20 +184,36 @@ pub mod error;
 pub mod tokenizer;
 
 pub mod aggregation;
+pub mod alias;
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod collector;
 pub mod directory;
+pub mod export;
 pub mod fastfield;
 pub mod fieldnorm;
 pub mod index;
+pub mod ingest;
+pub mod ltr;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod points;
 pub mod positions;
 pub mod postings;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
 
 /// Module containing the different query implementations.
 pub mod query;
 pub mod schema;
 pub mod space_usage;
 pub mod store;
+pub mod suggest;
+pub mod term_vectors;
 pub mod termdict;
+#[cfg(feature = "mmap")]
+pub mod time_partition;
+pub mod vector;
 
 mod docset;
 mod reader;
@@ -205,7 +221,10 @@ mod reader;
 #[cfg(test)]
 mod compat_tests;
 
-pub use self::reader::{IndexReader, IndexReaderBuilder, ReloadPolicy, Warmer};
+pub use self::reader::{
+    IndexReader, IndexReaderBuilder, PointInTimeId, ReloadPolicy, SlowQuery, SlowQueryCallback,
+    SlowQueryLog, SlowQuerySegment, Warmer, WarmupPlan,
+};
 pub mod snippet;
 
 use std::fmt;
@@ -218,11 +237,14 @@ use serde::{Deserialize, Serialize};
 pub use self::docset::{DocSet, COLLECT_BLOCK_BUFFER_LEN, TERMINATED};
 #[doc(hidden)]
 pub use crate::core::json_utils;
-pub use crate::core::{Executor, Searcher, SearcherGeneration};
+pub use crate::core::{
+    Executor, IndexDocAddress, MemoryBudget, MemoryBudgetExceeded, MemoryReservation,
+    MultiSearcher, Searcher, SearcherGeneration,
+};
 pub use crate::directory::Directory;
 pub use crate::index::{
-    Index, IndexBuilder, IndexMeta, IndexSettings, InvertedIndexReader, Order, Segment,
-    SegmentMeta, SegmentReader,
+    DocStoreFamily, Index, IndexBuilder, IndexMeta, IndexSettings, InvertedIndexReader, Order,
+    Segment, SegmentMeta, SegmentReader,
 };
 pub use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
 pub use crate::schema::{Document, TantivyDocument, Term};
@@ -285,6 +307,13 @@ pub mod merge_policy {
     };
 }
 
+/// Defines the order in which tantivy runs merges
+pub mod merge_scheduler {
+    pub use crate::indexer::{
+        DefaultMergeScheduler, FifoMergeScheduler, MergeScheduler, SmallestMergeFirstScheduler,
+    };
+}
+
 /// A `u32` identifying a document within a segment.
 /// Documents have their `DocId` assigned incrementally,
 /// as they are added in the segment.
@@ -0,0 +1,245 @@
+use std::collections::{BTreeSet, HashMap};
+
+use columnar::Column;
+
+use crate::fieldnorm::FieldNormReader;
+use crate::postings::{Postings, SegmentPostings};
+use crate::query::{min_window, Bm25Weight, EnableScoring, Query, Scorer};
+use crate::schema::{Field, IndexRecordOption};
+use crate::{DocAddress, DocId, DocSet, Score, Searcher, SegmentReader, Term};
+
+/// A feature of a document for a query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// The score of the document for the query, or 0 if the document does not match it.
+    QueryScore,
+    /// The sum of the BM25 scores of the terms of the query on the given field.
+    Bm25(Field),
+    /// The number of distinct terms of the query appearing in the given field.
+    TermMatchCount(Field),
+    /// The number of positions of the smallest window of the given field containing all of the
+    /// terms of the query which appear in the field.
+    ///
+    /// The feature is missing if none of the terms appear in the field, or if the field does not
+    /// record positions.
+    MinWindow(Field),
+    /// The first value of the numerical fast field with the given name, converted to a float.
+    ///
+    /// The feature is missing if the document has no value.
+    FastField(String),
+}
+
+/// `FeatureExtractor` computes named [features](Feature) of documents for a query.
+///
+/// The features of each document are returned as a vector, in the order the features were
+/// added. Missing features are `NaN`.
+#[derive(Clone, Debug, Default)]
+pub struct FeatureExtractor {
+    features: Vec<(String, Feature)>,
+}
+
+/// The postings of a term of the query, within a segment.
+struct TermFeatures {
+    field: Field,
+    postings: SegmentPostings,
+    bm25_weight: Option<Bm25Weight>,
+    needs_positions: bool,
+}
+
+impl FeatureExtractor {
+    /// Adds a feature, computed after the previously added ones.
+    pub fn add_feature(&mut self, name: &str, feature: Feature) {
+        self.features.push((name.to_string(), feature));
+    }
+
+    /// Returns the names of the features, in order.
+    pub fn feature_names(&self) -> Vec<&str> {
+        self.features
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Computes the features of the given documents for the query.
+    ///
+    /// The documents are visited segment by segment, in a single pass over the postings of the
+    /// terms of the query. They do not need to match the query.
+    pub fn extract(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        doc_addresses: &[DocAddress],
+    ) -> crate::Result<Vec<Vec<Score>>> {
+        let feature_fields: BTreeSet<Field> = self
+            .features
+            .iter()
+            .filter_map(|(_, feature)| match feature {
+                Feature::Bm25(field)
+                | Feature::TermMatchCount(field)
+                | Feature::MinWindow(field) => Some(*field),
+                Feature::QueryScore | Feature::FastField(_) => None,
+            })
+            .collect();
+        let mut terms: BTreeSet<&Term> = BTreeSet::new();
+        query.query_terms(&mut |term, _| {
+            if feature_fields.contains(&term.field()) {
+                terms.insert(term);
+            }
+        });
+        let bm25_weights: Vec<Option<Bm25Weight>> = terms
+            .iter()
+            .map(|&term| {
+                if !self.has_feature(&Feature::Bm25(term.field())) {
+                    return Ok(None);
+                }
+                Bm25Weight::for_terms(searcher, std::slice::from_ref(term)).map(Some)
+            })
+            .collect::<crate::Result<_>>()?;
+        let query_weight_opt = if self.has_feature(&Feature::QueryScore) {
+            Some(query.weight(EnableScoring::enabled_from_searcher(searcher))?)
+        } else {
+            None
+        };
+
+        let mut docs_per_segment: HashMap<u32, Vec<(DocId, usize)>> = HashMap::new();
+        for (ord, doc_address) in doc_addresses.iter().enumerate() {
+            docs_per_segment
+                .entry(doc_address.segment_ord)
+                .or_default()
+                .push((doc_address.doc_id, ord));
+        }
+        let mut features = vec![Vec::new(); doc_addresses.len()];
+        for (segment_ord, mut docs) in docs_per_segment {
+            docs.sort_unstable();
+            let segment_reader = searcher.segment_reader(segment_ord);
+            let mut query_scorer_opt = query_weight_opt
+                .as_ref()
+                .map(|query_weight| query_weight.scorer(segment_reader, 1.0))
+                .transpose()?;
+            let mut term_features = Vec::with_capacity(terms.len());
+            for (&term, bm25_weight) in terms.iter().zip(&bm25_weights) {
+                let field = term.field();
+                let needs_positions = self.has_feature(&Feature::MinWindow(field));
+                let option = if needs_positions {
+                    IndexRecordOption::WithFreqsAndPositions
+                } else {
+                    IndexRecordOption::WithFreqs
+                };
+                let postings = segment_reader
+                    .inverted_index(field)?
+                    .read_postings(term, option)?
+                    .unwrap_or_else(SegmentPostings::empty);
+                term_features.push(TermFeatures {
+                    field,
+                    postings,
+                    bm25_weight: bm25_weight.clone(),
+                    needs_positions,
+                });
+            }
+            let mut fieldnorm_readers = HashMap::new();
+            for &field in &feature_fields {
+                let fieldnorm_reader = segment_reader
+                    .fieldnorms_readers()
+                    .get_field(field)?
+                    .unwrap_or_else(|| FieldNormReader::constant(segment_reader.max_doc(), 1));
+                fieldnorm_readers.insert(field, fieldnorm_reader);
+            }
+            let fast_field_columns = self.fast_field_columns(segment_reader)?;
+
+            for (doc, ord) in docs {
+                let query_score = match &mut query_scorer_opt {
+                    Some(query_scorer) => score_doc(query_scorer.as_mut(), doc),
+                    None => 0.0,
+                };
+                let mut positions_per_field: HashMap<Field, Vec<Vec<u32>>> = HashMap::new();
+                for term_feature in &mut term_features {
+                    if term_feature.postings.doc() < doc {
+                        term_feature.postings.seek(doc);
+                    }
+                    if term_feature.needs_positions && term_feature.postings.doc() == doc {
+                        let mut positions = Vec::new();
+                        term_feature.postings.positions(&mut positions);
+                        positions_per_field
+                            .entry(term_feature.field)
+                            .or_default()
+                            .push(positions);
+                    }
+                }
+                let matching_terms = || {
+                    term_features
+                        .iter()
+                        .filter(move |term_feature| term_feature.postings.doc() == doc)
+                };
+                let mut doc_features = Vec::with_capacity(self.features.len());
+                for (_, feature) in &self.features {
+                    let value = match feature {
+                        Feature::QueryScore => query_score,
+                        Feature::Bm25(field) => {
+                            let fieldnorm_id = fieldnorm_readers[field].fieldnorm_id(doc);
+                            matching_terms()
+                                .filter(|term_feature| term_feature.field == *field)
+                                .filter_map(|term_feature| {
+                                    let bm25_weight = term_feature.bm25_weight.as_ref()?;
+                                    Some(
+                                        bm25_weight
+                                            .score(fieldnorm_id, term_feature.postings.term_freq()),
+                                    )
+                                })
+                                .sum()
+                        }
+                        Feature::TermMatchCount(field) => matching_terms()
+                            .filter(|term_feature| term_feature.field == *field)
+                            .count()
+                            as Score,
+                        Feature::MinWindow(field) => positions_per_field
+                            .get(field)
+                            .and_then(|term_positions| min_window(term_positions))
+                            .map(|window| window as Score)
+                            .unwrap_or(Score::NAN),
+                        Feature::FastField(field_name) => fast_field_columns[field_name.as_str()]
+                            .as_ref()
+                            .and_then(|column| column.first(doc))
+                            .map(|value| value as Score)
+                            .unwrap_or(Score::NAN),
+                    };
+                    doc_features.push(value);
+                }
+                features[ord] = doc_features;
+            }
+        }
+        Ok(features)
+    }
+
+    fn has_feature(&self, feature: &Feature) -> bool {
+        self.features
+            .iter()
+            .any(|(_, added_feature)| added_feature == feature)
+    }
+
+    fn fast_field_columns(
+        &self,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<HashMap<&str, Option<Column<f64>>>> {
+        let mut fast_field_columns = HashMap::new();
+        for (_, feature) in &self.features {
+            if let Feature::FastField(field_name) = feature {
+                let column_opt = segment_reader
+                    .fast_fields()
+                    .column_opt_coerced::<f64>(field_name)?;
+                fast_field_columns.insert(field_name.as_str(), column_opt);
+            }
+        }
+        Ok(fast_field_columns)
+    }
+}
+
+fn score_doc(scorer: &mut dyn Scorer, doc: DocId) -> Score {
+    if scorer.doc() < doc {
+        scorer.seek(doc);
+    }
+    if scorer.doc() == doc {
+        scorer.score()
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,134 @@
+//! Feature extraction for learning to rank.
+//!
+//! A learning to rank model reranks the top hits of a query from features describing how well
+//! each of them matches the query. The [`FeatureExtractor`] computes such features for a given
+//! query and a given set of documents, e.g. to build the training set of the model, or to feed
+//! the model when reranking the hits.
+//!
+//! ```rust
+//! use tantivy::collector::TopDocs;
+//! use tantivy::ltr::{Feature, FeatureExtractor};
+//! use tantivy::query::QueryParser;
+//! use tantivy::schema::{Schema, FAST, TEXT};
+//! use tantivy::{doc, DocAddress, Index, IndexWriter};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let popularity = schema_builder.add_u64_field("popularity", FAST);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+//! index_writer.add_document(doc!(title => "The Old Man and the Sea", popularity => 10u64))?;
+//! index_writer.add_document(doc!(title => "The Sea, the Sea", popularity => 3u64))?;
+//! index_writer.commit()?;
+//!
+//! let searcher = index.reader()?.searcher();
+//! let query = QueryParser::for_index(&index, vec![title]).parse_query("old sea")?;
+//! let doc_addresses: Vec<DocAddress> = searcher
+//!     .search(&query, &TopDocs::with_limit(10))?
+//!     .into_iter()
+//!     .map(|(_score, doc_address)| doc_address)
+//!     .collect();
+//!
+//! let mut feature_extractor = FeatureExtractor::default();
+//! feature_extractor.add_feature("score", Feature::QueryScore);
+//! feature_extractor.add_feature("title_matches", Feature::TermMatchCount(title));
+//! feature_extractor.add_feature("popularity", Feature::FastField("popularity".to_string()));
+//! let features = feature_extractor.extract(&searcher, &*query, &doc_addresses)?;
+//! assert_eq!(features.len(), 2);
+//! assert_eq!(features[0][1..], [2.0, 10.0]);
+//! # Ok(())
+//! # }
+//! ```
+
+mod feature_extractor;
+
+pub use self::feature_extractor::{Feature, FeatureExtractor};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{Query, QueryParser};
+    use crate::schema::{Schema, FAST, STRING, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter};
+
+    #[test]
+    fn test_feature_extractor() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(
+            title => "red running shoes",
+            body => "shoes for running in the rain, red or blue",
+            tag => "shoes",
+            price => 49.5,
+        ))?;
+        index_writer.add_document(doc!(title => "blue shoes", body => "blue"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "red hat", body => "a hat", price => 5.0))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let query = QueryParser::for_index(&index, vec![title, body])
+            .parse_query("red shoes running tag:shoes")?;
+        let mut feature_extractor = FeatureExtractor::default();
+        feature_extractor.add_feature("score", Feature::QueryScore);
+        feature_extractor.add_feature("title_bm25", Feature::Bm25(title));
+        feature_extractor.add_feature("body_matches", Feature::TermMatchCount(body));
+        feature_extractor.add_feature("tag_matches", Feature::TermMatchCount(tag));
+        feature_extractor.add_feature("body_window", Feature::MinWindow(body));
+        feature_extractor.add_feature("price", Feature::FastField("price".to_string()));
+        assert_eq!(
+            feature_extractor.feature_names(),
+            [
+                "score",
+                "title_bm25",
+                "body_matches",
+                "tag_matches",
+                "body_window",
+                "price"
+            ]
+        );
+
+        // The doc addresses do not need to be sorted, nor unique.
+        let doc_addresses = [
+            DocAddress::new(1, 0),
+            DocAddress::new(0, 1),
+            DocAddress::new(0, 0),
+            DocAddress::new(0, 1),
+        ];
+        let features = feature_extractor.extract(&searcher, &*query, &doc_addresses)?;
+        assert_eq!(features.len(), 4);
+        for (doc_features, doc_address) in features.iter().zip(doc_addresses) {
+            assert_nearly_equals!(
+                doc_features[0],
+                query.explain(&searcher, doc_address)?.value()
+            );
+        }
+        // Missing features are NaN, which is not equal to itself.
+        assert_eq!(format!("{:?}", features[1]), format!("{:?}", features[3]));
+
+        let red_running_shoes = &features[2];
+        // "shoes for running in the rain, red" spans 7 positions.
+        assert_eq!(red_running_shoes[2..5], [3.0, 1.0, 7.0]);
+        assert_eq!(red_running_shoes[5], 49.5);
+        let blue_shoes = &features[1];
+        assert!(red_running_shoes[1] > blue_shoes[1]);
+        assert_eq!(blue_shoes[2..4], [0.0, 0.0]);
+        // The window and the price of the blue shoes are missing.
+        assert!(blue_shoes[4].is_nan());
+        assert!(blue_shoes[5].is_nan());
+        let red_hat = &features[0];
+        assert_eq!(red_hat[2..4], [0.0, 0.0]);
+        assert!(red_hat[4].is_nan());
+        assert_eq!(red_hat[5], 5.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,798 @@
+//! Registry of counters, gauges and histograms, exposable in the
+//! [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+//!
+//! A [`MetricsRegistry`] can be used on its own to instrument an application, and it knows
+//! how to collect the metrics tantivy already maintains:
+//! - the events of an [`IndexWriter`](crate::IndexWriter), through
+//!   [`MetricsRegistry::index_writer_event_callback()`],
+//! - the snapshots of [`IndexWriterMetrics`], with [`MetricsRegistry::record_index_writer()`],
+//! - the segments and doc store cache of a [`Searcher`], with
+//!   [`MetricsRegistry::record_searcher()`],
+//! - the IO of an [`InstrumentedDirectory`](crate::directory::InstrumentedDirectory), with
+//!   [`MetricsRegistry::record_directory_io()`],
+//! - the number and latency of the searches run with [`MetricsRegistry::search()`].
+//!
+//! ```rust
+//! use tantivy::collector::Count;
+//! use tantivy::metrics::MetricsRegistry;
+//! use tantivy::query::AllQuery;
+//! use tantivy::schema::{Schema, TEXT};
+//! use tantivy::{doc, Index};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let index = Index::create_in_ram(schema_builder.build());
+//!
+//! let registry = MetricsRegistry::default();
+//! let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+//! index_writer.set_event_callback(registry.index_writer_event_callback());
+//! index_writer.add_document(doc!(title => "running shoes"))?;
+//! index_writer.commit()?;
+//! registry.record_index_writer(&index_writer.metrics());
+//!
+//! let searcher = index.reader()?.searcher();
+//! assert_eq!(registry.search(&searcher, &AllQuery, &Count)?, 1);
+//! registry.record_searcher(&searcher);
+//!
+//! let exposition = registry.encode_prometheus();
+//! assert!(exposition.contains("tantivy_searches_total 1\n"));
+//! assert!(exposition.contains("tantivy_indexer_segments_flushed_total 1\n"));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::collector::Collector;
+use crate::directory::{DirectoryIoMetrics, IoMetrics};
+use crate::index::SegmentComponent;
+use crate::indexer::{IndexWriterEvent, IndexWriterEventCallback, IndexWriterMetrics};
+use crate::query::Query;
+use crate::Searcher;
+
+/// Upper bounds, in seconds, of the buckets of the duration histograms recorded by the
+/// [`MetricsRegistry`].
+pub const DEFAULT_DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Monotonically increasing counter.
+#[derive(Clone, Debug, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    /// Increments the counter by one.
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    /// Increments the counter by `value`.
+    pub fn inc_by(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Sets the value of the counter.
+    ///
+    /// This is meant to mirror a counter maintained elsewhere. A value lower than the previous
+    /// one is interpreted by Prometheus as a counter reset.
+    pub fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    /// Returns the value of the counter.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Value that can go up and down.
+#[derive(Clone, Debug, Default)]
+pub struct Gauge(Arc<AtomicU64>);
+
+impl Gauge {
+    /// Sets the value of the gauge.
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the value of the gauge.
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Distribution of observed values, counted in buckets.
+#[derive(Clone, Debug)]
+pub struct Histogram(Arc<HistogramInner>);
+
+#[derive(Debug)]
+struct HistogramInner {
+    bucket_bounds: Vec<f64>,
+    state: Mutex<HistogramState>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct HistogramState {
+    // Number of observations of each bucket, not cumulated.
+    bucket_counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bucket_bounds: &[f64]) -> Histogram {
+        let mut bucket_bounds = bucket_bounds.to_vec();
+        bucket_bounds.retain(|bound| bound.is_finite());
+        bucket_bounds.sort_by(f64::total_cmp);
+        bucket_bounds.dedup();
+        let state = HistogramState {
+            bucket_counts: vec![0; bucket_bounds.len()],
+            count: 0,
+            sum: 0.0,
+        };
+        Histogram(Arc::new(HistogramInner {
+            bucket_bounds,
+            state: Mutex::new(state),
+        }))
+    }
+
+    /// Records a value.
+    pub fn observe(&self, value: f64) {
+        let bucket_ord = self.0.bucket_bounds.partition_point(|&bound| bound < value);
+        let mut state = self.0.state.lock().unwrap();
+        if let Some(bucket_count) = state.bucket_counts.get_mut(bucket_ord) {
+            *bucket_count += 1;
+        }
+        state.count += 1;
+        state.sum += value;
+    }
+
+    /// Records a duration, in seconds.
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(duration.as_secs_f64());
+    }
+
+    /// Returns the number of observations.
+    pub fn count(&self) -> u64 {
+        self.0.state.lock().unwrap().count
+    }
+
+    /// Returns the sum of the observed values.
+    pub fn sum(&self) -> f64 {
+        self.0.state.lock().unwrap().sum
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    fn name(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Metric {
+    Counter(Counter),
+    Gauge(Gauge),
+    Histogram(Histogram),
+}
+
+type Labels = Vec<(String, String)>;
+
+struct MetricFamily {
+    help: String,
+    kind: MetricKind,
+    series: BTreeMap<Labels, Metric>,
+}
+
+/// Registry of named metrics.
+///
+/// A metric is identified by its name and its labels. Registering a metric that already
+/// exists returns a handle to the existing one, so that the handles do not need to be kept
+/// around. Cloning the registry returns a handle to the same metrics.
+#[derive(Clone, Default)]
+pub struct MetricsRegistry {
+    families: Arc<Mutex<BTreeMap<String, MetricFamily>>>,
+}
+
+impl fmt::Debug for MetricsRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let families = self.families.lock().unwrap();
+        f.debug_struct("MetricsRegistry")
+            .field("metrics", &families.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl MetricsRegistry {
+    /// Returns the counter with the given name and labels, registering it if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name or a label name is not a valid Prometheus name, or if the name is
+    /// already registered for a metric of another type.
+    pub fn counter(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Counter {
+        let metric = self.get_or_register(name, help, labels, MetricKind::Counter, || {
+            Metric::Counter(Counter::default())
+        });
+        match metric {
+            Metric::Counter(counter) => counter,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the gauge with the given name and labels, registering it if needed.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`MetricsRegistry::counter()`].
+    pub fn gauge(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Gauge {
+        let metric = self.get_or_register(name, help, labels, MetricKind::Gauge, || {
+            Metric::Gauge(Gauge::default())
+        });
+        match metric {
+            Metric::Gauge(gauge) => gauge,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the histogram with the given name and labels, registering it if needed.
+    ///
+    /// `bucket_bounds` are the upper bounds of the buckets. The `+Inf` bucket is implicit. They
+    /// are ignored if the histogram already exists.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`MetricsRegistry::counter()`].
+    pub fn histogram(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[(&str, &str)],
+        bucket_bounds: &[f64],
+    ) -> Histogram {
+        let metric = self.get_or_register(name, help, labels, MetricKind::Histogram, || {
+            Metric::Histogram(Histogram::new(bucket_bounds))
+        });
+        match metric {
+            Metric::Histogram(histogram) => histogram,
+            _ => unreachable!(),
+        }
+    }
+
+    fn get_or_register(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[(&str, &str)],
+        kind: MetricKind,
+        new_metric: impl FnOnce() -> Metric,
+    ) -> Metric {
+        assert!(is_valid_name(name), "Invalid metric name {name:?}");
+        let mut labels: Labels = labels
+            .iter()
+            .map(|(label_name, label_value)| {
+                assert!(
+                    is_valid_name(label_name) && !label_name.contains(':'),
+                    "Invalid label name {label_name:?}"
+                );
+                (label_name.to_string(), label_value.to_string())
+            })
+            .collect();
+        labels.sort();
+        let mut families = self.families.lock().unwrap();
+        let family = families
+            .entry(name.to_string())
+            .or_insert_with(|| MetricFamily {
+                help: help.to_string(),
+                kind,
+                series: BTreeMap::new(),
+            });
+        assert_eq!(
+            family.kind,
+            kind,
+            "Metric {name:?} is already registered as a {}",
+            family.kind.name()
+        );
+        family
+            .series
+            .entry(labels)
+            .or_insert_with(new_metric)
+            .clone()
+    }
+
+    /// Encodes all of the metrics in the Prometheus text format.
+    pub fn encode_prometheus(&self) -> String {
+        let mut output = String::new();
+        self.write_prometheus(&mut output)
+            .expect("Writing to a String cannot fail");
+        output
+    }
+
+    /// Writes all of the metrics in the Prometheus text format.
+    pub fn write_prometheus(&self, output: &mut dyn Write) -> fmt::Result {
+        let families = self.families.lock().unwrap();
+        for (name, family) in families.iter() {
+            writeln!(output, "# HELP {name} {}", escape_help(&family.help))?;
+            writeln!(output, "# TYPE {name} {}", family.kind.name())?;
+            for (labels, metric) in &family.series {
+                match metric {
+                    Metric::Counter(counter) => {
+                        write_sample(output, name, labels, None, &counter.get().to_string())?;
+                    }
+                    Metric::Gauge(gauge) => {
+                        write_sample(output, name, labels, None, &format_value(gauge.get()))?;
+                    }
+                    Metric::Histogram(histogram) => {
+                        write_histogram(output, name, labels, histogram)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a callback recording the flushes and merges of an
+    /// [`IndexWriter`](crate::IndexWriter), to be passed to
+    /// [`IndexWriter::set_event_callback()`](crate::IndexWriter::set_event_callback).
+    ///
+    /// It records:
+    /// - `tantivy_indexer_segments_flushed_total`, `tantivy_indexer_flushed_docs_total` and
+    ///   the `tantivy_indexer_flush_duration_seconds` histogram,
+    /// - `tantivy_merges_total`, `tantivy_merge_bytes_written_total` and the
+    ///   `tantivy_merge_duration_seconds` histogram.
+    pub fn index_writer_event_callback(&self) -> IndexWriterEventCallback {
+        let segments_flushed = self.counter(
+            "tantivy_indexer_segments_flushed_total",
+            "Number of segments flushed by the indexing workers.",
+            &[],
+        );
+        let flushed_docs = self.counter(
+            "tantivy_indexer_flushed_docs_total",
+            "Number of documents of the segments flushed by the indexing workers.",
+            &[],
+        );
+        let flush_duration = self.histogram(
+            "tantivy_indexer_flush_duration_seconds",
+            "Time spent serializing the segments flushed by the indexing workers.",
+            &[],
+            DEFAULT_DURATION_BUCKETS,
+        );
+        let merges = self.counter(
+            "tantivy_merges_total",
+            "Number of merges that completed successfully.",
+            &[],
+        );
+        let merge_bytes_written = self.counter(
+            "tantivy_merge_bytes_written_total",
+            "Number of bytes of the segments written by merges.",
+            &[],
+        );
+        let merge_duration = self.histogram(
+            "tantivy_merge_duration_seconds",
+            "Time spent merging segments.",
+            &[],
+            DEFAULT_DURATION_BUCKETS,
+        );
+        Arc::new(move |event: &IndexWriterEvent| match event {
+            IndexWriterEvent::SegmentFlushed {
+                num_docs, duration, ..
+            } => {
+                segments_flushed.inc();
+                flushed_docs.inc_by(u64::from(*num_docs));
+                flush_duration.observe_duration(*duration);
+            }
+            IndexWriterEvent::MergeCompleted {
+                num_bytes,
+                duration,
+                ..
+            } => {
+                merges.inc();
+                merge_bytes_written.inc_by(*num_bytes);
+                merge_duration.observe_duration(*duration);
+            }
+        })
+    }
+
+    /// Records a snapshot of the metrics of an [`IndexWriter`](crate::IndexWriter), as
+    /// `tantivy_indexer_docs_indexed_total`, `tantivy_indexer_memory_usage_bytes` and
+    /// `tantivy_merges_pending`.
+    pub fn record_index_writer(&self, metrics: &IndexWriterMetrics) {
+        self.counter(
+            "tantivy_indexer_docs_indexed_total",
+            "Number of documents processed by the indexing workers.",
+            &[],
+        )
+        .set(metrics.num_docs_indexed);
+        self.gauge(
+            "tantivy_indexer_memory_usage_bytes",
+            "Memory used by the indexing workers to buffer documents.",
+            &[],
+        )
+        .set(metrics.memory_usage_bytes as f64);
+        self.gauge(
+            "tantivy_merges_pending",
+            "Number of merges that are scheduled or running.",
+            &[],
+        )
+        .set(metrics.num_pending_merges as f64);
+    }
+
+    /// Records the number of segments and documents of a [`Searcher`], and the stats of its
+    /// doc store cache, as `tantivy_searcher_segments`, `tantivy_searcher_docs`,
+    /// `tantivy_doc_store_cache_entries`, `tantivy_doc_store_cache_hits_total` and
+    /// `tantivy_doc_store_cache_misses_total`.
+    ///
+    /// The doc store cache is created with the [`IndexReader`](crate::IndexReader), so its
+    /// counters reset when a new reader is opened.
+    pub fn record_searcher(&self, searcher: &Searcher) {
+        self.gauge(
+            "tantivy_searcher_segments",
+            "Number of segments of the searcher.",
+            &[],
+        )
+        .set(searcher.segment_readers().len() as f64);
+        self.gauge(
+            "tantivy_searcher_docs",
+            "Number of documents of the searcher, excluding deleted documents.",
+            &[],
+        )
+        .set(searcher.num_docs() as f64);
+        let cache_stats = searcher.doc_store_cache_stats();
+        self.gauge(
+            "tantivy_doc_store_cache_entries",
+            "Number of blocks in the doc store cache.",
+            &[],
+        )
+        .set(cache_stats.num_entries as f64);
+        self.counter(
+            "tantivy_doc_store_cache_hits_total",
+            "Number of doc store cache hits.",
+            &[],
+        )
+        .set(cache_stats.cache_hits as u64);
+        self.counter(
+            "tantivy_doc_store_cache_misses_total",
+            "Number of doc store cache misses.",
+            &[],
+        )
+        .set(cache_stats.cache_misses as u64);
+    }
+
+    /// Records the IO metrics of an
+    /// [`InstrumentedDirectory`](crate::directory::InstrumentedDirectory), labelled with the
+    /// `component` the files belong to, or `other` for the files that do not belong to a
+    /// segment.
+    pub fn record_directory_io(&self, metrics: &DirectoryIoMetrics) {
+        for &component in SegmentComponent::iterator() {
+            self.record_io(component_label(component), &metrics.component(component));
+        }
+        self.record_io("other", &metrics.other());
+    }
+
+    fn record_io(&self, component: &str, io_metrics: &IoMetrics) {
+        let labels = [("component", component)];
+        self.counter(
+            "tantivy_directory_reads_total",
+            "Number of reads of the directory.",
+            &labels,
+        )
+        .set(io_metrics.num_reads);
+        self.counter(
+            "tantivy_directory_read_bytes_total",
+            "Number of bytes read from the directory.",
+            &labels,
+        )
+        .set(io_metrics.bytes_read);
+        self.gauge(
+            "tantivy_directory_read_duration_seconds_total",
+            "Cumulated duration of the reads of the directory.",
+            &labels,
+        )
+        .set(io_metrics.read_latency.as_secs_f64());
+        self.counter(
+            "tantivy_directory_written_bytes_total",
+            "Number of bytes written to the directory.",
+            &labels,
+        )
+        .set(io_metrics.bytes_written);
+        self.gauge(
+            "tantivy_directory_open_file_handles",
+            "Number of file handles of the directory currently open.",
+            &labels,
+        )
+        .set(io_metrics.num_open_file_handles as f64);
+    }
+
+    /// Runs a search with [`Searcher::search()`], recording `tantivy_searches_total`,
+    /// `tantivy_search_errors_total` and the `tantivy_search_duration_seconds` histogram.
+    pub fn search<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        let start = Instant::now();
+        let result = searcher.search(query, collector);
+        self.histogram(
+            "tantivy_search_duration_seconds",
+            "Time spent running searches.",
+            &[],
+            DEFAULT_DURATION_BUCKETS,
+        )
+        .observe_duration(start.elapsed());
+        self.counter("tantivy_searches_total", "Number of searches run.", &[])
+            .inc();
+        let errors = self.counter(
+            "tantivy_search_errors_total",
+            "Number of searches that returned an error.",
+            &[],
+        );
+        if result.is_err() {
+            errors.inc();
+        }
+        result
+    }
+}
+
+fn component_label(component: SegmentComponent) -> &'static str {
+    match component {
+        SegmentComponent::Postings => "postings",
+        SegmentComponent::Positions => "positions",
+        SegmentComponent::FastFields => "fast_fields",
+        SegmentComponent::FieldNorms => "field_norms",
+        SegmentComponent::Points => "points",
+        SegmentComponent::Vectors => "vectors",
+        SegmentComponent::TermVectors => "term_vectors",
+        SegmentComponent::Terms => "terms",
+        SegmentComponent::Store => "store",
+        SegmentComponent::TempStore => "temp_store",
+        SegmentComponent::Delete => "delete",
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first_char) = chars.next() else {
+        return false;
+    };
+    (first_char.is_ascii_alphabetic() || first_char == '_' || first_char == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn escape_label_value(label_value: &str) -> String {
+    label_value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_sample(
+    output: &mut dyn Write,
+    name: &str,
+    labels: &[(String, String)],
+    le: Option<&str>,
+    value: &str,
+) -> fmt::Result {
+    output.write_str(name)?;
+    if !labels.is_empty() || le.is_some() {
+        let le_label = le.map(|le| ("le", le));
+        let labels = labels
+            .iter()
+            .map(|(label_name, label_value)| (label_name.as_str(), label_value.as_str()))
+            .chain(le_label);
+        output.write_char('{')?;
+        for (ord, (label_name, label_value)) in labels.enumerate() {
+            if ord > 0 {
+                output.write_char(',')?;
+            }
+            write!(
+                output,
+                "{label_name}=\"{}\"",
+                escape_label_value(label_value)
+            )?;
+        }
+        output.write_char('}')?;
+    }
+    writeln!(output, " {value}")
+}
+
+fn write_histogram(
+    output: &mut dyn Write,
+    name: &str,
+    labels: &[(String, String)],
+    histogram: &Histogram,
+) -> fmt::Result {
+    let state = histogram.0.state.lock().unwrap().clone();
+    let bucket_name = format!("{name}_bucket");
+    let mut cumulated_count = 0;
+    for (bound, bucket_count) in histogram.0.bucket_bounds.iter().zip(&state.bucket_counts) {
+        cumulated_count += bucket_count;
+        write_sample(
+            output,
+            &bucket_name,
+            labels,
+            Some(&format_value(*bound)),
+            &cumulated_count.to_string(),
+        )?;
+    }
+    write_sample(
+        output,
+        &bucket_name,
+        labels,
+        Some("+Inf"),
+        &state.count.to_string(),
+    )?;
+    write_sample(
+        output,
+        &format!("{name}_sum"),
+        labels,
+        None,
+        &format_value(state.sum),
+    )?;
+    write_sample(
+        output,
+        &format!("{name}_count"),
+        labels,
+        None,
+        &state.count.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::MetricsRegistry;
+    use crate::collector::Count;
+    use crate::directory::{InstrumentedDirectory, RamDirectory};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_metrics_registry_encoding() {
+        let registry = MetricsRegistry::default();
+        let counter = registry.counter("requests_total", "Number of\nrequests.", &[]);
+        counter.inc();
+        registry
+            .counter("requests_total", "Ignored help.", &[])
+            .inc_by(2);
+        registry
+            .gauge("temperature", "Temperature.", &[("room", "a\"b")])
+            .set(1.5);
+        registry
+            .gauge("temperature", "Temperature.", &[("room", "hall")])
+            .set(f64::INFINITY);
+        let histogram = registry.histogram("latency_seconds", "Latency.", &[], &[1.0, 0.1]);
+        histogram.observe(0.05);
+        histogram.observe(0.1);
+        histogram.observe_duration(Duration::from_millis(500));
+        histogram.observe(3.0);
+        assert_eq!(counter.get(), 3);
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(
+            registry.encode_prometheus(),
+            "# HELP latency_seconds Latency.\n# TYPE latency_seconds histogram\n\
+             latency_seconds_bucket{le=\"0.1\"} 2\nlatency_seconds_bucket{le=\"1\"} 3\n\
+             latency_seconds_bucket{le=\"+Inf\"} 4\nlatency_seconds_sum 3.65\n\
+             latency_seconds_count 4\n\
+             # HELP requests_total Number of\\nrequests.\n# TYPE requests_total counter\n\
+             requests_total 3\n\
+             # HELP temperature Temperature.\n# TYPE temperature gauge\n\
+             temperature{room=\"a\\\"b\"} 1.5\ntemperature{room=\"hall\"} +Inf\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "already registered as a counter")]
+    fn test_metrics_registry_kind_mismatch() {
+        let registry = MetricsRegistry::default();
+        registry.counter("requests_total", "Number of requests.", &[]);
+        registry.gauge("requests_total", "Number of requests.", &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid metric name")]
+    fn test_metrics_registry_invalid_name() {
+        MetricsRegistry::default().counter("requests-total", "Number of requests.", &[]);
+    }
+
+    #[test]
+    fn test_metrics_registry_tantivy_metrics() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let directory = InstrumentedDirectory::wrap(Box::new(RamDirectory::create()));
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let registry = MetricsRegistry::default();
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_event_callback(registry.index_writer_event_callback());
+        index_writer.add_document(doc!(title => "running shoes"))?;
+        index_writer.add_document(doc!(title => "hat"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "shoes"))?;
+        index_writer.commit()?;
+        index_writer
+            .merge(&index.searchable_segment_ids()?)
+            .wait()?;
+        registry.record_index_writer(&index_writer.metrics());
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(title, "shoes"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(registry.search(&searcher, &query, &Count)?, 2);
+        assert_eq!(registry.search(&searcher, &AllQuery, &Count)?, 3);
+        searcher.doc::<TantivyDocument>(crate::DocAddress::new(0, 0))?;
+        registry.record_searcher(&searcher);
+        registry.record_directory_io(&directory.metrics());
+
+        let exposition = registry.encode_prometheus();
+        for expected_line in [
+            "tantivy_indexer_segments_flushed_total 2",
+            "tantivy_indexer_flushed_docs_total 3",
+            "tantivy_indexer_flush_duration_seconds_count 2",
+            "tantivy_indexer_docs_indexed_total 3",
+            "tantivy_merges_total 1",
+            "tantivy_merge_duration_seconds_count 1",
+            "tantivy_searches_total 2",
+            "tantivy_search_errors_total 0",
+            "tantivy_search_duration_seconds_bucket{le=\"+Inf\"} 2",
+            "tantivy_searcher_segments 1",
+            "tantivy_searcher_docs 3",
+            "tantivy_doc_store_cache_misses_total 1",
+        ] {
+            assert!(
+                exposition.lines().any(|line| line == expected_line),
+                "{expected_line:?} not found in:\n{exposition}"
+            );
+        }
+        let store_reads = registry
+            .counter(
+                "tantivy_directory_reads_total",
+                "",
+                &[("component", "store")],
+            )
+            .get();
+        assert!(store_reads > 0);
+        assert!(exposition.contains("tantivy_directory_written_bytes_total{component=\"other\"}"));
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+//! The points index is a one-dimensional BKD tree built for numeric and date fields
+//! set as [points](crate::schema::NumericOptions::set_points).
+//!
+//! For each field, the `(value, doc)` pairs of the segment are sorted by value
+//! and split into leaf blocks of [`LEAF_NUM_POINTS`] points.
+//! Each leaf stores its values, bitpacked relative to the smallest value of the leaf,
+//! followed by the matching doc ids.
+//!
+//! Since leaves are sorted by value, the inner nodes of the tree are implicit: the
+//! bounds of all leaves are kept in memory, and a range query visits only the leaves
+//! overlapping the range. Leaves fully contained in the range emit their doc ids
+//! without decoding any value.
+//!
+//! Values are mapped to `u64` in an order-preserving way, see
+//! [`MonotonicallyMappableToU64`](columnar::MonotonicallyMappableToU64).
+mod reader;
+mod serializer;
+mod writer;
+
+pub use self::reader::{PointsReader, PointsReaders};
+pub use self::serializer::PointsSerializer;
+pub use self::writer::PointsWriter;
+
+/// Number of points in each leaf of the points index, except the last one.
+pub const LEAF_NUM_POINTS: usize = 512;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use common::BitSet;
+
+    use super::{PointsReaders, PointsSerializer, LEAF_NUM_POINTS};
+    use crate::directory::{Directory, RamDirectory};
+    use crate::schema::Field;
+    use crate::DocId;
+
+    fn points_in_range(
+        points: &mut [(u64, DocId)],
+        range: std::ops::RangeInclusive<u64>,
+    ) -> Vec<DocId> {
+        let directory = RamDirectory::create();
+        let path = Path::new("test.points");
+        let field = Field::from_field_id(1);
+        {
+            let write = directory.open_write(path).unwrap();
+            let mut serializer = PointsSerializer::from_write(write).unwrap();
+            serializer.serialize_field(field, points).unwrap();
+            serializer.close().unwrap();
+        }
+        let file = directory.open_read(path).unwrap();
+        let points_readers = PointsReaders::open(file).unwrap();
+        assert!(points_readers
+            .get_field(Field::from_field_id(0))
+            .unwrap()
+            .is_none());
+        let points_reader = points_readers.get_field(field).unwrap().unwrap();
+        assert_eq!(points_reader.num_points() as usize, points.len());
+        let mut bitset = BitSet::with_max_value(10_000);
+        points_reader.for_each_doc_in_range(range, |doc| bitset.insert(doc));
+        (0..10_000).filter(|doc| bitset.contains(*doc)).collect()
+    }
+
+    #[test]
+    fn test_points_empty() {
+        assert!(points_in_range(&mut [], 0..=u64::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_points_range() {
+        let mut points: Vec<(u64, DocId)> = (0..5_000u32)
+            .map(|doc| (((doc as u64) * 7_919) % 1_000, doc))
+            .collect();
+        assert!(points.len() > 5 * LEAF_NUM_POINTS);
+        for range in [0..=0, 3..=17, 100..=999, 999..=u64::MAX, 1_000..=2_000] {
+            let mut expected: Vec<DocId> = points
+                .iter()
+                .filter(|(val, _)| range.contains(val))
+                .map(|(_, doc)| *doc)
+                .collect();
+            expected.sort();
+            assert_eq!(points_in_range(&mut points, range), expected);
+        }
+    }
+
+    #[test]
+    fn test_points_large_values() {
+        let mut points = vec![(u64::MAX, 3), (0, 1), (u64::MAX - 1, 2), (1 << 60, 0)];
+        assert_eq!(points_in_range(&mut points, 1..=u64::MAX), vec![0, 2, 3]);
+        assert_eq!(points_in_range(&mut points, u64::MAX..=u64::MAX), vec![3]);
+        assert_eq!(points_in_range(&mut points, 0..=(1 << 60)), vec![0, 1]);
+    }
+}
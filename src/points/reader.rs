@@ -0,0 +1,188 @@
+use std::io;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use common::{BinarySerializable, VInt};
+use tantivy_bitpacker::BitUnpacker;
+
+use super::LEAF_NUM_POINTS;
+use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::schema::Field;
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::DocId;
+
+/// Reader for the points index of all fields of a segment.
+#[derive(Clone)]
+pub struct PointsReaders {
+    data: Arc<CompositeFile>,
+}
+
+impl PointsReaders {
+    /// Creates a points reader.
+    pub fn open(file: FileSlice) -> crate::Result<PointsReaders> {
+        let data = CompositeFile::open(&file)?;
+        Ok(PointsReaders {
+            data: Arc::new(data),
+        })
+    }
+
+    /// Creates a points reader without any field.
+    ///
+    /// This is used for segments written before the points index existed.
+    pub fn empty() -> PointsReaders {
+        PointsReaders {
+            data: Arc::new(CompositeFile::empty()),
+        }
+    }
+
+    /// Returns the `PointsReader` for a specific field.
+    ///
+    /// Returns `None` if the field has no points index in this segment.
+    pub fn get_field(&self, field: Field) -> crate::Result<Option<PointsReader>> {
+        if let Some(file) = self.data.open_read(field) {
+            let points_reader = PointsReader::open(file.read_bytes()?)?;
+            Ok(Some(points_reader))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Leaf {
+    min_value: u64,
+    max_value: u64,
+    value_unpacker: BitUnpacker,
+    num_points: u32,
+    // Offsets of the values and of the doc ids of the leaf, relative to the leaves data.
+    values_offset: usize,
+    docs_offset: usize,
+    end_offset: usize,
+}
+
+/// Reads the points index of a given field.
+///
+/// See the [module documentation](crate::points) for a description of the format.
+#[derive(Clone)]
+pub struct PointsReader {
+    leaves: Vec<Leaf>,
+    doc_unpacker: BitUnpacker,
+    num_points: u32,
+    data: OwnedBytes,
+}
+
+fn num_bytes(num_vals: u32, num_bits: u8) -> usize {
+    (num_vals as usize * num_bits as usize).div_ceil(8)
+}
+
+impl PointsReader {
+    /// Opens the points index of a field.
+    pub fn open(mut bytes: OwnedBytes) -> io::Result<PointsReader> {
+        let num_points = VInt::deserialize(&mut bytes)?.val() as u32;
+        let doc_num_bits = u8::deserialize(&mut bytes)?;
+        let num_leaves = (num_points as usize).div_ceil(LEAF_NUM_POINTS);
+        let mut leaves = Vec::with_capacity(num_leaves);
+        let mut offset = 0;
+        for leaf_ord in 0..num_leaves {
+            let min_value = u64::deserialize(&mut bytes)?;
+            let max_value = u64::deserialize(&mut bytes)?;
+            let value_num_bits = u8::deserialize(&mut bytes)?;
+            let leaf_num_points =
+                (num_points as usize - leaf_ord * LEAF_NUM_POINTS).min(LEAF_NUM_POINTS) as u32;
+            let docs_offset = offset + num_bytes(leaf_num_points, value_num_bits);
+            let end_offset = docs_offset + num_bytes(leaf_num_points, doc_num_bits);
+            leaves.push(Leaf {
+                min_value,
+                max_value,
+                value_unpacker: BitUnpacker::new(value_num_bits),
+                num_points: leaf_num_points,
+                values_offset: offset,
+                docs_offset,
+                end_offset,
+            });
+            offset = end_offset;
+        }
+        if bytes.len() != offset {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Points index length does not match its leaves.",
+            ));
+        }
+        Ok(PointsReader {
+            leaves,
+            doc_unpacker: BitUnpacker::new(doc_num_bits),
+            num_points,
+            data: bytes,
+        })
+    }
+
+    /// Returns the number of points, i.e. the number of `(value, doc)` pairs.
+    pub fn num_points(&self) -> u32 {
+        self.num_points
+    }
+
+    /// Returns the smallest value of the field, or `None` if there are no points.
+    pub fn min_value(&self) -> Option<u64> {
+        self.leaves.first().map(|leaf| leaf.min_value)
+    }
+
+    /// Returns the largest value of the field, or `None` if there are no points.
+    pub fn max_value(&self) -> Option<u64> {
+        self.leaves.last().map(|leaf| leaf.max_value)
+    }
+
+    fn leaf_values<'a>(&'a self, leaf: &'a Leaf) -> impl Iterator<Item = u64> + 'a {
+        let data = &self.data.as_slice()[leaf.values_offset..leaf.docs_offset];
+        (0..leaf.num_points).map(move |idx| leaf.min_value + leaf.value_unpacker.get(idx, data))
+    }
+
+    fn leaf_docs<'a>(&'a self, leaf: &'a Leaf) -> impl Iterator<Item = DocId> + 'a {
+        let data = &self.data.as_slice()[leaf.docs_offset..leaf.end_offset];
+        (0..leaf.num_points).map(move |idx| self.doc_unpacker.get(idx, data) as DocId)
+    }
+
+    /// Calls `callback` with the doc of each point whose value is within `range`.
+    ///
+    /// Only the leaves overlapping the range are visited. A document may be emitted several
+    /// times if it has several values within the range.
+    pub fn for_each_doc_in_range(
+        &self,
+        range: RangeInclusive<u64>,
+        mut callback: impl FnMut(DocId),
+    ) {
+        let (start, end) = (*range.start(), *range.end());
+        if start > end {
+            return;
+        }
+        let first_leaf = self.leaves.partition_point(|leaf| leaf.max_value < start);
+        for leaf in &self.leaves[first_leaf..] {
+            if leaf.min_value > end {
+                break;
+            }
+            if start <= leaf.min_value && leaf.max_value <= end {
+                self.leaf_docs(leaf).for_each(&mut callback);
+                continue;
+            }
+            for (value, doc) in self.leaf_values(leaf).zip(self.leaf_docs(leaf)) {
+                if value > end {
+                    break;
+                }
+                if value >= start {
+                    callback(doc);
+                }
+            }
+        }
+    }
+
+    /// Iterates over all of the points, sorted by value.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, DocId)> + '_ {
+        self.leaves
+            .iter()
+            .flat_map(move |leaf| self.leaf_values(leaf).zip(self.leaf_docs(leaf)))
+    }
+}
@@ -0,0 +1,76 @@
+use std::io;
+use std::io::Write;
+
+use common::{BinarySerializable, VInt};
+use tantivy_bitpacker::{compute_num_bits, BitPacker};
+
+use super::LEAF_NUM_POINTS;
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::Field;
+use crate::DocId;
+
+/// Returns the number of bits used to bitpack the given amplitude.
+///
+/// The bit unpacker does not accept bit widths in `[57..63]`.
+pub(crate) fn value_num_bits(amplitude: u64) -> u8 {
+    let num_bits = compute_num_bits(amplitude);
+    if num_bits > 56 {
+        64
+    } else {
+        num_bits
+    }
+}
+
+/// The points serializer is in charge of
+/// the serialization of the points index of all fields.
+pub struct PointsSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl PointsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<PointsSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(PointsSerializer { composite_write })
+    }
+
+    /// Serialize the points of the given field.
+    ///
+    /// The points are `(value, doc)` pairs, and get sorted in place.
+    pub fn serialize_field(&mut self, field: Field, points: &mut [(u64, DocId)]) -> io::Result<()> {
+        points.sort_unstable();
+        let max_doc_in_points = points.iter().map(|(_, doc)| *doc).max().unwrap_or(0);
+        let doc_num_bits = compute_num_bits(max_doc_in_points as u64);
+        let write = self.composite_write.for_field(field);
+        VInt(points.len() as u64).serialize(write)?;
+        doc_num_bits.serialize(write)?;
+        for leaf in points.chunks(LEAF_NUM_POINTS) {
+            let min_value = leaf[0].0;
+            let max_value = leaf[leaf.len() - 1].0;
+            min_value.serialize(write)?;
+            max_value.serialize(write)?;
+            value_num_bits(max_value - min_value).serialize(write)?;
+        }
+        let mut bit_packer = BitPacker::new();
+        for leaf in points.chunks(LEAF_NUM_POINTS) {
+            let min_value = leaf[0].0;
+            let num_bits = value_num_bits(leaf[leaf.len() - 1].0 - min_value);
+            for (value, _) in leaf {
+                bit_packer.write(value - min_value, num_bits, write)?;
+            }
+            bit_packer.flush(write)?;
+            for (_, doc) in leaf {
+                bit_packer.write(*doc as u64, doc_num_bits, write)?;
+            }
+            bit_packer.flush(write)?;
+        }
+        write.flush()?;
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use std::{io, iter};
+
+use columnar::MonotonicallyMappableToU64;
+
+use super::PointsSerializer;
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, FieldType, Schema};
+use crate::DocId;
+
+/// The `PointsWriter` is in charge of recording the `(value, doc)` pairs
+/// of each field with a points index.
+pub struct PointsWriter {
+    // For each field, `None` if the field has no points index.
+    points_per_field: Vec<Option<Vec<(u64, DocId)>>>,
+    schema: Schema,
+}
+
+impl PointsWriter {
+    /// Returns the fields with a points index according to the given schema.
+    pub(crate) fn fields_with_points(schema: &Schema) -> Vec<Field> {
+        schema
+            .fields()
+            .filter_map(|(field, field_entry)| {
+                let has_points = match field_entry.field_type() {
+                    FieldType::U64(options) | FieldType::I64(options) | FieldType::F64(options) => {
+                        options.is_points()
+                    }
+                    FieldType::Date(options) => options.is_points(),
+                    _ => false,
+                };
+                has_points.then_some(field)
+            })
+            .collect()
+    }
+
+    /// Initialize with state for tracking the points fields
+    /// specified in the schema.
+    pub fn for_schema(schema: &Schema) -> PointsWriter {
+        let mut points_per_field: Vec<Option<Vec<(u64, DocId)>>> = iter::repeat_with(|| None)
+            .take(schema.num_fields())
+            .collect();
+        for field in PointsWriter::fields_with_points(schema) {
+            points_per_field[field.field_id() as usize] = Some(Vec::new());
+        }
+        PointsWriter {
+            points_per_field,
+            schema: schema.clone(),
+        }
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.points_per_field
+            .iter()
+            .flatten()
+            .map(|points| points.capacity() * std::mem::size_of::<(u64, DocId)>())
+            .sum()
+    }
+
+    /// Records the values of the points fields of a document.
+    ///
+    /// Values that do not match the type of their field are ignored.
+    pub fn add_document<D: Document>(&mut self, doc: DocId, document: &D) {
+        for (field, value) in document.iter_fields_and_values() {
+            let Some(points) = self
+                .points_per_field
+                .get_mut(field.field_id() as usize)
+                .and_then(Option::as_mut)
+            else {
+                continue;
+            };
+            let value = value.as_value();
+            let value_u64_opt = match self.schema.get_field_entry(field).field_type() {
+                FieldType::U64(_) => value.as_u64(),
+                FieldType::I64(_) => value.as_i64().map(i64::to_u64),
+                FieldType::F64(_) => value.as_f64().map(f64::to_u64),
+                FieldType::Date(options) => value
+                    .as_datetime()
+                    .map(|date| date.truncate(options.get_precision()).to_u64()),
+                _ => None,
+            };
+            if let Some(value_u64) = value_u64_opt {
+                points.push((value_u64, doc));
+            }
+        }
+    }
+
+    /// Serialize the recorded points of all fields.
+    pub fn serialize(mut self, mut points_serializer: PointsSerializer) -> io::Result<()> {
+        for (field_id, points_opt) in self.points_per_field.iter_mut().enumerate() {
+            if let Some(points) = points_opt {
+                points_serializer.serialize_field(Field::from_field_id(field_id as u32), points)?;
+            }
+        }
+        points_serializer.close()?;
+        Ok(())
+    }
+}
@@ -26,7 +26,7 @@ pub(crate) use self::indexing_context::IndexingContext;
 pub(crate) use self::per_field_postings_writer::PerFieldPostingsWriter;
 pub use self::postings::Postings;
 pub(crate) use self::postings_writer::{serialize_postings, IndexingPosition, PostingsWriter};
-pub use self::segment_postings::SegmentPostings;
+pub use self::segment_postings::{SegmentPostings, TermOccurrence};
 pub use self::serializer::{FieldSerializer, InvertedIndexSerializer};
 pub(crate) use self::skip::{BlockInfo, SkipReader};
 pub use self::term_info::TermInfo;
@@ -43,7 +43,7 @@ pub(crate) enum FreqReadingOption {
 pub(crate) mod tests {
     use std::mem;
 
-    use super::{InvertedIndexSerializer, Postings};
+    use super::{InvertedIndexSerializer, Postings, SegmentPostings, TermOccurrence};
     use crate::docset::{DocSet, TERMINATED};
     use crate::fieldnorm::FieldNormReader;
     use crate::index::{Index, SegmentComponent, SegmentReader};
@@ -349,6 +349,98 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_positions_and_offsets() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_indexing = TextFieldIndexing::default()
+            .set_tokenizer("default")
+            .set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets);
+        let text_field = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(text_indexing),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for _ in 0..200 {
+            index_writer.add_document(doc!(text_field => "a b a"))?;
+        }
+        index_writer.add_document(doc!(text_field => "b  a", text_field => "c a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.commit()?;
+
+        let occurrence = |position, offset_from, offset_to| TermOccurrence {
+            position,
+            offset_from,
+            offset_to,
+        };
+        let check_postings = |mut postings: SegmentPostings, doc_offset: DocId| {
+            let mut positions = Vec::new();
+            let mut occurrences = Vec::new();
+            for doc in doc_offset..doc_offset + 200 {
+                assert_eq!(postings.doc(), doc);
+                postings.positions(&mut positions);
+                assert_eq!(&positions[..], &[0, 2]);
+                postings.occurrences(&mut occurrences);
+                assert_eq!(occurrences, [occurrence(0, 0, 1), occurrence(2, 4, 5)]);
+                postings.advance();
+            }
+            // The offsets of the second value are shifted by the length of the first one.
+            postings.positions(&mut positions);
+            assert_eq!(&positions[..], &[1, 4]);
+            postings.occurrences(&mut occurrences);
+            assert_eq!(occurrences, [occurrence(1, 3, 4), occurrence(4, 7, 8)]);
+        };
+
+        let term_a = Term::from_field_text(text_field, "a");
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let segment_reader = searcher
+            .segment_readers()
+            .iter()
+            .find(|segment_reader| segment_reader.max_doc() > 1)
+            .unwrap();
+        let inverted_index = segment_reader.inverted_index(text_field)?;
+        check_postings(
+            inverted_index
+                .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositionsAndOffsets)?
+                .unwrap(),
+            0,
+        );
+        // Positions are still available, without offsets.
+        let mut postings = inverted_index
+            .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)?
+            .unwrap();
+        let mut positions = Vec::new();
+        postings.positions(&mut positions);
+        assert_eq!(&positions[..], &[0, 2]);
+
+        // The offsets are kept when merging segments.
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let inverted_index = segment_reader.inverted_index(text_field)?;
+        let mut postings = inverted_index
+            .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositionsAndOffsets)?
+            .unwrap();
+        let mut occurrences = Vec::new();
+        if postings.doc() == 0 && postings.term_freq() == 1 {
+            postings.occurrences(&mut occurrences);
+            assert_eq!(occurrences, [occurrence(0, 0, 1)]);
+            postings.advance();
+            check_postings(postings, 1);
+        } else {
+            check_postings(postings.clone(), 0);
+            postings.seek(201);
+            postings.occurrences(&mut occurrences);
+            assert_eq!(occurrences, [occurrence(0, 0, 1)]);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_skip_next() -> crate::Result<()> {
         let term_0 = Term::from_field_u64(Field::from_field_id(0), 0);
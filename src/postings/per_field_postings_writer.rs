@@ -1,6 +1,8 @@
 use crate::postings::json_postings_writer::JsonPostingsWriter;
 use crate::postings::postings_writer::SpecializedPostingsWriter;
-use crate::postings::recorder::{DocIdRecorder, TermFrequencyRecorder, TfAndPositionRecorder};
+use crate::postings::recorder::{
+    DocIdRecorder, TermFrequencyRecorder, TfAndPositionRecorder, TfPositionAndOffsetRecorder,
+};
 use crate::postings::PostingsWriter;
 use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, Schema};
 
@@ -42,6 +44,9 @@ fn posting_writer_from_field_entry(field_entry: &FieldEntry) -> Box<dyn Postings
                 IndexRecordOption::WithFreqsAndPositions => {
                     SpecializedPostingsWriter::<TfAndPositionRecorder>::default().into()
                 }
+                IndexRecordOption::WithFreqsAndPositionsAndOffsets => {
+                    SpecializedPostingsWriter::<TfPositionAndOffsetRecorder>::default().into()
+                }
             })
             .unwrap_or_else(|| SpecializedPostingsWriter::<DocIdRecorder>::default().into()),
         FieldType::U64(_)
@@ -64,6 +69,9 @@ fn posting_writer_from_field_entry(field_entry: &FieldEntry) -> Box<dyn Postings
                     IndexRecordOption::WithFreqsAndPositions => {
                         JsonPostingsWriter::<TfAndPositionRecorder>::default().into()
                     }
+                    IndexRecordOption::WithFreqsAndPositionsAndOffsets => {
+                        JsonPostingsWriter::<TfPositionAndOffsetRecorder>::default().into()
+                    }
                 }
             } else {
                 JsonPostingsWriter::<DocIdRecorder>::default().into()
@@ -97,6 +97,9 @@ pub(crate) fn serialize_postings(
 pub(crate) struct IndexingPosition {
     pub num_tokens: u32,
     pub end_position: u32,
+    /// Byte offset of the text being indexed, within the texts of the field joined
+    /// by a space.
+    pub start_offset: u32,
 }
 
 /// The `PostingsWriter` is in charge of receiving documenting
@@ -113,6 +116,22 @@ pub(crate) trait PostingsWriter: Send + Sync {
     ///   information.
     fn subscribe(&mut self, doc: DocId, pos: u32, term: &Term, ctx: &mut IndexingContext);
 
+    /// Record that a document contains a term at a given position, along with the byte
+    /// offsets of its token.
+    ///
+    /// The offsets are only retained if the field records them.
+    fn subscribe_with_offsets(
+        &mut self,
+        doc: DocId,
+        pos: u32,
+        _offset_from: u32,
+        _offset_to: u32,
+        term: &Term,
+        ctx: &mut IndexingContext,
+    ) {
+        self.subscribe(doc, pos, term, ctx);
+    }
+
     /// Serializes the postings on disk.
     /// The actual serialization format is handled by the `PostingsSerializer`.
     fn serialize(
@@ -150,7 +169,16 @@ pub(crate) trait PostingsWriter: Send + Sync {
             term_buffer.append_bytes(token.text.as_bytes());
             let start_position = indexing_position.end_position + token.position as u32;
             end_position = end_position.max(start_position + token.position_length as u32);
-            self.subscribe(doc_id, start_position, term_buffer, ctx);
+            let offset_from = indexing_position.start_offset + token.offset_from as u32;
+            let offset_to = indexing_position.start_offset + token.offset_to as u32;
+            self.subscribe_with_offsets(
+                doc_id,
+                start_position,
+                offset_from,
+                offset_to,
+                term_buffer,
+                ctx,
+            );
             num_tokens += 1;
         });
 
@@ -199,6 +227,19 @@ impl<Rec: Recorder> SpecializedPostingsWriter<Rec> {
 impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
     #[inline]
     fn subscribe(&mut self, doc: DocId, position: u32, term: &Term, ctx: &mut IndexingContext) {
+        self.subscribe_with_offsets(doc, position, 0u32, 0u32, term, ctx);
+    }
+
+    #[inline]
+    fn subscribe_with_offsets(
+        &mut self,
+        doc: DocId,
+        position: u32,
+        offset_from: u32,
+        offset_to: u32,
+        term: &Term,
+        ctx: &mut IndexingContext,
+    ) {
         debug_assert!(term.serialized_term().len() >= 4);
         self.total_num_tokens += 1;
         let (term_index, arena) = (&mut ctx.term_index, &mut ctx.arena);
@@ -209,12 +250,12 @@ impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
                     recorder.close_doc(arena);
                     recorder.new_doc(doc, arena);
                 }
-                recorder.record_position(position, arena);
+                recorder.record_position(position, offset_from, offset_to, arena);
                 recorder
             } else {
                 let mut recorder = Rec::default();
                 recorder.new_doc(doc, arena);
-                recorder.record_position(position, arena);
+                recorder.record_position(position, offset_from, offset_to, arena);
                 recorder
             }
         });
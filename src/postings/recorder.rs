@@ -55,15 +55,22 @@ impl Iterator for VInt32Reader<'_> {
 ///   * the document id
 ///   * the term frequency
 ///   * the term positions
+///   * the offsets of the tokens
 pub(crate) trait Recorder: Copy + Default + Send + Sync + 'static {
     /// Returns the current document
     fn current_doc(&self) -> u32;
     /// Starts recording information about a new document
     /// This method shall only be called if the term is within the document.
     fn new_doc(&mut self, doc: DocId, arena: &mut MemoryArena);
-    /// Record the position of a term. For each document,
-    /// this method will be called `term_freq` times.
-    fn record_position(&mut self, position: u32, arena: &mut MemoryArena);
+    /// Record the position of a term, and the byte offsets of its token.
+    /// For each document, this method will be called `term_freq` times.
+    fn record_position(
+        &mut self,
+        position: u32,
+        offset_from: u32,
+        offset_to: u32,
+        arena: &mut MemoryArena,
+    );
     /// Close the document. It will help record the term frequency.
     fn close_doc(&mut self, arena: &mut MemoryArena);
     /// Pushes the postings information to the serializer.
@@ -105,7 +112,14 @@ impl Recorder for DocIdRecorder {
     }
 
     #[inline]
-    fn record_position(&mut self, _position: u32, _arena: &mut MemoryArena) {}
+    fn record_position(
+        &mut self,
+        _position: u32,
+        _offset_from: u32,
+        _offset_to: u32,
+        _arena: &mut MemoryArena,
+    ) {
+    }
 
     #[inline]
     fn close_doc(&mut self, _arena: &mut MemoryArena) {}
@@ -167,7 +181,13 @@ impl Recorder for TermFrequencyRecorder {
     }
 
     #[inline]
-    fn record_position(&mut self, _position: u32, _arena: &mut MemoryArena) {
+    fn record_position(
+        &mut self,
+        _position: u32,
+        _offset_from: u32,
+        _offset_to: u32,
+        _arena: &mut MemoryArena,
+    ) {
         self.current_tf += 1;
     }
 
@@ -224,7 +244,13 @@ impl Recorder for TfAndPositionRecorder {
     }
 
     #[inline]
-    fn record_position(&mut self, position: u32, arena: &mut MemoryArena) {
+    fn record_position(
+        &mut self,
+        position: u32,
+        _offset_from: u32,
+        _offset_to: u32,
+        arena: &mut MemoryArena,
+    ) {
         self.stack
             .writer(arena)
             .write_u32_vint(position.wrapping_add(1u32));
@@ -271,6 +297,93 @@ impl Recorder for TfAndPositionRecorder {
     }
 }
 
+/// Recorder encoding term frequencies, positions, and the offsets of the tokens.
+///
+/// For each occurrence, the position delta is followed by the delta of the start offset
+/// of the token, and by the length of the token.
+#[derive(Clone, Copy, Default)]
+pub struct TfPositionAndOffsetRecorder {
+    stack: ExpUnrolledLinkedList,
+    current_doc: DocId,
+    term_doc_freq: u32,
+}
+
+impl Recorder for TfPositionAndOffsetRecorder {
+    #[inline]
+    fn current_doc(&self) -> DocId {
+        self.current_doc
+    }
+
+    #[inline]
+    fn new_doc(&mut self, doc: DocId, arena: &mut MemoryArena) {
+        let delta = doc - self.current_doc;
+        self.current_doc = doc;
+        self.term_doc_freq += 1u32;
+        self.stack.writer(arena).write_u32_vint(delta);
+    }
+
+    #[inline]
+    fn record_position(
+        &mut self,
+        position: u32,
+        offset_from: u32,
+        offset_to: u32,
+        arena: &mut MemoryArena,
+    ) {
+        let mut writer = self.stack.writer(arena);
+        writer.write_u32_vint(position.wrapping_add(1u32));
+        writer.write_u32_vint(offset_from);
+        writer.write_u32_vint(offset_to);
+    }
+
+    #[inline]
+    fn close_doc(&mut self, arena: &mut MemoryArena) {
+        self.stack.writer(arena).write_u32_vint(POSITION_END);
+    }
+
+    fn serialize(
+        &self,
+        arena: &MemoryArena,
+        serializer: &mut FieldSerializer<'_>,
+        buffer_lender: &mut BufferLender,
+    ) {
+        let (buffer_u8, buffer_positions) = buffer_lender.lend_all();
+        self.stack.read_to_end(arena, buffer_u8);
+        let mut u32_it = VInt32Reader::new(&buffer_u8[..]);
+        let mut prev_doc = 0;
+        while let Some(delta_doc_id) = u32_it.next() {
+            let doc_id = prev_doc + delta_doc_id;
+            prev_doc = doc_id;
+            let mut prev_position_plus_one = 1u32;
+            let mut prev_offset_from = 0u32;
+            buffer_positions.clear();
+            loop {
+                match u32_it.next() {
+                    Some(POSITION_END) | None => {
+                        break;
+                    }
+                    Some(position_plus_one) => {
+                        let offset_from = u32_it.next().unwrap_or(0u32);
+                        let offset_to = u32_it.next().unwrap_or(offset_from);
+                        buffer_positions.push(position_plus_one - prev_position_plus_one);
+                        // Tokens are not guaranteed to come in the order of their offsets.
+                        buffer_positions.push(offset_from.wrapping_sub(prev_offset_from));
+                        buffer_positions.push(offset_to.wrapping_sub(offset_from));
+                        prev_position_plus_one = position_plus_one;
+                        prev_offset_from = offset_from;
+                    }
+                }
+            }
+            let term_freq = buffer_positions.len() as u32 / 3;
+            serializer.write_doc(doc_id, term_freq, buffer_positions);
+        }
+    }
+
+    fn term_doc_freq(&self) -> Option<u32> {
+        Some(self.term_doc_freq)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
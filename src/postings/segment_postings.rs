@@ -7,6 +7,21 @@ use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
 use crate::postings::{branchless_binary_search, BlockSegmentPostings, Postings};
 use crate::{DocId, TERMINATED};
 
+/// An occurrence of a term in a document: its position, and the byte offsets of its token in
+/// the text of the field.
+///
+/// The texts of a field with several values are joined by a space, so that the offsets
+/// of the tokens of a value are shifted by the lengths of the previous values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TermOccurrence {
+    /// Position of the term.
+    pub position: u32,
+    /// Byte offset of the first character of the token.
+    pub offset_from: u32,
+    /// Byte offset following the last character of the token.
+    pub offset_to: u32,
+}
+
 /// `SegmentPostings` represents the inverted list or postings associated with
 /// a term in a `Segment`.
 ///
@@ -17,6 +32,7 @@ pub struct SegmentPostings {
     pub(crate) block_cursor: BlockSegmentPostings,
     cur: usize,
     position_reader: Option<PositionReader>,
+    has_offsets: bool,
 }
 
 impl SegmentPostings {
@@ -26,6 +42,7 @@ impl SegmentPostings {
             block_cursor: BlockSegmentPostings::empty(),
             cur: 0,
             position_reader: None,
+            has_offsets: false,
         }
     }
 
@@ -86,7 +103,7 @@ impl SegmentPostings {
             IndexRecordOption::Basic,
         )
         .unwrap();
-        SegmentPostings::from_block_postings(block_segment_postings, None)
+        SegmentPostings::from_block_postings(block_segment_postings, None, false)
     }
 
     /// Helper functions to create `SegmentPostings` for tests.
@@ -134,7 +151,7 @@ impl SegmentPostings {
             IndexRecordOption::WithFreqs,
         )
         .unwrap();
-        SegmentPostings::from_block_postings(block_segment_postings, None)
+        SegmentPostings::from_block_postings(block_segment_postings, None, false)
     }
 
     /// Reads a Segment postings from an &[u8]
@@ -142,16 +159,87 @@ impl SegmentPostings {
     /// * `len` - number of document in the posting lists.
     /// * `data` - data array. The complete data is not necessarily used.
     /// * `freq_handler` - the freq handler is in charge of decoding frequencies and/or positions
+    /// * `has_offsets` - whether the positions are followed by the offsets of their tokens
     pub(crate) fn from_block_postings(
         segment_block_postings: BlockSegmentPostings,
         position_reader: Option<PositionReader>,
+        has_offsets: bool,
     ) -> SegmentPostings {
         SegmentPostings {
             block_cursor: segment_block_postings,
             cur: 0, // cursor within the block
             position_reader,
+            has_offsets,
+        }
+    }
+
+    fn num_vals_per_position(&self) -> usize {
+        if self.has_offsets {
+            3
+        } else {
+            1
         }
     }
+
+    /// Appends the raw position data of the current document to `output`: the position
+    /// deltas, interleaved with the offset deltas if the field records offsets.
+    ///
+    /// Returns false if no positions are available.
+    fn append_raw_positions(&mut self, output: &mut Vec<u32>) -> bool {
+        let term_freq = self.term_freq();
+        let num_vals_per_position = self.num_vals_per_position();
+        let Some(position_reader) = self.position_reader.as_mut() else {
+            return false;
+        };
+        debug_assert!(
+            !self.block_cursor.freqs().is_empty(),
+            "No positions available"
+        );
+        let read_offset = self.block_cursor.position_offset()
+            + (self.block_cursor.freqs()[..self.cur]
+                .iter()
+                .cloned()
+                .sum::<u32>() as u64);
+        let prev_len = output.len();
+        // TODO: instead of zeroing the output, we could use MaybeUninit or similar.
+        output.resize(prev_len + term_freq as usize * num_vals_per_position, 0u32);
+        position_reader.read(
+            read_offset * num_vals_per_position as u64,
+            &mut output[prev_len..],
+        );
+        true
+    }
+
+    /// Returns the positions of the term in the current document, along with the
+    /// byte offsets of their tokens.
+    ///
+    /// The output is empty if the field was not indexed with
+    /// [`IndexRecordOption::WithFreqsAndPositionsAndOffsets`](crate::schema::IndexRecordOption::WithFreqsAndPositionsAndOffsets),
+    /// or if the postings were opened without positions.
+    pub fn occurrences(&mut self, output: &mut Vec<TermOccurrence>) {
+        output.clear();
+        if !self.has_offsets {
+            return;
+        }
+        let mut raw_positions = Vec::new();
+        if !self.append_raw_positions(&mut raw_positions) {
+            return;
+        }
+        let mut occurrence = TermOccurrence::default();
+        for vals in raw_positions.chunks_exact(3) {
+            occurrence.position += vals[0];
+            occurrence.offset_from = occurrence.offset_from.wrapping_add(vals[1]);
+            occurrence.offset_to = occurrence.offset_from.wrapping_add(vals[2]);
+            output.push(occurrence);
+        }
+    }
+
+    /// Returns the delta-encoded position data of the current document, as it is passed to
+    /// [`FieldSerializer::write_doc()`](crate::postings::FieldSerializer::write_doc).
+    pub(crate) fn raw_positions(&mut self, output: &mut Vec<u32>) {
+        output.clear();
+        self.append_raw_positions(output);
+    }
 }
 
 impl DocSet for SegmentPostings {
@@ -238,27 +326,19 @@ impl Postings for SegmentPostings {
     }
 
     fn append_positions_with_offset(&mut self, offset: u32, output: &mut Vec<u32>) {
-        let term_freq = self.term_freq();
         let prev_len = output.len();
-        if let Some(position_reader) = self.position_reader.as_mut() {
-            debug_assert!(
-                !self.block_cursor.freqs().is_empty(),
-                "No positions available"
-            );
-            let read_offset = self.block_cursor.position_offset()
-                + (self.block_cursor.freqs()[..self.cur]
-                    .iter()
-                    .cloned()
-                    .sum::<u32>() as u64);
-            // TODO: instead of zeroing the output, we could use MaybeUninit or similar.
-            output.resize(prev_len + term_freq as usize, 0u32);
-            position_reader.read(read_offset, &mut output[prev_len..]);
-            let mut cum = offset;
-            for output_mut in output[prev_len..].iter_mut() {
-                cum += *output_mut;
-                *output_mut = cum;
-            }
+        if !self.append_raw_positions(output) {
+            return;
+        }
+        let num_vals_per_position = self.num_vals_per_position();
+        let num_positions = (output.len() - prev_len) / num_vals_per_position;
+        let mut cum = offset;
+        for i in 0..num_positions {
+            // When offsets are recorded, the positions are compacted in place.
+            cum += output[prev_len + i * num_vals_per_position];
+            output[prev_len + i] = cum;
         }
+        output.truncate(prev_len + num_positions);
     }
 }
 
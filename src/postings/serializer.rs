@@ -106,6 +106,7 @@ pub struct FieldSerializer<'a> {
     term_dictionary_builder: TermDictionaryBuilder<&'a mut CountingWriter<WritePtr>>,
     postings_serializer: PostingsSerializer<&'a mut CountingWriter<WritePtr>>,
     positions_serializer_opt: Option<PositionSerializer<&'a mut CountingWriter<WritePtr>>>,
+    has_offsets: bool,
     current_term_info: TermInfo,
     term_open: bool,
 }
@@ -144,6 +145,7 @@ impl<'a> FieldSerializer<'a> {
             term_dictionary_builder,
             postings_serializer,
             positions_serializer_opt,
+            has_offsets: index_record_option.has_offsets(),
             current_term_info: TermInfo::default(),
             term_open: false,
         })
@@ -194,13 +196,20 @@ impl<'a> FieldSerializer<'a> {
     /// For instance, if the positions are `2, 3, 17`,
     /// `position_deltas` is `2, 1, 14`
     ///
+    /// If the field records offsets, each position delta is followed by the delta of the
+    /// start offset of the token, and by the length of the token.
+    ///
     /// Term frequencies and positions may be ignored by the serializer depending
     /// on the configuration of the field in the `Schema`.
     pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32]) {
         self.current_term_info.doc_freq += 1;
         self.postings_serializer.write_doc(doc_id, term_freq);
         if let Some(ref mut positions_serializer) = self.positions_serializer_opt.as_mut() {
-            assert_eq!(term_freq as usize, position_deltas.len());
+            let num_vals_per_position = if self.has_offsets { 3 } else { 1 };
+            assert_eq!(
+                term_freq as usize * num_vals_per_position,
+                position_deltas.len()
+            );
             positions_serializer.write_positions_delta(position_deltas);
         }
     }
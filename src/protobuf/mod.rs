@@ -0,0 +1,54 @@
+//! Protobuf messages for distributed search.
+//!
+//! A distributed search sends a query and a collector spec to the shards, which each return
+//! a fruit: their top documents, their count, or their intermediate aggregation results. The
+//! messages of this module encode these with [`prost`], and convert from and to the native
+//! types of tantivy. They are also defined in the `tantivy.proto` file next to this module,
+//! for the nodes written in other languages.
+//!
+//! ```rust
+//! use tantivy::protobuf::prost::Message;
+//! use tantivy::protobuf::{CollectorSpec, Query, ShardFruit, Term, TopDocsSpec};
+//! use tantivy::schema::{Schema, TEXT};
+//! use tantivy::{doc, Index, IndexWriter};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+//! index_writer.add_document(doc!(title => "the old man and the sea"))?;
+//! index_writer.commit()?;
+//!
+//! // On the coordinator.
+//! let query = Query::term(Term::text("title", "sea")).boost(2.0);
+//! let query_bytes = query.encode_to_vec();
+//! let collector_spec = CollectorSpec::top_docs(TopDocsSpec { limit: 10, offset: 0 });
+//! let collector_spec_bytes = collector_spec.encode_to_vec();
+//!
+//! // On a shard.
+//! let query = Query::decode(&query_bytes[..]).unwrap().to_query(&index)?;
+//! let collector_spec = CollectorSpec::decode(&collector_spec_bytes[..]).unwrap();
+//! let searcher = index.reader()?.searcher();
+//! let shard_fruit = collector_spec.search(&searcher, query.as_ref(), Default::default())?;
+//! let shard_fruit_bytes = shard_fruit.encode_to_vec();
+//!
+//! // Back on the coordinator.
+//! let shard_fruit = ShardFruit::decode(&shard_fruit_bytes[..]).unwrap();
+//! assert_eq!(shard_fruit.into_top_docs()?.len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+mod request;
+mod response;
+
+pub use prost;
+
+pub use self::request::{
+    collector_spec, query, term, AggregationSpec, AllQuery, BooleanClause, BooleanQuery,
+    BoostQuery, CollectorSpec, CountSpec, Occur, Query, QueryString, Term, TermQuery, TopDocsSpec,
+};
+pub use self::response::{
+    shard_fruit, AggregationFruit, CountFruit, ScoredDoc, ShardFruit, TopDocsFruit,
+};
@@ -0,0 +1,481 @@
+use crate::aggregation::agg_req::Aggregations;
+use crate::aggregation::{AggregationLimitsGuard, DistributedAggregationCollector};
+use crate::collector::{Count, TopDocs};
+use crate::protobuf::{AggregationFruit, ShardFruit};
+use crate::query::QueryParser;
+use crate::schema::{IndexRecordOption, Schema, Type};
+use crate::{query as native_query, Index, Searcher, TantivyError};
+
+/// A term of a field, identified by its name in the schema.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Term {
+    /// Name of the field.
+    #[prost(string, tag = "1")]
+    pub field: String,
+    /// Value of the term.
+    #[prost(oneof = "term::Value", tags = "2, 3, 4, 5, 6, 7")]
+    pub value: Option<term::Value>,
+}
+
+/// Nested types of [`Term`].
+pub mod term {
+    /// Value of a [`Term`](super::Term).
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Value {
+        /// Value of a text field.
+        #[prost(string, tag = "2")]
+        Text(String),
+        /// Value of an `u64` field.
+        #[prost(uint64, tag = "3")]
+        U64(u64),
+        /// Value of an `i64` field.
+        #[prost(int64, tag = "4")]
+        I64(i64),
+        /// Value of an `f64` field.
+        #[prost(double, tag = "5")]
+        F64(f64),
+        /// Value of a `bool` field.
+        #[prost(bool, tag = "6")]
+        Bool(bool),
+        /// Value of a bytes field.
+        #[prost(bytes = "vec", tag = "7")]
+        Bytes(Vec<u8>),
+    }
+}
+
+impl Term {
+    /// Creates a term of the text field named `field`.
+    pub fn text(field: &str, text: &str) -> Term {
+        Term {
+            field: field.to_string(),
+            value: Some(term::Value::Text(text.to_string())),
+        }
+    }
+
+    /// Converts a native term of an index with the schema `schema`.
+    ///
+    /// Returns an error for the types of terms without a protobuf representation, like the terms
+    /// of JSON fields.
+    pub fn from_term(native_term: &crate::Term, schema: &Schema) -> crate::Result<Term> {
+        let value_bytes = native_term.value();
+        let value = match value_bytes.typ() {
+            Type::Str => value_bytes
+                .as_str()
+                .map(|text| term::Value::Text(text.to_string())),
+            Type::U64 => value_bytes.as_u64().map(term::Value::U64),
+            Type::I64 => value_bytes.as_i64().map(term::Value::I64),
+            Type::F64 => value_bytes.as_f64().map(term::Value::F64),
+            Type::Bool => value_bytes.as_bool().map(term::Value::Bool),
+            Type::Bytes => value_bytes
+                .as_bytes()
+                .map(|bytes| term::Value::Bytes(bytes.to_vec())),
+            typ => {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Terms of type {typ:?} cannot be converted to protobuf"
+                )))
+            }
+        };
+        Ok(Term {
+            field: schema.get_field_name(native_term.field()).to_string(),
+            value,
+        })
+    }
+
+    /// Converts the term to a native term of an index with the schema `schema`.
+    pub fn to_term(&self, schema: &Schema) -> crate::Result<crate::Term> {
+        let field = schema.get_field(&self.field)?;
+        let native_term = match self.value.as_ref() {
+            Some(term::Value::Text(text)) => crate::Term::from_field_text(field, text),
+            Some(term::Value::U64(val)) => crate::Term::from_field_u64(field, *val),
+            Some(term::Value::I64(val)) => crate::Term::from_field_i64(field, *val),
+            Some(term::Value::F64(val)) => crate::Term::from_field_f64(field, *val),
+            Some(term::Value::Bool(val)) => crate::Term::from_field_bool(field, *val),
+            Some(term::Value::Bytes(bytes)) => crate::Term::from_field_bytes(field, bytes),
+            None => {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The term of the field {:?} has no value",
+                    self.field
+                )))
+            }
+        };
+        Ok(native_term)
+    }
+}
+
+/// Matches all of the documents.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AllQuery {}
+
+/// Matches the documents containing a term.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TermQuery {
+    /// The term searched.
+    #[prost(message, optional, tag = "1")]
+    pub term: Option<Term>,
+}
+
+/// Occurrence of a [`BooleanClause`], see [`Occur`](crate::query::Occur).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Occur {
+    /// The clause may match.
+    Should = 0,
+    /// The clause must match.
+    Must = 1,
+    /// The clause must not match.
+    MustNot = 2,
+    /// The clause must match, without contributing to the score.
+    Filter = 3,
+}
+
+impl From<native_query::Occur> for Occur {
+    fn from(occur: native_query::Occur) -> Occur {
+        match occur {
+            native_query::Occur::Should => Occur::Should,
+            native_query::Occur::Must => Occur::Must,
+            native_query::Occur::MustNot => Occur::MustNot,
+            native_query::Occur::Filter => Occur::Filter,
+        }
+    }
+}
+
+impl From<Occur> for native_query::Occur {
+    fn from(occur: Occur) -> native_query::Occur {
+        match occur {
+            Occur::Should => native_query::Occur::Should,
+            Occur::Must => native_query::Occur::Must,
+            Occur::MustNot => native_query::Occur::MustNot,
+            Occur::Filter => native_query::Occur::Filter,
+        }
+    }
+}
+
+/// A clause of a [`BooleanQuery`].
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BooleanClause {
+    /// Occurrence of the clause.
+    #[prost(enumeration = "Occur", tag = "1")]
+    pub occur: i32,
+    /// Query of the clause.
+    #[prost(message, optional, tag = "2")]
+    pub query: Option<Query>,
+}
+
+/// Combines queries, see [`BooleanQuery`](crate::query::BooleanQuery).
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BooleanQuery {
+    /// The clauses of the query.
+    #[prost(message, repeated, tag = "1")]
+    pub clauses: Vec<BooleanClause>,
+}
+
+/// A query in the syntax of the [`QueryParser`].
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryString {
+    /// The text of the query.
+    #[prost(string, tag = "1")]
+    pub query: String,
+    /// Names of the fields in which the terms not prefixed with a field are searched.
+    #[prost(string, repeated, tag = "2")]
+    pub default_fields: Vec<String>,
+}
+
+/// Multiplies the scores of a query, see [`BoostQuery`](crate::query::BoostQuery).
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BoostQuery {
+    /// The boosted query.
+    #[prost(message, optional, boxed, tag = "1")]
+    pub query: Option<Box<Query>>,
+    /// The boost factor.
+    #[prost(float, tag = "2")]
+    pub boost: f32,
+}
+
+/// A query, converted to a native query with [`Query::to_query`].
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Query {
+    /// The kind of query.
+    #[prost(oneof = "query::Kind", tags = "1, 2, 3, 4, 5")]
+    pub kind: Option<query::Kind>,
+}
+
+/// Nested types of [`Query`].
+pub mod query {
+    /// Kind of a [`Query`](super::Query).
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        /// Matches all of the documents.
+        #[prost(message, tag = "1")]
+        All(super::AllQuery),
+        /// Matches the documents containing a term.
+        #[prost(message, tag = "2")]
+        Term(super::TermQuery),
+        /// Combines queries.
+        #[prost(message, tag = "3")]
+        Boolean(super::BooleanQuery),
+        /// A query in the syntax of the query parser.
+        #[prost(message, tag = "4")]
+        QueryString(super::QueryString),
+        /// Multiplies the scores of a query.
+        #[prost(message, tag = "5")]
+        Boost(super::BoostQuery),
+    }
+}
+
+impl Query {
+    /// Creates a query matching all of the documents.
+    pub fn all() -> Query {
+        Query {
+            kind: Some(query::Kind::All(AllQuery {})),
+        }
+    }
+
+    /// Creates a query matching the documents containing `term`.
+    pub fn term(term: Term) -> Query {
+        Query {
+            kind: Some(query::Kind::Term(TermQuery { term: Some(term) })),
+        }
+    }
+
+    /// Creates a query combining `clauses`.
+    pub fn boolean(clauses: Vec<(Occur, Query)>) -> Query {
+        let clauses = clauses
+            .into_iter()
+            .map(|(occur, query)| BooleanClause {
+                occur: occur.into(),
+                query: Some(query),
+            })
+            .collect();
+        Query {
+            kind: Some(query::Kind::Boolean(BooleanQuery { clauses })),
+        }
+    }
+
+    /// Creates a query in the syntax of the [`QueryParser`].
+    pub fn query_string(query: &str, default_fields: &[&str]) -> Query {
+        Query {
+            kind: Some(query::Kind::QueryString(QueryString {
+                query: query.to_string(),
+                default_fields: default_fields
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect(),
+            })),
+        }
+    }
+
+    /// Multiplies the scores of the query by `boost`.
+    #[must_use]
+    pub fn boost(self, boost: f32) -> Query {
+        Query {
+            kind: Some(query::Kind::Boost(BoostQuery {
+                query: Some(Box::new(self)),
+                boost,
+            })),
+        }
+    }
+
+    /// Converts the query to a native query on `index`.
+    ///
+    /// The terms of the query strings are tokenized with the tokenizers of `index`.
+    pub fn to_query(&self, index: &Index) -> crate::Result<Box<dyn native_query::Query>> {
+        let schema = index.schema();
+        match self.kind.as_ref() {
+            Some(query::Kind::All(_)) => Ok(Box::new(native_query::AllQuery)),
+            Some(query::Kind::Term(term_query)) => {
+                let term = term_query
+                    .term
+                    .as_ref()
+                    .ok_or_else(|| missing_message("term query", "term"))?
+                    .to_term(&schema)?;
+                let index_record_option = schema
+                    .get_field_entry(term.field())
+                    .field_type()
+                    .get_index_record_option()
+                    .unwrap_or(IndexRecordOption::Basic);
+                Ok(Box::new(native_query::TermQuery::new(
+                    term,
+                    index_record_option,
+                )))
+            }
+            Some(query::Kind::Boolean(boolean_query)) => {
+                let subqueries = boolean_query
+                    .clauses
+                    .iter()
+                    .map(|clause| {
+                        let occur = Occur::try_from(clause.occur).map_err(|_| {
+                            TantivyError::InvalidArgument(format!("Unknown occur {}", clause.occur))
+                        })?;
+                        let subquery = clause
+                            .query
+                            .as_ref()
+                            .ok_or_else(|| missing_message("boolean clause", "query"))?
+                            .to_query(index)?;
+                        Ok((occur.into(), subquery))
+                    })
+                    .collect::<crate::Result<Vec<_>>>()?;
+                Ok(Box::new(native_query::BooleanQuery::new(subqueries)))
+            }
+            Some(query::Kind::QueryString(query_string)) => {
+                let default_fields = query_string
+                    .default_fields
+                    .iter()
+                    .map(|field_name| schema.get_field(field_name))
+                    .collect::<crate::Result<Vec<_>>>()?;
+                let query_parser = QueryParser::for_index(index, default_fields);
+                Ok(query_parser.parse_query(&query_string.query)?)
+            }
+            Some(query::Kind::Boost(boost_query)) => {
+                let query = boost_query
+                    .query
+                    .as_ref()
+                    .ok_or_else(|| missing_message("boost query", "query"))?
+                    .to_query(index)?;
+                Ok(Box::new(native_query::BoostQuery::new(
+                    query,
+                    boost_query.boost,
+                )))
+            }
+            None => Err(missing_message("query", "kind")),
+        }
+    }
+}
+
+/// Counts the matching documents, see [`Count`].
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CountSpec {}
+
+/// Collects the top documents, see [`TopDocs`].
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TopDocsSpec {
+    /// Number of documents returned.
+    #[prost(uint32, tag = "1")]
+    pub limit: u32,
+    /// Number of top documents skipped.
+    #[prost(uint32, tag = "2")]
+    pub offset: u32,
+}
+
+impl TopDocsSpec {
+    /// Converts the spec to a native collector.
+    ///
+    /// Returns an error if the limit is 0.
+    pub fn to_collector(&self) -> crate::Result<TopDocs> {
+        if self.limit == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The limit of the top docs must be greater than 0".to_string(),
+            ));
+        }
+        Ok(TopDocs::with_limit(self.limit as usize).and_offset(self.offset as usize))
+    }
+}
+
+/// Computes aggregations, see the [aggregation module](crate::aggregation).
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AggregationSpec {
+    /// The aggregation request, in its JSON format.
+    #[prost(string, tag = "1")]
+    pub request_json: String,
+}
+
+impl AggregationSpec {
+    /// Converts an aggregation request.
+    pub fn from_aggregations(aggregations: &Aggregations) -> crate::Result<AggregationSpec> {
+        let request_json = serde_json::to_string(aggregations)
+            .map_err(|err| TantivyError::InternalError(err.to_string()))?;
+        Ok(AggregationSpec { request_json })
+    }
+
+    /// Converts the spec to the native collector computing the intermediate results of a shard.
+    pub fn to_collector(
+        &self,
+        limits: AggregationLimitsGuard,
+    ) -> crate::Result<DistributedAggregationCollector> {
+        let aggregations: Aggregations = serde_json::from_str(&self.request_json)
+            .map_err(|err| TantivyError::InvalidArgument(err.to_string()))?;
+        Ok(DistributedAggregationCollector::from_aggs(
+            aggregations,
+            limits,
+        ))
+    }
+}
+
+/// The collector run by the shards.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CollectorSpec {
+    /// The kind of collector.
+    #[prost(oneof = "collector_spec::Kind", tags = "1, 2, 3")]
+    pub kind: Option<collector_spec::Kind>,
+}
+
+/// Nested types of [`CollectorSpec`].
+pub mod collector_spec {
+    /// Kind of a [`CollectorSpec`](super::CollectorSpec).
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        /// Counts the matching documents.
+        #[prost(message, tag = "1")]
+        Count(super::CountSpec),
+        /// Collects the top documents.
+        #[prost(message, tag = "2")]
+        TopDocs(super::TopDocsSpec),
+        /// Computes aggregations.
+        #[prost(message, tag = "3")]
+        Aggregation(super::AggregationSpec),
+    }
+}
+
+impl CollectorSpec {
+    /// Creates a spec counting the matching documents.
+    pub fn count() -> CollectorSpec {
+        CollectorSpec {
+            kind: Some(collector_spec::Kind::Count(CountSpec {})),
+        }
+    }
+
+    /// Creates a spec collecting the top documents.
+    pub fn top_docs(top_docs_spec: TopDocsSpec) -> CollectorSpec {
+        CollectorSpec {
+            kind: Some(collector_spec::Kind::TopDocs(top_docs_spec)),
+        }
+    }
+
+    /// Creates a spec computing aggregations.
+    pub fn aggregation(aggregation_spec: AggregationSpec) -> CollectorSpec {
+        CollectorSpec {
+            kind: Some(collector_spec::Kind::Aggregation(aggregation_spec)),
+        }
+    }
+
+    /// Runs the collector on the shard searched by `searcher`, and returns its fruit.
+    ///
+    /// `limits` bound the memory and the number of buckets of the aggregations.
+    pub fn search(
+        &self,
+        searcher: &Searcher,
+        query: &dyn native_query::Query,
+        limits: AggregationLimitsGuard,
+    ) -> crate::Result<ShardFruit> {
+        match self.kind.as_ref() {
+            Some(collector_spec::Kind::Count(_)) => {
+                Ok(ShardFruit::count(searcher.search(query, &Count)? as u64))
+            }
+            Some(collector_spec::Kind::TopDocs(top_docs_spec)) => {
+                let top_docs = searcher.search(query, &top_docs_spec.to_collector()?)?;
+                Ok(ShardFruit::top_docs(top_docs))
+            }
+            Some(collector_spec::Kind::Aggregation(aggregation_spec)) => {
+                let collector = aggregation_spec.to_collector(limits)?;
+                let intermediate_results = searcher.search(query, &collector)?;
+                Ok(ShardFruit::aggregation(
+                    AggregationFruit::from_intermediate_results(&intermediate_results)?,
+                ))
+            }
+            None => Err(missing_message("collector spec", "kind")),
+        }
+    }
+}
+
+pub(crate) fn missing_message(message: &str, field: &str) -> TantivyError {
+    TantivyError::InvalidArgument(format!("The {message} has no {field}"))
+}
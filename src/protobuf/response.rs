@@ -0,0 +1,284 @@
+use crate::aggregation::intermediate_agg_result::IntermediateAggregationResults;
+use crate::protobuf::request::missing_message;
+use crate::{DocAddress, Score, TantivyError};
+
+/// Number of documents matching the query on a shard.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CountFruit {
+    /// Number of matching documents.
+    #[prost(uint64, tag = "1")]
+    pub count: u64,
+}
+
+/// A document of a [`TopDocsFruit`].
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScoredDoc {
+    /// Score of the document.
+    #[prost(float, tag = "1")]
+    pub score: f32,
+    /// Ordinal of the segment of the document in the searcher of the shard.
+    #[prost(uint32, tag = "2")]
+    pub segment_ord: u32,
+    /// Id of the document in its segment.
+    #[prost(uint32, tag = "3")]
+    pub doc_id: u32,
+}
+
+/// Top documents of a shard, by decreasing score.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TopDocsFruit {
+    /// The documents.
+    #[prost(message, repeated, tag = "1")]
+    pub docs: Vec<ScoredDoc>,
+}
+
+impl From<Vec<(Score, DocAddress)>> for TopDocsFruit {
+    fn from(top_docs: Vec<(Score, DocAddress)>) -> TopDocsFruit {
+        let docs = top_docs
+            .into_iter()
+            .map(|(score, doc_address)| ScoredDoc {
+                score,
+                segment_ord: doc_address.segment_ord,
+                doc_id: doc_address.doc_id,
+            })
+            .collect();
+        TopDocsFruit { docs }
+    }
+}
+
+impl From<TopDocsFruit> for Vec<(Score, DocAddress)> {
+    fn from(top_docs_fruit: TopDocsFruit) -> Vec<(Score, DocAddress)> {
+        top_docs_fruit
+            .docs
+            .into_iter()
+            .map(|doc| (doc.score, DocAddress::new(doc.segment_ord, doc.doc_id)))
+            .collect()
+    }
+}
+
+/// Intermediate aggregation results of a shard, to be merged with the results of the other
+/// shards.
+///
+/// The results are serialized with postcard, and can only be decoded by tantivy.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AggregationFruit {
+    /// The serialized intermediate results.
+    #[prost(bytes = "vec", tag = "1")]
+    pub intermediate_results: Vec<u8>,
+}
+
+impl AggregationFruit {
+    /// Serializes the intermediate results of a shard.
+    pub fn from_intermediate_results(
+        intermediate_results: &IntermediateAggregationResults,
+    ) -> crate::Result<AggregationFruit> {
+        let intermediate_results = postcard::to_allocvec(intermediate_results)
+            .map_err(|err| TantivyError::InternalError(err.to_string()))?;
+        Ok(AggregationFruit {
+            intermediate_results,
+        })
+    }
+
+    /// Deserializes the intermediate results of the shard.
+    pub fn to_intermediate_results(&self) -> crate::Result<IntermediateAggregationResults> {
+        postcard::from_bytes(&self.intermediate_results).map_err(|err| {
+            TantivyError::InvalidArgument(format!(
+                "Failed to deserialize the intermediate aggregation results: {err}"
+            ))
+        })
+    }
+}
+
+/// The fruit of a [`CollectorSpec`](super::CollectorSpec) on a shard.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ShardFruit {
+    /// The kind of fruit.
+    #[prost(oneof = "shard_fruit::Kind", tags = "1, 2, 3")]
+    pub kind: Option<shard_fruit::Kind>,
+}
+
+/// Nested types of [`ShardFruit`].
+pub mod shard_fruit {
+    /// Kind of a [`ShardFruit`](super::ShardFruit).
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        /// Number of matching documents.
+        #[prost(message, tag = "1")]
+        Count(super::CountFruit),
+        /// Top documents.
+        #[prost(message, tag = "2")]
+        TopDocs(super::TopDocsFruit),
+        /// Intermediate aggregation results.
+        #[prost(message, tag = "3")]
+        Aggregation(super::AggregationFruit),
+    }
+}
+
+impl ShardFruit {
+    /// Creates the fruit of a count.
+    pub fn count(count: u64) -> ShardFruit {
+        ShardFruit {
+            kind: Some(shard_fruit::Kind::Count(CountFruit { count })),
+        }
+    }
+
+    /// Creates the fruit of a top docs collector.
+    pub fn top_docs(top_docs: Vec<(Score, DocAddress)>) -> ShardFruit {
+        ShardFruit {
+            kind: Some(shard_fruit::Kind::TopDocs(top_docs.into())),
+        }
+    }
+
+    /// Creates the fruit of an aggregation.
+    pub fn aggregation(aggregation_fruit: AggregationFruit) -> ShardFruit {
+        ShardFruit {
+            kind: Some(shard_fruit::Kind::Aggregation(aggregation_fruit)),
+        }
+    }
+
+    /// Returns the count, or an error if the fruit is not a count.
+    pub fn into_count(self) -> crate::Result<u64> {
+        match self.kind {
+            Some(shard_fruit::Kind::Count(count_fruit)) => Ok(count_fruit.count),
+            _ => Err(missing_message("shard fruit", "count")),
+        }
+    }
+
+    /// Returns the top documents, or an error if the fruit is not a top docs fruit.
+    pub fn into_top_docs(self) -> crate::Result<Vec<(Score, DocAddress)>> {
+        match self.kind {
+            Some(shard_fruit::Kind::TopDocs(top_docs_fruit)) => Ok(top_docs_fruit.into()),
+            _ => Err(missing_message("shard fruit", "top docs")),
+        }
+    }
+
+    /// Returns the intermediate aggregation results, or an error if the fruit is not an
+    /// aggregation fruit.
+    pub fn into_intermediate_aggregation_results(
+        self,
+    ) -> crate::Result<IntermediateAggregationResults> {
+        match self.kind {
+            Some(shard_fruit::Kind::Aggregation(aggregation_fruit)) => {
+                aggregation_fruit.to_intermediate_results()
+            }
+            _ => Err(missing_message("shard fruit", "aggregation results")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::AggregationLimitsGuard;
+    use crate::collector::Count;
+    use crate::protobuf::{
+        AggregationSpec, CollectorSpec, Occur, Query, ShardFruit, Term, TopDocsSpec,
+    };
+    use crate::schema::{Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::{Index, IndexWriter};
+
+    fn roundtrip<M: Message + Default>(message: &M) -> M {
+        M::decode(&message.encode_to_vec()[..]).unwrap()
+    }
+
+    #[test]
+    fn test_protobuf_distributed_search() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let year = schema_builder.add_u64_field("year", INDEXED | FAST);
+        let schema = schema_builder.build();
+        let shards: Vec<Index> = (0..2)
+            .map(|_| Index::create_in_ram(schema.clone()))
+            .collect();
+        for (shard_ord, shard) in shards.iter().enumerate() {
+            let mut index_writer: IndexWriter = shard.writer_for_tests()?;
+            for doc_id in 0..3u64 {
+                index_writer.add_document(doc!(
+                    title => "the old man and the sea",
+                    category => if doc_id == 0 { "novel" } else { "essay" },
+                    year => 2000 + shard_ord as u64 * 3 + doc_id,
+                ))?;
+            }
+            index_writer.commit()?;
+        }
+
+        let native_term = crate::Term::from_field_u64(year, 2004);
+        let term = Term::from_term(&native_term, &schema)?;
+        assert_eq!(term.to_term(&schema)?, native_term);
+        let query = roundtrip(&Query::boolean(vec![
+            (Occur::Must, Query::query_string("sea", &["title"])),
+            (Occur::MustNot, Query::term(term)),
+            (Occur::Should, Query::all().boost(2.0)),
+        ]));
+        let aggregations: Aggregations =
+            serde_json::from_str(r#"{"categories": {"terms": {"field": "category"}}}"#).unwrap();
+        let collector_specs = [
+            CollectorSpec::count(),
+            CollectorSpec::top_docs(TopDocsSpec {
+                limit: 2,
+                offset: 0,
+            }),
+            CollectorSpec::aggregation(AggregationSpec::from_aggregations(&aggregations)?),
+        ];
+        let mut shard_fruits: Vec<Vec<ShardFruit>> = vec![Vec::new(); collector_specs.len()];
+        for shard in &shards {
+            let searcher = shard.reader()?.searcher();
+            let native_query = query.to_query(shard)?;
+            for (collector_spec, fruits) in collector_specs.iter().zip(&mut shard_fruits) {
+                let collector_spec = roundtrip(collector_spec);
+                let shard_fruit = collector_spec.search(
+                    &searcher,
+                    native_query.as_ref(),
+                    AggregationLimitsGuard::default(),
+                )?;
+                fruits.push(roundtrip(&shard_fruit));
+            }
+        }
+
+        let [count_fruits, top_docs_fruits, aggregation_fruits]: [Vec<ShardFruit>; 3] =
+            shard_fruits.try_into().unwrap();
+        let count: u64 = count_fruits
+            .into_iter()
+            .map(ShardFruit::into_count)
+            .sum::<crate::Result<u64>>()?;
+        assert_eq!(count, 5);
+        for top_docs_fruit in top_docs_fruits {
+            let top_docs = top_docs_fruit.clone().into_top_docs()?;
+            assert_eq!(top_docs.len(), 2);
+            assert!(top_docs_fruit.into_count().is_err());
+        }
+        let mut shard_results = aggregation_fruits
+            .into_iter()
+            .map(ShardFruit::into_intermediate_aggregation_results);
+        let mut intermediate_results = shard_results.next().unwrap()?;
+        for shard_result in shard_results {
+            intermediate_results.merge_fruits(shard_result?)?;
+        }
+        let results: AggregationResults =
+            intermediate_results.into_final_result(aggregations, Default::default())?;
+        let results_json = serde_json::to_value(&results).unwrap();
+        assert_eq!(
+            results_json["categories"]["buckets"][0],
+            serde_json::json!({"key": "essay", "doc_count": 3})
+        );
+
+        let searcher = shards[0].reader()?.searcher();
+        assert_eq!(
+            searcher.search(query.to_query(&shards[0])?.as_ref(), &Count)?,
+            3
+        );
+        assert!(Query::default().to_query(&shards[0]).is_err());
+        assert!(CollectorSpec::top_docs(TopDocsSpec::default())
+            .search(
+                &searcher,
+                query.to_query(&shards[0])?.as_ref(),
+                Default::default()
+            )
+            .is_err());
+        Ok(())
+    }
+}
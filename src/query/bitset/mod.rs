@@ -1,6 +1,7 @@
 use common::{BitSet, TinySet};
 
 use crate::docset::{DocSet, TERMINATED};
+use crate::fastfield::AliveBitSet;
 use crate::DocId;
 
 /// A `BitSetDocSet` makes it possible to iterate through a bitset as if it was a `DocSet`.
@@ -24,6 +25,28 @@ impl BitSetDocSet {
         self.cursor_bucket = bucket_addr;
         self.cursor_tinybitset = self.docs.tinyset(bucket_addr);
     }
+
+    /// Consumes the remaining documents, and returns the number of those accepted by `filter`.
+    ///
+    /// The documents are counted a tiny bitset at a time, with `filter` mapping the tiny bitset
+    /// of a bucket to the tiny bitset of the accepted documents.
+    fn count_with_filter(&mut self, filter: impl Fn(u32, TinySet) -> TinySet) -> u32 {
+        if self.doc == TERMINATED {
+            return 0;
+        }
+        let current_doc = TinySet::singleton(self.doc % 64u32);
+        let mut count = filter(
+            self.cursor_bucket,
+            self.cursor_tinybitset.union(current_doc),
+        )
+        .len();
+        for bucket in self.cursor_bucket + 1..self.docs.num_buckets() {
+            count += filter(bucket, self.docs.tinyset(bucket)).len();
+        }
+        self.cursor_tinybitset = TinySet::empty();
+        self.doc = TERMINATED;
+        count
+    }
 }
 
 impl From<BitSet> for BitSetDocSet {
@@ -91,6 +114,19 @@ impl DocSet for BitSetDocSet {
     fn size_hint(&self) -> u32 {
         self.docs.len() as u32
     }
+
+    /// Counts the alive documents with popcounts over the buckets of the bitset and of the
+    /// alive bitset, rather than iterating over them.
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        let alive_bitset = alive_bitset.bitset();
+        self.count_with_filter(|bucket, tinyset| tinyset.intersect(alive_bitset.tinyset(bucket)))
+    }
+
+    /// Counts the documents with popcounts over the buckets of the bitset, rather than
+    /// iterating over them.
+    fn count_including_deleted(&mut self) -> u32 {
+        self.count_with_filter(|_bucket, tinyset| tinyset)
+    }
 }
 
 #[cfg(test)]
@@ -101,6 +137,7 @@ mod tests {
 
     use super::BitSetDocSet;
     use crate::docset::{DocSet, TERMINATED};
+    use crate::fastfield::AliveBitSet;
     use crate::tests::generate_nonunique_unsorted;
     use crate::DocId;
 
@@ -135,6 +172,26 @@ mod tests {
         assert!(!remaining);
     }
 
+    #[test]
+    fn test_bitset_count() {
+        let docs = [1, 3, 63, 64, 100, 200, 201, 299];
+        let mut docset = create_docbitset(&docs, 300);
+        assert_eq!(docset.count_including_deleted(), 8);
+        assert_eq!(docset.doc(), TERMINATED);
+        assert_eq!(docset.count_including_deleted(), 0);
+
+        let mut docset = create_docbitset(&docs, 300);
+        assert_eq!(docset.seek(64), 64);
+        assert_eq!(docset.count_including_deleted(), 5);
+
+        let alive_bitset = AliveBitSet::for_test_from_deleted_docs(&[3, 64, 201, 250], 300);
+        let mut docset = create_docbitset(&docs, 300);
+        assert_eq!(docset.count(&alive_bitset), 5);
+        let mut docset = create_docbitset(&docs, 300);
+        assert_eq!(docset.seek(64), 64);
+        assert_eq!(docset.count(&alive_bitset), 3);
+    }
+
     #[test]
     fn test_empty() {
         let bitset = BitSet::with_max_value(1000);
@@ -1,12 +1,18 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
 use crate::fieldnorm::FieldNormReader;
-use crate::query::Explanation;
+use crate::query::{Bm25Similarity, Explanation, Similarity, SimilarityScorer, TermStatistics};
 use crate::schema::Field;
 use crate::{Score, Searcher, Term};
 
-const K1: Score = 1.2;
-const B: Score = 0.75;
+pub(crate) const K1: Score = 1.2;
+pub(crate) const B: Score = 0.75;
+
+/// A term frequency larger than any term frequency of a document, used to compute upper bounds
+/// of the scores.
+const MAX_TERM_FREQ: u32 = 2_013_265_944;
 
 /// An interface to compute the statistics needed in BM25 scoring.
 ///
@@ -55,15 +61,28 @@ pub(crate) fn idf(doc_freq: u64, doc_count: u64) -> Score {
     (1.0 + x).ln()
 }
 
-fn cached_tf_component(fieldnorm: u32, average_fieldnorm: Score) -> Score {
-    K1 * (1.0 - B + B * fieldnorm as Score / average_fieldnorm)
+fn idf_explanation(term_doc_freq: u64, total_num_docs: u64) -> Explanation {
+    let mut idf_explain = Explanation::new(
+        "idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))",
+        idf(term_doc_freq, total_num_docs),
+    );
+    idf_explain.add_const(
+        "n, number of docs containing this term",
+        term_doc_freq as Score,
+    );
+    idf_explain.add_const("N, total number of docs", total_num_docs as Score);
+    idf_explain
 }
 
-fn compute_tf_cache(average_fieldnorm: Score) -> [Score; 256] {
+fn cached_tf_component(fieldnorm: u32, average_fieldnorm: Score, k1: Score, b: Score) -> Score {
+    k1 * (1.0 - b + b * fieldnorm as Score / average_fieldnorm)
+}
+
+fn compute_tf_cache(average_fieldnorm: Score, k1: Score, b: Score) -> [Score; 256] {
     let mut cache: [Score; 256] = [0.0; 256];
     for (fieldnorm_id, cache_mut) in cache.iter_mut().enumerate() {
         let fieldnorm = FieldNormReader::id_to_fieldnorm(fieldnorm_id as u8);
-        *cache_mut = cached_tf_component(fieldnorm, average_fieldnorm);
+        *cache_mut = cached_tf_component(fieldnorm, average_fieldnorm, k1, b);
     }
     cache
 }
@@ -75,23 +94,27 @@ pub struct Bm25Params {
 }
 
 /// A struct used for computing BM25 scores.
+///
+/// Fields scored with another [`Similarity`] than BM25 get their scores from the
+/// [`SimilarityScorer`] of their terms.
 #[derive(Clone)]
 pub struct Bm25Weight {
     idf_explain: Option<Explanation>,
     weight: Score,
     cache: [Score; 256],
     average_fieldnorm: Score,
+    k1: Score,
+    b: Score,
+    similarity_scorer: Option<Arc<dyn SimilarityScorer>>,
+    max_similarity_score: Score,
 }
 
 impl Bm25Weight {
     /// Increase the weight by a multiplicative factor.
     pub fn boost_by(&self, boost: Score) -> Bm25Weight {
-        Bm25Weight {
-            idf_explain: self.idf_explain.clone(),
-            weight: self.weight * boost,
-            cache: self.cache,
-            average_fieldnorm: self.average_fieldnorm,
-        }
+        let mut boosted_weight = self.clone();
+        boosted_weight.weight *= boost;
+        boosted_weight
     }
 
     /// Construct a [Bm25Weight] for a phrase of terms.
@@ -99,35 +122,70 @@ impl Bm25Weight {
         statistics: &dyn Bm25StatisticsProvider,
         terms: &[Term],
     ) -> crate::Result<Bm25Weight> {
-        assert!(!terms.is_empty(), "Bm25 requires at least one term");
-        let field = terms[0].field();
-        for term in &terms[1..] {
-            assert_eq!(
-                term.field(),
-                field,
-                "All terms must belong to the same field."
-            );
+        let term_statistics = TermStatistics::for_terms(statistics, terms)?;
+        Ok(Bm25Weight::for_term_statistics(&term_statistics, K1, B))
+    }
+
+    /// Construct a [Bm25Weight] for a phrase of terms, scored with the given [`Similarity`].
+    pub fn for_terms_with_similarity(
+        statistics: &dyn Bm25StatisticsProvider,
+        terms: &[Term],
+        similarity: &dyn Similarity,
+    ) -> crate::Result<Bm25Weight> {
+        let term_statistics = TermStatistics::for_terms(statistics, terms)?;
+        if let Some(bm25_similarity) = similarity.downcast_ref::<Bm25Similarity>() {
+            // BM25 does not need to go through a `SimilarityScorer`.
+            return Ok(Bm25Weight::for_term_statistics(
+                &term_statistics,
+                bm25_similarity.k1(),
+                bm25_similarity.b(),
+            ));
         }
+        Ok(Bm25Weight::for_similarity_scorer(
+            similarity.scorer(&term_statistics),
+        ))
+    }
 
-        let total_num_tokens = statistics.total_num_tokens(field)?;
-        let total_num_docs = statistics.total_num_docs()?;
-        let average_fieldnorm = total_num_tokens as Score / total_num_docs as Score;
-
-        if terms.len() == 1 {
-            let term_doc_freq = statistics.doc_freq(&terms[0])?;
-            Ok(Bm25Weight::for_one_term(
-                term_doc_freq,
-                total_num_docs,
-                average_fieldnorm,
-            ))
+    /// Construct a [Bm25Weight] computing its scores with a [`SimilarityScorer`].
+    pub fn for_similarity_scorer(similarity_scorer: Box<dyn SimilarityScorer>) -> Bm25Weight {
+        let max_similarity_score = (0..=u8::MAX)
+            .map(|fieldnorm_id| {
+                similarity_scorer.score(
+                    MAX_TERM_FREQ,
+                    FieldNormReader::id_to_fieldnorm(fieldnorm_id),
+                )
+            })
+            .fold(0.0, Score::max);
+        Bm25Weight {
+            idf_explain: None,
+            weight: 1.0,
+            cache: [0.0; 256],
+            average_fieldnorm: 0.0,
+            k1: K1,
+            b: B,
+            similarity_scorer: Some(Arc::from(similarity_scorer)),
+            max_similarity_score,
+        }
+    }
+
+    pub(crate) fn for_term_statistics(
+        term_statistics: &TermStatistics,
+        k1: Score,
+        b: Score,
+    ) -> Bm25Weight {
+        let total_num_docs = term_statistics.total_num_docs;
+        let average_fieldnorm = term_statistics.average_fieldnorm();
+        if let [term_doc_freq] = term_statistics.doc_freqs[..] {
+            let idf_explain = idf_explanation(term_doc_freq, total_num_docs);
+            Bm25Weight::with_params(Some(idf_explain), average_fieldnorm, k1, b)
         } else {
-            let mut idf_sum: Score = 0.0;
-            for term in terms {
-                let term_doc_freq = statistics.doc_freq(term)?;
-                idf_sum += idf(term_doc_freq, total_num_docs);
-            }
+            let idf_sum: Score = term_statistics
+                .doc_freqs
+                .iter()
+                .map(|&term_doc_freq| idf(term_doc_freq, total_num_docs))
+                .sum();
             let idf_explain = Explanation::new("idf", idf_sum);
-            Ok(Bm25Weight::new(idf_explain, average_fieldnorm))
+            Bm25Weight::with_params(Some(idf_explain), average_fieldnorm, k1, b)
         }
     }
 
@@ -137,14 +195,7 @@ impl Bm25Weight {
         total_num_docs: u64,
         avg_fieldnorm: Score,
     ) -> Bm25Weight {
-        let idf = idf(term_doc_freq, total_num_docs);
-        let mut idf_explain =
-            Explanation::new("idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))", idf);
-        idf_explain.add_const(
-            "n, number of docs containing this term",
-            term_doc_freq as Score,
-        );
-        idf_explain.add_const("N, total number of docs", total_num_docs as Score);
+        let idf_explain = idf_explanation(term_doc_freq, total_num_docs);
         Bm25Weight::new(idf_explain, avg_fieldnorm)
     }
     /// Construct a [Bm25Weight] for a single term.
@@ -159,33 +210,56 @@ impl Bm25Weight {
     }
 
     pub(crate) fn new(idf_explain: Explanation, average_fieldnorm: Score) -> Bm25Weight {
-        let weight = idf_explain.value() * (1.0 + K1);
-        Bm25Weight {
-            idf_explain: Some(idf_explain),
-            weight,
-            cache: compute_tf_cache(average_fieldnorm),
-            average_fieldnorm,
-        }
+        Bm25Weight::with_params(Some(idf_explain), average_fieldnorm, K1, B)
     }
     pub(crate) fn new_without_explain(idf: f32, average_fieldnorm: Score) -> Bm25Weight {
-        let weight = idf * (1.0 + K1);
+        let mut bm25_weight = Bm25Weight::with_params(None, average_fieldnorm, K1, B);
+        bm25_weight.weight = idf * (1.0 + K1);
+        bm25_weight
+    }
+
+    fn with_params(
+        idf_explain: Option<Explanation>,
+        average_fieldnorm: Score,
+        k1: Score,
+        b: Score,
+    ) -> Bm25Weight {
+        let idf = idf_explain.as_ref().map(Explanation::value).unwrap_or(0.0);
         Bm25Weight {
-            idf_explain: None,
-            weight,
-            cache: compute_tf_cache(average_fieldnorm),
+            idf_explain,
+            weight: idf * (1.0 + k1),
+            cache: compute_tf_cache(average_fieldnorm, k1, b),
             average_fieldnorm,
+            k1,
+            b,
+            similarity_scorer: None,
+            max_similarity_score: 0.0,
         }
     }
 
     /// Compute the BM25 score of a single document.
     #[inline]
     pub fn score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
+        if let Some(similarity_scorer) = &self.similarity_scorer {
+            let fieldnorm = FieldNormReader::id_to_fieldnorm(fieldnorm_id);
+            return self.weight * similarity_scorer.score(term_freq, fieldnorm);
+        }
         self.weight * self.tf_factor(fieldnorm_id, term_freq)
     }
 
     /// Compute the maximum possible BM25 score given this weight.
     pub fn max_score(&self) -> Score {
-        self.score(255u8, 2_013_265_944)
+        if self.similarity_scorer.is_some() {
+            return self.weight * self.max_similarity_score;
+        }
+        self.score(255u8, MAX_TERM_FREQ)
+    }
+
+    /// Returns true if the (fieldnorm id, term frequency) pairs maximizing the scores of the
+    /// blocks of postings, computed at indexing time with the default BM25 parameters, also
+    /// maximize the scores of this weight.
+    pub(crate) fn has_block_max_pairs(&self) -> bool {
+        self.similarity_scorer.is_none() && self.k1 == K1 && self.b == B
     }
 
     #[inline]
@@ -200,6 +274,13 @@ impl Bm25Weight {
         // The explain format is directly copied from Lucene's.
         // (So, Kudos to Lucene)
         let score = self.score(fieldnorm_id, term_freq);
+        if let Some(similarity_scorer) = &self.similarity_scorer {
+            let fieldnorm = FieldNormReader::id_to_fieldnorm(fieldnorm_id);
+            let mut explanation = Explanation::new("TermQuery, product of...", score);
+            explanation.add_const("boost", self.weight);
+            explanation.add_detail(similarity_scorer.explain(term_freq, fieldnorm));
+            return explanation;
+        }
 
         let norm = self.cache[fieldnorm_id as usize];
         let term_freq = term_freq as Score;
@@ -211,8 +292,8 @@ impl Bm25Weight {
         );
 
         tf_explanation.add_const("freq, occurrences of term within document", term_freq);
-        tf_explanation.add_const("k1, term saturation parameter", K1);
-        tf_explanation.add_const("b, length normalization parameter", B);
+        tf_explanation.add_const("k1, term saturation parameter", self.k1);
+        tf_explanation.add_const("b, length normalization parameter", self.b);
         tf_explanation.add_const(
             "dl, length of field",
             FieldNormReader::id_to_fieldnorm(fieldnorm_id) as Score,
@@ -220,7 +301,7 @@ impl Bm25Weight {
         tf_explanation.add_const("avgdl, average length of field", self.average_fieldnorm);
 
         let mut explanation = Explanation::new("TermQuery, product of...", score);
-        explanation.add_detail(Explanation::new("(K1+1)", K1 + 1.0));
+        explanation.add_detail(Explanation::new("(K1+1)", self.k1 + 1.0));
         if let Some(idf_explain) = &self.idf_explain {
             explanation.add_detail(idf_explain.clone());
         }
@@ -229,6 +310,16 @@ impl Bm25Weight {
     }
 }
 
+impl SimilarityScorer for Bm25Weight {
+    fn score(&self, term_freq: u32, fieldnorm: u32) -> Score {
+        Bm25Weight::score(self, FieldNormReader::fieldnorm_to_id(fieldnorm), term_freq)
+    }
+
+    fn explain(&self, term_freq: u32, fieldnorm: u32) -> Explanation {
+        Bm25Weight::explain(self, FieldNormReader::fieldnorm_to_id(fieldnorm), term_freq)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
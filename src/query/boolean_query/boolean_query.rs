@@ -1,14 +1,19 @@
 use super::boolean_weight::BooleanWeight;
 use crate::query::{EnableScoring, Occur, Query, SumCombiner, TermQuery, Weight};
 use crate::schema::{IndexRecordOption, Term};
+use crate::snippet::HighlightPattern;
 
 /// The boolean query returns a set of documents
 /// that matches the Boolean combination of constituent subqueries.
 ///
 /// The documents matched by the boolean query are those which
-/// - match all of the sub queries associated with the `Must` occurrence
+/// - match all of the sub queries associated with the `Must` or `Filter` occurrence
 /// - match none of the sub queries associated with the `MustNot` occurrence.
-/// - match at least one of the sub queries associated with the `Must` or `Should` occurrence.
+/// - match at least one of the sub queries associated with the `Must`, `Filter` or `Should`
+///   occurrence.
+///
+/// The sub queries associated with the `Filter` occurrence do not contribute to the score, and
+/// are executed with scoring disabled.
 ///
 /// You can combine other query types and their `Occur`ances into one `BooleanQuery`
 ///
@@ -158,7 +163,14 @@ impl Query for BooleanQuery {
         let sub_weights = self
             .subqueries
             .iter()
-            .map(|(occur, subquery)| Ok((*occur, subquery.weight(enable_scoring)?)))
+            .map(|(occur, subquery)| {
+                let sub_enable_scoring = if *occur == Occur::Filter {
+                    enable_scoring.into_disabled()
+                } else {
+                    enable_scoring
+                };
+                Ok((*occur, subquery.weight(sub_enable_scoring)?))
+            })
             .collect::<crate::Result<_>>()?;
         Ok(Box::new(BooleanWeight::with_minimum_number_should_match(
             sub_weights,
@@ -173,19 +185,33 @@ impl Query for BooleanQuery {
             subquery.query_terms(visitor);
         }
     }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        for (occur, subquery) in &self.subqueries {
+            if *occur != Occur::MustNot {
+                subquery.highlight_patterns(visitor);
+            }
+        }
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        for (_, subquery) in &self.subqueries {
+            subquery.named_queries(visitor);
+        }
+    }
 }
 
 impl BooleanQuery {
     /// Creates a new boolean query.
     pub fn new(subqueries: Vec<(Occur, Box<dyn Query>)>) -> BooleanQuery {
         // If the bool query includes at least one should clause
-        // and no Must or MustNot clauses, the default value is 1. Otherwise, the default value is
-        // 0. Keep pace with Elasticsearch.
+        // and no Must, MustNot or Filter clauses, the default value is 1. Otherwise, the default
+        // value is 0. Keep pace with Elasticsearch.
         let mut minimum_required = 0;
         for (occur, _) in &subqueries {
             match occur {
                 Occur::Should => minimum_required = 1,
-                Occur::Must | Occur::MustNot => {
+                Occur::Must | Occur::MustNot | Occur::Filter => {
                     minimum_required = 0;
                     break;
                 }
@@ -9,7 +9,7 @@ use crate::query::score_combiner::{DoNothingCombiner, ScoreCombiner};
 use crate::query::term_query::TermScorer;
 use crate::query::weight::{for_each_docset_buffered, for_each_pruning_scorer, for_each_scorer};
 use crate::query::{
-    intersect_scorers, BufferedUnionScorer, EmptyScorer, Exclude, Explanation, Occur,
+    intersect_scorers, BufferedUnionScorer, ConstScorer, EmptyScorer, Exclude, Explanation, Occur,
     RequiredOptionalScorer, Scorer, Weight,
 };
 use crate::{DocId, Score};
@@ -161,6 +161,16 @@ impl<TScoreCombiner: ScoreCombiner> BooleanWeight<TScoreCombiner> {
             Required(Box<dyn Scorer>),
         }
         let mut must_scorers = per_occur_scorers.remove(&Occur::Must);
+        if let Some(filter_scorers) = per_occur_scorers.remove(&Occur::Filter) {
+            // Filter clauses are required, but do not contribute to the score.
+            let filter_scorers = filter_scorers.into_iter().map(|filter_scorer| {
+                let filter_scorer: Box<dyn Scorer> = Box::new(ConstScorer::new(filter_scorer, 0.0));
+                filter_scorer
+            });
+            must_scorers
+                .get_or_insert_with(Vec::new)
+                .extend(filter_scorers);
+        }
         let should_opt = if let Some(mut should_scorers) = per_occur_scorers.remove(&Occur::Should)
         {
             let num_of_should_scorers = should_scorers.len();
@@ -253,10 +263,13 @@ impl<TScoreCombiner: ScoreCombiner + Sync> Weight for BooleanWeight<TScoreCombin
             Ok(Box::new(EmptyScorer))
         } else if self.weights.len() == 1 {
             let &(occur, ref weight) = &self.weights[0];
-            if occur == Occur::MustNot {
-                Ok(Box::new(EmptyScorer))
-            } else {
-                weight.scorer(reader, boost)
+            match occur {
+                Occur::MustNot => Ok(Box::new(EmptyScorer)),
+                Occur::Filter => Ok(Box::new(ConstScorer::new(
+                    weight.scorer(reader, boost)?,
+                    0.0,
+                ))),
+                Occur::Must | Occur::Should => weight.scorer(reader, boost),
             }
         } else if self.scoring_enabled {
             self.complex_scorer(reader, boost, &self.score_combiner_fn)
@@ -360,9 +373,10 @@ impl<TScoreCombiner: ScoreCombiner + Sync> Weight for BooleanWeight<TScoreCombin
     }
 }
 
+/// Returns true if the clauses with this occur contribute to the score.
 fn is_positive_occur(occur: Occur) -> bool {
     match occur {
         Occur::Must | Occur::Should => true,
-        Occur::MustNot => false,
+        Occur::MustNot | Occur::Filter => false,
     }
 }
@@ -287,6 +287,62 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_filter_score() -> crate::Result<()> {
+        let (index, text_field) = aux_test_helper()?;
+
+        let make_term_query = |text: &str| {
+            let term_query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            let query: Box<dyn Query> = Box::new(term_query);
+            query
+        };
+        let reader = index.reader()?;
+        let score_docs = |boolean_query: &dyn Query| {
+            let fruit = reader
+                .searcher()
+                .search(boolean_query, &TEST_COLLECTOR_WITH_SCORE)
+                .unwrap();
+            (fruit.docs().to_vec(), fruit.scores().to_vec())
+        };
+
+        let (must_docs, must_scores) =
+            score_docs(&BooleanQuery::new(vec![(Occur::Must, make_term_query("a"))]));
+        {
+            // The filter clause restricts the matches, without changing the scores.
+            let boolean_query = BooleanQuery::new(vec![
+                (Occur::Must, make_term_query("a")),
+                (Occur::Filter, make_term_query("b")),
+            ]);
+            let (docs, scores) = score_docs(&boolean_query);
+            assert_eq!(docs, vec![DocAddress::new(0, 0), DocAddress::new(0, 3)]);
+            assert_eq!(must_docs[0], docs[0]);
+            assert_nearly_equals!(scores[0], must_scores[0]);
+            assert_eq!(must_docs[2], docs[1]);
+            assert_nearly_equals!(scores[1], must_scores[2]);
+        }
+        {
+            // Should clauses are optional when there is a filter clause.
+            let boolean_query = BooleanQuery::new(vec![
+                (Occur::Should, make_term_query("a")),
+                (Occur::Filter, make_term_query("d")),
+            ]);
+            let (docs, scores) = score_docs(&boolean_query);
+            assert_eq!(docs, vec![DocAddress::new(0, 3), DocAddress::new(0, 4)]);
+            assert_nearly_equals!(scores[0], must_scores[2]);
+            assert_nearly_equals!(scores[1], 0.0);
+        }
+        {
+            let boolean_query = BooleanQuery::new(vec![(Occur::Filter, make_term_query("c"))]);
+            let (docs, scores) = score_docs(&boolean_query);
+            assert_eq!(docs.len(), 4);
+            assert!(scores.iter().all(|score| *score == 0.0));
+        }
+        Ok(())
+    }
+
     #[test]
     pub fn test_explain() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -3,6 +3,8 @@ use std::fmt;
 use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
 use crate::fastfield::AliveBitSet;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::Field;
+use crate::snippet::HighlightPattern;
 use crate::{DocId, DocSet, Score, SegmentReader, Term};
 
 /// `BoostQuery` is a wrapper over a query used to boost its score.
@@ -51,6 +53,14 @@ impl Query for BoostQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor)
     }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        self.query.highlight_patterns(visitor);
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        self.query.named_queries(visitor);
+    }
 }
 
 /// Weight associated to the BoostQuery.
@@ -130,6 +140,10 @@ impl<S: Scorer> Scorer for BoostScorer<S> {
     fn score(&mut self) -> Score {
         self.underlying.score() * self.boost
     }
+
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        self.underlying.matched_positions(visitor);
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use columnar::Column;
+use rustc_hash::FxHashMap;
+
+use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
+use crate::fastfield::AliveBitSet;
+use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::Field;
+use crate::snippet::HighlightPattern;
+use crate::{DocId, DocSet, Score, SegmentReader, Term};
+
+/// The boosts of a [`BoostTableQuery`], keyed on the values of a fast field.
+#[derive(Clone, Debug)]
+enum BoostTable {
+    U64(Arc<FxHashMap<u64, Score>>),
+    Str(Arc<FxHashMap<String, Score>>),
+}
+
+/// `BoostTableQuery` is a wrapper over a query, boosting the score of its documents with a table
+/// of boosts keyed on the values of a fast field.
+///
+/// The table is supplied at query time, e.g. click-through-rate boosts keyed on a document id
+/// field, or boosts keyed on the terms of a category field. It can be updated without reindexing.
+///
+/// The document set matched by the `BoostTableQuery` is strictly the same as the underlying query.
+/// The score of each document is the score of the underlying query multiplied by the boost of its
+/// fast field value. Documents without a value in the table keep their score. If a document has
+/// several values in the table, the largest boost applies.
+pub struct BoostTableQuery {
+    query: Box<dyn Query>,
+    field_name: String,
+    table: BoostTable,
+}
+
+impl BoostTableQuery {
+    /// Builds a `BoostTableQuery` with boosts keyed on the values of a `u64` fast field.
+    pub fn for_u64_field(
+        query: Box<dyn Query>,
+        field_name: String,
+        boosts: HashMap<u64, Score>,
+    ) -> BoostTableQuery {
+        BoostTableQuery {
+            query,
+            field_name,
+            table: BoostTable::U64(Arc::new(boosts.into_iter().collect())),
+        }
+    }
+
+    /// Builds a `BoostTableQuery` with boosts keyed on the terms of a `str` fast field.
+    pub fn for_str_field(
+        query: Box<dyn Query>,
+        field_name: String,
+        boosts: HashMap<String, Score>,
+    ) -> BoostTableQuery {
+        BoostTableQuery {
+            query,
+            field_name,
+            table: BoostTable::Str(Arc::new(boosts.into_iter().collect())),
+        }
+    }
+}
+
+impl Clone for BoostTableQuery {
+    fn clone(&self) -> Self {
+        BoostTableQuery {
+            query: self.query.box_clone(),
+            field_name: self.field_name.clone(),
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for BoostTableQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let num_boosts = match &self.table {
+            BoostTable::U64(boosts) => boosts.len(),
+            BoostTable::Str(boosts) => boosts.len(),
+        };
+        write!(
+            f,
+            "BoostTable(query={:?}, field={}, num_boosts={})",
+            self.query, self.field_name, num_boosts
+        )
+    }
+}
+
+impl Query for BoostTableQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let weight_without_boost = self.query.weight(enable_scoring)?;
+        let boosted_weight = if enable_scoring.is_scoring_enabled() {
+            Box::new(BoostTableWeight {
+                weight: weight_without_boost,
+                field_name: self.field_name.clone(),
+                table: self.table.clone(),
+            })
+        } else {
+            weight_without_boost
+        };
+        Ok(boosted_weight)
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        self.query.highlight_patterns(visitor);
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        self.query.named_queries(visitor);
+    }
+}
+
+/// Weight associated to the `BoostTableQuery`.
+struct BoostTableWeight {
+    weight: Box<dyn Weight>,
+    field_name: String,
+    table: BoostTable,
+}
+
+impl BoostTableWeight {
+    /// Returns the boosts of the segment, or `None` if the segment has no fast field column.
+    ///
+    /// For `str` fast fields, the values of the column are term ordinals, so the table is
+    /// translated to the term ordinals of the segment.
+    fn segment_boosts(&self, reader: &SegmentReader) -> crate::Result<Option<SegmentBoosts>> {
+        match &self.table {
+            BoostTable::U64(boosts) => {
+                let column_opt = reader.fast_fields().column_opt::<u64>(&self.field_name)?;
+                Ok(column_opt.map(|column| SegmentBoosts {
+                    column,
+                    boosts: boosts.clone(),
+                }))
+            }
+            BoostTable::Str(boosts) => {
+                let Some(str_column) = reader.fast_fields().str(&self.field_name)? else {
+                    return Ok(None);
+                };
+                let dictionary = str_column.dictionary();
+                let mut term_ord_boosts =
+                    FxHashMap::with_capacity_and_hasher(boosts.len(), Default::default());
+                for (term, boost) in boosts.iter() {
+                    if let Some(term_ord) = dictionary.term_ord(term)? {
+                        term_ord_boosts.insert(term_ord, *boost);
+                    }
+                }
+                Ok(Some(SegmentBoosts {
+                    column: str_column.ords().clone(),
+                    boosts: Arc::new(term_ord_boosts),
+                }))
+            }
+        }
+    }
+}
+
+/// The fast field column of a segment, with the boosts keyed on its values.
+struct SegmentBoosts {
+    column: Column<u64>,
+    boosts: Arc<FxHashMap<u64, Score>>,
+}
+
+impl SegmentBoosts {
+    /// Returns the largest boost of the values of `doc`, if any of them is in the table.
+    fn doc_boost(&self, doc: DocId) -> Option<Score> {
+        self.column
+            .values_for_doc(doc)
+            .filter_map(|value| self.boosts.get(&value).copied())
+            .reduce(Score::max)
+    }
+}
+
+impl Weight for BoostTableWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader, boost)?;
+        let Some(segment_boosts) = self.segment_boosts(reader)? else {
+            return Ok(scorer);
+        };
+        if segment_boosts.boosts.is_empty() {
+            return Ok(scorer);
+        }
+        Ok(Box::new(BoostTableScorer {
+            underlying: scorer,
+            segment_boosts,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let underlying_explanation = self.weight.explain(reader, doc)?;
+        let table_boost = self
+            .segment_boosts(reader)?
+            .and_then(|segment_boosts| segment_boosts.doc_boost(doc));
+        let Some(table_boost) = table_boost else {
+            return Ok(underlying_explanation);
+        };
+        let score = underlying_explanation.value() * table_boost;
+        let mut explanation = Explanation::new_with_string(
+            format!(
+                "Boost table x{table_boost} on field {} of ...",
+                self.field_name
+            ),
+            score,
+        );
+        explanation.add_detail(underlying_explanation);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+struct BoostTableScorer {
+    underlying: Box<dyn Scorer>,
+    segment_boosts: SegmentBoosts,
+}
+
+impl DocSet for BoostTableScorer {
+    fn advance(&mut self) -> DocId {
+        self.underlying.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.underlying.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId; COLLECT_BLOCK_BUFFER_LEN]) -> usize {
+        self.underlying.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.underlying.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.underlying.size_hint()
+    }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        self.underlying.count(alive_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.underlying.count_including_deleted()
+    }
+}
+
+impl Scorer for BoostTableScorer {
+    fn score(&mut self) -> Score {
+        let score = self.underlying.score();
+        match self.segment_boosts.doc_boost(self.underlying.doc()) {
+            Some(table_boost) => score * table_boost,
+            None => score,
+        }
+    }
+
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        self.underlying.matched_positions(visitor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::BoostTableQuery;
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, Query};
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{DocAddress, Index, IndexWriter};
+
+    fn build_test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => 1u64, category => "books"))?;
+        index_writer.add_document(doc!(id => 2u64, category => "music"))?;
+        index_writer.add_document(doc!(id => 3u64, category => "books", category => "music"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_boost_table_query_u64_field() -> crate::Result<()> {
+        let index = build_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let boosts = HashMap::from_iter([(2u64, 3.0), (3, 2.0), (4, 10.0)]);
+        let query = BoostTableQuery::for_u64_field(Box::new(AllQuery), "id".to_string(), boosts);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert_eq!(
+            top_docs,
+            vec![
+                (3.0, DocAddress::new(0, 1)),
+                (2.0, DocAddress::new(0, 2)),
+                (1.0, DocAddress::new(0, 0)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_boost_table_query_str_field() -> crate::Result<()> {
+        let index = build_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let boosts = HashMap::from_iter([("books".to_string(), 2.0), ("music".to_string(), 0.5)]);
+        let query =
+            BoostTableQuery::for_str_field(Box::new(AllQuery), "category".to_string(), boosts);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert_eq!(
+            top_docs,
+            vec![
+                (2.0, DocAddress::new(0, 0)),
+                (2.0, DocAddress::new(0, 2)),
+                (0.5, DocAddress::new(0, 1)),
+            ]
+        );
+        let explanation = query.explain(&searcher, DocAddress::new(0, 1))?;
+        assert_eq!(explanation.value(), 0.5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_boost_table_query_missing_field() -> crate::Result<()> {
+        let index = build_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let boosts = HashMap::from_iter([(1u64, 3.0)]);
+        let query =
+            BoostTableQuery::for_u64_field(Box::new(AllQuery), "missing".to_string(), boosts);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert!(top_docs.iter().all(|(score, _)| *score == 1.0));
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use super::combined_field_weight::CombinedFieldWeight;
+use crate::query::bm25::idf;
+use crate::query::{BooleanQuery, EnableScoring, Explanation, Occur, Query, Weight};
+use crate::schema::{Field, FieldType, IndexRecordOption};
+use crate::{Score, Term};
+
+/// A `CombinedFieldQuery` scores documents with BM25F, as if several text fields were a single
+/// field.
+///
+/// The occurrences of a term in the different fields are summed, each of them multiplied by
+/// the weight of its field, to get the term frequency of the combined field. The lengths of
+/// the fields are combined the same way. The combined term frequency and length are then
+/// scored with BM25, using the statistics of the combined field: its document frequency is
+/// estimated as the largest document frequency of the term among the fields.
+///
+/// Compared to summing the independent BM25 scores of each field, e.g. with a
+/// [`BooleanQuery`], a term occurring in several fields does not get its term frequency
+/// saturated separately in each of them, and the rarity of the term does not depend on the
+/// field it occurs in.
+///
+/// The documents matching any of the terms are returned, and the scores of the terms are
+/// summed.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::CombinedFieldQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, DocAddress, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "Moby Dick", body => "The whale hunt"))?;
+/// index_writer.add_document(doc!(title => "The Whale", body => "A whale is a big mammal"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = CombinedFieldQuery::new(vec![(title, 2.0), (body, 1.0)], &["whale"]);
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CombinedFieldQuery {
+    field_weights: Vec<(Field, Score)>,
+    /// For each term text, the term of each of the fields.
+    term_groups: Vec<Vec<Term>>,
+}
+
+impl fmt::Debug for CombinedFieldQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "CombinedFieldQuery(fields={:?}, terms={:?})",
+            self.field_weights, self.term_groups
+        )
+    }
+}
+
+impl CombinedFieldQuery {
+    /// Creates a new combined field query, matching the given term texts in the given fields.
+    ///
+    /// The term texts are not tokenized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no field is given, or if a weight is not positive.
+    pub fn new(field_weights: Vec<(Field, Score)>, term_texts: &[&str]) -> CombinedFieldQuery {
+        assert!(
+            !field_weights.is_empty(),
+            "A combined field query requires at least one field."
+        );
+        for &(field, weight) in &field_weights {
+            assert!(
+                weight > 0.0,
+                "The weight of {field:?} must be positive, got {weight}."
+            );
+        }
+        let term_groups = term_texts
+            .iter()
+            .map(|term_text| {
+                field_weights
+                    .iter()
+                    .map(|&(field, _)| Term::from_field_text(field, term_text))
+                    .collect()
+            })
+            .collect();
+        CombinedFieldQuery {
+            field_weights,
+            term_groups,
+        }
+    }
+
+    /// The fields of the query, with their weight.
+    pub fn field_weights(&self) -> &[(Field, Score)] {
+        &self.field_weights
+    }
+
+    fn single_term_query(&self, terms: &[Term]) -> CombinedFieldQuery {
+        CombinedFieldQuery {
+            field_weights: self.field_weights.clone(),
+            term_groups: vec![terms.to_vec()],
+        }
+    }
+
+    fn specialized_weight(
+        &self,
+        enable_scoring: EnableScoring<'_>,
+        terms: &[Term],
+    ) -> crate::Result<CombinedFieldWeight> {
+        let schema = enable_scoring.schema();
+        for &(field, _) in &self.field_weights {
+            let field_entry = schema.get_field_entry(field);
+            let has_freqs = match field_entry.field_type() {
+                FieldType::Str(text_options) => text_options
+                    .get_indexing_options()
+                    .map(|indexing_options| indexing_options.index_option().has_freq())
+                    .unwrap_or(false),
+                _ => false,
+            };
+            if !has_freqs {
+                return Err(crate::TantivyError::SchemaError(format!(
+                    "Applied combined field query on field {:?}, which is not a text field \
+                     indexed with frequencies",
+                    field_entry.name()
+                )));
+            }
+        }
+        let field_weights: Vec<Score> = self
+            .field_weights
+            .iter()
+            .map(|&(_, weight)| weight)
+            .collect();
+        let EnableScoring::Enabled {
+            statistics_provider,
+            ..
+        } = enable_scoring
+        else {
+            return Ok(CombinedFieldWeight::new(
+                terms.to_vec(),
+                field_weights,
+                Explanation::new("<no score>", 1.0),
+                1.0,
+                IndexRecordOption::Basic,
+                false,
+            ));
+        };
+        let total_num_docs = statistics_provider.total_num_docs()?;
+        let mut doc_freq = 0u64;
+        let mut total_num_tokens = 0.0;
+        for (term, &weight) in terms.iter().zip(&field_weights) {
+            doc_freq = doc_freq.max(statistics_provider.doc_freq(term)?);
+            total_num_tokens +=
+                weight * statistics_provider.total_num_tokens(term.field())? as Score;
+        }
+        let mut idf_explain = Explanation::new(
+            "idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))",
+            idf(doc_freq, total_num_docs),
+        );
+        idf_explain.add_const(
+            "n, largest number of docs containing this term in a field",
+            doc_freq as Score,
+        );
+        idf_explain.add_const("N, total number of docs", total_num_docs as Score);
+        Ok(CombinedFieldWeight::new(
+            terms.to_vec(),
+            field_weights,
+            idf_explain,
+            total_num_tokens / total_num_docs as Score,
+            IndexRecordOption::WithFreqs,
+            true,
+        ))
+    }
+}
+
+impl Query for CombinedFieldQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        if let [terms] = &self.term_groups[..] {
+            return Ok(Box::new(self.specialized_weight(enable_scoring, terms)?));
+        }
+        let term_queries: Vec<(Occur, Box<dyn Query>)> = self
+            .term_groups
+            .iter()
+            .map(|terms| -> (Occur, Box<dyn Query>) {
+                (Occur::Should, Box::new(self.single_term_query(terms)))
+            })
+            .collect();
+        BooleanQuery::new(term_queries).weight(enable_scoring)
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        for term in self.term_groups.iter().flatten() {
+            visitor(term, false);
+        }
+    }
+}
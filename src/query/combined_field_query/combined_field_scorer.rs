@@ -0,0 +1,131 @@
+use crate::docset::{DocSet, TERMINATED};
+use crate::fieldnorm::FieldNormReader;
+use crate::postings::{Postings, SegmentPostings};
+use crate::query::bm25::{B, K1};
+use crate::query::{Explanation, Scorer};
+use crate::{DocId, Score};
+
+/// Scores the union of the postings of a term in several fields with BM25F.
+pub struct CombinedFieldScorer {
+    /// The postings of the term in each of the fields.
+    postings: Vec<SegmentPostings>,
+    fieldnorm_readers: Vec<FieldNormReader>,
+    field_weights: Vec<Score>,
+    weight: Score,
+    average_fieldnorm: Score,
+    doc: DocId,
+}
+
+impl CombinedFieldScorer {
+    pub fn new(
+        postings: Vec<SegmentPostings>,
+        fieldnorm_readers: Vec<FieldNormReader>,
+        field_weights: Vec<Score>,
+        weight: Score,
+        average_fieldnorm: Score,
+    ) -> CombinedFieldScorer {
+        let mut scorer = CombinedFieldScorer {
+            postings,
+            fieldnorm_readers,
+            field_weights,
+            weight,
+            average_fieldnorm,
+            doc: TERMINATED,
+        };
+        scorer.update_doc();
+        scorer
+    }
+
+    fn update_doc(&mut self) -> DocId {
+        self.doc = self
+            .postings
+            .iter()
+            .map(DocSet::doc)
+            .min()
+            .unwrap_or(TERMINATED);
+        self.doc
+    }
+
+    /// Returns the term frequency and the length of the combined field, weighted by the
+    /// weights of the fields.
+    fn combined_term_freq_and_fieldnorm(&self) -> (Score, Score) {
+        let mut term_freq = 0.0;
+        let mut fieldnorm = 0.0;
+        for ((postings, fieldnorm_reader), &field_weight) in self
+            .postings
+            .iter()
+            .zip(&self.fieldnorm_readers)
+            .zip(&self.field_weights)
+        {
+            if postings.doc() == self.doc {
+                term_freq += field_weight * postings.term_freq() as Score;
+            }
+            fieldnorm += field_weight * fieldnorm_reader.fieldnorm(self.doc) as Score;
+        }
+        (term_freq, fieldnorm)
+    }
+
+    fn tf_norm(&self, fieldnorm: Score) -> Score {
+        K1 * (1.0 - B + B * fieldnorm / self.average_fieldnorm)
+    }
+
+    pub fn explain(&self, idf_explain: Explanation) -> Explanation {
+        let (term_freq, fieldnorm) = self.combined_term_freq_and_fieldnorm();
+        let mut tf_explanation = Explanation::new(
+            "freq / (freq + k1 * (1 - b + b * dl / avgdl))",
+            term_freq / (term_freq + self.tf_norm(fieldnorm)),
+        );
+        tf_explanation.add_const(
+            "freq, weighted sum of the occurrences of term within the fields",
+            term_freq,
+        );
+        tf_explanation.add_const("k1, term saturation parameter", K1);
+        tf_explanation.add_const("b, length normalization parameter", B);
+        tf_explanation.add_const("dl, weighted sum of the lengths of the fields", fieldnorm);
+        tf_explanation.add_const(
+            "avgdl, average weighted sum of the lengths of the fields",
+            self.average_fieldnorm,
+        );
+        let score = self.weight * tf_explanation.value();
+        let mut explanation = Explanation::new("BM25F, product of...", score);
+        explanation.add_detail(Explanation::new("(K1+1)", K1 + 1.0));
+        explanation.add_detail(idf_explain);
+        explanation.add_detail(tf_explanation);
+        explanation
+    }
+}
+
+impl DocSet for CombinedFieldScorer {
+    fn advance(&mut self) -> DocId {
+        for postings in &mut self.postings {
+            if postings.doc() == self.doc {
+                postings.advance();
+            }
+        }
+        self.update_doc()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        for postings in &mut self.postings {
+            if postings.doc() < target {
+                postings.seek(target);
+            }
+        }
+        self.update_doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.postings.iter().map(DocSet::size_hint).sum()
+    }
+}
+
+impl Scorer for CombinedFieldScorer {
+    fn score(&mut self) -> Score {
+        let (term_freq, fieldnorm) = self.combined_term_freq_and_fieldnorm();
+        self.weight * term_freq / (term_freq + self.tf_norm(fieldnorm))
+    }
+}
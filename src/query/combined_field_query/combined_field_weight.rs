@@ -0,0 +1,113 @@
+use super::combined_field_scorer::CombinedFieldScorer;
+use crate::docset::{DocSet, COLLECT_BLOCK_BUFFER_LEN};
+use crate::fieldnorm::FieldNormReader;
+use crate::index::SegmentReader;
+use crate::postings::SegmentPostings;
+use crate::query::bm25::K1;
+use crate::query::explanation::does_not_match;
+use crate::query::weight::{for_each_docset_buffered, for_each_scorer};
+use crate::query::{Explanation, Scorer, Weight};
+use crate::schema::IndexRecordOption;
+use crate::{DocId, Score, Term};
+
+pub struct CombinedFieldWeight {
+    /// The term of each of the fields.
+    terms: Vec<Term>,
+    field_weights: Vec<Score>,
+    idf_explain: Explanation,
+    average_fieldnorm: Score,
+    index_record_option: IndexRecordOption,
+    scoring_enabled: bool,
+}
+
+impl CombinedFieldWeight {
+    pub fn new(
+        terms: Vec<Term>,
+        field_weights: Vec<Score>,
+        idf_explain: Explanation,
+        average_fieldnorm: Score,
+        index_record_option: IndexRecordOption,
+        scoring_enabled: bool,
+    ) -> CombinedFieldWeight {
+        CombinedFieldWeight {
+            terms,
+            field_weights,
+            idf_explain,
+            average_fieldnorm,
+            index_record_option,
+            scoring_enabled,
+        }
+    }
+
+    fn specialized_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> crate::Result<CombinedFieldScorer> {
+        let mut postings = Vec::with_capacity(self.terms.len());
+        let mut fieldnorm_readers = Vec::with_capacity(self.terms.len());
+        for term in &self.terms {
+            let field = term.field();
+            let inverted_index = reader.inverted_index(field)?;
+            postings.push(
+                inverted_index
+                    .read_postings(term, self.index_record_option)?
+                    .unwrap_or_else(SegmentPostings::empty),
+            );
+            let fieldnorm_reader_opt = if self.scoring_enabled {
+                reader.fieldnorms_readers().get_field(field)?
+            } else {
+                None
+            };
+            fieldnorm_readers.push(
+                fieldnorm_reader_opt
+                    .unwrap_or_else(|| FieldNormReader::constant(reader.max_doc(), 1)),
+            );
+        }
+        let weight = boost * self.idf_explain.value() * (1.0 + K1);
+        Ok(CombinedFieldScorer::new(
+            postings,
+            fieldnorm_readers,
+            self.field_weights.clone(),
+            weight,
+            self.average_fieldnorm,
+        ))
+    }
+}
+
+impl Weight for CombinedFieldWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        Ok(Box::new(self.specialized_scorer(reader, boost)?))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.specialized_scorer(reader, 1.0)?;
+        if scorer.doc() > doc || scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = scorer.explain(self.idf_explain.clone());
+        explanation.add_context(format!("Terms={:?}", self.terms));
+        Ok(explanation)
+    }
+
+    fn for_each(
+        &self,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score),
+    ) -> crate::Result<()> {
+        let mut scorer = self.specialized_scorer(reader, 1.0)?;
+        for_each_scorer(&mut scorer, callback);
+        Ok(())
+    }
+
+    fn for_each_no_score(
+        &self,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(&[DocId]),
+    ) -> crate::Result<()> {
+        let mut scorer = self.specialized_scorer(reader, 1.0)?;
+        let mut buffer = [0u32; COLLECT_BLOCK_BUFFER_LEN];
+        for_each_docset_buffered(&mut scorer, &mut buffer, callback);
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+mod combined_field_query;
+mod combined_field_scorer;
+mod combined_field_weight;
+
+pub use self::combined_field_query::CombinedFieldQuery;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::TopDocs;
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, Score, Term};
+
+    fn doc_ids(top_docs: &[(Score, DocAddress)]) -> Vec<u32> {
+        top_docs
+            .iter()
+            .map(|(_, doc_address)| doc_address.doc_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_combined_field_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // "rust" appears in both fields.
+        index_writer.add_document(doc!(title => "rust", body => "rust rust"))?;
+        // "rust" only appears in the title, as well as in the title of the other documents.
+        index_writer.add_document(doc!(title => "rust", body => "cargo book"))?;
+        index_writer.add_document(doc!(title => "rust tutorial", body => "cargo"))?;
+        index_writer.add_document(doc!(title => "python", body => "snake"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = CombinedFieldQuery::new(vec![(title, 1.0), (body, 1.0)], &["rust"]);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(doc_ids(&top_docs), [0, 1, 2]);
+        for &(score, doc_address) in &top_docs {
+            assert_nearly_equals!(query.explain(&searcher, doc_address)?.value(), score);
+        }
+        assert_eq!(query.count(&searcher)?, 3);
+
+        // Summing the BM25 scores of the fields overrates the rare occurrences in the body.
+        let term_query = |field| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, "rust"),
+                IndexRecordOption::WithFreqs,
+            ))
+        };
+        let boolean_query = BooleanQuery::new(vec![
+            (Occur::Should, term_query(title)),
+            (Occur::Should, term_query(body)),
+        ]);
+        let boolean_top_docs = searcher.search(&boolean_query, &TopDocs::with_limit(10))?;
+        let ratio = |top_docs: &[(Score, DocAddress)]| top_docs[0].0 / top_docs[1].0;
+        assert!(ratio(&boolean_top_docs) > 2.0 * ratio(&top_docs));
+
+        // The weight of a field scales its term frequencies and lengths.
+        let weighted_query = CombinedFieldQuery::new(vec![(title, 1.0), (body, 3.0)], &["cargo"]);
+        let weighted_top_docs = searcher.search(&weighted_query, &TopDocs::with_limit(10))?;
+        assert_eq!(doc_ids(&weighted_top_docs), [2, 1]);
+
+        // The scores of several terms are summed.
+        let query = CombinedFieldQuery::new(vec![(title, 1.0), (body, 1.0)], &["rust", "snake"]);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_combined_field_query_requires_freqs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let query = CombinedFieldQuery::new(vec![(title, 1.0), (tag, 1.0)], &["rust"]);
+        assert!(matches!(
+            searcher.search(&query, &TopDocs::with_limit(1)),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use std::fmt;
 
 use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
+use crate::fastfield::AliveBitSet;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::snippet::HighlightPattern;
 use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
 
 /// `ConstScoreQuery` is a wrapper over a query to provide a constant score.
@@ -49,6 +51,14 @@ impl Query for ConstScoreQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor);
     }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        self.query.highlight_patterns(visitor);
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        self.query.named_queries(visitor);
+    }
 }
 
 struct ConstWeight {
@@ -130,6 +140,14 @@ impl<TDocSet: DocSet> DocSet for ConstScorer<TDocSet> {
     fn size_hint(&self) -> u32 {
         self.docset.size_hint()
     }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        self.docset.count(alive_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.docset.count_including_deleted()
+    }
 }
 
 impl<TDocSet: DocSet + 'static> Scorer for ConstScorer<TDocSet> {
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::query::Bm25StatisticsProvider;
+use crate::schema::Field;
+use crate::{Searcher, Term};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct FieldStatistics {
+    total_num_tokens: u64,
+    /// Document frequencies, keyed on the serialized value of the terms.
+    #[serde(with = "doc_freqs_as_pairs")]
+    doc_freqs: BTreeMap<Vec<u8>, u64>,
+}
+
+/// The corpus statistics used by BM25: number of documents, number of tokens of the fields, and
+/// document frequencies of the terms.
+///
+/// In a distributed search, each shard exports its statistics, the coordinator merges them with
+/// [`CorpusStatistics::merge`], and sends the merged statistics back to the shards. Searching
+/// with [`Searcher::search_with_statistics_provider`] and the merged statistics then scores the
+/// documents of all of the shards with the same, global, IDF.
+///
+/// The statistics are serializable, and their fields are identified by their [`Field`]: all of
+/// the shards must share the same schema.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorpusStatistics {
+    num_docs: u64,
+    fields: BTreeMap<Field, FieldStatistics>,
+}
+
+impl CorpusStatistics {
+    /// Computes the statistics of all of the terms of `fields`.
+    ///
+    /// This browses the whole term dictionaries of the fields, and can be expensive.
+    pub fn for_fields(searcher: &Searcher, fields: &[Field]) -> crate::Result<CorpusStatistics> {
+        let mut corpus_statistics = CorpusStatistics::with_num_tokens(searcher, fields)?;
+        for &field in fields {
+            let field_statistics = corpus_statistics.fields.entry(field).or_default();
+            for segment_reader in searcher.segment_readers() {
+                let inverted_index = segment_reader.inverted_index(field)?;
+                let mut term_stream = inverted_index.terms().stream()?;
+                while term_stream.advance() {
+                    *field_statistics
+                        .doc_freqs
+                        .entry(term_stream.key().to_vec())
+                        .or_default() += u64::from(term_stream.value().doc_freq);
+                }
+            }
+        }
+        Ok(corpus_statistics)
+    }
+
+    /// Computes the statistics of `terms`, and of the fields of these terms.
+    pub fn for_terms(searcher: &Searcher, terms: &[Term]) -> crate::Result<CorpusStatistics> {
+        let mut fields: Vec<Field> = terms.iter().map(Term::field).collect();
+        fields.sort();
+        fields.dedup();
+        let mut corpus_statistics = CorpusStatistics::with_num_tokens(searcher, &fields)?;
+        for term in terms {
+            let doc_freq = searcher.doc_freq(term)?;
+            corpus_statistics
+                .fields
+                .entry(term.field())
+                .or_default()
+                .doc_freqs
+                .insert(term.serialized_value_bytes().to_vec(), doc_freq);
+        }
+        Ok(corpus_statistics)
+    }
+
+    fn with_num_tokens(searcher: &Searcher, fields: &[Field]) -> crate::Result<CorpusStatistics> {
+        let mut corpus_statistics = CorpusStatistics {
+            num_docs: searcher.total_num_docs()?,
+            fields: BTreeMap::new(),
+        };
+        for &field in fields {
+            let total_num_tokens = searcher.total_num_tokens(field)?;
+            corpus_statistics.fields.insert(
+                field,
+                FieldStatistics {
+                    total_num_tokens,
+                    doc_freqs: BTreeMap::new(),
+                },
+            );
+        }
+        Ok(corpus_statistics)
+    }
+
+    /// Adds the statistics of `other`, computed on another shard.
+    pub fn merge(&mut self, other: &CorpusStatistics) {
+        self.num_docs += other.num_docs;
+        for (field, other_field_statistics) in &other.fields {
+            let field_statistics = self.fields.entry(*field).or_default();
+            field_statistics.total_num_tokens += other_field_statistics.total_num_tokens;
+            for (term_bytes, doc_freq) in &other_field_statistics.doc_freqs {
+                *field_statistics
+                    .doc_freqs
+                    .entry(term_bytes.clone())
+                    .or_default() += doc_freq;
+            }
+        }
+    }
+
+    /// Returns the number of documents of the corpus, deleted documents included.
+    pub fn num_docs(&self) -> u64 {
+        self.num_docs
+    }
+}
+
+/// The statistics of the fields and terms which were not exported are 0.
+impl Bm25StatisticsProvider for CorpusStatistics {
+    fn total_num_tokens(&self, field: Field) -> crate::Result<u64> {
+        Ok(self
+            .fields
+            .get(&field)
+            .map_or(0, |field_statistics| field_statistics.total_num_tokens))
+    }
+
+    fn total_num_docs(&self) -> crate::Result<u64> {
+        Ok(self.num_docs)
+    }
+
+    fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
+        Ok(self
+            .fields
+            .get(&term.field())
+            .and_then(|field_statistics| {
+                field_statistics
+                    .doc_freqs
+                    .get(term.serialized_value_bytes())
+            })
+            .copied()
+            .unwrap_or(0))
+    }
+}
+
+/// Serializes the document frequencies as a list of pairs, as formats like JSON do not support
+/// maps keyed on bytes.
+mod doc_freqs_as_pairs {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        doc_freqs: &BTreeMap<Vec<u8>, u64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let pairs: Vec<(&Vec<u8>, &u64)> = doc_freqs.iter().collect();
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Vec<u8>, u64>, D::Error> {
+        let pairs: Vec<(Vec<u8>, u64)> = Vec::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CorpusStatistics;
+    use crate::collector::TopDocs;
+    use crate::query::{Bm25StatisticsProvider, QueryParser};
+    use crate::schema::{Schema, TEXT};
+    use crate::{assert_nearly_equals, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_corpus_statistics_global_idf() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let shard_docs = [
+            vec!["apple banana", "apple cherry", "apple"],
+            vec!["banana cherry", "banana banana", "durian"],
+        ];
+        let shards: Vec<Index> = shard_docs
+            .iter()
+            .map(|docs| {
+                let index = Index::create_in_ram(schema.clone());
+                let mut index_writer: IndexWriter = index.writer_for_tests()?;
+                for doc in docs {
+                    index_writer.add_document(doc!(body => *doc))?;
+                }
+                index_writer.commit()?;
+                Ok(index)
+            })
+            .collect::<crate::Result<_>>()?;
+        // A single index holding the documents of all of the shards.
+        let global_index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = global_index.writer_for_tests()?;
+        for docs in &shard_docs {
+            for doc in docs {
+                index_writer.add_document(doc!(body => *doc))?;
+            }
+            index_writer.commit()?;
+        }
+
+        let searchers: Vec<_> = shards
+            .iter()
+            .map(|shard| Ok(shard.reader()?.searcher()))
+            .collect::<crate::Result<_>>()?;
+        let mut global_statistics = CorpusStatistics::default();
+        for searcher in &searchers {
+            let shard_statistics = CorpusStatistics::for_fields(searcher, &[body])?;
+            let json = serde_json::to_string(&shard_statistics).unwrap();
+            assert_eq!(
+                serde_json::from_str::<CorpusStatistics>(&json).unwrap(),
+                shard_statistics
+            );
+            global_statistics.merge(&shard_statistics);
+        }
+        let banana = Term::from_field_text(body, "banana");
+        assert_eq!(global_statistics.num_docs(), 6);
+        assert_eq!(global_statistics.doc_freq(&banana)?, 3);
+        assert_eq!(global_statistics.total_num_tokens(body)?, 10);
+        let terms = [banana.clone()];
+        let mut term_statistics = CorpusStatistics::for_terms(&searchers[0], &terms)?;
+        term_statistics.merge(&CorpusStatistics::for_terms(&searchers[1], &terms)?);
+        assert_eq!(term_statistics.doc_freq(&banana)?, 3);
+        assert_eq!(
+            term_statistics.doc_freq(&Term::from_field_text(body, "apple"))?,
+            0
+        );
+
+        let query = QueryParser::for_index(&global_index, vec![body]).parse_query("banana")?;
+        let global_top_docs = global_index
+            .reader()?
+            .searcher()
+            .search(&query, &TopDocs::with_limit(1))?;
+        let shard_top_docs = searchers[1].search_with_statistics_provider(
+            &query,
+            &TopDocs::with_limit(1),
+            &global_statistics,
+        )?;
+        assert_nearly_equals!(shard_top_docs[0].0, global_top_docs[0].0);
+        let local_top_docs = searchers[1].search(&query, &TopDocs::with_limit(1))?;
+        assert!((local_top_docs[0].0 - global_top_docs[0].0).abs() > 0.01);
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use crate::query::{BooleanWeight, DisjunctionMaxCombiner, EnableScoring, Occur, Query, Weight};
+use crate::snippet::HighlightPattern;
 use crate::{Score, Term};
 
 /// The disjunction max query returns documents matching one or more wrapped queries,
@@ -109,6 +110,18 @@ impl Query for DisjunctionMaxQuery {
             disjunct.query_terms(visitor);
         }
     }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        for disjunct in &self.disjuncts {
+            disjunct.highlight_patterns(visitor);
+        }
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        for disjunct in &self.disjuncts {
+            disjunct.named_queries(visitor);
+        }
+    }
 }
 
 impl DisjunctionMaxQuery {
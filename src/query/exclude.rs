@@ -1,5 +1,6 @@
 use crate::docset::{DocSet, TERMINATED};
 use crate::query::Scorer;
+use crate::schema::Field;
 use crate::{DocId, Score};
 
 #[inline]
@@ -87,6 +88,10 @@ where
     fn score(&mut self) -> Score {
         self.underlying_docset.score()
     }
+
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        self.underlying_docset.matched_positions(visitor);
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+
+use crate::collector::TopDocs;
+use crate::docset::{DocSet, TERMINATED};
+use crate::index::SegmentId;
+use crate::query::explanation::does_not_match;
+use crate::query::{EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::snippet::HighlightPattern;
+use crate::{DocAddress, DocId, Score, SegmentReader, TantivyError, Term};
+
+/// The rank constant of the reciprocal rank fusion, as in the original paper.
+const DEFAULT_RANK_CONSTANT: Score = 60.0;
+
+/// How the ranked lists of the sub-queries of a [`FusionQuery`] are merged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScoreFusion {
+    /// Reciprocal rank fusion: a document ranked `rank` (starting at 1) by a sub-query of
+    /// weight `w` gets `w / (rank_constant + rank)`.
+    ///
+    /// Only the ranks matter, so the scores of the sub-queries do not need to be comparable.
+    ReciprocalRank {
+        /// Dampens the advantage of the top ranks. Defaults to 60.
+        rank_constant: Score,
+    },
+    /// Weighted sum of the scores of the sub-queries, each min-max normalized to `[0, 1]` over
+    /// its ranked list.
+    Linear,
+}
+
+impl Default for ScoreFusion {
+    fn default() -> ScoreFusion {
+        ScoreFusion::ReciprocalRank {
+            rank_constant: DEFAULT_RANK_CONSTANT,
+        }
+    }
+}
+
+/// `FusionQuery` merges the top documents of several queries into one ranked list, e.g. for
+/// hybrid search combining a lexical query with a [`KnnQuery`](crate::query::KnnQuery).
+///
+/// Each sub-query is run over the whole index when the weight of the query is created, and
+/// its top `limit` documents are kept. The documents of these lists are then scored with the
+/// [`ScoreFusion`] of the query, and are the only documents matched by the query. The fusion
+/// requires a searcher, so the query cannot be used with scoring disabled from a schema only.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{FusionQuery, KnnQuery, QueryParser, ScoreFusion};
+/// use tantivy::schema::{BytesOptions, Schema, VectorOptions, VectorSimilarity, TEXT};
+/// use tantivy::vector::vector_to_bytes;
+/// use tantivy::{doc, DocAddress, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let embedding = schema_builder.add_bytes_field(
+///     "embedding",
+///     BytesOptions::default().set_vector(VectorOptions::new(2, VectorSimilarity::Cosine)),
+/// );
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(
+///     title => "running shoes",
+///     embedding => vector_to_bytes(&[1.0, 0.0]),
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "trail sneakers",
+///     embedding => vector_to_bytes(&[0.9, 0.1]),
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "shoes polish",
+///     embedding => vector_to_bytes(&[0.0, 1.0]),
+/// ))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let lexical_query = QueryParser::for_index(&index, vec![title]).parse_query("shoes")?;
+/// let knn_query = KnnQuery::new(embedding, vec![1.0, 0.05], 2);
+/// let query = FusionQuery::new(
+///     vec![(lexical_query, 1.0), (Box::new(knn_query), 1.0)],
+///     10,
+///     ScoreFusion::default(),
+/// );
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+/// // "running shoes" is at the top of both lists.
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+/// assert_eq!(top_docs.len(), 3);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FusionQuery {
+    queries: Vec<(Box<dyn Query>, Score)>,
+    limit: usize,
+    fusion: ScoreFusion,
+}
+
+impl Clone for FusionQuery {
+    fn clone(&self) -> Self {
+        FusionQuery {
+            queries: self
+                .queries
+                .iter()
+                .map(|(query, weight)| (query.box_clone(), *weight))
+                .collect(),
+            limit: self.limit,
+            fusion: self.fusion,
+        }
+    }
+}
+
+impl FusionQuery {
+    /// Creates a query fusing the top `limit` documents of each of the weighted `queries`.
+    pub fn new(
+        queries: Vec<(Box<dyn Query>, Score)>,
+        limit: usize,
+        fusion: ScoreFusion,
+    ) -> FusionQuery {
+        FusionQuery {
+            queries,
+            limit,
+            fusion,
+        }
+    }
+
+    /// Returns the score fusion of the query.
+    pub fn fusion(&self) -> ScoreFusion {
+        self.fusion
+    }
+}
+
+impl Query for FusionQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let searcher = enable_scoring.searcher().ok_or_else(|| {
+            TantivyError::InvalidArgument(
+                "FusionQuery requires a searcher to rank the documents of its queries".to_string(),
+            )
+        })?;
+        let mut ranked_lists = Vec::with_capacity(self.queries.len());
+        for (query, weight) in &self.queries {
+            let top_docs = if self.limit == 0 {
+                Vec::new()
+            } else {
+                searcher.search(query.as_ref(), &TopDocs::with_limit(self.limit))?
+            };
+            ranked_lists.push(RankedList::new(top_docs, *weight));
+        }
+        let mut fused_scores: HashMap<DocAddress, Score> = HashMap::new();
+        for ranked_list in &ranked_lists {
+            for (rank, (score, doc_address)) in ranked_list.top_docs.iter().enumerate() {
+                *fused_scores.entry(*doc_address).or_default() +=
+                    ranked_list.fused_score(self.fusion, rank, *score);
+            }
+        }
+        let segment_ids: Vec<SegmentId> = searcher
+            .segment_readers()
+            .iter()
+            .map(SegmentReader::segment_id)
+            .collect();
+        let mut docs_per_segment: HashMap<SegmentId, Vec<(DocId, Score)>> = HashMap::new();
+        for (doc_address, score) in fused_scores {
+            docs_per_segment
+                .entry(segment_ids[doc_address.segment_ord as usize])
+                .or_default()
+                .push((doc_address.doc_id, score));
+        }
+        for docs in docs_per_segment.values_mut() {
+            docs.sort_unstable_by_key(|(doc, _)| *doc);
+        }
+        Ok(Box::new(FusionWeight {
+            ranked_lists,
+            fusion: self.fusion,
+            segment_ids,
+            docs_per_segment,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        for (query, _) in &self.queries {
+            query.query_terms(visitor);
+        }
+    }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        for (query, _) in &self.queries {
+            query.highlight_patterns(visitor);
+        }
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        for (query, _) in &self.queries {
+            query.named_queries(visitor);
+        }
+    }
+}
+
+/// The top documents of a sub-query, with the bounds of their scores.
+struct RankedList {
+    top_docs: Vec<(Score, DocAddress)>,
+    weight: Score,
+    min_score: Score,
+    max_score: Score,
+}
+
+impl RankedList {
+    fn new(top_docs: Vec<(Score, DocAddress)>, weight: Score) -> RankedList {
+        let (min_score, max_score) = top_docs.iter().fold(
+            (Score::INFINITY, Score::NEG_INFINITY),
+            |(min_score, max_score), (score, _)| (min_score.min(*score), max_score.max(*score)),
+        );
+        RankedList {
+            top_docs,
+            weight,
+            min_score,
+            max_score,
+        }
+    }
+
+    /// Returns the contribution of the document at the 0-based `rank` of the list.
+    fn fused_score(&self, fusion: ScoreFusion, rank: usize, score: Score) -> Score {
+        match fusion {
+            ScoreFusion::ReciprocalRank { rank_constant } => {
+                self.weight / (rank_constant + (rank + 1) as Score)
+            }
+            ScoreFusion::Linear => {
+                let normalized_score = if self.max_score > self.min_score {
+                    (score - self.min_score) / (self.max_score - self.min_score)
+                } else {
+                    1.0
+                };
+                self.weight * normalized_score
+            }
+        }
+    }
+}
+
+/// Weight of the [`FusionQuery`].
+struct FusionWeight {
+    ranked_lists: Vec<RankedList>,
+    fusion: ScoreFusion,
+    segment_ids: Vec<SegmentId>,
+    docs_per_segment: HashMap<SegmentId, Vec<(DocId, Score)>>,
+}
+
+impl Weight for FusionWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some(docs) = self.docs_per_segment.get(&reader.segment_id()) else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        Ok(Box::new(FusionScorer {
+            docs: docs.clone(),
+            cursor: 0,
+            boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let segment_ord = self
+            .segment_ids
+            .iter()
+            .position(|segment_id| *segment_id == reader.segment_id())
+            .ok_or_else(|| does_not_match(doc))?;
+        let doc_address = DocAddress::new(segment_ord as u32, doc);
+        let mut query_explanations = Vec::new();
+        for (query_ord, ranked_list) in self.ranked_lists.iter().enumerate() {
+            let Some(rank) = ranked_list
+                .top_docs
+                .iter()
+                .position(|(_, top_doc_address)| *top_doc_address == doc_address)
+            else {
+                continue;
+            };
+            let score = ranked_list.top_docs[rank].0;
+            let mut query_explanation = Explanation::new_with_string(
+                format!("Query #{query_ord}"),
+                ranked_list.fused_score(self.fusion, rank, score),
+            );
+            query_explanation.add_const("Rank", (rank + 1) as Score);
+            query_explanation.add_const("Score", score);
+            query_explanation.add_const("Weight", ranked_list.weight);
+            query_explanations.push(query_explanation);
+        }
+        if query_explanations.is_empty() {
+            return Err(does_not_match(doc));
+        }
+        let fused_score = query_explanations.iter().map(Explanation::value).sum();
+        let mut explanation = Explanation::new("FusionQuery", fused_score);
+        explanation.add_context(format!("{:?}", self.fusion));
+        for query_explanation in query_explanations {
+            explanation.add_detail(query_explanation);
+        }
+        Ok(explanation)
+    }
+}
+
+/// Scorer over the fused documents of a segment, sorted by doc id.
+struct FusionScorer {
+    docs: Vec<(DocId, Score)>,
+    cursor: usize,
+    boost: Score,
+}
+
+impl DocSet for FusionScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.docs.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs
+            .get(self.cursor)
+            .map(|(doc, _)| *doc)
+            .unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len() as u32
+    }
+}
+
+impl Scorer for FusionScorer {
+    fn score(&mut self) -> Score {
+        self.docs[self.cursor].1 * self.boost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FusionQuery, ScoreFusion};
+    use crate::collector::TopDocs;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{EnableScoring, KnnQuery, Query, TermQuery};
+    use crate::schema::{
+        BytesOptions, Field, IndexRecordOption, Schema, VectorOptions, VectorSimilarity, TEXT,
+    };
+    use crate::vector::vector_to_bytes;
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, Score, Term};
+
+    fn create_index() -> crate::Result<(Index, Field, Field)> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let vector_field = schema_builder.add_bytes_field(
+            "vector",
+            BytesOptions::default().set_vector(VectorOptions::new(2, VectorSimilarity::DotProduct)),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        // Lexical ranking for "apple": doc 0, doc 1. Vector ranking: doc 2, doc 1, doc 0.
+        index_writer.add_document(doc!(
+            text_field => "apple apple",
+            vector_field => vector_to_bytes(&[0.1, 0.0]),
+        ))?;
+        index_writer.add_document(doc!(
+            text_field => "apple pie with a lot of cream",
+            vector_field => vector_to_bytes(&[0.5, 0.0]),
+        ))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(
+            text_field => "banana",
+            vector_field => vector_to_bytes(&[0.9, 0.0]),
+        ))?;
+        index_writer.add_document(doc!(text_field => "cherry"))?;
+        index_writer.commit()?;
+        Ok((index, text_field, vector_field))
+    }
+
+    fn fusion_query(
+        text_field: Field,
+        vector_field: Field,
+        weights: [Score; 2],
+        fusion: ScoreFusion,
+    ) -> FusionQuery {
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "apple"),
+            IndexRecordOption::WithFreqs,
+        );
+        let knn_query = KnnQuery::new(vector_field, vec![1.0, 0.0], 3);
+        FusionQuery::new(
+            vec![
+                (Box::new(term_query), weights[0]),
+                (Box::new(knn_query), weights[1]),
+            ],
+            3,
+            fusion,
+        )
+    }
+
+    #[test]
+    fn test_fusion_query_reciprocal_rank() -> crate::Result<()> {
+        let (index, text_field, vector_field) = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let query = fusion_query(text_field, vector_field, [1.0, 1.0], ScoreFusion::default());
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        let expected = [
+            (1.0 / 61.0 + 1.0 / 63.0, DocAddress::new(0, 0)),
+            (1.0 / 62.0 + 1.0 / 62.0, DocAddress::new(0, 1)),
+            (1.0 / 61.0, DocAddress::new(1, 0)),
+        ];
+        assert_eq!(top_docs.len(), expected.len());
+        for ((score, doc_address), (expected_score, expected_doc_address)) in
+            top_docs.iter().zip(expected)
+        {
+            assert_eq!(*doc_address, expected_doc_address);
+            assert_nearly_equals!(*score, expected_score);
+            assert_nearly_equals!(query.explain(&searcher, *doc_address)?.value(), *score);
+        }
+        assert!(query.explain(&searcher, DocAddress::new(1, 1)).is_err());
+
+        // Only the vector ranking counts if the lexical one has no weight.
+        let query = fusion_query(text_field, vector_field, [0.0, 1.0], ScoreFusion::default());
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        assert_eq!(top_docs[0].1, DocAddress::new(1, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fusion_query_linear() -> crate::Result<()> {
+        let (index, text_field, vector_field) = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let query = fusion_query(text_field, vector_field, [1.0, 0.5], ScoreFusion::Linear);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        // The top document of each list gets its weight, the last one gets 0.
+        let expected = [
+            (1.0, DocAddress::new(0, 0)),
+            (0.5, DocAddress::new(1, 0)),
+            (0.5 * 0.5, DocAddress::new(0, 1)),
+        ];
+        assert_eq!(top_docs.len(), expected.len());
+        for ((score, doc_address), (expected_score, expected_doc_address)) in
+            top_docs.iter().zip(expected)
+        {
+            assert_nearly_equals!(*score, expected_score);
+            assert_eq!(*doc_address, expected_doc_address);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fusion_query_requires_searcher() -> crate::Result<()> {
+        let (index, text_field, vector_field) = create_index()?;
+        let query = fusion_query(text_field, vector_field, [1.0, 1.0], ScoreFusion::Linear);
+        let schema = index.schema();
+        assert!(query
+            .weight(EnableScoring::disabled_from_schema(&schema))
+            .is_err());
+        let empty_query = FusionQuery::new(Vec::new(), 10, ScoreFusion::default());
+        let searcher = index.reader()?.searcher();
+        assert!(searcher
+            .search(&empty_query, &TopDocs::with_limit(10))?
+            .is_empty());
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use crate::docset::{DocSet, TERMINATED};
 use crate::query::term_query::TermScorer;
 use crate::query::{EmptyScorer, Scorer};
+use crate::schema::Field;
 use crate::{DocId, Score};
 
 /// Returns the intersection scorer.
@@ -155,6 +156,14 @@ where
             + self.right.score()
             + self.others.iter_mut().map(Scorer::score).sum::<Score>()
     }
+
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        self.left.matched_positions(visitor);
+        self.right.matched_positions(visitor);
+        for other in &mut self.others {
+            other.matched_positions(visitor);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,402 @@
+use common::BitSet;
+
+use crate::collector::TopNComputer;
+use crate::docset::{DocSet, TERMINATED};
+use crate::query::explanation::does_not_match;
+use crate::query::{EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::{Field, VectorSimilarity};
+use crate::vector::{vector_options_for_query, VectorReader};
+use crate::{DocId, Score, SegmentReader};
+
+/// `KnnQuery` matches the `k` documents whose vector is the most similar to a query vector,
+/// and scores them by their similarity.
+///
+/// The field has to be a vector field, see [`crate::vector`]. If the field has an HNSW graph
+/// (see [`VectorOptions::set_hnsw`](crate::schema::VectorOptions::set_hnsw)), the search is
+/// approximate. Otherwise, or for segments without a graph, the similarity is computed
+/// for every vector.
+///
+/// The `k` nearest neighbors are computed per segment: use a
+/// [`TopDocs`](crate::collector::TopDocs) collector with a limit of `k` to get the global
+/// top `k`.
+///
+/// # Filtering
+///
+/// A `KnnQuery` can be combined with other queries in a
+/// [`BooleanQuery`](crate::query::BooleanQuery), but the other clauses then only apply
+/// to the `k` nearest neighbors, which may leave fewer than `k` results. A filter set with
+/// [`KnnQuery::with_filter`] is applied during the search instead, so that the `k` most
+/// similar documents matching the filter are returned. If the filter matches few documents,
+/// their similarity is computed exactly.
+#[derive(Debug)]
+pub struct KnnQuery {
+    field: Field,
+    query_vector: Vec<f32>,
+    k: usize,
+    ef_search: usize,
+    filter: Option<Box<dyn Query>>,
+}
+
+impl Clone for KnnQuery {
+    fn clone(&self) -> Self {
+        KnnQuery {
+            field: self.field,
+            query_vector: self.query_vector.clone(),
+            k: self.k,
+            ef_search: self.ef_search,
+            filter: self.filter.as_ref().map(|filter| filter.box_clone()),
+        }
+    }
+}
+
+impl KnnQuery {
+    /// Creates a query matching the `k` documents whose vector in `field` are the most
+    /// similar to `query_vector`.
+    pub fn new(field: Field, query_vector: Vec<f32>, k: usize) -> KnnQuery {
+        KnnQuery {
+            field,
+            query_vector,
+            k,
+            ef_search: k,
+            filter: None,
+        }
+    }
+
+    /// Only matches documents matching `filter`.
+    #[must_use]
+    pub fn with_filter(mut self, filter: Box<dyn Query>) -> KnnQuery {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Sets the number of candidates kept while searching the HNSW graph.
+    ///
+    /// Values larger than `k` improve the recall at the expense of speed.
+    /// Defaults to `k`.
+    #[must_use]
+    pub fn with_ef_search(mut self, ef_search: usize) -> KnnQuery {
+        self.ef_search = ef_search;
+        self
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+impl Query for KnnQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let vector_options =
+            vector_options_for_query(enable_scoring.schema(), self.field, &self.query_vector)?;
+        let filter_weight = if let Some(filter) = &self.filter {
+            let filter_enable_scoring = match enable_scoring.searcher() {
+                Some(searcher) => EnableScoring::disabled_from_searcher(searcher),
+                None => EnableScoring::disabled_from_schema(enable_scoring.schema()),
+            };
+            Some(filter.weight(filter_enable_scoring)?)
+        } else {
+            None
+        };
+        Ok(Box::new(KnnWeight {
+            field: self.field,
+            query_vector: self.query_vector.clone(),
+            similarity: vector_options.similarity(),
+            k: self.k,
+            ef_search: self.ef_search.max(self.k),
+            filter_weight,
+        }))
+    }
+}
+
+/// Weight of the [`KnnQuery`].
+pub struct KnnWeight {
+    field: Field,
+    query_vector: Vec<f32>,
+    similarity: VectorSimilarity,
+    k: usize,
+    ef_search: usize,
+    filter_weight: Option<Box<dyn Weight>>,
+}
+
+impl KnnWeight {
+    fn exact_top_k(
+        &self,
+        vector_reader: &VectorReader,
+        accept: impl Fn(DocId) -> bool,
+    ) -> Vec<(Score, DocId)> {
+        let mut top_n: TopNComputer<Score, DocId> = TopNComputer::new(self.k);
+        let mut vector_buffer = Vec::with_capacity(self.query_vector.len());
+        for vector_ord in 0..vector_reader.num_vectors() {
+            let doc = vector_reader.doc(vector_ord);
+            if !accept(doc) {
+                continue;
+            }
+            vector_reader.read_vector(vector_ord, &mut vector_buffer);
+            top_n.push(
+                self.similarity.score(&self.query_vector, &vector_buffer),
+                doc,
+            );
+        }
+        top_n
+            .into_sorted_vec()
+            .into_iter()
+            .map(|cdoc| (cdoc.feature, cdoc.doc))
+            .collect()
+    }
+
+    fn top_k(&self, reader: &SegmentReader) -> crate::Result<Vec<(Score, DocId)>> {
+        if self.k == 0 {
+            return Ok(Vec::new());
+        }
+        let Some(vector_reader) = reader.vectors_readers().get_field(self.field)? else {
+            return Ok(Vec::new());
+        };
+        let filter_bitset = if let Some(filter_weight) = &self.filter_weight {
+            let mut filter_bitset = BitSet::with_max_value(reader.max_doc());
+            filter_weight.for_each_no_score(reader, &mut |docs| {
+                for &doc in docs {
+                    filter_bitset.insert(doc);
+                }
+            })?;
+            Some(filter_bitset)
+        } else {
+            None
+        };
+        let alive_bitset = reader.alive_bitset();
+        let accept = |doc: DocId| {
+            alive_bitset.map_or(true, |alive_bitset| alive_bitset.is_alive(doc))
+                && filter_bitset
+                    .as_ref()
+                    .map_or(true, |filter_bitset| filter_bitset.contains(doc))
+        };
+        // Searching the graph is pointless if the filter leaves fewer candidates than
+        // the graph search would visit.
+        let is_filter_selective = filter_bitset
+            .as_ref()
+            .is_some_and(|filter_bitset| filter_bitset.len() <= self.ef_search);
+        if !is_filter_selective {
+            if let Some(mut top_k) = vector_reader.search_hnsw(
+                &self.query_vector,
+                self.similarity,
+                self.ef_search,
+                accept,
+            ) {
+                top_k.truncate(self.k);
+                return Ok(top_k);
+            }
+        }
+        Ok(self.exact_top_k(&vector_reader, accept))
+    }
+}
+
+impl Weight for KnnWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let mut top_k = self.top_k(reader)?;
+        if top_k.is_empty() {
+            return Ok(Box::new(EmptyScorer));
+        }
+        top_k.sort_unstable_by_key(|(_, doc)| *doc);
+        Ok(Box::new(KnnScorer {
+            top_k,
+            cursor: 0,
+            boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("KnnQuery", scorer.score()))
+    }
+}
+
+/// Scorer over the nearest neighbors of a segment, sorted by doc id.
+struct KnnScorer {
+    top_k: Vec<(Score, DocId)>,
+    cursor: usize,
+    boost: Score,
+}
+
+impl DocSet for KnnScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.top_k.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.top_k
+            .get(self.cursor)
+            .map(|(_, doc)| *doc)
+            .unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.top_k.len() as u32
+    }
+}
+
+impl Scorer for KnnScorer {
+    fn score(&mut self) -> Score {
+        self.top_k[self.cursor].0 * self.boost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::KnnQuery;
+    use crate::collector::TopDocs;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{
+        BytesOptions, Field, HnswOptions, IndexRecordOption, Schema, VectorOptions,
+        VectorSimilarity, STRING,
+    };
+    use crate::vector::vector_to_bytes;
+    use crate::{DocAddress, Index, IndexWriter, Searcher, Term};
+
+    fn test_vector(i: u64) -> [f32; 4] {
+        let mut state = i.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        [0; 4].map(|_| {
+            state ^= state >> 29;
+            state = state.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            (state >> 40) as f32 / (1u64 << 24) as f32
+        })
+    }
+
+    fn create_index(vector_options: VectorOptions) -> crate::Result<(Index, Field, Field)> {
+        let mut schema_builder = Schema::builder();
+        let vector_field = schema_builder
+            .add_bytes_field("vector", BytesOptions::default().set_vector(vector_options));
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..2_000u64 {
+            let tag = if i % 10 == 0 { "rare" } else { "common" };
+            index_writer.add_document(doc!(
+                vector_field => vector_to_bytes(&test_vector(i)),
+                tag_field => tag,
+            ))?;
+            if i % 700 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        Ok((index, vector_field, tag_field))
+    }
+
+    fn top_docs(searcher: &Searcher, query: &dyn Query, k: usize) -> HashSet<DocAddress> {
+        searcher
+            .search(query, &TopDocs::with_limit(k))
+            .unwrap()
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect()
+    }
+
+    #[test]
+    fn test_knn_query_recall() -> crate::Result<()> {
+        let vector_options =
+            VectorOptions::new(4, VectorSimilarity::Euclidean).set_hnsw(HnswOptions::default());
+        let (index, vector_field, tag_field) = create_index(vector_options)?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.segment_readers().len() > 1);
+        let query_vector = vec![0.5, 0.2, 0.7, 0.1];
+        let tag_query = |tag: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(tag_field, tag),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let check_recall = |query: KnnQuery, filter: Option<&str>| {
+            let filter_query = filter.map(tag_query);
+            let exact: HashSet<DocAddress> = searcher
+                .knn(vector_field, &query_vector, 10, filter_query.as_deref())
+                .unwrap()
+                .into_iter()
+                .map(|(_, doc_address)| doc_address)
+                .collect();
+            let approximate = top_docs(&searcher, &query, 10);
+            assert_eq!(approximate.len(), 10);
+            assert!(approximate.intersection(&exact).count() >= 9);
+        };
+        let query = KnnQuery::new(vector_field, query_vector.clone(), 10).with_ef_search(50);
+        check_recall(query.clone(), None);
+        check_recall(
+            query.clone().with_filter(tag_query("common")),
+            Some("common"),
+        );
+        check_recall(query.clone().with_filter(tag_query("rare")), Some("rare"));
+
+        // As a boolean clause, the filter only applies to the nearest neighbors.
+        let boolean_query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(query) as Box<dyn Query>),
+            (Occur::Must, tag_query("rare")),
+        ]);
+        assert!(top_docs(&searcher, &boolean_query, 10).len() < 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_knn_query_merge_and_exact_fallback() -> crate::Result<()> {
+        for vector_options in [
+            VectorOptions::new(4, VectorSimilarity::DotProduct),
+            VectorOptions::new(4, VectorSimilarity::Cosine).set_hnsw(HnswOptions {
+                max_connections: 8,
+                ef_construction: 40,
+            }),
+        ] {
+            let (index, vector_field, tag_field) = create_index(vector_options)?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.delete_term(Term::from_field_text(tag_field, "rare"));
+            index_writer.commit()?;
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let segment_reader = searcher.segment_reader(0);
+            let vector_reader = segment_reader
+                .vectors_readers()
+                .get_field(vector_field)?
+                .unwrap();
+            assert_eq!(vector_reader.num_vectors(), 1_800);
+            assert_eq!(
+                vector_reader.has_hnsw_graph(),
+                vector_options.hnsw_options().is_some()
+            );
+            let query_vector = vec![0.1, 0.9, 0.3, 0.4];
+            let exact: HashSet<DocAddress> = searcher
+                .knn(vector_field, &query_vector, 5, None)?
+                .into_iter()
+                .map(|(_, doc_address)| doc_address)
+                .collect();
+            let query = KnnQuery::new(vector_field, query_vector, 5).with_ef_search(50);
+            let approximate = top_docs(&searcher, &query, 5);
+            assert!(approximate.intersection(&exact).count() >= 4);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_knn_query_invalid() -> crate::Result<()> {
+        let vector_options = VectorOptions::new(4, VectorSimilarity::Euclidean);
+        let (index, vector_field, tag_field) = create_index(vector_options)?;
+        let searcher = index.reader()?.searcher();
+        let query = KnnQuery::new(vector_field, vec![1.0, 2.0], 3);
+        assert!(searcher.search(&query, &TopDocs::with_limit(3)).is_err());
+        let query = KnnQuery::new(tag_field, vec![1.0, 2.0, 3.0, 4.0], 3);
+        assert!(searcher.search(&query, &TopDocs::with_limit(3)).is_err());
+        let query = KnnQuery::new(vector_field, vec![1.0, 2.0, 3.0, 4.0], 0);
+        assert!(top_docs(&searcher, &query, 3).is_empty());
+        Ok(())
+    }
+}
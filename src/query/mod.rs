@@ -4,18 +4,26 @@ mod bitset;
 mod bm25;
 mod boolean_query;
 mod boost_query;
+mod boost_table_query;
+mod combined_field_query;
 mod const_score_query;
+mod corpus_statistics;
 mod disjunction;
 mod disjunction_max_query;
 mod empty_query;
 mod exclude;
 mod exist_query;
 mod explanation;
+mod fusion_query;
 mod fuzzy_query;
 mod intersection;
+mod knn_query;
 mod more_like_this;
+mod named_query;
 mod phrase_prefix_query;
 mod phrase_query;
+mod prefix_query;
+mod proximity_boost_query;
 mod query;
 mod query_parser;
 mod range_query;
@@ -23,6 +31,7 @@ mod regex_query;
 mod reqopt_scorer;
 mod scorer;
 mod set_query;
+mod similarity;
 mod term_query;
 mod union;
 mod weight;
@@ -39,28 +48,44 @@ pub use self::bitset::BitSetDocSet;
 pub use self::bm25::{Bm25StatisticsProvider, Bm25Weight};
 pub use self::boolean_query::{BooleanQuery, BooleanWeight};
 pub use self::boost_query::{BoostQuery, BoostWeight};
+pub use self::boost_table_query::BoostTableQuery;
+pub use self::combined_field_query::CombinedFieldQuery;
 pub use self::const_score_query::{ConstScoreQuery, ConstScorer};
+pub use self::corpus_statistics::CorpusStatistics;
 pub use self::disjunction_max_query::DisjunctionMaxQuery;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
 pub use self::exist_query::ExistsQuery;
 pub use self::explanation::Explanation;
-#[cfg(test)]
-pub(crate) use self::fuzzy_query::DfaWrapper;
+pub use self::fusion_query::{FusionQuery, ScoreFusion};
+pub(crate) use self::fuzzy_query::{levenshtein_automaton_builder, DfaWrapper};
 pub use self::fuzzy_query::FuzzyTermQuery;
 pub use self::intersection::{intersect_scorers, Intersection};
+pub use self::knn_query::{KnnQuery, KnnWeight};
 pub use self::more_like_this::{MoreLikeThisQuery, MoreLikeThisQueryBuilder};
+pub use self::named_query::NamedQuery;
 pub use self::phrase_prefix_query::PhrasePrefixQuery;
 pub use self::phrase_query::regex_phrase_query::{wildcard_query_to_regex_str, RegexPhraseQuery};
 pub use self::phrase_query::PhraseQuery;
+pub use self::prefix_query::{PrefixQuery, PrefixQueryRewrite};
+pub(crate) use self::proximity_boost_query::min_window;
+pub use self::proximity_boost_query::{
+    ProximityBoost, ProximityBoostQuery, ProximitySegmentTweaker,
+};
 pub use self::query::{EnableScoring, Query, QueryClone};
-pub use self::query_parser::{QueryParser, QueryParserError};
+pub use self::query_parser::{
+    QueryParser, QueryParserError, QueryRelaxer, Relaxation, RelaxedSearch,
+};
 pub use self::range_query::*;
 pub use self::regex_query::RegexQuery;
 pub use self::reqopt_scorer::RequiredOptionalScorer;
 pub use self::score_combiner::{DisjunctionMaxCombiner, ScoreCombiner, SumCombiner};
 pub use self::scorer::Scorer;
 pub use self::set_query::TermSetQuery;
+pub use self::similarity::{
+    Bm25Similarity, DfrSimilarity, LmDirichletSimilarity, Similarity, SimilarityManager,
+    SimilarityScorer, TermStatistics, TfIdfSimilarity,
+};
 pub use self::term_query::TermQuery;
 pub use self::union::BufferedUnionScorer;
 #[cfg(test)]
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use crate::query::{EnableScoring, Query, Weight};
+use crate::snippet::HighlightPattern;
+use crate::Term;
+
+/// `NamedQuery` gives a name to a query, typically a clause of a
+/// [`BooleanQuery`](crate::query::BooleanQuery).
+///
+/// The named query matches and scores documents exactly as the underlying query. Its name
+/// makes it possible to report which clauses matched each hit, with the
+/// [`MatchedQueriesCollector`](crate::collector::MatchedQueriesCollector).
+pub struct NamedQuery {
+    name: String,
+    query: Box<dyn Query>,
+}
+
+impl NamedQuery {
+    /// Names the given query.
+    pub fn new(name: impl ToString, query: Box<dyn Query>) -> NamedQuery {
+        NamedQuery {
+            name: name.to_string(),
+            query,
+        }
+    }
+
+    /// Returns the name of the query.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the underlying query.
+    pub fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
+}
+
+impl Clone for NamedQuery {
+    fn clone(&self) -> Self {
+        NamedQuery {
+            name: self.name.clone(),
+            query: self.query.box_clone(),
+        }
+    }
+}
+
+impl fmt::Debug for NamedQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Named(name={:?}, query={:?})", self.name, self.query)
+    }
+}
+
+impl Query for NamedQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        self.query.weight(enable_scoring)
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor);
+    }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        self.query.highlight_patterns(visitor);
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        visitor(&self.name, self.query.as_ref());
+        self.query.named_queries(visitor);
+    }
+}
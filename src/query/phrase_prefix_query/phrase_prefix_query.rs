@@ -4,6 +4,7 @@ use super::{prefix_end, PhrasePrefixWeight};
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, InvertedIndexRangeWeight, Query, Weight};
 use crate::schema::{Field, IndexRecordOption, Term};
+use crate::snippet::HighlightPattern;
 
 const DEFAULT_MAX_EXPANSIONS: u32 = 50;
 
@@ -112,7 +113,12 @@ impl PhrasePrefixQuery {
         let terms = self.phrase_terms();
         let bm25_weight_opt = match enable_scoring {
             EnableScoring::Enabled { searcher, .. } => {
-                Some(Bm25Weight::for_terms(searcher, &terms)?)
+                let similarity = searcher.index().similarity_for_field(self.field)?;
+                Some(Bm25Weight::for_terms_with_similarity(
+                    searcher,
+                    &terms,
+                    &*similarity,
+                )?)
             }
             EnableScoring::Disabled { .. } => None,
         };
@@ -162,4 +168,12 @@ impl Query for PhrasePrefixQuery {
             visitor(term, true);
         }
     }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        visitor(HighlightPattern::Phrase {
+            terms: &self.phrase_terms,
+            prefix: Some(&self.prefix),
+            slop: 0,
+        });
+    }
 }
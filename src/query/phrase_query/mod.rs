@@ -17,7 +17,7 @@ pub(crate) mod tests {
     use super::*;
     use crate::collector::tests::{TEST_COLLECTOR_WITHOUT_SCORE, TEST_COLLECTOR_WITH_SCORE};
     use crate::index::Index;
-    use crate::query::{EnableScoring, QueryParser, Weight};
+    use crate::query::{EnableScoring, Query, QueryParser, Scorer, Weight};
     use crate::schema::{Schema, Term, TEXT};
     use crate::{assert_nearly_equals, DocAddress, DocId, IndexWriter, TERMINATED};
 
@@ -72,6 +72,29 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_phrase_scorer_matched_positions() -> crate::Result<()> {
+        let index = create_index(&["c a b d a"])?;
+        let text_field = index.schema().get_field("text").unwrap();
+        let searcher = index.reader()?.searcher();
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text_field, "a"),
+            Term::from_field_text(text_field, "b"),
+        ]);
+        let phrase_weight =
+            phrase_query.weight(EnableScoring::disabled_from_searcher(&searcher))?;
+        let mut phrase_scorer = phrase_weight.scorer(searcher.segment_reader(0), 1.0)?;
+        assert_eq!(phrase_scorer.doc(), 0);
+        let mut positions = Vec::new();
+        phrase_scorer.matched_positions(&mut |field, position| {
+            assert_eq!(field, text_field);
+            positions.push(position);
+        });
+        positions.sort();
+        assert_eq!(positions, vec![1, 2, 4]);
+        Ok(())
+    }
+
     #[test]
     pub fn test_phrase_query_simple() -> crate::Result<()> {
         let index = create_index(&["a b b d c g c", "a b a b c"])?;
@@ -2,6 +2,7 @@ use super::PhraseWeight;
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, Query, Weight};
 use crate::schema::{Field, IndexRecordOption, Term};
+use crate::snippet::HighlightPattern;
 
 /// `PhraseQuery` matches a specific sequence of words.
 ///
@@ -119,9 +120,16 @@ impl PhraseQuery {
         let terms = self.phrase_terms();
         let bm25_weight_opt = match enable_scoring {
             EnableScoring::Enabled {
+                searcher,
                 statistics_provider,
-                ..
-            } => Some(Bm25Weight::for_terms(statistics_provider, &terms)?),
+            } => {
+                let similarity = searcher.index().similarity_for_field(self.field)?;
+                Some(Bm25Weight::for_terms_with_similarity(
+                    statistics_provider,
+                    &terms,
+                    &*similarity,
+                )?)
+            }
             EnableScoring::Disabled { .. } => None,
         };
         let mut weight = PhraseWeight::new(self.phrase_terms.clone(), bm25_weight_opt);
@@ -146,4 +154,12 @@ impl Query for PhraseQuery {
             visitor(term, true);
         }
     }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        visitor(HighlightPattern::Phrase {
+            terms: &self.phrase_terms,
+            prefix: None,
+            slop: self.slop,
+        });
+    }
 }
@@ -5,6 +5,7 @@ use crate::fieldnorm::FieldNormReader;
 use crate::postings::Postings;
 use crate::query::bm25::Bm25Weight;
 use crate::query::{Intersection, Scorer};
+use crate::schema::Field;
 use crate::{DocId, Score};
 
 struct PostingsWithOffset<TPostings> {
@@ -55,6 +56,8 @@ pub struct PhraseScorer<TPostings: Postings> {
     left_slops: Vec<u8>,
     positions_buffer: Vec<u32>,
     slops_buffer: Vec<u8>,
+    // The field of the phrase, used to report the matched positions.
+    field_opt: Option<Field>,
 }
 
 /// Returns true if and only if the two sorted arrays contain a common element
@@ -393,6 +396,7 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
             left_slops: Vec::with_capacity(100),
             slops_buffer: Vec::with_capacity(100),
             positions_buffer: Vec::with_capacity(100),
+            field_opt: None,
         };
         if scorer.doc() != TERMINATED && !scorer.phrase_match() {
             scorer.advance();
@@ -400,6 +404,12 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
         scorer
     }
 
+    /// Sets the field of the phrase, so that the matched positions are reported.
+    pub(crate) fn with_field(mut self, field: Field) -> PhraseScorer<TPostings> {
+        self.field_opt = Some(field);
+        self
+    }
+
     pub fn phrase_count(&self) -> u32 {
         self.phrase_count
     }
@@ -547,6 +557,23 @@ impl<TPostings: Postings> Scorer for PhraseScorer<TPostings> {
             1.0f32
         }
     }
+
+    /// Visits the positions of all of the terms of the phrase in the current document.
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        let Some(field) = self.field_opt else {
+            return;
+        };
+        for ord in 0..self.num_terms {
+            self.intersection_docset
+                .docset_mut_specialized(ord)
+                .postings
+                .positions(&mut self.positions_buffer);
+            for &position in &self.positions_buffer {
+                visitor(field, position);
+            }
+        }
+        self.positions_buffer.clear();
+    }
 }
 
 #[cfg(test)]
@@ -60,12 +60,16 @@ impl PhraseWeight {
                 return Ok(None);
             }
         }
-        Ok(Some(PhraseScorer::new(
-            term_postings_list,
-            similarity_weight_opt,
-            fieldnorm_reader,
-            self.slop,
-        )))
+        let field = self.phrase_terms[0].1.field();
+        Ok(Some(
+            PhraseScorer::new(
+                term_postings_list,
+                similarity_weight_opt,
+                fieldnorm_reader,
+                self.slop,
+            )
+            .with_field(field),
+        ))
     }
 
     pub fn slop(&mut self, slop: u32) {
@@ -145,9 +145,16 @@ impl RegexPhraseQuery {
         let terms = self.phrase_terms();
         let bm25_weight_opt = match enable_scoring {
             EnableScoring::Enabled {
+                searcher,
                 statistics_provider,
-                ..
-            } => Some(Bm25Weight::for_terms(statistics_provider, &terms)?),
+            } => {
+                let similarity = searcher.index().similarity_for_field(self.field)?;
+                Some(Bm25Weight::for_terms_with_similarity(
+                    statistics_provider,
+                    &terms,
+                    &*similarity,
+                )?)
+            }
             EnableScoring::Disabled { .. } => None,
         };
         let weight = RegexPhraseWeight::new(
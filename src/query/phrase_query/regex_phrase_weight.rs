@@ -90,12 +90,15 @@ impl RegexPhraseWeight {
             posting_lists.push((offset, union));
         }
 
-        Ok(Some(PhraseScorer::new(
-            posting_lists,
-            similarity_weight_opt,
-            fieldnorm_reader,
-            self.slop,
-        )))
+        Ok(Some(
+            PhraseScorer::new(
+                posting_lists,
+                similarity_weight_opt,
+                fieldnorm_reader,
+                self.slop,
+            )
+            .with_field(self.field),
+        ))
     }
 
     /// Add all docs of the term to the docset
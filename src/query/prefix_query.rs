@@ -0,0 +1,248 @@
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+use super::phrase_prefix_query::prefix_end;
+use crate::query::{BooleanQuery, EnableScoring, InvertedIndexRangeWeight, Query, Weight};
+use crate::schema::{Field, Term};
+use crate::snippet::HighlightPattern;
+
+/// How a [`PrefixQuery`] is executed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrefixQueryRewrite {
+    /// Matching documents are collected in a bitset, and all get the same score.
+    ///
+    /// This is the cheapest way to run a prefix query, as the postings are only read once, and
+    /// no term statistics are required.
+    #[default]
+    ConstantScore,
+    /// The prefix is expanded into a [`BooleanQuery`] of `Should` term queries, so that
+    /// documents are scored by the terms they contain.
+    ///
+    /// This is only used if scoring is enabled, and falls back to `ConstantScore` otherwise.
+    ScoringBoolean,
+}
+
+/// A `PrefixQuery` matches all of the documents containing a term starting with a given prefix.
+///
+/// The number of terms the prefix expands to can be capped with
+/// [`PrefixQuery::set_max_expansions`]. Terms are expanded in lexicographic order, so the
+/// terms past the cap are ignored.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::PrefixQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter, Term};
+///
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// {
+///     let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+///     index_writer.add_document(doc!(
+///         title => "The Name of the Wind",
+///     ))?;
+///     index_writer.add_document(doc!(
+///         title => "The Diary of Muadib",
+///     ))?;
+///     index_writer.add_document(doc!(
+///         title => "A Dairy Cow",
+///     ))?;
+///     index_writer.add_document(doc!(
+///         title => "The Diary of a Young Girl",
+///     ))?;
+///     index_writer.commit()?;
+/// }
+///
+/// let reader = index.reader()?;
+/// let searcher = reader.searcher();
+///
+/// let query = PrefixQuery::new(Term::from_field_text(title, "d"));
+/// let count = searcher.search(&query, &Count)?;
+/// assert_eq!(count, 3);
+/// Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PrefixQuery {
+    // The offset is always 0. It is kept to be able to expose the prefix as a highlight pattern.
+    prefix: (usize, Term),
+    rewrite: PrefixQueryRewrite,
+    max_expansions: Option<u32>,
+}
+
+impl PrefixQuery {
+    /// Creates a new `PrefixQuery` matching the terms starting with `prefix`.
+    pub fn new(prefix: Term) -> PrefixQuery {
+        PrefixQuery {
+            prefix: (0, prefix),
+            rewrite: PrefixQueryRewrite::default(),
+            max_expansions: None,
+        }
+    }
+
+    /// Sets how the query is executed. Defaults to [`PrefixQueryRewrite::ConstantScore`].
+    pub fn set_rewrite(&mut self, rewrite: PrefixQueryRewrite) {
+        self.rewrite = rewrite;
+    }
+
+    /// Maximum number of terms to which the prefix will expand.
+    ///
+    /// With the constant score rewrite, the cap applies to each segment.
+    pub fn set_max_expansions(&mut self, value: u32) {
+        self.max_expansions = Some(value);
+    }
+
+    /// The [`Field`] this `PrefixQuery` is targeting.
+    pub fn field(&self) -> Field {
+        self.prefix.1.field()
+    }
+
+    /// The prefix the matched terms start with.
+    pub fn prefix(&self) -> &Term {
+        &self.prefix.1
+    }
+
+    fn term_from_value_bytes(&self, value_bytes: &[u8]) -> Term {
+        let mut term = Term::with_capacity(value_bytes.len());
+        term.set_field_and_type(self.field(), self.prefix.1.typ());
+        term.append_bytes(value_bytes);
+        term
+    }
+
+    /// Returns the terms starting with the prefix, across all segments, up to `max_expansions`.
+    fn expand_terms(&self, enable_scoring: &EnableScoring<'_>) -> crate::Result<Vec<Term>> {
+        let Some(searcher) = enable_scoring.searcher() else {
+            return Ok(Vec::new());
+        };
+        let prefix_bytes = self.prefix.1.serialized_value_bytes();
+        let end = prefix_end(prefix_bytes);
+        let max_expansions = self.max_expansions.map(|value| value as usize);
+        let mut expanded_values: BTreeSet<Vec<u8>> = BTreeSet::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(self.field())?;
+            let mut stream_builder = inverted_index.terms().range().ge(prefix_bytes);
+            if let Some(end) = &end {
+                stream_builder = stream_builder.lt(end);
+            }
+            let mut stream = stream_builder.into_stream()?;
+            let mut num_segment_terms = 0;
+            while stream.advance() {
+                if max_expansions.is_some_and(|max_expansions| num_segment_terms >= max_expansions)
+                {
+                    break;
+                }
+                num_segment_terms += 1;
+                expanded_values.insert(stream.key().to_vec());
+            }
+        }
+        Ok(expanded_values
+            .into_iter()
+            .take(max_expansions.unwrap_or(usize::MAX))
+            .map(|value_bytes| self.term_from_value_bytes(&value_bytes))
+            .collect())
+    }
+}
+
+impl Query for PrefixQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        if self.rewrite == PrefixQueryRewrite::ScoringBoolean && enable_scoring.is_scoring_enabled()
+        {
+            let terms = self.expand_terms(&enable_scoring)?;
+            return BooleanQuery::new_multiterms_query(terms).weight(enable_scoring);
+        }
+        let upper_bound = if let Some(end) = prefix_end(self.prefix.1.serialized_value_bytes()) {
+            Bound::Excluded(self.term_from_value_bytes(&end))
+        } else {
+            Bound::Unbounded
+        };
+        Ok(Box::new(InvertedIndexRangeWeight::new(
+            self.field(),
+            &Bound::Included(self.prefix.1.clone()),
+            &upper_bound,
+            self.max_expansions.map(u64::from),
+        )))
+    }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        visitor(HighlightPattern::Phrase {
+            terms: &[],
+            prefix: Some(&self.prefix),
+            slop: 0,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PrefixQuery, PrefixQueryRewrite};
+    use crate::collector::{Count, TopDocs};
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    fn build_test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "diary"))?;
+        index_writer.add_document(doc!(title => "dairy dairy"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "door"))?;
+        index_writer.add_document(doc!(title => "window"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_prefix_query() -> crate::Result<()> {
+        let index = build_test_index()?;
+        let title = index.schema().get_field("title").unwrap();
+        let searcher = index.reader()?.searcher();
+
+        let query = PrefixQuery::new(Term::from_field_text(title, "d"));
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert!(top_docs.iter().all(|(score, _)| *score == 1.0));
+
+        let query = PrefixQuery::new(Term::from_field_text(title, "da"));
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        let query = PrefixQuery::new(Term::from_field_text(title, "x"));
+        assert_eq!(searcher.search(&query, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefix_query_scoring_boolean() -> crate::Result<()> {
+        let index = build_test_index()?;
+        let title = index.schema().get_field("title").unwrap();
+        let searcher = index.reader()?.searcher();
+
+        let mut query = PrefixQuery::new(Term::from_field_text(title, "d"));
+        query.set_rewrite(PrefixQueryRewrite::ScoringBoolean);
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert_eq!(top_docs.len(), 3);
+        // "dairy" appears twice in its document.
+        assert!(top_docs[0].0 > top_docs[2].0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefix_query_max_expansions() -> crate::Result<()> {
+        let index = build_test_index()?;
+        let title = index.schema().get_field("title").unwrap();
+        let searcher = index.reader()?.searcher();
+
+        // The terms are "dairy", "diary" and "door", the expansion is capped to "dairy" and
+        // "diary".
+        let mut query = PrefixQuery::new(Term::from_field_text(title, "d"));
+        query.set_rewrite(PrefixQueryRewrite::ScoringBoolean);
+        query.set_max_expansions(2);
+        assert_eq!(searcher.search(&query, &TopDocs::with_limit(3))?.len(), 2);
+        Ok(())
+    }
+}
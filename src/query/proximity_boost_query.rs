@@ -0,0 +1,457 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::collector::{ScoreSegmentTweaker, ScoreTweaker};
+use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
+use crate::fastfield::AliveBitSet;
+use crate::postings::{Postings, SegmentPostings};
+use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::{Field, IndexRecordOption};
+use crate::snippet::HighlightPattern;
+use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
+
+/// `ProximityBoost` boosts the score of documents in which the given terms appear close to
+/// each other.
+///
+/// The proximity of a document is computed from the smallest window of positions containing
+/// all of the terms appearing in the document: it is `(num_terms - 1) / (window - 1)`, i.e. 1
+/// when the terms are adjacent, and decreasing as they get further apart. It is 0 when fewer
+/// than two of the terms appear in the document. The score is multiplied by
+/// `1 + factor * proximity`.
+///
+/// `ProximityBoost` can rescore the top documents, as a [`ScoreTweaker`] given to
+/// [`TopDocs::tweak_score`](crate::collector::TopDocs::tweak_score), or be applied to the
+/// matching documents of a query with a [`ProximityBoostQuery`].
+///
+/// The field of the terms is required to have positions indexed.
+#[derive(Clone, Debug)]
+pub struct ProximityBoost {
+    field: Field,
+    terms: Vec<Term>,
+    factor: Score,
+}
+
+impl ProximityBoost {
+    /// Creates a proximity boost for the given terms, with the given boost factor.
+    ///
+    /// There must be at least one term, and all terms must belong to the same field.
+    /// Duplicate terms are ignored.
+    pub fn new(terms: Vec<Term>, factor: Score) -> ProximityBoost {
+        assert!(
+            !terms.is_empty(),
+            "A proximity boost is required to have at least one term."
+        );
+        let field = terms[0].field();
+        assert!(
+            terms[1..].iter().all(|term| term.field() == field),
+            "All terms from a proximity boost must belong to the same field"
+        );
+        let terms: BTreeSet<Term> = terms.into_iter().collect();
+        ProximityBoost {
+            field,
+            terms: terms.into_iter().collect(),
+            factor,
+        }
+    }
+
+    /// The [`Field`] of the terms.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// The terms, sorted and deduplicated.
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// The boost factor.
+    pub fn factor(&self) -> Score {
+        self.factor
+    }
+
+    fn proximity_segment_tweaker(
+        &self,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<ProximitySegmentTweaker> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let has_positions = field_entry
+            .field_type()
+            .get_index_record_option()
+            .map(IndexRecordOption::has_positions)
+            .unwrap_or(false);
+        if !has_positions {
+            let field_name = field_entry.name();
+            return Err(TantivyError::SchemaError(format!(
+                "Applied proximity boost on field {field_name:?}, which does not have positions \
+                 indexed"
+            )));
+        }
+        let inverted_index = segment_reader.inverted_index(self.field)?;
+        let mut term_postings = Vec::with_capacity(self.terms.len());
+        for term in &self.terms {
+            if let Some(postings) =
+                inverted_index.read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
+            {
+                term_postings.push(postings);
+            }
+        }
+        Ok(ProximitySegmentTweaker {
+            term_postings,
+            factor: self.factor,
+            positions_buffer: Vec::new(),
+        })
+    }
+}
+
+impl ScoreTweaker<Score> for ProximityBoost {
+    type Child = ProximitySegmentTweaker;
+
+    fn segment_tweaker(&self, segment_reader: &SegmentReader) -> crate::Result<Self::Child> {
+        self.proximity_segment_tweaker(segment_reader)
+    }
+}
+
+/// Segment tweaker associated with the [`ProximityBoost`].
+///
+/// Documents are expected in increasing order.
+pub struct ProximitySegmentTweaker {
+    term_postings: Vec<SegmentPostings>,
+    factor: Score,
+    positions_buffer: Vec<Vec<u32>>,
+}
+
+impl ProximitySegmentTweaker {
+    /// Returns the number of terms appearing in the document and the number of positions of
+    /// the smallest window containing all of them, if at least two terms appear.
+    fn terms_window(&mut self, doc: DocId) -> Option<(usize, u32)> {
+        let mut num_terms = 0;
+        for postings in &mut self.term_postings {
+            if postings.doc() < doc {
+                postings.seek(doc);
+            }
+            if postings.doc() == doc {
+                if self.positions_buffer.len() == num_terms {
+                    self.positions_buffer.push(Vec::new());
+                }
+                postings.positions(&mut self.positions_buffer[num_terms]);
+                num_terms += 1;
+            }
+        }
+        if num_terms < 2 {
+            return None;
+        }
+        let window = min_window(&self.positions_buffer[..num_terms])?;
+        Some((num_terms, window))
+    }
+
+    /// Returns the factor the score is multiplied by, for `num_terms` terms appearing in a
+    /// window of `window` positions.
+    fn window_multiplier(&self, num_terms: usize, window: u32) -> Score {
+        // Distinct terms may share a position, e.g. synonyms.
+        let num_gaps = (num_terms - 1) as u32;
+        let proximity = num_gaps as Score / (window - 1).max(num_gaps) as Score;
+        1.0 + self.factor * proximity
+    }
+}
+
+impl ScoreSegmentTweaker<Score> for ProximitySegmentTweaker {
+    fn score(&mut self, doc: DocId, score: Score) -> Score {
+        match self.terms_window(doc) {
+            Some((num_terms, window)) => score * self.window_multiplier(num_terms, window),
+            None => score,
+        }
+    }
+}
+
+/// Returns the number of positions of the smallest window containing a position of each of the
+/// given lists of positions, or `None` if there are no lists.
+pub(crate) fn min_window(term_positions: &[Vec<u32>]) -> Option<u32> {
+    if term_positions.iter().any(Vec::is_empty) {
+        return None;
+    }
+    let mut positions: Vec<(u32, usize)> = term_positions
+        .iter()
+        .enumerate()
+        .flat_map(|(term_ord, positions)| positions.iter().map(move |&pos| (pos, term_ord)))
+        .collect();
+    positions.sort_unstable();
+    let mut counts = vec![0usize; term_positions.len()];
+    let mut num_covered_terms = 0;
+    let mut min_window: Option<u32> = None;
+    let mut start = 0;
+    for &(end_pos, term_ord) in &positions {
+        if counts[term_ord] == 0 {
+            num_covered_terms += 1;
+        }
+        counts[term_ord] += 1;
+        while num_covered_terms == term_positions.len() {
+            let (start_pos, start_term_ord) = positions[start];
+            let window = end_pos - start_pos + 1;
+            min_window = Some(min_window.map_or(window, |min_window| min_window.min(window)));
+            counts[start_term_ord] -= 1;
+            if counts[start_term_ord] == 0 {
+                num_covered_terms -= 1;
+            }
+            start += 1;
+        }
+    }
+    min_window
+}
+
+/// `ProximityBoostQuery` is a wrapper over a query applying a [`ProximityBoost`] to its score.
+///
+/// The document set matched by the `ProximityBoostQuery` is strictly the same as the
+/// underlying query.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{ProximityBoost, ProximityBoostQuery, QueryParser};
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, DocAddress, Index, IndexWriter, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "shoes for running in the rain"))?;
+/// index_writer.add_document(doc!(title => "running shoes for the rain"))?;
+/// index_writer.commit()?;
+///
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("running shoes")?;
+/// let terms = vec![
+///     Term::from_field_text(title, "running"),
+///     Term::from_field_text(title, "shoes"),
+/// ];
+/// let query = ProximityBoostQuery::new(query, ProximityBoost::new(terms, 1.0));
+/// let searcher = index.reader()?.searcher();
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+/// // "running" and "shoes" are adjacent in the second document.
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProximityBoostQuery {
+    query: Box<dyn Query>,
+    proximity_boost: ProximityBoost,
+}
+
+impl ProximityBoostQuery {
+    /// Builds a proximity boost query.
+    pub fn new(query: Box<dyn Query>, proximity_boost: ProximityBoost) -> ProximityBoostQuery {
+        ProximityBoostQuery {
+            query,
+            proximity_boost,
+        }
+    }
+}
+
+impl Clone for ProximityBoostQuery {
+    fn clone(&self) -> Self {
+        ProximityBoostQuery {
+            query: self.query.box_clone(),
+            proximity_boost: self.proximity_boost.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ProximityBoostQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ProximityBoost(query={:?}, terms={:?}, factor={})",
+            self.query, self.proximity_boost.terms, self.proximity_boost.factor
+        )
+    }
+}
+
+impl Query for ProximityBoostQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(enable_scoring)?;
+        if !enable_scoring.is_scoring_enabled() {
+            return Ok(weight);
+        }
+        Ok(Box::new(ProximityBoostWeight {
+            weight,
+            proximity_boost: self.proximity_boost.clone(),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        self.query.highlight_patterns(visitor);
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        self.query.named_queries(visitor);
+    }
+}
+
+/// Weight associated to the [`ProximityBoostQuery`].
+struct ProximityBoostWeight {
+    weight: Box<dyn Weight>,
+    proximity_boost: ProximityBoost,
+}
+
+impl Weight for ProximityBoostWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        Ok(Box::new(ProximityBoostScorer {
+            underlying: self.weight.scorer(reader, boost)?,
+            segment_tweaker: self.proximity_boost.proximity_segment_tweaker(reader)?,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let underlying_explanation = self.weight.explain(reader, doc)?;
+        let mut segment_tweaker = self.proximity_boost.proximity_segment_tweaker(reader)?;
+        let mut proximity_explanation = match segment_tweaker.terms_window(doc) {
+            Some((num_terms, window)) => Explanation::new_with_string(
+                format!("Proximity boost for {num_terms} terms in a window of {window}"),
+                segment_tweaker.window_multiplier(num_terms, window),
+            ),
+            None => Explanation::new("Proximity boost for less than two terms", 1.0),
+        };
+        proximity_explanation.add_const("factor", self.proximity_boost.factor);
+        let mut explanation = Explanation::new(
+            "ProximityBoost",
+            underlying_explanation.value() * proximity_explanation.value(),
+        );
+        explanation.add_detail(underlying_explanation);
+        explanation.add_detail(proximity_explanation);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+struct ProximityBoostScorer {
+    underlying: Box<dyn Scorer>,
+    segment_tweaker: ProximitySegmentTweaker,
+}
+
+impl DocSet for ProximityBoostScorer {
+    fn advance(&mut self) -> DocId {
+        self.underlying.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.underlying.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId; COLLECT_BLOCK_BUFFER_LEN]) -> usize {
+        self.underlying.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.underlying.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.underlying.size_hint()
+    }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        self.underlying.count(alive_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.underlying.count_including_deleted()
+    }
+}
+
+impl Scorer for ProximityBoostScorer {
+    fn score(&mut self) -> Score {
+        let doc = self.underlying.doc();
+        let score = self.underlying.score();
+        self.segment_tweaker.score(doc, score)
+    }
+
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        self.underlying.matched_positions(visitor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{min_window, ProximityBoost, ProximityBoostQuery};
+    use crate::assert_nearly_equals;
+    use crate::collector::TopDocs;
+    use crate::query::{Query, QueryParser};
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_min_window() {
+        assert_eq!(min_window(&[]), None);
+        assert_eq!(min_window(&[vec![3, 7]]), Some(1));
+        assert_eq!(min_window(&[vec![0, 10], vec![5, 12]]), Some(3));
+        assert_eq!(min_window(&[vec![1], vec![9], vec![4, 20]]), Some(9));
+        assert_eq!(min_window(&[vec![1], vec![]]), None);
+    }
+
+    #[test]
+    fn test_proximity_boost() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "a b c d e"))?;
+        index_writer.add_document(doc!(title => "a c b"))?;
+        index_writer.add_document(doc!(title => "c b a"))?;
+        index_writer.add_document(doc!(title => "a a a a a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let doc = |doc_id| DocAddress::new(0, doc_id);
+
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("a b")?;
+        let terms = vec![
+            Term::from_field_text(title, "b"),
+            Term::from_field_text(title, "a"),
+            Term::from_field_text(title, "a"),
+        ];
+        let proximity_boost = ProximityBoost::new(terms, 2.0);
+        assert_eq!(proximity_boost.terms().len(), 2);
+
+        let proximity_query = ProximityBoostQuery::new(query.box_clone(), proximity_boost.clone());
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(4))?;
+        let boosted_top_docs = searcher.search(&proximity_query, &TopDocs::with_limit(4))?;
+        let rescored_top_docs = searcher.search(
+            &query,
+            &TopDocs::with_limit(4).tweak_score(proximity_boost.clone()),
+        )?;
+        assert_eq!(boosted_top_docs, rescored_top_docs);
+        for (score, doc_address) in &top_docs {
+            let (boosted_score, _) = boosted_top_docs
+                .iter()
+                .find(|(_, boosted_doc_address)| boosted_doc_address == doc_address)
+                .unwrap();
+            // Adjacent terms triple the score, terms separated by one position double it.
+            let multiplier = match doc_address.doc_id {
+                0 | 2 => 3.0,
+                1 => 2.0,
+                _ => 1.0,
+            };
+            assert_nearly_equals!(*boosted_score, score * multiplier);
+            let explanation = proximity_query.explain(&searcher, *doc_address)?;
+            assert_nearly_equals!(explanation.value(), *boosted_score);
+        }
+        assert_eq!(boosted_top_docs[3].1, doc(3));
+
+        // The matched documents are the ones of the underlying query.
+        assert_eq!(proximity_query.count(&searcher)?, 4);
+
+        let tag_boost = ProximityBoost::new(vec![Term::from_field_text(tag, "a")], 1.0);
+        let tag_query = ProximityBoostQuery::new(query, tag_boost);
+        assert!(matches!(
+            searcher.search(&tag_query, &TopDocs::with_limit(4)),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use super::Weight;
 use crate::core::searcher::Searcher;
 use crate::query::Explanation;
 use crate::schema::Schema;
+use crate::snippet::HighlightPattern;
 use crate::{DocAddress, Term};
 
 /// Argument used in `Query::weight(..)`
@@ -69,6 +70,16 @@ impl<'a> EnableScoring<'a> {
         }
     }
 
+    /// Returns the same `EnableScoring`, with scoring disabled.
+    pub fn into_disabled(self) -> EnableScoring<'a> {
+        match self {
+            EnableScoring::Enabled { searcher, .. } => {
+                EnableScoring::disabled_from_searcher(searcher)
+            }
+            disabled @ EnableScoring::Disabled { .. } => disabled,
+        }
+    }
+
     /// Returns the searcher if available.
     pub fn searcher(&self) -> Option<&Searcher> {
         match self {
@@ -160,6 +171,22 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
     /// Note that there can be multiple instances of any given term
     /// in a query and deduplication must be handled by the visitor.
     fn query_terms<'a>(&'a self, _visitor: &mut dyn FnMut(&'a Term, bool)) {}
+
+    /// Extract the patterns of the query that match occurrences of terms in a document,
+    /// and pass them to the given closure.
+    ///
+    /// They are used by the [`Highlighter`](crate::snippet::Highlighter) to only highlight
+    /// the occurrences that actually match the query, e.g. the terms of a phrase when they
+    /// appear as a phrase. Patterns of the clauses excluding documents are not visited.
+    ///
+    /// By default, each term extracted by [`Query::query_terms`] is a pattern of its own.
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        self.query_terms(&mut |term, _| visitor(HighlightPattern::Term(term)));
+    }
+
+    /// Visits the [named](crate::query::NamedQuery) sub-queries of the query, including the
+    /// query itself and nested named queries, with their name.
+    fn named_queries<'a>(&'a self, _visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {}
 }
 
 /// Implements `box_clone`.
@@ -188,6 +215,14 @@ impl Query for Box<dyn Query> {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.as_ref().query_terms(visitor);
     }
+
+    fn highlight_patterns<'a>(&'a self, visitor: &mut dyn FnMut(HighlightPattern<'a>)) {
+        self.as_ref().highlight_patterns(visitor);
+    }
+
+    fn named_queries<'a>(&'a self, visitor: &mut dyn FnMut(&'a str, &'a dyn Query)) {
+        self.as_ref().named_queries(visitor);
+    }
 }
 
 impl QueryClone for Box<dyn Query> {
@@ -23,6 +23,7 @@ pub enum LogicalLiteral {
     All,
 }
 
+#[derive(Clone)]
 pub enum LogicalAst {
     Clause(Vec<(Occur, LogicalAst)>),
     Leaf(Box<LogicalLiteral>),
@@ -72,6 +73,7 @@ fn occur_letter(occur: Occur) -> &'static str {
         Occur::Must => "+",
         Occur::MustNot => "-",
         Occur::Should => "",
+        Occur::Filter => "#",
     }
 }
 
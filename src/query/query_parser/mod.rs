@@ -1,4 +1,6 @@
 mod query_parser;
+mod query_relaxer;
 
 pub mod logical_ast;
 pub use self::query_parser::{QueryParser, QueryParserError};
+pub use self::query_relaxer::{QueryRelaxer, Relaxation, RelaxedSearch};
@@ -102,6 +102,10 @@ pub enum QueryParserError {
     /// The format for the ip field is invalid.
     #[error("The ip field is malformed: {0}")]
     IpFormatError(#[from] AddrParseError),
+    /// A phrase query was requested for a field on which phrase queries were disabled
+    /// with [`QueryParser::set_field_phrase_queries_allowed`].
+    #[error("Phrase queries are not allowed on the field '{0}'")]
+    PhraseQueryNotAllowed(String),
 }
 
 /// Recursively remove empty clause from the AST
@@ -192,6 +196,12 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 /// Additionally, specific fields can be marked to use fuzzy term queries for each literal
 /// via the [`QueryParser::set_field_fuzzy`] method.
 ///
+/// The default operator, the tokenizer used on query text and whether phrase queries are
+/// permitted can also be configured per field (See
+/// [`set_field_conjunction_by_default(...)`](QueryParser::set_field_conjunction_by_default),
+/// [`set_field_tokenizer(...)`](QueryParser::set_field_tokenizer) and
+/// [`set_field_phrase_queries_allowed(...)`](QueryParser::set_field_phrase_queries_allowed)).
+///
 /// Phrase terms support the `~` slop operator which allows to set the phrase's matching
 /// distance in words. `"big wolf"~1` will return documents containing the phrase `"big bad wolf"`.
 ///
@@ -206,13 +216,22 @@ pub struct QueryParser {
     tokenizer_manager: TokenizerManager,
     boost: FxHashMap<Field, Score>,
     fuzzy: FxHashMap<Field, Fuzzy>,
+    field_settings: FxHashMap<Field, FieldSettings>,
 }
 
 #[derive(Clone)]
-struct Fuzzy {
-    prefix: bool,
-    distance: u8,
-    transpose_cost_one: bool,
+pub(crate) struct Fuzzy {
+    pub(crate) prefix: bool,
+    pub(crate) distance: u8,
+    pub(crate) transpose_cost_one: bool,
+}
+
+/// Per field overrides of the query parser settings.
+#[derive(Clone, Default)]
+struct FieldSettings {
+    conjunction_by_default: Option<bool>,
+    tokenizer: Option<String>,
+    phrase_queries_disallowed: bool,
 }
 
 fn all_negative(ast: &LogicalAst) -> bool {
@@ -260,6 +279,7 @@ impl QueryParser {
             conjunction_by_default: false,
             boost: Default::default(),
             fuzzy: Default::default(),
+            field_settings: Default::default(),
         }
     }
 
@@ -320,24 +340,64 @@ impl QueryParser {
         );
     }
 
+    /// Sets the default way to compose queries targeting a specific field.
+    ///
+    /// This overrides [`set_conjunction_by_default`](QueryParser::set_conjunction_by_default)
+    /// for the clauses targeting `field` without an explicit operator. For instance, if
+    /// conjunction is enabled for `title` only, `title:(happy tax) payer` is interpreted as
+    /// `(title:happy AND title:tax) OR payer`.
+    pub fn set_field_conjunction_by_default(&mut self, field: Field, conjunction_by_default: bool) {
+        self.field_settings
+            .entry(field)
+            .or_default()
+            .conjunction_by_default = Some(conjunction_by_default);
+    }
+
+    /// Sets the tokenizer used to analyze the query text targeting a specific field.
+    ///
+    /// By default, the query text is analyzed with the tokenizer the field is indexed with.
+    /// The tokenizer has to be registered in the [`TokenizerManager`] of the query parser.
+    pub fn set_field_tokenizer(&mut self, field: Field, tokenizer_name: &str) {
+        self.field_settings.entry(field).or_default().tokenizer = Some(tokenizer_name.to_string());
+    }
+
+    /// Sets whether phrase queries are permitted on a specific field.
+    ///
+    /// Phrase queries are allowed by default. If disallowed, a query producing a phrase on
+    /// `field` returns a [`QueryParserError::PhraseQueryNotAllowed`] error.
+    pub fn set_field_phrase_queries_allowed(&mut self, field: Field, allowed: bool) {
+        self.field_settings
+            .entry(field)
+            .or_default()
+            .phrase_queries_disallowed = !allowed;
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
     /// is not a valid query.
+    ///
+    /// With the `tracing` feature, parsing is wrapped in a `DEBUG` level `tantivy_parse_query`
+    /// span recording the query.
     pub fn parse_query(&self, query: &str) -> Result<Box<dyn Query>, QueryParserError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("tantivy_parse_query", query).entered();
         let logical_ast = self.parse_query_to_logical_ast(query)?;
         Ok(convert_to_query(&self.fuzzy, logical_ast))
     }
 
     /// Parse a query leniently
     ///
-    /// This variant parses invalid query on a best effort basis. If some part of the query can't
+    /// This variant parses invalid query on a best effort basis. Syntax errors, like unbalanced
+    /// quotes or parentheses, are recovered from. If some part of the query can't
     /// reasonably be executed (range query without field, searching on a non existing field,
     /// searching without precising field when no default field is provided...), they may get
     /// turned into a "match-nothing" subquery.
     ///
     /// In case it encountered such issues, they are reported as a Vec of errors.
     pub fn parse_query_lenient(&self, query: &str) -> (Box<dyn Query>, Vec<QueryParserError>) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("tantivy_parse_query", query).entered();
         let (logical_ast, errors) = self.parse_query_to_logical_ast_lenient(query);
         (convert_to_query(&self.fuzzy, logical_ast), errors)
     }
@@ -369,8 +429,21 @@ impl QueryParser {
         (convert_to_query(&self.fuzzy, logical_ast), errors)
     }
 
+    /// Returns the fuzzy options of the fields set with [`QueryParser::set_field_fuzzy`].
+    pub(crate) fn fuzzy(&self) -> &FxHashMap<Field, Fuzzy> {
+        &self.fuzzy
+    }
+
+    /// Returns the schema of the query parser.
+    pub(crate) fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
     /// Parse the user query into an AST.
-    fn parse_query_to_logical_ast(&self, query: &str) -> Result<LogicalAst, QueryParserError> {
+    pub(crate) fn parse_query_to_logical_ast(
+        &self,
+        query: &str,
+    ) -> Result<LogicalAst, QueryParserError> {
         let user_input_ast = query_grammar::parse_query(query)
             .map_err(|_| QueryParserError::SyntaxError(query.to_string()))?;
         let (ast, mut err) = self.compute_logical_ast_lenient(user_input_ast);
@@ -465,13 +538,14 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_entry.name().to_string())
                 })?;
+                let tokenizer_name = self.field_tokenizer(field, option.tokenizer());
                 let mut text_analyzer =
-                    self.tokenizer_manager
-                        .get(option.tokenizer())
-                        .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                    self.tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
+                        QueryParserError::UnknownTokenizer {
                             field: field_entry.name().to_string(),
-                            tokenizer: option.tokenizer().to_string(),
-                        })?;
+                            tokenizer: tokenizer_name.to_string(),
+                        }
+                    })?;
                 let mut terms: Vec<Term> = Vec::new();
                 let mut token_stream = text_analyzer.token_stream(phrase);
                 token_stream.process(&mut |token| {
@@ -536,7 +610,11 @@ impl QueryParser {
         let field_entry = self.schema.get_field_entry(field);
         let field_type = field_entry.field_type();
         let field_name = field_entry.name();
-        if !field_type.is_indexed() {
+        // Store-only fast fields are filtered on their fast field.
+        let field_supports_ff_term_queries = field_type.is_fast()
+            && !field_type.is_json()
+            && is_type_valid_for_fastfield_range_query(field_type.value_type());
+        if !field_type.is_indexed() && !field_supports_ff_term_queries {
             return Err(QueryParserError::FieldNotIndexed(field_name.to_string()));
         }
         if field_type.value_type() != Type::Json && !json_path.is_empty() {
@@ -545,6 +623,35 @@ impl QueryParser {
                 "{field_name}.{json_path}"
             )));
         }
+        let logical_literals =
+            self.compute_literals_for_leaf(field, json_path, phrase, slop, prefix)?;
+        let phrase_queries_disallowed = self
+            .field_settings
+            .get(&field)
+            .is_some_and(|settings| settings.phrase_queries_disallowed);
+        if phrase_queries_disallowed
+            && logical_literals
+                .iter()
+                .any(|literal| matches!(literal, LogicalLiteral::Phrase { .. }))
+        {
+            return Err(QueryParserError::PhraseQueryNotAllowed(
+                field_name.to_string(),
+            ));
+        }
+        Ok(logical_literals)
+    }
+
+    fn compute_literals_for_leaf(
+        &self,
+        field: Field,
+        json_path: &str,
+        phrase: &str,
+        slop: u32,
+        prefix: bool,
+    ) -> Result<Vec<LogicalLiteral>, QueryParserError> {
+        let field_entry = self.schema.get_field_entry(field);
+        let field_type = field_entry.field_type();
+        let field_name = field_entry.name();
         match *field_type {
             FieldType::U64(_) => {
                 let val: u64 = u64::from_str(phrase)?;
@@ -572,10 +679,18 @@ impl QueryParser {
                 Ok(vec![LogicalLiteral::Term(dt_term)])
             }
             FieldType::Str(ref str_options) => {
-                let indexing_options = str_options.get_indexing_options().ok_or_else(|| {
-                    // This should have been seen earlier really.
-                    QueryParserError::FieldNotIndexed(field_name.to_string())
-                })?;
+                let Some(indexing_options) = str_options.get_indexing_options() else {
+                    // The value of a store-only field is looked up as is in its fast field.
+                    if prefix {
+                        return Err(QueryParserError::UnsupportedQuery(format!(
+                            "Prefix queries are not supported on store-only field {field_name:?}"
+                        )));
+                    }
+                    let term = Term::from_field_text(field, phrase);
+                    return Ok(vec![LogicalLiteral::Term(term)]);
+                };
+                let tokenizer_name = self.field_tokenizer(field, indexing_options.tokenizer());
+                let indexing_options = indexing_options.clone().set_tokenizer(tokenizer_name);
                 let mut text_analyzer = self
                     .tokenizer_manager
                     .get(indexing_options.tokenizer())
@@ -589,7 +704,7 @@ impl QueryParser {
                     phrase,
                     slop,
                     prefix,
-                    indexing_options,
+                    &indexing_options,
                     &mut text_analyzer,
                 )?
                 .into_iter()
@@ -600,6 +715,9 @@ impl QueryParser {
                 field,
                 json_path,
                 phrase,
+                self.field_settings
+                    .get(&field)
+                    .and_then(|settings| settings.tokenizer.as_deref()),
                 &self.tokenizer_manager,
                 json_options,
             ),
@@ -633,6 +751,38 @@ impl QueryParser {
         }
     }
 
+    /// Returns the default occur of a clause, taking into account the default operator of the
+    /// field it targets, if any.
+    fn default_occur_for(&self, user_input_ast: &UserInputAst) -> Occur {
+        let conjunction_by_default = match user_input_ast {
+            UserInputAst::Leaf(leaf) => match leaf.as_ref() {
+                UserInputLeaf::Literal(UserInputLiteral {
+                    field_name: Some(full_path),
+                    ..
+                }) => self
+                    .split_full_path(full_path)
+                    .and_then(|(field, _)| self.field_settings.get(&field))
+                    .and_then(|settings| settings.conjunction_by_default),
+                _ => None,
+            },
+            UserInputAst::Boost(ast, _) => return self.default_occur_for(ast),
+            UserInputAst::Clause(_) => None,
+        };
+        match conjunction_by_default {
+            Some(true) => Occur::Must,
+            Some(false) => Occur::Should,
+            None => self.default_occur(),
+        }
+    }
+
+    /// Returns the tokenizer used on the query text targeting `field`.
+    fn field_tokenizer<'a>(&'a self, field: Field, indexing_tokenizer: &'a str) -> &'a str {
+        self.field_settings
+            .get(&field)
+            .and_then(|settings| settings.tokenizer.as_deref())
+            .unwrap_or(indexing_tokenizer)
+    }
+
     fn resolve_bound(
         &self,
         field: Field,
@@ -656,13 +806,12 @@ impl QueryParser {
     ) -> (LogicalAst, Vec<QueryParserError>) {
         match user_input_ast {
             UserInputAst::Clause(sub_queries) => {
-                let default_occur = self.default_occur();
                 let mut logical_sub_queries: Vec<(Occur, LogicalAst)> = Vec::new();
                 let mut errors = Vec::new();
                 for (occur_opt, sub_ast) in sub_queries {
+                    let occur = occur_opt.unwrap_or_else(|| self.default_occur_for(&sub_ast));
                     let (sub_ast, mut sub_errors) =
                         self.compute_logical_ast_with_occur_lenient(sub_ast);
-                    let occur = occur_opt.unwrap_or(default_occur);
                     logical_sub_queries.push((occur, sub_ast));
                     errors.append(&mut sub_errors);
                 }
@@ -944,6 +1093,7 @@ fn generate_literals_for_json_object(
     field: Field,
     json_path: &str,
     phrase: &str,
+    tokenizer_override: Option<&str>,
     tokenizer_manager: &TokenizerManager,
     json_options: &JsonObjectOptions,
 ) -> Result<Vec<LogicalLiteral>, QueryParserError> {
@@ -951,12 +1101,13 @@ fn generate_literals_for_json_object(
         // This should have been seen earlier really.
         QueryParserError::FieldNotIndexed(field_name.to_string())
     })?;
-    let mut text_analyzer = tokenizer_manager
-        .get(text_options.tokenizer())
-        .ok_or_else(|| QueryParserError::UnknownTokenizer {
+    let tokenizer_name = tokenizer_override.unwrap_or(text_options.tokenizer());
+    let mut text_analyzer = tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
+        QueryParserError::UnknownTokenizer {
             field: field_name.to_string(),
-            tokenizer: text_options.tokenizer().to_string(),
-        })?;
+            tokenizer: tokenizer_name.to_string(),
+        }
+    })?;
     let index_record_option = text_options.index_option();
     let mut logical_literals = Vec::new();
 
@@ -998,7 +1149,10 @@ fn generate_literals_for_json_object(
     Ok(logical_literals)
 }
 
-fn convert_to_query(fuzzy: &FxHashMap<Field, Fuzzy>, logical_ast: LogicalAst) -> Box<dyn Query> {
+pub(crate) fn convert_to_query(
+    fuzzy: &FxHashMap<Field, Fuzzy>,
+    logical_ast: LogicalAst,
+) -> Box<dyn Query> {
     match trim_ast(logical_ast) {
         Some(LogicalAst::Clause(trimmed_clause)) => {
             let occur_subqueries = trimmed_clause
@@ -1922,4 +2076,89 @@ mod test {
             );
         }
     }
+
+    #[test]
+    pub fn test_set_field_conjunction_by_default() {
+        let mut query_parser = make_query_parser();
+        let title = query_parser.schema.get_field("title").unwrap();
+        query_parser.set_field_conjunction_by_default(title, true);
+        let query = query_parser
+            .parse_query_to_logical_ast("title:(happy tax) payer")
+            .unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "((+Term(field=0, type=Str, \"happy\") +Term(field=0, type=Str, \"tax\")) \
+             Term(field=0, type=Str, \"payer\") Term(field=1, type=Str, \"payer\"))"
+        );
+    }
+
+    #[test]
+    pub fn test_set_field_tokenizer() {
+        let mut query_parser = make_query_parser();
+        let title = query_parser.schema.get_field("title").unwrap();
+        query_parser.set_field_tokenizer(title, "raw");
+        let query = query_parser
+            .parse_query_to_logical_ast("title:Hello")
+            .unwrap();
+        assert_eq!(format!("{query:?}"), r#"Term(field=0, type=Str, "Hello")"#);
+
+        query_parser.set_field_tokenizer(title, "unknown");
+        assert_matches!(
+            query_parser.parse_query_to_logical_ast("title:Hello"),
+            Err(QueryParserError::UnknownTokenizer { .. })
+        );
+    }
+
+    #[test]
+    pub fn test_set_field_phrase_queries_allowed() {
+        let mut query_parser = make_query_parser();
+        let title = query_parser.schema.get_field("title").unwrap();
+        query_parser.set_field_phrase_queries_allowed(title, false);
+        assert_eq!(
+            query_parser
+                .parse_query_to_logical_ast("title:\"happy tax\"")
+                .unwrap_err(),
+            QueryParserError::PhraseQueryNotAllowed("title".to_string())
+        );
+        assert!(query_parser
+            .parse_query_to_logical_ast("text:\"happy tax\" title:happy")
+            .is_ok());
+
+        query_parser.set_field_phrase_queries_allowed(title, true);
+        assert!(query_parser
+            .parse_query_to_logical_ast("title:\"happy tax\"")
+            .is_ok());
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient() {
+        let query_parser = make_query_parser_with_default_fields(&["title"]);
+        let (query, errors) = query_parser.parse_query_lenient("title:\"a b");
+        assert_eq!(
+            format!("{query:?}"),
+            "PhraseQuery { field: Field(0), phrase_terms: [(0, Term(field=0, type=Str, \"a\")), \
+             (1, Term(field=0, type=Str, \"b\"))], slop: 0 }"
+        );
+        assert_matches!(&errors[..], [QueryParserError::SyntaxError(_)]);
+
+        let (query, errors) = query_parser.parse_query_lenient("a) (b");
+        assert_eq!(
+            format!("{query:?}"),
+            "BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, \"a\"))), \
+             (Should, TermQuery(Term(field=0, type=Str, \"b\")))], minimum_number_should_match: \
+             1 }"
+        );
+        assert_eq!(errors.len(), 2);
+
+        let (query, errors) = query_parser.parse_query_lenient("nofield:a b");
+        assert_eq!(
+            format!("{query:?}"),
+            "BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, \"b\")))], \
+             minimum_number_should_match: 1 }"
+        );
+        assert_eq!(
+            errors,
+            vec![QueryParserError::FieldDoesNotExist("nofield".to_string())]
+        );
+    }
 }
@@ -0,0 +1,369 @@
+use std::collections::{HashMap, HashSet};
+
+use super::logical_ast::{LogicalAst, LogicalLiteral};
+use super::query_parser::{convert_to_query, Fuzzy};
+use super::QueryParser;
+use crate::collector::Collector;
+use crate::query::{Occur, Query};
+use crate::schema::FieldType;
+use crate::{Searcher, Term};
+
+/// A way to relax a query which matches no documents, see [`QueryRelaxer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relaxation {
+    /// Turns the required clauses of the query into optional ones, e.g. `a AND b` into `a OR b`.
+    Disjunction,
+    /// Drops the `num_words` words of the query appearing in the most documents, i.e. with the
+    /// lowest inverse document frequency. A word is dropped in all of the fields it is searched
+    /// in, and at least one word is kept. Phrases are never dropped.
+    DropCommonWords {
+        /// Maximum number of words to drop.
+        num_words: usize,
+    },
+    /// Searches the terms of the text fields with [fuzzy term
+    /// queries](crate::query::FuzzyTermQuery).
+    Fuzzy {
+        /// Maximum Levenshtein distance, up to 2.
+        distance: u8,
+        /// Whether a transposition of two characters costs 1 or 2 edits.
+        transpose_cost_one: bool,
+    },
+}
+
+/// The result of a [`QueryRelaxer::search`].
+pub struct RelaxedSearch<TFruit> {
+    /// The fruit of the collector, for the first query matching documents.
+    pub fruit: TFruit,
+    /// The relaxations applied to the query which produced the fruit. Empty if the strict query
+    /// matched documents.
+    pub relaxations: Vec<Relaxation>,
+    /// The query which produced the fruit.
+    pub query: Box<dyn Query>,
+}
+
+/// `QueryRelaxer` runs a query text parsed by a [`QueryParser`] and, when it matches no
+/// documents, progressively relaxes it until it does.
+///
+/// The relaxations of the policy are applied cumulatively: the first relaxed query has the
+/// first relaxation, the second one has the first two relaxations, and so on. The default
+/// policy drops the most common word, then turns the query into a disjunction, then enables
+/// fuzziness with a distance of 1. Relaxing is only useful for strict queries, typically
+/// with a query parser set with [`QueryParser::set_conjunction_by_default`].
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{QueryParser, QueryRelaxer, Relaxation};
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "red hat"))?;
+/// index_writer.add_document(doc!(title => "blue shoes"))?;
+/// index_writer.add_document(doc!(title => "blue scarf"))?;
+/// index_writer.commit()?;
+///
+/// let mut query_parser = QueryParser::for_index(&index, vec![title]);
+/// query_parser.set_conjunction_by_default();
+/// let query_relaxer = QueryRelaxer::new(query_parser);
+/// let searcher = index.reader()?.searcher();
+/// let relaxed_search = query_relaxer.search(&searcher, "blue hat", &TopDocs::with_limit(10))?;
+/// // "blue", the most common word, is dropped.
+/// assert_eq!(relaxed_search.fruit.len(), 1);
+/// assert_eq!(
+///     relaxed_search.relaxations,
+///     [Relaxation::DropCommonWords { num_words: 1 }]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct QueryRelaxer {
+    query_parser: QueryParser,
+    relaxations: Vec<Relaxation>,
+}
+
+impl QueryRelaxer {
+    /// Creates a query relaxer with the default policy.
+    pub fn new(query_parser: QueryParser) -> QueryRelaxer {
+        QueryRelaxer {
+            query_parser,
+            relaxations: vec![
+                Relaxation::DropCommonWords { num_words: 1 },
+                Relaxation::Disjunction,
+                Relaxation::Fuzzy {
+                    distance: 1,
+                    transpose_cost_one: true,
+                },
+            ],
+        }
+    }
+
+    /// Sets the relaxations applied, in order, while the query matches no documents.
+    pub fn set_relaxations(&mut self, relaxations: Vec<Relaxation>) {
+        self.relaxations = relaxations;
+    }
+
+    /// Parses the query text and runs it with the collector, relaxing it while it matches no
+    /// documents.
+    ///
+    /// If no relaxed query matches documents, the fruit is the one of the most relaxed query.
+    pub fn search<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        query_text: &str,
+        collector: &C,
+    ) -> crate::Result<RelaxedSearch<C::Fruit>> {
+        let logical_ast = self.query_parser.parse_query_to_logical_ast(query_text)?;
+        for num_relaxations in 0..=self.relaxations.len() {
+            let relaxations = &self.relaxations[..num_relaxations];
+            let query = self.relaxed_query(searcher, logical_ast.clone(), relaxations)?;
+            let is_most_relaxed = num_relaxations == self.relaxations.len();
+            if !is_most_relaxed && query.count(searcher)? == 0 {
+                continue;
+            }
+            let fruit = searcher.search(&*query, collector)?;
+            return Ok(RelaxedSearch {
+                fruit,
+                relaxations: relaxations.to_vec(),
+                query,
+            });
+        }
+        unreachable!("the most relaxed query is always run")
+    }
+
+    fn relaxed_query(
+        &self,
+        searcher: &Searcher,
+        mut logical_ast: LogicalAst,
+        relaxations: &[Relaxation],
+    ) -> crate::Result<Box<dyn Query>> {
+        let mut fuzzy = self.query_parser.fuzzy().clone();
+        for relaxation in relaxations {
+            match *relaxation {
+                Relaxation::Disjunction => make_disjunction(&mut logical_ast),
+                Relaxation::DropCommonWords { num_words } => {
+                    drop_common_words(searcher, &mut logical_ast, num_words)?;
+                }
+                Relaxation::Fuzzy {
+                    distance,
+                    transpose_cost_one,
+                } => {
+                    let schema = self.query_parser.schema();
+                    let mut text_fields = HashSet::new();
+                    visit_terms(&logical_ast, &mut |term| {
+                        let field_type = schema.get_field_entry(term.field()).field_type();
+                        if matches!(field_type, FieldType::Str(_)) {
+                            text_fields.insert(term.field());
+                        }
+                    });
+                    for field in text_fields {
+                        fuzzy.insert(
+                            field,
+                            Fuzzy {
+                                prefix: false,
+                                distance,
+                                transpose_cost_one,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        Ok(convert_to_query(&fuzzy, logical_ast))
+    }
+}
+
+fn make_disjunction(logical_ast: &mut LogicalAst) {
+    match logical_ast {
+        LogicalAst::Clause(children) => {
+            for (occur, child) in children {
+                if *occur == Occur::Must {
+                    *occur = Occur::Should;
+                }
+                make_disjunction(child);
+            }
+        }
+        LogicalAst::Boost(child, _) => make_disjunction(child),
+        LogicalAst::Leaf(_) => {}
+    }
+}
+
+/// Visits the term literals of the clauses which are not excluded.
+fn visit_terms<'a>(logical_ast: &'a LogicalAst, visitor: &mut dyn FnMut(&'a Term)) {
+    match logical_ast {
+        LogicalAst::Clause(children) => {
+            for (occur, child) in children {
+                if *occur != Occur::MustNot {
+                    visit_terms(child, visitor);
+                }
+            }
+        }
+        LogicalAst::Boost(child, _) => visit_terms(child, visitor),
+        LogicalAst::Leaf(literal) => {
+            if let LogicalLiteral::Term(term) = literal.as_ref() {
+                visitor(term);
+            }
+        }
+    }
+}
+
+fn drop_common_words(
+    searcher: &Searcher,
+    logical_ast: &mut LogicalAst,
+    num_words: usize,
+) -> crate::Result<()> {
+    // The same word is searched with a term per field.
+    let mut words: HashMap<Vec<u8>, Vec<Term>> = HashMap::new();
+    visit_terms(logical_ast, &mut |term| {
+        words
+            .entry(term.serialized_value_bytes().to_vec())
+            .or_default()
+            .push(term.clone());
+    });
+    let mut word_doc_freqs = Vec::with_capacity(words.len());
+    for (word, terms) in words {
+        let mut doc_freq = 0;
+        for term in &terms {
+            doc_freq += searcher.doc_freq(term)?;
+        }
+        word_doc_freqs.push((doc_freq, word));
+    }
+    word_doc_freqs.sort_unstable_by(|left, right| right.cmp(left));
+    let num_dropped_words = num_words.min(word_doc_freqs.len().saturating_sub(1));
+    let dropped_words: HashSet<Vec<u8>> = word_doc_freqs
+        .into_iter()
+        .take(num_dropped_words)
+        .map(|(_, word)| word)
+        .collect();
+    if !dropped_words.is_empty() {
+        drop_words(logical_ast, &dropped_words);
+    }
+    Ok(())
+}
+
+fn is_dropped_word(logical_ast: &LogicalAst, dropped_words: &HashSet<Vec<u8>>) -> bool {
+    match logical_ast {
+        LogicalAst::Leaf(literal) => match literal.as_ref() {
+            LogicalLiteral::Term(term) => dropped_words.contains(term.serialized_value_bytes()),
+            _ => false,
+        },
+        LogicalAst::Boost(child, _) => is_dropped_word(child, dropped_words),
+        LogicalAst::Clause(_) => false,
+    }
+}
+
+fn drop_words(logical_ast: &mut LogicalAst, dropped_words: &HashSet<Vec<u8>>) {
+    match logical_ast {
+        LogicalAst::Clause(children) => {
+            children.retain(|(occur, child)| {
+                *occur == Occur::MustNot || !is_dropped_word(child, dropped_words)
+            });
+            for (occur, child) in children {
+                if *occur != Occur::MustNot {
+                    drop_words(child, dropped_words);
+                }
+            }
+        }
+        LogicalAst::Boost(child, _) => drop_words(child, dropped_words),
+        LogicalAst::Leaf(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QueryRelaxer, Relaxation};
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter, Searcher};
+
+    fn create_index() -> crate::Result<(Index, QueryParser)> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "red running shoes", body => "for trails"))?;
+        index_writer.add_document(doc!(title => "red hat", body => "wool"))?;
+        index_writer.add_document(doc!(title => "blue shoes", body => "red laces"))?;
+        index_writer.commit()?;
+        let mut query_parser = QueryParser::for_index(&index, vec![title, body]);
+        query_parser.set_conjunction_by_default();
+        Ok((index, query_parser))
+    }
+
+    fn relaxed_search(
+        query_relaxer: &QueryRelaxer,
+        searcher: &Searcher,
+        query_text: &str,
+    ) -> crate::Result<(usize, Vec<Relaxation>)> {
+        let relaxed_search = query_relaxer.search(searcher, query_text, &Count)?;
+        Ok((relaxed_search.fruit, relaxed_search.relaxations))
+    }
+
+    #[test]
+    fn test_query_relaxer() -> crate::Result<()> {
+        let (index, query_parser) = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let query_relaxer = QueryRelaxer::new(query_parser);
+        let drop_common_word = Relaxation::DropCommonWords { num_words: 1 };
+
+        // The strict query matches.
+        assert_eq!(
+            relaxed_search(&query_relaxer, &searcher, "red shoes")?,
+            (2, vec![])
+        );
+        // "red" appears in all documents, across the title and the body: dropping it
+        // leaves "wool trails", matching nothing, so the query becomes a disjunction.
+        assert_eq!(
+            relaxed_search(&query_relaxer, &searcher, "red wool trails")?,
+            (2, vec![drop_common_word, Relaxation::Disjunction])
+        );
+        // "shoes" is the most common word.
+        assert_eq!(
+            relaxed_search(&query_relaxer, &searcher, "hat shoes")?,
+            (1, vec![drop_common_word])
+        );
+        // Excluded words are never dropped, even if they are the most common.
+        assert_eq!(
+            relaxed_search(&query_relaxer, &searcher, "hat trails -shoes")?,
+            (1, vec![drop_common_word])
+        );
+        // Neither word exists: "shoez" is dropped, and only fuzziness finds "runing".
+        let relaxed = relaxed_search(&query_relaxer, &searcher, "runing shoez")?;
+        assert_eq!(relaxed.0, 1);
+        assert_eq!(relaxed.1.len(), 3);
+        // Nothing matches even with all the relaxations.
+        let relaxed = relaxed_search(&query_relaxer, &searcher, "xxxxxxx yyyyyyy")?;
+        assert_eq!(relaxed.0, 0);
+        assert_eq!(relaxed.1.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_relaxer_custom_policy() -> crate::Result<()> {
+        let (index, query_parser) = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let mut query_relaxer = QueryRelaxer::new(query_parser);
+        let fuzzy = Relaxation::Fuzzy {
+            distance: 1,
+            transpose_cost_one: true,
+        };
+        query_relaxer.set_relaxations(vec![fuzzy]);
+        assert_eq!(
+            relaxed_search(&query_relaxer, &searcher, "red runnign shoes")?,
+            (1, vec![fuzzy])
+        );
+        query_relaxer.set_relaxations(Vec::new());
+        assert_eq!(
+            relaxed_search(&query_relaxer, &searcher, "red runnign shoes")?,
+            (0, vec![])
+        );
+        assert!(query_relaxer.search(&searcher, "title:(", &Count).is_err());
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
 use crate::schema::Type;
 
 mod fast_field_range_doc_set;
+mod point_range_query;
 mod range_query;
 mod range_query_fastfield;
 
 pub use common::bounds::BoundsRange;
 
+pub use self::point_range_query::*;
 pub use self::range_query::*;
 pub use self::range_query_fastfield::*;
 
@@ -0,0 +1,260 @@
+use std::ops::{Bound, RangeInclusive};
+
+use common::BitSet;
+
+use crate::fastfield::FastValue;
+use crate::index::SegmentReader;
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    BitSetDocSet, ConstScorer, EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::{Field, FieldType, Type};
+use crate::{DocId, Score, TantivyError};
+
+/// `PointRangeQuery` matches all documents that have at least one value within a defined range,
+/// using the [points index](crate::points) of the field.
+///
+/// The field has to be a numeric or date field set as points, see
+/// [`NumericOptions::set_points`](crate::schema::NumericOptions::set_points).
+///
+/// Matched document will all get a constant `Score` of one.
+///
+/// # Implementation
+///
+/// The points index stores the values of the field sorted, in leaf blocks.
+/// Only the leaves overlapping the range are visited, which makes selective range
+/// queries much cheaper than scanning the fast field on large segments.
+/// Matching documents are collected _upfront_ into a `BitSet`.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::PointRangeQuery;
+/// use tantivy::schema::{NumericOptions, Schema};
+/// use tantivy::{doc, Index, IndexWriter};
+/// use std::ops::Bound;
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let year_field = schema_builder.add_u64_field("year", NumericOptions::default().set_points());
+/// let schema = schema_builder.build();
+///
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// for year in 1950u64..2017u64 {
+///     let num_docs_within_year = 10 + (year - 1950) * (year - 1950);
+///     for _ in 0..num_docs_within_year {
+///       index_writer.add_document(doc!(year_field => year))?;
+///     }
+/// }
+/// index_writer.commit()?;
+///
+/// let reader = index.reader()?;
+/// let searcher = reader.searcher();
+/// let docs_in_the_sixties = PointRangeQuery::new(
+///     year_field,
+///     Bound::Included(1960u64),
+///     Bound::Excluded(1970u64),
+/// );
+/// let num_60s_books = searcher.search(&docs_in_the_sixties, &Count)?;
+/// assert_eq!(num_60s_books, 2285);
+/// Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PointRangeQuery {
+    field: Field,
+    value_type: Type,
+    range: Option<RangeInclusive<u64>>,
+}
+
+impl PointRangeQuery {
+    /// Creates a new `PointRangeQuery` over `field`.
+    ///
+    /// The type of the bounds has to match the type of the field.
+    pub fn new<T: FastValue>(
+        field: Field,
+        lower_bound: Bound<T>,
+        upper_bound: Bound<T>,
+    ) -> PointRangeQuery {
+        let start_opt = match lower_bound {
+            Bound::Included(val) => Some(val.to_u64()),
+            Bound::Excluded(val) => val.to_u64().checked_add(1),
+            Bound::Unbounded => Some(u64::MIN),
+        };
+        let end_opt = match upper_bound {
+            Bound::Included(val) => Some(val.to_u64()),
+            Bound::Excluded(val) => val.to_u64().checked_sub(1),
+            Bound::Unbounded => Some(u64::MAX),
+        };
+        PointRangeQuery {
+            field,
+            value_type: T::to_type(),
+            range: start_opt.zip(end_opt).map(|(start, end)| start..=end),
+        }
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+impl Query for PointRangeQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let field_entry = enable_scoring.schema().get_field_entry(self.field);
+        let has_points = match field_entry.field_type() {
+            FieldType::U64(options) | FieldType::I64(options) | FieldType::F64(options) => {
+                options.is_points()
+            }
+            FieldType::Date(options) => options.is_points(),
+            _ => false,
+        };
+        if !has_points {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not set as points.",
+                field_entry.name()
+            )));
+        }
+        if field_entry.field_type().value_type() != self.value_type {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is of type {:?}, but the range is of type {:?}.",
+                field_entry.name(),
+                field_entry.field_type().value_type(),
+                self.value_type
+            )));
+        }
+        Ok(Box::new(PointRangeWeight {
+            field: self.field,
+            range: self.range.clone(),
+        }))
+    }
+}
+
+/// Weight of the [`PointRangeQuery`].
+pub struct PointRangeWeight {
+    field: Field,
+    range: Option<RangeInclusive<u64>>,
+}
+
+impl Weight for PointRangeWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some(range) = self.range.clone() else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let Some(points_reader) = reader.points_readers().get_field(self.field)? else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+        points_reader.for_each_doc_in_range(range, |doc| doc_bitset.insert(doc));
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("PointRangeQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::PointRangeQuery;
+    use crate::collector::Count;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{DateOptions, NumericOptions, Schema, FAST, INDEXED};
+    use crate::{DateTime, Index, IndexWriter};
+
+    #[test]
+    fn test_point_range_query_i64_with_merge_and_deletes() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let num_field =
+            schema_builder.add_i64_field("num", NumericOptions::from(INDEXED).set_points());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for val in -1_000i64..1_000 {
+            index_writer.add_document(doc!(num_field=>val, num_field=>val + 10_000))?;
+            if val % 500 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.segment_readers().len() > 1);
+        let count = |lower: Bound<i64>, upper: Bound<i64>| {
+            let query = PointRangeQuery::new(num_field, lower, upper);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(Bound::Included(-10), Bound::Excluded(10)), 20);
+        assert_eq!(count(Bound::Excluded(-10), Bound::Included(10)), 20);
+        assert_eq!(count(Bound::Unbounded, Bound::Excluded(-990)), 10);
+        assert_eq!(count(Bound::Included(9_000), Bound::Unbounded), 2_000);
+        assert_eq!(count(Bound::Included(5), Bound::Excluded(5)), 0);
+        assert_eq!(count(Bound::Unbounded, Bound::Unbounded), 2_000);
+
+        index_writer.delete_term(crate::Term::from_field_i64(num_field, 0));
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let query = PointRangeQuery::new(num_field, Bound::Included(-10i64), Bound::Excluded(10));
+        assert_eq!(searcher.search(&query, &Count)?, 19);
+        let query = PointRangeQuery::new(
+            num_field,
+            Bound::Included(9_999i64),
+            Bound::Included(10_001),
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_point_range_query_f64_and_date() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let f64_field = schema_builder.add_f64_field("f64", NumericOptions::default().set_points());
+        let date_field = schema_builder.add_date_field("date", DateOptions::default().set_points());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..100i64 {
+            index_writer.add_document(doc!(
+                f64_field => i as f64 - 50.5,
+                date_field => DateTime::from_timestamp_secs(i * 3600),
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = PointRangeQuery::new(f64_field, Bound::Excluded(-1.0), Bound::Included(1.0));
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        let query = PointRangeQuery::new(
+            date_field,
+            Bound::Included(DateTime::from_timestamp_secs(24 * 3600)),
+            Bound::Excluded(DateTime::from_timestamp_secs(48 * 3600)),
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 24);
+        Ok(())
+    }
+
+    #[test]
+    fn test_point_range_query_invalid_field() {
+        let mut schema_builder = Schema::builder();
+        let fast_field = schema_builder.add_u64_field("fast", FAST);
+        let points_field =
+            schema_builder.add_u64_field("points", NumericOptions::default().set_points());
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader().unwrap().searcher();
+        let query = PointRangeQuery::new(fast_field, Bound::Included(1u64), Bound::Unbounded);
+        assert!(searcher.search(&query, &Count).is_err());
+        let query = PointRangeQuery::new(points_field, Bound::Included(1i64), Bound::Unbounded);
+        assert!(searcher.search(&query, &Count).is_err());
+    }
+}
@@ -6,8 +6,8 @@ use std::net::Ipv6Addr;
 use std::ops::{Bound, RangeInclusive};
 
 use columnar::{
-    Column, ColumnType, MonotonicallyMappableToU128, MonotonicallyMappableToU64, NumericalType,
-    StrColumn,
+    Column, ColumnStatistics, ColumnType, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
+    NumericalType, StrColumn,
 };
 use common::bounds::{BoundsRange, TransformBound};
 
@@ -15,7 +15,7 @@ use super::fast_field_range_doc_set::RangeDocSet;
 use crate::query::{
     AllScorer, ConstScorer, EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight,
 };
-use crate::schema::{Type, ValueBytes};
+use crate::schema::{value_type_to_column_type, Type, ValueBytes};
 use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
 
 #[derive(Clone, Debug)]
@@ -201,6 +201,16 @@ impl Weight for FastFieldRangeWeight {
             })?;
 
             let fast_field_reader = reader.fast_fields();
+            // The column statistics make it possible to skip segments without opening the column.
+            if let Some(column_type) = value_type_to_column_type(field_type.value_type()) {
+                if let Some(column_statistics) =
+                    fast_field_reader.column_statistics(&field_name, column_type)?
+                {
+                    if !may_match_bounds(&column_statistics, &bounds) {
+                        return Ok(Box::new(EmptyScorer));
+                    }
+                }
+            }
             let Some((column, _col_type)) = fast_field_reader.u64_lenient_for_type(
                 Some(&[
                     ColumnType::U64,
@@ -213,11 +223,7 @@ impl Weight for FastFieldRangeWeight {
             else {
                 return Ok(Box::new(EmptyScorer));
             };
-            search_on_u64_ff(
-                column,
-                boost,
-                BoundsRange::new(bounds.lower_bound, bounds.upper_bound),
-            )
+            search_on_u64_ff(column, boost, bounds)
         }
     }
 
@@ -417,6 +423,26 @@ fn search_on_u64_ff(
     Ok(Box::new(ConstScorer::new(docset, boost)))
 }
 
+/// Returns false if the column statistics show that no value of the column lies within the
+/// bounds.
+fn may_match_bounds(column_statistics: &ColumnStatistics, bounds: &BoundsRange<u64>) -> bool {
+    if column_statistics.num_values() == 0 {
+        return false;
+    }
+    let Some((min_value, max_value)) = column_statistics.min_max_u64() else {
+        return true;
+    };
+    let Some(value_range) = bound_to_value_range(
+        &bounds.lower_bound,
+        &bounds.upper_bound,
+        min_value,
+        max_value,
+    ) else {
+        return false;
+    };
+    !value_range.is_empty() && *value_range.start() <= max_value && *value_range.end() >= min_value
+}
+
 /// Returns true if the type maps to a u64 fast field
 pub(crate) fn maps_to_u64_fastfield(typ: Type) -> bool {
     match typ {
@@ -474,6 +500,7 @@ fn bound_to_value_range<T: MonotonicallyMappableToU64>(
 mod tests {
     use std::ops::{Bound, RangeInclusive};
 
+    use columnar::{ColumnType, MonotonicallyMappableToU64};
     use common::bounds::BoundsRange;
     use common::DateTime;
     use proptest::prelude::*;
@@ -483,8 +510,10 @@ mod tests {
     use time::format_description::well_known::Rfc3339;
     use time::OffsetDateTime;
 
+    use super::may_match_bounds;
     use crate::collector::{Count, TopDocs};
     use crate::fastfield::FastValue;
+    use crate::indexer::NoMergePolicy;
     use crate::query::range_query::range_query_fastfield::FastFieldRangeWeight;
     use crate::query::{QueryParser, RangeQuery, Weight};
     use crate::schema::{
@@ -552,6 +581,67 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_range_query_column_statistics_pruning() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let num_field = schema_builder.add_i64_field("num", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for val in 0..100i64 {
+            index_writer.add_document(doc!(num_field => val))?;
+        }
+        index_writer.commit()?;
+        for val in 1_000..1_100i64 {
+            index_writer.add_document(doc!(num_field => val))?;
+        }
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let bounds = BoundsRange::new(Bound::Included(10i64), Bound::Excluded(1_010i64));
+        let bounds_u64 = bounds.map_bound(|val| val.to_u64());
+        let mut segment_statistics = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let column_statistics = segment_reader
+                .fast_fields()
+                .column_statistics("num", ColumnType::I64)?
+                .unwrap();
+            assert!(may_match_bounds(&column_statistics, &bounds_u64));
+            segment_statistics.push(column_statistics);
+        }
+        segment_statistics.sort_by_key(|column_statistics| column_statistics.null_count());
+        assert_eq!(segment_statistics[0].min_value::<i64>(), Some(0));
+        assert_eq!(segment_statistics[0].max_value::<i64>(), Some(99));
+        assert_eq!(segment_statistics[1].null_count(), 1);
+        assert_eq!(segment_statistics[1].min_value::<i64>(), Some(1_000));
+        assert!(!may_match_bounds(
+            &segment_statistics[0],
+            &BoundsRange::new(Bound::Excluded(99i64.to_u64()), Bound::Unbounded)
+        ));
+        assert!(!may_match_bounds(
+            &segment_statistics[1],
+            &BoundsRange::new(
+                Bound::Included(100i64.to_u64()),
+                Bound::Excluded(1_000i64.to_u64())
+            )
+        ));
+
+        let count = |lower: Bound<i64>, upper: Bound<i64>| {
+            let query = RangeQuery::new(
+                lower.map(|val| Term::from_field_i64(num_field, val)),
+                upper.map(|val| Term::from_field_i64(num_field, val)),
+            );
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(bounds.lower_bound, bounds.upper_bound), 100);
+        assert_eq!(count(Bound::Included(100), Bound::Excluded(1_000)), 0);
+        assert_eq!(count(Bound::Excluded(99), Bound::Included(1_000)), 1);
+        assert_eq!(count(Bound::Unbounded, Bound::Included(-1)), 0);
+        Ok(())
+    }
+
     #[test]
     fn test_date_range_query() {
         let mut schema_builder = Schema::builder();
@@ -3,6 +3,7 @@ use std::marker::PhantomData;
 use crate::docset::DocSet;
 use crate::query::score_combiner::ScoreCombiner;
 use crate::query::Scorer;
+use crate::schema::Field;
 use crate::{DocId, Score};
 
 /// Given a required scorer and an optional scorer
@@ -86,6 +87,14 @@ where
         self.score_cache = Some(score);
         score
     }
+
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        let doc = self.doc();
+        self.req_scorer.matched_positions(visitor);
+        if self.opt_scorer.doc() <= doc && self.opt_scorer.seek(doc) == doc {
+            self.opt_scorer.matched_positions(visitor);
+        }
+    }
 }
 
 #[cfg(test)]
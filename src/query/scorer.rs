@@ -3,6 +3,7 @@ use std::ops::DerefMut;
 use downcast_rs::impl_downcast;
 
 use crate::docset::DocSet;
+use crate::schema::Field;
 use crate::Score;
 
 /// Scored set of documents matching a query within a specific segment.
@@ -13,6 +14,22 @@ pub trait Scorer: downcast_rs::Downcast + DocSet + 'static {
     ///
     /// This method will perform a bit of computation and is not cached.
     fn score(&mut self) -> Score;
+
+    /// Visits the positions of the terms matched in the current document, with the field
+    /// they belong to.
+    ///
+    /// This makes it possible for a [`Collector`](crate::collector::Collector) to extract
+    /// match spans or compute proximity features, by calling [`Weight::scorer`] in
+    /// [`Collector::collect_segment`](crate::collector::Collector::collect_segment), without
+    /// reading the postings a second time.
+    ///
+    /// Positions are only available for the scorers of term and phrase queries, if the field
+    /// and the query record them, and for the intersections and wrappers of these scorers.
+    /// Unions buffer their documents ahead of the current document, and do not visit any
+    /// position. The order of the visited positions is unspecified.
+    ///
+    /// [`Weight::scorer`]: crate::query::Weight::scorer
+    fn matched_positions(&mut self, _visitor: &mut dyn FnMut(Field, u32)) {}
 }
 
 impl_downcast!(Scorer);
@@ -21,4 +38,8 @@ impl Scorer for Box<dyn Scorer> {
     fn score(&mut self) -> Score {
         self.deref_mut().score()
     }
+
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        self.deref_mut().matched_positions(visitor);
+    }
 }
@@ -0,0 +1,54 @@
+use super::{Similarity, SimilarityScorer, TermStatistics};
+use crate::query::bm25::{B, K1};
+use crate::query::Bm25Weight;
+use crate::Score;
+
+/// The Okapi BM25 similarity, the default similarity of tantivy.
+///
+/// `k1` controls the saturation of the term frequency, and `b` the normalization by the length
+/// of the field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bm25Similarity {
+    k1: Score,
+    b: Score,
+}
+
+impl Bm25Similarity {
+    /// Creates a BM25 similarity with the given parameters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k1` is negative, or if `b` is not within `[0, 1]`.
+    pub fn new(k1: Score, b: Score) -> Bm25Similarity {
+        assert!(k1 >= 0.0, "k1 must be non-negative, got {k1}");
+        assert!((0.0..=1.0).contains(&b), "b must be within [0, 1], got {b}");
+        Bm25Similarity { k1, b }
+    }
+
+    /// Returns the term frequency saturation parameter.
+    pub fn k1(&self) -> Score {
+        self.k1
+    }
+
+    /// Returns the length normalization parameter.
+    pub fn b(&self) -> Score {
+        self.b
+    }
+}
+
+impl Default for Bm25Similarity {
+    /// Creates a BM25 similarity with `k1 = 1.2` and `b = 0.75`.
+    fn default() -> Bm25Similarity {
+        Bm25Similarity::new(K1, B)
+    }
+}
+
+impl Similarity for Bm25Similarity {
+    fn scorer(&self, term_statistics: &TermStatistics) -> Box<dyn SimilarityScorer> {
+        Box::new(Bm25Weight::for_term_statistics(
+            term_statistics,
+            self.k1,
+            self.b,
+        ))
+    }
+}
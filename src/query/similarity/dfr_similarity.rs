@@ -0,0 +1,93 @@
+use super::{Similarity, SimilarityScorer, TermStatistics};
+use crate::query::Explanation;
+use crate::Score;
+
+const DEFAULT_C: Score = 1.0;
+
+/// A divergence from randomness similarity, with the `In` basic model, the Laplace after-effect
+/// and the `H2` normalization, scoring a document as `tfn * log2((N + 1) / (n + 0.5)) / (tfn +
+/// 1)`, with `tfn = freq * log2(1 + c * avgdl / dl)`.
+///
+/// The `n` of a phrase is the smallest `n` of its terms.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DfrSimilarity {
+    c: Score,
+}
+
+impl DfrSimilarity {
+    /// Creates a DFR similarity with the given length normalization parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` is not positive.
+    pub fn new(c: Score) -> DfrSimilarity {
+        assert!(c > 0.0, "c must be positive, got {c}");
+        DfrSimilarity { c }
+    }
+
+    /// Returns the length normalization parameter.
+    pub fn c(&self) -> Score {
+        self.c
+    }
+}
+
+impl Default for DfrSimilarity {
+    /// Creates a DFR similarity with `c = 1`.
+    fn default() -> DfrSimilarity {
+        DfrSimilarity::new(DEFAULT_C)
+    }
+}
+
+impl Similarity for DfrSimilarity {
+    fn scorer(&self, term_statistics: &TermStatistics) -> Box<dyn SimilarityScorer> {
+        let idf = ((term_statistics.total_num_docs as Score + 1.0)
+            / (term_statistics.min_doc_freq() as Score + 0.5))
+            .log2();
+        Box::new(DfrScorer {
+            c: self.c,
+            idf,
+            average_fieldnorm: term_statistics.average_fieldnorm(),
+        })
+    }
+}
+
+struct DfrScorer {
+    c: Score,
+    idf: Score,
+    average_fieldnorm: Score,
+}
+
+impl DfrScorer {
+    fn normalized_term_freq(&self, term_freq: u32, fieldnorm: u32) -> Score {
+        term_freq as Score
+            * (1.0 + self.c * self.average_fieldnorm / fieldnorm.max(1) as Score).log2()
+    }
+}
+
+impl SimilarityScorer for DfrScorer {
+    fn score(&self, term_freq: u32, fieldnorm: u32) -> Score {
+        let tfn = self.normalized_term_freq(term_freq, fieldnorm);
+        tfn * self.idf / (tfn + 1.0)
+    }
+
+    fn explain(&self, term_freq: u32, fieldnorm: u32) -> Explanation {
+        let mut explanation = Explanation::new(
+            "DFR InL2, computed as tfn * log2((N + 1) / (n + 0.5)) / (tfn + 1)",
+            self.score(term_freq, fieldnorm),
+        );
+        explanation.add_const("log2((N + 1) / (n + 0.5))", self.idf);
+        let mut tfn_explanation = Explanation::new(
+            "tfn, computed as freq * log2(1 + c * avgdl / dl)",
+            self.normalized_term_freq(term_freq, fieldnorm),
+        );
+        tfn_explanation.add_const(
+            "freq, occurrences of term within document",
+            term_freq as Score,
+        );
+        tfn_explanation.add_const("c, length normalization parameter", self.c);
+        tfn_explanation.add_const("avgdl, average length of field", self.average_fieldnorm);
+        tfn_explanation.add_const("dl, length of field", fieldnorm as Score);
+        explanation.add_detail(tfn_explanation);
+        explanation
+    }
+}
@@ -0,0 +1,83 @@
+use super::{Similarity, SimilarityScorer, TermStatistics};
+use crate::query::Explanation;
+use crate::Score;
+
+const DEFAULT_MU: Score = 2000.0;
+
+/// The language model similarity with Dirichlet smoothing, scoring a document as
+/// `log(1 + freq / (mu * p)) + log(mu / (dl + mu))`, or 0 if this is negative.
+///
+/// `p` is the probability of the term in the field, `(n + 1) / (T + 1)`, where the number of
+/// occurrences of the term in the index is estimated by its number of documents `n`, and `T`
+/// is the total number of tokens of the field. The `n` of a phrase is the smallest `n` of its
+/// terms.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LmDirichletSimilarity {
+    mu: Score,
+}
+
+impl LmDirichletSimilarity {
+    /// Creates a language model similarity with the given smoothing parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mu` is not positive.
+    pub fn new(mu: Score) -> LmDirichletSimilarity {
+        assert!(mu > 0.0, "mu must be positive, got {mu}");
+        LmDirichletSimilarity { mu }
+    }
+
+    /// Returns the smoothing parameter.
+    pub fn mu(&self) -> Score {
+        self.mu
+    }
+}
+
+impl Default for LmDirichletSimilarity {
+    /// Creates a language model similarity with `mu = 2000`.
+    fn default() -> LmDirichletSimilarity {
+        LmDirichletSimilarity::new(DEFAULT_MU)
+    }
+}
+
+impl Similarity for LmDirichletSimilarity {
+    fn scorer(&self, term_statistics: &TermStatistics) -> Box<dyn SimilarityScorer> {
+        let collection_probability = (term_statistics.min_doc_freq() as Score + 1.0)
+            / (term_statistics.total_num_tokens as Score + 1.0);
+        Box::new(LmDirichletScorer {
+            mu: self.mu,
+            collection_probability,
+        })
+    }
+}
+
+struct LmDirichletScorer {
+    mu: Score,
+    collection_probability: Score,
+}
+
+impl SimilarityScorer for LmDirichletScorer {
+    fn score(&self, term_freq: u32, fieldnorm: u32) -> Score {
+        let score = (1.0 + term_freq as Score / (self.mu * self.collection_probability)).ln()
+            + (self.mu / (fieldnorm as Score + self.mu)).ln();
+        score.max(0.0)
+    }
+
+    fn explain(&self, term_freq: u32, fieldnorm: u32) -> Explanation {
+        let mut explanation = Explanation::new(
+            "LM Dirichlet, computed as max(0, log(1 + freq / (mu * p)) + log(mu / (dl + mu)))",
+            self.score(term_freq, fieldnorm),
+        );
+        explanation.add_const(
+            "freq, occurrences of term within document",
+            term_freq as Score,
+        );
+        explanation.add_const("mu, smoothing parameter", self.mu);
+        explanation.add_const(
+            "p, probability of the term in the field",
+            self.collection_probability,
+        );
+        explanation.add_const("dl, length of field", fieldnorm as Score);
+        explanation
+    }
+}
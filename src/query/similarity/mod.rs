@@ -0,0 +1,197 @@
+//! Similarities define how the terms of a query score the documents of a field.
+//!
+//! The similarity of a text field is set by name in its [`TextFieldIndexing`] options, and
+//! looked up in the [`SimilarityManager`] of the index, see [`Index::similarities`].
+//! By default, fields are scored with BM25.
+//!
+//! [`TextFieldIndexing`]: crate::schema::TextFieldIndexing
+//! [`Index::similarities`]: crate::Index::similarities
+
+mod bm25_similarity;
+mod dfr_similarity;
+mod lm_dirichlet_similarity;
+mod similarity_manager;
+mod tfidf_similarity;
+
+use downcast_rs::{impl_downcast, Downcast};
+
+pub use self::bm25_similarity::Bm25Similarity;
+pub use self::dfr_similarity::DfrSimilarity;
+pub use self::lm_dirichlet_similarity::LmDirichletSimilarity;
+pub use self::similarity_manager::SimilarityManager;
+pub use self::tfidf_similarity::TfIdfSimilarity;
+use crate::query::{Bm25StatisticsProvider, Explanation};
+use crate::{Score, Term};
+
+/// The statistics of the terms scored together, e.g. the terms of a phrase, and of their field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermStatistics {
+    /// The total number of documents in the index.
+    pub total_num_docs: u64,
+    /// The total number of tokens in the field across all documents in the index.
+    pub total_num_tokens: u64,
+    /// The number of documents containing each of the terms.
+    pub doc_freqs: Vec<u64>,
+}
+
+impl TermStatistics {
+    pub(crate) fn for_terms(
+        statistics: &dyn Bm25StatisticsProvider,
+        terms: &[Term],
+    ) -> crate::Result<TermStatistics> {
+        assert!(!terms.is_empty(), "Bm25 requires at least one term");
+        let field = terms[0].field();
+        for term in &terms[1..] {
+            assert_eq!(
+                term.field(),
+                field,
+                "All terms must belong to the same field."
+            );
+        }
+        let doc_freqs = terms
+            .iter()
+            .map(|term| statistics.doc_freq(term))
+            .collect::<crate::Result<Vec<u64>>>()?;
+        Ok(TermStatistics {
+            total_num_docs: statistics.total_num_docs()?,
+            total_num_tokens: statistics.total_num_tokens(field)?,
+            doc_freqs,
+        })
+    }
+
+    /// Returns the average number of tokens of the field in a document.
+    pub fn average_fieldnorm(&self) -> Score {
+        self.total_num_tokens as Score / self.total_num_docs as Score
+    }
+
+    /// Returns the smallest document frequency of the terms, which is an estimate of the number
+    /// of documents containing all of them.
+    pub fn min_doc_freq(&self) -> u64 {
+        self.doc_freqs.iter().copied().min().unwrap_or(0)
+    }
+}
+
+/// A `Similarity` computes the relevance of documents for terms of a given field.
+///
+/// For each term of a query, or for the terms of a phrase, the similarity creates a
+/// [`SimilarityScorer`] from the statistics of the index, typically deriving an inverse
+/// document frequency from them. The scorer then scores the documents from their term
+/// frequency and the length of their field.
+///
+/// The scores must be non-negative, and must not decrease when the term frequency increases.
+pub trait Similarity: Downcast + Send + Sync + 'static {
+    /// Creates the scorer of terms with the given statistics.
+    fn scorer(&self, term_statistics: &TermStatistics) -> Box<dyn SimilarityScorer>;
+}
+
+impl_downcast!(Similarity);
+
+/// Scores the documents containing a term, or a phrase.
+pub trait SimilarityScorer: Send + Sync + 'static {
+    /// Returns the score of a document containing the term `term_freq` times, in a field of
+    /// `fieldnorm` tokens.
+    fn score(&self, term_freq: u32, fieldnorm: u32) -> Score;
+
+    /// Produces an [`Explanation`] of a score.
+    fn explain(&self, term_freq: u32, fieldnorm: u32) -> Explanation;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::TopDocs;
+    use crate::query::{Query, QueryParser, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter};
+
+    fn text_options(similarity: &str) -> TextOptions {
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_similarity(similarity),
+        )
+    }
+
+    #[test]
+    fn test_similarity_per_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let bm25_field = schema_builder.add_text_field("bm25", TEXT);
+        let tfidf_field = schema_builder.add_text_field("tfidf", text_options("tfidf"));
+        let lm_field = schema_builder.add_text_field("lm", text_options("lm_dirichlet"));
+        let dfr_field = schema_builder.add_text_field("dfr", text_options("dfr"));
+        let custom_field = schema_builder.add_text_field("custom", text_options("bm25_no_norm"));
+        let index = Index::create_in_ram(schema_builder.build());
+        index
+            .similarities()
+            .register("bm25_no_norm", Bm25Similarity::new(1.2, 0.0));
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for text in ["a b c d e f g h", "a a b", "b", "a"] {
+            index_writer.add_document(doc!(
+                bm25_field => text,
+                tfidf_field => text,
+                lm_field => text,
+                dfr_field => text,
+                custom_field => text,
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let top_docs = |field| -> crate::Result<Vec<(Score, DocAddress)>> {
+            let query = TermQuery::new(
+                Term::from_field_text(field, "a"),
+                IndexRecordOption::WithFreqs,
+            );
+            searcher.search(&query, &TopDocs::with_limit(3))
+        };
+        let doc_ids = |top_docs: &[(Score, DocAddress)]| -> Vec<u32> {
+            top_docs
+                .iter()
+                .map(|(_, doc_address)| doc_address.doc_id)
+                .collect()
+        };
+        let bm25_top_docs = top_docs(bm25_field)?;
+        assert_eq!(doc_ids(&bm25_top_docs), [1, 3, 0]);
+        for field in [tfidf_field, lm_field, dfr_field] {
+            let similarity_top_docs = top_docs(field)?;
+            // The long document ranks last, but the scores differ.
+            assert_eq!(doc_ids(&similarity_top_docs)[2], 0);
+            assert!((similarity_top_docs[0].0 - bm25_top_docs[0].0).abs() > 0.01);
+            for &(score, doc_address) in &similarity_top_docs {
+                let query = TermQuery::new(
+                    Term::from_field_text(field, "a"),
+                    IndexRecordOption::WithFreqs,
+                );
+                let explanation = query.explain(&searcher, doc_address)?;
+                assert_nearly_equals!(explanation.value(), score);
+            }
+        }
+        // Without length normalization, the documents containing "a" once score the same.
+        let custom_top_docs = top_docs(custom_field)?;
+        assert_nearly_equals!(custom_top_docs[1].0, custom_top_docs[2].0);
+
+        // Phrase queries are scored with the similarity of their field too.
+        let query_parser = QueryParser::for_index(&index, vec![dfr_field]);
+        let query = query_parser.parse_query("\"a b\"")?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert_eq!(doc_ids(&top_docs), [1, 0]);
+        for (score, doc_address) in top_docs {
+            assert_nearly_equals!(query.explain(&searcher, doc_address)?.value(), score);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_similarity() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", text_options("unknown"));
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        assert!(searcher.search(&query, &TopDocs::with_limit(1)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use super::{Bm25Similarity, DfrSimilarity, LmDirichletSimilarity, Similarity, TfIdfSimilarity};
+
+/// The similarity manager serves as a store for the similarities the fields of an index can
+/// be scored with.
+///
+/// By default, it is populated with the following similarities.
+///
+/// - `bm25` : [`Bm25Similarity`] with `k1 = 1.2` and `b = 0.75`.
+/// - `tfidf` : [`TfIdfSimilarity`].
+/// - `lm_dirichlet` : [`LmDirichletSimilarity`] with `mu = 2000`.
+/// - `dfr` : [`DfrSimilarity`] with `c = 1`.
+#[derive(Clone)]
+pub struct SimilarityManager {
+    similarities: Arc<RwLock<HashMap<String, Arc<dyn Similarity>>>>,
+}
+
+impl SimilarityManager {
+    /// Creates an empty similarity manager.
+    pub fn new() -> Self {
+        Self {
+            similarities: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Registers a new similarity associated with a given name.
+    pub fn register<S: Similarity>(&self, similarity_name: &str, similarity: S) {
+        self.similarities
+            .write()
+            .expect("Acquiring the lock should never fail")
+            .insert(similarity_name.to_string(), Arc::new(similarity));
+    }
+
+    /// Accessing a similarity given its name.
+    pub fn get(&self, similarity_name: &str) -> Option<Arc<dyn Similarity>> {
+        self.similarities
+            .read()
+            .expect("Acquiring the lock should never fail")
+            .get(similarity_name)
+            .cloned()
+    }
+}
+
+impl Default for SimilarityManager {
+    /// Creates a `SimilarityManager` prepopulated with
+    /// the default similarities of `tantivy`.
+    fn default() -> SimilarityManager {
+        let manager = SimilarityManager::new();
+        manager.register("bm25", Bm25Similarity::default());
+        manager.register("tfidf", TfIdfSimilarity);
+        manager.register("lm_dirichlet", LmDirichletSimilarity::default());
+        manager.register("dfr", DfrSimilarity::default());
+        manager
+    }
+}
@@ -0,0 +1,49 @@
+use super::{Similarity, SimilarityScorer, TermStatistics};
+use crate::query::Explanation;
+use crate::Score;
+
+/// The classic TF-IDF similarity, scoring a document as
+/// `sqrt(freq) * idf^2 / sqrt(dl)`, with `idf = 1 + ln((N + 1) / (n + 1))`.
+///
+/// The `idf` of the terms of a phrase is the sum of their `idf`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TfIdfSimilarity;
+
+fn idf(doc_freq: u64, total_num_docs: u64) -> Score {
+    1.0 + ((total_num_docs as Score + 1.0) / (doc_freq as Score + 1.0)).ln()
+}
+
+impl Similarity for TfIdfSimilarity {
+    fn scorer(&self, term_statistics: &TermStatistics) -> Box<dyn SimilarityScorer> {
+        let idf: Score = term_statistics
+            .doc_freqs
+            .iter()
+            .map(|&doc_freq| idf(doc_freq, term_statistics.total_num_docs))
+            .sum();
+        Box::new(TfIdfScorer { idf })
+    }
+}
+
+struct TfIdfScorer {
+    idf: Score,
+}
+
+impl SimilarityScorer for TfIdfScorer {
+    fn score(&self, term_freq: u32, fieldnorm: u32) -> Score {
+        (term_freq as Score).sqrt() * self.idf * self.idf / (fieldnorm.max(1) as Score).sqrt()
+    }
+
+    fn explain(&self, term_freq: u32, fieldnorm: u32) -> Explanation {
+        let mut explanation = Explanation::new(
+            "TF-IDF, computed as sqrt(freq) * idf^2 / sqrt(dl)",
+            self.score(term_freq, fieldnorm),
+        );
+        explanation.add_const(
+            "freq, occurrences of term within document",
+            term_freq as Score,
+        );
+        explanation.add_const("idf, computed as 1 + log((N + 1) / (n + 1))", self.idf);
+        explanation.add_const("dl, length of field", fieldnorm as Score);
+        explanation
+    }
+}
@@ -1,8 +1,10 @@
 use std::fmt;
+use std::ops::Bound;
 
 use super::term_weight::TermWeight;
 use crate::query::bm25::Bm25Weight;
-use crate::query::{EnableScoring, Explanation, Query, Weight};
+use crate::query::range_query::is_type_valid_for_fastfield_range_query;
+use crate::query::{BoundsRange, EnableScoring, Explanation, FastFieldRangeWeight, Query, Weight};
 use crate::schema::IndexRecordOption;
 use crate::Term;
 
@@ -97,9 +99,16 @@ impl TermQuery {
         }
         let bm25_weight = match enable_scoring {
             EnableScoring::Enabled {
+                searcher,
                 statistics_provider,
-                ..
-            } => Bm25Weight::for_terms(statistics_provider, &[self.term.clone()])?,
+            } => {
+                let similarity = searcher.index().similarity_for_field(self.term.field())?;
+                Bm25Weight::for_terms_with_similarity(
+                    statistics_provider,
+                    std::slice::from_ref(&self.term),
+                    &*similarity,
+                )?
+            }
             EnableScoring::Disabled { .. } => {
                 Bm25Weight::new(Explanation::new("<no score>", 1.0f32), 1.0f32)
             }
@@ -122,6 +131,22 @@ impl TermQuery {
 
 impl Query for TermQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let field_type = enable_scoring
+            .schema()
+            .get_field_entry(self.term.field())
+            .field_type();
+        if !field_type.is_indexed()
+            && field_type.is_fast()
+            && is_type_valid_for_fastfield_range_query(self.term.typ())
+        {
+            // Store-only fields are not in the inverted index, but can still be filtered on
+            // their fast field.
+            let bounds = BoundsRange::new(
+                Bound::Included(self.term.clone()),
+                Bound::Included(self.term.clone()),
+            );
+            return Ok(Box::new(FastFieldRangeWeight::new(bounds)));
+        }
         Ok(Box::new(self.specialized_weight(enable_scoring)?))
     }
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
@@ -137,8 +162,10 @@ mod tests {
     use columnar::MonotonicallyMappableToU128;
 
     use crate::collector::{Count, TopDocs};
-    use crate::query::{Query, QueryParser, TermQuery};
-    use crate::schema::{IndexRecordOption, IntoIpv6Addr, Schema, INDEXED, STORED};
+    use crate::query::{EnableScoring, Query, QueryParser, TermQuery};
+    use crate::schema::{
+        IndexRecordOption, IntoIpv6Addr, Schema, FAST, INDEXED, STORED, STRING, TEXT,
+    };
     use crate::{Index, IndexWriter, Term};
 
     #[test]
@@ -194,4 +221,54 @@ mod tests {
         assert_single_hit(query_from_text(format!("\"{ip_addr_1}\"")));
         assert_single_hit(query_from_text(format!("\"{ip_addr_2}\"")));
     }
+
+    #[test]
+    fn test_term_query_on_store_only_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let tag_field = schema_builder.add_text_field("tag", STRING | FAST);
+        let year_field = schema_builder.add_u64_field("year", INDEXED | FAST);
+        schema_builder.set_all_store_only();
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for year in 2000..2010u64 {
+            let tag = if year % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(
+                body_field => "some long text",
+                tag_field => tag,
+                year_field => year,
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(
+            segment_reader
+                .inverted_index(body_field)?
+                .terms()
+                .num_terms(),
+            0
+        );
+
+        let count = |query: &dyn Query| searcher.search(query, &Count).unwrap();
+        let term_query = |term| TermQuery::new(term, IndexRecordOption::Basic);
+        assert_eq!(
+            count(&term_query(Term::from_field_text(tag_field, "odd"))),
+            5
+        );
+        assert_eq!(
+            count(&term_query(Term::from_field_u64(year_field, 2003))),
+            1
+        );
+        assert!(term_query(Term::from_field_text(body_field, "text"))
+            .weight(EnableScoring::disabled_from_searcher(&searcher))
+            .is_err());
+
+        let query_parser = QueryParser::for_index(&index, vec![tag_field]);
+        assert_eq!(count(&*query_parser.parse_query("even AND year:2004")?), 1);
+        assert_eq!(count(&*query_parser.parse_query("year:[2002 TO 2005]")?), 4);
+        assert!(query_parser.parse_query("body:text").is_err());
+        assert!(query_parser.parse_query("\"ev\"*").is_err());
+        Ok(())
+    }
 }
@@ -3,6 +3,7 @@ use crate::fieldnorm::FieldNormReader;
 use crate::postings::{FreqReadingOption, Postings, SegmentPostings};
 use crate::query::bm25::Bm25Weight;
 use crate::query::{Explanation, Scorer};
+use crate::schema::Field;
 use crate::{DocId, Score};
 
 #[derive(Clone)]
@@ -10,6 +11,9 @@ pub struct TermScorer {
     postings: SegmentPostings,
     fieldnorm_reader: FieldNormReader,
     similarity_weight: Bm25Weight,
+    // The field of the term, used to report the matched positions.
+    field_opt: Option<Field>,
+    positions_buffer: Vec<u32>,
 }
 
 impl TermScorer {
@@ -22,9 +26,17 @@ impl TermScorer {
             postings,
             fieldnorm_reader,
             similarity_weight,
+            field_opt: None,
+            positions_buffer: Vec::new(),
         }
     }
 
+    /// Sets the field of the term, so that the matched positions are reported.
+    pub(crate) fn with_field(mut self, field: Field) -> TermScorer {
+        self.field_opt = Some(field);
+        self
+    }
+
     pub(crate) fn shallow_seek(&mut self, target_doc: DocId) {
         self.postings.block_cursor.shallow_seek(target_doc);
     }
@@ -121,6 +133,16 @@ impl Scorer for TermScorer {
         let term_freq = self.term_freq();
         self.similarity_weight.score(fieldnorm_id, term_freq)
     }
+
+    fn matched_positions(&mut self, visitor: &mut dyn FnMut(Field, u32)) {
+        let Some(field) = self.field_opt else {
+            return;
+        };
+        self.postings.positions(&mut self.positions_buffer);
+        for &position in &self.positions_buffer {
+            visitor(field, position);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -132,7 +154,7 @@ mod tests {
     use crate::merge_policy::NoMergePolicy;
     use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
     use crate::query::term_query::TermScorer;
-    use crate::query::{Bm25Weight, EnableScoring, Scorer, TermQuery};
+    use crate::query::{Bm25Weight, BooleanQuery, EnableScoring, Query, Scorer, TermQuery};
     use crate::schema::{IndexRecordOption, Schema, TEXT};
     use crate::{
         assert_nearly_equals, DocId, DocSet, Index, IndexWriter, Score, Searcher, Term, TERMINATED,
@@ -333,4 +355,62 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_term_scorer_matched_positions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a b a c"))?;
+        index_writer.add_document(doc!(text_field => "c b"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let term_query = |text: &str, index_record_option| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text_field, text),
+                index_record_option,
+            ))
+        };
+        let matched_positions = |scorer: &mut Box<dyn Scorer>| {
+            let mut positions = Vec::new();
+            scorer.matched_positions(&mut |field, position| positions.push((field, position)));
+            positions.sort();
+            positions
+        };
+
+        let query = term_query("a", IndexRecordOption::WithFreqsAndPositions);
+        let weight = query.weight(EnableScoring::enabled_from_searcher(&searcher))?;
+        let mut scorer = weight.scorer(segment_reader, 1.0)?;
+        assert_eq!(scorer.doc(), 0);
+        assert_eq!(
+            matched_positions(&mut scorer),
+            vec![(text_field, 0), (text_field, 2)]
+        );
+
+        // Positions are not read if the query does not require them.
+        let query = term_query("a", IndexRecordOption::WithFreqs);
+        let weight = query.weight(EnableScoring::enabled_from_searcher(&searcher))?;
+        let mut scorer = weight.scorer(segment_reader, 1.0)?;
+        assert!(matched_positions(&mut scorer).is_empty());
+
+        let query = BooleanQuery::intersection(vec![
+            term_query("b", IndexRecordOption::WithFreqsAndPositions),
+            term_query("c", IndexRecordOption::WithFreqsAndPositions),
+        ]);
+        let weight = query.weight(EnableScoring::enabled_from_searcher(&searcher))?;
+        let mut scorer = weight.scorer(segment_reader, 1.0)?;
+        assert_eq!(scorer.doc(), 0);
+        assert_eq!(
+            matched_positions(&mut scorer),
+            vec![(text_field, 1), (text_field, 3)]
+        );
+        assert_eq!(scorer.advance(), 1);
+        assert_eq!(
+            matched_positions(&mut scorer),
+            vec![(text_field, 0), (text_field, 1)]
+        );
+        Ok(())
+    }
 }
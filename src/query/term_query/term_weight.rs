@@ -127,18 +127,8 @@ impl TermWeight {
         let similarity_weight = self.similarity_weight.boost_by(boost);
         let postings_opt: Option<SegmentPostings> =
             inverted_index.read_postings(&self.term, self.index_record_option)?;
-        if let Some(segment_postings) = postings_opt {
-            Ok(TermScorer::new(
-                segment_postings,
-                fieldnorm_reader,
-                similarity_weight,
-            ))
-        } else {
-            Ok(TermScorer::new(
-                SegmentPostings::empty(),
-                fieldnorm_reader,
-                similarity_weight,
-            ))
-        }
+        let segment_postings = postings_opt.unwrap_or_else(SegmentPostings::empty);
+        let term_scorer = TermScorer::new(segment_postings, fieldnorm_reader, similarity_weight);
+        Ok(term_scorer.with_field(field))
     }
 }
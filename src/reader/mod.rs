@@ -1,16 +1,27 @@
+mod point_in_time;
+mod result_cache;
+mod slow_query_log;
 mod warming;
+mod warmup;
 
+use std::num::NonZeroUsize;
 use std::sync::atomic::AtomicU64;
 use std::sync::{atomic, Arc, Weak};
+use std::time::Duration;
 
 use arc_swap::ArcSwap;
+pub use point_in_time::PointInTimeId;
+pub use slow_query_log::{SlowQuery, SlowQueryCallback, SlowQueryLog, SlowQuerySegment};
 pub use warming::Warmer;
+pub use warmup::WarmupPlan;
 
+use self::point_in_time::PointsInTime;
+pub(crate) use self::result_cache::ResultCache;
 use self::warming::WarmingState;
-use crate::core::searcher::{SearcherGeneration, SearcherInner};
+use crate::core::searcher::{SearcherGeneration, SearcherInner, SearcherSettings};
 use crate::directory::{Directory, WatchCallback, WatchHandle, META_LOCK};
 use crate::store::DOCSTORE_CACHE_CAPACITY;
-use crate::{Index, Inventory, Searcher, SegmentReader, TrackedObject};
+use crate::{Index, Inventory, MemoryBudget, Searcher, SegmentReader, TrackedObject};
 
 /// Defines when a new version of the index should be reloaded.
 ///
@@ -27,7 +38,10 @@ pub enum ReloadPolicy {
     Manual,
     /// The index is reloaded within milliseconds after a new commit is available.
     /// This is made possible by watching changes in the `meta.json` file.
-    OnCommitWithDelay, // TODO add NEAR_REAL_TIME(target_ms)
+    ///
+    /// The index is also reloaded when uncommitted segments are published by
+    /// [`IndexWriter::refresh()`](crate::IndexWriter::refresh).
+    OnCommitWithDelay,
 }
 
 /// [`IndexReader`] builder
@@ -35,6 +49,10 @@ pub enum ReloadPolicy {
 /// It makes it possible to configure:
 /// - [`ReloadPolicy`] defining when new index versions are detected
 /// - [`Warmer`] implementations
+/// - a [`WarmupPlan`] applied to every new searcher
+/// - a [`SlowQueryLog`] reporting the slow searches
+/// - a [`MemoryBudget`] bounding the memory of the searches and of the doc store caches
+/// - a cache of the results of [`Searcher::search_cached`]
 /// - number of warming threads, for parallelizing warming work
 /// - The cache size of the underlying doc store readers.
 #[derive(Clone)]
@@ -43,6 +61,10 @@ pub struct IndexReaderBuilder {
     index: Index,
     warmers: Vec<Weak<dyn Warmer>>,
     num_warming_threads: usize,
+    warmup_plan: Option<WarmupPlan>,
+    slow_query_log: Option<SlowQueryLog>,
+    memory_budget: Option<MemoryBudget>,
+    result_cache: Option<(NonZeroUsize, Duration)>,
     doc_store_cache_num_blocks: usize,
 }
 
@@ -54,6 +76,10 @@ impl IndexReaderBuilder {
             index,
             warmers: Vec::new(),
             num_warming_threads: 1,
+            warmup_plan: None,
+            slow_query_log: None,
+            memory_budget: None,
+            result_cache: None,
             doc_store_cache_num_blocks: DOCSTORE_CACHE_CAPACITY,
         }
     }
@@ -70,38 +96,45 @@ impl IndexReaderBuilder {
             self.warmers,
             searcher_generation_inventory.clone(),
         )?;
+        let searcher_settings = SearcherSettings {
+            doc_store_cache_num_blocks: self.doc_store_cache_num_blocks,
+            slow_query_log: self.slow_query_log,
+            memory_budget: self.memory_budget,
+            result_cache: self
+                .result_cache
+                .map(|(num_entries, time_to_live)| ResultCache::new(num_entries, time_to_live)),
+        };
         let inner_reader = InnerIndexReader::new(
-            self.doc_store_cache_num_blocks,
+            searcher_settings,
             self.index,
             warming_state,
+            self.warmup_plan,
             searcher_generation_inventory,
         )?;
         let inner_reader_arc = Arc::new(inner_reader);
-        let watch_handle_opt: Option<WatchHandle> = match self.reload_policy {
+        let watch_handles: Vec<WatchHandle> = match self.reload_policy {
             ReloadPolicy::Manual => {
                 // No need to set anything...
-                None
+                Vec::new()
             }
             ReloadPolicy::OnCommitWithDelay => {
                 let inner_reader_arc_clone = inner_reader_arc.clone();
-                let callback = move || {
+                let callback = WatchCallback::new(move || {
                     if let Err(err) = inner_reader_arc_clone.reload() {
                         error!(
                             "Error while loading searcher after commit was detected. {:?}",
                             err
                         );
                     }
-                };
-                let watch_handle = inner_reader_arc
-                    .index
-                    .directory()
-                    .watch(WatchCallback::new(callback))?;
-                Some(watch_handle)
+                });
+                let watch_handle = inner_reader_arc.index.directory().watch(callback.clone())?;
+                let refresh_watch_handle = inner_reader_arc.index.near_real_time().watch(callback);
+                vec![watch_handle, refresh_watch_handle]
             }
         };
         Ok(IndexReader {
             inner: inner_reader_arc,
-            _watch_handle_opt: watch_handle_opt,
+            _watch_handles: watch_handles,
         })
     }
 
@@ -116,7 +149,9 @@ impl IndexReaderBuilder {
 
     /// Sets the cache size of the doc store readers.
     ///
-    /// The doc store readers cache by default DOCSTORE_CACHE_CAPACITY(100) decompressed blocks.
+    /// The doc store readers of all the segments of a searcher share an LRU cache of
+    /// decompressed blocks, which holds by default DOCSTORE_CACHE_CAPACITY(100) blocks.
+    /// Each searcher generation gets its own cache. Setting the capacity to 0 disables the cache.
     #[must_use]
     pub fn doc_store_cache_num_blocks(
         mut self,
@@ -133,6 +168,49 @@ impl IndexReaderBuilder {
         self
     }
 
+    /// Sets the [`WarmupPlan`] applied to every new searcher.
+    ///
+    /// The plan is applied after the [`Warmer`]s, before the searcher is returned by
+    /// [`IndexReader::searcher()`]. An error while warming up fails the reload.
+    #[must_use]
+    pub fn warmup_plan(mut self, warmup_plan: WarmupPlan) -> IndexReaderBuilder {
+        self.warmup_plan = Some(warmup_plan);
+        self
+    }
+
+    /// Sets the [`SlowQueryLog`] reporting the searches of the searchers of the reader which
+    /// exceed its threshold.
+    #[must_use]
+    pub fn slow_query_log(mut self, slow_query_log: SlowQueryLog) -> IndexReaderBuilder {
+        self.slow_query_log = Some(slow_query_log);
+        self
+    }
+
+    /// Sets the [`MemoryBudget`] against which the doc store caches and the collectors of the
+    /// searchers of the reader reserve their memory.
+    ///
+    /// The budget may be shared with other readers and aggregation requests.
+    #[must_use]
+    pub fn memory_budget(mut self, memory_budget: MemoryBudget) -> IndexReaderBuilder {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    /// Enables the cache of the results of [`Searcher::search_cached`].
+    ///
+    /// The cache holds at most `num_entries` results, evicting the least recently used ones
+    /// first. A result is recomputed once it is older than `time_to_live`. All of the results are
+    /// invalidated when the reader is reloaded. Each reader gets its own cache.
+    #[must_use]
+    pub fn result_cache(
+        mut self,
+        num_entries: NonZeroUsize,
+        time_to_live: Duration,
+    ) -> IndexReaderBuilder {
+        self.result_cache = Some((num_entries, time_to_live));
+        self
+    }
+
     /// Sets the number of warming threads.
     ///
     /// This allows parallelizing warming work when there are multiple [`Warmer`] registered with
@@ -153,19 +231,22 @@ impl TryInto<IndexReader> for IndexReaderBuilder {
 }
 
 struct InnerIndexReader {
-    doc_store_cache_num_blocks: usize,
+    searcher_settings: SearcherSettings,
     index: Index,
     warming_state: WarmingState,
+    warmup_plan: Option<WarmupPlan>,
     searcher: arc_swap::ArcSwap<SearcherInner>,
     searcher_generation_counter: Arc<AtomicU64>,
     searcher_generation_inventory: Inventory<SearcherGeneration>,
+    points_in_time: PointsInTime,
 }
 
 impl InnerIndexReader {
     fn new(
-        doc_store_cache_num_blocks: usize,
+        searcher_settings: SearcherSettings,
         index: Index,
         warming_state: WarmingState,
+        warmup_plan: Option<WarmupPlan>,
         // The searcher_generation_inventory is not used as source, but as target to track the
         // loaded segments.
         searcher_generation_inventory: Inventory<SearcherGeneration>,
@@ -174,31 +255,41 @@ impl InnerIndexReader {
 
         let searcher = Self::create_searcher(
             &index,
-            doc_store_cache_num_blocks,
+            &searcher_settings,
             &warming_state,
+            warmup_plan.as_ref(),
             &searcher_generation_counter,
             &searcher_generation_inventory,
         )?;
         Ok(InnerIndexReader {
-            doc_store_cache_num_blocks,
+            searcher_settings,
             index,
             warming_state,
+            warmup_plan,
             searcher: ArcSwap::from(searcher),
             searcher_generation_counter,
             searcher_generation_inventory,
+            points_in_time: PointsInTime::default(),
         })
     }
     /// Opens the freshest segments [`SegmentReader`].
     ///
+    /// The segments published by [`IndexWriter::refresh()`](crate::IndexWriter::refresh)
+    /// are used instead of the committed ones if they are more recent.
+    ///
     /// This function acquires a lock to prevent GC from removing files
     /// as we are opening our index.
     fn open_segment_readers(index: &Index) -> crate::Result<Vec<SegmentReader>> {
         // Prevents segment files from getting deleted while we are in the process of opening them
         let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
-        let searchable_segments = index.searchable_segments()?;
-        let segment_readers = searchable_segments
-            .iter()
-            .map(SegmentReader::open)
+        let committed_metas = index.load_metas()?;
+        let segment_metas = match index.near_real_time().snapshot() {
+            Some(snapshot) if snapshot.opstamp > committed_metas.opstamp => snapshot.segment_metas,
+            _ => committed_metas.segments,
+        };
+        let segment_readers = segment_metas
+            .into_iter()
+            .map(|segment_meta| SegmentReader::open(&index.segment(segment_meta)))
             .collect::<crate::Result<_>>()?;
         Ok(segment_readers)
     }
@@ -216,8 +307,9 @@ impl InnerIndexReader {
 
     fn create_searcher(
         index: &Index,
-        doc_store_cache_num_blocks: usize,
+        searcher_settings: &SearcherSettings,
         warming_state: &WarmingState,
+        warmup_plan: Option<&WarmupPlan>,
         searcher_generation_counter: &Arc<AtomicU64>,
         searcher_generation_inventory: &Inventory<SearcherGeneration>,
     ) -> crate::Result<Arc<SearcherInner>> {
@@ -234,21 +326,29 @@ impl InnerIndexReader {
             index.clone(),
             segment_readers,
             searcher_generation,
-            doc_store_cache_num_blocks,
+            searcher_settings.clone(),
         )?);
 
         warming_state.warm_new_searcher_generation(&searcher.clone().into())?;
+        if let Some(warmup_plan) = warmup_plan {
+            Searcher::from(searcher.clone()).warm_up(warmup_plan)?;
+        }
         Ok(searcher)
     }
 
     fn reload(&self) -> crate::Result<()> {
         let searcher = Self::create_searcher(
             &self.index,
-            self.doc_store_cache_num_blocks,
+            &self.searcher_settings,
             &self.warming_state,
+            self.warmup_plan.as_ref(),
             &self.searcher_generation_counter,
             &self.searcher_generation_inventory,
         )?;
+        if let Some(result_cache) = self.searcher_settings.result_cache.as_ref() {
+            let generation_id = Searcher::from(searcher.clone()).generation().generation_id();
+            result_cache.retain_generation(generation_id);
+        }
 
         self.searcher.store(searcher);
 
@@ -269,7 +369,7 @@ impl InnerIndexReader {
 #[derive(Clone)]
 pub struct IndexReader {
     inner: Arc<InnerIndexReader>,
-    _watch_handle_opt: Option<WatchHandle>,
+    _watch_handles: Vec<WatchHandle>,
 }
 
 impl IndexReader {
@@ -279,7 +379,7 @@ impl IndexReader {
     }
 
     /// Update searchers so that they reflect the state of the last
-    /// `.commit()`.
+    /// `.commit()` or `.refresh()`.
     ///
     /// If you set up the [`ReloadPolicy::OnCommitWithDelay`] (which is the default)
     /// every commit should be rapidly reflected on your `IndexReader` and you should
@@ -301,4 +401,36 @@ impl IndexReader {
     pub fn searcher(&self) -> Searcher {
         self.inner.searcher()
     }
+
+    /// Pins the current searcher as a point in time, and returns its id.
+    ///
+    /// The searcher of a point in time is returned by
+    /// [`IndexReader::point_in_time_searcher`] until it is released, no matter how many commits
+    /// and reloads happen meanwhile, so that the pages of a paginated search are all computed
+    /// over the same documents. The files of its segments are not garbage collected until then.
+    ///
+    /// The point in time is released automatically when it has not been accessed for
+    /// `keep_alive`.
+    pub fn open_point_in_time(&self, keep_alive: Duration) -> crate::Result<PointInTimeId> {
+        self.inner
+            .points_in_time
+            .open(&self.inner.index, self.searcher(), keep_alive)
+    }
+
+    /// Returns the searcher of the point in time `id`, and extends its keep alive.
+    ///
+    /// Returns an error if the point in time was released or expired.
+    pub fn point_in_time_searcher(&self, id: PointInTimeId) -> crate::Result<Searcher> {
+        self.inner.points_in_time.searcher(id)
+    }
+
+    /// Releases the point in time `id`, returning whether it was still open.
+    pub fn release_point_in_time(&self, id: PointInTimeId) -> bool {
+        self.inner.points_in_time.release(id)
+    }
+
+    /// Returns the ids of the points in time which are still open.
+    pub fn points_in_time(&self) -> Vec<PointInTimeId> {
+        self.inner.points_in_time.ids()
+    }
 }
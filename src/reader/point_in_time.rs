@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::directory::{Directory, META_LOCK};
+use crate::index::SegmentMeta;
+use crate::{Index, Searcher, TantivyError};
+
+/// Identifier of a point in time opened with
+/// [`IndexReader::open_point_in_time`](crate::IndexReader::open_point_in_time).
+///
+/// It converts from and to an `u64`, so that it can be handed to the clients of a paginated
+/// search and sent back with their next page request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PointInTimeId(u64);
+
+impl From<u64> for PointInTimeId {
+    fn from(id: u64) -> PointInTimeId {
+        PointInTimeId(id)
+    }
+}
+
+impl From<PointInTimeId> for u64 {
+    fn from(id: PointInTimeId) -> u64 {
+        id.0
+    }
+}
+
+impl fmt::Display for PointInTimeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+struct PointInTime {
+    searcher: Searcher,
+    // Keeps the files of the segments of the searcher from being garbage collected.
+    _segment_metas: Vec<SegmentMeta>,
+    keep_alive: Duration,
+    expires_at: Instant,
+}
+
+/// The points in time opened on an [`IndexReader`](crate::IndexReader).
+///
+/// Expired points in time are released lazily, on the next call to the registry.
+#[derive(Default)]
+pub(crate) struct PointsInTime {
+    inner: Mutex<PointsInTimeInner>,
+}
+
+#[derive(Default)]
+struct PointsInTimeInner {
+    next_id: u64,
+    points_in_time: HashMap<PointInTimeId, PointInTime>,
+}
+
+impl PointsInTimeInner {
+    fn release_expired(&mut self, now: Instant) {
+        self.points_in_time
+            .retain(|_, point_in_time| point_in_time.expires_at > now);
+    }
+}
+
+impl PointsInTime {
+    /// Pins `searcher` until `keep_alive` elapses without it being accessed.
+    pub fn open(
+        &self,
+        index: &Index,
+        searcher: Searcher,
+        keep_alive: Duration,
+    ) -> crate::Result<PointInTimeId> {
+        let segment_metas = {
+            // Prevents the garbage collection from running while we look for the segments.
+            let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
+            let living_segment_metas = index.list_all_segment_metas();
+            searcher
+                .segment_readers()
+                .iter()
+                .filter_map(|segment_reader| {
+                    living_segment_metas
+                        .iter()
+                        .find(|segment_meta| {
+                            segment_meta.id() == segment_reader.segment_id()
+                                && segment_meta.delete_opstamp() == segment_reader.delete_opstamp()
+                        })
+                        .cloned()
+                })
+                .collect()
+        };
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.release_expired(now);
+        let id = PointInTimeId(inner.next_id);
+        inner.next_id += 1;
+        inner.points_in_time.insert(
+            id,
+            PointInTime {
+                searcher,
+                _segment_metas: segment_metas,
+                keep_alive,
+                expires_at: now + keep_alive,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Returns the searcher of the point in time `id`, and extends its keep alive.
+    pub fn searcher(&self, id: PointInTimeId) -> crate::Result<Searcher> {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.release_expired(now);
+        let point_in_time = inner.points_in_time.get_mut(&id).ok_or_else(|| {
+            TantivyError::InvalidArgument(format!("Unknown or expired point in time {id}"))
+        })?;
+        point_in_time.expires_at = now + point_in_time.keep_alive;
+        Ok(point_in_time.searcher.clone())
+    }
+
+    /// Releases the point in time `id`, returning whether it was still open.
+    pub fn release(&self, id: PointInTimeId) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.release_expired(Instant::now());
+        inner.points_in_time.remove(&id).is_some()
+    }
+
+    /// Returns the ids of the points in time which have not expired.
+    pub fn ids(&self) -> Vec<PointInTimeId> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.release_expired(Instant::now());
+        let mut ids: Vec<PointInTimeId> = inner.points_in_time.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::collector::Count;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy, TantivyError};
+
+    #[test]
+    fn test_point_in_time() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for _ in 0..2 {
+            index_writer.add_document(doc!(tag => "a"))?;
+            index_writer.commit()?;
+        }
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let point_in_time_id = reader.open_point_in_time(Duration::from_secs(3600))?;
+        let pinned_segment_ids: Vec<_> = reader
+            .point_in_time_searcher(point_in_time_id)?
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.segment_id())
+            .collect();
+        assert_eq!(pinned_segment_ids.len(), 2);
+
+        // Merging the segments and garbage collecting does not affect the point in time.
+        index_writer.add_document(doc!(tag => "b"))?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.garbage_collect_files().wait()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().search(&AllQuery, &Count)?, 3);
+        assert_eq!(reader.searcher().segment_readers().len(), 1);
+        let num_pinned_files = |index: &Index| {
+            index
+                .directory()
+                .list_managed_files()
+                .iter()
+                .filter(|path| {
+                    pinned_segment_ids.iter().any(|segment_id| {
+                        path.to_string_lossy()
+                            .starts_with(&segment_id.uuid_string())
+                    })
+                })
+                .count()
+        };
+        assert!(num_pinned_files(&index) > 0);
+        let searcher = reader.point_in_time_searcher(point_in_time_id)?;
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+        assert_eq!(reader.points_in_time(), vec![point_in_time_id]);
+
+        assert!(reader.release_point_in_time(point_in_time_id));
+        assert!(!reader.release_point_in_time(point_in_time_id));
+        drop(searcher);
+        index_writer.garbage_collect_files().wait()?;
+        assert_eq!(num_pinned_files(&index), 0);
+        assert!(matches!(
+            reader.point_in_time_searcher(point_in_time_id),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_point_in_time_expires() -> crate::Result<()> {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let reader = index.reader()?;
+        let expired_id = reader.open_point_in_time(Duration::ZERO)?;
+        let open_id = reader.open_point_in_time(Duration::from_secs(3600))?;
+        assert_ne!(expired_id, open_id);
+        assert_eq!(u64::from(open_id), 1);
+        assert!(reader.point_in_time_searcher(expired_id).is_err());
+        assert!(reader.point_in_time_searcher(open_id).is_ok());
+        assert_eq!(reader.points_in_time(), vec![open_id]);
+        Ok(())
+    }
+}
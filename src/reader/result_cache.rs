@@ -0,0 +1,209 @@
+use std::any::Any;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+
+use crate::query::Query;
+use crate::store::CacheStats;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ResultCacheKey {
+    generation_id: u64,
+    query: String,
+    collector_key: String,
+}
+
+struct CachedResult {
+    result: Box<dyn Any + Send>,
+    inserted_at: Instant,
+}
+
+struct InnerResultCache {
+    cache: LruCache<ResultCacheKey, CachedResult>,
+    time_to_live: Duration,
+    cache_hits: usize,
+    cache_misses: usize,
+}
+
+/// Cache of the results of the searches run with
+/// [`Searcher::search_cached`](crate::Searcher::search_cached).
+///
+/// Results are evicted when the cache is full, least recently used first, or when they are older than the time to live of the cache. As they are keyed on the
+/// searcher generation, reloading the reader invalidates all of them.
+#[derive(Clone)]
+pub(crate) struct ResultCache {
+    inner: Arc<Mutex<InnerResultCache>>,
+}
+
+impl ResultCache {
+    pub fn new(num_entries: NonZeroUsize, time_to_live: Duration) -> ResultCache {
+        ResultCache {
+            inner: Arc::new(Mutex::new(InnerResultCache {
+                cache: LruCache::new(num_entries),
+                time_to_live,
+                cache_hits: 0,
+                cache_misses: 0,
+            })),
+        }
+    }
+
+    /// Returns the cached result of `query` and `collector_key` for the generation
+    /// `generation_id`, computing it with `search` on a miss.
+    pub fn get_or_search<F, S>(
+        &self,
+        generation_id: u64,
+        query: &dyn Query,
+        collector_key: &str,
+        search: S,
+    ) -> crate::Result<F>
+    where
+        F: Clone + Send + 'static,
+        S: FnOnce() -> crate::Result<F>,
+    {
+        let key = ResultCacheKey {
+            generation_id,
+            query: format!("{query:?}"),
+            collector_key: collector_key.to_string(),
+        };
+        if let Some(result) = self.get(&key) {
+            return Ok(result);
+        }
+        let result = search()?;
+        self.inner.lock().unwrap().cache.put(
+            key,
+            CachedResult {
+                result: Box::new(result.clone()),
+                inserted_at: Instant::now(),
+            },
+        );
+        Ok(result)
+    }
+
+    fn get<F: Clone + 'static>(&self, key: &ResultCacheKey) -> Option<F> {
+        let mut inner = self.inner.lock().unwrap();
+        let time_to_live = inner.time_to_live;
+        // A result of another type is only found if the collector key was reused for a collector
+        // of another type, and is treated as a miss.
+        let result = match inner.cache.get(key) {
+            Some(cached_result) if cached_result.inserted_at.elapsed() < time_to_live => {
+                cached_result.result.downcast_ref::<F>().cloned()
+            }
+            Some(_) => {
+                inner.cache.pop(key);
+                None
+            }
+            None => None,
+        };
+        if result.is_some() {
+            inner.cache_hits += 1;
+        } else {
+            inner.cache_misses += 1;
+        }
+        result
+    }
+
+    /// Evicts the results of the generations other than `generation_id`.
+    pub fn retain_generation(&self, generation_id: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        let stale_keys: Vec<ResultCacheKey> = inner
+            .cache
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.generation_id != generation_id)
+            .cloned()
+            .collect();
+        for stale_key in &stale_keys {
+            inner.cache.pop(stale_key);
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.inner.lock().unwrap();
+        CacheStats {
+            num_entries: inner.cache.len(),
+            cache_hits: inner.cache_hits,
+            cache_misses: inner.cache_misses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::{AggregationCollector, AggregationLimitsGuard};
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy, Term};
+
+    #[test]
+    fn test_result_cache() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for tag_value in ["a", "b", "a"] {
+            index_writer.add_document(doc!(tag => tag_value))?;
+        }
+        index_writer.commit()?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .result_cache(NonZeroUsize::new(10).unwrap(), Duration::from_secs(3600))
+            .try_into()?;
+
+        let aggregations: Aggregations =
+            serde_json::from_str(r#"{"tags": {"terms": {"field": "tag"}}}"#).unwrap();
+        let aggregation_collector =
+            AggregationCollector::from_aggs(aggregations, AggregationLimitsGuard::default());
+        let searcher = reader.searcher();
+        let aggregation_results: AggregationResults =
+            searcher.search_cached(&AllQuery, &aggregation_collector, "tags")?;
+        let cached_aggregation_results: AggregationResults =
+            searcher.search_cached(&AllQuery, &aggregation_collector, "tags")?;
+        assert_eq!(aggregation_results, cached_aggregation_results);
+        let term_query = TermQuery::new(Term::from_field_text(tag, "a"), IndexRecordOption::Basic);
+        let top_docs = searcher.search_cached(&term_query, &TopDocs::with_limit(10), "top10")?;
+        assert_eq!(top_docs.len(), 2);
+        assert_eq!(
+            searcher.search_cached(&term_query, &TopDocs::with_limit(10), "top10")?,
+            top_docs
+        );
+        let stats = searcher.result_cache_stats();
+        assert_eq!(stats.num_entries, 2);
+        assert_eq!(stats.cache_hits, 2);
+        assert_eq!(stats.cache_misses, 2);
+
+        // Reloading invalidates the cached results.
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().result_cache_stats().num_entries, 0);
+        let searcher = reader.searcher();
+        let top_docs = searcher.search_cached(&term_query, &TopDocs::with_limit(10), "top10")?;
+        assert_eq!(top_docs.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_result_cache_time_to_live() -> crate::Result<()> {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let reader = index
+            .reader_builder()
+            .result_cache(NonZeroUsize::new(10).unwrap(), Duration::ZERO)
+            .try_into()?;
+        let searcher = reader.searcher();
+        for _ in 0..2 {
+            searcher.search_cached(&AllQuery, &TopDocs::with_limit(10), "top10")?;
+        }
+        assert_eq!(searcher.result_cache_stats().cache_hits, 0);
+        assert_eq!(searcher.result_cache_stats().cache_misses, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::index::{SegmentId, SegmentReader};
+use crate::query::{Query, Weight};
+
+/// Callback receiving the [`SlowQuery`]s of a [`SlowQueryLog`].
+///
+/// It is called from the thread running the search, before the search returns, and should
+/// return quickly.
+pub type SlowQueryCallback = Arc<dyn Fn(&SlowQuery<'_>) + Send + Sync>;
+
+/// The collection of a segment by a [`SlowQuery`].
+#[derive(Clone, Debug)]
+pub struct SlowQuerySegment {
+    /// Ordinal of the segment in the searcher.
+    pub segment_ord: u32,
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Time spent collecting the segment.
+    pub duration: Duration,
+    /// Number of alive documents of the segment matching the query, or `None` if they could
+    /// not be counted.
+    pub num_hits: Option<u32>,
+}
+
+/// A search which took longer than the threshold of the [`SlowQueryLog`].
+#[derive(Debug)]
+pub struct SlowQuery<'a> {
+    /// The query searched.
+    pub query: &'a dyn Query,
+    /// The type name of the collector.
+    pub collector_type: &'static str,
+    /// Total duration of the search, including the creation of the weight and the merge of
+    /// the segment fruits.
+    pub duration: Duration,
+    /// The collection of each segment.
+    pub segments: Vec<SlowQuerySegment>,
+}
+
+impl SlowQuery<'_> {
+    /// Returns the number of documents matching the query, over all of the segments whose
+    /// hits could be counted.
+    pub fn num_hits(&self) -> u64 {
+        self.segments
+            .iter()
+            .filter_map(|segment| segment.num_hits)
+            .map(u64::from)
+            .sum()
+    }
+}
+
+/// Reports the searches which take longer than a threshold to a callback.
+///
+/// It is set up with [`IndexReaderBuilder::slow_query_log`](crate::IndexReaderBuilder::slow_query_log)
+/// and applies to all of the searches run through the searchers of the reader.
+///
+/// The hits of a slow query are counted again once the search is over, so that the callback
+/// can be told how many documents matched no matter what the collector did with them. This
+/// only happens for the searches exceeding the threshold.
+#[derive(Clone)]
+pub struct SlowQueryLog {
+    threshold: Duration,
+    callback: SlowQueryCallback,
+}
+
+impl SlowQueryLog {
+    /// Creates a slow query log calling `callback` for each search taking `threshold` or
+    /// longer.
+    pub fn new(threshold: Duration, callback: SlowQueryCallback) -> SlowQueryLog {
+        SlowQueryLog {
+            threshold,
+            callback,
+        }
+    }
+
+    /// Returns the duration from which a search is reported.
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Calls the callback if the search took longer than the threshold.
+    pub(crate) fn record(
+        &self,
+        query: &dyn Query,
+        collector_type: &'static str,
+        weight: &dyn Weight,
+        segment_readers: &[SegmentReader],
+        segment_durations: &[Duration],
+        duration: Duration,
+    ) {
+        if duration < self.threshold {
+            return;
+        }
+        let segments = segment_readers
+            .iter()
+            .zip(segment_durations)
+            .enumerate()
+            .map(
+                |(segment_ord, (segment_reader, &duration))| SlowQuerySegment {
+                    segment_ord: segment_ord as u32,
+                    segment_id: segment_reader.segment_id(),
+                    duration,
+                    num_hits: weight.count(segment_reader).ok(),
+                },
+            )
+            .collect();
+        (self.callback)(&SlowQuery {
+            query,
+            collector_type,
+            duration,
+            segments,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::SlowQueryLog;
+    use crate::collector::{Count, TopDocs};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy, Term};
+
+    #[test]
+    fn test_slow_query_log() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for tag_values in [["a", "b", "a"], ["b", "b", "a"]] {
+            for tag_value in tag_values {
+                index_writer.add_document(doc!(tag => tag_value))?;
+            }
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_text(tag, "b"));
+        index_writer.add_document(doc!(tag => "c"))?;
+        index_writer.commit()?;
+
+        let slow_queries = Arc::new(Mutex::new(Vec::new()));
+        let slow_queries_clone = slow_queries.clone();
+        let slow_query_log = SlowQueryLog::new(
+            Duration::ZERO,
+            Arc::new(move |slow_query| {
+                let mut segment_hits: Vec<Option<u32>> = slow_query
+                    .segments
+                    .iter()
+                    .map(|segment| segment.num_hits)
+                    .collect();
+                segment_hits.sort();
+                slow_queries_clone.lock().unwrap().push((
+                    format!("{:?}", slow_query.query),
+                    slow_query.collector_type.to_string(),
+                    segment_hits,
+                    slow_query.num_hits(),
+                ));
+            }),
+        );
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .slow_query_log(slow_query_log)
+            .try_into()?;
+        let searcher = reader.searcher();
+        let term_query = TermQuery::new(Term::from_field_text(tag, "a"), IndexRecordOption::Basic);
+        assert_eq!(searcher.search(&term_query, &Count)?, 3);
+        searcher.search(&AllQuery, &TopDocs::with_limit(1))?;
+
+        let slow_queries = slow_queries.lock().unwrap();
+        assert_eq!(slow_queries.len(), 2);
+        let (query, collector_type, segment_hits, num_hits) = &slow_queries[0];
+        assert!(query.contains("TermQuery"));
+        assert!(collector_type.ends_with("Count"));
+        assert_eq!(segment_hits, &[Some(0), Some(1), Some(2)]);
+        assert_eq!(*num_hits, 3);
+        let (_, collector_type, segment_hits, num_hits) = &slow_queries[1];
+        assert!(collector_type.contains("TopDocs"));
+        assert_eq!(segment_hits, &[Some(1), Some(1), Some(2)]);
+        assert_eq!(*num_hits, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_slow_query_log_threshold() -> crate::Result<()> {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let num_calls = Arc::new(Mutex::new(0));
+        let num_calls_clone = num_calls.clone();
+        let reader = index
+            .reader_builder()
+            .slow_query_log(SlowQueryLog::new(
+                Duration::from_secs(3600),
+                Arc::new(move |_| *num_calls_clone.lock().unwrap() += 1),
+            ))
+            .try_into()?;
+        reader.searcher().search(&AllQuery, &Count)?;
+        assert_eq!(*num_calls.lock().unwrap(), 0);
+        Ok(())
+    }
+}
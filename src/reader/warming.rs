@@ -1,19 +1,37 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::ops::Deref;
 use std::sync::{Arc, Mutex, Weak};
 use std::thread::JoinHandle;
 use std::time::Duration;
 
-use crate::{Executor, Inventory, Searcher, SearcherGeneration, TantivyError};
+use crate::index::SegmentId;
+use crate::{
+    Executor, Inventory, Opstamp, Searcher, SearcherGeneration, SegmentReader, TantivyError,
+};
 
 pub const GC_INTERVAL: Duration = Duration::from_secs(1);
 
 /// `Warmer` can be used to maintain segment-level state e.g. caches.
 ///
 /// They must be registered with the [`IndexReaderBuilder`](super::IndexReaderBuilder).
+/// A new searcher is only published by the reader once it has been warmed by all of the
+/// warmers, so that the first queries on it do not pay for the warming work.
 pub trait Warmer: Sync + Send {
     /// Perform any warming work using the provided [`Searcher`].
-    fn warm(&self, searcher: &Searcher) -> crate::Result<()>;
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()> {
+        let _ = searcher;
+        Ok(())
+    }
+
+    /// Perform any warming work on a segment of a new [`Searcher`], after [`Warmer::warm`].
+    ///
+    /// It is only called for the segments which were not part of the previously warmed
+    /// searcher of the reader, or whose deletes changed since, so that the segments shared by
+    /// consecutive searchers are warmed once.
+    fn warm_segment(&self, segment_reader: &SegmentReader) -> crate::Result<()> {
+        let _ = segment_reader;
+        Ok(())
+    }
 
     /// Discards internal state for any [`SearcherGeneration`] not provided.
     fn garbage_collect(&self, live_generations: &[&SearcherGeneration]);
@@ -34,6 +52,7 @@ impl WarmingState {
             warmers,
             gc_thread: None,
             warmed_generation_ids: Default::default(),
+            warmed_segments: Default::default(),
             searcher_generation_inventory,
         }))))
     }
@@ -64,6 +83,8 @@ struct WarmingStateInner {
     // This list is used to avoid triggers the individual Warmer GCs
     // if no warmed generation needs to be collected.
     warmed_generation_ids: HashSet<u64>,
+    // Segments of the last warmed generation, with their delete opstamp.
+    warmed_segments: BTreeMap<SegmentId, Option<Opstamp>>,
     searcher_generation_inventory: Inventory<SearcherGeneration>,
 }
 
@@ -85,8 +106,25 @@ impl WarmingStateInner {
         self.start_gc_thread_maybe(this)?;
         self.warmed_generation_ids
             .insert(searcher.generation().generation_id());
-        warming_executor(self.num_warming_threads.min(warmers.len()))?
-            .map(|warmer| warmer.warm(searcher), warmers.into_iter())?;
+        let new_segment_readers: Vec<&SegmentReader> = searcher
+            .segment_readers()
+            .iter()
+            .filter(|segment_reader| {
+                self.warmed_segments.get(&segment_reader.segment_id())
+                    != Some(&segment_reader.delete_opstamp())
+            })
+            .collect();
+        warming_executor(self.num_warming_threads.min(warmers.len()))?.map(
+            |warmer| {
+                warmer.warm(searcher)?;
+                for segment_reader in &new_segment_readers {
+                    warmer.warm_segment(segment_reader)?;
+                }
+                Ok(())
+            },
+            warmers.into_iter(),
+        )?;
+        self.warmed_segments = searcher.generation().segments().clone();
         Ok(())
     }
 
@@ -181,14 +219,16 @@ mod tests {
     use crate::directory::RamDirectory;
     use crate::index::SegmentId;
     use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
+    use crate::indexer::NoMergePolicy;
     use crate::schema::{Schema, INDEXED};
-    use crate::{Index, IndexSettings, ReloadPolicy, Searcher};
+    use crate::{Index, IndexSettings, IndexWriter, ReloadPolicy, Searcher, SegmentReader, Term};
 
     #[derive(Default)]
     struct TestWarmer {
         active_segment_ids: RwLock<HashSet<SegmentId>>,
         warm_calls: AtomicUsize,
         gc_calls: AtomicUsize,
+        warmed_segment_ids: RwLock<Vec<SegmentId>>,
     }
 
     impl TestWarmer {
@@ -228,6 +268,14 @@ mod tests {
             Ok(())
         }
 
+        fn warm_segment(&self, segment_reader: &SegmentReader) -> crate::Result<()> {
+            self.warmed_segment_ids
+                .write()
+                .unwrap()
+                .push(segment_reader.segment_id());
+            Ok(())
+        }
+
         fn garbage_collect(&self, live_generations: &[&SearcherGeneration]) {
             self.gc_calls
                 .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -342,4 +390,50 @@ mod tests {
     fn warming_four_threads() -> crate::Result<()> {
         test_warming(4)
     }
+
+    #[test]
+    fn warming_new_segments() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("pk", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer: IndexWriter = index.writer_for_tests()?;
+        writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0u64..2u64 {
+            writer.add_document(doc!(field => i))?;
+            writer.add_document(doc!(field => i + 10))?;
+            writer.commit()?;
+        }
+
+        let warmer = Arc::new(TestWarmer::default());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .warmers(vec![Arc::downgrade(&warmer) as Weak<dyn Warmer>])
+            .try_into()?;
+        let warmed_segment_ids = || -> HashSet<SegmentId> {
+            warmer
+                .warmed_segment_ids
+                .read()
+                .unwrap()
+                .iter()
+                .copied()
+                .collect()
+        };
+        assert_eq!(warmed_segment_ids(), segment_ids(&reader.searcher()));
+
+        writer.add_document(doc!(field => 2u64))?;
+        writer.commit()?;
+        reader.reload()?;
+        // Only the new segment is warmed.
+        assert_eq!(warmer.warmed_segment_ids.read().unwrap().len(), 3);
+        assert_eq!(warmed_segment_ids(), segment_ids(&reader.searcher()));
+
+        // A segment whose deletes changed is warmed again.
+        writer.delete_term(Term::from_field_u64(field, 0));
+        writer.commit()?;
+        reader.reload()?;
+        assert_eq!(warmer.warmed_segment_ids.read().unwrap().len(), 4);
+        assert_eq!(warmer.warm_calls(), 3);
+        Ok(())
+    }
 }
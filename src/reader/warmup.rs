@@ -0,0 +1,175 @@
+use std::hint::black_box;
+use std::io;
+
+use crate::collector::TopDocs;
+use crate::directory::FileSlice;
+use crate::query::Query;
+use crate::schema::Field;
+use crate::Searcher;
+
+/// Pages are touched every `PAGE_SIZE` bytes to fault them in.
+const PAGE_SIZE: usize = 4_096;
+
+/// Components of the segments of a [`Searcher`] to load ahead of the first queries.
+///
+/// Searchers open some components lazily, and with memory mapped directories the data of
+/// the segments that were just published is usually not in the page cache yet: the first
+/// queries hitting a new searcher are slower. Warming up a searcher opens the selected
+/// components and reads all of their pages, or fetches them with directories that are not
+/// memory mapped.
+///
+/// A plan is applied to a searcher with [`Searcher::warm_up`], or to all the searchers of a
+/// reader, before they are used, with
+/// [`IndexReaderBuilder::warmup_plan`](crate::IndexReaderBuilder::warmup_plan).
+#[derive(Debug, Default)]
+pub struct WarmupPlan {
+    term_dictionary_fields: Vec<Field>,
+    fast_field_names: Vec<String>,
+    doc_store_queries: Vec<(Box<dyn Query>, usize)>,
+}
+
+impl Clone for WarmupPlan {
+    fn clone(&self) -> Self {
+        WarmupPlan {
+            term_dictionary_fields: self.term_dictionary_fields.clone(),
+            fast_field_names: self.fast_field_names.clone(),
+            doc_store_queries: self
+                .doc_store_queries
+                .iter()
+                .map(|(query, limit)| (query.box_clone(), *limit))
+                .collect(),
+        }
+    }
+}
+
+impl WarmupPlan {
+    /// Warms up the inverted index of a field, and loads its term dictionary.
+    #[must_use]
+    pub fn term_dictionary(mut self, field: Field) -> WarmupPlan {
+        self.term_dictionary_fields.push(field);
+        self
+    }
+
+    /// Opens the columns of a fast field, and loads them.
+    ///
+    /// The field name may refer to a path within a JSON field, see
+    /// [`FastFieldReaders::warm`](crate::fastfield::FastFieldReaders::warm).
+    #[must_use]
+    pub fn fast_field(mut self, field_name: impl ToString) -> WarmupPlan {
+        self.fast_field_names.push(field_name.to_string());
+        self
+    }
+
+    /// Loads the doc store blocks of the `limit` best documents for a query into the doc
+    /// store cache of the searcher.
+    ///
+    /// This is typically used with the queries serving the most frequent requests. The doc store
+    /// cache holds a limited number of blocks, see
+    /// [`IndexReaderBuilder::doc_store_cache_num_blocks`](crate::IndexReaderBuilder::doc_store_cache_num_blocks).
+    #[must_use]
+    pub fn doc_store_blocks(mut self, query: Box<dyn Query>, limit: usize) -> WarmupPlan {
+        self.doc_store_queries.push((query, limit));
+        self
+    }
+
+    pub(crate) fn warm_up(&self, searcher: &Searcher) -> crate::Result<()> {
+        for segment_reader in searcher.segment_readers() {
+            for &field in &self.term_dictionary_fields {
+                segment_reader.inverted_index(field)?;
+                if let Some(termdict_file) = segment_reader.termdict_file(field) {
+                    prefault(&termdict_file)?;
+                }
+            }
+            let fast_fields = segment_reader.fast_fields();
+            for field_name in &self.fast_field_names {
+                fast_fields.warm(&[field_name])?;
+                for column_handle in fast_fields.dynamic_column_handles(field_name)? {
+                    prefault(column_handle.file_slice())?;
+                }
+            }
+        }
+        for (query, limit) in &self.doc_store_queries {
+            if *limit == 0 {
+                continue;
+            }
+            let top_docs = searcher.search(query, &TopDocs::with_limit(*limit))?;
+            for (_score, doc_address) in top_docs {
+                searcher
+                    .store_reader(doc_address.segment_ord)
+                    .get_document_bytes(doc_address.doc_id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a byte of every page of a file slice, to fault them in if the file is memory
+/// mapped.
+fn prefault(file_slice: &FileSlice) -> io::Result<()> {
+    let bytes = file_slice.read_bytes()?;
+    let checksum = bytes
+        .as_slice()
+        .iter()
+        .step_by(PAGE_SIZE)
+        .fold(0u8, |checksum, byte| checksum ^ byte);
+    black_box(checksum);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, FAST, STORED, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+
+    #[test]
+    fn test_warmup_plan() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING | STORED);
+        let num_field = schema_builder.add_u64_field("num", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for num in 0..10u64 {
+            let tag = if num % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(tag_field => tag, num_field => num))?;
+        }
+        index_writer.commit()?;
+
+        let query = TermQuery::new(
+            Term::from_field_text(tag_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        let warmup_plan = WarmupPlan::default()
+            .term_dictionary(tag_field)
+            .fast_field("num")
+            .doc_store_blocks(Box::new(query), 10);
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .warmup_plan(warmup_plan)
+            .try_into()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.doc_store_cache_stats().num_entries, 1);
+        assert_eq!(
+            searcher.segment_readers()[0]
+                .fast_fields()
+                .num_opened_columns(),
+            1
+        );
+
+        index_writer.add_document(doc!(tag_field => "even", num_field => 10u64))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        assert_eq!(searcher.doc_store_cache_stats().num_entries, 2);
+        for segment_reader in searcher.segment_readers() {
+            assert_eq!(segment_reader.fast_fields().num_opened_columns(), 1);
+        }
+        let cache_misses = searcher.doc_store_cache_stats().cache_misses;
+        let _doc: TantivyDocument = searcher.doc(crate::DocAddress::new(1, 0))?;
+        assert_eq!(searcher.doc_store_cache_stats().cache_misses, cache_misses);
+        Ok(())
+    }
+}
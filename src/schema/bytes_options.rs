@@ -3,6 +3,7 @@ use std::ops::BitOr;
 use serde::{Deserialize, Serialize};
 
 use super::flags::{FastFlag, IndexedFlag, SchemaFlagList, StoredFlag};
+use super::VectorOptions;
 /// Define how a bytes field should be handled by tantivy.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(from = "BytesOptionsDeser")]
@@ -11,6 +12,8 @@ pub struct BytesOptions {
     fieldnorms: bool,
     fast: bool,
     stored: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vector: Option<VectorOptions>,
 }
 
 /// For backward compatibility we add an intermediary to interpret the
@@ -25,6 +28,8 @@ struct BytesOptionsDeser {
     fieldnorms: Option<bool>,
     fast: bool,
     stored: bool,
+    #[serde(default)]
+    vector: Option<VectorOptions>,
 }
 
 impl From<BytesOptionsDeser> for BytesOptions {
@@ -34,6 +39,7 @@ impl From<BytesOptionsDeser> for BytesOptions {
             fieldnorms: deser.fieldnorms.unwrap_or(deser.indexed),
             fast: deser.fast,
             stored: deser.stored,
+            vector: deser.vector,
         }
     }
 }
@@ -101,6 +107,32 @@ impl BytesOptions {
         self.stored = true;
         self
     }
+
+    /// Sets the field as stored, and removes it from the inverted index.
+    ///
+    /// The fast field, if any, is kept.
+    pub(crate) fn set_store_only(mut self) -> BytesOptions {
+        self.stored = true;
+        self.indexed = false;
+        self.fieldnorms = false;
+        self
+    }
+
+    /// Returns the vector options if the field holds dense vectors.
+    #[inline]
+    pub fn get_vector_options(&self) -> Option<VectorOptions> {
+        self.vector
+    }
+
+    /// Set the field as holding a dense vector per document.
+    ///
+    /// The vectors are stored in the vectors component of the segment, which
+    /// gives random access to them, see [`Searcher::knn`](crate::Searcher::knn).
+    #[must_use]
+    pub fn set_vector(mut self, vector_options: VectorOptions) -> BytesOptions {
+        self.vector = Some(vector_options);
+        self
+    }
 }
 
 impl<T: Into<BytesOptions>> BitOr<T> for BytesOptions {
@@ -113,6 +145,7 @@ impl<T: Into<BytesOptions>> BitOr<T> for BytesOptions {
             fieldnorms: self.fieldnorms | other.fieldnorms,
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
+            vector: self.vector.or(other.vector),
         }
     }
 }
@@ -130,6 +163,7 @@ impl From<FastFlag> for BytesOptions {
             fieldnorms: false,
             stored: false,
             fast: true,
+            vector: None,
         }
     }
 }
@@ -141,6 +175,7 @@ impl From<StoredFlag> for BytesOptions {
             fieldnorms: false,
             stored: true,
             fast: false,
+            vector: None,
         }
     }
 }
@@ -152,6 +187,7 @@ impl From<IndexedFlag> for BytesOptions {
             fieldnorms: true,
             stored: false,
             fast: false,
+            vector: None,
         }
     }
 }
@@ -228,7 +264,8 @@ mod tests {
                 indexed: true,
                 fieldnorms: true,
                 fast: false,
-                stored: false
+                stored: false,
+                vector: None,
             }
         );
     }
@@ -247,7 +284,8 @@ mod tests {
                 indexed: false,
                 fieldnorms: false,
                 fast: false,
-                stored: false
+                stored: false,
+                vector: None,
             }
         );
     }
@@ -267,7 +305,8 @@ mod tests {
                 indexed: true,
                 fieldnorms: false,
                 fast: false,
-                stored: false
+                stored: false,
+                vector: None,
             }
         );
     }
@@ -288,7 +327,8 @@ mod tests {
                 indexed: false,
                 fieldnorms: true,
                 fast: false,
-                stored: false
+                stored: false,
+                vector: None,
             }
         );
     }
@@ -21,6 +21,14 @@ pub struct DateOptions {
     // compression on fast fields.
     #[serde(default)]
     precision: DateTimePrecision,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fast_codec: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    points: bool,
+}
+
+fn is_false(val: &bool) -> bool {
+    !val
 }
 
 impl DateOptions {
@@ -58,6 +66,16 @@ impl DateOptions {
         self
     }
 
+    /// Sets the field as stored, and removes it from the inverted index.
+    ///
+    /// The fast field, if any, is kept.
+    pub(crate) fn set_store_only(mut self) -> DateOptions {
+        self.stored = true;
+        self.indexed = false;
+        self.fieldnorms = false;
+        self
+    }
+
     /// Set the field as indexed.
     ///
     /// Setting an integer as indexed will generate
@@ -106,6 +124,37 @@ impl DateOptions {
     pub fn get_precision(&self) -> DateTimePrecision {
         self.precision
     }
+
+    /// Sets the custom codec used to encode the fast field.
+    ///
+    /// The codec has to be registered with [`columnar::register_column_codec`]
+    /// before documents are indexed and before the fast field is read.
+    #[must_use]
+    pub fn set_fast_codec(mut self, codec_name: &str) -> DateOptions {
+        self.fast_codec = Some(codec_name.to_string());
+        self
+    }
+
+    /// Returns the name of the custom codec used to encode the fast field, if any.
+    pub fn get_fast_codec(&self) -> Option<&str> {
+        self.fast_codec.as_deref()
+    }
+
+    /// Returns true iff the field has a points index.
+    #[inline]
+    pub fn is_points(&self) -> bool {
+        self.points
+    }
+
+    /// Set the field as having a points index.
+    ///
+    /// Values are recorded in the points index with the precision
+    /// given by [`DateOptions::set_precision`].
+    #[must_use]
+    pub fn set_points(mut self) -> DateOptions {
+        self.points = true;
+        self
+    }
 }
 
 impl From<()> for DateOptions {
@@ -153,6 +202,8 @@ impl<T: Into<DateOptions>> BitOr<T> for DateOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             precision: self.precision,
+            fast_codec: self.fast_codec.or(other.fast_codec),
+            points: self.points | other.points,
         }
     }
 }
@@ -297,6 +297,7 @@ pub struct BinaryDocumentDeserializer<'de, R> {
     position: usize,
     doc_store_version: DocStoreVersion,
     reader: &'de mut R,
+    projection: Option<&'de [Field]>,
 }
 
 impl<'de, R> BinaryDocumentDeserializer<'de, R>
@@ -314,9 +315,50 @@ where R: Read
             position: 0,
             doc_store_version,
             reader,
+            projection: None,
         })
     }
 
+    /// Attempts to create a new document deserializer from a given reader,
+    /// which only deserializes the values of the given fields.
+    ///
+    /// The values of the other fields are skipped over.
+    pub(crate) fn from_reader_with_projection(
+        reader: &'de mut R,
+        doc_store_version: DocStoreVersion,
+        fields: &'de [Field],
+    ) -> Result<Self, DeserializeError> {
+        let mut deserializer = Self::from_reader(reader, doc_store_version)?;
+        deserializer.projection = Some(fields);
+        Ok(deserializer)
+    }
+
+    fn is_projected(&self, field: Field) -> bool {
+        self.projection
+            .map(|fields| fields.contains(&field))
+            .unwrap_or(true)
+    }
+
+    /// Skips over the value of a field which is not part of the projection.
+    fn skip_value(&mut self, value_len: Option<u64>) -> Result<(), DeserializeError> {
+        if let Some(value_len) = value_len {
+            let num_skipped_bytes =
+                io::copy(&mut (&mut *self.reader).take(value_len), &mut io::sink())?;
+            if num_skipped_bytes != value_len {
+                return Err(DeserializeError::from(io::Error::from(
+                    io::ErrorKind::UnexpectedEof,
+                )));
+            }
+        } else {
+            // Older doc stores do not record the length of values, so they have
+            // to be deserialized to be skipped.
+            let deserializer =
+                BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
+            OwnedValue::deserialize(deserializer)?;
+        }
+        Ok(())
+    }
+
     /// Returns true if the deserializer has deserialized all the entries
     /// within the document.
     fn is_complete(&self) -> bool {
@@ -333,21 +375,62 @@ where R: Read
     }
 
     fn next_field<V: ValueDeserialize>(&mut self) -> Result<Option<(Field, V)>, DeserializeError> {
-        if self.is_complete() {
-            return Ok(None);
-        }
+        loop {
+            if self.is_complete() {
+                return Ok(None);
+            }
 
-        let field = Field::deserialize(self.reader).map_err(DeserializeError::from)?;
-        let deserializer =
-            BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
-        let value = V::deserialize(deserializer)?;
+            let field = Field::deserialize(self.reader).map_err(DeserializeError::from)?;
+            let value_len = if self.doc_store_version >= DocStoreVersion::V3 {
+                Some(VInt::deserialize_u64(self.reader)?)
+            } else {
+                None
+            };
+            self.position += 1;
+
+            if !self.is_projected(field) {
+                self.skip_value(value_len)?;
+                continue;
+            }
 
-        self.position += 1;
+            let deserializer =
+                BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
+            let value = V::deserialize(deserializer)?;
 
-        Ok(Some((field, value)))
+            return Ok(Some((field, value)));
+        }
     }
 }
 
+/// Returns the number of bytes used by each field value of a serialized document, including
+/// the field and length header of the value.
+pub(crate) fn field_value_num_bytes(
+    mut doc_bytes: &[u8],
+    doc_store_version: DocStoreVersion,
+) -> Result<Vec<(Field, usize)>, DeserializeError> {
+    let num_field_values = VInt::deserialize_u64(&mut doc_bytes)?;
+    let mut field_value_num_bytes = Vec::with_capacity(num_field_values as usize);
+    for _ in 0..num_field_values {
+        let start_len = doc_bytes.len();
+        let field = Field::deserialize(&mut doc_bytes)?;
+        if doc_store_version >= DocStoreVersion::V3 {
+            let value_len = VInt::deserialize_u64(&mut doc_bytes)? as usize;
+            if value_len > doc_bytes.len() {
+                return Err(DeserializeError::from(io::Error::from(
+                    io::ErrorKind::UnexpectedEof,
+                )));
+            }
+            doc_bytes = &doc_bytes[value_len..];
+        } else {
+            let deserializer =
+                BinaryValueDeserializer::from_reader(&mut doc_bytes, doc_store_version)?;
+            OwnedValue::deserialize(deserializer)?;
+        }
+        field_value_num_bytes.push((field, start_len - doc_bytes.len()));
+    }
+    Ok(field_value_num_bytes)
+}
+
 /// A single value deserializer that deserializes a value serialized with `BinarySerializable`.
 /// TODO: Improve docs
 pub struct BinaryValueDeserializer<'de, R> {
@@ -460,7 +543,7 @@ where R: Read
                 let timestamp_micros = <i64 as BinarySerializable>::deserialize(self.reader)?;
                 Ok(DateTime::from_timestamp_micros(timestamp_micros))
             }
-            DocStoreVersion::V2 => {
+            DocStoreVersion::V2 | DocStoreVersion::V3 | DocStoreVersion::V4 => {
                 let timestamp_nanos = <i64 as BinarySerializable>::deserialize(self.reader)?;
                 Ok(DateTime::from_timestamp_nanos(timestamp_nanos))
             }
@@ -1155,4 +1238,68 @@ mod tests {
             OwnedValue::Object(expected_object.into_iter().collect())
         );
     }
+
+    #[test]
+    fn test_document_deserialize_with_projection() {
+        use crate::schema::document::BinaryDocumentSerializer;
+        use crate::schema::{Schema, STORED};
+        use crate::TantivyDocument;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let schema = schema_builder.build();
+        let doc = doc!(
+            title => "The Old Man and the Sea",
+            attributes => json!({"author": "Hemingway", "pages": 127}),
+            body => "He was an old man who fished alone",
+            count => 3u64,
+            count => 4u64,
+        );
+        let projection = [count, title];
+        let expected_doc = doc!(
+            title => "The Old Man and the Sea",
+            count => 3u64,
+            count => 4u64,
+        );
+
+        let mut doc_bytes = Vec::new();
+        BinaryDocumentSerializer::new(&mut doc_bytes, &schema)
+            .serialize_doc(&doc)
+            .unwrap();
+        let mut reader = &doc_bytes[..];
+        let deserializer = BinaryDocumentDeserializer::from_reader_with_projection(
+            &mut reader,
+            DOC_STORE_VERSION,
+            &projection,
+        )
+        .unwrap();
+        let projected_doc = TantivyDocument::deserialize(deserializer).unwrap();
+        assert_eq!(projected_doc, expected_doc);
+        assert!(reader.is_empty());
+
+        // Values are not prefixed with their length before V3.
+        let mut v2_doc_bytes = Vec::new();
+        VInt(doc.field_values().count() as u64)
+            .serialize(&mut v2_doc_bytes)
+            .unwrap();
+        for (field, value) in doc.field_values() {
+            field.serialize(&mut v2_doc_bytes).unwrap();
+            BinaryValueSerializer::new(&mut v2_doc_bytes)
+                .serialize_value(value.as_value())
+                .unwrap();
+        }
+        let mut reader = &v2_doc_bytes[..];
+        let deserializer = BinaryDocumentDeserializer::from_reader_with_projection(
+            &mut reader,
+            DocStoreVersion::V2,
+            &projection,
+        )
+        .unwrap();
+        let projected_doc = TantivyDocument::deserialize(deserializer).unwrap();
+        assert_eq!(projected_doc, expected_doc);
+        assert!(reader.is_empty());
+    }
 }
@@ -7,12 +7,14 @@ use common::{f64_to_u64, BinarySerializable, VInt};
 
 use super::{OwnedValue, ReferenceValueLeaf};
 use crate::schema::document::{type_codes, Document, ReferenceValue, Value};
-use crate::schema::Schema;
+use crate::schema::{Field, Schema};
 
 /// A serializer writing documents which implement [`Document`] to a provided writer.
 pub struct BinaryDocumentSerializer<'se, W> {
     writer: &'se mut W,
     schema: &'se Schema,
+    // Only the stored fields accepted by the filter are serialized, if there is one.
+    field_filter: Option<&'se dyn Fn(Field) -> bool>,
 }
 
 impl<'se, W> BinaryDocumentSerializer<'se, W>
@@ -20,7 +22,25 @@ where W: Write
 {
     /// Creates a new serializer with a provided writer.
     pub(crate) fn new(writer: &'se mut W, schema: &'se Schema) -> Self {
-        Self { writer, schema }
+        Self {
+            writer,
+            schema,
+            field_filter: None,
+        }
+    }
+
+    /// Creates a new serializer, which only serializes the stored fields accepted by
+    /// `field_filter`.
+    pub(crate) fn with_field_filter(
+        writer: &'se mut W,
+        schema: &'se Schema,
+        field_filter: &'se dyn Fn(Field) -> bool,
+    ) -> Self {
+        Self {
+            writer,
+            schema,
+            field_filter: Some(field_filter),
+        }
     }
 
     /// Attempts to serialize a given document and write the output
@@ -29,17 +49,23 @@ where W: Write
     pub(crate) fn serialize_doc<D>(&mut self, doc: &D) -> io::Result<()>
     where D: Document {
         let stored_field_values = || {
-            doc.iter_fields_and_values()
-                .filter(|(field, _)| self.schema.get_field_entry(*field).is_stored())
+            doc.iter_fields_and_values().filter(|(field, _)| {
+                self.schema.get_field_entry(*field).is_stored()
+                    && self.field_filter.map_or(true, |field_filter| field_filter(*field))
+            })
         };
         let num_field_values = stored_field_values().count();
         let mut actual_length = 0;
 
         VInt(num_field_values as u64).serialize(self.writer)?;
+        // Each value is prefixed with its length, so that the fields which are not
+        // needed can be skipped when reading a subset of the fields of a document.
+        let mut value_bytes = Vec::new();
         for (field, value_access) in stored_field_values() {
             field.serialize(self.writer)?;
 
-            let mut serializer = BinaryValueSerializer::new(self.writer);
+            value_bytes.clear();
+            let mut serializer = BinaryValueSerializer::new(&mut value_bytes);
             match value_access.as_value() {
                 ReferenceValue::Leaf(ReferenceValueLeaf::PreTokStr(pre_tokenized_text)) => {
                     serializer.serialize_value(ReferenceValue::Leaf::<&'_ OwnedValue>(
@@ -50,6 +76,8 @@ where W: Write
                     serializer.serialize_value(value_access.as_value())?;
                 }
             }
+            VInt(value_bytes.len() as u64).serialize(self.writer)?;
+            self.writer.write_all(&value_bytes)?;
 
             actual_length += 1;
         }
@@ -689,7 +717,10 @@ mod tests {
             VInt($len as u64).serialize(&mut writer).unwrap();
             $(
                 $field_id.serialize(&mut writer).unwrap();
-                $value.serialize(&mut writer).unwrap();
+                let mut value_bytes = Vec::new();
+                $value.serialize(&mut value_bytes).unwrap();
+                VInt(value_bytes.len() as u64).serialize(&mut writer).unwrap();
+                writer.extend_from_slice(&value_bytes);
             )*
 
             writer
@@ -710,10 +741,13 @@ mod tests {
         let result = serialize_doc(&document, &schema);
         let mut expected = expected_doc_data!(length document.len());
         name.serialize(&mut expected).unwrap();
-        expected
-            .extend_from_slice(&binary_repr!(type_codes::TEXT_CODE => String::from("ChillFish8")));
+        let value = binary_repr!(type_codes::TEXT_CODE => String::from("ChillFish8"));
+        VInt(value.len() as u64).serialize(&mut expected).unwrap();
+        expected.extend_from_slice(&value);
         age.serialize(&mut expected).unwrap();
-        expected.extend_from_slice(&binary_repr!(type_codes::U64_CODE => 20u64));
+        let value = binary_repr!(type_codes::U64_CODE => 20u64);
+        VInt(value.len() as u64).serialize(&mut expected).unwrap();
+        expected.extend_from_slice(&value);
         assert_eq!(
             result, expected,
             "Expected serialized document to match the binary representation"
@@ -732,8 +766,9 @@ mod tests {
         let result = serialize_doc(&document, &schema);
         let mut expected = expected_doc_data!(length 1);
         name.serialize(&mut expected).unwrap();
-        expected
-            .extend_from_slice(&binary_repr!(type_codes::TEXT_CODE => String::from("ChillFish8")));
+        let value = binary_repr!(type_codes::TEXT_CODE => String::from("ChillFish8"));
+        VInt(value.len() as u64).serialize(&mut expected).unwrap();
+        expected.extend_from_slice(&value);
         assert_eq!(
             result, expected,
             "Expected serialized document to match the binary representation"
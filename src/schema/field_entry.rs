@@ -18,6 +18,12 @@ pub struct FieldEntry {
     name: String,
     #[serde(flatten)]
     field_type: FieldType,
+    #[serde(default, skip_serializing_if = "is_false")]
+    unique_key: bool,
+}
+
+fn is_false(val: &bool) -> bool {
+    !val
 }
 
 impl FieldEntry {
@@ -27,6 +33,7 @@ impl FieldEntry {
         FieldEntry {
             name: field_name,
             field_type,
+            unique_key: false,
         }
     }
 
@@ -90,6 +97,21 @@ impl FieldEntry {
         &self.field_type
     }
 
+    /// Returns true if the field is the unique key of the schema.
+    ///
+    /// See [`SchemaBuilder::set_unique_key`](crate::schema::SchemaBuilder::set_unique_key).
+    pub fn is_unique_key(&self) -> bool {
+        self.unique_key
+    }
+
+    pub(crate) fn set_unique_key(&mut self, unique_key: bool) {
+        self.unique_key = unique_key;
+    }
+
+    pub(crate) fn set_store_only(&mut self) {
+        self.field_type = self.field_type.clone().into_store_only();
+    }
+
     /// Returns true if the field is indexed.
     ///
     /// An indexed field is searchable.
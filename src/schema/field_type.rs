@@ -244,6 +244,29 @@ impl FieldType {
         }
     }
 
+    /// Returns the store-only version of this field type: the field is stored, keeps its fast
+    /// field if any, but is not indexed in the inverted index anymore.
+    ///
+    /// Facets are always indexed, and are only set as stored.
+    pub(crate) fn into_store_only(self) -> FieldType {
+        match self {
+            FieldType::Str(text_options) => FieldType::Str(text_options.set_store_only()),
+            FieldType::U64(int_options) => FieldType::U64(int_options.set_store_only()),
+            FieldType::I64(int_options) => FieldType::I64(int_options.set_store_only()),
+            FieldType::F64(int_options) => FieldType::F64(int_options.set_store_only()),
+            FieldType::Bool(int_options) => FieldType::Bool(int_options.set_store_only()),
+            FieldType::Date(date_options) => FieldType::Date(date_options.set_store_only()),
+            FieldType::Facet(facet_options) => FieldType::Facet(facet_options.set_stored()),
+            FieldType::Bytes(bytes_options) => FieldType::Bytes(bytes_options.set_store_only()),
+            FieldType::JsonObject(json_object_options) => {
+                FieldType::JsonObject(json_object_options.set_store_only())
+            }
+            FieldType::IpAddr(ip_addr_options) => {
+                FieldType::IpAddr(ip_addr_options.set_store_only())
+            }
+        }
+    }
+
     /// Returns the index record option for the field.
     ///
     /// If the field is not indexed, returns `None`.
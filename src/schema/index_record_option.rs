@@ -26,6 +26,12 @@ pub enum IndexRecordOption {
     /// Positions are required to run a [`PhraseQuery`](crate::query::PhraseQuery).
     #[serde(rename = "position")]
     WithFreqsAndPositions,
+    /// records the document id, the term frequency, the positions of
+    /// the occurrences in the document, and the byte offsets of their tokens in the text.
+    /// Offsets make it possible to highlight a document without tokenizing its text again.
+    /// (See [`Highlighter::highlight_hit()`](crate::snippet::Highlighter::highlight_hit))
+    #[serde(rename = "offset")]
+    WithFreqsAndPositionsAndOffsets,
 }
 
 impl IndexRecordOption {
@@ -34,7 +40,9 @@ impl IndexRecordOption {
     pub fn has_freq(self) -> bool {
         match self {
             IndexRecordOption::Basic => false,
-            IndexRecordOption::WithFreqs | IndexRecordOption::WithFreqsAndPositions => true,
+            IndexRecordOption::WithFreqs
+            | IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
         }
     }
 
@@ -43,7 +51,19 @@ impl IndexRecordOption {
     pub fn has_positions(self) -> bool {
         match self {
             IndexRecordOption::Basic | IndexRecordOption::WithFreqs => false,
-            IndexRecordOption::WithFreqsAndPositions => true,
+            IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
+        }
+    }
+
+    /// Returns true if this option include encoding
+    /// the offsets of the tokens.
+    pub fn has_offsets(self) -> bool {
+        match self {
+            IndexRecordOption::Basic
+            | IndexRecordOption::WithFreqs
+            | IndexRecordOption::WithFreqsAndPositions => false,
+            IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
         }
     }
 
@@ -52,10 +72,17 @@ impl IndexRecordOption {
         use IndexRecordOption::*;
 
         match (other, self) {
-            (WithFreqsAndPositions, WithFreqsAndPositions) => WithFreqsAndPositions,
-            (WithFreqs, WithFreqs) => WithFreqs,
-            (WithFreqsAndPositions, WithFreqs) => WithFreqs,
-            (WithFreqs, WithFreqsAndPositions) => WithFreqs,
+            (WithFreqsAndPositionsAndOffsets, WithFreqsAndPositionsAndOffsets) => {
+                WithFreqsAndPositionsAndOffsets
+            }
+            (
+                WithFreqsAndPositions | WithFreqsAndPositionsAndOffsets,
+                WithFreqsAndPositions | WithFreqsAndPositionsAndOffsets,
+            ) => WithFreqsAndPositions,
+            (
+                WithFreqs | WithFreqsAndPositions | WithFreqsAndPositionsAndOffsets,
+                WithFreqs | WithFreqsAndPositions | WithFreqsAndPositionsAndOffsets,
+            ) => WithFreqs,
             _ => Basic,
         }
     }
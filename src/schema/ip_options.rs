@@ -71,6 +71,16 @@ impl IpAddrOptions {
         self
     }
 
+    /// Sets the field as stored, and removes it from the inverted index.
+    ///
+    /// The fast field, if any, is kept.
+    pub(crate) fn set_store_only(mut self) -> Self {
+        self.stored = true;
+        self.indexed = false;
+        self.fieldnorms = false;
+        self
+    }
+
     /// Set the field as indexed.
     ///
     /// Setting an ip address as indexed will generate
@@ -120,6 +120,15 @@ impl JsonObjectOptions {
         self
     }
 
+    /// Sets the field as stored, and removes it from the inverted index.
+    ///
+    /// The fast field, if any, is kept.
+    pub(crate) fn set_store_only(mut self) -> Self {
+        self.stored = true;
+        self.indexing = None;
+        self
+    }
+
     /// Set the field as a fast field.
     ///
     /// Fast fields are designed for random access.
@@ -16,6 +16,10 @@ pub struct NumericOptions {
     stored: bool,
     #[serde(skip_serializing_if = "is_false")]
     coerce: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fast_codec: Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    points: bool,
 }
 
 fn is_false(val: &bool) -> bool {
@@ -37,6 +41,10 @@ struct NumericOptionsDeser {
     stored: bool,
     #[serde(default)]
     coerce: bool,
+    #[serde(default)]
+    fast_codec: Option<String>,
+    #[serde(default)]
+    points: bool,
 }
 
 impl From<NumericOptionsDeser> for NumericOptions {
@@ -47,6 +55,8 @@ impl From<NumericOptionsDeser> for NumericOptions {
             fast: deser.fast,
             stored: deser.stored,
             coerce: deser.coerce,
+            fast_codec: deser.fast_codec,
+            points: deser.points,
         }
     }
 }
@@ -89,6 +99,38 @@ impl NumericOptions {
         self
     }
 
+    /// Returns the name of the custom codec used to encode the fast field, if any.
+    pub fn get_fast_codec(&self) -> Option<&str> {
+        self.fast_codec.as_deref()
+    }
+
+    /// Sets the custom codec used to encode the fast field.
+    ///
+    /// The codec has to be registered with [`columnar::register_column_codec`]
+    /// before documents are indexed and before the fast field is read.
+    #[must_use]
+    pub fn set_fast_codec(mut self, codec_name: &str) -> NumericOptions {
+        self.fast_codec = Some(codec_name.to_string());
+        self
+    }
+
+    /// Returns true iff the field has a points index.
+    #[inline]
+    pub fn is_points(&self) -> bool {
+        self.points
+    }
+
+    /// Set the field as having a points index.
+    ///
+    /// The points index is a tree of sorted value blocks, used by
+    /// [`PointRangeQuery`](crate::query::PointRangeQuery) to run
+    /// selective range queries without scanning the fast field.
+    #[must_use]
+    pub fn set_points(mut self) -> NumericOptions {
+        self.points = true;
+        self
+    }
+
     /// Set the field as stored.
     ///
     /// Only the fields that are set as *stored* are
@@ -99,6 +141,16 @@ impl NumericOptions {
         self
     }
 
+    /// Sets the field as stored, and removes it from the inverted index.
+    ///
+    /// The fast field, if any, is kept.
+    pub(crate) fn set_store_only(mut self) -> NumericOptions {
+        self.stored = true;
+        self.indexed = false;
+        self.fieldnorms = false;
+        self
+    }
+
     /// Set the field as indexed.
     ///
     /// Setting an integer as indexed will generate
@@ -145,6 +197,8 @@ impl From<CoerceFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: true,
+            fast_codec: None,
+            points: false,
         }
     }
 }
@@ -157,6 +211,8 @@ impl From<FastFlag> for NumericOptions {
             stored: false,
             fast: true,
             coerce: false,
+            fast_codec: None,
+            points: false,
         }
     }
 }
@@ -169,6 +225,8 @@ impl From<StoredFlag> for NumericOptions {
             stored: true,
             fast: false,
             coerce: false,
+            fast_codec: None,
+            points: false,
         }
     }
 }
@@ -181,6 +239,8 @@ impl From<IndexedFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: false,
+            fast_codec: None,
+            points: false,
         }
     }
 }
@@ -196,6 +256,8 @@ impl<T: Into<NumericOptions>> BitOr<T> for NumericOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             coerce: self.coerce | other.coerce,
+            fast_codec: self.fast_codec.or(other.fast_codec),
+            points: self.points | other.points,
         }
     }
 }
@@ -230,6 +292,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_codec: None,
+                points: false,
             }
         );
     }
@@ -249,6 +313,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_codec: None,
+                points: false,
             }
         );
     }
@@ -269,6 +335,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_codec: None,
+                points: false,
             }
         );
     }
@@ -290,6 +358,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_codec: None,
+                points: false,
             }
         );
     }
@@ -312,6 +382,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: true,
+                fast_codec: None,
+                points: false,
             }
         );
     }
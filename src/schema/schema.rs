@@ -205,6 +205,83 @@ impl SchemaBuilder {
         field
     }
 
+    /// Declares `field` as the unique key of the schema, replacing any previous unique key.
+    ///
+    /// Documents are then identified by the value of this field, and
+    /// [`IndexWriter::upsert`](crate::IndexWriter::upsert) replaces any previous document
+    /// with the same key.
+    ///
+    /// Only `upsert` enforces the uniqueness of the key:
+    /// [`IndexWriter::add_document`](crate::IndexWriter::add_document) accepts a document
+    /// whose key is already in the index, and merges keep all of the documents with the same
+    /// key.
+    ///
+    /// A text field must use exactly the `raw` tokenizer, like
+    /// [`STRING`](crate::schema::STRING). Other tokenizers, including the ones normalizing
+    /// the output of `raw`, e.g. to lowercase it, are rejected: `upsert` deletes the previous
+    /// document by the value of the key as is, which would not match its indexed term.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not exist, is not indexed, or is not a `u64`, `i64` or bytes
+    /// field or a text field using the `raw` tokenizer.
+    pub fn set_unique_key(&mut self, field: Field) {
+        let field_entry = &self.fields[field.field_id() as usize];
+        let is_valid_key = field_entry.is_indexed()
+            && match field_entry.field_type() {
+                FieldType::U64(_) | FieldType::I64(_) | FieldType::Bytes(_) => true,
+                FieldType::Str(text_options) => text_options
+                    .get_indexing_options()
+                    .map(|indexing_options| indexing_options.tokenizer() == "raw")
+                    .unwrap_or(false),
+                _ => false,
+            };
+        assert!(
+            is_valid_key,
+            "Field {} cannot be a unique key: it must be an indexed u64, i64, bytes field, or a \
+             text field using the raw tokenizer",
+            field_entry.name()
+        );
+        for field_entry in &mut self.fields {
+            field_entry.set_unique_key(false);
+        }
+        self.fields[field.field_id() as usize].set_unique_key(true);
+    }
+
+    /// Makes `field` store-only: the field is stored, keeps its fast field if any, but is not
+    /// indexed in the inverted index.
+    ///
+    /// This drastically shrinks the index for archive tiers, where full text is only displayed
+    /// and documents are filtered on metadata columns. A [`TermQuery`](crate::query::TermQuery)
+    /// or a [`RangeQuery`](crate::query::RangeQuery) on a store-only fast field is executed
+    /// on its fast field.
+    ///
+    /// Facet fields are always indexed, and are only set as stored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field is the unique key of the schema, which has to be indexed.
+    pub fn set_store_only(&mut self, field: Field) {
+        let field_entry = &mut self.fields[field.field_id() as usize];
+        assert!(
+            !field_entry.is_unique_key(),
+            "Field {} cannot be store-only: it is the unique key of the schema",
+            field_entry.name()
+        );
+        field_entry.set_store_only();
+    }
+
+    /// Makes all the fields added so far store-only, except the unique key.
+    ///
+    /// See [`SchemaBuilder::set_store_only`].
+    pub fn set_all_store_only(&mut self) {
+        for field_entry in &mut self.fields {
+            if !field_entry.is_unique_key() {
+                field_entry.set_store_only();
+            }
+        }
+    }
+
     /// Finalize the creation of a `Schema`
     /// This will consume your `SchemaBuilder`
     pub fn build(self) -> Schema {
@@ -301,6 +378,13 @@ impl Schema {
             .map(|(field_id, field_entry)| (Field::from_field_id(field_id as u32), field_entry))
     }
 
+    /// Returns the unique key of the schema, if any.
+    pub fn unique_key_field(&self) -> Option<Field> {
+        self.fields()
+            .find(|(_, field_entry)| field_entry.is_unique_key())
+            .map(|(field, _)| field)
+    }
+
     /// Creates a new builder.
     pub fn builder() -> SchemaBuilder {
         SchemaBuilder::default()
@@ -439,6 +523,64 @@ mod tests {
         assert!(schema.get_field_entry(field_str).is_indexed());
     }
 
+    #[test]
+    fn test_unique_key() {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let other_id_field = schema_builder.add_u64_field("other_id", INDEXED);
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.set_unique_key(other_id_field);
+        schema_builder.set_unique_key(id_field);
+        let schema = schema_builder.build();
+        assert_eq!(schema.unique_key_field(), Some(id_field));
+        assert!(!schema.get_field_entry(other_id_field).is_unique_key());
+
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        assert_eq!(schema_json.matches("\"unique_key\":true").count(), 1);
+        let deserialized_schema: Schema = serde_json::from_str(&schema_json).unwrap();
+        assert_eq!(deserialized_schema.unique_key_field(), Some(id_field));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unique_key_must_be_untokenized() {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        schema_builder.set_unique_key(title_field);
+    }
+
+    #[test]
+    fn test_store_only() {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let title_field = schema_builder.add_text_field("title", TEXT | FAST);
+        let date_field = schema_builder.add_date_field("date", INDEXED | FAST);
+        let facet_field = schema_builder.add_facet_field("facet", FacetOptions::default());
+        schema_builder.set_unique_key(id_field);
+        schema_builder.set_all_store_only();
+        let schema = schema_builder.build();
+        assert!(schema.get_field_entry(id_field).is_indexed());
+        assert!(!schema.get_field_entry(id_field).is_stored());
+        for field in [title_field, date_field] {
+            let field_entry = schema.get_field_entry(field);
+            assert!(!field_entry.is_indexed());
+            assert!(field_entry.is_fast());
+            assert!(field_entry.is_stored());
+            assert!(!field_entry.has_fieldnorms());
+        }
+        assert!(schema.get_field_entry(facet_field).is_indexed());
+        assert!(schema.get_field_entry(facet_field).is_stored());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unique_key_cannot_be_store_only() {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        schema_builder.set_unique_key(id_field);
+        schema_builder.set_store_only(id_field);
+    }
+
     #[test]
     pub fn test_schema_serialization() {
         let mut schema_builder = Schema::builder();
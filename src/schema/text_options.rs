@@ -152,6 +152,15 @@ impl TextOptions {
         self
     }
 
+    /// Sets the field as stored, and removes it from the inverted index.
+    ///
+    /// The fast field, if any, is kept.
+    pub(crate) fn set_store_only(mut self) -> TextOptions {
+        self.stored = true;
+        self.indexing = None;
+        self
+    }
+
     /// Sets the field as indexed, with the specific indexing options.
     #[must_use]
     pub fn set_indexing_options(mut self, indexing: TextFieldIndexing) -> TextOptions {
@@ -194,6 +203,10 @@ impl TokenizerName {
 /// - The name of the `Tokenizer` that should be used to process the field.
 /// - Flag indicating, if fieldnorms should be stored (See [fieldnorm](crate::fieldnorm)). Defaults
 ///   to `true`.
+/// - The name of the [`Similarity`](crate::query::Similarity) the field is scored with. Defaults
+///   to `bm25`.
+/// - Flag indicating, if term vectors should be stored (See [term vectors](crate::term_vectors)).
+///   Defaults to `false`.
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     #[serde(default)]
@@ -202,6 +215,29 @@ pub struct TextFieldIndexing {
     fieldnorms: bool,
     #[serde(default)]
     tokenizer: TokenizerName,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SimilarityName::is_default")]
+    similarity: SimilarityName,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    term_vectors: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
+pub(crate) struct SimilarityName(Cow<'static, str>);
+
+const DEFAULT_SIMILARITY_NAME: &str = "bm25";
+
+impl Default for SimilarityName {
+    fn default() -> Self {
+        SimilarityName(Cow::Borrowed(DEFAULT_SIMILARITY_NAME))
+    }
+}
+
+impl SimilarityName {
+    fn is_default(&self) -> bool {
+        self.0 == DEFAULT_SIMILARITY_NAME
+    }
 }
 
 pub(crate) fn default_fieldnorms() -> bool {
@@ -214,6 +250,8 @@ impl Default for TextFieldIndexing {
             tokenizer: TokenizerName::default(),
             record: IndexRecordOption::default(),
             fieldnorms: default_fieldnorms(),
+            similarity: SimilarityName::default(),
+            term_vectors: false,
         }
     }
 }
@@ -258,6 +296,36 @@ impl TextFieldIndexing {
     pub fn index_option(&self) -> IndexRecordOption {
         self.record
     }
+
+    /// Sets the similarity the field is scored with.
+    ///
+    /// The similarity must be registered in the
+    /// [`SimilarityManager`](crate::query::SimilarityManager) of the index.
+    #[must_use]
+    pub fn set_similarity(mut self, similarity_name: &str) -> TextFieldIndexing {
+        self.similarity = SimilarityName(Cow::Owned(similarity_name.to_string()));
+        self
+    }
+
+    /// Returns the name of the similarity the field is scored with.
+    pub fn similarity(&self) -> &str {
+        &self.similarity.0
+    }
+
+    /// Sets whether the term vectors of the field are stored.
+    ///
+    /// The term vector of a document lists the terms of the field in the document, with the
+    /// positions and byte offsets of their occurrences. See [term vectors](crate::term_vectors).
+    #[must_use]
+    pub fn set_term_vectors(mut self, term_vectors: bool) -> TextFieldIndexing {
+        self.term_vectors = term_vectors;
+        self
+    }
+
+    /// Returns true if and only if the term vectors of the field are stored.
+    pub fn term_vectors(&self) -> bool {
+        self.term_vectors
+    }
 }
 
 /// The field will be untokenized and indexed.
@@ -266,6 +334,8 @@ pub const STRING: TextOptions = TextOptions {
         tokenizer: TokenizerName::from_static(NO_TOKENIZER_NAME),
         fieldnorms: true,
         record: IndexRecordOption::Basic,
+        similarity: SimilarityName(Cow::Borrowed(DEFAULT_SIMILARITY_NAME)),
+        term_vectors: false,
     }),
     stored: false,
     fast: FastFieldTextOptions::IsEnabled(false),
@@ -278,6 +348,8 @@ pub const TEXT: TextOptions = TextOptions {
         tokenizer: TokenizerName::from_static(DEFAULT_TOKENIZER_NAME),
         fieldnorms: true,
         record: IndexRecordOption::WithFreqsAndPositions,
+        similarity: SimilarityName(Cow::Borrowed(DEFAULT_SIMILARITY_NAME)),
+        term_vectors: false,
     }),
     stored: false,
     coerce: false,
@@ -370,10 +442,34 @@ mod tests {
 
     #[test]
     fn test_cmp_index_record_option() {
+        assert!(
+            IndexRecordOption::WithFreqsAndPositionsAndOffsets
+                > IndexRecordOption::WithFreqsAndPositions
+        );
         assert!(IndexRecordOption::WithFreqsAndPositions > IndexRecordOption::WithFreqs);
         assert!(IndexRecordOption::WithFreqs > IndexRecordOption::Basic);
     }
 
+    #[test]
+    fn test_downgrade_index_record_option() {
+        use IndexRecordOption::*;
+        let options = [
+            Basic,
+            WithFreqs,
+            WithFreqsAndPositions,
+            WithFreqsAndPositionsAndOffsets,
+        ];
+        for left in options {
+            for right in options {
+                assert_eq!(left.downgrade(right), left.min(right));
+            }
+        }
+        assert_eq!(
+            serde_json::to_string(&WithFreqsAndPositionsAndOffsets).unwrap(),
+            r#""offset""#
+        );
+    }
+
     #[test]
     fn serde_default_test() {
         let json = r#"
@@ -394,6 +490,35 @@ mod tests {
         assert_eq!(options3.indexing, None);
     }
 
+    #[test]
+    fn test_similarity_serde() {
+        let indexing = TextFieldIndexing::default();
+        assert_eq!(indexing.similarity(), "bm25");
+        // The default similarity is not serialized.
+        assert!(!serde_json::to_string(&indexing)
+            .unwrap()
+            .contains("similarity"));
+        let indexing = indexing.set_similarity("tfidf");
+        let json = serde_json::to_string(&indexing).unwrap();
+        assert!(json.contains(r#""similarity":"tfidf""#));
+        let indexing: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert_eq!(indexing.similarity(), "tfidf");
+    }
+
+    #[test]
+    fn test_term_vectors_serde() {
+        let indexing = TextFieldIndexing::default();
+        assert!(!indexing.term_vectors());
+        assert!(!serde_json::to_string(&indexing)
+            .unwrap()
+            .contains("term_vectors"));
+        let indexing = indexing.set_term_vectors(true);
+        let json = serde_json::to_string(&indexing).unwrap();
+        assert!(json.contains(r#""term_vectors":true"#));
+        let indexing: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert!(indexing.term_vectors());
+    }
+
     #[test]
     fn serde_fast_field_tokenizer() {
         let json = r#" {
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+/// Similarity used to compare dense vectors.
+///
+/// Whatever the similarity, a higher score means the vectors are more similar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorSimilarity {
+    /// Dot product of the vectors.
+    ///
+    /// This is equivalent to the cosine similarity for normalized vectors,
+    /// but cheaper to compute.
+    DotProduct,
+    /// Cosine of the angle between the vectors.
+    Cosine,
+    /// `1 / (1 + d²)`, where `d` is the euclidean distance between the vectors.
+    Euclidean,
+}
+
+/// Parameters of the HNSW graph used for approximate nearest neighbor search.
+///
+/// See [`KnnQuery`](crate::query::KnnQuery).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HnswOptions {
+    /// Maximum number of neighbors of a node in the upper layers of the graph.
+    /// Nodes of the base layer have up to twice as many neighbors.
+    pub max_connections: u32,
+    /// Number of candidates considered when inserting a node in the graph.
+    pub ef_construction: u32,
+}
+
+impl Default for HnswOptions {
+    fn default() -> HnswOptions {
+        HnswOptions {
+            max_connections: 16,
+            ef_construction: 100,
+        }
+    }
+}
+
+/// Defines a bytes field as holding a dense vector of `f32` per document.
+///
+/// The vector is given as the little-endian representation of its components, see
+/// [`vector_to_bytes`](crate::vector::vector_to_bytes).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorOptions {
+    dims: u32,
+    similarity: VectorSimilarity,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hnsw: Option<HnswOptions>,
+}
+
+impl VectorOptions {
+    /// Creates the options of a vector field with `dims` dimensions.
+    pub fn new(dims: u32, similarity: VectorSimilarity) -> VectorOptions {
+        VectorOptions {
+            dims,
+            similarity,
+            hnsw: None,
+        }
+    }
+
+    /// Builds an HNSW graph over the vectors of each segment, enabling approximate
+    /// nearest neighbor search with [`KnnQuery`](crate::query::KnnQuery).
+    #[must_use]
+    pub fn set_hnsw(mut self, hnsw_options: HnswOptions) -> VectorOptions {
+        self.hnsw = Some(hnsw_options);
+        self
+    }
+
+    /// Returns the parameters of the HNSW graph, if the field has one.
+    pub fn hnsw_options(&self) -> Option<HnswOptions> {
+        self.hnsw
+    }
+
+    /// Returns the number of dimensions of the vectors.
+    pub fn dims(&self) -> u32 {
+        self.dims
+    }
+
+    /// Returns the similarity used to compare the vectors.
+    pub fn similarity(&self) -> VectorSimilarity {
+        self.similarity
+    }
+}
@@ -0,0 +1,646 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::{select_best_fragment_combination, FragmentCandidate, Snippet};
+use super::{DEFAULT_MAX_NUM_CHARS, DEFAULT_SNIPPET_POSTFIX, DEFAULT_SNIPPET_PREFIX};
+use crate::docset::DocSet;
+use crate::postings::{TermInfo, TermOccurrence};
+use crate::query::Query;
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, IndexRecordOption};
+use crate::tokenizer::{TextAnalyzer, Token};
+use crate::{DocAddress, DocId, InvertedIndexReader, Score, Searcher, TantivyDocument, Term};
+
+/// Maximum number of terms a prefix is expanded to when reading the occurrences of the words
+/// from the postings, as for [`PhrasePrefixQuery`](crate::query::PhrasePrefixQuery).
+const MAX_PREFIX_EXPANSIONS: usize = 50;
+
+/// A pattern of a query matching occurrences of terms in a document.
+///
+/// Patterns are extracted from a query with [`Query::highlight_patterns`].
+#[derive(Debug, Clone, Copy)]
+pub enum HighlightPattern<'a> {
+    /// Matches every occurrence of a term.
+    Term(&'a Term),
+    /// Matches the occurrences of terms appearing as a phrase.
+    Phrase {
+        /// The terms of the phrase, with their offset in the phrase.
+        terms: &'a [(usize, Term)],
+        /// A prefix matching the last word of the phrase, with its offset in the phrase.
+        prefix: Option<&'a (usize, Term)>,
+        /// The number of moves allowed for the terms to match the phrase.
+        slop: u32,
+    },
+}
+
+impl HighlightPattern<'_> {
+    fn field(&self) -> Option<Field> {
+        match self {
+            HighlightPattern::Term(term) => Some(term.field()),
+            HighlightPattern::Phrase { terms, prefix, .. } => {
+                terms.first().or(*prefix).map(|(_, term)| term.field())
+            }
+        }
+    }
+}
+
+/// A word of a phrase, with its offset in the phrase.
+#[derive(Debug)]
+struct PhraseWord {
+    offset: usize,
+    text: String,
+    is_prefix: bool,
+    score: Score,
+}
+
+impl PhraseWord {
+    fn matches(&self, token: &Token) -> bool {
+        if self.is_prefix {
+            token.text.starts_with(&self.text)
+        } else {
+            token.text == self.text
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Term { text: String, score: Score },
+    Phrase { words: Vec<PhraseWord>, slop: u32 },
+}
+
+impl Matcher {
+    /// Returns the words of the matcher, and whether they are prefixes.
+    fn words(&self) -> Vec<(&str, bool)> {
+        match self {
+            Matcher::Term { text, .. } => vec![(text, false)],
+            Matcher::Phrase { words, .. } => words
+                .iter()
+                .map(|word| (word.text.as_str(), word.is_prefix))
+                .collect(),
+        }
+    }
+}
+
+fn term_text(term: &Term) -> Option<String> {
+    term.value().as_str().map(str::to_string)
+}
+
+/// Returns the ordinals of the tokens of each match of a phrase.
+///
+/// As for [`PhraseQuery`](crate::query::PhraseQuery), the slop is the sum of the distances
+/// between the positions of consecutive words and their expected positions.
+fn match_phrase(words: &[PhraseWord], slop: u32, tokens: &[Token]) -> Vec<Vec<usize>> {
+    let candidates: Vec<Vec<usize>> = words
+        .iter()
+        .map(|word| {
+            (0..tokens.len())
+                .filter(|&token_ord| word.matches(&tokens[token_ord]))
+                .collect()
+        })
+        .collect();
+    let mut phrase_matches = Vec::new();
+    let mut token_ords = Vec::with_capacity(words.len());
+    for &first_token_ord in &candidates[0] {
+        token_ords.clear();
+        token_ords.push(first_token_ord);
+        let start = tokens[first_token_ord].position as i64 - words[0].offset as i64;
+        if match_phrase_words(words, &candidates, tokens, 1, start, slop, &mut token_ords) {
+            phrase_matches.push(token_ords.clone());
+        }
+    }
+    phrase_matches
+}
+
+/// Matches the words of a phrase from `word_ord` on, given the position at which the
+/// previous word is expected to start the phrase.
+fn match_phrase_words(
+    words: &[PhraseWord],
+    candidates: &[Vec<usize>],
+    tokens: &[Token],
+    word_ord: usize,
+    previous_start: i64,
+    remaining_slop: u32,
+    token_ords: &mut Vec<usize>,
+) -> bool {
+    if word_ord == words.len() {
+        return true;
+    }
+    for &token_ord in &candidates[word_ord] {
+        if token_ords.contains(&token_ord) {
+            continue;
+        }
+        let start = tokens[token_ord].position as i64 - words[word_ord].offset as i64;
+        let distance = start.abs_diff(previous_start);
+        if distance > remaining_slop as u64 {
+            continue;
+        }
+        token_ords.push(token_ord);
+        if match_phrase_words(
+            words,
+            candidates,
+            tokens,
+            word_ord + 1,
+            start,
+            remaining_slop - distance as u32,
+            token_ords,
+        ) {
+            return true;
+        }
+        token_ords.pop();
+    }
+    false
+}
+
+/// `Highlighter`
+///
+/// Unlike the [`SnippetGenerator`](super::SnippetGenerator), which highlights every
+/// occurrence of the terms of a query, the highlighter follows the structure of the query
+/// given by [`Query::highlight_patterns`]: the terms of a phrase are only highlighted where
+/// they appear as a phrase, within the slop of the query, and the terms of clauses excluding
+/// documents are not highlighted.
+///
+/// If the field is indexed with [`IndexRecordOption::WithFreqsAndPositionsAndOffsets`],
+/// [`Highlighter::highlight_hit`] reads the occurrences of the words of the query from the
+/// postings instead of tokenizing the stored text again, which is much cheaper for long
+/// documents.
+///
+/// # Example
+///
+/// ```rust
+/// # use tantivy::query::QueryParser;
+/// # use tantivy::schema::{Schema, TEXT};
+/// # use tantivy::{doc, Index};
+/// use tantivy::snippet::Highlighter;
+///
+/// # fn main() -> tantivy::Result<()> {
+/// #    let mut schema_builder = Schema::builder();
+/// #    let text_field = schema_builder.add_text_field("text", TEXT);
+/// #    let schema = schema_builder.build();
+/// #    let index = Index::create_in_ram(schema);
+/// #    let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// #    let doc = doc!(text_field => "the old man and the sea, an old sea story");
+/// #    index_writer.add_document(doc.clone())?;
+/// #    index_writer.commit()?;
+/// #    let query_parser = QueryParser::for_index(&index, vec![text_field]);
+/// // ...
+/// let query = query_parser.parse_query("\"old sea\"").unwrap();
+/// # let reader = index.reader()?;
+/// # let searcher = reader.searcher();
+/// let mut highlighter = Highlighter::create(&searcher, &*query, text_field)?;
+/// highlighter.set_tags("<em>", "</em>");
+/// let snippet = highlighter.highlight_doc(&doc);
+/// assert_eq!(
+///     snippet.to_html(),
+///     "the old man and the sea, an <em>old</em> <em>sea</em> story"
+/// );
+/// #    Ok(())
+/// # }
+/// ```
+pub struct Highlighter {
+    matchers: Vec<Matcher>,
+    tokenizer: TextAnalyzer,
+    field: Field,
+    max_num_chars: usize,
+    pre_tag: String,
+    post_tag: String,
+}
+
+impl Highlighter {
+    /// Creates a new highlighter for the given query and field.
+    ///
+    /// The words of the query are weighted by their rarity in the index, so that the
+    /// fragment with the rarest matches is preferred.
+    pub fn create(
+        searcher: &Searcher,
+        query: &dyn Query,
+        field: Field,
+    ) -> crate::Result<Highlighter> {
+        let mut patterns: Vec<HighlightPattern> = Vec::new();
+        query.highlight_patterns(&mut |pattern| {
+            if pattern.field() == Some(field) {
+                patterns.push(pattern);
+            }
+        });
+        let mut scores: HashMap<&Term, Score> = HashMap::new();
+        let mut score = |term| -> crate::Result<Score> {
+            if let Some(&score) = scores.get(term) {
+                return Ok(score);
+            }
+            let score = 1.0 / (1.0 + searcher.doc_freq(term)? as Score);
+            scores.insert(term, score);
+            Ok(score)
+        };
+        let mut matchers = Vec::with_capacity(patterns.len());
+        for pattern in patterns {
+            match pattern {
+                HighlightPattern::Term(term) => {
+                    if let Some(text) = term_text(term) {
+                        matchers.push(Matcher::Term {
+                            text,
+                            score: score(term)?,
+                        });
+                    }
+                }
+                HighlightPattern::Phrase {
+                    terms,
+                    prefix,
+                    slop,
+                } => {
+                    let mut words = Vec::with_capacity(terms.len() + 1);
+                    let words_with_prefix = terms
+                        .iter()
+                        .map(|term| (term, false))
+                        .chain(prefix.map(|prefix| (prefix, true)));
+                    for ((offset, term), is_prefix) in words_with_prefix {
+                        // Phrases on non-text values, e.g. in JSON fields, are not highlighted.
+                        let Some(text) = term_text(term) else {
+                            words.clear();
+                            break;
+                        };
+                        words.push(PhraseWord {
+                            offset: *offset,
+                            text,
+                            is_prefix,
+                            score: score(term)?,
+                        });
+                    }
+                    if !words.is_empty() {
+                        matchers.push(Matcher::Phrase { words, slop });
+                    }
+                }
+            }
+        }
+        let tokenizer = searcher.index().tokenizer_for_field(field)?;
+        Ok(Highlighter {
+            matchers,
+            tokenizer,
+            field,
+            max_num_chars: DEFAULT_MAX_NUM_CHARS,
+            pre_tag: DEFAULT_SNIPPET_PREFIX.to_string(),
+            post_tag: DEFAULT_SNIPPET_POSTFIX.to_string(),
+        })
+    }
+
+    /// Sets the maximum number of chars of the fragment. Default is 150.
+    pub fn set_max_num_chars(&mut self, max_num_chars: usize) {
+        self.max_num_chars = max_num_chars;
+    }
+
+    /// Sets the tags inserted before and after the highlighted parts by
+    /// [`Snippet::to_html`]. Default is `<b>` and `</b>`.
+    pub fn set_tags(&mut self, pre_tag: &str, post_tag: &str) {
+        self.pre_tag = pre_tag.to_string();
+        self.post_tag = post_tag.to_string();
+    }
+
+    /// Highlights the text associated with the `Highlighter`'s field in the given `Document`.
+    pub fn highlight_doc<D: Document>(&self, doc: &D) -> Snippet {
+        let mut text = String::new();
+        for (field, value) in doc.iter_fields_and_values() {
+            let value = value as D::Value<'_>;
+            if field != self.field {
+                continue;
+            }
+            if let Some(val) = value.as_str() {
+                text.push(' ');
+                text.push_str(val);
+            }
+        }
+        self.highlight(text.trim())
+    }
+
+    /// Highlights the given text.
+    pub fn highlight(&self, text: &str) -> Snippet {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut tokenizer = self.tokenizer.clone();
+        let mut token_stream = tokenizer.token_stream(text);
+        token_stream.process(&mut |token| tokens.push(token.clone()));
+        self.highlight_tokens(text, &tokens)
+    }
+
+    /// Highlights the text associated with the `Highlighter`'s field in the document of a hit.
+    ///
+    /// If the field is indexed with [`IndexRecordOption::WithFreqsAndPositionsAndOffsets`],
+    /// the occurrences of the words of the query are read from the postings, and the stored
+    /// text is only used to extract the fragment. As the other tokens of the text are
+    /// unknown, the fragment then stops at its last highlighted token.
+    ///
+    /// Otherwise, the stored text is tokenized again, as in [`Highlighter::highlight_doc`].
+    pub fn highlight_hit(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+    ) -> crate::Result<Snippet> {
+        let doc: TantivyDocument = searcher.doc_fields(doc_address, &[self.field])?;
+        let has_offsets = searcher
+            .schema()
+            .get_field_entry(self.field)
+            .field_type()
+            .index_record_option()
+            .is_some_and(IndexRecordOption::has_offsets);
+        if !has_offsets {
+            return Ok(self.highlight_doc(&doc));
+        }
+        // The offsets are relative to the values of the field joined by a space.
+        let mut texts: Vec<String> = Vec::new();
+        for (field, value) in doc.iter_fields_and_values() {
+            if field != self.field {
+                continue;
+            }
+            if let Some(val) = value.as_str() {
+                texts.push(val.to_string());
+            } else if let Some(pre_tokenized_text) = value.as_pre_tokenized_text() {
+                texts.push(pre_tokenized_text.text);
+            }
+        }
+        let text = texts.join(" ");
+        let inverted_index = searcher
+            .segment_reader(doc_address.segment_ord)
+            .inverted_index(self.field)?;
+        let mut tokens = self.tokens_from_postings(&inverted_index, doc_address.doc_id)?;
+        tokens.retain(|token| text.get(token.offset_from..token.offset_to).is_some());
+        Ok(self.highlight_tokens(&text, &tokens))
+    }
+
+    /// Reads the occurrences of the words of the query in a document from the postings.
+    fn tokens_from_postings(
+        &self,
+        inverted_index: &InvertedIndexReader,
+        doc: DocId,
+    ) -> crate::Result<Vec<Token>> {
+        let words: BTreeSet<(&str, bool)> = self
+            .matchers
+            .iter()
+            .flat_map(|matcher| matcher.words())
+            .collect();
+        let mut tokens = Vec::new();
+        let mut occurrences_buffer = Vec::new();
+        for (text, is_prefix) in words {
+            if !is_prefix {
+                let term = Term::from_field_text(self.field, text);
+                if let Some(term_info) = inverted_index.get_term_info(&term)? {
+                    let occurrences =
+                        read_occurrences(inverted_index, &term_info, doc, &mut occurrences_buffer)?;
+                    tokens.extend(occurrences.iter().map(|occ| to_token(occ, text)));
+                }
+                continue;
+            }
+            let mut stream = inverted_index
+                .terms()
+                .range()
+                .ge(text.as_bytes())
+                .into_stream()?;
+            let mut num_expansions = 0;
+            while num_expansions < MAX_PREFIX_EXPANSIONS
+                && stream.advance()
+                && stream.key().starts_with(text.as_bytes())
+            {
+                num_expansions += 1;
+                let Ok(term_text) = std::str::from_utf8(stream.key()) else {
+                    continue;
+                };
+                let occurrences =
+                    read_occurrences(inverted_index, stream.value(), doc, &mut occurrences_buffer)?;
+                tokens.extend(occurrences.iter().map(|occ| to_token(occ, term_text)));
+            }
+        }
+        tokens.sort_by_key(|token| (token.position, token.offset_from));
+        tokens.dedup();
+        Ok(tokens)
+    }
+
+    fn highlight_tokens(&self, text: &str, tokens: &[Token]) -> Snippet {
+        let mut token_scores: Vec<Option<Score>> = vec![None; tokens.len()];
+        for matcher in &self.matchers {
+            match matcher {
+                Matcher::Term { text, score } => {
+                    for (token, token_score) in tokens.iter().zip(token_scores.iter_mut()) {
+                        if token.text == *text {
+                            *token_score = Some(token_score.unwrap_or(0.0).max(*score));
+                        }
+                    }
+                }
+                Matcher::Phrase { words, slop } => {
+                    for token_ords in match_phrase(words, *slop, tokens) {
+                        for (word, token_ord) in words.iter().zip(token_ords) {
+                            let token_score = &mut token_scores[token_ord];
+                            *token_score = Some(token_score.unwrap_or(0.0).max(word.score));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut fragment = FragmentCandidate::new(0);
+        let mut fragments: Vec<FragmentCandidate> = vec![];
+        for (token, token_score) in tokens.iter().zip(token_scores) {
+            if (token.offset_to - fragment.start_offset) > self.max_num_chars {
+                if fragment.score > 0.0 {
+                    fragments.push(fragment)
+                };
+                fragment = FragmentCandidate::new(token.offset_from);
+            }
+            fragment.add_token(token, token_score);
+        }
+        if fragment.score > 0.0 {
+            fragments.push(fragment)
+        }
+        let mut snippet = select_best_fragment_combination(&fragments, text);
+        if !snippet.is_empty() {
+            snippet.set_snippet_prefix_postfix(&self.pre_tag, &self.post_tag);
+        }
+        snippet
+    }
+}
+
+/// Reads the occurrences of a term in a document.
+fn read_occurrences<'a>(
+    inverted_index: &InvertedIndexReader,
+    term_info: &TermInfo,
+    doc: DocId,
+    occurrences: &'a mut Vec<TermOccurrence>,
+) -> crate::Result<&'a [TermOccurrence]> {
+    occurrences.clear();
+    let mut postings = inverted_index.read_postings_from_terminfo(
+        term_info,
+        IndexRecordOption::WithFreqsAndPositionsAndOffsets,
+    )?;
+    if postings.doc() < doc {
+        postings.seek(doc);
+    }
+    if postings.doc() == doc {
+        postings.occurrences(occurrences);
+    }
+    Ok(occurrences)
+}
+
+fn to_token(occurrence: &TermOccurrence, text: &str) -> Token {
+    Token {
+        offset_from: occurrence.offset_from as usize,
+        offset_to: occurrence.offset_to as usize,
+        position: occurrence.position as usize,
+        text: text.to_string(),
+        position_length: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{
+        BooleanQuery, Occur, PhrasePrefixQuery, PhraseQuery, QueryParser, TermQuery,
+    };
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STORED, TEXT};
+    use crate::{DocAddress, Index, IndexWriter};
+
+    fn highlight(text: &str, build_query: impl Fn(Field) -> Box<dyn Query>) -> String {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        index_writer.add_document(doc!(text_field => text)).unwrap();
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query = build_query(text_field);
+        let highlighter = Highlighter::create(&searcher, &*query, text_field).unwrap();
+        highlighter.highlight(text).to_html()
+    }
+
+    fn term(field: Field, text: &str) -> Term {
+        Term::from_field_text(field, text)
+    }
+
+    #[test]
+    fn test_highlighter_phrase() {
+        let html = highlight("big dog and a big red dog", |field| {
+            Box::new(PhraseQuery::new(vec![
+                term(field, "big"),
+                term(field, "dog"),
+            ]))
+        });
+        assert_eq!(html, "<b>big</b> <b>dog</b> and a big red dog");
+    }
+
+    #[test]
+    fn test_highlighter_phrase_with_slop() {
+        let html = highlight("big dog and a big red dog", |field| {
+            let mut query = PhraseQuery::new(vec![term(field, "big"), term(field, "dog")]);
+            query.set_slop(1);
+            Box::new(query)
+        });
+        assert_eq!(
+            html,
+            "<b>big</b> <b>dog</b> and a <b>big</b> red <b>dog</b>"
+        );
+        let html = highlight("dog and big", |field| {
+            let mut query = PhraseQuery::new(vec![term(field, "big"), term(field, "dog")]);
+            query.set_slop(1);
+            Box::new(query)
+        });
+        // The words are too far apart, so nothing is highlighted.
+        assert_eq!(html, "");
+    }
+
+    #[test]
+    fn test_highlighter_phrase_prefix() {
+        let html = highlight("red dogs and red cats", |field| {
+            Box::new(PhrasePrefixQuery::new(vec![
+                term(field, "red"),
+                term(field, "ca"),
+            ]))
+        });
+        assert_eq!(html, "red dogs and <b>red</b> <b>cats</b>");
+    }
+
+    #[test]
+    fn test_highlighter_skips_excluded_terms() {
+        let html = highlight("big dog and a cat", |field| {
+            Box::new(BooleanQuery::new(vec![
+                (
+                    Occur::Must,
+                    Box::new(TermQuery::new(term(field, "dog"), IndexRecordOption::Basic)),
+                ),
+                (
+                    Occur::MustNot,
+                    Box::new(TermQuery::new(term(field, "cat"), IndexRecordOption::Basic)),
+                ),
+            ]))
+        });
+        assert_eq!(html, "big <b>dog</b> and a cat");
+    }
+
+    #[test]
+    fn test_highlighter_query_parser() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let text = "The quick brown fox jumps over the lazy dog. The dog is quick.";
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => text))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("\"quick brown\" lazy")?;
+        let mut highlighter = Highlighter::create(&searcher, &*query, text_field)?;
+        highlighter.set_tags("[", "]");
+        highlighter.set_max_num_chars(30);
+        let snippet = highlighter.highlight(text);
+        assert_eq!(snippet.to_html(), "The [quick] [brown] fox jumps over");
+        highlighter.set_max_num_chars(150);
+        let snippet = highlighter.highlight(text);
+        assert_eq!(
+            snippet.to_html(),
+            "The [quick] [brown] fox jumps over the [lazy] dog. The dog is quick"
+        );
+        assert!(highlighter.highlight("nothing to see").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_highlighter_highlight_hit() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_indexing = TextFieldIndexing::default()
+            .set_tokenizer("default")
+            .set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets);
+        let text_options = TextOptions::default()
+            .set_indexing_options(text_indexing)
+            .set_stored();
+        let offsets_field = schema_builder.add_text_field("offsets", text_options);
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            offsets_field => "A Big dog and a big red dog.",
+            offsets_field => "Dogs are big, and red dogs bigger.",
+            text_field => "A Big dog and a big red dog.",
+        ))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![offsets_field, text_field]);
+        let doc_address = DocAddress::new(0, 0);
+
+        let query = query_parser.parse_query("offsets:\"big dog\" offsets:red")?;
+        let highlighter = Highlighter::create(&searcher, &*query, offsets_field)?;
+        assert_eq!(
+            highlighter.highlight_hit(&searcher, doc_address)?.to_html(),
+            "A <b>Big</b> <b>dog</b> and a big <b>red</b> dog. Dogs are big, and <b>red</b>"
+        );
+
+        let query = query_parser.parse_query("offsets:\"red big\"~2 offsets:\"dogs bi\"*")?;
+        let highlighter = Highlighter::create(&searcher, &*query, offsets_field)?;
+        assert_eq!(
+            highlighter.highlight_hit(&searcher, doc_address)?.to_html(),
+            "A Big dog and a <b>big</b> <b>red</b> dog. Dogs are big, and red <b>dogs</b> \
+             <b>bigger</b>"
+        );
+
+        // Without offsets, the text is tokenized again.
+        let query = query_parser.parse_query("text:\"big dog\"")?;
+        let highlighter = Highlighter::create(&searcher, &*query, text_field)?;
+        assert_eq!(
+            highlighter.highlight_hit(&searcher, doc_address)?.to_html(),
+            "A <b>Big</b> <b>dog</b> and a big red dog"
+        );
+        Ok(())
+    }
+}
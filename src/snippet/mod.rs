@@ -54,6 +54,13 @@
 //!
 //! SnippetGenerator needs to be created from the `Searcher` and the query, and the field on which
 //! the `SnippetGenerator` should generate the snippets.
+//!
+//! The [`Highlighter`] generates snippets the same way, but only highlights the occurrences
+//! matching the structure of the query, e.g. the terms of a phrase query where they appear as a
+//! phrase.
+
+mod highlighter;
+mod multi_field_snippet_generator;
 
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
@@ -67,6 +74,9 @@ use crate::schema::Field;
 use crate::tokenizer::{TextAnalyzer, Token};
 use crate::{Score, Searcher, Term};
 
+pub use self::highlighter::{HighlightPattern, Highlighter};
+pub use self::multi_field_snippet_generator::MultiFieldSnippetGenerator;
+
 const DEFAULT_MAX_NUM_CHARS: usize = 150;
 
 const DEFAULT_SNIPPET_PREFIX: &str = "<b>";
@@ -101,9 +111,14 @@ impl FragmentCandidate {
     /// if the token is one of the terms, the score
     /// and highlighted fields are updated in the fragment.
     fn try_add_token(&mut self, token: &Token, terms: &BTreeMap<String, Score>) {
+        self.add_token(token, terms.get(&token.text.to_lowercase()).copied());
+    }
+
+    /// Adds the token to the fragment, highlighting it if it has a score.
+    fn add_token(&mut self, token: &Token, score_opt: Option<Score>) {
         self.stop_offset = token.offset_to;
 
-        if let Some(&score) = terms.get(&token.text.to_lowercase()) {
+        if let Some(score) = score_opt {
             self.score += score;
             self.highlighted.push(token.offset_from..token.offset_to);
         }
@@ -333,6 +348,28 @@ fn is_sorted(mut it: impl Iterator<Item = usize>) -> bool {
     true
 }
 
+/// Returns the text of the terms found in the index, associated with their score.
+fn terms_text(
+    searcher: &Searcher,
+    terms: BTreeSet<&Term>,
+) -> crate::Result<BTreeMap<String, Score>> {
+    let mut terms_text: BTreeMap<String, Score> = Default::default();
+    for term in terms {
+        let term_value = term.value();
+        let term_str = if let Some(term_str) = term_value.as_str() {
+            term_str
+        } else {
+            continue;
+        };
+        let doc_freq = searcher.doc_freq(term)?;
+        if doc_freq > 0 {
+            let score = 1.0 / (1.0 + doc_freq as Score);
+            terms_text.insert(term_str.to_string(), score);
+        }
+    }
+    Ok(terms_text)
+}
+
 /// `SnippetGenerator`
 ///
 /// # Example
@@ -408,20 +445,7 @@ impl SnippetGenerator {
                 terms.insert(term);
             }
         });
-        let mut terms_text: BTreeMap<String, Score> = Default::default();
-        for term in terms {
-            let term_value = term.value();
-            let term_str = if let Some(term_str) = term_value.as_str() {
-                term_str
-            } else {
-                continue;
-            };
-            let doc_freq = searcher.doc_freq(term)?;
-            if doc_freq > 0 {
-                let score = 1.0 / (1.0 + doc_freq as Score);
-                terms_text.insert(term_str.to_string(), score);
-            }
-        }
+        let terms_text = terms_text(searcher, terms)?;
         let tokenizer = searcher.index().tokenizer_for_field(field)?;
         Ok(SnippetGenerator {
             terms_text,
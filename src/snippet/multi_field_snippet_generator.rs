@@ -0,0 +1,218 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::{terms_text, Snippet, SnippetGenerator, DEFAULT_MAX_NUM_CHARS};
+use crate::query::Query;
+use crate::schema::document::{Document, Value};
+use crate::schema::Field;
+use crate::{DocAddress, Searcher, TantivyDocument, Term};
+
+/// Generates the snippets of several fields of a document at once.
+///
+/// The terms of the query are extracted once for all of the fields, and the snippets of a hit
+/// are generated from a single fetch of its stored fields, instead of having to create and run
+/// a [`SnippetGenerator`] per field.
+///
+/// # Example
+///
+/// ```rust
+/// # use tantivy::collector::TopDocs;
+/// # use tantivy::query::QueryParser;
+/// # use tantivy::schema::{Schema, STORED, TEXT};
+/// # use tantivy::{doc, Index};
+/// use tantivy::snippet::MultiFieldSnippetGenerator;
+///
+/// # fn main() -> tantivy::Result<()> {
+/// #    let mut schema_builder = Schema::builder();
+/// #    let title = schema_builder.add_text_field("title", TEXT | STORED);
+/// #    let body = schema_builder.add_text_field("body", TEXT | STORED);
+/// #    let index = Index::create_in_ram(schema_builder.build());
+/// #    let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// #    index_writer.add_document(doc!(
+/// #        title => "The Old Man and the Sea",
+/// #        body => "He was an old man who fished alone in a skiff in the Gulf Stream.",
+/// #    ))?;
+/// #    index_writer.commit()?;
+/// #    let query_parser = QueryParser::for_index(&index, vec![title, body]);
+/// // ...
+/// let query = query_parser.parse_query("old sea")?;
+/// # let searcher = index.reader()?.searcher();
+/// let snippet_generator = MultiFieldSnippetGenerator::create(&searcher, &*query, &[title, body])?;
+/// let (_score, doc_address) = searcher.search(&query, &TopDocs::with_limit(1))?[0];
+/// let snippets = snippet_generator.snippets(&searcher, doc_address)?;
+/// assert_eq!(
+///     snippets[&title].to_html(),
+///     "The <b>Old</b> Man and the <b>Sea</b>"
+/// );
+/// assert_eq!(
+///     snippets[&body].to_html(),
+///     "He was an <b>old</b> man who fished alone in a skiff in the Gulf Stream"
+/// );
+/// #    Ok(())
+/// # }
+/// ```
+pub struct MultiFieldSnippetGenerator {
+    snippet_generators: Vec<SnippetGenerator>,
+}
+
+impl MultiFieldSnippetGenerator {
+    /// Creates a new snippet generator for the given fields.
+    pub fn create(
+        searcher: &Searcher,
+        query: &dyn Query,
+        fields: &[Field],
+    ) -> crate::Result<MultiFieldSnippetGenerator> {
+        let mut terms_per_field: HashMap<Field, BTreeSet<&Term>> = fields
+            .iter()
+            .map(|&field| (field, BTreeSet::new()))
+            .collect();
+        query.query_terms(&mut |term, _| {
+            if let Some(terms) = terms_per_field.get_mut(&term.field()) {
+                terms.insert(term);
+            }
+        });
+        let mut snippet_generators = Vec::with_capacity(terms_per_field.len());
+        for &field in fields {
+            // Fields requested several times only get a single snippet.
+            let Some(terms) = terms_per_field.remove(&field) else {
+                continue;
+            };
+            snippet_generators.push(SnippetGenerator::new(
+                terms_text(searcher, terms)?,
+                searcher.index().tokenizer_for_field(field)?,
+                field,
+                DEFAULT_MAX_NUM_CHARS,
+            ));
+        }
+        Ok(MultiFieldSnippetGenerator { snippet_generators })
+    }
+
+    /// Sets a maximum number of chars for the snippets of all of the fields. Default is 150.
+    pub fn set_max_num_chars(&mut self, max_num_chars: usize) {
+        for snippet_generator in &mut self.snippet_generators {
+            snippet_generator.set_max_num_chars(max_num_chars);
+        }
+    }
+
+    /// Returns the fields the snippets are generated for.
+    pub fn fields(&self) -> Vec<Field> {
+        self.snippet_generators
+            .iter()
+            .map(|snippet_generator| snippet_generator.field)
+            .collect()
+    }
+
+    /// Generates the snippets of the fields of the given `Document`.
+    ///
+    /// The returned map has a snippet for each field, which is empty if none of the terms of
+    /// the query appear in the field.
+    pub fn snippets_from_doc<D: Document>(&self, doc: &D) -> HashMap<Field, Snippet> {
+        let mut texts: HashMap<Field, String> = self
+            .snippet_generators
+            .iter()
+            .map(|snippet_generator| (snippet_generator.field, String::new()))
+            .collect();
+        for (field, value) in doc.iter_fields_and_values() {
+            let value = value as D::Value<'_>;
+            let Some(text) = texts.get_mut(&field) else {
+                continue;
+            };
+            if let Some(val) = value.as_str() {
+                text.push(' ');
+                text.push_str(val);
+            }
+        }
+        self.snippet_generators
+            .iter()
+            .map(|snippet_generator| {
+                let field = snippet_generator.field;
+                (field, snippet_generator.snippet(texts[&field].trim()))
+            })
+            .collect()
+    }
+
+    /// Fetches the stored fields of a hit and generates their snippets.
+    ///
+    /// Only the fields of the snippets are fetched from the doc store, see
+    /// [`Searcher::doc_fields`].
+    pub fn snippets(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+    ) -> crate::Result<HashMap<Field, Snippet>> {
+        let doc: TantivyDocument = searcher.doc_fields(doc_address, &self.fields())?;
+        Ok(self.snippets_from_doc(&doc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, STORED, STRING, TEXT};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_multi_field_snippet_generator() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let tag = schema_builder.add_text_field("tag", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            title => "Rust search engines",
+            body => "Tantivy is a full-text search engine library.",
+            body => "It is written in Rust.",
+            tag => "rust",
+        ))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let query = query_parser.parse_query("rust library")?;
+
+        let mut snippet_generator =
+            MultiFieldSnippetGenerator::create(&searcher, &*query, &[title, body, tag, title])?;
+        assert_eq!(snippet_generator.fields(), vec![title, body, tag]);
+        let snippets = snippet_generator.snippets(&searcher, DocAddress::new(0, 0))?;
+        assert_eq!(snippets.len(), 3);
+        assert_eq!(snippets[&title].to_html(), "<b>Rust</b> search engines");
+        assert_eq!(
+            snippets[&body].to_html(),
+            "Tantivy is a full-text search engine <b>library</b>. It is written in <b>Rust</b>"
+        );
+        // The query has no terms on the tag field.
+        assert!(snippets[&tag].is_empty());
+
+        snippet_generator.set_max_num_chars(20);
+        let snippets = snippet_generator.snippets(&searcher, DocAddress::new(0, 0))?;
+        assert_eq!(snippets[&body].to_html(), "<b>library</b>. It is");
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_field_snippet_generator_matches_single_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let doc = doc!(
+            title => "a title about snippets",
+            body => "a body about titles, snippets and fragments",
+        );
+        index_writer.add_document(doc.clone())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let query = query_parser.parse_query("snippets fragments")?;
+        let snippets = MultiFieldSnippetGenerator::create(&searcher, &*query, &[title, body])?
+            .snippets_from_doc(&doc);
+        for field in [title, body] {
+            let snippet =
+                SnippetGenerator::create(&searcher, &*query, field)?.snippet_from_doc(&doc);
+            assert_eq!(snippets[&field].to_html(), snippet.to_html());
+            assert_eq!(snippets[&field].highlighted(), snippet.highlighted());
+        }
+        Ok(())
+    }
+}
@@ -7,7 +7,8 @@
 //! storage-level details into consideration. For example, if your file system block size is 4096
 //! bytes, we can under-count actual resultant space usage by up to 4095 bytes per file.
 
-use std::collections::HashMap;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use common::ByteCount;
 use serde::{Deserialize, Serialize};
@@ -57,6 +58,22 @@ impl SearcherSpaceUsage {
     pub fn total(&self) -> ByteCount {
         self.total
     }
+
+    /// Space usage of each field, summed over all of the segments, ordered by field.
+    pub fn per_field(&self) -> Vec<FieldSpaceUsage> {
+        let mut per_field: BTreeMap<Field, FieldSpaceUsage> = BTreeMap::new();
+        for segment in &self.segments {
+            for field_space_usage in segment.per_field() {
+                match per_field.entry(field_space_usage.field) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(field_space_usage);
+                    }
+                    Entry::Occupied(mut entry) => entry.get_mut().add(&field_space_usage),
+                }
+            }
+        }
+        per_field.into_values().collect()
+    }
 }
 
 /// Represents combined space usage for all of the large components comprising a segment.
@@ -69,6 +86,9 @@ pub struct SegmentSpaceUsage {
     positions: PerFieldSpaceUsage,
     fast_fields: PerFieldSpaceUsage,
     fieldnorms: PerFieldSpaceUsage,
+    points: PerFieldSpaceUsage,
+    vectors: PerFieldSpaceUsage,
+    term_vectors: PerFieldSpaceUsage,
 
     store: StoreSpaceUsage,
 
@@ -86,6 +106,9 @@ impl SegmentSpaceUsage {
         positions: PerFieldSpaceUsage,
         fast_fields: PerFieldSpaceUsage,
         fieldnorms: PerFieldSpaceUsage,
+        points: PerFieldSpaceUsage,
+        vectors: PerFieldSpaceUsage,
+        term_vectors: PerFieldSpaceUsage,
         store: StoreSpaceUsage,
         deletes: ByteCount,
     ) -> SegmentSpaceUsage {
@@ -94,6 +117,9 @@ impl SegmentSpaceUsage {
             + positions.total()
             + fast_fields.total()
             + fieldnorms.total()
+            + points.total()
+            + vectors.total()
+            + term_vectors.total()
             + store.total()
             + deletes;
         SegmentSpaceUsage {
@@ -103,6 +129,9 @@ impl SegmentSpaceUsage {
             positions,
             fast_fields,
             fieldnorms,
+            points,
+            vectors,
+            term_vectors,
             store,
             deletes,
             total,
@@ -121,6 +150,9 @@ impl SegmentSpaceUsage {
             Positions => PerField(self.positions().clone()),
             FastFields => PerField(self.fast_fields().clone()),
             FieldNorms => PerField(self.fieldnorms().clone()),
+            Points => PerField(self.points().clone()),
+            Vectors => PerField(self.vectors().clone()),
+            TermVectors => PerField(self.term_vectors().clone()),
             Terms => PerField(self.termdict().clone()),
             SegmentComponent::Store => ComponentSpaceUsage::Store(self.store().clone()),
             SegmentComponent::TempStore => ComponentSpaceUsage::Store(self.store().clone()),
@@ -158,6 +190,21 @@ impl SegmentSpaceUsage {
         &self.fieldnorms
     }
 
+    /// Space usage for the points index
+    pub fn points(&self) -> &PerFieldSpaceUsage {
+        &self.points
+    }
+
+    /// Space usage for the dense vectors
+    pub fn vectors(&self) -> &PerFieldSpaceUsage {
+        &self.vectors
+    }
+
+    /// Space usage for the term vectors
+    pub fn term_vectors(&self) -> &PerFieldSpaceUsage {
+        &self.term_vectors
+    }
+
     /// Space usage for stored documents
     pub fn store(&self) -> &StoreSpaceUsage {
         &self.store
@@ -172,6 +219,144 @@ impl SegmentSpaceUsage {
     pub fn total(&self) -> ByteCount {
         self.total
     }
+
+    /// Space usage of a field over all of the components of the segment.
+    pub fn field(&self, field: Field) -> FieldSpaceUsage {
+        let num_bytes = |per_field: &PerFieldSpaceUsage| {
+            per_field
+                .fields
+                .get(&field)
+                .map(FieldUsage::total)
+                .unwrap_or_default()
+        };
+        FieldSpaceUsage {
+            field,
+            termdict: num_bytes(&self.termdict),
+            postings: num_bytes(&self.postings),
+            positions: num_bytes(&self.positions),
+            fast_fields: num_bytes(&self.fast_fields),
+            fieldnorms: num_bytes(&self.fieldnorms),
+            points: num_bytes(&self.points),
+            vectors: num_bytes(&self.vectors),
+            term_vectors: num_bytes(&self.term_vectors),
+            store: num_bytes(&self.store.fields),
+        }
+    }
+
+    /// Space usage of each field of the segment, ordered by field.
+    pub fn per_field(&self) -> Vec<FieldSpaceUsage> {
+        let fields: BTreeSet<Field> = [
+            &self.termdict,
+            &self.postings,
+            &self.positions,
+            &self.fast_fields,
+            &self.fieldnorms,
+            &self.points,
+            &self.vectors,
+            &self.term_vectors,
+            &self.store.fields,
+        ]
+        .into_iter()
+        .flat_map(|per_field| per_field.fields.keys().copied())
+        .collect();
+        fields.into_iter().map(|field| self.field(field)).collect()
+    }
+}
+
+/// Represents the space usage of a field over all of the components of a segment, or of all of
+/// the segments of a searcher.
+///
+/// The stored fields are compressed together, so the space usage of a field in the store is an
+/// estimate. See [`StoreReader::fields_space_usage`](crate::store::StoreReader::fields_space_usage).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FieldSpaceUsage {
+    field: Field,
+    termdict: ByteCount,
+    postings: ByteCount,
+    positions: ByteCount,
+    fast_fields: ByteCount,
+    fieldnorms: ByteCount,
+    points: ByteCount,
+    vectors: ByteCount,
+    term_vectors: ByteCount,
+    store: ByteCount,
+}
+
+impl FieldSpaceUsage {
+    fn add(&mut self, other: &FieldSpaceUsage) {
+        self.termdict += other.termdict;
+        self.postings += other.postings;
+        self.positions += other.positions;
+        self.fast_fields += other.fast_fields;
+        self.fieldnorms += other.fieldnorms;
+        self.points += other.points;
+        self.vectors += other.vectors;
+        self.term_vectors += other.term_vectors;
+        self.store += other.store;
+    }
+
+    /// Field
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Space usage in the term dictionary
+    pub fn termdict(&self) -> ByteCount {
+        self.termdict
+    }
+
+    /// Space usage in the postings lists
+    pub fn postings(&self) -> ByteCount {
+        self.postings
+    }
+
+    /// Space usage in the positions
+    pub fn positions(&self) -> ByteCount {
+        self.positions
+    }
+
+    /// Space usage in the fast fields
+    pub fn fast_fields(&self) -> ByteCount {
+        self.fast_fields
+    }
+
+    /// Space usage in the field norms
+    pub fn fieldnorms(&self) -> ByteCount {
+        self.fieldnorms
+    }
+
+    /// Space usage in the points index
+    pub fn points(&self) -> ByteCount {
+        self.points
+    }
+
+    /// Space usage in the dense vectors
+    pub fn vectors(&self) -> ByteCount {
+        self.vectors
+    }
+
+    /// Space usage in the term vectors
+    pub fn term_vectors(&self) -> ByteCount {
+        self.term_vectors
+    }
+
+    /// Estimated space usage in the doc store
+    pub fn store(&self) -> ByteCount {
+        self.store
+    }
+
+    /// Total space usage in bytes for this field
+    pub fn total(&self) -> ByteCount {
+        self.termdict
+            + self.postings
+            + self.positions
+            + self.fast_fields
+            + self.fieldnorms
+            + self.points
+            + self.vectors
+            + self.term_vectors
+            + self.store
+    }
 }
 
 /// Represents space usage for the Store for this segment.
@@ -183,11 +368,21 @@ impl SegmentSpaceUsage {
 pub struct StoreSpaceUsage {
     data: ByteCount,
     offsets: ByteCount,
+    #[serde(default)]
+    fields: PerFieldSpaceUsage,
 }
 
 impl StoreSpaceUsage {
     pub(crate) fn new(data: ByteCount, offsets: ByteCount) -> StoreSpaceUsage {
-        StoreSpaceUsage { data, offsets }
+        StoreSpaceUsage {
+            data,
+            offsets,
+            fields: PerFieldSpaceUsage::default(),
+        }
+    }
+
+    pub(crate) fn with_fields(self, fields: PerFieldSpaceUsage) -> StoreSpaceUsage {
+        StoreSpaceUsage { fields, ..self }
     }
 
     /// Space usage for the data part of the store
@@ -200,6 +395,11 @@ impl StoreSpaceUsage {
         self.offsets
     }
 
+    /// Estimated space usage of each field in the data part of the store
+    pub fn fields(&self) -> &PerFieldSpaceUsage {
+        &self.fields
+    }
+
     /// Total space usage in bytes for this Store
     pub fn total(&self) -> ByteCount {
         self.data + self.offsets
@@ -210,7 +410,7 @@ impl StoreSpaceUsage {
 ///
 /// A field can appear with a single index (typically 0) or with multiple indexes.
 /// Multiple indexes are used to handle variable length things, where
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PerFieldSpaceUsage {
     fields: HashMap<Field, FieldUsage>,
     total: ByteCount,
@@ -290,6 +490,8 @@ impl FieldUsage {
 
 #[cfg(test)]
 mod test {
+    use common::ByteCount;
+
     use crate::index::Index;
     use crate::schema::{Field, Schema, FAST, INDEXED, STORED, TEXT};
     use crate::space_usage::PerFieldSpaceUsage;
@@ -439,6 +641,86 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_per_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let num = schema_builder.add_u64_field("num", FAST | INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for i in 0..2u64 {
+                for j in 0..100u64 {
+                    index_writer.add_document(doc!(
+                        title => format!("title {j}"),
+                        body => format!("{i}/{j} {}", "a long body which is only stored ".repeat(20)),
+                        num => j,
+                    ))?;
+                }
+                index_writer.commit()?;
+            }
+        }
+
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let searcher_space_usage = searcher.space_usage()?;
+        assert_eq!(2, searcher_space_usage.segments().len());
+
+        let segment = &searcher_space_usage.segments()[0];
+        let per_field = segment.per_field();
+        assert_eq!(
+            per_field
+                .iter()
+                .map(|usage| usage.field())
+                .collect::<Vec<_>>(),
+            vec![title, body, num]
+        );
+        let title_usage = segment.field(title);
+        assert!(title_usage.termdict() > 0);
+        assert!(title_usage.postings() > 0);
+        assert!(title_usage.positions() > 0);
+        assert!(title_usage.fieldnorms() > 0);
+        assert_eq!(title_usage.fast_fields(), 0);
+        assert!(title_usage.store() > 0);
+        let body_usage = segment.field(body);
+        assert_eq!(body_usage.total(), body_usage.store());
+        assert!(body_usage.store() > title_usage.store());
+        let num_usage = segment.field(num);
+        assert!(num_usage.fast_fields() > 0);
+        assert_eq!(num_usage.store(), 0);
+        // The stored fields share the data of the store.
+        let store_fields_total = title_usage.store() + body_usage.store();
+        assert!(store_fields_total <= segment.store().data_usage());
+        assert!(store_fields_total + ByteCount::from(2u64) >= segment.store().data_usage());
+        assert_eq!(
+            per_field
+                .iter()
+                .map(|usage| usage.total())
+                .sum::<ByteCount>(),
+            segment.termdict().total()
+                + segment.postings().total()
+                + segment.positions().total()
+                + segment.fast_fields().total()
+                + segment.fieldnorms().total()
+                + store_fields_total
+        );
+
+        let searcher_per_field = searcher_space_usage.per_field();
+        assert_eq!(searcher_per_field.len(), 3);
+        assert_eq!(
+            searcher_per_field[1].store(),
+            searcher_space_usage
+                .segments()
+                .iter()
+                .map(|segment| segment.field(body).store())
+                .sum::<ByteCount>()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_deletes() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
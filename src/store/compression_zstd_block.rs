@@ -1,6 +1,7 @@
 use std::io;
 
-use zstd::bulk::{compress_to_buffer, decompress_to_buffer};
+use zstd::bulk::{Compressor, Decompressor};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
 use zstd::DEFAULT_COMPRESSION_LEVEL;
 
 #[inline]
@@ -8,6 +9,28 @@ pub fn compress(
     uncompressed: &[u8],
     compressed: &mut Vec<u8>,
     compression_level: Option<i32>,
+) -> io::Result<()> {
+    let mut compressor = Compressor::new(compression_level.unwrap_or(DEFAULT_COMPRESSION_LEVEL))?;
+    compress_with(&mut compressor, uncompressed, compressed)
+}
+
+/// Compresses a block with a dictionary.
+///
+/// The compression level is the one the dictionary was prepared with.
+#[inline]
+pub fn compress_with_dictionary(
+    uncompressed: &[u8],
+    compressed: &mut Vec<u8>,
+    dictionary: &EncoderDictionary<'_>,
+) -> io::Result<()> {
+    let mut compressor = Compressor::with_prepared_dictionary(dictionary)?;
+    compress_with(&mut compressor, uncompressed, compressed)
+}
+
+fn compress_with(
+    compressor: &mut Compressor<'_>,
+    uncompressed: &[u8],
+    compressed: &mut Vec<u8>,
 ) -> io::Result<()> {
     let count_size = std::mem::size_of::<u32>();
     let max_size = zstd::zstd_safe::compress_bound(uncompressed.len()) + count_size;
@@ -15,11 +38,8 @@ pub fn compress(
     compressed.clear();
     compressed.resize(max_size, 0);
 
-    let compressed_size = compress_to_buffer(
-        uncompressed,
-        &mut compressed[count_size..],
-        compression_level.unwrap_or(DEFAULT_COMPRESSION_LEVEL),
-    )?;
+    let compressed_size =
+        compressor.compress_to_buffer(uncompressed, &mut compressed[count_size..])?;
 
     compressed[0..count_size].copy_from_slice(&(uncompressed.len() as u32).to_le_bytes());
     compressed.resize(compressed_size + count_size, 0);
@@ -29,6 +49,26 @@ pub fn compress(
 
 #[inline]
 pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<()> {
+    let mut decompressor = Decompressor::new()?;
+    decompress_with(&mut decompressor, compressed, decompressed)
+}
+
+/// Decompresses a block that was compressed with the given dictionary.
+#[inline]
+pub fn decompress_with_dictionary(
+    compressed: &[u8],
+    decompressed: &mut Vec<u8>,
+    dictionary: &DecoderDictionary<'_>,
+) -> io::Result<()> {
+    let mut decompressor = Decompressor::with_prepared_dictionary(dictionary)?;
+    decompress_with(&mut decompressor, compressed, decompressed)
+}
+
+fn decompress_with(
+    decompressor: &mut Decompressor<'_>,
+    compressed: &[u8],
+    decompressed: &mut Vec<u8>,
+) -> io::Result<()> {
     let count_size = std::mem::size_of::<u32>();
     let uncompressed_size = u32::from_le_bytes(
         compressed
@@ -41,7 +81,8 @@ pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<(
     decompressed.clear();
     decompressed.resize(uncompressed_size, 0);
 
-    let decompressed_size = decompress_to_buffer(&compressed[count_size..], decompressed)?;
+    let decompressed_size =
+        decompressor.decompress_to_buffer(&compressed[count_size..], decompressed)?;
 
     if decompressed_size != uncompressed_size {
         return Err(io::Error::new(
@@ -52,3 +93,13 @@ pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<(
 
     Ok(())
 }
+
+/// Trains a dictionary of at most `dictionary_size` bytes on the given samples.
+///
+/// `samples` is the concatenation of the samples, of lengths `sample_sizes`.
+///
+/// Returns an empty dictionary if zstd fails to train one, which happens when there
+/// are too few samples.
+pub fn train_dictionary(samples: &[u8], sample_sizes: &[usize], dictionary_size: usize) -> Vec<u8> {
+    zstd::dict::from_continuous(samples, sample_sizes, dictionary_size).unwrap_or_default()
+}
@@ -1,4 +1,6 @@
 use std::io;
+#[cfg(feature = "zstd-compression")]
+use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +17,9 @@ pub enum Decompressor {
     /// Use the zstd decompressor
     #[cfg(feature = "zstd-compression")]
     Zstd,
+    /// Use the zstd decompressor, with the dictionary stored in the doc store
+    #[cfg(feature = "zstd-compression")]
+    ZstdWithDictionary,
 }
 
 impl From<Compressor> for Decompressor {
@@ -24,7 +29,13 @@ impl From<Compressor> for Decompressor {
             #[cfg(feature = "lz4-compression")]
             Compressor::Lz4 => Decompressor::Lz4,
             #[cfg(feature = "zstd-compression")]
-            Compressor::Zstd(_) => Decompressor::Zstd,
+            Compressor::Zstd(zstd_compressor) => {
+                if zstd_compressor.dictionary_size.is_some() {
+                    Decompressor::ZstdWithDictionary
+                } else {
+                    Decompressor::Zstd
+                }
+            }
         }
     }
 }
@@ -37,6 +48,8 @@ impl Decompressor {
             1 => Decompressor::Lz4,
             #[cfg(feature = "zstd-compression")]
             4 => Decompressor::Zstd,
+            #[cfg(feature = "zstd-compression")]
+            5 => Decompressor::ZstdWithDictionary,
             _ => panic!("unknown compressor id {id:?}"),
         }
     }
@@ -48,6 +61,17 @@ impl Decompressor {
             Self::Lz4 => 1,
             #[cfg(feature = "zstd-compression")]
             Self::Zstd => 4,
+            #[cfg(feature = "zstd-compression")]
+            Self::ZstdWithDictionary => 5,
+        }
+    }
+
+    /// Returns true if the blocks are compressed with a dictionary stored in the doc store.
+    pub(crate) fn has_dictionary(&self) -> bool {
+        match self {
+            #[cfg(feature = "zstd-compression")]
+            Self::ZstdWithDictionary => true,
+            _ => false,
         }
     }
 
@@ -73,10 +97,65 @@ impl Decompressor {
             Self::Lz4 => super::compression_lz4_block::decompress(compressed, decompressed),
             #[cfg(feature = "zstd-compression")]
             Self::Zstd => super::compression_zstd_block::decompress(compressed, decompressed),
+            #[cfg(feature = "zstd-compression")]
+            Self::ZstdWithDictionary => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "decompressing requires the dictionary of the doc store",
+            )),
         }
     }
 }
 
+/// Decompresses the blocks of a doc store, using the dictionary of the doc store if any.
+#[derive(Clone)]
+pub(crate) struct BlockDecompressor {
+    decompressor: Decompressor,
+    #[cfg(feature = "zstd-compression")]
+    dictionary: Option<Arc<zstd::dict::DecoderDictionary<'static>>>,
+}
+
+impl BlockDecompressor {
+    /// Creates a block decompressor, given the dictionary bytes stored in the doc store.
+    ///
+    /// The dictionary is empty if the decompressor does not use one, or if no dictionary
+    /// could be trained when the doc store was written.
+    pub(crate) fn new(decompressor: Decompressor, dictionary: &[u8]) -> BlockDecompressor {
+        #[cfg(not(feature = "zstd-compression"))]
+        let _ = dictionary;
+        BlockDecompressor {
+            decompressor,
+            #[cfg(feature = "zstd-compression")]
+            dictionary: (decompressor.has_dictionary() && !dictionary.is_empty())
+                .then(|| Arc::new(zstd::dict::DecoderDictionary::copy(dictionary))),
+        }
+    }
+
+    pub(crate) fn decompressor(&self) -> Decompressor {
+        self.decompressor
+    }
+
+    pub(crate) fn decompress(&self, compressed_block: &[u8]) -> io::Result<Vec<u8>> {
+        #[cfg(feature = "zstd-compression")]
+        if self.decompressor.has_dictionary() {
+            let mut decompressed_block = Vec::new();
+            if let Some(dictionary) = &self.dictionary {
+                super::compression_zstd_block::decompress_with_dictionary(
+                    compressed_block,
+                    &mut decompressed_block,
+                    dictionary,
+                )?;
+            } else {
+                super::compression_zstd_block::decompress(
+                    compressed_block,
+                    &mut decompressed_block,
+                )?;
+            }
+            return Ok(decompressed_block);
+        }
+        self.decompressor.decompress(compressed_block)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,5 +170,13 @@ mod tests {
             Decompressor::from(Compressor::Zstd(Default::default())),
             Decompressor::Zstd
         );
+        #[cfg(feature = "zstd-compression")]
+        assert_eq!(
+            Decompressor::from(Compressor::Zstd(super::super::ZstdCompressor {
+                compression_level: None,
+                dictionary_size: Some(1_000),
+            })),
+            Decompressor::ZstdWithDictionary
+        );
     }
 }
@@ -10,18 +10,24 @@ pub struct DocStoreFooter {
     pub offset: u64,
     pub doc_store_version: DocStoreVersion,
     pub decompressor: Decompressor,
+    pub dictionary_len: u32,
+    pub families_len: u32,
 }
 
 /// Serialises the footer to a byte-array
 /// - offset : 8 bytes
 /// - compressor id: 1 byte
-/// - reserved for future use: 15 bytes
+/// - dictionary length: 4 bytes
+/// - families length: 4 bytes
+/// - reserved for future use: 7 bytes
 impl BinarySerializable for DocStoreFooter {
     fn serialize<W: io::Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
         BinarySerializable::serialize(&DOC_STORE_VERSION, writer)?;
         BinarySerializable::serialize(&self.offset, writer)?;
         BinarySerializable::serialize(&self.decompressor.get_id(), writer)?;
-        writer.write_all(&[0; 15])?;
+        BinarySerializable::serialize(&self.dictionary_len, writer)?;
+        BinarySerializable::serialize(&self.families_len, writer)?;
+        writer.write_all(&[0; 7])?;
         Ok(())
     }
 
@@ -34,12 +40,16 @@ impl BinarySerializable for DocStoreFooter {
         }
         let offset = u64::deserialize(reader)?;
         let compressor_id = u8::deserialize(reader)?;
-        let mut skip_buf = [0; 15];
+        let dictionary_len = u32::deserialize(reader)?;
+        let families_len = u32::deserialize(reader)?;
+        let mut skip_buf = [0; 7];
         reader.read_exact(&mut skip_buf)?;
         Ok(DocStoreFooter {
             offset,
             doc_store_version,
             decompressor: Decompressor::from_id(compressor_id),
+            dictionary_len,
+            families_len,
         })
     }
 }
@@ -53,11 +63,15 @@ impl DocStoreFooter {
         offset: u64,
         decompressor: Decompressor,
         doc_store_version: DocStoreVersion,
+        dictionary_len: u32,
+        families_len: u32,
     ) -> Self {
         DocStoreFooter {
             offset,
             doc_store_version,
             decompressor,
+            dictionary_len,
+            families_len,
         }
     }
 
@@ -83,5 +97,5 @@ fn doc_store_footer_test() {
     // This test is just to safe guard changes on the footer.
     // When the doc store footer is updated, make sure to update also the serialize/deserialize
     // methods
-    assert_eq!(core::mem::size_of::<DocStoreFooter>(), 16);
+    assert_eq!(core::mem::size_of::<DocStoreFooter>(), 24);
 }
@@ -0,0 +1,95 @@
+use std::io;
+
+use common::{BinarySerializable, VInt};
+
+use crate::store::index::Checkpoint;
+use crate::DocId;
+
+/// The list of the checkpoints of the blocks of a family of stored fields.
+///
+/// Contrary to the skip index, the blocks do not need to be contiguous, so that
+/// the blocks of several families can be interleaved in the doc store.
+///
+/// It is serialized as its number of checkpoints followed, for each checkpoint, by its number
+/// of docs, the number of bytes since the end of the previous block and its number of bytes.
+#[derive(Default)]
+pub struct CheckpointList {
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl CheckpointList {
+    pub fn insert(&mut self, checkpoint: Checkpoint) {
+        self.checkpoints.push(checkpoint);
+    }
+
+    pub fn serialize_into<W: io::Write>(&self, output: &mut W) -> io::Result<()> {
+        VInt(self.checkpoints.len() as u64).serialize(output)?;
+        let mut prev_end = 0;
+        for checkpoint in &self.checkpoints {
+            VInt(checkpoint.doc_range.len() as u64).serialize(output)?;
+            VInt((checkpoint.byte_range.start - prev_end) as u64).serialize(output)?;
+            VInt(checkpoint.byte_range.len() as u64).serialize(output)?;
+            prev_end = checkpoint.byte_range.end;
+        }
+        Ok(())
+    }
+
+    pub fn deserialize<R: io::Read>(reader: &mut R) -> io::Result<CheckpointList> {
+        let num_checkpoints = VInt::deserialize_u64(reader)? as usize;
+        let mut checkpoints = Vec::with_capacity(num_checkpoints);
+        let mut doc: DocId = 0;
+        let mut prev_end = 0;
+        for _ in 0..num_checkpoints {
+            let num_docs = VInt::deserialize_u64(reader)? as DocId;
+            let start = prev_end + VInt::deserialize_u64(reader)? as usize;
+            let end = start + VInt::deserialize_u64(reader)? as usize;
+            checkpoints.push(Checkpoint {
+                doc_range: doc..doc + num_docs,
+                byte_range: start..end,
+            });
+            doc += num_docs;
+            prev_end = end;
+        }
+        Ok(CheckpointList { checkpoints })
+    }
+
+    pub fn checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
+        self.checkpoints.iter().cloned()
+    }
+
+    pub fn seek(&self, target: DocId) -> Option<Checkpoint> {
+        let idx = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.doc_range.end <= target);
+        self.checkpoints
+            .get(idx)
+            .filter(|checkpoint| checkpoint.doc_range.contains(&target))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_list() -> io::Result<()> {
+        let mut checkpoint_list = CheckpointList::default();
+        checkpoint_list.insert(Checkpoint {
+            doc_range: 0..3,
+            byte_range: 0..10,
+        });
+        checkpoint_list.insert(Checkpoint {
+            doc_range: 3..4,
+            byte_range: 25..30,
+        });
+        let mut buffer = Vec::new();
+        checkpoint_list.serialize_into(&mut buffer)?;
+        let checkpoint_list = CheckpointList::deserialize(&mut &buffer[..])?;
+        assert_eq!(checkpoint_list.checkpoints().count(), 2);
+        assert_eq!(checkpoint_list.seek(2).unwrap().byte_range, 0..10);
+        assert_eq!(checkpoint_list.seek(3).unwrap().byte_range, 25..30);
+        assert!(checkpoint_list.seek(4).is_none());
+        Ok(())
+    }
+}
@@ -3,9 +3,11 @@ const CHECKPOINT_PERIOD: usize = 8;
 use std::fmt;
 use std::ops::Range;
 mod block;
+mod checkpoint_list;
 mod skip_index;
 mod skip_index_builder;
 
+pub use self::checkpoint_list::CheckpointList;
 pub use self::skip_index::SkipIndex;
 pub use self::skip_index_builder::SkipIndexBuilder;
 use crate::DocId;
@@ -36,6 +38,31 @@ impl fmt::Debug for Checkpoint {
     }
 }
 
+/// The index of the blocks of a family of stored fields.
+///
+/// A skip index is used when the doc store has a single family, and a checkpoint list when
+/// the blocks of several families are interleaved.
+pub enum BlockIndex {
+    SkipIndex(SkipIndex),
+    CheckpointList(CheckpointList),
+}
+
+impl BlockIndex {
+    pub fn checkpoints(&self) -> Box<dyn Iterator<Item = Checkpoint> + '_> {
+        match self {
+            BlockIndex::SkipIndex(skip_index) => Box::new(skip_index.checkpoints()),
+            BlockIndex::CheckpointList(checkpoint_list) => Box::new(checkpoint_list.checkpoints()),
+        }
+    }
+
+    pub fn seek(&self, target: DocId) -> Option<Checkpoint> {
+        match self {
+            BlockIndex::SkipIndex(skip_index) => skip_index.seek(target),
+            BlockIndex::CheckpointList(checkpoint_list) => checkpoint_list.seek(target),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -16,6 +16,18 @@
 //! the block a second time, but their is no real
 //! uncompressed block* cache.
 //!
+//! With Zstd, a dictionary can be trained on the first documents of a segment
+//! (see [`ZstdCompressor::dictionary_size`]). It is stored in the doc store and
+//! improves the compression of small blocks of similar documents.
+//!
+//! Stored fields can also be grouped into families (see [`DocStoreFamily`](crate::DocStoreFamily)),
+//! which are kept in their own blocks, with their own compressor and block size. Reading a
+//! subset of the fields of a document only decompresses the blocks of their families.
+//!
+//! Each compressed block ends with a checksum, so that corrupted blocks are detected when they
+//! are read. [`StoreReader::iter_salvage`] reads the documents of a damaged doc store, skipping
+//! the corrupted blocks and reporting the documents that were lost.
+//!
 //! A typical use case for the store is, once
 //! the search result page has been computed, returning
 //! the actual content of the 10 best document.
@@ -38,13 +50,14 @@ mod writer;
 
 pub use self::compressors::{Compressor, ZstdCompressor};
 pub use self::decompressors::Decompressor;
-pub use self::reader::{CacheStats, StoreReader};
+pub use self::reader::{BlockCache, CacheStats, SalvagedDoc, StoreReader};
 pub(crate) use self::reader::{DocStoreVersion, DOCSTORE_CACHE_CAPACITY};
+pub(crate) use self::writer::StoreFamily;
 pub use self::writer::StoreWriter;
 mod store_compressor;
 
 /// Doc store version in footer to handle format changes.
-pub(crate) const DOC_STORE_VERSION: DocStoreVersion = DocStoreVersion::V2;
+pub(crate) const DOC_STORE_VERSION: DocStoreVersion = DocStoreVersion::V4;
 
 #[cfg(feature = "lz4-compression")]
 mod compression_lz4_block;
@@ -215,6 +228,140 @@ pub(crate) mod tests {
         )
     }
 
+    #[cfg(feature = "zstd-compression")]
+    #[test]
+    fn test_store_zstd_dictionary() -> crate::Result<()> {
+        let compressor = Compressor::Zstd(ZstdCompressor {
+            compression_level: None,
+            dictionary_size: Some(4_096),
+        });
+        test_store(compressor, 1_024, false)?;
+        test_store(compressor, 1_024, true)
+    }
+
+    #[cfg(feature = "zstd-compression")]
+    #[test]
+    fn test_store_zstd_dictionary_too_few_docs() -> crate::Result<()> {
+        let path = Path::new("store");
+        let directory = RamDirectory::create();
+        let store_wrt = directory.open_write(path)?;
+        let compressor = Compressor::Zstd(ZstdCompressor {
+            compression_level: None,
+            dictionary_size: Some(4_096),
+        });
+        let schema = write_lorem_ipsum_store(store_wrt, 3, compressor, BLOCK_SIZE, false);
+        let field_title = schema.get_field("title").unwrap();
+        let store = StoreReader::open(directory.open_read(path)?, 10)?;
+        assert_eq!(store.decompressor(), Decompressor::ZstdWithDictionary);
+        for i in 0..3 {
+            assert_eq!(
+                *store
+                    .get::<TantivyDocument>(i)?
+                    .get_first(field_title)
+                    .unwrap()
+                    .as_str()
+                    .unwrap(),
+                format!("Doc {i}")
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_families() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field_body = schema_builder.add_text_field("body", TextOptions::default().set_stored());
+        let field_title =
+            schema_builder.add_text_field("title", TextOptions::default().set_stored());
+        let schema = schema_builder.build();
+        let families = vec![StoreFamily {
+            fields: vec![field_title],
+            compressor: Compressor::None,
+            block_size: 256,
+        }];
+        for separate_thread in [false, true] {
+            let path = Path::new("store");
+            let directory = RamDirectory::create();
+            let mut store_writer = StoreWriter::with_families(
+                directory.open_write(path)?,
+                Compressor::default(),
+                BLOCK_SIZE,
+                families.clone(),
+                separate_thread,
+            )?;
+            for i in 0..NUM_DOCS {
+                let mut doc = TantivyDocument::default();
+                doc.add_text(field_body, LOREM);
+                doc.add_text(field_title, format!("Doc {i}"));
+                store_writer.store(&doc, &schema)?;
+            }
+            store_writer.close()?;
+
+            let store = StoreReader::open(directory.open_read(path)?, 10)?;
+            assert_eq!(
+                store.family_layout(),
+                vec![(vec![field_title], Decompressor::None)]
+            );
+            // Only the block of the family of the title is read.
+            let doc: TantivyDocument = store.get_fields(3, &[field_title])?;
+            assert_eq!(doc.get_first(field_title).unwrap().as_str(), Some("Doc 3"));
+            assert!(doc.get_first(field_body).is_none());
+            assert_eq!(store.cache_stats().cache_misses, 1);
+
+            let doc: TantivyDocument = store.get(3)?;
+            assert_eq!(doc.get_first(field_title).unwrap().as_str(), Some("Doc 3"));
+            assert_eq!(doc.get_first(field_body).unwrap().as_str(), Some(LOREM));
+            assert_eq!(store.cache_stats().cache_misses, 2);
+
+            let alive_bitset = AliveBitSet::for_test_from_deleted_docs(&[0, 5], NUM_DOCS as u32);
+            let titles: Vec<String> = store
+                .iter::<TantivyDocument>(Some(&alive_bitset))
+                .map(|doc| {
+                    let doc = doc?;
+                    assert_eq!(doc.get_first(field_body).unwrap().as_str(), Some(LOREM));
+                    Ok(doc
+                        .get_first(field_title)
+                        .unwrap()
+                        .as_str()
+                        .unwrap()
+                        .to_string())
+                })
+                .collect::<crate::Result<_>>()?;
+            let expected_titles: Vec<String> = (0..NUM_DOCS)
+                .filter(|i| ![0, 5].contains(i))
+                .map(|i| format!("Doc {i}"))
+                .collect();
+            assert_eq!(titles, expected_titles);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_families_invalid_fields() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT | STORED);
+        schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        let family = |fields: &[&str]| crate::DocStoreFamily {
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            compression: Compressor::None,
+            blocksize: BLOCK_SIZE,
+        };
+        let create_index = |families: Vec<crate::DocStoreFamily>| {
+            Index::builder()
+                .schema(schema.clone())
+                .settings(crate::IndexSettings {
+                    docstore_families: families,
+                    ..Default::default()
+                })
+                .create_in_ram()
+        };
+        assert!(create_index(vec![family(&["body"])]).is_ok());
+        assert!(create_index(vec![family(&["unknown"])]).is_err());
+        assert!(create_index(vec![family(&["title"])]).is_err());
+        assert!(create_index(vec![family(&["body"]), family(&["body"])]).is_err());
+    }
+
     #[test]
     fn test_store_with_delete() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -318,6 +465,124 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "zstd-compression")]
+    #[test]
+    fn test_merge_with_zstd_dictionary() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text_field", TEXT | STORED);
+        let schema = schema_builder.build();
+        let mut index = Index::builder().schema(schema).create_in_ram()?;
+        index.settings_mut().docstore_compression = Compressor::Zstd(ZstdCompressor {
+            compression_level: None,
+            dictionary_size: Some(4_096),
+        });
+        index.settings_mut().docstore_blocksize = 1_024;
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for segment in 0..2 {
+                for i in 0..1_000 {
+                    index_writer
+                        .add_document(doc!(text_field=> format!("{segment} {i} {LOREM}")))?;
+                }
+                index_writer.commit()?;
+            }
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let reader = searcher.segment_reader(0);
+        let store = reader.get_store_reader(10)?;
+        assert_eq!(store.decompressor(), Decompressor::ZstdWithDictionary);
+        let mut texts: Vec<String> = store
+            .iter::<TantivyDocument>(reader.alive_bitset())
+            .map(|doc| {
+                let doc = doc?;
+                Ok(doc
+                    .get_first(text_field)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_string())
+            })
+            .collect::<crate::Result<_>>()?;
+        texts.sort();
+        let mut expected_texts: Vec<String> = (0..2)
+            .flat_map(|segment| (0..1_000).map(move |i| format!("{segment} {i} {LOREM}")))
+            .collect();
+        expected_texts.sort();
+        assert_eq!(texts, expected_texts);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_with_families() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text_field", TEXT | STORED);
+        let id_field = schema_builder.add_text_field("id", STORED);
+        let schema = schema_builder.build();
+        let mut index = Index::builder().schema(schema).create_in_ram()?;
+        index.settings_mut().docstore_blocksize = 1_024;
+        index.settings_mut().docstore_families = vec![crate::DocStoreFamily {
+            fields: vec!["id".to_string()],
+            compression: Compressor::None,
+            blocksize: 128,
+        }];
+        let add_segments = |index: &Index, segments: std::ops::Range<usize>| {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for segment in segments {
+                for i in 0..100 {
+                    index_writer.add_document(doc!(
+                        text_field => LOREM,
+                        id_field => format!("{segment} {i}"),
+                    ))?;
+                }
+                index_writer.commit()?;
+            }
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()
+        };
+        let check_ids = |index: &Index, num_segments: usize| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let reader = searcher.segment_reader(0);
+            let store = reader.get_store_reader(10)?;
+            let mut ids: Vec<String> = store
+                .iter::<TantivyDocument>(reader.alive_bitset())
+                .map(|doc| {
+                    let doc = doc?;
+                    assert_eq!(doc.get_first(text_field).unwrap().as_str(), Some(LOREM));
+                    Ok(doc
+                        .get_first(id_field)
+                        .unwrap()
+                        .as_str()
+                        .unwrap()
+                        .to_string())
+                })
+                .collect::<crate::Result<_>>()?;
+            ids.sort();
+            let mut expected_ids: Vec<String> = (0..num_segments)
+                .flat_map(|segment| (0..100).map(move |i| format!("{segment} {i}")))
+                .collect();
+            expected_ids.sort();
+            assert_eq!(ids, expected_ids);
+            Ok(())
+        };
+        // The doc stores have the same families, and are stacked.
+        add_segments(&index, 0..2)?;
+        check_ids(&index, 2)?;
+        // The families changed, the documents are split again.
+        index.settings_mut().docstore_families.clear();
+        add_segments(&index, 2..3)?;
+        check_ids(&index, 3)?;
+        let searcher = index.reader()?.searcher();
+        let store = searcher.segment_reader(0).get_store_reader(10)?;
+        assert!(store.family_layout().is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_merge_of_small_segments() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
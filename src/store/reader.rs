@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::io;
 use std::iter::Sum;
@@ -6,17 +7,22 @@ use std::ops::{AddAssign, Range};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
-use common::{BinarySerializable, OwnedBytes};
+use common::{BinarySerializable, ByteCount, OwnedBytes, VInt};
 use lru::LruCache;
 
+use super::decompressors::BlockDecompressor;
 use super::footer::DocStoreFooter;
-use super::index::SkipIndex;
+use super::index::{BlockIndex, CheckpointList, SkipIndex};
 use super::Decompressor;
+use crate::core::{MemoryBudget, MemoryReservation};
 use crate::directory::FileSlice;
 use crate::error::DataCorruption;
 use crate::fastfield::AliveBitSet;
-use crate::schema::document::{BinaryDocumentDeserializer, DocumentDeserialize};
-use crate::space_usage::StoreSpaceUsage;
+use crate::schema::document::{
+    field_value_num_bytes, BinaryDocumentDeserializer, DocumentDeserialize,
+};
+use crate::schema::Field;
+use crate::space_usage::{FieldUsage, PerFieldSpaceUsage, StoreSpaceUsage};
 use crate::store::index::Checkpoint;
 use crate::DocId;
 #[cfg(feature = "quickwit")]
@@ -24,6 +30,9 @@ use crate::Executor;
 
 pub(crate) const DOCSTORE_CACHE_CAPACITY: usize = 100;
 
+/// Maximum number of blocks per family decompressed to estimate the space usage of each field.
+const NUM_SAMPLED_BLOCKS: usize = 8;
+
 type Block = OwnedBytes;
 
 /// The format version of the document store.
@@ -31,12 +40,19 @@ type Block = OwnedBytes;
 pub(crate) enum DocStoreVersion {
     V1 = 1,
     V2 = 2,
+    /// The value of each stored field is prefixed with its length, so that fields can be
+    /// skipped without being deserialized.
+    V3 = 3,
+    /// Each compressed block ends with a CRC32 checksum, so that corrupted blocks are detected.
+    V4 = 4,
 }
 impl Display for DocStoreVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DocStoreVersion::V1 => write!(f, "V1"),
             DocStoreVersion::V2 => write!(f, "V2"),
+            DocStoreVersion::V3 => write!(f, "V3"),
+            DocStoreVersion::V4 => write!(f, "V4"),
         }
     }
 }
@@ -49,6 +65,8 @@ impl BinarySerializable for DocStoreVersion {
         Ok(match u32::deserialize(reader)? {
             1 => DocStoreVersion::V1,
             2 => DocStoreVersion::V2,
+            3 => DocStoreVersion::V3,
+            4 => DocStoreVersion::V4,
             v => {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidData,
@@ -59,29 +77,83 @@ impl BinarySerializable for DocStoreVersion {
     }
 }
 
+/// The blocks of a family of stored fields.
+struct StoreFamilyReader {
+    // Empty for the fields that do not belong to any family.
+    fields: Vec<Field>,
+    block_decompressor: BlockDecompressor,
+    block_index: BlockIndex,
+}
+
+impl StoreFamilyReader {
+    /// Get checkpoint for `DocId`. The checkpoint can be used to load a block containing the
+    /// document.
+    fn block_checkpoint(&self, doc_id: DocId) -> crate::Result<Checkpoint> {
+        self.block_index.seek(doc_id).ok_or_else(|| {
+            crate::TantivyError::InvalidArgument(format!("Failed to lookup Doc #{doc_id}."))
+        })
+    }
+}
+
 /// Reads document off tantivy's [`Store`](./index.html)
 pub struct StoreReader {
-    decompressor: Decompressor,
+    // The first family holds the fields that do not belong to any family.
+    families: Vec<StoreFamilyReader>,
     doc_store_version: DocStoreVersion,
     data: FileSlice,
-    skip_index: Arc<SkipIndex>,
     space_usage: StoreSpaceUsage,
-    cache: BlockCache,
+    cache: Arc<BlockCache>,
+    // Identifies the blocks of this store in the cache, which may be shared with other stores.
+    cache_id: usize,
 }
 
-/// The cache for decompressed blocks.
-struct BlockCache {
-    cache: Option<Mutex<LruCache<usize, Block>>>,
+/// Source of the ids telling apart the blocks of different stores in a [`BlockCache`].
+static NEXT_CACHE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An LRU cache for decompressed doc store blocks.
+///
+/// A block cache can be shared by several store readers, so that the number of blocks
+/// kept in memory is bounded across all of them. A [`Searcher`](crate::Searcher) shares
+/// one block cache across the store readers of all of its segments.
+pub struct BlockCache {
+    // Blocks are keyed by the cache id of their store and their start offset.
+    cache: Option<Mutex<LruCache<(usize, usize), Block>>>,
+    // Memory of the cached blocks, if the cache is bound by a memory budget.
+    memory_reservation: Option<MemoryReservation>,
     cache_hits: AtomicUsize,
     cache_misses: AtomicUsize,
 }
 
 impl BlockCache {
-    fn get_from_cache(&self, pos: usize) -> Option<Block> {
+    /// Creates a block cache holding at most `num_blocks` decompressed blocks.
+    ///
+    /// A capacity of 0 disables the cache.
+    pub fn new(num_blocks: usize) -> BlockCache {
+        BlockCache {
+            cache: NonZeroUsize::new(num_blocks)
+                .map(|num_blocks| Mutex::new(LruCache::new(num_blocks))),
+            memory_reservation: None,
+            cache_hits: Default::default(),
+            cache_misses: Default::default(),
+        }
+    }
+
+    /// Creates a block cache holding at most `num_blocks` decompressed blocks, whose memory is
+    /// reserved against `memory_budget`.
+    ///
+    /// Blocks which do not fit in the budget are not cached.
+    pub fn with_memory_budget(num_blocks: usize, memory_budget: &MemoryBudget) -> BlockCache {
+        BlockCache {
+            memory_reservation: Some(memory_budget.empty_reservation()),
+            ..BlockCache::new(num_blocks)
+        }
+    }
+
+    fn get_from_cache(&self, key: (usize, usize)) -> Option<Block> {
         if let Some(block) = self
             .cache
             .as_ref()
-            .and_then(|cache| cache.lock().unwrap().get(&pos).cloned())
+            .and_then(|cache| cache.lock().unwrap().get(&key).cloned())
         {
             self.cache_hits.fetch_add(1, Ordering::SeqCst);
             return Some(block);
@@ -90,13 +162,32 @@ impl BlockCache {
         None
     }
 
-    fn put_into_cache(&self, pos: usize, data: Block) {
-        if let Some(cache) = self.cache.as_ref() {
-            cache.lock().unwrap().put(pos, data);
+    /// Returns true if a block is cached, without updating the statistics of the cache.
+    fn contains(&self, key: (usize, usize)) -> bool {
+        self.cache
+            .as_ref()
+            .is_some_and(|cache| cache.lock().unwrap().contains(&key))
+    }
+
+    fn put_into_cache(&self, key: (usize, usize), data: Block) {
+        let Some(cache) = self.cache.as_ref() else {
+            return;
+        };
+        let mut cache = cache.lock().unwrap();
+        let Some(memory_reservation) = self.memory_reservation.as_ref() else {
+            cache.put(key, data);
+            return;
+        };
+        if memory_reservation.try_grow(data.len() as u64).is_err() {
+            return;
+        }
+        if let Some((_, replaced_data)) = cache.push(key, data) {
+            memory_reservation.shrink(replaced_data.len() as u64);
         }
     }
 
-    fn stats(&self) -> CacheStats {
+    /// Returns the number of cached blocks, and the hit and miss statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
         CacheStats {
             cache_hits: self.cache_hits.load(Ordering::Relaxed),
             cache_misses: self.cache_misses.load(Ordering::Relaxed),
@@ -114,10 +205,20 @@ impl BlockCache {
     fn peek_lru(&self) -> Option<usize> {
         self.cache
             .as_ref()
-            .and_then(|cache| cache.lock().unwrap().peek_lru().map(|(&k, _)| k))
+            .and_then(|cache| cache.lock().unwrap().peek_lru().map(|(&(_, pos), _)| pos))
     }
 }
 
+/// A document, or a range of lost documents, returned by
+/// [`StoreReader::iter_salvage`].
+#[derive(Debug)]
+pub enum SalvagedDoc<D> {
+    /// A document that could be read.
+    Doc(DocId, D),
+    /// A range of documents that could not be read, because their block is corrupted.
+    Lost(Range<DocId>),
+}
+
 #[derive(Debug, Default)]
 /// CacheStats for the `StoreReader`.
 pub struct CacheStats {
@@ -155,49 +256,101 @@ impl StoreReader {
     /// `cache_num_blocks` sets the number of decompressed blocks to be cached in an LRU.
     /// The size of blocks is configurable, this should be reflexted in the
     pub fn open(store_file: FileSlice, cache_num_blocks: usize) -> io::Result<StoreReader> {
-        let (footer, data_and_offset) = DocStoreFooter::extract_footer(store_file)?;
+        Self::open_with_block_cache(store_file, Arc::new(BlockCache::new(cache_num_blocks)))
+    }
 
+    /// Opens a store reader, caching its decompressed blocks in the given block cache.
+    ///
+    /// The block cache can be shared with other store readers.
+    pub fn open_with_block_cache(
+        store_file: FileSlice,
+        block_cache: Arc<BlockCache>,
+    ) -> io::Result<StoreReader> {
+        let (footer, body) = DocStoreFooter::extract_footer(store_file)?;
+
+        let (data_and_families, dictionary_file) =
+            body.split_from_end(footer.dictionary_len as usize);
+        let dictionary_bytes = dictionary_file.read_bytes()?;
+        let (data_and_offset, families_file) =
+            data_and_families.split_from_end(footer.families_len as usize);
         let (data_file, offset_index_file) = data_and_offset.split(footer.offset as usize);
         let index_data = offset_index_file.read_bytes()?;
-        let space_usage =
-            StoreSpaceUsage::new(data_file.num_bytes(), offset_index_file.num_bytes());
-        let skip_index = SkipIndex::open(index_data);
+        let space_usage = StoreSpaceUsage::new(
+            data_file.num_bytes(),
+            offset_index_file.num_bytes() + families_file.num_bytes(),
+        );
+        let mut families_bytes = families_file.read_bytes()?;
+        // The families section, if any, starts with the index of the main family.
+        let block_index = if families_bytes.is_empty() {
+            BlockIndex::SkipIndex(SkipIndex::open(index_data))
+        } else {
+            BlockIndex::CheckpointList(CheckpointList::deserialize(&mut families_bytes)?)
+        };
+        let mut families = vec![StoreFamilyReader {
+            fields: Vec::new(),
+            block_decompressor: BlockDecompressor::new(
+                footer.decompressor,
+                dictionary_bytes.as_slice(),
+            ),
+            block_index,
+        }];
+        families.extend(read_families(families_bytes)?);
         Ok(StoreReader {
-            decompressor: footer.decompressor,
+            families,
             doc_store_version: footer.doc_store_version,
             data: data_file,
-            cache: BlockCache {
-                cache: NonZeroUsize::new(cache_num_blocks)
-                    .map(|cache_num_blocks| Mutex::new(LruCache::new(cache_num_blocks))),
-                cache_hits: Default::default(),
-                cache_misses: Default::default(),
-            },
-            skip_index: Arc::new(skip_index),
+            cache: block_cache,
+            cache_id: NEXT_CACHE_ID.fetch_add(1, Ordering::Relaxed),
             space_usage,
         })
     }
 
     pub(crate) fn block_checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
-        self.skip_index.checkpoints()
+        self.family_block_checkpoints(0)
     }
 
-    pub(crate) fn decompressor(&self) -> Decompressor {
-        self.decompressor
+    /// Returns the checkpoints of the family with the given ordinal, the ordinal 0 being
+    /// used for the fields that do not belong to any family.
+    pub(crate) fn family_block_checkpoints(
+        &self,
+        family_ord: usize,
+    ) -> impl Iterator<Item = Checkpoint> + '_ {
+        self.families[family_ord].block_index.checkpoints()
     }
 
-    /// Returns the cache hit and miss statistics of the store reader.
-    pub(crate) fn cache_stats(&self) -> CacheStats {
-        self.cache.stats()
+    /// Returns the fields and the decompressor of each family.
+    pub(crate) fn family_layout(&self) -> Vec<(Vec<Field>, Decompressor)> {
+        self.families[1..]
+            .iter()
+            .map(|family| {
+                (
+                    family.fields.clone(),
+                    family.block_decompressor.decompressor(),
+                )
+            })
+            .collect()
     }
 
-    /// Get checkpoint for `DocId`. The checkpoint can be used to load a block containing the
-    /// document.
+    /// Returns true if the blocks of any family are compressed with a dictionary.
+    pub(crate) fn has_dictionary(&self) -> bool {
+        self.families
+            .iter()
+            .any(|family| family.block_decompressor.decompressor().has_dictionary())
+    }
+
+    pub(crate) fn doc_store_version(&self) -> DocStoreVersion {
+        self.doc_store_version
+    }
+
+    pub(crate) fn decompressor(&self) -> Decompressor {
+        self.families[0].block_decompressor.decompressor()
+    }
+
+    /// Returns the cache hit and miss statistics of the block cache of the store reader.
     ///
-    /// Advanced API. In most cases use [`get`](Self::get).
-    fn block_checkpoint(&self, doc_id: DocId) -> crate::Result<Checkpoint> {
-        self.skip_index.seek(doc_id).ok_or_else(|| {
-            crate::TantivyError::InvalidArgument(format!("Failed to lookup Doc #{doc_id}."))
-        })
+    /// If the block cache is shared, the statistics include the other store readers.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
     }
 
     pub(crate) fn block_data(&self) -> io::Result<OwnedBytes> {
@@ -208,18 +361,19 @@ impl StoreReader {
         self.data.slice(checkpoint.byte_range.clone()).read_bytes()
     }
 
-    /// Loads and decompresses a block.
+    /// Loads and decompresses a block of a family.
     ///
     /// Advanced API. In most cases use [`get`](Self::get).
-    fn read_block(&self, checkpoint: &Checkpoint) -> io::Result<Block> {
-        let cache_key = checkpoint.byte_range.start;
+    fn read_block(&self, family: &StoreFamilyReader, checkpoint: &Checkpoint) -> io::Result<Block> {
+        let cache_key = (self.cache_id, checkpoint.byte_range.start);
         if let Some(block) = self.cache.get_from_cache(cache_key) {
             return Ok(block);
         }
 
         let compressed_block = self.get_compressed_block(checkpoint)?;
-        let decompressed_block =
-            OwnedBytes::new(self.decompressor.decompress(compressed_block.as_ref())?);
+        let decompressed_block = OwnedBytes::new(family.block_decompressor.decompress(
+            check_block_checksum(compressed_block.as_slice(), self.doc_store_version)?,
+        )?);
 
         self.cache
             .put_into_cache(cache_key, decompressed_block.clone());
@@ -227,6 +381,50 @@ impl StoreReader {
         Ok(decompressed_block)
     }
 
+    /// Loads the blocks containing some documents into the block cache.
+    ///
+    /// The blocks that are not cached yet are read all at once, which lets directories
+    /// able to batch reads, like the `IoUringDirectory`, fetch them concurrently. This is
+    /// typically called with the documents selected by a collector, before reading them.
+    ///
+    /// Blocks beyond the capacity of the cache evict the least recently used ones, so
+    /// prefetching more blocks than the cache can hold is wasteful.
+    pub fn prefetch(&self, doc_ids: &[DocId]) -> crate::Result<()> {
+        if self.cache.cache.is_none() {
+            return Ok(());
+        }
+        let mut missing_blocks: Vec<(&StoreFamilyReader, Checkpoint)> = Vec::new();
+        let mut missing_block_starts = HashSet::new();
+        for family in &self.families {
+            for &doc_id in doc_ids {
+                let checkpoint = family.block_checkpoint(doc_id)?;
+                let block_start = checkpoint.byte_range.start;
+                if !self.cache.contains((self.cache_id, block_start))
+                    && missing_block_starts.insert(block_start)
+                {
+                    missing_blocks.push((family, checkpoint));
+                }
+            }
+        }
+        let byte_ranges: Vec<Range<usize>> = missing_blocks
+            .iter()
+            .map(|(_, checkpoint)| checkpoint.byte_range.clone())
+            .collect();
+        let compressed_blocks = self.data.read_bytes_slices(&byte_ranges)?;
+        for ((family, checkpoint), compressed_block) in
+            missing_blocks.into_iter().zip(compressed_blocks)
+        {
+            let decompressed_block = OwnedBytes::new(family.block_decompressor.decompress(
+                check_block_checksum(compressed_block.as_slice(), self.doc_store_version)?,
+            )?);
+            self.cache.put_into_cache(
+                (self.cache_id, checkpoint.byte_range.start),
+                decompressed_block,
+            );
+        }
+        Ok(())
+    }
+
     /// Reads a given document.
     ///
     /// Calling `.get(doc)` is relatively costly as it requires
@@ -245,6 +443,44 @@ impl StoreReader {
         D::deserialize(deserializer).map_err(crate::TantivyError::from)
     }
 
+    /// Reads the values of the given fields of a document.
+    ///
+    /// Only the values of the requested fields are deserialized, the others are
+    /// skipped over. The returned document does not contain any other field.
+    pub fn get_fields<D: DocumentDeserialize>(
+        &self,
+        doc_id: DocId,
+        fields: &[Field],
+    ) -> crate::Result<D> {
+        // Only the families containing some of the requested fields are read.
+        let family_docs = self
+            .families
+            .iter()
+            .enumerate()
+            .filter(|(family_ord, family)| {
+                if *family_ord == 0 {
+                    fields.iter().any(|field| {
+                        self.families[1..]
+                            .iter()
+                            .all(|family| !family.fields.contains(field))
+                    })
+                } else {
+                    fields.iter().any(|field| family.fields.contains(field))
+                }
+            })
+            .map(|(_, family)| self.get_family_document_bytes(family, doc_id))
+            .collect::<crate::Result<Vec<OwnedBytes>>>()?;
+        let mut doc_bytes = merge_family_documents(family_docs)?;
+
+        let deserializer = BinaryDocumentDeserializer::from_reader_with_projection(
+            &mut doc_bytes,
+            self.doc_store_version,
+            fields,
+        )
+        .map_err(crate::TantivyError::from)?;
+        D::deserialize(deserializer).map_err(crate::TantivyError::from)
+    }
+
     /// Returns raw bytes of a given document.
     ///
     /// Calling `.get(doc)` is relatively costly as it requires
@@ -252,8 +488,21 @@ impl StoreReader {
     /// so accessing docs from the same compressed block should be faster.
     /// For that reason a store reader should be kept and reused.
     pub fn get_document_bytes(&self, doc_id: DocId) -> crate::Result<OwnedBytes> {
-        let checkpoint = self.block_checkpoint(doc_id)?;
-        let block = self.read_block(&checkpoint)?;
+        let family_docs = self
+            .families
+            .iter()
+            .map(|family| self.get_family_document_bytes(family, doc_id))
+            .collect::<crate::Result<Vec<OwnedBytes>>>()?;
+        merge_family_documents(family_docs)
+    }
+
+    fn get_family_document_bytes(
+        &self,
+        family: &StoreFamilyReader,
+        doc_id: DocId,
+    ) -> crate::Result<OwnedBytes> {
+        let checkpoint = family.block_checkpoint(doc_id)?;
+        let block = self.read_block(family, &checkpoint)?;
         Self::get_document_bytes_from_block(block, doc_id, &checkpoint)
     }
 
@@ -288,6 +537,57 @@ impl StoreReader {
         })
     }
 
+    /// Iterator over all Documents of a damaged doc store, in recovery mode.
+    ///
+    /// Contrary to [`iter`](Self::iter), corrupted blocks do not fail the iteration: their
+    /// documents are skipped and reported as [`SalvagedDoc::Lost`] doc ranges, so that the
+    /// documents which are still readable can be recovered.
+    /// The `alive_bitset` has to be forwarded from the `SegmentReader` or the results may be wrong.
+    /// Deleted documents are never reported as lost.
+    pub fn iter_salvage<'a: 'b, 'b, D: DocumentDeserialize>(
+        &'b self,
+        alive_bitset: Option<&'a AliveBitSet>,
+    ) -> impl Iterator<Item = SalvagedDoc<D>> + 'b {
+        let last_doc_id = self
+            .block_checkpoints()
+            .last()
+            .map(|checkpoint| checkpoint.doc_range.end)
+            .unwrap_or(0);
+        let alive_doc_ids = (0..last_doc_id)
+            .filter(move |doc_id| alive_bitset.map_or(true, |bitset| bitset.is_alive(*doc_id)));
+        let mut docs = alive_doc_ids
+            .zip(self.iter_raw(alive_bitset))
+            .map(move |(doc_id, doc_bytes_res)| {
+                let doc_res = doc_bytes_res.and_then(|mut doc_bytes| {
+                    let deserializer = BinaryDocumentDeserializer::from_reader(
+                        &mut doc_bytes,
+                        self.doc_store_version,
+                    )
+                    .map_err(crate::TantivyError::from)?;
+                    D::deserialize(deserializer).map_err(crate::TantivyError::from)
+                });
+                (doc_id, doc_res)
+            })
+            .peekable();
+        std::iter::from_fn(move || {
+            let (doc_id, doc_res) = docs.next()?;
+            if let Ok(doc) = doc_res {
+                return Some(SalvagedDoc::Doc(doc_id, doc));
+            }
+            // Consecutive lost documents are reported as a single range.
+            let mut lost_doc_range = doc_id..doc_id + 1;
+            while docs
+                .next_if(|(next_doc_id, next_doc_res)| {
+                    *next_doc_id == lost_doc_range.end && next_doc_res.is_err()
+                })
+                .is_some()
+            {
+                lost_doc_range.end += 1;
+            }
+            Some(SalvagedDoc::Lost(lost_doc_range))
+        })
+    }
+
     /// Iterator over all raw Documents in their order as they are stored in the doc store.
     /// Use this, if you want to extract all Documents from the doc store.
     /// The `alive_bitset` has to be forwarded from the `SegmentReader` or the results may be wrong.
@@ -295,16 +595,42 @@ impl StoreReader {
         &'b self,
         alive_bitset: Option<&'a AliveBitSet>,
     ) -> impl Iterator<Item = crate::Result<OwnedBytes>> + 'b {
-        let last_doc_id = self
-            .block_checkpoints()
+        let mut family_iters: Vec<_> = self
+            .families
+            .iter()
+            .map(|family| self.iter_family_raw(family, alive_bitset))
+            .collect();
+        std::iter::from_fn(move || {
+            let family_docs: Vec<crate::Result<OwnedBytes>> = family_iters
+                .iter_mut()
+                .map(Iterator::next)
+                .collect::<Option<_>>()?;
+            Some(
+                family_docs
+                    .into_iter()
+                    .collect::<crate::Result<Vec<OwnedBytes>>>()
+                    .and_then(merge_family_documents),
+            )
+        })
+    }
+
+    /// Iterator over the raw documents of a family.
+    fn iter_family_raw<'a: 'b, 'b>(
+        &'b self,
+        family: &'b StoreFamilyReader,
+        alive_bitset: Option<&'a AliveBitSet>,
+    ) -> impl Iterator<Item = crate::Result<OwnedBytes>> + 'b {
+        let last_doc_id = family
+            .block_index
+            .checkpoints()
             .last()
             .map(|checkpoint| checkpoint.doc_range.end)
             .unwrap_or(0);
-        let mut checkpoint_block_iter = self.block_checkpoints();
+        let mut checkpoint_block_iter = family.block_index.checkpoints();
         let mut curr_checkpoint = checkpoint_block_iter.next();
         let mut curr_block = curr_checkpoint
             .as_ref()
-            .map(|checkpoint| self.read_block(checkpoint).map_err(|e| e.kind())); // map error in order to enable cloning
+            .map(|checkpoint| self.read_block(family, checkpoint).map_err(|e| e.kind())); // map error in order to enable cloning
         let mut doc_pos = 0;
         (0..last_doc_id)
             .filter_map(move |doc_id| {
@@ -314,9 +640,9 @@ impl StoreReader {
                 // check move to next checkpoint
                 if doc_id >= curr_checkpoint.as_ref().unwrap().doc_range.end {
                     curr_checkpoint = checkpoint_block_iter.next();
-                    curr_block = curr_checkpoint
-                        .as_ref()
-                        .map(|checkpoint| self.read_block(checkpoint).map_err(|e| e.kind()));
+                    curr_block = curr_checkpoint.as_ref().map(|checkpoint| {
+                        self.read_block(family, checkpoint).map_err(|e| e.kind())
+                    });
                     doc_pos = 0;
                 }
 
@@ -350,6 +676,143 @@ impl StoreReader {
     pub fn space_usage(&self) -> StoreSpaceUsage {
         self.space_usage.clone()
     }
+
+    /// Estimates the space usage of each stored field.
+    ///
+    /// Fields are compressed together in blocks, so their space usage is not known exactly.
+    /// Up to `NUM_SAMPLED_BLOCKS` blocks of each family are decompressed, and the compressed
+    /// size of the family is split between its fields pro rata of their uncompressed size in
+    /// these blocks.
+    pub fn fields_space_usage(&self) -> io::Result<PerFieldSpaceUsage> {
+        let mut num_bytes_per_field: HashMap<Field, u64> = HashMap::new();
+        for family in &self.families {
+            let checkpoints: Vec<Checkpoint> = family.block_index.checkpoints().collect();
+            let family_num_bytes: u64 = checkpoints
+                .iter()
+                .map(|checkpoint| checkpoint.byte_range.len() as u64)
+                .sum();
+            let step = checkpoints.len().div_ceil(NUM_SAMPLED_BLOCKS).max(1);
+            let mut sampled_num_bytes_per_field: HashMap<Field, u64> = HashMap::new();
+            for checkpoint in checkpoints.iter().step_by(step) {
+                let block = self.read_block(family, checkpoint)?;
+                for doc_pos in 0..checkpoint.doc_range.len() as u32 {
+                    let doc_range = block_read_index(block.as_slice(), doc_pos)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                    let field_value_num_bytes =
+                        field_value_num_bytes(&block.as_slice()[doc_range], self.doc_store_version)
+                            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                    for (field, num_bytes) in field_value_num_bytes {
+                        *sampled_num_bytes_per_field.entry(field).or_default() += num_bytes as u64;
+                    }
+                }
+            }
+            let sampled_num_bytes: u64 = sampled_num_bytes_per_field.values().sum();
+            if sampled_num_bytes == 0 {
+                continue;
+            }
+            for (field, num_bytes) in sampled_num_bytes_per_field {
+                let estimated_num_bytes = u128::from(family_num_bytes) * u128::from(num_bytes)
+                    / u128::from(sampled_num_bytes);
+                *num_bytes_per_field.entry(field).or_default() += estimated_num_bytes as u64;
+            }
+        }
+        let field_usages = num_bytes_per_field
+            .into_iter()
+            .map(|(field, num_bytes)| {
+                let mut field_usage = FieldUsage::empty(field);
+                field_usage.add_field_idx(0, ByteCount::from(num_bytes));
+                field_usage
+            })
+            .collect();
+        Ok(PerFieldSpaceUsage::new(field_usages))
+    }
+}
+
+/// Reads the families of a doc store, following the index of the main family in the
+/// families section.
+///
+/// They are serialized as their number, followed for each family by its fields, the id of its
+/// decompressor, its checkpoint list and its dictionary, prefixed by its length.
+fn read_families(mut bytes: OwnedBytes) -> io::Result<Vec<StoreFamilyReader>> {
+    if bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let num_families = VInt::deserialize_u64(&mut bytes)? as usize;
+    let mut families = Vec::with_capacity(num_families);
+    for _ in 0..num_families {
+        let num_fields = VInt::deserialize_u64(&mut bytes)? as usize;
+        let fields = (0..num_fields)
+            .map(|_| Field::deserialize(&mut bytes))
+            .collect::<io::Result<Vec<Field>>>()?;
+        let decompressor = Decompressor::from_id(u8::deserialize(&mut bytes)?);
+        let checkpoint_list = CheckpointList::deserialize(&mut bytes)?;
+        let dictionary_bytes = read_len_prefixed(&mut bytes)?;
+        families.push(StoreFamilyReader {
+            fields,
+            block_decompressor: BlockDecompressor::new(decompressor, dictionary_bytes.as_slice()),
+            block_index: BlockIndex::CheckpointList(checkpoint_list),
+        });
+    }
+    Ok(families)
+}
+
+fn read_len_prefixed(bytes: &mut OwnedBytes) -> io::Result<OwnedBytes> {
+    let len = VInt::deserialize_u64(bytes)? as usize;
+    if bytes.len() < len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "doc store families section is truncated",
+        ));
+    }
+    let prefixed_bytes = bytes.slice(0..len);
+    bytes.advance(len);
+    Ok(prefixed_bytes)
+}
+
+/// Checks the checksum which ends the compressed blocks of recent doc stores, and returns the
+/// compressed block without it.
+fn check_block_checksum(
+    compressed_block: &[u8],
+    doc_store_version: DocStoreVersion,
+) -> io::Result<&[u8]> {
+    if doc_store_version < DocStoreVersion::V4 {
+        return Ok(compressed_block);
+    }
+    let checksum_start = compressed_block
+        .len()
+        .checked_sub(std::mem::size_of::<u32>())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "doc store block is too short to hold a checksum, data corruption",
+            )
+        })?;
+    let (compressed_block, checksum) = compressed_block.split_at(checksum_start);
+    if crc32fast::hash(compressed_block).to_le_bytes() != checksum {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "doc store block checksum mismatch, data corruption",
+        ));
+    }
+    Ok(compressed_block)
+}
+
+/// Merges the entries of a document in the different families into a single document.
+fn merge_family_documents(mut family_docs: Vec<OwnedBytes>) -> crate::Result<OwnedBytes> {
+    if family_docs.len() == 1 {
+        return Ok(family_docs.pop().unwrap());
+    }
+    let mut num_field_values = 0;
+    for family_doc in &mut family_docs {
+        num_field_values += VInt::deserialize_u64(family_doc)?;
+    }
+    let mut doc_bytes =
+        Vec::with_capacity(family_docs.iter().map(OwnedBytes::len).sum::<usize>() + 10);
+    VInt(num_field_values).serialize(&mut doc_bytes)?;
+    for family_doc in &family_docs {
+        doc_bytes.extend_from_slice(family_doc.as_slice());
+    }
+    Ok(OwnedBytes::new(doc_bytes))
 }
 
 fn block_read_index(block: &[u8], doc_pos: u32) -> crate::Result<Range<usize>> {
@@ -381,27 +844,50 @@ impl StoreReader {
     /// In most cases use [`get_async`](Self::get_async)
     ///
     /// Loads and decompresses a block asynchronously.
+    ///
+    /// The block is read with the async API of the file handle of the doc store,
+    /// and decompressed on the `executor`.
+    /// If the file handle does not support async reads, the block is read on the
+    /// `executor` too, so that the calling task is not blocked on IO.
     async fn read_block_async(
         &self,
+        family: &StoreFamilyReader,
         checkpoint: &Checkpoint,
         executor: &Executor,
     ) -> io::Result<Block> {
-        let cache_key = checkpoint.byte_range.start;
-        if let Some(block) = self.cache.get_from_cache(checkpoint.byte_range.start) {
+        let cache_key = (self.cache_id, checkpoint.byte_range.start);
+        if let Some(block) = self.cache.get_from_cache(cache_key) {
             return Ok(block);
         }
 
-        let compressed_block = self
-            .data
-            .slice(checkpoint.byte_range.clone())
-            .read_bytes_async()
-            .await?;
-
-        let decompressor = self.decompressor;
-        let maybe_decompressed_block = executor
-            .spawn_blocking(move || decompressor.decompress(compressed_block.as_ref()))
-            .await
-            .expect("decompression panicked");
+        let block_slice = self.data.slice(checkpoint.byte_range.clone());
+        let block_decompressor = family.block_decompressor.clone();
+        let doc_store_version = self.doc_store_version;
+        let maybe_decompressed_block = match block_slice.read_bytes_async().await {
+            Ok(compressed_block) => {
+                executor
+                    .spawn_blocking(move || {
+                        block_decompressor.decompress(check_block_checksum(
+                            compressed_block.as_slice(),
+                            doc_store_version,
+                        )?)
+                    })
+                    .await
+            }
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => {
+                executor
+                    .spawn_blocking(move || {
+                        let compressed_block = block_slice.read_bytes()?;
+                        block_decompressor.decompress(check_block_checksum(
+                            compressed_block.as_slice(),
+                            doc_store_version,
+                        )?)
+                    })
+                    .await
+            }
+            Err(err) => return Err(err),
+        }
+        .expect("decompression panicked");
         let decompressed_block = OwnedBytes::new(maybe_decompressed_block?);
 
         self.cache
@@ -416,9 +902,17 @@ impl StoreReader {
         doc_id: DocId,
         executor: &Executor,
     ) -> crate::Result<OwnedBytes> {
-        let checkpoint = self.block_checkpoint(doc_id)?;
-        let block = self.read_block_async(&checkpoint, executor).await?;
-        Self::get_document_bytes_from_block(block, doc_id, &checkpoint)
+        let mut family_docs = Vec::with_capacity(self.families.len());
+        for family in &self.families {
+            let checkpoint = family.block_checkpoint(doc_id)?;
+            let block = self.read_block_async(family, &checkpoint, executor).await?;
+            family_docs.push(Self::get_document_bytes_from_block(
+                block,
+                doc_id,
+                &checkpoint,
+            )?);
+        }
+        merge_family_documents(family_docs)
     }
 
     /// Fetches a document asynchronously. Async version of [`get`](Self::get).
@@ -456,6 +950,8 @@ mod tests {
     #[test]
     fn test_doc_store_version_ord() {
         assert!(DocStoreVersion::V1 < DocStoreVersion::V2);
+        assert!(DocStoreVersion::V2 < DocStoreVersion::V3);
+        assert!(DocStoreVersion::V3 < DocStoreVersion::V4);
     }
 
     #[test]
@@ -497,8 +993,165 @@ mod tests {
         assert_eq!(store.cache_stats().cache_hits, 1);
         assert_eq!(store.cache_stats().cache_misses, 2);
 
-        assert_eq!(store.cache.peek_lru(), Some(11207));
+        assert_eq!(store.cache.peek_lru(), Some(11393));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_prefetch() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let path = Path::new("store");
+        let writer = directory.open_write(path)?;
+        let schema = write_lorem_ipsum_store(writer, 500, Compressor::default(), BLOCK_SIZE, true);
+        let title = schema.get_field("title").unwrap();
+        let store_file = directory.open_read(path)?;
+        let store = StoreReader::open(store_file, DOCSTORE_CACHE_CAPACITY)?;
+
+        store.prefetch(&[0, 1, 499])?;
+        assert_eq!(store.cache.len(), 2);
+        assert_eq!(store.cache_stats().cache_misses, 0);
+        let doc = store.get(499)?;
+        assert_eq!(get_text_field(&doc, &title), Some("Doc 499"));
+        assert_eq!(store.cache_stats().cache_hits, 1);
+        assert_eq!(store.cache_stats().cache_misses, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_shared_block_cache() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let block_cache = Arc::new(BlockCache::new(2));
+        let mut stores = Vec::new();
+        for (path, num_docs) in [("store1", 10), ("store2", 20)] {
+            let path = Path::new(path);
+            let writer = directory.open_write(path)?;
+            write_lorem_ipsum_store(writer, num_docs, Compressor::default(), BLOCK_SIZE, false);
+            let store_file = directory.open_read(path)?;
+            stores.push(StoreReader::open_with_block_cache(
+                store_file,
+                block_cache.clone(),
+            )?);
+        }
+        let title = Field::from_field_id(1);
+
+        // Both stores have a single block starting at offset 0, which must not be confused.
+        let doc = stores[0].get(3)?;
+        assert_eq!(get_text_field(&doc, &title), Some("Doc 3"));
+        let doc = stores[1].get(13)?;
+        assert_eq!(get_text_field(&doc, &title), Some("Doc 13"));
+        let doc = stores[0].get(4)?;
+        assert_eq!(get_text_field(&doc, &title), Some("Doc 4"));
+
+        let cache_stats = block_cache.stats();
+        assert_eq!(cache_stats.num_entries, 2);
+        assert_eq!(cache_stats.cache_hits, 1);
+        assert_eq!(cache_stats.cache_misses, 2);
+        assert_eq!(stores[1].cache_stats().cache_hits, 1);
+        Ok(())
+    }
 
+    #[test]
+    fn test_store_salvage_corrupted_block() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let path = Path::new("store");
+        let writer = directory.open_write(path)?;
+        let schema = write_lorem_ipsum_store(writer, 500, Compressor::default(), BLOCK_SIZE, true);
+        let title = schema.get_field("title").unwrap();
+        let store_file = directory.open_read(path)?;
+        let store = StoreReader::open(store_file.clone(), 0)?;
+        let checkpoints: Vec<Checkpoint> = store.block_checkpoints().collect();
+        assert!(checkpoints.len() > 2);
+        let corrupted_checkpoint = checkpoints[1].clone();
+
+        // Flip a byte in the middle of the second block.
+        let mut store_bytes = store_file.read_bytes()?.as_slice().to_vec();
+        let byte_range = corrupted_checkpoint.byte_range.clone();
+        store_bytes[(byte_range.start + byte_range.end) / 2] ^= 1;
+        let store = StoreReader::open(FileSlice::from(store_bytes), 0)?;
+
+        let err = store
+            .get::<TantivyDocument>(corrupted_checkpoint.doc_range.start)
+            .unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+        assert!(store.get::<TantivyDocument>(0).is_ok());
+        assert!(store
+            .iter::<TantivyDocument>(None)
+            .any(|doc_res| doc_res.is_err()));
+
+        // The documents of the corrupted block are skipped, except the deleted ones.
+        let deleted_doc = corrupted_checkpoint.doc_range.start;
+        let alive_bitset = AliveBitSet::for_test_from_deleted_docs(&[deleted_doc], 500);
+        let mut num_docs = 0;
+        let mut lost_doc_ranges = Vec::new();
+        for salvaged_doc in store.iter_salvage::<TantivyDocument>(Some(&alive_bitset)) {
+            match salvaged_doc {
+                SalvagedDoc::Doc(doc_id, doc) => {
+                    assert_eq!(
+                        get_text_field(&doc, &title),
+                        Some(format!("Doc {doc_id}").as_str())
+                    );
+                    num_docs += 1;
+                }
+                SalvagedDoc::Lost(doc_range) => lost_doc_ranges.push(doc_range),
+            }
+        }
+        assert_eq!(
+            lost_doc_ranges,
+            vec![deleted_doc + 1..corrupted_checkpoint.doc_range.end]
+        );
+        assert_eq!(num_docs, 500 - corrupted_checkpoint.doc_range.len());
+        Ok(())
+    }
+
+    /// A file handle which does not support async reads.
+    #[cfg(feature = "quickwit")]
+    #[derive(Debug)]
+    struct SyncFileHandle(OwnedBytes);
+
+    #[cfg(feature = "quickwit")]
+    impl crate::directory::FileHandle for SyncFileHandle {
+        fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+            Ok(self.0.slice(range))
+        }
+    }
+
+    #[cfg(feature = "quickwit")]
+    impl common::HasLen for SyncFileHandle {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[cfg(feature = "quickwit")]
+    #[test]
+    fn test_store_get_async() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let path = Path::new("store");
+        let writer = directory.open_write(path)?;
+        let schema = write_lorem_ipsum_store(writer, 500, Compressor::default(), BLOCK_SIZE, true);
+        let title = schema.get_field("title").unwrap();
+        let store_bytes = directory.open_read(path)?.read_bytes()?;
+        let store_files = [
+            FileSlice::new(Arc::new(store_bytes.clone())),
+            FileSlice::new(Arc::new(SyncFileHandle(store_bytes))),
+        ];
+        for executor in [
+            Executor::single_thread(),
+            Executor::multi_thread(2, "store-test")?,
+        ] {
+            for store_file in store_files.clone() {
+                let store = StoreReader::open(store_file, 10)?;
+                let doc: TantivyDocument =
+                    futures::executor::block_on(store.get_async(499, &executor))?;
+                assert_eq!(get_text_field(&doc, &title), Some("Doc 499"));
+                // The second read hits the cache.
+                let doc: TantivyDocument =
+                    futures::executor::block_on(store.get_async(498, &executor))?;
+                assert_eq!(get_text_field(&doc, &title), Some("Doc 498"));
+                assert_eq!(store.cache_stats().cache_hits, 1);
+            }
+        }
         Ok(())
     }
 }
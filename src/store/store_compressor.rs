@@ -3,12 +3,14 @@ use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 use std::thread::JoinHandle;
 use std::{io, thread};
 
-use common::{BinarySerializable, CountingWriter, TerminatingWrite};
+use common::{BinarySerializable, CountingWriter, TerminatingWrite, VInt};
 
+use super::writer::StoreFamily;
 use super::DOC_STORE_VERSION;
 use crate::directory::WritePtr;
+use crate::schema::Field;
 use crate::store::footer::DocStoreFooter;
-use crate::store::index::{Checkpoint, SkipIndexBuilder};
+use crate::store::index::{Checkpoint, CheckpointList, SkipIndexBuilder};
 use crate::store::{Compressor, Decompressor, StoreReader};
 use crate::DocId;
 
@@ -22,8 +24,13 @@ enum BlockCompressorVariants {
 }
 
 impl BlockCompressor {
-    pub fn new(compressor: Compressor, wrt: WritePtr, dedicated_thread: bool) -> io::Result<Self> {
-        let block_compressor_impl = BlockCompressorImpl::new(compressor, wrt);
+    pub fn new(
+        compressor: Compressor,
+        families: &[StoreFamily],
+        wrt: WritePtr,
+        dedicated_thread: bool,
+    ) -> io::Result<Self> {
+        let block_compressor_impl = BlockCompressorImpl::new(compressor, families, wrt);
         if dedicated_thread {
             let dedicated_thread_compressor =
                 DedicatedThreadBlockCompressorImpl::new(block_compressor_impl)?;
@@ -37,18 +44,25 @@ impl BlockCompressor {
         }
     }
 
+    /// Compresses and writes a block of the family with the given ordinal.
+    ///
+    /// The family ordinal 0 is used for the fields that do not belong to any family.
     pub fn compress_block_and_write(
         &mut self,
+        family_ord: usize,
         bytes: &[u8],
         num_docs_in_block: u32,
     ) -> io::Result<()> {
         match &mut self.0 {
             BlockCompressorVariants::SameThread(block_compressor) => {
-                block_compressor.compress_block_and_write(bytes, num_docs_in_block)?;
+                block_compressor.compress_block_and_write(family_ord, bytes, num_docs_in_block)?;
             }
             BlockCompressorVariants::DedicatedThread(different_thread_block_compressor) => {
-                different_thread_block_compressor
-                    .compress_block_and_write(bytes, num_docs_in_block)?;
+                different_thread_block_compressor.compress_block_and_write(
+                    family_ord,
+                    bytes,
+                    num_docs_in_block,
+                )?;
             }
         }
         Ok(())
@@ -77,87 +91,392 @@ impl BlockCompressor {
     }
 }
 
-struct BlockCompressorImpl {
+/// Number of bytes of documents sampled to train a dictionary, per byte of dictionary.
+///
+/// zstd recommends training on a sample about 100 times larger than the dictionary.
+#[cfg(feature = "zstd-compression")]
+const DICTIONARY_SAMPLE_SIZE_FACTOR: usize = 100;
+
+/// The dictionary of a compressor that uses one.
+#[cfg(feature = "zstd-compression")]
+enum DictionaryState {
+    /// Blocks are held back until enough documents have been seen to train the dictionary.
+    Sampling {
+        dictionary_size: usize,
+        blocks: Vec<(Vec<u8>, u32)>,
+        num_bytes: usize,
+    },
+    /// The dictionary has been trained. It is empty if zstd could not train one.
+    Trained {
+        dictionary: Vec<u8>,
+        encoder_dictionary: Option<zstd::dict::EncoderDictionary<'static>>,
+    },
+}
+
+/// Builds the index of the blocks of a family, see
+/// [`BlockIndex`](crate::store::index::BlockIndex).
+enum BlockIndexBuilder {
+    SkipIndex(SkipIndexBuilder),
+    CheckpointList(CheckpointList),
+}
+
+impl BlockIndexBuilder {
+    fn insert(&mut self, checkpoint: Checkpoint) {
+        match self {
+            BlockIndexBuilder::SkipIndex(skip_index_builder) => {
+                skip_index_builder.insert(checkpoint)
+            }
+            BlockIndexBuilder::CheckpointList(checkpoint_list) => {
+                checkpoint_list.insert(checkpoint)
+            }
+        }
+    }
+
+    fn serialize_into<W: Write>(self, output: &mut W) -> io::Result<()> {
+        match self {
+            BlockIndexBuilder::SkipIndex(skip_index_builder) => {
+                skip_index_builder.serialize_into(output)
+            }
+            BlockIndexBuilder::CheckpointList(checkpoint_list) => {
+                checkpoint_list.serialize_into(output)
+            }
+        }
+    }
+}
+
+/// The state of the blocks of a family of stored fields.
+struct FamilyCompressor {
+    // Empty for the fields that do not belong to any family.
+    fields: Vec<Field>,
     compressor: Compressor,
     first_doc_in_block: DocId,
-    offset_index_writer: SkipIndexBuilder,
+    block_index: BlockIndexBuilder,
+    #[cfg(feature = "zstd-compression")]
+    dictionary_state: Option<DictionaryState>,
+}
+
+impl FamilyCompressor {
+    fn new(fields: Vec<Field>, compressor: Compressor, block_index: BlockIndexBuilder) -> Self {
+        Self {
+            fields,
+            compressor,
+            first_doc_in_block: 0,
+            block_index,
+            #[cfg(feature = "zstd-compression")]
+            dictionary_state: compressor.dictionary_size().map(|dictionary_size| {
+                DictionaryState::Sampling {
+                    dictionary_size,
+                    blocks: Vec::new(),
+                    num_bytes: 0,
+                }
+            }),
+        }
+    }
+
+    fn register_checkpoint(&mut self, checkpoint: Checkpoint) {
+        self.first_doc_in_block = checkpoint.doc_range.end;
+        self.block_index.insert(checkpoint);
+    }
+
+    fn has_dictionary(&self) -> bool {
+        #[cfg(feature = "zstd-compression")]
+        return self.dictionary_state.is_some();
+        #[cfg(not(feature = "zstd-compression"))]
+        return false;
+    }
+
+    /// Returns the trained dictionary, which is empty if the family does not use one.
+    fn dictionary(&self) -> &[u8] {
+        #[cfg(feature = "zstd-compression")]
+        if let Some(DictionaryState::Trained { dictionary, .. }) = &self.dictionary_state {
+            return dictionary;
+        }
+        &[]
+    }
+}
+
+struct BlockCompressorImpl {
+    // The first family holds the fields that do not belong to any family.
+    families: Vec<FamilyCompressor>,
     intermediary_buffer: Vec<u8>,
     writer: CountingWriter<WritePtr>,
 }
 
 impl BlockCompressorImpl {
-    fn new(compressor: Compressor, writer: WritePtr) -> Self {
+    fn new(compressor: Compressor, families: &[StoreFamily], writer: WritePtr) -> Self {
+        // The blocks of the different families are interleaved, so that they cannot be
+        // indexed with a skip index.
+        let block_index = || {
+            if families.is_empty() {
+                BlockIndexBuilder::SkipIndex(SkipIndexBuilder::new())
+            } else {
+                BlockIndexBuilder::CheckpointList(CheckpointList::default())
+            }
+        };
+        let families =
+            std::iter::once(FamilyCompressor::new(Vec::new(), compressor, block_index()))
+                .chain(families.iter().map(|family| {
+                    FamilyCompressor::new(family.fields.clone(), family.compressor, block_index())
+                }))
+                .collect();
         Self {
-            compressor,
-            first_doc_in_block: 0,
-            offset_index_writer: SkipIndexBuilder::new(),
+            families,
             intermediary_buffer: Vec::new(),
             writer: CountingWriter::wrap(writer),
         }
     }
 
-    fn compress_block_and_write(&mut self, data: &[u8], num_docs_in_block: u32) -> io::Result<()> {
+    fn compress_block_and_write(
+        &mut self,
+        family_ord: usize,
+        data: &[u8],
+        num_docs_in_block: u32,
+    ) -> io::Result<()> {
         assert!(num_docs_in_block > 0);
+        #[cfg(feature = "zstd-compression")]
+        if let Some(DictionaryState::Sampling {
+            dictionary_size,
+            blocks,
+            num_bytes,
+        }) = &mut self.families[family_ord].dictionary_state
+        {
+            blocks.push((data.to_vec(), num_docs_in_block));
+            *num_bytes += data.len();
+            if *num_bytes >= *dictionary_size * DICTIONARY_SAMPLE_SIZE_FACTOR {
+                self.train_dictionary(family_ord)?;
+            }
+            return Ok(());
+        }
+        self.write_block(family_ord, data, num_docs_in_block)
+    }
+
+    /// Trains the dictionary of a family on the blocks held back so far, and writes them.
+    #[cfg(feature = "zstd-compression")]
+    fn train_dictionary(&mut self, family_ord: usize) -> io::Result<()> {
+        let family = &mut self.families[family_ord];
+        let Some(DictionaryState::Sampling {
+            dictionary_size,
+            blocks,
+            ..
+        }) = &mut family.dictionary_state
+        else {
+            return Ok(());
+        };
+        let dictionary_size = *dictionary_size;
+        let blocks = std::mem::take(blocks);
+        let mut samples = Vec::new();
+        let mut sample_sizes = Vec::new();
+        for (block, _) in &blocks {
+            push_block_samples(block, &mut samples, &mut sample_sizes)?;
+        }
+        let dictionary = super::compression_zstd_block::train_dictionary(
+            &samples,
+            &sample_sizes,
+            dictionary_size,
+        );
+        let encoder_dictionary = if dictionary.is_empty() {
+            None
+        } else {
+            let compression_level = match family.compressor {
+                Compressor::Zstd(zstd_compressor) => zstd_compressor.compression_level,
+                _ => None,
+            };
+            Some(zstd::dict::EncoderDictionary::copy(
+                &dictionary,
+                compression_level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL),
+            ))
+        };
+        family.dictionary_state = Some(DictionaryState::Trained {
+            dictionary,
+            encoder_dictionary,
+        });
+        for (block, num_docs_in_block) in blocks {
+            self.write_block(family_ord, &block, num_docs_in_block)?;
+        }
+        Ok(())
+    }
+
+    fn write_block(
+        &mut self,
+        family_ord: usize,
+        data: &[u8],
+        num_docs_in_block: u32,
+    ) -> io::Result<()> {
+        let family = &mut self.families[family_ord];
         self.intermediary_buffer.clear();
-        self.compressor
+        #[cfg(feature = "zstd-compression")]
+        if let Some(DictionaryState::Trained {
+            encoder_dictionary: Some(encoder_dictionary),
+            ..
+        }) = &family.dictionary_state
+        {
+            super::compression_zstd_block::compress_with_dictionary(
+                data,
+                &mut self.intermediary_buffer,
+                encoder_dictionary,
+            )?;
+        } else {
+            family
+                .compressor
+                .compress_into(data, &mut self.intermediary_buffer)?;
+        }
+        #[cfg(not(feature = "zstd-compression"))]
+        family
+            .compressor
             .compress_into(data, &mut self.intermediary_buffer)?;
+        // The checksum makes it possible to detect corrupted blocks when reading them.
+        let checksum = crc32fast::hash(&self.intermediary_buffer);
+        self.intermediary_buffer
+            .extend_from_slice(&checksum.to_le_bytes());
 
         let start_offset = self.writer.written_bytes() as usize;
         self.writer.write_all(&self.intermediary_buffer)?;
         let end_offset = self.writer.written_bytes() as usize;
 
-        self.register_checkpoint(Checkpoint {
-            doc_range: self.first_doc_in_block..self.first_doc_in_block + num_docs_in_block,
+        family.register_checkpoint(Checkpoint {
+            doc_range: family.first_doc_in_block..family.first_doc_in_block + num_docs_in_block,
             byte_range: start_offset..end_offset,
         });
         Ok(())
     }
 
-    fn register_checkpoint(&mut self, checkpoint: Checkpoint) {
-        self.offset_index_writer.insert(checkpoint.clone());
-        self.first_doc_in_block = checkpoint.doc_range.end;
-    }
-
     /// Stacks a store reader on top of the documents written so far.
     /// This method is an optimization compared to iterating over the documents
     /// in the store and adding them one by one, as the store's data will
     /// not be decompressed and then recompressed.
     fn stack(&mut self, store_reader: StoreReader) -> io::Result<()> {
-        let doc_shift = self.first_doc_in_block;
+        // Each doc store has its own dictionary, so that blocks compressed with a dictionary
+        // cannot be copied over.
+        if store_reader.has_dictionary() || self.has_dictionary() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "doc stores compressed with a dictionary cannot be stacked",
+            ));
+        }
+        if store_reader.doc_store_version() != DOC_STORE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "doc stores of older versions cannot be stacked",
+            ));
+        }
+        if store_reader.family_layout() != self.family_layout() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "doc stores with different families cannot be stacked",
+            ));
+        }
+        // All of the families contain all of the documents written so far.
+        let doc_shift = self.families[0].first_doc_in_block;
         let start_shift = self.writer.written_bytes() as usize;
 
         // just bulk write all of the block of the given reader.
         self.writer
             .write_all(store_reader.block_data()?.as_slice())?;
 
-        // concatenate the index of the `store_reader`, after translating
-        // its start doc id and its start file offset.
-        for mut checkpoint in store_reader.block_checkpoints() {
-            checkpoint.doc_range.start += doc_shift;
-            checkpoint.doc_range.end += doc_shift;
-            checkpoint.byte_range.start += start_shift;
-            checkpoint.byte_range.end += start_shift;
-            self.register_checkpoint(checkpoint);
+        // concatenate the indexes of the `store_reader`, after translating
+        // their start doc id and their start file offset.
+        for (family_ord, family) in self.families.iter_mut().enumerate() {
+            for mut checkpoint in store_reader.family_block_checkpoints(family_ord) {
+                checkpoint.doc_range.start += doc_shift;
+                checkpoint.doc_range.end += doc_shift;
+                checkpoint.byte_range.start += start_shift;
+                checkpoint.byte_range.end += start_shift;
+                family.register_checkpoint(checkpoint);
+            }
         }
         Ok(())
     }
 
+    fn has_dictionary(&self) -> bool {
+        self.families.iter().any(FamilyCompressor::has_dictionary)
+    }
+
+    fn family_layout(&self) -> Vec<(Vec<Field>, Decompressor)> {
+        self.families[1..]
+            .iter()
+            .map(|family| (family.fields.clone(), Decompressor::from(family.compressor)))
+            .collect()
+    }
+
     fn close(mut self) -> io::Result<()> {
+        #[cfg(feature = "zstd-compression")]
+        for family_ord in 0..self.families.len() {
+            self.train_dictionary(family_ord)?;
+        }
+        let mut families = self.families.into_iter();
+        let main_family = families.next().expect("the main family is always present");
+        let families: Vec<FamilyCompressor> = families.collect();
+        let main_compressor = main_family.compressor;
+        let main_dictionary = main_family.dictionary().to_vec();
         let header_offset: u64 = self.writer.written_bytes();
+        let families_len = if families.is_empty() {
+            main_family.block_index.serialize_into(&mut self.writer)?;
+            0
+        } else {
+            // The skip index is left empty, the blocks of all of the families are indexed in the
+            // families section.
+            SkipIndexBuilder::new().serialize_into(&mut self.writer)?;
+            let families_start = self.writer.written_bytes();
+            main_family.block_index.serialize_into(&mut self.writer)?;
+            VInt(families.len() as u64).serialize(&mut self.writer)?;
+            for family in families {
+                VInt(family.fields.len() as u64).serialize(&mut self.writer)?;
+                for field in &family.fields {
+                    field.serialize(&mut self.writer)?;
+                }
+                Decompressor::from(family.compressor)
+                    .get_id()
+                    .serialize(&mut self.writer)?;
+                let dictionary = family.dictionary().to_vec();
+                family.block_index.serialize_into(&mut self.writer)?;
+                VInt(dictionary.len() as u64).serialize(&mut self.writer)?;
+                self.writer.write_all(&dictionary)?;
+            }
+            (self.writer.written_bytes() - families_start) as u32
+        };
+        self.writer.write_all(&main_dictionary)?;
         let docstore_footer = DocStoreFooter::new(
             header_offset,
-            Decompressor::from(self.compressor),
+            Decompressor::from(main_compressor),
             DOC_STORE_VERSION,
+            main_dictionary.len() as u32,
+            families_len,
         );
-        self.offset_index_writer.serialize_into(&mut self.writer)?;
         docstore_footer.serialize(&mut self.writer)?;
         self.writer.terminate()
     }
 }
 
+/// Appends the documents of an uncompressed block to `samples`, and their sizes to
+/// `sample_sizes`.
+///
+/// A block ends with the start offsets of its documents, followed by its number of documents.
+#[cfg(feature = "zstd-compression")]
+fn push_block_samples(
+    block: &[u8],
+    samples: &mut Vec<u8>,
+    sample_sizes: &mut Vec<usize>,
+) -> io::Result<()> {
+    let size_of_u32 = std::mem::size_of::<u32>();
+    let num_docs = u32::deserialize(&mut &block[block.len() - size_of_u32..])? as usize;
+    let index_start = block.len() - (num_docs + 1) * size_of_u32;
+    let mut doc_starts = Vec::with_capacity(num_docs);
+    let mut index = &block[index_start..block.len() - size_of_u32];
+    for _ in 0..num_docs {
+        doc_starts.push(u32::deserialize(&mut index)? as usize);
+    }
+    doc_starts.push(index_start);
+    for doc_range in doc_starts.windows(2) {
+        sample_sizes.push(doc_range[1] - doc_range[0]);
+    }
+    samples.extend_from_slice(&block[..index_start]);
+    Ok(())
+}
+
 // ---------------------------------
 enum BlockCompressorMessage {
     CompressBlockAndWrite {
+        family_ord: usize,
         block_data: Vec<u8>,
         num_docs_in_block: u32,
     },
@@ -181,11 +500,15 @@ impl DedicatedThreadBlockCompressorImpl {
                 while let Ok(packet) = rx.recv() {
                     match packet {
                         BlockCompressorMessage::CompressBlockAndWrite {
+                            family_ord,
                             block_data,
                             num_docs_in_block,
                         } => {
-                            block_compressor
-                                .compress_block_and_write(&block_data[..], num_docs_in_block)?;
+                            block_compressor.compress_block_and_write(
+                                family_ord,
+                                &block_data[..],
+                                num_docs_in_block,
+                            )?;
                         }
                         BlockCompressorMessage::Stack(store_reader) => {
                             block_compressor.stack(store_reader)?;
@@ -201,8 +524,14 @@ impl DedicatedThreadBlockCompressorImpl {
         })
     }
 
-    fn compress_block_and_write(&mut self, bytes: &[u8], num_docs_in_block: u32) -> io::Result<()> {
+    fn compress_block_and_write(
+        &mut self,
+        family_ord: usize,
+        bytes: &[u8],
+        num_docs_in_block: u32,
+    ) -> io::Result<()> {
         self.send(BlockCompressorMessage::CompressBlockAndWrite {
+            family_ord,
             block_data: bytes.to_vec(),
             num_docs_in_block,
         })
@@ -249,8 +578,8 @@ mod tests {
     use crate::Directory;
 
     fn populate_block_compressor(mut block_compressor: BlockCompressor) -> io::Result<()> {
-        block_compressor.compress_block_and_write(b"hello", 1)?;
-        block_compressor.compress_block_and_write(b"happy", 1)?;
+        block_compressor.compress_block_and_write(0, b"hello", 1)?;
+        block_compressor.compress_block_and_write(0, b"happy", 1)?;
         block_compressor.close()?;
         Ok(())
     }
@@ -262,8 +591,8 @@ mod tests {
         let path2 = Path::new("path2");
         let wrt1 = ram_directory.open_write(path1).unwrap();
         let wrt2 = ram_directory.open_write(path2).unwrap();
-        let block_compressor1 = BlockCompressor::new(Compressor::None, wrt1, true).unwrap();
-        let block_compressor2 = BlockCompressor::new(Compressor::None, wrt2, false).unwrap();
+        let block_compressor1 = BlockCompressor::new(Compressor::None, &[], wrt1, true).unwrap();
+        let block_compressor2 = BlockCompressor::new(Compressor::None, &[], wrt2, false).unwrap();
         populate_block_compressor(block_compressor1).unwrap();
         populate_block_compressor(block_compressor2).unwrap();
         let data1 = ram_directory.open_read(path1).unwrap();
@@ -1,15 +1,88 @@
+use std::collections::HashMap;
 use std::io;
 
-use common::BinarySerializable;
+use common::{BinarySerializable, VInt};
 
 use super::compressors::Compressor;
-use super::StoreReader;
+use super::{Decompressor, StoreReader};
 use crate::directory::WritePtr;
+use crate::index::DocStoreFamily;
 use crate::schema::document::{BinaryDocumentSerializer, Document};
-use crate::schema::Schema;
+use crate::schema::{Field, Schema};
 use crate::store::store_compressor::BlockCompressor;
 use crate::DocId;
 
+/// A family of stored fields, kept in their own blocks of the doc store.
+///
+/// This is the resolved version of a [`DocStoreFamily`].
+#[derive(Clone, Debug)]
+pub(crate) struct StoreFamily {
+    pub fields: Vec<Field>,
+    pub compressor: Compressor,
+    pub block_size: usize,
+}
+
+impl StoreFamily {
+    /// Resolves the fields of the doc store families of the index settings.
+    ///
+    /// Returns an error if a field does not exist or is not stored, or if it belongs to
+    /// several families.
+    pub(crate) fn from_settings(
+        families: &[DocStoreFamily],
+        schema: &Schema,
+    ) -> crate::Result<Vec<StoreFamily>> {
+        let mut family_fields: Vec<Field> = Vec::new();
+        let mut store_families = Vec::with_capacity(families.len());
+        for family in families {
+            let mut fields = Vec::with_capacity(family.fields.len());
+            for field_name in &family.fields {
+                let field = schema.get_field(field_name)?;
+                if !schema.get_field_entry(field).is_stored() {
+                    return Err(crate::TantivyError::InvalidArgument(format!(
+                        "Field {field_name:?} of a doc store family is not stored."
+                    )));
+                }
+                if family_fields.contains(&field) {
+                    return Err(crate::TantivyError::InvalidArgument(format!(
+                        "Field {field_name:?} belongs to several doc store families."
+                    )));
+                }
+                family_fields.push(field);
+                fields.push(field);
+            }
+            store_families.push(StoreFamily {
+                fields,
+                compressor: family.compression,
+                block_size: family.blocksize,
+            });
+        }
+        Ok(store_families)
+    }
+}
+
+/// The documents of a family that are not written to disk yet.
+struct FamilyBlock {
+    block_size: usize,
+    num_docs_in_current_block: DocId,
+    current_block: Vec<u8>,
+    doc_pos: Vec<u32>,
+}
+
+impl FamilyBlock {
+    fn new(block_size: usize) -> FamilyBlock {
+        FamilyBlock {
+            block_size,
+            num_docs_in_current_block: 0,
+            current_block: Vec::new(),
+            doc_pos: Vec::new(),
+        }
+    }
+
+    fn mem_usage(&self) -> usize {
+        self.current_block.capacity() + self.doc_pos.capacity() * std::mem::size_of::<u32>()
+    }
+}
+
 /// Write tantivy's [`Store`](./index.html)
 ///
 /// Contrary to the other components of `tantivy`,
@@ -17,12 +90,17 @@ use crate::DocId;
 /// as opposed to when the segment is getting finalized.
 ///
 /// The skip list index on the other hand, is built in memory.
+///
+/// The stored fields of each [family](crate::DocStoreFamily) are kept in their own blocks.
+/// Every document has an entry, possibly empty, in the blocks of every family.
 pub struct StoreWriter {
     compressor: Compressor,
-    block_size: usize,
-    num_docs_in_current_block: DocId,
-    current_block: Vec<u8>,
-    doc_pos: Vec<u32>,
+    families: Vec<StoreFamily>,
+    // The family of each field which belongs to a family. The ordinal of a family is its
+    // position in `families` plus one, the ordinal 0 being used for the other fields.
+    field_families: HashMap<Field, usize>,
+    // One block per family ordinal.
+    blocks: Vec<FamilyBlock>,
     block_compressor: BlockCompressor,
 }
 
@@ -37,13 +115,40 @@ impl StoreWriter {
         block_size: usize,
         dedicated_thread: bool,
     ) -> io::Result<StoreWriter> {
-        let block_compressor = BlockCompressor::new(compressor, writer, dedicated_thread)?;
+        StoreWriter::with_families(writer, compressor, block_size, Vec::new(), dedicated_thread)
+    }
+
+    /// Create a store writer, which keeps the fields of each family in their own blocks.
+    ///
+    /// `compressor` and `block_size` apply to the fields that do not belong to any family.
+    pub(crate) fn with_families(
+        writer: WritePtr,
+        compressor: Compressor,
+        block_size: usize,
+        families: Vec<StoreFamily>,
+        dedicated_thread: bool,
+    ) -> io::Result<StoreWriter> {
+        let block_compressor =
+            BlockCompressor::new(compressor, &families, writer, dedicated_thread)?;
+        let field_families = families
+            .iter()
+            .enumerate()
+            .flat_map(|(family_idx, family)| {
+                family
+                    .fields
+                    .iter()
+                    .map(move |&field| (field, family_idx + 1))
+            })
+            .collect();
+        let blocks = std::iter::once(block_size)
+            .chain(families.iter().map(|family| family.block_size))
+            .map(FamilyBlock::new)
+            .collect();
         Ok(StoreWriter {
             compressor,
-            block_size,
-            num_docs_in_current_block: 0,
-            doc_pos: Vec::new(),
-            current_block: Vec::new(),
+            families,
+            field_families,
+            blocks,
             block_compressor,
         })
     }
@@ -52,43 +157,64 @@ impl StoreWriter {
         self.compressor
     }
 
+    /// Returns the fields and the decompressor of each family.
+    pub(crate) fn family_layout(&self) -> Vec<(Vec<Field>, Decompressor)> {
+        self.families
+            .iter()
+            .map(|family| (family.fields.clone(), Decompressor::from(family.compressor)))
+            .collect()
+    }
+
     /// The memory used (inclusive childs)
     pub fn mem_usage(&self) -> usize {
-        self.current_block.capacity() + self.doc_pos.capacity() * std::mem::size_of::<u32>()
+        self.blocks.iter().map(FamilyBlock::mem_usage).sum()
     }
 
-    /// Checks if the current block is full, and if so, compresses and flushes it.
-    fn check_flush_block(&mut self) -> io::Result<()> {
+    /// Checks if the current block of a family is full, and if so, compresses and flushes it.
+    fn check_flush_block(&mut self, family_ord: usize) -> io::Result<()> {
+        let block = &self.blocks[family_ord];
         // this does not count the VInt storing the index length itself, but it is negligible in
         // front of everything else.
-        let index_len = self.doc_pos.len() * std::mem::size_of::<usize>();
-        if self.current_block.len() + index_len > self.block_size {
-            self.send_current_block_to_compressor()?;
+        let index_len = block.doc_pos.len() * std::mem::size_of::<usize>();
+        if block.current_block.len() + index_len > block.block_size {
+            self.send_current_block_to_compressor(family_ord)?;
         }
         Ok(())
     }
 
-    /// Flushes current uncompressed block and sends to compressor.
-    fn send_current_block_to_compressor(&mut self) -> io::Result<()> {
+    /// Flushes current uncompressed block of a family and sends to compressor.
+    fn send_current_block_to_compressor(&mut self, family_ord: usize) -> io::Result<()> {
+        let block = &mut self.blocks[family_ord];
         // We don't do anything if the current block is empty to begin with.
-        if self.current_block.is_empty() {
+        if block.current_block.is_empty() {
             return Ok(());
         }
 
         let size_of_u32 = std::mem::size_of::<u32>();
-        self.current_block
-            .reserve((self.doc_pos.len() + 1) * size_of_u32);
+        block
+            .current_block
+            .reserve((block.doc_pos.len() + 1) * size_of_u32);
 
-        for pos in self.doc_pos.iter() {
-            pos.serialize(&mut self.current_block)?;
+        for pos in block.doc_pos.iter() {
+            pos.serialize(&mut block.current_block)?;
         }
-        (self.doc_pos.len() as u32).serialize(&mut self.current_block)?;
+        (block.doc_pos.len() as u32).serialize(&mut block.current_block)?;
 
-        self.block_compressor
-            .compress_block_and_write(&self.current_block, self.num_docs_in_current_block)?;
-        self.doc_pos.clear();
-        self.current_block.clear();
-        self.num_docs_in_current_block = 0;
+        self.block_compressor.compress_block_and_write(
+            family_ord,
+            &block.current_block,
+            block.num_docs_in_current_block,
+        )?;
+        block.doc_pos.clear();
+        block.current_block.clear();
+        block.num_docs_in_current_block = 0;
+        Ok(())
+    }
+
+    fn send_all_blocks_to_compressor(&mut self) -> io::Result<()> {
+        for family_ord in 0..self.blocks.len() {
+            self.send_current_block_to_compressor(family_ord)?;
+        }
         Ok(())
     }
 
@@ -97,13 +223,29 @@ impl StoreWriter {
     /// The document id is implicitly the current number
     /// of documents.
     pub fn store<D: Document>(&mut self, document: &D, schema: &Schema) -> io::Result<()> {
-        self.doc_pos.push(self.current_block.len() as u32);
+        for family_ord in 0..self.blocks.len() {
+            let field_families = &self.field_families;
+            let block = &mut self.blocks[family_ord];
+            block.doc_pos.push(block.current_block.len() as u32);
 
-        let mut serializer = BinaryDocumentSerializer::new(&mut self.current_block, schema);
-        serializer.serialize_doc(document)?;
+            if field_families.is_empty() {
+                let mut serializer =
+                    BinaryDocumentSerializer::new(&mut block.current_block, schema);
+                serializer.serialize_doc(document)?;
+            } else {
+                let field_filter =
+                    |field: Field| field_families.get(&field).copied().unwrap_or(0) == family_ord;
+                let mut serializer = BinaryDocumentSerializer::with_field_filter(
+                    &mut block.current_block,
+                    schema,
+                    &field_filter,
+                );
+                serializer.serialize_doc(document)?;
+            }
 
-        self.num_docs_in_current_block += 1;
-        self.check_flush_block()?;
+            block.num_docs_in_current_block += 1;
+            self.check_flush_block(family_ord)?;
+        }
         Ok(())
     }
 
@@ -112,10 +254,45 @@ impl StoreWriter {
     /// The document id is implicitly the current number
     /// of documents.
     pub fn store_bytes(&mut self, serialized_document: &[u8]) -> io::Result<()> {
-        self.doc_pos.push(self.current_block.len() as u32);
-        self.current_block.extend_from_slice(serialized_document);
-        self.num_docs_in_current_block += 1;
-        self.check_flush_block()?;
+        if self.families.is_empty() {
+            return self.store_family_bytes(0, serialized_document);
+        }
+        // The document is split across the families. Each field value is serialized as its
+        // field, the length of its value and the value itself.
+        let mut family_docs: Vec<(u64, Vec<u8>)> = vec![(0, Vec::new()); self.blocks.len()];
+        let mut reader = serialized_document;
+        let num_field_values = VInt::deserialize(&mut reader)?.val();
+        for _ in 0..num_field_values {
+            let entry_start = serialized_document.len() - reader.len();
+            let field = Field::deserialize(&mut reader)?;
+            let value_len = VInt::deserialize(&mut reader)?.val() as usize;
+            let entry_end = serialized_document.len() - reader.len() + value_len;
+            let entry = serialized_document
+                .get(entry_start..entry_end)
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "truncated stored field value")
+                })?;
+            reader = &serialized_document[entry_end..];
+            let family_ord = self.field_families.get(&field).copied().unwrap_or(0);
+            let (num_family_values, family_doc) = &mut family_docs[family_ord];
+            *num_family_values += 1;
+            family_doc.extend_from_slice(entry);
+        }
+        for (family_ord, (num_family_values, family_doc)) in family_docs.into_iter().enumerate() {
+            let mut family_doc_bytes = Vec::with_capacity(family_doc.len() + 1);
+            VInt(num_family_values).serialize(&mut family_doc_bytes)?;
+            family_doc_bytes.extend_from_slice(&family_doc);
+            self.store_family_bytes(family_ord, &family_doc_bytes)?;
+        }
+        Ok(())
+    }
+
+    fn store_family_bytes(&mut self, family_ord: usize, family_doc: &[u8]) -> io::Result<()> {
+        let block = &mut self.blocks[family_ord];
+        block.doc_pos.push(block.current_block.len() as u32);
+        block.current_block.extend_from_slice(family_doc);
+        block.num_docs_in_current_block += 1;
+        self.check_flush_block(family_ord)?;
         Ok(())
     }
 
@@ -123,9 +300,11 @@ impl StoreWriter {
     /// This method is an optimization compared to iterating over the documents
     /// in the store and adding them one by one, as the store's data will
     /// not be decompressed and then recompressed.
+    ///
+    /// The store reader must have the same families as the store writer.
     pub fn stack(&mut self, store_reader: StoreReader) -> io::Result<()> {
-        // We flush the current block first before stacking
-        self.send_current_block_to_compressor()?;
+        // We flush the current blocks first before stacking
+        self.send_all_blocks_to_compressor()?;
         self.block_compressor.stack_reader(store_reader)?;
         Ok(())
     }
@@ -135,7 +314,7 @@ impl StoreWriter {
     /// Compress the last unfinished block if any,
     /// and serializes the skip list index on disc.
     pub fn close(mut self) -> io::Result<()> {
-        self.send_current_block_to_compressor()?;
+        self.send_all_blocks_to_compressor()?;
         self.block_compressor.close()?;
         Ok(())
     }
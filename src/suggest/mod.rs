@@ -0,0 +1,221 @@
+//! Term suggestions, e.g. to propose spelling corrections for the terms of a query.
+//!
+//! The terms of the term dictionary of a field within a given Levenshtein distance of a term
+//! are collected with a Levenshtein automaton, in each segment, and ranked by edit distance,
+//! then by decreasing document frequency.
+//!
+//! ```rust
+//! use tantivy::schema::{Schema, TEXT};
+//! use tantivy::suggest::SuggestOptions;
+//! use tantivy::{doc, Index, IndexWriter};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+//! index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+//! index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+//! index_writer.add_document(doc!(title => "The Diary of a Young Girl"))?;
+//! index_writer.commit()?;
+//!
+//! let searcher = index.reader()?.searcher();
+//! let suggestions = searcher.suggest_terms(title, "diarz", &SuggestOptions::default())?;
+//! let terms: Vec<&str> = suggestions
+//!     .iter()
+//!     .map(|suggestion| suggestion.term.as_str())
+//!     .collect();
+//! assert_eq!(terms, ["diary", "dairy"]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The [`PhraseSuggester`] combines the suggestions of the words of a query text into
+//! corrections of the whole text.
+
+mod phrase_suggester;
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use levenshtein_automata::Distance;
+
+use crate::query::{levenshtein_automaton_builder, DfaWrapper};
+use crate::schema::{Field, FieldType};
+use crate::{Searcher, TantivyError};
+
+pub use self::phrase_suggester::{PhraseSuggester, PhraseSuggestion};
+
+/// Options of [`Searcher::suggest_terms`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuggestOptions {
+    /// Maximum Levenshtein distance between the term and its suggestions, up to 2.
+    pub max_distance: u8,
+    /// Whether a transposition of two characters costs 1 or 2 edits.
+    pub transposition_cost_one: bool,
+    /// Maximum number of suggestions returned.
+    pub limit: usize,
+    /// Minimum number of documents containing a suggestion.
+    pub min_doc_freq: u64,
+}
+
+impl Default for SuggestOptions {
+    fn default() -> SuggestOptions {
+        SuggestOptions {
+            max_distance: 2,
+            transposition_cost_one: true,
+            limit: 5,
+            min_doc_freq: 1,
+        }
+    }
+}
+
+/// A term suggested as a correction for another term.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermSuggestion {
+    /// Text of the suggested term.
+    pub term: String,
+    /// Levenshtein distance to the corrected term.
+    pub distance: u8,
+    /// Number of documents containing the suggested term, including deleted documents.
+    pub doc_freq: u64,
+}
+
+pub(crate) fn suggest_terms(
+    searcher: &Searcher,
+    field: Field,
+    term: &str,
+    options: &SuggestOptions,
+) -> crate::Result<Vec<TermSuggestion>> {
+    let field_entry = searcher.schema().get_field_entry(field);
+    if !matches!(field_entry.field_type(), FieldType::Str(_)) || !field_entry.is_indexed() {
+        return Err(TantivyError::InvalidArgument(format!(
+            "Terms can only be suggested for indexed text fields, {:?} is not one",
+            field_entry.name()
+        )));
+    }
+    let automaton_builder =
+        levenshtein_automaton_builder(options.max_distance, options.transposition_cost_one)?;
+    let automaton = DfaWrapper(automaton_builder.build_dfa(term));
+
+    let mut doc_freqs: BTreeMap<Vec<u8>, u64> = BTreeMap::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(field)?;
+        let mut term_stream = inverted_index.terms().search(&automaton).into_stream()?;
+        while term_stream.advance() {
+            *doc_freqs.entry(term_stream.key().to_vec()).or_default() +=
+                u64::from(term_stream.value().doc_freq);
+        }
+    }
+
+    let mut suggestions: Vec<TermSuggestion> = Vec::new();
+    for (term_bytes, doc_freq) in doc_freqs {
+        if doc_freq < options.min_doc_freq {
+            continue;
+        }
+        let Distance::Exact(distance) = automaton.0.eval(&term_bytes) else {
+            continue;
+        };
+        // The term itself is not a correction.
+        if distance == 0 {
+            continue;
+        }
+        let Ok(term_text) = String::from_utf8(term_bytes) else {
+            continue;
+        };
+        suggestions.push(TermSuggestion {
+            term: term_text,
+            distance,
+            doc_freq,
+        });
+    }
+    suggestions.sort_by(|left, right| {
+        (left.distance, Reverse(left.doc_freq), &left.term).cmp(&(
+            right.distance,
+            Reverse(right.doc_freq),
+            &right.term,
+        ))
+    });
+    suggestions.truncate(options.limit);
+    Ok(suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_suggest_terms() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "search engine"))?;
+        index_writer.add_document(doc!(text_field => "research"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "search searches"))?;
+        index_writer.add_document(doc!(text_field => "starch"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let suggestions =
+            searcher.suggest_terms(text_field, "serch", &SuggestOptions::default())?;
+        let suggestion = |term: &str, distance, doc_freq| TermSuggestion {
+            term: term.to_string(),
+            distance,
+            doc_freq,
+        };
+        // The doc freqs are summed over the segments.
+        assert_eq!(
+            suggestions,
+            [suggestion("search", 1, 2), suggestion("starch", 2, 1)]
+        );
+
+        let options = SuggestOptions {
+            max_distance: 3,
+            limit: 2,
+            ..SuggestOptions::default()
+        };
+        assert!(matches!(
+            searcher.suggest_terms(text_field, "serch", &options),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        let options = SuggestOptions {
+            min_doc_freq: 2,
+            ..SuggestOptions::default()
+        };
+        let suggestions = searcher.suggest_terms(text_field, "searchs", &options)?;
+        assert_eq!(suggestions, [suggestion("search", 1, 2)]);
+
+        // The term itself is not suggested.
+        let options = SuggestOptions {
+            max_distance: 1,
+            ..SuggestOptions::default()
+        };
+        let suggestions = searcher.suggest_terms(text_field, "search", &options)?;
+        assert_eq!(suggestions, [suggestion("starch", 1, 1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggest_terms_requires_text_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", crate::schema::INDEXED);
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        assert!(searcher
+            .suggest_terms(id_field, "1", &SuggestOptions::default())
+            .is_err());
+        assert!(searcher
+            .suggest_terms(tag_field, "tag", &SuggestOptions::default())?
+            .is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+use super::{suggest_terms, SuggestOptions};
+use crate::collector::Count;
+use crate::query::{BooleanQuery, PhraseQuery, Query, TermQuery};
+use crate::schema::{Field, IndexRecordOption};
+use crate::tokenizer::{TextAnalyzer, Token};
+use crate::{Searcher, Term};
+
+const DEFAULT_LIMIT: usize = 3;
+const DEFAULT_MAX_COLLATIONS: usize = 20;
+
+/// A rewrite of a whole query text suggested by the [`PhraseSuggester`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhraseSuggestion {
+    /// The query text, with its corrected words.
+    pub text: String,
+    /// The total number of edits of the corrected words.
+    pub distance: u32,
+    /// The number of documents matching the corrected words.
+    pub num_hits: usize,
+}
+
+/// A word of the query text and its candidate corrections.
+struct WordCandidates {
+    token: Token,
+    /// The candidates for the word, with their edit distance and their document frequency.
+    /// The word itself is its only candidate if it appears in the index.
+    candidates: Vec<(String, u8, u64)>,
+}
+
+/// A combination of candidates, given by their index in the [`WordCandidates`].
+#[derive(Clone)]
+struct Combination {
+    candidate_ords: Vec<usize>,
+    distance: u32,
+    log_doc_freq: f64,
+}
+
+/// `PhraseSuggester` suggests corrections of a whole query text ("did you mean").
+///
+/// The words of the text are tokenized with the tokenizer of the field, and each of them which
+/// does not appear in the index is corrected with the term suggestions of
+/// [`Searcher::suggest_terms`]. The combinations of
+/// candidates with the fewest edits and the most frequent terms are then collated: they are run
+/// as a query, a phrase query if the field records positions, and only the combinations
+/// matching documents are suggested.
+///
+/// ```rust
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::suggest::PhraseSuggester;
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+/// index_writer.add_document(doc!(title => "The Man Who Sold the World"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let phrase_suggester = PhraseSuggester::create(&searcher, title)?;
+/// let suggestions = phrase_suggester.suggest(&searcher, "old mam and the see")?;
+/// assert_eq!(suggestions[0].text, "old man and the sea");
+/// # Ok(())
+/// # }
+/// ```
+pub struct PhraseSuggester {
+    field: Field,
+    tokenizer: TextAnalyzer,
+    use_phrase_query: bool,
+    term_options: SuggestOptions,
+    limit: usize,
+    max_collations: usize,
+}
+
+impl PhraseSuggester {
+    /// Creates a new phrase suggester for the given field.
+    pub fn create(searcher: &Searcher, field: Field) -> crate::Result<PhraseSuggester> {
+        let tokenizer = searcher.index().tokenizer_for_field(field)?;
+        let use_phrase_query = searcher
+            .schema()
+            .get_field_entry(field)
+            .field_type()
+            .index_record_option()
+            .is_some_and(IndexRecordOption::has_positions);
+        Ok(PhraseSuggester {
+            field,
+            tokenizer,
+            use_phrase_query,
+            term_options: SuggestOptions::default(),
+            limit: DEFAULT_LIMIT,
+            max_collations: DEFAULT_MAX_COLLATIONS,
+        })
+    }
+
+    /// Sets the options used to suggest corrections for each word.
+    pub fn set_term_options(&mut self, term_options: SuggestOptions) {
+        self.term_options = term_options;
+    }
+
+    /// Sets the maximum number of suggestions. Default is 3.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// Sets the maximum number of combinations of candidates run as a query to check
+    /// that they match documents. Default is 20.
+    pub fn set_max_collations(&mut self, max_collations: usize) {
+        self.max_collations = max_collations;
+    }
+
+    /// Suggests corrections of the given query text.
+    ///
+    /// The suggestions are ranked by number of edits, then by frequency of their terms. A text
+    /// whose words all appear in the index has no suggestions.
+    pub fn suggest(&self, searcher: &Searcher, text: &str) -> crate::Result<Vec<PhraseSuggestion>> {
+        let mut words: Vec<WordCandidates> = Vec::new();
+        let mut tokenizer = self.tokenizer.clone();
+        let mut token_stream = tokenizer.token_stream(text);
+        while let Some(token) = token_stream.next() {
+            let doc_freq = searcher.doc_freq(&Term::from_field_text(self.field, &token.text))?;
+            let candidates = if doc_freq > 0 {
+                // Words of the index are not corrected.
+                vec![(token.text.clone(), 0, doc_freq)]
+            } else {
+                suggest_terms(searcher, self.field, &token.text, &self.term_options)?
+                    .into_iter()
+                    .map(|suggestion| (suggestion.term, suggestion.distance, suggestion.doc_freq))
+                    .collect()
+            };
+            if candidates.is_empty() {
+                // Nothing can be suggested for the text.
+                return Ok(Vec::new());
+            }
+            words.push(WordCandidates {
+                token: token.clone(),
+                candidates,
+            });
+        }
+
+        let mut suggestions = Vec::new();
+        for combination in self.best_combinations(&words) {
+            if suggestions.len() >= self.limit {
+                break;
+            }
+            if combination.distance == 0 {
+                continue;
+            }
+            let terms: Vec<Term> = words
+                .iter()
+                .zip(&combination.candidate_ords)
+                .map(|(word, &ord)| Term::from_field_text(self.field, &word.candidates[ord].0))
+                .collect();
+            let num_hits = searcher.search(&*self.collation_query(terms), &Count)?;
+            if num_hits == 0 {
+                continue;
+            }
+            suggestions.push(PhraseSuggestion {
+                text: rewrite_text(text, &words, &combination),
+                distance: combination.distance,
+                num_hits,
+            });
+        }
+        Ok(suggestions)
+    }
+
+    /// Returns the best `max_collations` combinations of candidates, with a beam search.
+    fn best_combinations(&self, words: &[WordCandidates]) -> Vec<Combination> {
+        let mut combinations = vec![Combination {
+            candidate_ords: Vec::new(),
+            distance: 0,
+            log_doc_freq: 0.0,
+        }];
+        for word in words {
+            let mut next_combinations =
+                Vec::with_capacity(combinations.len() * word.candidates.len());
+            for combination in &combinations {
+                for (ord, (_, distance, doc_freq)) in word.candidates.iter().enumerate() {
+                    let mut next_combination = combination.clone();
+                    next_combination.candidate_ords.push(ord);
+                    next_combination.distance += u32::from(*distance);
+                    next_combination.log_doc_freq += (*doc_freq as f64).ln();
+                    next_combinations.push(next_combination);
+                }
+            }
+            next_combinations.sort_by(|left, right| {
+                left.distance.cmp(&right.distance).then(
+                    right
+                        .log_doc_freq
+                        .partial_cmp(&left.log_doc_freq)
+                        .unwrap_or(std::cmp::Ordering::Equal),
+                )
+            });
+            // The unchanged text does not count as a collation.
+            next_combinations.truncate(self.max_collations + 1);
+            combinations = next_combinations;
+        }
+        combinations
+    }
+
+    fn collation_query(&self, mut terms: Vec<Term>) -> Box<dyn Query> {
+        if terms.len() == 1 {
+            return Box::new(TermQuery::new(
+                terms.pop().unwrap(),
+                IndexRecordOption::Basic,
+            ));
+        }
+        if self.use_phrase_query {
+            return Box::new(PhraseQuery::new(terms));
+        }
+        let term_queries: Vec<Box<dyn Query>> = terms
+            .into_iter()
+            .map(|term| -> Box<dyn Query> {
+                Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+            })
+            .collect();
+        Box::new(BooleanQuery::intersection(term_queries))
+    }
+}
+
+/// Replaces the corrected words of the text.
+fn rewrite_text(text: &str, words: &[WordCandidates], combination: &Combination) -> String {
+    let mut rewritten_text = String::with_capacity(text.len());
+    let mut offset = 0;
+    for (word, &ord) in words.iter().zip(&combination.candidate_ords) {
+        let (candidate, distance, _) = &word.candidates[ord];
+        if *distance == 0 {
+            continue;
+        }
+        rewritten_text.push_str(&text[offset..word.token.offset_from]);
+        rewritten_text.push_str(candidate);
+        offset = word.token.offset_to;
+    }
+    rewritten_text.push_str(&text[offset..]);
+    rewritten_text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter};
+
+    fn suggested_texts(suggestions: &[PhraseSuggestion]) -> Vec<&str> {
+        suggestions
+            .iter()
+            .map(|suggestion| suggestion.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_phrase_suggester() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "new york city"))?;
+        index_writer.add_document(doc!(text_field => "new york times"))?;
+        index_writer.add_document(doc!(text_field => "york new"))?;
+        index_writer.add_document(doc!(text_field => "new work"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let phrase_suggester = PhraseSuggester::create(&searcher, text_field)?;
+
+        let suggestions = phrase_suggester.suggest(&searcher, "New Yrok Tims!")?;
+        assert_eq!(
+            suggestions,
+            [PhraseSuggestion {
+                text: "New york times!".to_string(),
+                distance: 2,
+                num_hits: 1,
+            }]
+        );
+
+        // "new work" is closer, but "new york" is more frequent.
+        let suggestions = phrase_suggester.suggest(&searcher, "new yorc")?;
+        assert_eq!(suggested_texts(&suggestions), ["new york", "new work"]);
+
+        // Candidates are collated as a phrase: "york" never comes before "city".
+        let suggestions = phrase_suggester.suggest(&searcher, "citi york")?;
+        assert!(suggestions.is_empty());
+
+        // A text without misspelled words has no suggestions.
+        assert!(phrase_suggester.suggest(&searcher, "new york")?.is_empty());
+        // Nor does a text with a word which cannot be corrected.
+        assert!(phrase_suggester
+            .suggest(&searcher, "new xxxxxxxx")?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_phrase_suggester_without_positions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag_field => "rust"))?;
+        index_writer.add_document(doc!(tag_field => "dust"))?;
+        index_writer.add_document(doc!(tag_field => "dust"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let mut phrase_suggester = PhraseSuggester::create(&searcher, tag_field)?;
+        phrase_suggester.set_limit(1);
+        let suggestions = phrase_suggester.suggest(&searcher, "bust")?;
+        assert_eq!(suggested_texts(&suggestions), ["dust"]);
+        Ok(())
+    }
+}
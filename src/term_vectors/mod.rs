@@ -0,0 +1,133 @@
+//! Term vectors, the terms of a field in a document.
+//!
+//! The term vectors of a text field are stored when its indexing options are set with
+//! [`TextFieldIndexing::set_term_vectors`](crate::schema::TextFieldIndexing::set_term_vectors).
+//! The term vector of a document lists the terms of the field in the document, sorted, with
+//! the positions and byte offsets of their occurrences. The offsets are relative to the values
+//! of the field joined by a space, like the offsets of the postings.
+//!
+//! The term vectors of a segment are stored in their own segment component, giving random access
+//! to the term vector of any document. They are recorded while the text is tokenized for the
+//! inverted index, so that e.g. highlighting, `MoreLikeThis` on a stored document, or feature
+//! extraction can use the terms of a document without tokenizing it again.
+//!
+//! The term vector of a document is read with
+//! [`Searcher::term_vector`](crate::Searcher::term_vector).
+mod reader;
+mod serializer;
+mod writer;
+
+pub use self::reader::{TermVectorReader, TermVectorsReaders};
+pub use self::serializer::TermVectorsSerializer;
+pub(crate) use self::writer::RecordingTokenStream;
+pub use self::writer::TermVectorsWriter;
+
+/// An occurrence of a term in a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TermOccurrence {
+    /// Position of the token.
+    pub position: u32,
+    /// Byte offset of the beginning of the token.
+    pub offset_from: u32,
+    /// Byte offset of the end of the token.
+    pub offset_to: u32,
+}
+
+/// A term of a term vector, with its occurrences in the document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermVectorEntry {
+    /// The text of the term.
+    pub term: String,
+    /// The occurrences of the term, sorted by position.
+    pub occurrences: Vec<TermOccurrence>,
+}
+
+impl TermVectorEntry {
+    /// Returns the number of occurrences of the term in the document.
+    pub fn term_freq(&self) -> u32 {
+        self.occurrences.len() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, TextFieldIndexing, TextOptions, TEXT};
+    use crate::{DocAddress, Index, IndexWriter};
+
+    fn occurrence(position: u32, offset_from: u32, offset_to: u32) -> TermOccurrence {
+        TermOccurrence {
+            position,
+            offset_from,
+            offset_to,
+        }
+    }
+
+    #[test]
+    fn test_term_vectors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default().set_term_vectors(true),
+        );
+        let title = schema_builder.add_text_field("title", text_options);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(title => "The cat", title => "the dog"))?;
+        index_writer.add_document(doc!(body => "no title"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "a dog"))?;
+        index_writer.commit()?;
+
+        let check_term_vectors = |index: &Index, doc_addresses: [DocAddress; 3]| {
+            let searcher = index.reader()?.searcher();
+            assert_eq!(
+                searcher.term_vector(doc_addresses[0], title)?,
+                vec![
+                    TermVectorEntry {
+                        term: "cat".to_string(),
+                        occurrences: vec![occurrence(1, 4, 7)],
+                    },
+                    TermVectorEntry {
+                        term: "dog".to_string(),
+                        occurrences: vec![occurrence(4, 12, 15)],
+                    },
+                    TermVectorEntry {
+                        term: "the".to_string(),
+                        occurrences: vec![occurrence(0, 0, 3), occurrence(3, 8, 11)],
+                    },
+                ]
+            );
+            assert!(searcher.term_vector(doc_addresses[1], title)?.is_empty());
+            let term_vector = searcher.term_vector(doc_addresses[2], title)?;
+            assert_eq!(term_vector.len(), 2);
+            assert_eq!(term_vector[1].term, "dog");
+            assert_eq!(term_vector[1].term_freq(), 1);
+            // The body field has no term vectors.
+            assert!(searcher.term_vector(doc_addresses[0], body).is_err());
+            crate::Result::Ok(())
+        };
+        check_term_vectors(
+            &index,
+            [
+                DocAddress::new(0, 0),
+                DocAddress::new(0, 1),
+                DocAddress::new(1, 0),
+            ],
+        )?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        check_term_vectors(
+            &index,
+            [
+                DocAddress::new(0, 0),
+                DocAddress::new(0, 1),
+                DocAddress::new(0, 2),
+            ],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use std::io;
+use std::sync::Arc;
+
+use common::{BinarySerializable, VInt};
+
+use super::{TermOccurrence, TermVectorEntry};
+use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::schema::Field;
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::DocId;
+
+/// Reader for the term vectors of all fields of a segment.
+#[derive(Clone)]
+pub struct TermVectorsReaders {
+    data: Arc<CompositeFile>,
+}
+
+impl TermVectorsReaders {
+    /// Creates a term vectors reader.
+    pub fn open(file: FileSlice) -> crate::Result<TermVectorsReaders> {
+        let data = CompositeFile::open(&file)?;
+        Ok(TermVectorsReaders {
+            data: Arc::new(data),
+        })
+    }
+
+    /// Creates a term vectors reader without any field.
+    ///
+    /// This is used for segments written before term vectors existed.
+    pub fn empty() -> TermVectorsReaders {
+        TermVectorsReaders {
+            data: Arc::new(CompositeFile::empty()),
+        }
+    }
+
+    /// Returns the `TermVectorReader` for a specific field.
+    ///
+    /// Returns `None` if the field has no term vectors.
+    pub fn get_field(&self, field: Field) -> crate::Result<Option<TermVectorReader>> {
+        if let Some(file) = self.data.open_read(field) {
+            Ok(Some(TermVectorReader::open(file.read_bytes()?)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+/// Gives random access to the term vectors of a given field.
+#[derive(Clone)]
+pub struct TermVectorReader {
+    num_docs: u32,
+    doc_starts: OwnedBytes,
+    data: OwnedBytes,
+}
+
+impl TermVectorReader {
+    /// Opens the term vectors of a field.
+    pub fn open(mut bytes: OwnedBytes) -> io::Result<TermVectorReader> {
+        let num_docs = VInt::deserialize(&mut bytes)?.val() as u32;
+        let doc_starts_num_bytes = (num_docs as usize + 1) * std::mem::size_of::<u64>();
+        if bytes.len() < doc_starts_num_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Term vectors length does not match the number of documents.",
+            ));
+        }
+        let (doc_starts, data) = bytes.split(doc_starts_num_bytes);
+        Ok(TermVectorReader {
+            num_docs,
+            doc_starts,
+            data,
+        })
+    }
+
+    /// Returns the number of documents, including the documents without a term vector.
+    pub fn num_docs(&self) -> u32 {
+        self.num_docs
+    }
+
+    fn doc_start(&self, doc: DocId) -> usize {
+        let start = doc as usize * std::mem::size_of::<u64>();
+        let doc_start_bytes = &self.doc_starts.as_slice()[start..start + std::mem::size_of::<u64>()];
+        u64::from_le_bytes(doc_start_bytes.try_into().unwrap()) as usize
+    }
+
+    /// Returns the serialized term vector of `doc`, empty if the document has no term vector.
+    pub(crate) fn doc_bytes(&self, doc: DocId) -> &[u8] {
+        if doc >= self.num_docs {
+            return &[];
+        }
+        &self.data.as_slice()[self.doc_start(doc)..self.doc_start(doc + 1)]
+    }
+
+    /// Returns the term vector of `doc`.
+    ///
+    /// The entries are sorted by term, and the occurrences of each term by position. The term
+    /// vector is empty if the document has no value for the field.
+    pub fn term_vector(&self, doc: DocId) -> io::Result<Vec<TermVectorEntry>> {
+        let mut bytes = self.doc_bytes(doc);
+        if bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let num_terms = VInt::deserialize(&mut bytes)?.val() as usize;
+        let mut entries = Vec::with_capacity(num_terms);
+        for _ in 0..num_terms {
+            let term = String::deserialize(&mut bytes)?;
+            let num_occurrences = VInt::deserialize(&mut bytes)?.val() as usize;
+            let mut occurrences = Vec::with_capacity(num_occurrences);
+            let mut position = 0;
+            for _ in 0..num_occurrences {
+                position += VInt::deserialize(&mut bytes)?.val() as u32;
+                let offset_from = VInt::deserialize(&mut bytes)?.val() as u32;
+                let offset_to = offset_from + VInt::deserialize(&mut bytes)?.val() as u32;
+                occurrences.push(TermOccurrence {
+                    position,
+                    offset_from,
+                    offset_to,
+                });
+            }
+            entries.push(TermVectorEntry { term, occurrences });
+        }
+        Ok(entries)
+    }
+}
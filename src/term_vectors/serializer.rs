@@ -0,0 +1,47 @@
+use std::io;
+use std::io::Write;
+
+use common::{BinarySerializable, VInt};
+
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::Field;
+
+/// The term vectors serializer is in charge of
+/// the serialization of the term vectors of all fields.
+pub struct TermVectorsSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl TermVectorsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<TermVectorsSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(TermVectorsSerializer { composite_write })
+    }
+
+    /// Serialize the term vectors of the given field.
+    ///
+    /// The term vector of the document `doc` is
+    /// `data[doc_starts[doc]..doc_starts[doc + 1]]`, the last one ending at the end of `data`.
+    pub fn serialize_field(
+        &mut self,
+        field: Field,
+        doc_starts: &[u64],
+        data: &[u8],
+    ) -> io::Result<()> {
+        let write = self.composite_write.for_field(field);
+        VInt(doc_starts.len() as u64).serialize(write)?;
+        for doc_start in doc_starts.iter().copied().chain([data.len() as u64]) {
+            write.write_all(&doc_start.to_le_bytes())?;
+        }
+        write.write_all(data)?;
+        write.flush()?;
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
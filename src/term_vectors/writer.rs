@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::{io, iter};
+
+use common::{BinarySerializable, VInt};
+
+use super::{TermOccurrence, TermVectorsSerializer};
+use crate::schema::{Field, FieldType, Schema};
+use crate::tokenizer::{Token, TokenStream, MAX_TOKEN_LEN};
+use crate::DocId;
+
+#[derive(Default)]
+struct FieldTermVectors {
+    // Start of the term vector of each document in `data`.
+    doc_starts: Vec<u64>,
+    data: Vec<u8>,
+    // Occurrences of the terms of the document being indexed.
+    doc_terms: BTreeMap<String, Vec<TermOccurrence>>,
+}
+
+impl FieldTermVectors {
+    fn fill_up_to_doc(&mut self, doc: DocId) {
+        let data_len = self.data.len() as u64;
+        self.doc_starts.resize(self.doc_starts.len().max(doc as usize), data_len);
+    }
+}
+
+/// The `TermVectorsWriter` is in charge of recording the term vectors
+/// of the fields with term vectors.
+pub struct TermVectorsWriter {
+    // For each field, `None` if the field has no term vectors.
+    term_vectors_per_field: Vec<Option<FieldTermVectors>>,
+}
+
+impl TermVectorsWriter {
+    /// Returns the fields of the given schema with term vectors.
+    pub(crate) fn term_vector_fields(schema: &Schema) -> Vec<Field> {
+        schema
+            .fields()
+            .filter_map(|(field, field_entry)| match field_entry.field_type() {
+                FieldType::Str(text_options) => text_options
+                    .get_indexing_options()
+                    .filter(|indexing_options| indexing_options.term_vectors())
+                    .map(|_| field),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Initialize with state for tracking the fields with term vectors
+    /// specified in the schema.
+    pub fn for_schema(schema: &Schema) -> TermVectorsWriter {
+        let mut term_vectors_per_field: Vec<Option<FieldTermVectors>> = iter::repeat_with(|| None)
+            .take(schema.num_fields())
+            .collect();
+        for field in TermVectorsWriter::term_vector_fields(schema) {
+            term_vectors_per_field[field.field_id() as usize] = Some(FieldTermVectors::default());
+        }
+        TermVectorsWriter {
+            term_vectors_per_field,
+        }
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.term_vectors_per_field
+            .iter()
+            .flatten()
+            .map(|field_term_vectors| {
+                field_term_vectors.doc_starts.capacity() * std::mem::size_of::<u64>()
+                    + field_term_vectors.data.capacity()
+            })
+            .sum()
+    }
+
+    /// Returns true if the term vectors of the field are recorded.
+    pub(crate) fn has_term_vectors(&self, field: Field) -> bool {
+        matches!(
+            self.term_vectors_per_field.get(field.field_id() as usize),
+            Some(Some(_))
+        )
+    }
+
+    /// Records the tokens of a text of the field, for the document being indexed.
+    ///
+    /// The positions and offsets of the tokens are relative to the text, and are shifted by
+    /// `start_position` and `start_offset` like the postings do.
+    pub(crate) fn record_tokens(
+        &mut self,
+        field: Field,
+        start_position: u32,
+        start_offset: u32,
+        tokens: &[Token],
+    ) {
+        let Some(Some(field_term_vectors)) =
+            self.term_vectors_per_field.get_mut(field.field_id() as usize)
+        else {
+            return;
+        };
+        for token in tokens {
+            // Tokens exceeding `MAX_TOKEN_LEN` are dropped by the postings as well.
+            if token.text.len() > MAX_TOKEN_LEN {
+                continue;
+            }
+            let occurrence = TermOccurrence {
+                position: start_position + token.position as u32,
+                offset_from: start_offset + token.offset_from as u32,
+                offset_to: start_offset + token.offset_to as u32,
+            };
+            if let Some(occurrences) = field_term_vectors.doc_terms.get_mut(&token.text) {
+                occurrences.push(occurrence);
+            } else {
+                field_term_vectors
+                    .doc_terms
+                    .insert(token.text.clone(), vec![occurrence]);
+            }
+        }
+    }
+
+    /// Writes the term vector of the field for `doc`, from the tokens recorded so far.
+    pub(crate) fn end_field(&mut self, doc: DocId, field: Field) -> io::Result<()> {
+        let Some(Some(field_term_vectors)) =
+            self.term_vectors_per_field.get_mut(field.field_id() as usize)
+        else {
+            return Ok(());
+        };
+        field_term_vectors.fill_up_to_doc(doc);
+        field_term_vectors
+            .doc_starts
+            .push(field_term_vectors.data.len() as u64);
+        let doc_terms = std::mem::take(&mut field_term_vectors.doc_terms);
+        serialize_doc_terms(doc_terms, &mut field_term_vectors.data)
+    }
+
+    /// Ensures all documents up to `max_doc` have a (possibly empty) term vector.
+    pub fn fill_up_to_max_doc(&mut self, max_doc: DocId) {
+        for field_term_vectors in self.term_vectors_per_field.iter_mut().flatten() {
+            field_term_vectors.fill_up_to_doc(max_doc);
+        }
+    }
+
+    /// Serialize the recorded term vectors of all fields.
+    pub fn serialize(&self, mut term_vectors_serializer: TermVectorsSerializer) -> io::Result<()> {
+        for (field_id, field_term_vectors_opt) in self.term_vectors_per_field.iter().enumerate() {
+            if let Some(field_term_vectors) = field_term_vectors_opt {
+                term_vectors_serializer.serialize_field(
+                    Field::from_field_id(field_id as u32),
+                    &field_term_vectors.doc_starts,
+                    &field_term_vectors.data,
+                )?;
+            }
+        }
+        term_vectors_serializer.close()?;
+        Ok(())
+    }
+}
+
+/// Serializes the terms of a document, sorted, with their occurrences.
+fn serialize_doc_terms(
+    doc_terms: BTreeMap<String, Vec<TermOccurrence>>,
+    output: &mut Vec<u8>,
+) -> io::Result<()> {
+    VInt(doc_terms.len() as u64).serialize(output)?;
+    for (term, mut occurrences) in doc_terms {
+        occurrences.sort_by_key(|occurrence| (occurrence.position, occurrence.offset_from));
+        term.serialize(output)?;
+        VInt(occurrences.len() as u64).serialize(output)?;
+        let mut previous_position = 0;
+        for occurrence in occurrences {
+            VInt((occurrence.position - previous_position) as u64).serialize(output)?;
+            VInt(occurrence.offset_from as u64).serialize(output)?;
+            VInt(occurrence.offset_to.saturating_sub(occurrence.offset_from) as u64)
+                .serialize(output)?;
+            previous_position = occurrence.position;
+        }
+    }
+    Ok(())
+}
+
+/// Wraps a token stream, and keeps a copy of its tokens.
+///
+/// This makes it possible to record the term vectors of a text while it is indexed,
+/// without tokenizing it twice.
+pub(crate) struct RecordingTokenStream<'a> {
+    token_stream: &'a mut dyn TokenStream,
+    tokens: &'a mut Vec<Token>,
+}
+
+impl<'a> RecordingTokenStream<'a> {
+    pub(crate) fn new(
+        token_stream: &'a mut dyn TokenStream,
+        tokens: &'a mut Vec<Token>,
+    ) -> RecordingTokenStream<'a> {
+        RecordingTokenStream {
+            token_stream,
+            tokens,
+        }
+    }
+}
+
+impl TokenStream for RecordingTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        if !self.token_stream.advance() {
+            return false;
+        }
+        self.tokens.push(self.token_stream.token().clone());
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.token_stream.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token_stream.token_mut()
+    }
+}
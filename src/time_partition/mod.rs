@@ -0,0 +1,53 @@
+//! Management of a family of time-partitioned indexes.
+//!
+//! Log-like data is often indexed in one index per hour or per day, so that searches
+//! only touch the partitions covering the requested time range, and old data can be
+//! expired by simply deleting whole partitions.
+//!
+//! A [`TimePartitionedIndex`] manages such a family of indexes under a root directory,
+//! with one subdirectory per partition:
+//! - writes are routed to the partition covering the value of the timestamp field,
+//! - searches run over the partitions overlapping a time range,
+//! - partitions older than a cutoff can be expired.
+//!
+//! ```rust
+//! use tantivy::collector::Count;
+//! use tantivy::query::AllQuery;
+//! use tantivy::schema::{Schema, INDEXED, STORED, TEXT};
+//! use tantivy::time_partition::{
+//!     PartitionGranularity, TimePartitionedIndex, TimePartitionedIndexOptions,
+//! };
+//! use tantivy::{doc, DateTime};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let timestamp = schema_builder.add_date_field("timestamp", INDEXED | STORED);
+//! let message = schema_builder.add_text_field("message", TEXT);
+//! let schema = schema_builder.build();
+//!
+//! let root = tempfile::TempDir::new()?;
+//! let options = TimePartitionedIndexOptions::builder()
+//!     .granularity(PartitionGranularity::Hourly)
+//!     .build();
+//! let mut index = TimePartitionedIndex::open_or_create(root.path(), schema, timestamp, options)?;
+//! let hour = 3_600;
+//! let at = DateTime::from_timestamp_secs;
+//! index.add_document(doc!(timestamp=>at(10), message=>"starting"))?;
+//! index.add_document(doc!(timestamp=>at(hour + 10), message=>"ready"))?;
+//! index.commit()?;
+//! assert_eq!(index.partitions().len(), 2);
+//!
+//! let searcher = index.searcher(at(hour)..at(2 * hour))?;
+//! assert_eq!(searcher.search(&AllQuery, &Count)?, 1);
+//!
+//! index.expire_before(at(hour))?;
+//! assert_eq!(index.partitions().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+mod partition_granularity;
+mod time_partitioned_index;
+
+pub use self::partition_granularity::PartitionGranularity;
+pub use self::time_partitioned_index::{TimePartitionedIndex, TimePartitionedIndexOptions};
@@ -0,0 +1,133 @@
+use time::{Date, Month, OffsetDateTime};
+
+use crate::DateTime;
+
+const SECONDS_PER_HOUR: i64 = 3_600;
+const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR;
+
+/// Time span covered by each partition of a
+/// [`TimePartitionedIndex`](super::TimePartitionedIndex).
+///
+/// Partitions are aligned on UTC hours or days.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PartitionGranularity {
+    /// One partition per hour, stored in a directory named like `2024-01-02T03`.
+    Hourly,
+    /// One partition per day, stored in a directory named like `2024-01-02`.
+    #[default]
+    Daily,
+}
+
+impl PartitionGranularity {
+    fn num_seconds(self) -> i64 {
+        match self {
+            PartitionGranularity::Hourly => SECONDS_PER_HOUR,
+            PartitionGranularity::Daily => SECONDS_PER_DAY,
+        }
+    }
+
+    /// Returns the start of the partition containing `timestamp`.
+    pub fn partition_start(self, timestamp: DateTime) -> DateTime {
+        let secs = timestamp.into_timestamp_secs();
+        DateTime::from_timestamp_secs(secs - secs.rem_euclid(self.num_seconds()))
+    }
+
+    /// Returns the end (excluded) of the partition starting at `partition_start`.
+    pub fn partition_end(self, partition_start: DateTime) -> DateTime {
+        DateTime::from_timestamp_secs(partition_start.into_timestamp_secs() + self.num_seconds())
+    }
+
+    /// Returns the name of the directory of the partition starting at `partition_start`.
+    pub(crate) fn partition_name(self, partition_start: DateTime) -> String {
+        let datetime = partition_start.into_utc();
+        let day = format!(
+            "{:04}-{:02}-{:02}",
+            datetime.year(),
+            u8::from(datetime.month()),
+            datetime.day()
+        );
+        match self {
+            PartitionGranularity::Hourly => format!("{day}T{:02}", datetime.hour()),
+            PartitionGranularity::Daily => day,
+        }
+    }
+
+    /// Parses the name of the directory of a partition, returning the start of the partition.
+    pub(crate) fn parse_partition_name(self, partition_name: &str) -> Option<DateTime> {
+        let (day, hour) = match self {
+            PartitionGranularity::Hourly => {
+                let (day, hour) = partition_name.split_once('T')?;
+                (day, parse_digits(hour, 2)?)
+            }
+            PartitionGranularity::Daily => (partition_name, 0),
+        };
+        let mut day_parts = day.split('-');
+        let year = parse_digits(day_parts.next()?, 4)?;
+        let month = Month::try_from(parse_digits(day_parts.next()?, 2)? as u8).ok()?;
+        let day_of_month = parse_digits(day_parts.next()?, 2)? as u8;
+        if day_parts.next().is_some() {
+            return None;
+        }
+        let date = Date::from_calendar_date(year as i32, month, day_of_month).ok()?;
+        let datetime: OffsetDateTime = date.with_hms(hour as u8, 0, 0).ok()?.assume_utc();
+        Some(DateTime::from_utc(datetime))
+    }
+}
+
+fn parse_digits(text: &str, num_digits: usize) -> Option<u32> {
+    if text.len() != num_digits || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_start() {
+        let timestamp = DateTime::from_timestamp_secs(SECONDS_PER_DAY + 2 * SECONDS_PER_HOUR + 5);
+        assert_eq!(
+            PartitionGranularity::Hourly.partition_start(timestamp),
+            DateTime::from_timestamp_secs(SECONDS_PER_DAY + 2 * SECONDS_PER_HOUR)
+        );
+        assert_eq!(
+            PartitionGranularity::Daily.partition_start(timestamp),
+            DateTime::from_timestamp_secs(SECONDS_PER_DAY)
+        );
+        assert_eq!(
+            PartitionGranularity::Daily.partition_start(DateTime::from_timestamp_secs(-1)),
+            DateTime::from_timestamp_secs(-SECONDS_PER_DAY)
+        );
+    }
+
+    #[test]
+    fn test_partition_name() {
+        let timestamp = DateTime::from_timestamp_secs(1_704_164_645); // 2024-01-02T03:04:05Z
+        for (granularity, expected_name) in [
+            (PartitionGranularity::Hourly, "2024-01-02T03"),
+            (PartitionGranularity::Daily, "2024-01-02"),
+        ] {
+            let partition_start = granularity.partition_start(timestamp);
+            let partition_name = granularity.partition_name(partition_start);
+            assert_eq!(partition_name, expected_name);
+            assert_eq!(
+                granularity.parse_partition_name(&partition_name),
+                Some(partition_start)
+            );
+        }
+        assert_eq!(
+            PartitionGranularity::Daily.parse_partition_name("2024-01-02T03"),
+            None
+        );
+        assert_eq!(
+            PartitionGranularity::Hourly.parse_partition_name("2024-01-02"),
+            None
+        );
+        assert_eq!(
+            PartitionGranularity::Daily.parse_partition_name("2024-13-02"),
+            None
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use super::PartitionGranularity;
+use crate::directory::MmapDirectory;
+use crate::indexer::IndexWriterOptions;
+use crate::schema::document::Value;
+use crate::schema::{Field, FieldType, Schema, TantivyDocument};
+use crate::tokenizer::TokenizerManager;
+use crate::{
+    DateTime, Index, IndexReader, IndexSettings, IndexWriter, Opstamp, ReloadPolicy, Searcher,
+    TantivyError,
+};
+
+#[derive(Clone, bon::Builder)]
+/// Options of a [`TimePartitionedIndex`].
+pub struct TimePartitionedIndexOptions {
+    #[builder(default)]
+    /// Time span covered by each partition.
+    granularity: PartitionGranularity,
+    #[builder(default)]
+    /// Settings of the indexes created for new partitions.
+    index_settings: IndexSettings,
+    #[builder(default)]
+    /// Tokenizers registered on all of the partitions.
+    tokenizers: TokenizerManager,
+    #[builder(default = IndexWriterOptions::builder().build())]
+    /// Options of the writer of each partition.
+    ///
+    /// A writer is opened for every partition receiving documents, so the memory used
+    /// for indexing grows with the number of partitions written concurrently.
+    writer_options: IndexWriterOptions,
+}
+
+impl Default for TimePartitionedIndexOptions {
+    fn default() -> Self {
+        TimePartitionedIndexOptions::builder().build()
+    }
+}
+
+struct Partition {
+    index: Index,
+    reader: IndexReader,
+    // Opened on the first document added to the partition.
+    writer: Option<IndexWriter>,
+}
+
+/// A family of indexes, each holding the documents of a time partition.
+///
+/// See the [module documentation](super) for an example.
+pub struct TimePartitionedIndex {
+    root: PathBuf,
+    schema: Schema,
+    timestamp_field: Field,
+    options: TimePartitionedIndexOptions,
+    partitions: BTreeMap<DateTime, Partition>,
+}
+
+impl TimePartitionedIndex {
+    /// Opens the partitions found in `root`, creating the directory if needed.
+    ///
+    /// Documents are routed to partitions according to the value of `timestamp_field`,
+    /// which must be a date field of `schema`. Returns an error if an existing partition
+    /// has a different schema.
+    pub fn open_or_create<P: AsRef<Path>>(
+        root: P,
+        schema: Schema,
+        timestamp_field: Field,
+        options: TimePartitionedIndexOptions,
+    ) -> crate::Result<TimePartitionedIndex> {
+        let timestamp_field_entry = schema.get_field_entry(timestamp_field);
+        if !matches!(timestamp_field_entry.field_type(), FieldType::Date(_)) {
+            return Err(TantivyError::SchemaError(format!(
+                "The timestamp field {} must be a date field",
+                timestamp_field_entry.name()
+            )));
+        }
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root)?;
+        let mut time_partitioned_index = TimePartitionedIndex {
+            root,
+            schema,
+            timestamp_field,
+            options,
+            partitions: BTreeMap::new(),
+        };
+        for dir_entry in fs::read_dir(&time_partitioned_index.root)? {
+            let dir_entry = dir_entry?;
+            if !dir_entry.file_type()?.is_dir() {
+                continue;
+            }
+            let Some(partition_start) = dir_entry.file_name().to_str().and_then(|name| {
+                time_partitioned_index
+                    .options
+                    .granularity
+                    .parse_partition_name(name)
+            }) else {
+                continue;
+            };
+            let partition = time_partitioned_index.open_partition(partition_start)?;
+            time_partitioned_index
+                .partitions
+                .insert(partition_start, partition);
+        }
+        Ok(time_partitioned_index)
+    }
+
+    fn partition_path(&self, partition_start: DateTime) -> PathBuf {
+        self.root
+            .join(self.options.granularity.partition_name(partition_start))
+    }
+
+    fn open_partition(&self, partition_start: DateTime) -> crate::Result<Partition> {
+        let partition_path = self.partition_path(partition_start);
+        fs::create_dir_all(&partition_path)?;
+        let index = Index::builder()
+            .schema(self.schema.clone())
+            .settings(self.options.index_settings.clone())
+            .tokenizers(self.options.tokenizers.clone())
+            .open_or_create(MmapDirectory::open(&partition_path)?)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(Partition {
+            index,
+            reader,
+            writer: None,
+        })
+    }
+
+    /// Returns the schema shared by all of the partitions.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns the start of each partition, in chronological order.
+    pub fn partitions(&self) -> Vec<DateTime> {
+        self.partitions.keys().copied().collect()
+    }
+
+    /// Returns the index of the partition starting at `partition_start`, if it exists.
+    pub fn partition_index(&self, partition_start: DateTime) -> Option<&Index> {
+        self.partitions
+            .get(&partition_start)
+            .map(|partition| &partition.index)
+    }
+
+    /// Adds a document to the partition covering the value of its timestamp field,
+    /// creating the partition if needed.
+    ///
+    /// Returns the opstamp of the operation within the writer of the partition.
+    /// Returns an error if the document has no timestamp.
+    pub fn add_document(&mut self, document: TantivyDocument) -> crate::Result<Opstamp> {
+        let timestamp = document
+            .get_first(self.timestamp_field)
+            .and_then(|value| value.as_datetime())
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "The document does not have a value for the timestamp field {}",
+                    self.schema.get_field_name(self.timestamp_field)
+                ))
+            })?;
+        let partition_start = self.options.granularity.partition_start(timestamp);
+        if !self.partitions.contains_key(&partition_start) {
+            let partition = self.open_partition(partition_start)?;
+            self.partitions.insert(partition_start, partition);
+        }
+        let partition = self.partitions.get_mut(&partition_start).unwrap();
+        if partition.writer.is_none() {
+            let writer = partition
+                .index
+                .writer_with_options(self.options.writer_options.clone())?;
+            partition.writer = Some(writer);
+        }
+        partition.writer.as_ref().unwrap().add_document(document)
+    }
+
+    /// Commits the documents added to each partition, and makes them searchable.
+    pub fn commit(&mut self) -> crate::Result<()> {
+        for partition in self.partitions.values_mut() {
+            if let Some(writer) = partition.writer.as_mut() {
+                writer.commit()?;
+                partition.reader.reload()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a searcher over the partitions overlapping `time_range`.
+    ///
+    /// Partitions are searched as a whole, so documents slightly outside of `time_range`
+    /// may match: queries requiring an exact time range should also filter on the
+    /// timestamp field. The `Searcher` reports the index of the oldest searched
+    /// partition as its [`Searcher::index()`].
+    pub fn searcher(&self, time_range: Range<DateTime>) -> crate::Result<Searcher> {
+        let granularity = self.options.granularity;
+        let mut index_opt = None;
+        let mut segment_readers = Vec::new();
+        for (&partition_start, partition) in self.partitions.range(..time_range.end) {
+            if granularity.partition_end(partition_start) <= time_range.start {
+                continue;
+            }
+            index_opt.get_or_insert_with(|| partition.index.clone());
+            segment_readers.extend_from_slice(partition.reader.searcher().segment_readers());
+        }
+        let index = match index_opt {
+            Some(index) => index,
+            None => Index::builder()
+                .schema(self.schema.clone())
+                .tokenizers(self.options.tokenizers.clone())
+                .create_in_ram()?,
+        };
+        Searcher::from_segment_readers(index, segment_readers)
+    }
+
+    /// Deletes the partitions ending before `cutoff`, returning their start.
+    pub fn expire_before(&mut self, cutoff: DateTime) -> crate::Result<Vec<DateTime>> {
+        let granularity = self.options.granularity;
+        let expired_partition_starts: Vec<DateTime> = self
+            .partitions
+            .keys()
+            .copied()
+            .filter(|&partition_start| granularity.partition_end(partition_start) <= cutoff)
+            .collect();
+        for &partition_start in &expired_partition_starts {
+            // Dropping the partition stops its writer and releases its lock.
+            drop(self.partitions.remove(&partition_start));
+            fs::remove_dir_all(self.partition_path(partition_start))?;
+        }
+        Ok(expired_partition_starts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Count;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, INDEXED, STORED, STRING};
+    use crate::Term;
+
+    const HOUR: i64 = 3_600;
+
+    fn test_schema() -> (Schema, Field, Field) {
+        let mut schema_builder = Schema::builder();
+        let timestamp_field = schema_builder.add_date_field("timestamp", INDEXED | STORED);
+        let level_field = schema_builder.add_text_field("level", STRING);
+        (schema_builder.build(), timestamp_field, level_field)
+    }
+
+    fn hourly() -> TimePartitionedIndexOptions {
+        TimePartitionedIndexOptions::builder()
+            .granularity(PartitionGranularity::Hourly)
+            .build()
+    }
+
+    #[test]
+    fn test_time_partitioned_index() -> crate::Result<()> {
+        let (schema, timestamp_field, level_field) = test_schema();
+        let root = tempfile::TempDir::new()?;
+        let mut index = TimePartitionedIndex::open_or_create(
+            root.path(),
+            schema.clone(),
+            timestamp_field,
+            hourly(),
+        )?;
+        for (secs, level) in [
+            (10, "info"),
+            (HOUR + 10, "warn"),
+            (HOUR + 20, "info"),
+            (3 * HOUR, "info"),
+        ] {
+            index.add_document(doc!(
+                timestamp_field=>DateTime::from_timestamp_secs(secs),
+                level_field=>level
+            ))?;
+        }
+        assert!(matches!(
+            index.add_document(doc!(level_field=>"info")),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        index.commit()?;
+        let hours = |hours: Range<i64>| {
+            DateTime::from_timestamp_secs(hours.start * HOUR)
+                ..DateTime::from_timestamp_secs(hours.end * HOUR)
+        };
+        assert_eq!(
+            index.partitions(),
+            vec![hours(0..1).start, hours(1..2).start, hours(3..4).start]
+        );
+
+        let searcher = index.searcher(hours(1..4))?;
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 3);
+        let info_query = TermQuery::new(
+            Term::from_field_text(level_field, "info"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&info_query, &Count)?, 2);
+        assert_eq!(index.searcher(hours(2..3))?.num_docs(), 0);
+        assert_eq!(index.searcher(hours(-5..5))?.num_docs(), 4);
+        drop(index);
+
+        // Partitions are found when the index is reopened.
+        let mut index =
+            TimePartitionedIndex::open_or_create(root.path(), schema, timestamp_field, hourly())?;
+        assert_eq!(index.partitions().len(), 3);
+        assert_eq!(
+            index.expire_before(hours(2..3).start)?,
+            vec![hours(0..1).start, hours(1..2).start]
+        );
+        assert_eq!(index.partitions(), vec![hours(3..4).start]);
+        assert_eq!(index.searcher(hours(-5..5))?.num_docs(), 1);
+        assert_eq!(fs::read_dir(root.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_partitioned_index_requires_date_field() -> crate::Result<()> {
+        let (schema, _, level_field) = test_schema();
+        let root = tempfile::TempDir::new()?;
+        assert!(matches!(
+            TimePartitionedIndex::open_or_create(root.path(), schema, level_field, hourly()),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,477 @@
+//! Hierarchical Navigable Small World graphs, used for approximate nearest neighbor search.
+//!
+//! The nodes of the graph are the vector ordinals of a field. Each node is assigned a random
+//! level, with an exponentially decaying probability, and is linked to its nearest neighbors
+//! in every layer up to its level. A search greedily descends the sparse upper layers to find
+//! a good entry point in the base layer, which contains all of the nodes.
+//!
+//! # Format
+//!
+//! The graph is serialized after the vectors of the field, as:
+//! - the maximum number of connections `m` (u32)
+//! - the entry point (u32)
+//! - the number of layers (u32)
+//! - for each layer, from the base layer up:
+//!   - the number of nodes in the layer (u32)
+//!   - the sorted ordinals of these nodes (u32 each), except for the base layer which contains
+//!     all of the nodes
+//!   - for each node, the number of neighbors followed by the neighbors (u32 each), padded to the
+//!     maximum number of neighbors of the layer: `2 * m` in the base layer, `m` above.
+//!
+//! This fixed-width layout gives random access to the neighbors of any node without
+//! deserializing the graph.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::io::{self, Write};
+
+use common::{BinarySerializable, BitSet};
+
+use crate::directory::OwnedBytes;
+use crate::schema::{HnswOptions, VectorSimilarity};
+use crate::Score;
+
+const MAX_LEVEL: usize = 15;
+
+const U32_NUM_BYTES: usize = std::mem::size_of::<u32>();
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Candidate {
+    pub score: Score,
+    pub ord: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    // Higher scores first, then lower ordinals first.
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.ord.cmp(&self.ord))
+    }
+}
+
+fn splitmix64(val: u64) -> u64 {
+    let mut z = val.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Returns the level of a node.
+///
+/// The level is derived from a hash of the ordinal, so that building a graph is deterministic.
+fn node_level(ord: u32, max_connections: usize) -> usize {
+    // Uniform in `(0, 1]`.
+    let uniform = ((splitmix64(ord as u64) >> 11) + 1) as f64 / (1u64 << 53) as f64;
+    let level_multiplier = 1.0 / (max_connections.max(2) as f64).ln();
+    ((-uniform.ln() * level_multiplier) as usize).min(MAX_LEVEL)
+}
+
+/// Best-first search of the `ef` nodes most similar to the query within a layer.
+///
+/// Nodes rejected by `accept` are traversed, but are not part of the results.
+/// Returns the results sorted by decreasing score.
+fn search_layer(
+    entry_points: &[u32],
+    ef: usize,
+    visited: &mut BitSet,
+    mut score_fn: impl FnMut(u32) -> Score,
+    mut neighbors_fn: impl FnMut(u32, &mut Vec<u32>),
+    mut accept: impl FnMut(u32) -> bool,
+) -> Vec<Candidate> {
+    visited.clear();
+    let mut candidates: BinaryHeap<Candidate> = BinaryHeap::new();
+    let mut results: BinaryHeap<Reverse<Candidate>> = BinaryHeap::new();
+    let push_result = |results: &mut BinaryHeap<Reverse<Candidate>>, candidate: Candidate| {
+        results.push(Reverse(candidate));
+        if results.len() > ef {
+            results.pop();
+        }
+    };
+    for &ord in entry_points {
+        if visited.contains(ord) {
+            continue;
+        }
+        visited.insert(ord);
+        let candidate = Candidate {
+            score: score_fn(ord),
+            ord,
+        };
+        candidates.push(candidate);
+        if accept(ord) {
+            push_result(&mut results, candidate);
+        }
+    }
+    let mut neighbors = Vec::new();
+    while let Some(candidate) = candidates.pop() {
+        if results.len() >= ef && candidate.score < results.peek().unwrap().0.score {
+            break;
+        }
+        neighbors_fn(candidate.ord, &mut neighbors);
+        for &neighbor in &neighbors {
+            if visited.contains(neighbor) {
+                continue;
+            }
+            visited.insert(neighbor);
+            let score = score_fn(neighbor);
+            if results.len() < ef || score > results.peek().unwrap().0.score {
+                let neighbor_candidate = Candidate {
+                    score,
+                    ord: neighbor,
+                };
+                candidates.push(neighbor_candidate);
+                if accept(neighbor) {
+                    push_result(&mut results, neighbor_candidate);
+                }
+            }
+        }
+    }
+    let mut results: Vec<Candidate> = results
+        .into_iter()
+        .map(|Reverse(candidate)| candidate)
+        .collect();
+    results.sort_unstable_by(|left, right| right.cmp(left));
+    results
+}
+
+/// Builds the HNSW graph of the vectors of a field.
+pub(crate) struct HnswBuilder<'a> {
+    values: &'a [f32],
+    dims: usize,
+    similarity: VectorSimilarity,
+    max_connections: usize,
+    ef_construction: usize,
+    // For each node, its neighbors in each layer up to its level.
+    neighbors: Vec<Vec<Vec<u32>>>,
+    entry_point: u32,
+    visited: BitSet,
+}
+
+impl<'a> HnswBuilder<'a> {
+    /// Builds the graph of `num_vectors` vectors, the vector of ordinal `i` being
+    /// `values[i * dims..(i + 1) * dims]`.
+    pub fn build(
+        values: &'a [f32],
+        num_vectors: u32,
+        dims: usize,
+        similarity: VectorSimilarity,
+        hnsw_options: HnswOptions,
+    ) -> HnswBuilder<'a> {
+        let mut builder = HnswBuilder {
+            values,
+            dims,
+            similarity,
+            max_connections: hnsw_options.max_connections.max(1) as usize,
+            ef_construction: hnsw_options.ef_construction.max(1) as usize,
+            neighbors: Vec::with_capacity(num_vectors as usize),
+            entry_point: 0,
+            visited: BitSet::with_max_value(num_vectors),
+        };
+        for ord in 0..num_vectors {
+            builder.insert(ord);
+        }
+        builder
+    }
+
+    fn vector(&self, ord: u32) -> &'a [f32] {
+        let start = ord as usize * self.dims;
+        &self.values[start..start + self.dims]
+    }
+
+    fn max_neighbors(&self, layer: usize) -> usize {
+        if layer == 0 {
+            2 * self.max_connections
+        } else {
+            self.max_connections
+        }
+    }
+
+    fn search(
+        &mut self,
+        query: &[f32],
+        entry_points: &[u32],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let (values, dims, similarity) = (self.values, self.dims, self.similarity);
+        let neighbors = &self.neighbors;
+        search_layer(
+            entry_points,
+            ef,
+            &mut self.visited,
+            |ord| {
+                let start = ord as usize * dims;
+                similarity.score(query, &values[start..start + dims])
+            },
+            |ord, output| {
+                output.clear();
+                output.extend_from_slice(&neighbors[ord as usize][layer]);
+            },
+            |_| true,
+        )
+    }
+
+    /// Selects up to `max_neighbors` neighbors among `candidates`, sorted by decreasing score.
+    ///
+    /// A candidate is preferred if it is more similar to the node than to the already selected
+    /// neighbors, which keeps the graph navigable across clusters.
+    fn select_neighbors(&self, candidates: &[Candidate], max_neighbors: usize) -> Vec<u32> {
+        let mut selected: Vec<u32> = Vec::with_capacity(max_neighbors);
+        let mut discarded: Vec<u32> = Vec::new();
+        for candidate in candidates {
+            if selected.len() >= max_neighbors {
+                break;
+            }
+            let candidate_vector = self.vector(candidate.ord);
+            let is_diverse = selected.iter().all(|&selected_ord| {
+                self.similarity
+                    .score(candidate_vector, self.vector(selected_ord))
+                    < candidate.score
+            });
+            if is_diverse {
+                selected.push(candidate.ord);
+            } else {
+                discarded.push(candidate.ord);
+            }
+        }
+        let num_missing = max_neighbors.saturating_sub(selected.len());
+        selected.extend(discarded.into_iter().take(num_missing));
+        selected
+    }
+
+    fn prune_neighbors(&mut self, ord: u32, layer: usize) {
+        let vector = self.vector(ord);
+        let mut candidates: Vec<Candidate> = self.neighbors[ord as usize][layer]
+            .iter()
+            .map(|&neighbor| Candidate {
+                score: self.similarity.score(vector, self.vector(neighbor)),
+                ord: neighbor,
+            })
+            .collect();
+        candidates.sort_unstable_by(|left, right| right.cmp(left));
+        self.neighbors[ord as usize][layer] =
+            self.select_neighbors(&candidates, self.max_neighbors(layer));
+    }
+
+    fn insert(&mut self, ord: u32) {
+        let level = node_level(ord, self.max_connections);
+        self.neighbors.push(vec![Vec::new(); level + 1]);
+        if ord == 0 {
+            self.entry_point = 0;
+            return;
+        }
+        let query = self.vector(ord);
+        let top_level = self.neighbors[self.entry_point as usize].len() - 1;
+        let mut entry_points = vec![self.entry_point];
+        for layer in (level + 1..=top_level).rev() {
+            let closest = self.search(query, &entry_points, 1, layer);
+            entry_points = vec![closest[0].ord];
+        }
+        for layer in (0..=level.min(top_level)).rev() {
+            let candidates = self.search(query, &entry_points, self.ef_construction, layer);
+            let max_neighbors = self.max_neighbors(layer);
+            let selected = self.select_neighbors(&candidates, max_neighbors);
+            for &neighbor in &selected {
+                self.neighbors[neighbor as usize][layer].push(ord);
+                if self.neighbors[neighbor as usize][layer].len() > max_neighbors {
+                    self.prune_neighbors(neighbor, layer);
+                }
+            }
+            self.neighbors[ord as usize][layer] = selected;
+            entry_points = candidates.iter().map(|candidate| candidate.ord).collect();
+        }
+        if level > top_level {
+            self.entry_point = ord;
+        }
+    }
+
+    /// Serializes the graph, see the [module documentation](self) for the format.
+    pub fn serialize(&self, write: &mut impl Write) -> io::Result<()> {
+        let num_layers = self
+            .neighbors
+            .get(self.entry_point as usize)
+            .map(Vec::len)
+            .unwrap_or(0);
+        (self.max_connections as u32).serialize(write)?;
+        self.entry_point.serialize(write)?;
+        (num_layers as u32).serialize(write)?;
+        for layer in 0..num_layers {
+            let layer_nodes: Vec<u32> = (0..self.neighbors.len() as u32)
+                .filter(|&ord| self.neighbors[ord as usize].len() > layer)
+                .collect();
+            (layer_nodes.len() as u32).serialize(write)?;
+            if layer > 0 {
+                for &ord in &layer_nodes {
+                    ord.serialize(write)?;
+                }
+            }
+            let max_neighbors = self.max_neighbors(layer);
+            for &ord in &layer_nodes {
+                let neighbors = &self.neighbors[ord as usize][layer];
+                (neighbors.len() as u32).serialize(write)?;
+                for &neighbor in neighbors {
+                    neighbor.serialize(write)?;
+                }
+                for _ in neighbors.len()..max_neighbors {
+                    0u32.serialize(write)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_u32(bytes: &[u8], idx: usize) -> u32 {
+    let start = idx * U32_NUM_BYTES;
+    u32::from_le_bytes(bytes[start..start + U32_NUM_BYTES].try_into().unwrap())
+}
+
+#[derive(Clone)]
+struct GraphLayer {
+    num_nodes: u32,
+    // Sorted ordinals of the nodes of the layer, `None` for the base layer.
+    nodes: Option<OwnedBytes>,
+    // Number of neighbors of each node, followed by the padded neighbors.
+    slots: OwnedBytes,
+    slot_len: usize,
+}
+
+impl GraphLayer {
+    fn node_position(&self, ord: u32) -> Option<usize> {
+        let Some(nodes) = &self.nodes else {
+            return Some(ord as usize);
+        };
+        let (mut lo, mut hi) = (0usize, self.num_nodes as usize);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match read_u32(nodes.as_slice(), mid).cmp(&ord) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    fn neighbors(&self, ord: u32, output: &mut Vec<u32>) {
+        output.clear();
+        let Some(position) = self.node_position(ord) else {
+            return;
+        };
+        let slot_start = position * self.slot_len;
+        let slots = self.slots.as_slice();
+        let num_neighbors = read_u32(slots, slot_start) as usize;
+        output.extend((0..num_neighbors).map(|idx| read_u32(slots, slot_start + 1 + idx)));
+    }
+}
+
+/// Reads the HNSW graph of the vectors of a field.
+#[derive(Clone)]
+pub(crate) struct HnswGraph {
+    entry_point: u32,
+    layers: Vec<GraphLayer>,
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl HnswGraph {
+    /// Opens a serialized graph.
+    pub fn open(mut bytes: OwnedBytes) -> io::Result<HnswGraph> {
+        let max_connections = u32::deserialize(&mut bytes)? as usize;
+        let entry_point = u32::deserialize(&mut bytes)?;
+        let num_layers = u32::deserialize(&mut bytes)? as usize;
+        let mut layers = Vec::with_capacity(num_layers);
+        for layer in 0..num_layers {
+            let num_nodes = u32::deserialize(&mut bytes)?;
+            let nodes = if layer > 0 {
+                let num_bytes = num_nodes as usize * U32_NUM_BYTES;
+                if bytes.len() < num_bytes {
+                    return Err(invalid_data("HNSW graph is truncated."));
+                }
+                let (nodes, rest) = bytes.split(num_bytes);
+                bytes = rest;
+                Some(nodes)
+            } else {
+                None
+            };
+            let max_neighbors = if layer == 0 {
+                2 * max_connections
+            } else {
+                max_connections
+            };
+            let slot_len = 1 + max_neighbors;
+            let num_bytes = num_nodes as usize * slot_len * U32_NUM_BYTES;
+            if bytes.len() < num_bytes {
+                return Err(invalid_data("HNSW graph is truncated."));
+            }
+            let (slots, rest) = bytes.split(num_bytes);
+            bytes = rest;
+            layers.push(GraphLayer {
+                num_nodes,
+                nodes,
+                slots,
+                slot_len,
+            });
+        }
+        if !bytes.is_empty() {
+            return Err(invalid_data("HNSW graph has trailing bytes."));
+        }
+        Ok(HnswGraph {
+            entry_point,
+            layers,
+        })
+    }
+
+    /// Searches the `ef` nodes most similar to the query, among the nodes accepted by `accept`.
+    ///
+    /// `score_fn` returns the similarity of the query with the vector of a given ordinal.
+    /// Returns the results sorted by decreasing score.
+    pub fn search(
+        &self,
+        num_vectors: u32,
+        ef: usize,
+        mut score_fn: impl FnMut(u32) -> Score,
+        accept: impl FnMut(u32) -> bool,
+    ) -> Vec<Candidate> {
+        let Some((base_layer, upper_layers)) = self.layers.split_first() else {
+            return Vec::new();
+        };
+        let mut visited = BitSet::with_max_value(num_vectors);
+        let mut entry_points = vec![self.entry_point];
+        for layer in upper_layers.iter().rev() {
+            let closest = search_layer(
+                &entry_points,
+                1,
+                &mut visited,
+                &mut score_fn,
+                |ord, output| layer.neighbors(ord, output),
+                |_| true,
+            );
+            entry_points = vec![closest[0].ord];
+        }
+        search_layer(
+            &entry_points,
+            ef,
+            &mut visited,
+            score_fn,
+            |ord, output| base_layer.neighbors(ord, output),
+            accept,
+        )
+    }
+}
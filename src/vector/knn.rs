@@ -0,0 +1,147 @@
+use crate::collector::{Collector, SegmentCollector, TopNComputer};
+use crate::schema::{Field, FieldType, Schema, VectorOptions, VectorSimilarity};
+use crate::vector::VectorReader;
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Returns the options of the vector field `field`.
+///
+/// Returns an error if the field is not a vector field, or if the query vector
+/// does not have the number of dimensions of the field.
+pub(crate) fn vector_options_for_query(
+    schema: &Schema,
+    field: Field,
+    query_vector: &[f32],
+) -> crate::Result<VectorOptions> {
+    let field_entry = schema.get_field_entry(field);
+    let vector_options = match field_entry.field_type() {
+        FieldType::Bytes(bytes_options) => bytes_options.get_vector_options(),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        TantivyError::SchemaError(format!(
+            "Field {:?} is not a vector field.",
+            field_entry.name()
+        ))
+    })?;
+    if vector_options.dims() as usize != query_vector.len() {
+        return Err(TantivyError::InvalidArgument(format!(
+            "Expected a query vector of {} dimensions, got {}",
+            vector_options.dims(),
+            query_vector.len()
+        )));
+    }
+    Ok(vector_options)
+}
+
+/// Collector computing the `k` nearest neighbors of a query vector, by brute force.
+///
+/// The similarity of the query vector with the vector of every collected document
+/// is computed exactly. Documents without a vector are ignored.
+/// This collector can therefore be used with any query acting as a filter, or as a
+/// rescoring stage for a small set of candidates.
+///
+/// The fruit is the list of `(similarity score, doc address)`, sorted by decreasing score.
+/// See [`Searcher::knn`](crate::Searcher::knn).
+pub struct KnnCollector {
+    field: Field,
+    query_vector: Vec<f32>,
+    k: usize,
+}
+
+impl KnnCollector {
+    /// Creates a collector returning the `k` documents whose vector in `field` are the most
+    /// similar to `query_vector`.
+    pub fn new(field: Field, query_vector: Vec<f32>, k: usize) -> KnnCollector {
+        KnnCollector {
+            field,
+            query_vector,
+            k,
+        }
+    }
+}
+
+impl Collector for KnnCollector {
+    type Fruit = Vec<(Score, DocAddress)>;
+
+    type Child = KnnSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<KnnSegmentCollector> {
+        let vector_options =
+            vector_options_for_query(reader.schema(), self.field, &self.query_vector)?;
+        let vector_reader_opt = reader.vectors_readers().get_field(self.field)?;
+        Ok(KnnSegmentCollector {
+            segment_ord: segment_local_id,
+            vector_reader_opt,
+            similarity: vector_options.similarity(),
+            query_vector: self.query_vector.clone(),
+            vector_buffer: Vec::with_capacity(self.query_vector.len()),
+            top_n: TopNComputer::new(self.k),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn memory_estimate(&self) -> usize {
+        TopNComputer::<Score, DocId>::buffer_num_bytes(self.k)
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(Score, DocAddress)>>,
+    ) -> crate::Result<Vec<(Score, DocAddress)>> {
+        if self.k == 0 {
+            return Ok(Vec::new());
+        }
+        let mut top_n: TopNComputer<Score, DocAddress> = TopNComputer::new(self.k);
+        for (score, doc_address) in segment_fruits.into_iter().flatten() {
+            top_n.push(score, doc_address);
+        }
+        Ok(top_n
+            .into_sorted_vec()
+            .into_iter()
+            .map(|cdoc| (cdoc.feature, cdoc.doc))
+            .collect())
+    }
+}
+
+/// Segment collector associated with the [`KnnCollector`].
+pub struct KnnSegmentCollector {
+    segment_ord: SegmentOrdinal,
+    vector_reader_opt: Option<VectorReader>,
+    similarity: VectorSimilarity,
+    query_vector: Vec<f32>,
+    vector_buffer: Vec<f32>,
+    top_n: TopNComputer<Score, DocId>,
+}
+
+impl SegmentCollector for KnnSegmentCollector {
+    type Fruit = Vec<(Score, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let Some(vector_reader) = &self.vector_reader_opt else {
+            return;
+        };
+        if !vector_reader.read_doc_vector(doc, &mut self.vector_buffer) {
+            return;
+        }
+        let score = self
+            .similarity
+            .score(&self.query_vector, &self.vector_buffer);
+        self.top_n.push(score, doc);
+    }
+
+    fn harvest(self) -> Vec<(Score, DocAddress)> {
+        let segment_ord = self.segment_ord;
+        self.top_n
+            .into_sorted_vec()
+            .into_iter()
+            .map(|cdoc| (cdoc.feature, DocAddress::new(segment_ord, cdoc.doc)))
+            .collect()
+    }
+}
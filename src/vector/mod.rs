@@ -0,0 +1,180 @@
+//! Dense vectors, and exact nearest neighbor search.
+//!
+//! A vector field is a bytes field set with
+//! [`BytesOptions::set_vector`](crate::schema::BytesOptions::set_vector).
+//! Each document has at most one vector per vector field, given as the little-endian
+//! representation of its `f32` components (see [`vector_to_bytes`]).
+//!
+//! The vectors of a segment are stored column-oriented in their own segment component,
+//! as a sorted list of doc ids followed by the fixed-width vectors, which gives random
+//! access to the vector of any document.
+//!
+//! [`Searcher::knn`](crate::Searcher::knn) computes the similarity of a query vector with
+//! the vectors of all documents matching an optional filter query, and returns the top `k`.
+//!
+//! For large segments, an HNSW graph can be built over the vectors of a field with
+//! [`VectorOptions::set_hnsw`](crate::schema::VectorOptions::set_hnsw). The graph is built
+//! when a segment is serialized, rebuilt when segments are merged, and used by
+//! [`KnnQuery`](crate::query::KnnQuery) for approximate nearest neighbor search.
+//!
+//! For hybrid search, [`FusionQuery`](crate::query::FusionQuery) merges the nearest neighbors
+//! with the top documents of a lexical query.
+mod hnsw;
+mod knn;
+mod reader;
+mod serializer;
+mod similarity;
+mod writer;
+
+pub(crate) use self::knn::vector_options_for_query;
+pub use self::knn::{KnnCollector, KnnSegmentCollector};
+pub use self::reader::{VectorReader, VectorsReaders};
+pub use self::serializer::VectorsSerializer;
+pub use self::similarity::{cosine_similarity, dot_product, squared_euclidean_distance};
+pub use self::writer::VectorsWriter;
+
+/// Returns the bytes representation of a vector, as expected in a vector field.
+pub fn vector_to_bytes(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|val| val.to_le_bytes()).collect()
+}
+
+/// Decodes a vector from its bytes representation.
+///
+/// Trailing bytes that do not form a complete `f32` are ignored.
+pub fn bytes_to_vector(bytes: &[u8]) -> impl Iterator<Item = f32> + '_ {
+    bytes
+        .chunks_exact(std::mem::size_of::<f32>())
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::TermQuery;
+    use crate::schema::{
+        BytesOptions, IndexRecordOption, Schema, VectorOptions, VectorSimilarity, STRING,
+    };
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_similarities() {
+        let left: Vec<f32> = (0..19).map(|i| i as f32).collect();
+        let right: Vec<f32> = (0..19).map(|i| (i % 3) as f32 - 1.0).collect();
+        let naive_dot: f32 = left.iter().zip(&right).map(|(l, r)| l * r).sum();
+        assert_nearly_equals!(dot_product(&left, &right), naive_dot);
+        let naive_l2: f32 = left
+            .iter()
+            .zip(&right)
+            .map(|(l, r)| (l - r) * (l - r))
+            .sum();
+        assert_nearly_equals!(squared_euclidean_distance(&left, &right), naive_l2);
+        assert_nearly_equals!(cosine_similarity(&[1.0, 0.0], &[0.0, 2.0]), 0.0);
+        assert_nearly_equals!(cosine_similarity(&[1.0, 1.0], &[2.0, 2.0]), 1.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[2.0, 2.0]), 0.0);
+        assert_nearly_equals!(
+            VectorSimilarity::Euclidean.score(&[0.0, 1.0], &[1.0, 0.0]),
+            1.0 / 3.0
+        );
+    }
+
+    #[test]
+    fn test_vector_bytes_roundtrip() {
+        let vector = vec![1.5f32, -2.0, f32::MAX];
+        let bytes = vector_to_bytes(&vector);
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(bytes_to_vector(&bytes).collect::<Vec<f32>>(), vector);
+    }
+
+    #[test]
+    fn test_knn_with_filter_and_merge() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let vector_field = schema_builder.add_bytes_field(
+            "vector",
+            BytesOptions::default().set_vector(VectorOptions::new(2, VectorSimilarity::Euclidean)),
+        );
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..100u32 {
+            let tag = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(
+                vector_field => vector_to_bytes(&[i as f32, 0.0]),
+                tag_field => tag,
+            ))?;
+            if i % 30 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        // A document without any vector.
+        index_writer.add_document(doc!(tag_field => "even"))?;
+        index_writer.commit()?;
+
+        let knn_vals = |index: &Index, filter: Option<&str>| -> crate::Result<Vec<f32>> {
+            let searcher = index.reader()?.searcher();
+            let filter_query = filter.map(|tag| {
+                TermQuery::new(
+                    Term::from_field_text(tag_field, tag),
+                    IndexRecordOption::Basic,
+                )
+            });
+            let top_docs = searcher.knn(
+                vector_field,
+                &[41.2, 1.0],
+                3,
+                filter_query.as_ref().map(|query| query as _),
+            )?;
+            top_docs
+                .into_iter()
+                .map(|(_, doc_address): (_, DocAddress)| {
+                    let reader = searcher.segment_reader(doc_address.segment_ord);
+                    let vector_reader = reader.vectors_readers().get_field(vector_field)?.unwrap();
+                    let mut vector = Vec::new();
+                    assert!(vector_reader.read_doc_vector(doc_address.doc_id, &mut vector));
+                    Ok(vector[0])
+                })
+                .collect()
+        };
+        assert_eq!(knn_vals(&index, None)?, vec![41.0, 42.0, 40.0]);
+        assert_eq!(knn_vals(&index, Some("odd"))?, vec![41.0, 43.0, 39.0]);
+
+        index_writer.delete_term(Term::from_field_text(tag_field, "odd"));
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        assert_eq!(knn_vals(&index, None)?, vec![42.0, 40.0, 44.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_knn_invalid_vectors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let vector_field = schema_builder.add_bytes_field(
+            "vector",
+            BytesOptions::default().set_vector(VectorOptions::new(2, VectorSimilarity::Cosine)),
+        );
+        let bytes_field = schema_builder.add_bytes_field("bytes", BytesOptions::default());
+        let schema = schema_builder.build();
+        let mut vectors_writer = VectorsWriter::for_schema(&schema);
+        let doc = doc!(vector_field => vector_to_bytes(&[1.0]));
+        assert!(vectors_writer.add_document(0, &doc).is_err());
+        let doc = doc!(
+            vector_field => vector_to_bytes(&[1.0, 2.0]),
+            vector_field => vector_to_bytes(&[1.0, 2.0]),
+        );
+        assert!(vectors_writer.add_document(1, &doc).is_err());
+
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(bytes_field => vec![1u8]))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.knn(vector_field, &[1.0], 1, None).is_err());
+        assert!(searcher.knn(bytes_field, &[1.0, 2.0], 1, None).is_err());
+        assert!(searcher.knn(vector_field, &[1.0, 2.0], 1, None)?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use std::io;
+use std::sync::Arc;
+
+use common::{BinarySerializable, VInt};
+
+use super::hnsw::HnswGraph;
+use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::schema::{Field, VectorSimilarity};
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::{DocId, Score};
+
+/// Reader for the dense vectors of all vector fields of a segment.
+#[derive(Clone)]
+pub struct VectorsReaders {
+    data: Arc<CompositeFile>,
+}
+
+impl VectorsReaders {
+    /// Creates a vectors reader.
+    pub fn open(file: FileSlice) -> crate::Result<VectorsReaders> {
+        let data = CompositeFile::open(&file)?;
+        Ok(VectorsReaders {
+            data: Arc::new(data),
+        })
+    }
+
+    /// Creates a vectors reader without any field.
+    ///
+    /// This is used for segments written before vector fields existed.
+    pub fn empty() -> VectorsReaders {
+        VectorsReaders {
+            data: Arc::new(CompositeFile::empty()),
+        }
+    }
+
+    /// Returns the `VectorReader` for a specific field.
+    ///
+    /// Returns `None` if the field is not a vector field.
+    pub fn get_field(&self, field: Field) -> crate::Result<Option<VectorReader>> {
+        if let Some(file) = self.data.open_read(field) {
+            let mut vector_reader = VectorReader::open(file.read_bytes()?)?;
+            if let Some(graph_file) = self.data.open_read_with_idx(field, 1) {
+                vector_reader.graph = Some(HnswGraph::open(graph_file.read_bytes()?)?);
+            }
+            Ok(Some(vector_reader))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+/// Gives random access to the dense vectors of a given field.
+///
+/// Vectors are identified by an ordinal, following the order of the documents
+/// they belong to.
+#[derive(Clone)]
+pub struct VectorReader {
+    num_vectors: u32,
+    dims: u32,
+    docs: OwnedBytes,
+    values: OwnedBytes,
+    graph: Option<HnswGraph>,
+}
+
+impl VectorReader {
+    /// Opens the vectors of a field.
+    pub fn open(mut bytes: OwnedBytes) -> io::Result<VectorReader> {
+        let num_vectors = VInt::deserialize(&mut bytes)?.val() as u32;
+        let dims = VInt::deserialize(&mut bytes)?.val() as u32;
+        let docs_num_bytes = num_vectors as usize * std::mem::size_of::<DocId>();
+        let values_num_bytes = num_vectors as usize * dims as usize * std::mem::size_of::<f32>();
+        if bytes.len() != docs_num_bytes + values_num_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Vectors length does not match the number of vectors.",
+            ));
+        }
+        let (docs, values) = bytes.split(docs_num_bytes);
+        Ok(VectorReader {
+            num_vectors,
+            dims,
+            docs,
+            values,
+            graph: None,
+        })
+    }
+
+    /// Returns the number of vectors.
+    pub fn num_vectors(&self) -> u32 {
+        self.num_vectors
+    }
+
+    /// Returns the number of dimensions of the vectors.
+    pub fn dims(&self) -> u32 {
+        self.dims
+    }
+
+    /// Returns the document of the vector with the given ordinal.
+    pub fn doc(&self, vector_ord: u32) -> DocId {
+        let start = vector_ord as usize * std::mem::size_of::<DocId>();
+        let doc_bytes = &self.docs.as_slice()[start..start + std::mem::size_of::<DocId>()];
+        DocId::from_le_bytes(doc_bytes.try_into().unwrap())
+    }
+
+    /// Returns the ordinal of the vector of `doc`, if the document has a vector.
+    pub fn vector_ord(&self, doc: DocId) -> Option<u32> {
+        let mut lo = 0u32;
+        let mut hi = self.num_vectors;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let mid_doc = self.doc(mid);
+            if mid_doc < doc {
+                lo = mid + 1;
+            } else if mid_doc > doc {
+                hi = mid;
+            } else {
+                return Some(mid);
+            }
+        }
+        None
+    }
+
+    /// Decodes the vector with the given ordinal into `output`.
+    pub fn read_vector(&self, vector_ord: u32, output: &mut Vec<f32>) {
+        let num_bytes = self.dims as usize * std::mem::size_of::<f32>();
+        let start = vector_ord as usize * num_bytes;
+        output.clear();
+        output.extend(super::bytes_to_vector(
+            &self.values.as_slice()[start..start + num_bytes],
+        ));
+    }
+
+    /// Decodes the vector of `doc` into `output`.
+    ///
+    /// Returns false if the document has no vector.
+    pub fn read_doc_vector(&self, doc: DocId, output: &mut Vec<f32>) -> bool {
+        if let Some(vector_ord) = self.vector_ord(doc) {
+            self.read_vector(vector_ord, output);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if the vectors have an HNSW graph, see
+    /// [`HnswOptions`](crate::schema::HnswOptions).
+    pub fn has_hnsw_graph(&self) -> bool {
+        self.graph.is_some()
+    }
+
+    /// Searches the HNSW graph for the `ef` vectors the most similar to `query_vector`,
+    /// among the documents accepted by `accept`.
+    ///
+    /// Returns `None` if the vectors have no HNSW graph. Otherwise, returns the
+    /// `(score, doc)` pairs sorted by decreasing score. The search is approximate, and the
+    /// larger `ef` the better the recall.
+    pub fn search_hnsw(
+        &self,
+        query_vector: &[f32],
+        similarity: VectorSimilarity,
+        ef: usize,
+        mut accept: impl FnMut(DocId) -> bool,
+    ) -> Option<Vec<(Score, DocId)>> {
+        let graph = self.graph.as_ref()?;
+        let mut vector_buffer = Vec::with_capacity(self.dims as usize);
+        let candidates = graph.search(
+            self.num_vectors,
+            ef,
+            |vector_ord| {
+                self.read_vector(vector_ord, &mut vector_buffer);
+                similarity.score(query_vector, &vector_buffer)
+            },
+            |vector_ord| accept(self.doc(vector_ord)),
+        );
+        Some(
+            candidates
+                .into_iter()
+                .map(|candidate| (candidate.score, self.doc(candidate.ord)))
+                .collect(),
+        )
+    }
+}
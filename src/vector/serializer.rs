@@ -0,0 +1,70 @@
+use std::io;
+use std::io::Write;
+
+use common::{BinarySerializable, VInt};
+
+use super::hnsw::HnswBuilder;
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::{Field, VectorOptions};
+use crate::DocId;
+
+/// The vectors serializer is in charge of
+/// the serialization of the dense vectors of all fields.
+pub struct VectorsSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl VectorsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<VectorsSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(VectorsSerializer { composite_write })
+    }
+
+    /// Serialize the vectors of the given field.
+    ///
+    /// `docs` is sorted, and the vector of `docs[i]` is
+    /// `values[i * dims..(i + 1) * dims]`.
+    ///
+    /// If the field has HNSW options, the graph of the vectors is built and serialized
+    /// as well.
+    pub fn serialize_field(
+        &mut self,
+        field: Field,
+        vector_options: VectorOptions,
+        docs: &[DocId],
+        values: &[f32],
+    ) -> io::Result<()> {
+        let dims = vector_options.dims();
+        assert_eq!(docs.len() * dims as usize, values.len());
+        let write = self.composite_write.for_field(field);
+        VInt(docs.len() as u64).serialize(write)?;
+        VInt(dims as u64).serialize(write)?;
+        for doc in docs {
+            write.write_all(&doc.to_le_bytes())?;
+        }
+        for value in values {
+            write.write_all(&value.to_le_bytes())?;
+        }
+        write.flush()?;
+        if let Some(hnsw_options) = vector_options.hnsw_options() {
+            let graph = HnswBuilder::build(
+                values,
+                docs.len() as u32,
+                dims as usize,
+                vector_options.similarity(),
+                hnsw_options,
+            );
+            let write = self.composite_write.for_field_with_idx(field, 1);
+            graph.serialize(write)?;
+            write.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
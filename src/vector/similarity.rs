@@ -0,0 +1,73 @@
+use crate::schema::VectorSimilarity;
+use crate::Score;
+
+/// Number of independent accumulators.
+///
+/// Accumulating over independent lanes lets the compiler vectorize
+/// the loops below using the SIMD instructions of the target.
+const LANES: usize = 8;
+
+/// Returns the dot product of two vectors of the same length.
+pub fn dot_product(left: &[f32], right: &[f32]) -> f32 {
+    assert_eq!(left.len(), right.len());
+    let mut acc = [0f32; LANES];
+    let mut left_chunks = left.chunks_exact(LANES);
+    let mut right_chunks = right.chunks_exact(LANES);
+    for (left_chunk, right_chunk) in (&mut left_chunks).zip(&mut right_chunks) {
+        for lane in 0..LANES {
+            acc[lane] += left_chunk[lane] * right_chunk[lane];
+        }
+    }
+    let remainder: f32 = left_chunks
+        .remainder()
+        .iter()
+        .zip(right_chunks.remainder())
+        .map(|(left_val, right_val)| left_val * right_val)
+        .sum();
+    acc.iter().sum::<f32>() + remainder
+}
+
+/// Returns the squared euclidean distance between two vectors of the same length.
+pub fn squared_euclidean_distance(left: &[f32], right: &[f32]) -> f32 {
+    assert_eq!(left.len(), right.len());
+    let mut acc = [0f32; LANES];
+    let mut left_chunks = left.chunks_exact(LANES);
+    let mut right_chunks = right.chunks_exact(LANES);
+    for (left_chunk, right_chunk) in (&mut left_chunks).zip(&mut right_chunks) {
+        for lane in 0..LANES {
+            let diff = left_chunk[lane] - right_chunk[lane];
+            acc[lane] += diff * diff;
+        }
+    }
+    let remainder: f32 = left_chunks
+        .remainder()
+        .iter()
+        .zip(right_chunks.remainder())
+        .map(|(left_val, right_val)| (left_val - right_val) * (left_val - right_val))
+        .sum();
+    acc.iter().sum::<f32>() + remainder
+}
+
+/// Returns the cosine of the angle between two vectors of the same length.
+///
+/// Returns 0 if one of the vectors is null.
+pub fn cosine_similarity(left: &[f32], right: &[f32]) -> f32 {
+    let norms = (dot_product(left, left) * dot_product(right, right)).sqrt();
+    if norms == 0.0 {
+        return 0.0;
+    }
+    dot_product(left, right) / norms
+}
+
+impl VectorSimilarity {
+    /// Returns the similarity score of two vectors of the same length.
+    ///
+    /// The more similar the vectors, the higher the score.
+    pub fn score(&self, left: &[f32], right: &[f32]) -> Score {
+        match self {
+            VectorSimilarity::DotProduct => dot_product(left, right),
+            VectorSimilarity::Cosine => cosine_similarity(left, right),
+            VectorSimilarity::Euclidean => 1.0 / (1.0 + squared_euclidean_distance(left, right)),
+        }
+    }
+}
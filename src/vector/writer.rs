@@ -0,0 +1,112 @@
+use std::{io, iter};
+
+use super::{bytes_to_vector, VectorsSerializer};
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, FieldType, Schema, VectorOptions};
+use crate::{DocId, TantivyError};
+
+struct FieldVectors {
+    vector_options: VectorOptions,
+    docs: Vec<DocId>,
+    values: Vec<f32>,
+}
+
+/// The `VectorsWriter` is in charge of recording the dense vectors
+/// of each vector field.
+pub struct VectorsWriter {
+    // For each field, `None` if the field is not a vector field.
+    vectors_per_field: Vec<Option<FieldVectors>>,
+}
+
+impl VectorsWriter {
+    /// Returns the vector fields of the given schema, with their options.
+    pub(crate) fn vector_fields(schema: &Schema) -> Vec<(Field, VectorOptions)> {
+        schema
+            .fields()
+            .filter_map(|(field, field_entry)| match field_entry.field_type() {
+                FieldType::Bytes(bytes_options) => bytes_options
+                    .get_vector_options()
+                    .map(|vector_options| (field, vector_options)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Initialize with state for tracking the vector fields
+    /// specified in the schema.
+    pub fn for_schema(schema: &Schema) -> VectorsWriter {
+        let mut vectors_per_field: Vec<Option<FieldVectors>> = iter::repeat_with(|| None)
+            .take(schema.num_fields())
+            .collect();
+        for (field, vector_options) in VectorsWriter::vector_fields(schema) {
+            vectors_per_field[field.field_id() as usize] = Some(FieldVectors {
+                vector_options,
+                docs: Vec::new(),
+                values: Vec::new(),
+            });
+        }
+        VectorsWriter { vectors_per_field }
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.vectors_per_field
+            .iter()
+            .flatten()
+            .map(|field_vectors| {
+                field_vectors.docs.capacity() * std::mem::size_of::<DocId>()
+                    + field_vectors.values.capacity() * std::mem::size_of::<f32>()
+            })
+            .sum()
+    }
+
+    /// Records the vectors of a document.
+    ///
+    /// Returns an error if a vector does not have the number of dimensions of its field,
+    /// or if the document has several vectors for the same field.
+    pub fn add_document<D: Document>(&mut self, doc: DocId, document: &D) -> crate::Result<()> {
+        for (field, value) in document.iter_fields_and_values() {
+            let Some(field_vectors) = self
+                .vectors_per_field
+                .get_mut(field.field_id() as usize)
+                .and_then(Option::as_mut)
+            else {
+                continue;
+            };
+            let Some(bytes) = value.as_value().as_bytes() else {
+                continue;
+            };
+            let dims = field_vectors.vector_options.dims() as usize;
+            if bytes.len() != dims * std::mem::size_of::<f32>() {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Expected a vector of {dims} dimensions, got {} bytes",
+                    bytes.len()
+                )));
+            }
+            if field_vectors.docs.last() == Some(&doc) {
+                return Err(TantivyError::InvalidArgument(
+                    "A document can only have one vector per field".to_string(),
+                ));
+            }
+            field_vectors.docs.push(doc);
+            field_vectors.values.extend(bytes_to_vector(bytes));
+        }
+        Ok(())
+    }
+
+    /// Serialize the recorded vectors of all fields.
+    pub fn serialize(&self, mut vectors_serializer: VectorsSerializer) -> io::Result<()> {
+        for (field_id, field_vectors_opt) in self.vectors_per_field.iter().enumerate() {
+            if let Some(field_vectors) = field_vectors_opt {
+                vectors_serializer.serialize_field(
+                    Field::from_field_id(field_id as u32),
+                    field_vectors.vector_options,
+                    &field_vectors.docs,
+                    &field_vectors.values,
+                )?;
+            }
+        }
+        vectors_serializer.close()?;
+        Ok(())
+    }
+}